@@ -0,0 +1,49 @@
+//! Version and build information for the About overlay.
+//!
+//! Support questions usually start with "what exactly are you running?". The
+//! overlay opened with `i` answers it in one place: the version, how the
+//! binary was built, which optional features and data sources it includes,
+//! the configuration file in use, and what changed recently.
+
+/// The crate version.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Data sources the monitor can show tasks from.
+pub const SOURCES: [&str; 17] = ["engine", "websocket", "sse", "tes", "docker", "k8s", "local", "slurm", "lsf", "aws-batch", "stdin", "fifo", "socket", "file", "demo", "simulation", "replay"];
+
+/// Notable changes in this version, newest first.
+pub const CHANGELOG: &[&str] = &[
+    "Tooltips with live stats for the task row under the mouse",
+    "Top mode ranking tasks by CPU or memory (t)",
+    "Task list grouping by workflow, with collapsible groups (g, z)",
+    "Optional smoothing of noisy progress reports",
+    "key=value summaries of JSON log lines (e to expand)",
+    "Configurable highlighting of log lines",
+    "Up to four tiled log panes in the Logs tab",
+    "Saving a task's full log to a file (s)",
+];
+
+/// Returns the optional cargo features the binary was built with.
+pub fn features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "graphics") {
+        features.push("graphics");
+    }
+    if cfg!(feature = "memory-stats") {
+        features.push("memory-stats");
+    }
+    if cfg!(feature = "mock-engine") {
+        features.push("mock-engine");
+    }
+    if cfg!(feature = "telemetry") {
+        features.push("telemetry");
+    }
+    features
+}
+
+/// Returns the build profile, target platform, and architecture, such as
+/// `release, linux x86_64`.
+pub fn build() -> String {
+    let profile = if cfg!(debug_assertions) { "debug" } else { "release" };
+    format!("{}, {} {}", profile, std::env::consts::OS, std::env::consts::ARCH)
+}
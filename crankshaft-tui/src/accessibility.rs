@@ -0,0 +1,68 @@
+//! Support for screen readers.
+//!
+//! Besides the linear rendering done in the UI, a plain-text status summary
+//! can be appended to a side-channel file whenever it changes, so that it can
+//! be followed with `tail -f` or a screen reader's file monitoring.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::app::App;
+use crate::bus::{StateEvent, Subscriber};
+
+/// Returns a one-sentence summary of task counts, e.g.
+/// `19 tasks: 5 pending, 5 running, 5 completed, 4 failed.`
+pub fn status_summary(app: &App) -> String {
+    let counts = app.status_counts();
+    format!(
+        "{} tasks: {} pending, {} running, {} completed, {} failed.",
+        counts.total(),
+        counts.pending,
+        counts.running,
+        counts.completed,
+        counts.failed
+    )
+}
+
+/// Appends the status summary to a file whenever it changes.
+pub struct SummaryWriter {
+    /// Destination file
+    file: File,
+    /// The last summary written
+    last: Option<String>,
+}
+
+impl SummaryWriter {
+    /// Opens `path` for appending, creating it if needed.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            file: OpenOptions::new().create(true).append(true).open(path)?,
+            last: None,
+        })
+    }
+
+    /// Writes the summary if it differs from the last one written.
+    pub fn write(&mut self, summary: String) -> io::Result<()> {
+        if self.last.as_ref() == Some(&summary) {
+            return Ok(());
+        }
+        writeln!(self.file, "{}", summary)?;
+        self.file.flush()?;
+        self.last = Some(summary);
+        Ok(())
+    }
+}
+
+impl Subscriber for SummaryWriter {
+    fn name(&self) -> &str {
+        "Summary file"
+    }
+
+    fn notify(&mut self, event: &StateEvent, app: &App) -> eyre::Result<()> {
+        if *event == StateEvent::Updated {
+            self.write(status_summary(app))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+//! Control actions that change a task on its backend.
+//!
+//! Unlike the local actions in [`crate::undo`], these reach the data source
+//! and cannot be undone from here. In dry-run mode (`d`, or `--dry-run` on
+//! the command line) they are recorded in the audit log and reported as
+//! "would have …" instead of being sent, so the monitor can be demonstrated,
+//! or new operators trained, against a real backend without touching it.
+
+use std::fmt;
+
+/// An action that changes a task on its backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskAction {
+    /// Stop a pending or running task
+    Cancel {
+        /// ID of the task
+        id: String,
+    },
+    /// Queue a failed or finished task to run again
+    Retry {
+        /// ID of the task
+        id: String,
+    },
+    /// Move a pending task forward or back in its queue
+    Prioritize {
+        /// ID of the task
+        id: String,
+        /// `true` to move it forward, `false` to move it back
+        raise: bool,
+    },
+}
+
+impl TaskAction {
+    /// Returns the ID of the task the action applies to.
+    pub fn id(&self) -> &str {
+        match self {
+            TaskAction::Cancel { id } | TaskAction::Retry { id } | TaskAction::Prioritize { id, .. } => id,
+        }
+    }
+
+    /// Describes what the action would have done, for dry runs, such as
+    /// `would have cancelled task-1`.
+    pub fn would_have(&self) -> String {
+        match self {
+            TaskAction::Cancel { id } => format!("would have cancelled {}", id),
+            TaskAction::Retry { id } => format!("would have retried {}", id),
+            TaskAction::Prioritize { id, raise: true } => format!("would have raised the priority of {}", id),
+            TaskAction::Prioritize { id, raise: false } => format!("would have lowered the priority of {}", id),
+        }
+    }
+}
+
+impl fmt::Display for TaskAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskAction::Cancel { id } => write!(f, "cancel {}", id),
+            TaskAction::Retry { id } => write!(f, "retry {}", id),
+            TaskAction::Prioritize { id, raise: true } => write!(f, "raise priority of {}", id),
+            TaskAction::Prioritize { id, raise: false } => write!(f, "lower priority of {}", id),
+        }
+    }
+}
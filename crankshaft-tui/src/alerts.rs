@@ -0,0 +1,340 @@
+//! Alert rules evaluated against each running task's recent metrics.
+//!
+//! Metrics are sampled at a fixed interval and kept for as long as the
+//! longest rule window, which may be up to two hours. A rule either watches
+//! a value staying above a threshold, or its rate of change: memory climbing
+//! steadily for ten minutes is usually a leak, and warning about it beats
+//! finding out from the OOM kill.
+//!
+//! ```toml
+//! [[alerts.rules]]
+//! name = "likely memory leak"
+//! metric = "memory"
+//! condition = "rate"
+//! threshold = 104857600   # bytes per minute
+//! for_secs = 600
+//! severity = "warning"
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::app::{Task, TaskStatus};
+use crate::format::NumberFormat;
+use crate::record::unix_now;
+
+/// Time between metric samples.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Longest a rule's condition may be required to hold.
+pub const MAX_WINDOW: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// Most samples kept per task, whatever the rule windows: enough to span
+/// the longest window with a sample at each end, and one older.
+const MAX_SAMPLES: usize = (MAX_WINDOW.as_secs() / SAMPLE_INTERVAL.as_secs()) as usize + 2;
+
+/// The value a rule watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Metric {
+    /// Fraction of a CPU in use
+    Cpu,
+    /// Memory in use, in bytes
+    Memory,
+}
+
+impl Metric {
+    /// Returns the metric's name as used in configuration.
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Cpu => "cpu",
+            Metric::Memory => "memory",
+        }
+    }
+
+    /// Returns the task's current value of the metric.
+    fn value(self, task: &Task) -> f64 {
+        match self {
+            Metric::Cpu => task.cpu_usage,
+            Metric::Memory => task.memory_usage.used as f64,
+        }
+    }
+}
+
+/// What a rule checks over its window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Condition {
+    /// Every sample is above the threshold
+    Above,
+    /// The value rises faster than the threshold per minute, as the slope
+    /// of a line fitted through the samples
+    Rate,
+}
+
+/// How urgent an alert is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Worth a look
+    #[default]
+    Warning,
+    /// Likely to fail soon
+    Critical,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// A condition on one metric that raises an alert when it holds for a
+/// whole window.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRule {
+    /// Shown with the alert
+    pub name: String,
+    /// The watched value
+    pub metric: Metric,
+    /// What is checked
+    pub condition: Condition,
+    /// The limit, in the metric's unit (per minute for `rate`)
+    pub threshold: f64,
+    /// How long the condition must hold, in seconds, up to
+    /// [`MAX_WINDOW`]
+    #[serde(deserialize_with = "window_secs")]
+    pub for_secs: u64,
+    /// How urgent the alert is
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+impl AlertRule {
+    /// Returns how long the condition must hold.
+    fn window(&self) -> Duration {
+        Duration::from_secs(self.for_secs)
+    }
+
+    /// Returns the observed value (the rate, for rate rules) if the rule
+    /// holds over the samples.
+    fn evaluate(&self, samples: &VecDeque<Sample>, now: Instant) -> Option<f64> {
+        let oldest = samples.front()?;
+        if now.duration_since(oldest.at) < self.window() {
+            return None;
+        }
+        let window: Vec<(f64, f64)> = samples
+            .iter()
+            .filter(|sample| now.duration_since(sample.at) <= self.window())
+            .map(|sample| (now.duration_since(sample.at).as_secs_f64(), self.metric_of(sample)))
+            .collect();
+        if window.len() < 2 {
+            return None;
+        }
+
+        match self.condition {
+            Condition::Above => window
+                .iter()
+                .all(|(_, value)| *value > self.threshold)
+                .then(|| window.last().map_or(0.0, |(_, value)| *value)),
+            Condition::Rate => {
+                // Least-squares slope; ages count backwards, so negate it
+                let n = window.len() as f64;
+                let mean_age = window.iter().map(|(age, _)| age).sum::<f64>() / n;
+                let mean_value = window.iter().map(|(_, value)| value).sum::<f64>() / n;
+                let (covariance, variance) = window.iter().fold((0.0, 0.0), |(cov, var), (age, value)| {
+                    (cov + (age - mean_age) * (value - mean_value), var + (age - mean_age).powi(2))
+                });
+                if variance == 0.0 {
+                    return None;
+                }
+                let per_minute = -covariance / variance * 60.0;
+                (per_minute > self.threshold).then_some(per_minute)
+            }
+        }
+    }
+
+    /// Returns the watched metric's value in `sample`.
+    fn metric_of(&self, sample: &Sample) -> f64 {
+        match self.metric {
+            Metric::Cpu => sample.cpu,
+            Metric::Memory => sample.memory,
+        }
+    }
+}
+
+/// Reads a rule's `for_secs`, refusing windows longer than the samples
+/// kept, since the rule could never hold.
+fn window_secs<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let secs = u64::deserialize(deserializer)?;
+    if secs > MAX_WINDOW.as_secs() {
+        return Err(serde::de::Error::custom(format!(
+            "`for_secs` is {}, but metrics are only kept for {} seconds",
+            secs,
+            MAX_WINDOW.as_secs()
+        )));
+    }
+    Ok(secs)
+}
+
+/// The configured alert rules.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertRules {
+    /// Rules evaluated for every running task
+    pub rules: Vec<AlertRule>,
+}
+
+impl Default for AlertRules {
+    fn default() -> Self {
+        Self {
+            rules: vec![AlertRule {
+                name: "likely memory leak".to_string(),
+                metric: Metric::Memory,
+                condition: Condition::Rate,
+                threshold: (100 << 20) as f64,
+                for_secs: 600,
+                severity: Severity::Warning,
+            }],
+        }
+    }
+}
+
+/// A rule that currently holds for a task.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    /// The task the rule holds for
+    pub task_id: String,
+    /// The rule
+    pub rule: AlertRule,
+    /// The observed value, or rate per minute for rate rules
+    pub observed: f64,
+    /// When the alert was raised, in seconds since the Unix epoch
+    pub since: u64,
+}
+
+impl Alert {
+    /// Describes what was observed, e.g. `memory climbing 150 MiB/min over
+    /// 10m`.
+    pub fn describe(&self, numbers: &NumberFormat) -> String {
+        let value = |value: f64| match self.rule.metric {
+            Metric::Cpu => numbers.percent(value),
+            Metric::Memory => numbers.bytes(value.max(0.0) as u64),
+        };
+        let window = numbers.duration(self.rule.window());
+        match self.rule.condition {
+            Condition::Above => format!(
+                "{} above {} for {} (now {})",
+                self.rule.metric.name(),
+                value(self.rule.threshold),
+                window,
+                value(self.observed)
+            ),
+            Condition::Rate => format!("{} climbing {}/min over {}", self.rule.metric.name(), value(self.observed), window),
+        }
+    }
+}
+
+/// One sample of a task's metrics.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    /// When the sample was taken
+    at: Instant,
+    /// Fraction of a CPU in use
+    cpu: f64,
+    /// Memory in use, in bytes
+    memory: f64,
+}
+
+/// Metric history and active alerts for every running task.
+#[derive(Debug, Clone, Default)]
+pub struct Alerts {
+    /// Rules being evaluated
+    rules: Vec<AlertRule>,
+    /// Samples per task ID, oldest first
+    history: HashMap<String, VecDeque<Sample>>,
+    /// Alerts whose rule still holds
+    active: Vec<Alert>,
+    /// When the last sample was taken
+    last: Option<Instant>,
+}
+
+impl Alerts {
+    /// Replaces the rules, clearing alerts raised by the previous ones.
+    pub fn set_rules(&mut self, rules: &AlertRules) {
+        self.rules = rules.rules.clone();
+        self.active.clear();
+    }
+
+    /// Samples running tasks if the interval has elapsed by `now` and
+    /// re-evaluates every rule, returning the alerts raised by this call.
+    pub fn observe(&mut self, tasks: &HashMap<String, Task>, now: Instant) -> Vec<Alert> {
+        if self.rules.is_empty() {
+            return Vec::new();
+        }
+        if self.last.is_some_and(|last| now.duration_since(last) < SAMPLE_INTERVAL) {
+            return Vec::new();
+        }
+        self.last = Some(now);
+
+        let retention = self.rules.iter().map(AlertRule::window).max().unwrap_or_default() + SAMPLE_INTERVAL;
+        self.history
+            .retain(|id, _| tasks.get(id).is_some_and(|task| task.status == TaskStatus::Running));
+        for task in tasks.values().filter(|task| task.status == TaskStatus::Running) {
+            let samples = self.history.entry(task.id.clone()).or_default();
+            samples.push_back(Sample {
+                at: now,
+                cpu: Metric::Cpu.value(task),
+                memory: Metric::Memory.value(task),
+            });
+            while samples.len() > MAX_SAMPLES || samples.front().is_some_and(|s| now.duration_since(s.at) > retention) {
+                samples.pop_front();
+            }
+        }
+
+        let mut active = Vec::new();
+        let mut raised = Vec::new();
+        for (id, samples) in &self.history {
+            for rule in &self.rules {
+                let Some(observed) = rule.evaluate(samples, now) else {
+                    continue;
+                };
+                let previous = self.active.iter().find(|alert| alert.task_id == *id && alert.rule == *rule);
+                let alert = Alert {
+                    task_id: id.clone(),
+                    rule: rule.clone(),
+                    observed,
+                    since: previous.map_or_else(unix_now, |alert| alert.since),
+                };
+                if previous.is_none() {
+                    raised.push(alert.clone());
+                }
+                active.push(alert);
+            }
+        }
+        active.sort_by(|a, b| b.rule.severity.cmp(&a.rule.severity).then_with(|| a.task_id.cmp(&b.task_id)));
+        self.active = active;
+        raised
+    }
+
+    /// Returns every active alert, most severe first.
+    pub fn active(&self) -> &[Alert] {
+        &self.active
+    }
+
+    /// Returns the active alerts of one task, most severe first.
+    pub fn for_task<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a Alert> + 'a {
+        self.active.iter().filter(move |alert| alert.task_id == id)
+    }
+
+    /// Returns the most severe active alert level of a task, if any.
+    pub fn severity(&self, id: &str) -> Option<Severity> {
+        self.for_task(id).map(|alert| alert.rule.severity).max()
+    }
+}
@@ -0,0 +1,147 @@
+//! Detection of running tasks that take far longer than their step usually
+//! does.
+//!
+//! Among hundreds of shards of one step, a hung one looks like any other
+//! running task. Once enough shards of a step have completed, a running task
+//! whose elapsed time exceeds a multiple of their median is flagged:
+//!
+//! ```toml
+//! [anomalies]
+//! factor = 3.0
+//! min_completed = 5
+//! alert = true
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::app::{Task, TaskStatus};
+use crate::durations::by_step_including;
+use crate::timeline::step_name;
+
+/// Time between evaluations.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// When a running task counts as anomalously slow.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnomalyConfig {
+    /// Multiple of the step's median duration a task must exceed
+    pub factor: f64,
+    /// Completed tasks of a step needed before its median is trusted
+    pub min_completed: usize,
+    /// Report newly flagged tasks in the footer
+    pub alert: bool,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            factor: 3.0,
+            min_completed: 5,
+            alert: true,
+        }
+    }
+}
+
+/// A running task slower than its step's median allows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anomaly {
+    /// The slow task
+    pub task_id: String,
+    /// The step it belongs to
+    pub step: String,
+    /// Seconds the task has been running
+    pub elapsed: u64,
+    /// Median duration of the step's completed tasks, in seconds
+    pub median: u64,
+}
+
+impl Anomaly {
+    /// Returns the elapsed time as a multiple of the median.
+    pub fn ratio(&self) -> f64 {
+        self.elapsed as f64 / self.median.max(1) as f64
+    }
+}
+
+/// Running tasks currently flagged as slow.
+#[derive(Debug, Clone, Default)]
+pub struct Anomalies {
+    /// Detection thresholds
+    config: AnomalyConfig,
+    /// Flagged tasks by ID
+    active: HashMap<String, Anomaly>,
+    /// When the tasks were last evaluated
+    last: Option<Instant>,
+}
+
+impl Anomalies {
+    /// Replaces the detection thresholds.
+    pub fn set_config(&mut self, config: AnomalyConfig) {
+        self.config = config;
+        self.last = None;
+    }
+
+    /// Returns the detection thresholds.
+    pub fn config(&self) -> &AnomalyConfig {
+        &self.config
+    }
+
+    /// Re-evaluates running tasks if the check interval has elapsed,
+    /// returning the tasks flagged by this call. Medians also count the
+    /// `earlier` durations, by step, of tasks no longer in the store.
+    pub fn observe(&mut self, tasks: &HashMap<String, Task>, earlier: &BTreeMap<String, Vec<u64>>, now: u64) -> Vec<Anomaly> {
+        if self.last.is_some_and(|last| last.elapsed() < CHECK_INTERVAL) {
+            return Vec::new();
+        }
+        self.last = Some(Instant::now());
+
+        let medians: HashMap<String, u64> = by_step_including(tasks.values(), earlier)
+            .into_iter()
+            .filter(|step| step.count >= self.config.min_completed)
+            .map(|step| (step.step, step.p50))
+            .collect();
+
+        let mut active = HashMap::new();
+        let mut flagged = Vec::new();
+        for task in tasks.values().filter(|task| task.status == TaskStatus::Running) {
+            let step = step_name(&task.name);
+            let (Some(&median), Some(elapsed)) = (medians.get(step), task.duration(now)) else {
+                continue;
+            };
+            if elapsed as f64 <= self.config.factor * median.max(1) as f64 {
+                continue;
+            }
+            let anomaly = Anomaly {
+                task_id: task.id.clone(),
+                step: step.to_string(),
+                elapsed,
+                median,
+            };
+            if !self.active.contains_key(&task.id) {
+                flagged.push(anomaly.clone());
+            }
+            active.insert(task.id.clone(), anomaly);
+        }
+        flagged.sort_by(|a, b| a.task_id.cmp(&b.task_id));
+        self.active = active;
+        flagged
+    }
+
+    /// Returns the anomaly of a task, if it is flagged.
+    pub fn get(&self, id: &str) -> Option<&Anomaly> {
+        self.active.get(id)
+    }
+
+    /// Returns the number of flagged tasks.
+    pub fn len(&self) -> usize {
+        self.active.len()
+    }
+
+    /// Returns `true` if no task is flagged.
+    pub fn is_empty(&self) -> bool {
+        self.active.is_empty()
+    }
+}
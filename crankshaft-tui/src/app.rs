@@ -1,14 +1,107 @@
 //! Application state and logic for the TUI.
 
-use crossterm::event::{KeyCode, KeyEvent};
-use std::collections::HashMap;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+#[cfg(feature = "telemetry")]
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::accessibility::SummaryWriter;
+use crate::actions::TaskAction;
+use crate::alerts::Alerts;
+use crate::anomaly::Anomalies;
+use crate::audit::{AuditLog, Outcome};
+use crate::aws_batch::AwsBatchDataSource;
+use crate::bus::{EventBus, StateEvent, Subscriber};
+use crate::cache::{CachedSource, FetchConfig, FetchControl, FetchStats};
+use crate::capabilities::SourceCapabilities;
+use crate::capacity::Capacity;
+use crate::chart::{ChartSeries, ChartState, MetricHistory};
+use crate::config::{Config, TelemetryConfig};
+use crate::confirm::{Confirmable, ConfirmPolicy};
+use crate::control::ControlCommand;
+#[cfg(feature = "telemetry")]
+use crate::crash;
+#[cfg(unix)]
+use crate::control::ControlServer;
+use crate::diagnostics::{ConnectorHealth, Connections};
+#[cfg(unix)]
+use crate::docker::DockerDataSource;
+use crate::dot;
+use crate::durations::StepDurations;
+use crate::demo::MockDataSource;
+use crate::auth::RemoteAccess;
+use crate::engine::EngineConnection;
+use crate::external::{self, ExternalCommand};
+use crate::feed::{EventFeed, EventKind};
+use crate::file::FileDataSource;
+use crate::format::NumberFormat;
+use crate::glossary;
+#[cfg(feature = "graphics")]
+use crate::graphics::Graphics;
+use crate::groups::{Grouping, TaskGroup};
+use crate::highlight::Highlighter;
+use crate::history::{History, StoreSnapshot};
+use crate::k8s::{K8sDataSource, K8sTarget};
+use crate::local::LocalDataSource;
+use crate::logs::{DownloadState, LogBuffer, LogDownload, LogFetcher, LogLimits, LogProvider, LogRange, INITIAL_TAIL_LINES};
+use crate::lsf::LsfDataSource;
+use crate::memory::{self, MemoryLogger};
+use crate::merge::MergedDataSource;
+use crate::overlay::{Overlay, OverlayStack};
+use crate::panes::{LogPanes, MAX_PANES};
+use crate::perf::{Churn, PerfStats};
+use crate::progress::{self, ProgressInterpolator};
+use crate::record::{self, Compression, Recorder, Replayer};
+use crate::regions::Regions;
+use crate::retention::Retention;
+use crate::sim::{Simulator, SyntheticLogProvider};
+use crate::slo::Slo;
+use crate::slurm::SlurmDataSource;
+use crate::source::{DataSource, SourceEvent};
+use crate::sse::SseDataSource;
+use crate::stream::StreamDataSource;
+use crate::sort::{SortKey, SortOrder, TopMetric, MENU_LEVELS};
+use crate::sounds::Sounds;
+use crate::spark::Sparklines;
+use crate::state::LocalState;
+#[cfg(feature = "telemetry")]
+use crate::telemetry::Telemetry;
+use crate::tes::TesDataSource;
+use crate::theme::{StatusSymbols, Theme};
+use crate::timeline::LaneKey;
+use crate::undo::{LocalAction, UndoStack};
+use crate::updates::{Release, UpdateCheck};
+use crate::websocket::WebSocketDataSource;
+use crate::workflow::WorkflowMetadata;
+
+/// Number of recently viewed tasks whose logs stay cached in memory
+const RECENT_LOG_CACHE: usize = 8;
+
+/// Lines a log pane scrolls by per page key
+const LOG_SCROLL_LINES: usize = 10;
+
+/// How long a status message stays in the footer
+const STATUS_MESSAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a running task goes without updates before it is shown as stale
+pub const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(60);
 
 /// Task status enum
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Engines name their states differently; the aliases map the states they
+/// report onto these four when decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum TaskStatus {
+    #[serde(alias = "pending", alias = "created", alias = "queued", alias = "waiting")]
     Pending,
+    #[serde(alias = "running", alias = "started")]
     Running,
+    #[serde(alias = "completed", alias = "succeeded", alias = "success", alias = "done")]
     Completed,
+    #[serde(alias = "failed", alias = "error", alias = "cancelled", alias = "canceled", alias = "killed")]
     Failed,
 }
 
@@ -24,14 +117,433 @@ impl std::fmt::Display for TaskStatus {
 }
 
 /// Represents a task in the system
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
     pub id: String,
     pub name: String,
     pub status: TaskStatus,
+    #[serde(default)]
     pub progress: f64, // 0.0 to 1.0
+    #[serde(default)]
     pub cpu_usage: f64,
-    pub memory_usage: f64,
+    /// CPUs requested when the task was scheduled, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+    /// Memory in use, with the amount requested and the enforced limit
+    #[serde(default)]
+    pub memory_usage: MemoryUsage,
+    /// When the task started running, in seconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<u64>,
+    /// When the task completed or failed, in seconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<u64>,
+    /// IDs of the tasks that must finish before this one can start
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+    /// Place in the scheduler queue while pending, if the backend reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue: Option<QueuePosition>,
+    /// Attempt at running the task the backend is on, counting from 1, if
+    /// it reports when tasks are retried or requeued
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempt: Option<u32>,
+    /// Backend labels, such as the sample or backend a task belongs to
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    /// Command line the task runs, for backends that report it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command: Vec<String>,
+    /// Environment variables set for the task, for backends that report them
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// What each executor reported, for backends that run a task as a
+    /// sequence of containers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub executors: Vec<ExecutorLog>,
+    /// Live resource use of the task's container, for backends that sample
+    /// it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerStats>,
+    /// The pods that ran the task, oldest first, for backends that run tasks
+    /// as Kubernetes pods
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pods: Vec<PodInfo>,
+    /// Retained log output, bounded by the app's log limits
+    #[serde(skip)]
+    pub logs: LogBuffer,
+    /// The exact payload last received from the backend for this task
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<serde_json::Value>,
+    /// Progress last reported by the backend, when smoothing stored a
+    /// different value in `progress`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reported_progress: Option<f64>,
+    /// When the monitor last received an update for the task, in seconds
+    /// since the Unix epoch
+    #[serde(skip)]
+    pub last_updated: Option<u64>,
+}
+
+impl Task {
+    /// Returns `true` if the backend retried or requeued the task
+    pub fn is_requeued(&self) -> bool {
+        self.attempt.is_some_and(|attempt| attempt > 1)
+    }
+
+    /// Returns a copy of the task without its retained logs
+    pub fn without_logs(&self) -> Task {
+        Task {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            status: self.status,
+            progress: self.progress,
+            cpu_usage: self.cpu_usage,
+            cpus: self.cpus,
+            memory_usage: self.memory_usage,
+            started_at: self.started_at,
+            finished_at: self.finished_at,
+            dependencies: self.dependencies.clone(),
+            queue: self.queue,
+            attempt: self.attempt,
+            labels: self.labels.clone(),
+            command: self.command.clone(),
+            env: self.env.clone(),
+            executors: self.executors.clone(),
+            container: self.container,
+            pods: self.pods.clone(),
+            logs: LogBuffer::default(),
+            raw: self.raw.clone(),
+            reported_progress: self.reported_progress,
+            last_updated: self.last_updated,
+        }
+    }
+
+    /// Returns the pretty-printed backend payload, falling back to the
+    /// task's own serialization when the source provided no payload
+    pub fn raw_json(&self) -> String {
+        let value = match &self.raw {
+            Some(raw) => raw.clone(),
+            None => serde_json::to_value(self).unwrap_or_default(),
+        };
+        serde_json::to_string_pretty(&value).unwrap_or_default()
+    }
+
+    /// Returns how long the task has been running (or ran), in seconds,
+    /// given the current Unix time
+    pub fn duration(&self, now: u64) -> Option<u64> {
+        let started = self.started_at?;
+        Some(self.finished_at.unwrap_or(now).saturating_sub(started))
+    }
+
+    /// Returns memory use as a fraction of the task's limit (or request),
+    /// as previously stored in `memory_usage`
+    pub fn memory_ratio(&self) -> f64 {
+        self.memory_usage.ratio()
+    }
+}
+
+/// Where a pending task stands in its scheduler's queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuePosition {
+    /// Number of jobs ahead of it, plus one
+    pub position: u64,
+    /// When the scheduler expects it to start, in seconds since the Unix
+    /// epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_start: Option<u64>,
+}
+
+/// What one of a task's executors reported
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutorLog {
+    /// Container image the executor runs
+    pub image: String,
+    /// Command line it runs in the image
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub command: Vec<String>,
+    /// Environment variables set for it
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Exit code, once it has finished
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// The end of its standard output, as far as the backend keeps it
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub stdout: String,
+    /// The end of its standard error, as far as the backend keeps it
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub stderr: String,
+}
+
+/// One of the Kubernetes pods that ran a task
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PodInfo {
+    /// Name of the pod
+    pub name: String,
+    /// Its phase, such as `Running`
+    pub phase: String,
+    /// Node it was scheduled on, once it was
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// Times its containers were restarted
+    pub restarts: u32,
+    /// Why a container is waiting or stopped, such as `CrashLoopBackOff`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Resource use of a task's container, as last sampled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContainerStats {
+    /// CPUs in use, averaged since the previous sample
+    pub cpus: f64,
+    /// CPUs the container may use: its quota, or else the host's CPUs
+    pub cpu_limit: f64,
+    /// Bytes received over the network
+    pub network_rx: u64,
+    /// Bytes sent over the network
+    pub network_tx: u64,
+    /// Bytes read from block devices
+    pub block_read: u64,
+    /// Bytes written to block devices
+    pub block_write: u64,
+    /// Processes running in the container
+    pub pids: u64,
+}
+
+/// Nominal limit given to memory usage read in the legacy format.
+const LEGACY_MEMORY_LIMIT: u64 = 1 << 30;
+
+/// Memory consumption of a task in bytes
+///
+/// Recordings and snapshots made before memory was tracked in bytes hold
+/// the fraction of the task's limit in use as a bare number; it is read as
+/// that fraction of a nominal 1 GiB limit, so gauges and sorting are
+/// unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "StoredMemoryUsage")]
+pub struct MemoryUsage {
+    /// Bytes currently in use
+    pub used: u64,
+    /// Bytes requested when the task was scheduled, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requested: Option<u64>,
+    /// Bytes the task may use before being killed, if enforced
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+/// Memory usage as stored: in bytes, or as the legacy fraction.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredMemoryUsage {
+    /// Fraction of the limit in use
+    Fraction(f64),
+    /// The fields of [`MemoryUsage`]
+    Bytes {
+        /// Bytes currently in use
+        #[serde(default)]
+        used: u64,
+        /// Bytes requested when the task was scheduled, if known
+        #[serde(default)]
+        requested: Option<u64>,
+        /// Bytes the task may use before being killed, if enforced
+        #[serde(default)]
+        limit: Option<u64>,
+    },
+}
+
+impl From<StoredMemoryUsage> for MemoryUsage {
+    fn from(stored: StoredMemoryUsage) -> Self {
+        match stored {
+            StoredMemoryUsage::Fraction(fraction) => Self {
+                used: (fraction.max(0.0) * LEGACY_MEMORY_LIMIT as f64).round() as u64,
+                requested: None,
+                limit: Some(LEGACY_MEMORY_LIMIT),
+            },
+            StoredMemoryUsage::Bytes { used, requested, limit } => Self { used, requested, limit },
+        }
+    }
+}
+
+impl MemoryUsage {
+    /// Returns the amount usage is measured against: the limit if there is
+    /// one, otherwise the request
+    pub fn capacity(&self) -> Option<u64> {
+        self.limit.or(self.requested)
+    }
+
+    /// Returns usage as a fraction of [`capacity`](Self::capacity), or zero
+    /// when neither a limit nor a request is known
+    pub fn ratio(&self) -> f64 {
+        match self.capacity() {
+            Some(capacity) if capacity > 0 => self.used as f64 / capacity as f64,
+            _ => 0.0,
+        }
+    }
+}
+
+/// A single change to the task store.
+///
+/// [`App::apply_update`] is the only way embedders and connectors should
+/// mutate tasks, so every change is accounted for in the same place.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TaskUpdate {
+    /// A task appeared, or its full state was re-sent. Existing tasks are
+    /// replaced but keep their retained logs.
+    Created(Box<Task>),
+    /// A task moved to a new status
+    StatusChanged {
+        /// ID of the task
+        id: String,
+        /// Its new status
+        status: TaskStatus,
+    },
+    /// A task reported progress
+    Progress {
+        /// ID of the task
+        id: String,
+        /// Fraction complete, from 0.0 to 1.0
+        progress: f64,
+    },
+    /// A task reported resource usage; absent values are left unchanged
+    Metrics {
+        /// ID of the task
+        id: String,
+        /// Fraction of a CPU in use
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cpu_usage: Option<f64>,
+        /// Memory in use
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        memory_usage: Option<MemoryUsage>,
+    },
+    /// A pending task moved in its scheduler's queue
+    Queue {
+        /// ID of the task
+        id: String,
+        /// Its new place, or `None` if the backend no longer reports one
+        queue: Option<QueuePosition>,
+    },
+    /// A task wrote a line of output
+    LogLine {
+        /// ID of the task
+        id: String,
+        /// The line, without its trailing newline
+        line: String,
+    },
+    /// A task left the store
+    Removed {
+        /// ID of the task
+        id: String,
+    },
+}
+
+impl TaskUpdate {
+    /// Returns the ID of the task this update applies to
+    pub fn task_id(&self) -> &str {
+        match self {
+            TaskUpdate::Created(task) => &task.id,
+            TaskUpdate::StatusChanged { id, .. }
+            | TaskUpdate::Progress { id, .. }
+            | TaskUpdate::Metrics { id, .. }
+            | TaskUpdate::Queue { id, .. }
+            | TaskUpdate::LogLine { id, .. }
+            | TaskUpdate::Removed { id } => id,
+        }
+    }
+}
+
+/// Live state set aside while an older copy of the store is shown
+struct Scrub {
+    /// Index of the shown copy in the history
+    index: usize,
+    /// The live task store
+    tasks: HashMap<String, Task>,
+    /// The live task order
+    task_ids: Vec<String>,
+    /// Updates received while history was shown, applied on return to live
+    held: Vec<TaskUpdate>,
+}
+
+/// Number of tasks in each status
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatusCounts {
+    /// Tasks waiting to run
+    pub pending: usize,
+    /// Tasks currently running
+    pub running: usize,
+    /// Tasks that finished successfully
+    pub completed: usize,
+    /// Tasks that failed
+    pub failed: usize,
+}
+
+impl StatusCounts {
+    /// Returns the total number of tasks counted
+    pub fn total(&self) -> usize {
+        self.pending + self.running + self.completed + self.failed
+    }
+}
+
+/// Top-level tabs of the interface, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    /// Task list and details
+    Tasks,
+    /// Log output of the selected task
+    Logs,
+    /// Aggregate statistics
+    Statistics,
+    /// Bars of when each task ran
+    Timeline,
+    /// Notable events of the run, newest first
+    Events,
+    /// Keyboard shortcuts and about text
+    Help,
+}
+
+impl Tab {
+    /// All tabs, in display order
+    pub const ALL: [Tab; 6] = [Tab::Tasks, Tab::Logs, Tab::Statistics, Tab::Timeline, Tab::Events, Tab::Help];
+
+    /// Returns the tab at the given index, if any
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// Returns the position of the tab in the tab bar
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the title shown in the tab bar
+    pub fn title(self) -> &'static str {
+        match self {
+            Tab::Tasks => "Tasks",
+            Tab::Logs => "Logs",
+            Tab::Statistics => "Statistics",
+            Tab::Timeline => "Timeline",
+            Tab::Events => "Events",
+            Tab::Help => "Help",
+        }
+    }
+}
+
+impl std::str::FromStr for Tab {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Tab::ALL
+            .iter()
+            .copied()
+            .find(|tab| tab.title().eq_ignore_ascii_case(s) || (s.eq_ignore_ascii_case("stats") && *tab == Tab::Statistics))
+            .ok_or_else(|| format!("unknown tab `{}` (expected tasks, logs, statistics, timeline, events, or help)", s))
+    }
 }
 
 /// Main application state
@@ -41,133 +553,2438 @@ pub struct App {
     pub task_ids: Vec<String>,
     pub should_quit: bool,
     pub tab_index: usize,
+    /// Overlays drawn over the dashboard, in the order they were opened
+    pub overlays: OverlayStack,
+    /// Performance counters for the debug overlay
+    pub perf: PerfStats,
+    /// Retention limits applied to every task's log buffer
+    pub log_limits: LogLimits,
+    /// How fetches from the log provider and data source are cached and
+    /// limited
+    pub fetch_config: FetchConfig,
+    /// Smooths running tasks' progress between backend updates
+    pub progress: ProgressInterpolator,
+    /// Share of each forward progress report applied, if noisy reports are
+    /// smoothed in the store
+    pub smooth_progress: Option<f64>,
+    /// Colors and symbols used to render task status
+    pub theme: Theme,
+    /// Locale separators used for numbers, sizes, and durations
+    pub numbers: NumberFormat,
+    /// Render without decorative borders and emoji, with linear text regions
+    pub screen_reader: bool,
+    /// Replace spinners and animation with static indicators
+    pub reduced_motion: bool,
+    /// Number of ticks processed, used to drive animations
+    pub tick_count: u64,
+    /// Subscribers to state changes, such as recorders and summary writers
+    bus: EventBus,
+    /// Where tasks come from
+    source: Box<dyn DataSource>,
+    /// How often the source is polled, and the counts of its polls
+    source_fetch: FetchControl,
+    /// Background log fetcher for the viewed task
+    log_fetcher: Option<LogFetcher>,
+    /// Recently viewed tasks whose logs are kept, most recent first
+    recent_logs: VecDeque<String>,
+    /// Styles applied to matching parts of log lines
+    pub highlighter: Highlighter,
+    /// Whether JSON log lines are shown in full rather than summarised
+    pub expand_structured_logs: bool,
+    /// Task logs tiled in the Logs tab, if any are open
+    pub log_panes: LogPanes,
+    /// Path being typed to save the selected task's full log to
+    pub save_log_path: Option<String>,
+    /// The latest log download, running or finished
+    pub log_download: Option<LogDownload>,
+    /// Changes applied since the last update was recorded
+    pending_churn: Churn,
+    /// Compression used for snapshots written with the snapshot key
+    pub snapshot_compression: Compression,
+    /// Health of the connection to the data source
+    pub health: ConnectorHealth,
+    /// State of each source's connection, for the header
+    pub connections: Connections,
+    /// What the data source provides, so views of the rest are hidden
+    pub capabilities: SourceCapabilities,
+    /// Labels the data source shows as columns of the task list
+    pub label_columns: Vec<String>,
+    /// Metadata about the monitored workflow run, once known
+    pub workflow: Option<WorkflowMetadata>,
+    /// Whether the workflow metadata drawer is open
+    pub show_workflow: bool,
+    /// Whether the details pane shows the raw JSON payload
+    pub show_raw_json: bool,
+    /// Whether the details pane shows the metric chart
+    pub show_chart: bool,
+    /// Sampled metrics charted in the details pane
+    pub metrics: MetricHistory,
+    /// Cursor, scales, and hidden series of the metric chart
+    pub chart: ChartState,
+    /// Scroll offset of the raw JSON view, in lines
+    pub json_scroll: u16,
+    /// Text waiting to be copied to the clipboard by the render loop
+    pending_clipboard: Option<String>,
+    /// Task table waiting to be printed by the render loop in copy mode
+    pending_table: Option<String>,
+    /// Case-insensitive text filter applied to task IDs and names
+    pub filter: Option<String>,
+    /// Backend whose tasks alone are listed, when several are followed
+    pub backend_filter: Option<String>,
+    /// Tasks pinned to the top of the list
+    pub pinned: HashSet<String>,
+    /// Tasks hidden from the list
+    pub archived: HashSet<String>,
+    /// Local actions that can be undone with `u`
+    pub undo: UndoStack,
+    /// Every action taken, for the audit overlay and file
+    pub audit: AuditLog,
+    /// Task the selected one is compared with in the diff overlay
+    pub reference_task: Option<String>,
+    /// Command comparing two task specs, from the configuration
+    pub diff_tool: Option<String>,
+    /// Program waiting to be given the terminal by the render loop
+    pending_command: Option<ExternalCommand>,
+    /// Whether control actions are only recorded, not sent
+    pub dry_run: bool,
+    /// Usage counted for the report sent on exit, if reporting is enabled
+    #[cfg(feature = "telemetry")]
+    pub telemetry: Option<Telemetry>,
+    /// Inactivity after which the dashboard is blanked, if enabled
+    pub blank_after: Option<Duration>,
+    /// Whether the dashboard is blanked until the next key press
+    pub blanked: bool,
+    /// Whether the task counts are shown in the terminal window's title
+    pub terminal_title: bool,
+    /// Charts drawn with the terminal's graphics protocol, if it has one
+    #[cfg(feature = "graphics")]
+    pub graphics: Option<Graphics>,
+    /// When the last key was pressed
+    last_input: Instant,
+    /// Terminal cell (column, row) the mouse pointer rests on, so the task
+    /// row under it can show a tooltip
+    pub hover: Option<(u16, u16)>,
+    /// Display names given to tasks locally, by task ID
+    pub display_names: BTreeMap<String, String>,
+    /// Text being typed as the selected task's new display name
+    pub rename: Option<String>,
+    /// Where local state is saved, if anywhere
+    state_path: Option<PathBuf>,
+    /// Which actions ask for confirmation
+    pub confirm_policy: ConfirmPolicy,
+    /// Action waiting for the user to confirm it
+    pub confirmation: Option<Confirmable>,
+    /// Order of the task list
+    pub sort: SortOrder,
+    /// Live resource ranking the task list instead of the sort order, in
+    /// top mode
+    pub top: Option<TopMetric>,
+    /// Resources the site provides, as far as known
+    pub capacity: Capacity,
+    /// Number of pending tasks that can never run, when last checked
+    never_run: usize,
+    /// Acceptable failure rate of the run, for the error budget
+    pub slo: Slo,
+    /// Whether the error budget was exhausted when last checked
+    budget_exhausted: bool,
+    /// Highlighted row of the sort menu, while it is open
+    pub sort_menu: Option<usize>,
+    /// Glossary entry shown in the `?` popover, while it is open
+    pub glossary: Option<usize>,
+    /// Notable events of the run, for the Events tab
+    pub feed: EventFeed,
+    /// Note being typed for a bookmark of the current moment
+    pub bookmark_note: Option<String>,
+    /// How long a running task goes without updates before it is shown as
+    /// stale, if ever
+    pub stale_after: Option<Duration>,
+    /// What the last frame drew, for redrawing only what changed since
+    pub regions: Regions,
+    /// Counts changes to the tasks other than new log lines
+    store_version: u64,
+    /// Per-task activity charts for the task list
+    pub sparklines: Sparklines,
+    /// What the timeline's lanes are grouped by
+    pub timeline_lanes: LaneKey,
+    /// Grouping of the task list by workflow
+    pub grouping: Grouping,
+    /// Periodic copies of the task store for scrubbing back in time
+    pub history: History,
+    /// Alert rules and the alerts they currently raise
+    pub alerts: Alerts,
+    /// Bells rung for raised alerts, and whether they are muted
+    pub sounds: Sounds,
+    /// Running tasks that take far longer than their step's median
+    pub anomalies: Anomalies,
+    /// Pruning of finished tasks, and what pruned tasks left behind
+    pub retention: Retention,
+    /// Live state while an older copy of the store is shown
+    scrub: Option<Scrub>,
+    /// Release check in progress, if any
+    update_check: Option<UpdateCheck>,
+    /// A newer release found by the update check, until dismissed
+    pub available_update: Option<Release>,
+    /// Transient message shown in the footer, with the time it was set
+    pub status_message: Option<(String, Instant)>,
+    /// Control socket accepting commands from external tools, if bound
+    #[cfg(unix)]
+    control: Option<ControlServer>,
 }
 
 impl Default for App {
     fn default() -> Self {
-        // Create some sample tasks for demonstration
-        let mut tasks = HashMap::new();
-        let mut task_ids = Vec::new();
-        
-        for i in 1..20 {
-            let id = format!("task-{}", i);
-            let status = match i % 4 {
-                0 => TaskStatus::Pending,
-                1 => TaskStatus::Running,
-                2 => TaskStatus::Completed,
-                _ => TaskStatus::Failed,
-            };
-            
-            let progress = match status {
-                TaskStatus::Pending => 0.0,
-                TaskStatus::Running => (i as f64 % 10.0) / 10.0,
-                TaskStatus::Completed => 1.0,
-                TaskStatus::Failed => (i as f64 % 10.0) / 10.0,
-            };
-            
-            let task = Task {
-                id: id.clone(),
-                name: format!("Sample Task {}", i),
-                status,
-                progress,
-                cpu_usage: (i as f64 % 100.0) / 100.0,
-                memory_usage: (i as f64 % 80.0) / 100.0,
-            };
-            
-            task_ids.push(id.clone());
-            tasks.insert(id, task);
+        Self::new()
+    }
+}
+
+impl App {
+    /// Creates an application showing the demo tasks
+    pub fn new() -> Self {
+        let demo = MockDataSource::new();
+        Self {
+            capacity: demo.capacity(),
+            workflow: Some(demo.workflow()),
+            log_fetcher: Some(LogFetcher::spawn(SyntheticLogProvider::default())),
+            ..Self::with_source(demo)
         }
-        
+    }
+
+    /// Creates an application showing the tasks of `source`, taking what
+    /// it has to report right away
+    pub fn with_source(source: impl DataSource + 'static) -> Self {
+        let mut app = Self::base(Box::new(source));
+        app.poll_source();
+        app
+    }
+
+    /// Creates an application with nothing shown yet, following `source`
+    fn base(source: Box<dyn DataSource>) -> Self {
+        let source = CachedSource::new(source, FetchConfig::default());
+        let source_fetch = source.control();
         Self {
-            tasks,
+            tasks: HashMap::new(),
             selected_task_id: None,
-            task_ids,
+            task_ids: Vec::new(),
             should_quit: false,
             tab_index: 0,
+            overlays: OverlayStack::default(),
+            perf: PerfStats::default(),
+            log_limits: LogLimits::default(),
+            fetch_config: FetchConfig::default(),
+            progress: ProgressInterpolator::default(),
+            smooth_progress: None,
+            theme: Theme::default(),
+            numbers: NumberFormat::from_env(),
+            screen_reader: false,
+            reduced_motion: false,
+            tick_count: 0,
+            bus: EventBus::default(),
+            health: ConnectorHealth::new(source.name()),
+            connections: Connections::new(source.origins()),
+            capabilities: source.capabilities(),
+            label_columns: source.label_columns(),
+            source: Box::new(source),
+            source_fetch,
+            log_fetcher: None,
+            highlighter: Highlighter::default(),
+            expand_structured_logs: false,
+            log_panes: LogPanes::default(),
+            save_log_path: None,
+            log_download: None,
+            recent_logs: VecDeque::new(),
+            pending_churn: Churn::default(),
+            snapshot_compression: Compression::None,
+            workflow: None,
+            show_workflow: false,
+            show_raw_json: false,
+            show_chart: false,
+            pending_table: None,
+            audit: AuditLog::default(),
+            reference_task: None,
+            diff_tool: None,
+            pending_command: None,
+            dry_run: false,
+            #[cfg(feature = "telemetry")]
+            telemetry: None,
+            blank_after: None,
+            blanked: false,
+            terminal_title: true,
+            #[cfg(feature = "graphics")]
+            graphics: None,
+            last_input: Instant::now(),
+            hover: None,
+            metrics: MetricHistory::default(),
+            chart: ChartState::default(),
+            json_scroll: 0,
+            pending_clipboard: None,
+            filter: None,
+            backend_filter: None,
+            pinned: HashSet::new(),
+            archived: HashSet::new(),
+            undo: UndoStack::default(),
+            display_names: BTreeMap::new(),
+            rename: None,
+            state_path: None,
+            confirm_policy: ConfirmPolicy::default(),
+            confirmation: None,
+            sort: SortOrder::default(),
+            top: None,
+            capacity: Capacity::default(),
+            never_run: 0,
+            slo: Slo::default(),
+            budget_exhausted: false,
+            sort_menu: None,
+            glossary: None,
+            feed: EventFeed::default(),
+            bookmark_note: None,
+            stale_after: Some(DEFAULT_STALE_AFTER),
+            regions: Regions::default(),
+            store_version: 0,
+            sparklines: Sparklines::default(),
+            timeline_lanes: LaneKey::default(),
+            grouping: Grouping::default(),
+            history: History::default(),
+            alerts: Alerts::default(),
+            sounds: Sounds::default(),
+            anomalies: Anomalies::default(),
+            retention: Retention::default(),
+            scrub: None,
+            update_check: None,
+            available_update: None,
+            status_message: None,
+            #[cfg(unix)]
+            control: None,
         }
     }
-}
 
-impl App {
-    /// Creates a new application with default state
-    pub fn new() -> Self {
-        Self::default()
+    /// Creates an application showing `count` synthetic tasks that churn
+    /// on every tick
+    pub fn with_simulation(count: usize) -> Self {
+        let simulator = Simulator::new(count);
+        Self {
+            capacity: simulator.capacity(),
+            log_fetcher: Some(LogFetcher::spawn(SyntheticLogProvider::default())),
+            workflow: Some(WorkflowMetadata {
+                run_id: Some(format!("simulation-{}", count)),
+                name: Some("Synthetic load test".to_string()),
+                submitted_at: Some(record::unix_now()),
+                ..WorkflowMetadata::default()
+            }),
+            ..Self::with_source(simulator)
+        }
     }
-    
-    /// Handles key events
-    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
-        match key.code {
-            KeyCode::Char('q') | KeyCode::Esc => {
-                self.should_quit = true;
-                true
+
+    /// Creates an application showing the tasks of the engine at `url`,
+    /// streamed over a live connection made with `access`
+    pub fn with_engine(url: &str, access: RemoteAccess) -> Self {
+        let engine = EngineConnection::connect_with(url, access);
+        let status = format!("Connecting to {}", engine.url());
+        let mut app = Self::with_source(engine);
+        app.set_status(status);
+        app
+    }
+
+    /// Creates an application showing the updates pushed by the WebSocket
+    /// feed at `url`, as they arrive, subscribed to with `access`
+    pub fn with_websocket(url: &str, access: RemoteAccess) -> Self {
+        let websocket = WebSocketDataSource::connect_with(url, access);
+        let status = format!("Subscribing to {}", websocket.url());
+        let mut app = Self::with_source(websocket);
+        app.set_status(status);
+        app
+    }
+
+    /// Creates an application showing the task updates of the Server-Sent
+    /// Events stream at `url`, as they arrive, opened with `access`
+    pub fn with_sse(url: &str, access: RemoteAccess) -> Self {
+        let sse = SseDataSource::connect_with(url, access);
+        let status = format!("Following {}", sse.url());
+        let mut app = Self::with_source(sse);
+        app.set_status(status);
+        app
+    }
+
+    /// Creates an application showing the tasks of the TES service at
+    /// `url`, polled in the background with `access`
+    pub fn with_tes(url: &str, access: RemoteAccess) -> Self {
+        let tes = TesDataSource::connect_with(url, access);
+        let status = format!("Connecting to TES at {}", tes.url());
+        let mut app = Self::with_source(tes);
+        app.set_status(status);
+        app
+    }
+
+    /// Creates an application showing the containers of Crankshaft tasks
+    /// on the Docker daemon listening on `socket`, polled in the background
+    #[cfg(unix)]
+    pub fn with_docker(socket: &std::path::Path) -> Self {
+        let docker = DockerDataSource::connect(socket);
+        let status = format!("Connecting to Docker at {}", docker.socket().display());
+        let mut app = Self::with_source(docker);
+        app.set_status(status);
+        app
+    }
+
+    /// Creates an application showing the Kubernetes Jobs in `target`,
+    /// watched in the background
+    pub fn with_k8s(target: K8sTarget) -> Self {
+        let status = format!("Connecting to Kubernetes at {}", target.api);
+        let mut app = Self::with_source(K8sDataSource::connect(target));
+        app.set_status(status);
+        app
+    }
+
+    /// Creates an application showing the processes started by the local
+    /// engine whose process has the PID or name `engine`, sampled in the
+    /// background
+    pub fn with_local(engine: &str) -> Self {
+        let local = LocalDataSource::watch(engine);
+        let status = format!("Looking for process {}", local.engine());
+        let mut app = Self::with_source(local);
+        app.set_status(status);
+        app
+    }
+
+    /// Creates an application showing the Slurm jobs of `user`, polled in
+    /// the background
+    pub fn with_slurm(user: &str) -> Self {
+        let slurm = SlurmDataSource::connect(user);
+        let status = format!("Listing the Slurm jobs of {}", slurm.user());
+        let mut app = Self::with_source(slurm);
+        app.set_status(status);
+        app
+    }
+
+    /// Creates an application showing the LSF jobs of `user`, or of the
+    /// user running the monitor, polled in the background
+    pub fn with_lsf(user: Option<&str>) -> Self {
+        let lsf = LsfDataSource::connect(user);
+        let status = match lsf.user() {
+            Some(user) => format!("Listing the LSF jobs of {}", user),
+            None => "Listing LSF jobs".to_string(),
+        };
+        let mut app = Self::with_source(lsf);
+        app.set_status(status);
+        app
+    }
+
+    /// Creates an application showing the AWS Batch jobs in `queues`,
+    /// polled in the background
+    pub fn with_aws_batch(queues: &[String]) -> Self {
+        let aws_batch = AwsBatchDataSource::connect(queues);
+        let status = format!("Listing the AWS Batch jobs in {}", aws_batch.queues().join(", "));
+        let mut app = Self::with_source(aws_batch);
+        app.set_status(status);
+        app
+    }
+
+    /// Creates an application showing the tasks in the state file at
+    /// `path`, read again whenever it changes
+    pub fn with_file(path: &std::path::Path) -> Self {
+        let file = FileDataSource::watch(path);
+        let status = format!("Reading task states from {}", file.path().display());
+        let mut app = Self::with_source(file);
+        app.set_status(status);
+        app
+    }
+
+    /// Creates an application showing the tasks of several sources at
+    /// once, combined by `merged`
+    pub fn with_merged(merged: MergedDataSource) -> Self {
+        let status = format!("Combining the tasks of {}", merged.source_names().join(", "));
+        let mut app = Self::with_source(merged);
+        app.set_status(status);
+        app
+    }
+
+    /// Creates an application showing the updates piped to standard input
+    pub fn with_stdin() -> Self {
+        let mut app = Self::with_source(StreamDataSource::stdin());
+        app.set_status("Reading task updates from stdin");
+        app
+    }
+
+    /// Creates an application showing the updates pushed to `listener`, a
+    /// named pipe or socket
+    pub fn with_listener(listener: StreamDataSource) -> Self {
+        let status = match listener.path() {
+            Some(path) => format!("Listening for task updates on {}", path.display()),
+            None => format!("Reading task updates from {}", listener.name()),
+        };
+        let mut app = Self::with_source(listener);
+        app.set_status(status);
+        app
+    }
+
+    /// Creates an application that plays back a recorded session
+    pub fn with_replay(replay: Replayer) -> Self {
+        Self::with_source(replay)
+    }
+
+    /// Declares what the data source provides, leaving top mode if it ranks
+    /// by a metric that is not reported
+    pub fn set_capabilities(&mut self, capabilities: SourceCapabilities) {
+        self.capabilities = capabilities;
+        if self.top.is_some_and(|metric| !capabilities.top(metric)) {
+            self.top = None;
+        }
+    }
+
+    /// Records the task store to a file while the application runs
+    pub fn set_recorder(&mut self, recorder: Recorder) {
+        self.bus.subscribe(recorder);
+    }
+
+    /// Registers a component to be notified of state changes after every
+    /// update
+    pub fn subscribe(&mut self, subscriber: impl Subscriber + 'static) {
+        self.bus.subscribe(subscriber);
+    }
+
+    /// Replaces the workflow metadata shown in the metadata drawer
+    pub fn set_workflow_metadata(&mut self, metadata: WorkflowMetadata) {
+        self.workflow = Some(metadata);
+    }
+
+    /// Accepts commands from the given control socket while running
+    #[cfg(unix)]
+    pub fn set_control_server(&mut self, server: ControlServer) {
+        self.control = Some(server);
+    }
+
+    /// Applies a command received from an external tool
+    pub fn apply_control(&mut self, command: ControlCommand) {
+        match command {
+            ControlCommand::Select(id) => {
+                if !self.select_task(&id) {
+                    self.set_status(format!("Task {} not found yet; it will be selected when it appears", id));
+                }
             }
-            KeyCode::Tab => {
-                self.tab_index = (self.tab_index + 1) % 3; // Cycle through tabs
-                false
+            ControlCommand::Filter(filter) => self.set_filter(filter),
+            ControlCommand::Tab(tab) => self.set_tab(tab),
+        }
+    }
+
+    /// Returns `true` when playing back a recording rather than live data
+    pub fn is_replaying(&self) -> bool {
+        !self.source.is_live()
+    }
+
+    /// Returns `true` if no update for a running task has arrived within
+    /// the staleness threshold as of `now`, in seconds since the Unix epoch
+    ///
+    /// Only live sources that sample resource use keep running tasks'
+    /// figures moving; the others send changes alone, so their quiet tasks
+    /// are not stale.
+    pub fn is_stale(&self, task: &Task, now: u64) -> bool {
+        let Some(after) = self.stale_after else {
+            return false;
+        };
+        task.status == TaskStatus::Running
+            && self.capabilities.has_metrics()
+            && !self.is_replaying()
+            && !self.is_viewing_history()
+            && task.last_updated.is_some_and(|at| now.saturating_sub(at) >= after.as_secs())
+    }
+
+    /// Shows a transient message in the footer
+    ///
+    /// Messages are also kept in the internal log included in crash reports.
+    pub fn set_status(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        crate::crash::log(message.clone());
+        self.status_message = Some((message, Instant::now()));
+    }
+
+    /// Returns the footer status message if it has not expired
+    pub fn status(&self) -> Option<&str> {
+        self.status_message
+            .as_ref()
+            .filter(|(_, at)| at.elapsed() < STATUS_MESSAGE_TIMEOUT)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// Queues the selected task's raw JSON for copying to the clipboard
+    fn yank_raw_json(&mut self) {
+        let Some(task) = self.selected_task_id.as_ref().and_then(|id| self.tasks.get(id)) else {
+            return;
+        };
+        self.pending_clipboard = Some(task.raw_json());
+        self.set_status("Copied task JSON to the clipboard");
+    }
+
+    /// Moves the chart cursor one sample left or right along the selected
+    /// task's chart
+    fn move_chart_cursor(&mut self, left: bool) {
+        let Some(points) = self.selected_task_id.as_ref().and_then(|id| self.metrics.points(id)) else {
+            return;
+        };
+        if left {
+            self.chart.cursor.left(points);
+        } else {
+            self.chart.cursor.right(points);
+        }
+    }
+
+    /// Takes any text waiting to be copied to the clipboard
+    pub fn take_clipboard(&mut self) -> Option<String> {
+        self.pending_clipboard.take()
+    }
+
+    /// Takes the task table waiting to be printed in copy mode, if any
+    pub fn take_copy_table(&mut self) -> Option<String> {
+        self.pending_table.take()
+    }
+
+    /// Takes the program waiting to be run with the terminal, if any
+    pub fn take_external_command(&mut self) -> Option<ExternalCommand> {
+        self.pending_command.take()
+    }
+
+    /// Writes a snapshot of the task store to the current directory
+    pub fn write_snapshot(&mut self) {
+        let path = PathBuf::from(format!(
+            "crankshaft-snapshot-{}.json{}",
+            record::unix_now(),
+            self.snapshot_compression.extension()
+        ));
+        let tasks = self.task_ids.iter().filter_map(|id| self.tasks.get(id));
+        let action = format!("write snapshot {}", path.display());
+        match record::write_snapshot(&path, tasks) {
+            Ok(()) => {
+                self.record_action(action, Outcome::Done, None);
+                self.set_status(format!("Snapshot written to {}", path.display()));
             }
-            KeyCode::BackTab => {
-                self.tab_index = (self.tab_index + 2) % 3; // Cycle backwards
-                false
+            Err(err) => {
+                self.record_action(action, Outcome::Failed, Some(err.to_string()));
+                self.set_status(format!("Failed to write snapshot: {}", err));
             }
-            KeyCode::Down => {
-                self.next_task();
-                false
+        }
+    }
+
+    /// Writes the dependency graph of all tasks as Graphviz DOT to the
+    /// current directory
+    pub fn export_graph(&mut self) {
+        let path = PathBuf::from(format!("crankshaft-graph-{}.dot", record::unix_now()));
+        let tasks = self.task_ids.iter().filter_map(|id| self.tasks.get(id));
+        let action = format!("export graph {}", path.display());
+        match dot::write_dot(&path, tasks, &self.theme) {
+            Ok(()) => {
+                self.record_action(action, Outcome::Done, None);
+                self.set_status(format!("Graph written to {}", path.display()));
             }
-            KeyCode::Up => {
-                self.previous_task();
-                false
+            Err(err) => {
+                self.record_action(action, Outcome::Failed, Some(err.to_string()));
+                self.set_status(format!("Failed to write graph: {}", err));
             }
-            _ => false,
         }
     }
-    
-    /// Updates the application state
-    pub fn update(&mut self) {
-        // In a real implementation, this would fetch updated task information
-        // For now, we'll just update the progress of running tasks
-        for task in self.tasks.values_mut() {
-            if task.status == TaskStatus::Running {
-                task.progress += 0.01;
-                if task.progress >= 1.0 {
-                    task.progress = 1.0;
-                    task.status = TaskStatus::Completed;
+
+    /// Applies user configuration
+    pub fn apply_config(&mut self, config: &Config) {
+        self.set_log_limits(config.logs);
+        self.set_fetch_config(config.fetch);
+        self.set_reduced_motion(config.display.reduced_motion);
+        if !self.reduced_motion {
+            self.progress.set_enabled(config.display.interpolate_progress);
+        }
+        self.theme = config.theme;
+        self.confirm_policy = config.confirm;
+        self.sort = config.sort.clone();
+        self.capacity.merge(&config.capacity);
+        self.slo = config.slo;
+        self.sparklines.set_source(config.display.sparkline);
+        self.smooth_progress = config.display.smooth_progress;
+        self.timeline_lanes = config.timeline.lanes.clone();
+        self.grouping.set_config(&config.grouping);
+        self.diff_tool = config.tools.diff.clone();
+        self.alerts.set_rules(&config.alerts);
+        self.sounds.set_config(config.sounds);
+        self.sounds.watch(crate::crash::config_path());
+        self.anomalies.set_config(config.anomalies);
+        self.retention.set_config(config.retention);
+        self.highlighter = Highlighter::new(&config.highlights);
+        self.blank_after = config.privacy.blank_after_secs.map(Duration::from_secs);
+        self.terminal_title = config.display.terminal_title;
+        self.stale_after = (config.display.stale_after_secs > 0).then(|| Duration::from_secs(config.display.stale_after_secs));
+        self.regions.invalidate();
+        #[cfg(feature = "graphics")]
+        {
+            self.graphics = config.display.graphics.then(Graphics::detect).flatten();
+        }
+        if let Some(locale) = &config.display.locale {
+            self.numbers = NumberFormat::from_locale(locale);
+        }
+        self.set_screen_reader(config.accessibility.screen_reader);
+        if config.updates.check && self.update_check.is_none() {
+            self.update_check = Some(UpdateCheck::spawn());
+        }
+        if let Some(path) = &config.debug.memory_log {
+            let interval = config
+                .debug
+                .memory_log_interval_secs
+                .map_or(memory::DEFAULT_LOG_INTERVAL, Duration::from_secs);
+            match MemoryLogger::open(path, interval) {
+                Ok(logger) => self.bus.subscribe(logger),
+                Err(err) => self.set_status(format!("Cannot write memory log {}: {}", path.display(), err)),
+            }
+        }
+        match config.polling.intervals() {
+            Ok(intervals) => {
+                for (source, interval) in intervals {
+                    self.source.set_poll_interval(&source, interval);
                 }
             }
+            Err(err) => self.set_status(format!("Cannot set poll intervals: {:#}", err)),
+        }
+        if let Some(path) = &config.audit.file {
+            if let Err(err) = self.audit.open(path) {
+                self.set_status(format!("Cannot write audit log {}: {}", path.display(), err));
+            }
         }
+        if let Some(path) = &config.accessibility.summary_file {
+            match SummaryWriter::open(path) {
+                Ok(writer) => self.bus.subscribe(writer),
+                Err(err) => self.set_status(format!("Cannot write summaries to {}: {}", path.display(), err)),
+            }
+        }
+        self.set_telemetry(&config.telemetry);
     }
-    
-    /// Selects the next task in the list
-    fn next_task(&mut self) {
-        if self.task_ids.is_empty() {
+
+    /// Starts counting usage for the report sent on exit, if the
+    /// configuration enables it, and says so
+    #[cfg(feature = "telemetry")]
+    fn set_telemetry(&mut self, config: &TelemetryConfig) {
+        self.telemetry = None;
+        if !config.enabled {
             return;
         }
-        
-        let current_index = match &self.selected_task_id {
-            Some(id) => self.task_ids.iter().position(|x| x == id).unwrap_or(0),
-            None => 0,
-        };
-        
-        let next_index = (current_index + 1) % self.task_ids.len();
-        self.selected_task_id = Some(self.task_ids[next_index].clone());
+        match &config.endpoint {
+            Some(endpoint) => {
+                self.telemetry = Some(Telemetry::new(endpoint));
+                self.set_status(format!(
+                    "Anonymous usage reports will be sent to {} on exit; press i for details",
+                    endpoint
+                ));
+            }
+            None => self.set_status("Usage reporting is enabled but no endpoint is configured; nothing will be sent"),
+        }
     }
-    
-    /// Selects the previous task in the list
-    fn previous_task(&mut self) {
-        if self.task_ids.is_empty() {
-            return;
+
+    /// Reports that usage reporting was requested from a build without it
+    #[cfg(not(feature = "telemetry"))]
+    fn set_telemetry(&mut self, config: &TelemetryConfig) {
+        if config.enabled {
+            self.set_status("Usage reporting is not included in this build; nothing will be sent");
         }
-        
-        let current_index = match &self.selected_task_id {
-            Some(id) => self.task_ids.iter().position(|x| x == id).unwrap_or(0),
-            None => 0,
-        };
-        
-        let previous_index = if current_index == 0 {
-            self.task_ids.len() - 1
-        } else {
-            current_index - 1
+    }
+
+    /// Remembers the terminal size for the usage report
+    #[cfg(feature = "telemetry")]
+    pub fn record_terminal_size(&mut self, area: Rect) {
+        if let Some(telemetry) = &mut self.telemetry {
+            telemetry.terminal_size(area.width, area.height);
+        }
+    }
+
+    /// Sends the usage report, if reporting is enabled. Failures are only
+    /// logged, since the monitor is exiting anyway.
+    #[cfg(feature = "telemetry")]
+    pub fn send_usage_report(&mut self) {
+        if let Some(telemetry) = self.telemetry.take() {
+            if let Err(err) = telemetry.send() {
+                crash::log(format!("{:#}", err));
+            }
+        }
+    }
+
+    /// Enables or disables reduced-motion rendering
+    ///
+    /// Reduced motion also turns off progress interpolation, since gauges
+    /// creeping between updates are themselves an animation.
+    pub fn set_reduced_motion(&mut self, enabled: bool) {
+        self.reduced_motion = enabled;
+        if enabled {
+            self.progress.set_enabled(false);
+        }
+    }
+
+    /// Returns the current frame of an activity spinner, or a static
+    /// indicator in reduced-motion mode
+    pub fn spinner(&self) -> &'static str {
+        const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+        if self.reduced_motion || self.screen_reader {
+            return "•";
+        }
+        FRAMES[(self.tick_count % FRAMES.len() as u64) as usize]
+    }
+
+    /// Enables or disables screen-reader friendly rendering
+    pub fn set_screen_reader(&mut self, enabled: bool) {
+        self.screen_reader = enabled;
+        if enabled {
+            self.theme.symbols = StatusSymbols::Letters;
+        }
+    }
+
+    /// Counts tasks by status, including those pruned
+    pub fn status_counts(&self) -> StatusCounts {
+        let pruned = self.retention.pruned();
+        let mut counts = StatusCounts {
+            completed: pruned.completed,
+            failed: pruned.failed,
+            ..StatusCounts::default()
         };
-        
-        self.selected_task_id = Some(self.task_ids[previous_index].clone());
+        for task in self.tasks.values() {
+            match task.status {
+                TaskStatus::Pending => counts.pending += 1,
+                TaskStatus::Running => counts.running += 1,
+                TaskStatus::Completed => counts.completed += 1,
+                TaskStatus::Failed => counts.failed += 1,
+            }
+        }
+        counts
+    }
+
+    /// Returns the duration percentiles of completed tasks per step,
+    /// including those pruned
+    pub fn step_durations(&self) -> Vec<StepDurations> {
+        crate::durations::by_step_including(self.tasks.values(), &self.retention.pruned().durations)
+    }
+
+    /// Returns the progress to display for a task, interpolated between
+    /// backend updates when enabled
+    pub fn display_progress(&self, task: &Task) -> f64 {
+        self.progress.progress(task)
+    }
+
+    /// Changes the log retention limits for all current and future tasks
+    pub fn set_log_limits(&mut self, limits: LogLimits) {
+        self.log_limits = limits;
+        for task in self.tasks.values_mut() {
+            task.logs.set_limits(limits);
+        }
     }
-}
\ No newline at end of file
+
+    /// Changes how fetches from the log provider are cached and limited,
+    /// and how often the data source is polled
+    pub fn set_fetch_config(&mut self, config: FetchConfig) {
+        self.fetch_config = config;
+        self.source_fetch.set_config(config);
+        if let Some(fetcher) = &self.log_fetcher {
+            fetcher.set_fetch_config(config);
+        }
+    }
+
+    /// Returns the fetches made through the log provider, if there is one
+    pub fn fetch_stats(&self) -> Option<FetchStats> {
+        self.log_fetcher.as_ref().map(LogFetcher::fetch_stats)
+    }
+
+    /// Returns the polls of the data source so far
+    pub fn source_fetch_stats(&self) -> FetchStats {
+        self.source_fetch.stats()
+    }
+
+    /// Replaces the provider used to fetch task logs
+    pub fn set_log_provider(&mut self, provider: impl LogProvider) {
+        let fetcher = LogFetcher::spawn(provider);
+        fetcher.set_fetch_config(self.fetch_config);
+        self.log_fetcher = Some(fetcher);
+    }
+
+    /// Returns `true` while a log fetch for the task is outstanding
+    pub fn is_log_loading(&self, task_id: &str) -> bool {
+        self.log_fetcher
+            .as_ref()
+            .is_some_and(|fetcher| fetcher.is_pending(task_id))
+    }
+
+    /// Returns the currently displayed tab
+    pub fn current_tab(&self) -> Tab {
+        Tab::from_index(self.tab_index).unwrap_or(Tab::Tasks)
+    }
+
+    /// Switches to the given tab
+    pub fn set_tab(&mut self, tab: Tab) {
+        self.tab_index = tab.index();
+    }
+
+    /// Sets the task filter; an empty filter shows every task
+    pub fn set_filter(&mut self, filter: impl Into<String>) {
+        let filter = filter.into();
+        self.filter = if filter.is_empty() { None } else { Some(filter.to_lowercase()) };
+    }
+
+    /// Returns the backends the tasks in the store come from, in the order
+    /// their first tasks arrived
+    pub fn backends(&self) -> Vec<&str> {
+        let mut backends: Vec<&str> = Vec::new();
+        for backend in self.task_ids.iter().filter_map(|id| self.tasks.get(id)?.labels.get("backend")) {
+            if !backends.contains(&backend.as_str()) {
+                backends.push(backend);
+            }
+        }
+        backends
+    }
+
+    /// Lists only the tasks of the next backend, or every task again after
+    /// the last one
+    fn cycle_backend_filter(&mut self) {
+        let backends: Vec<String> = self.backends().into_iter().map(str::to_string).collect();
+        if backends.len() < 2 && self.backend_filter.is_none() {
+            self.set_status("Every task comes from the same backend");
+            return;
+        }
+        let next = match &self.backend_filter {
+            Some(current) => backends.iter().position(|backend| backend == current).and_then(|index| backends.get(index + 1)),
+            None => backends.first(),
+        };
+        self.backend_filter = next.cloned();
+        match &self.backend_filter {
+            Some(backend) => self.set_status(format!("Showing the tasks of {}", backend)),
+            None => self.set_status("Showing the tasks of every backend"),
+        }
+    }
+
+    /// Returns `true` if the task passes the current filter
+    pub fn matches_filter(&self, task: &Task) -> bool {
+        if let Some(backend) = &self.backend_filter {
+            if task.labels.get("backend") != Some(backend) {
+                return false;
+            }
+        }
+        match &self.filter {
+            Some(filter) => {
+                task.id.to_lowercase().contains(filter.as_str())
+                    || task.name.to_lowercase().contains(filter.as_str())
+                    || self.display_names.get(&task.id).is_some_and(|name| name.to_lowercase().contains(filter.as_str()))
+            }
+            None => true,
+        }
+    }
+
+    /// Returns the IDs of the tasks shown in the list, in display order
+    ///
+    /// Tasks follow the sort order, with pinned tasks first; archived tasks
+    /// are left out.
+    pub fn visible_task_ids(&self) -> Vec<&String> {
+        if !self.grouping.enabled {
+            return self.listed_task_ids();
+        }
+        self.task_groups()
+            .iter()
+            .flat_map(|group| group.listed().iter().copied())
+            .collect()
+    }
+
+    /// Returns the listed tasks split into workflow groups, with every task
+    /// of collapsed groups
+    pub fn task_groups(&self) -> Vec<TaskGroup<'_>> {
+        self.grouping.arrange(self.listed_task_ids(), &self.tasks)
+    }
+
+    /// Returns the IDs of the tasks that pass the filter and are not
+    /// archived, in list order
+    fn listed_task_ids(&self) -> Vec<&String> {
+        let mut ids: Vec<&String> = self
+            .task_ids
+            .iter()
+            .filter(|id| !self.archived.contains(*id))
+            .filter(|id| self.tasks.get(*id).is_some_and(|task| self.matches_filter(task)))
+            .collect();
+        if let Some(metric) = self.top {
+            ids.sort_by(|a, b| metric.compare(&self.tasks[*a], &self.tasks[*b]));
+        } else if !self.sort.is_empty() {
+            let now = record::unix_now();
+            ids.sort_by(|a, b| self.sort.compare(&self.tasks[*a], &self.tasks[*b], now));
+        }
+        if !self.pinned.is_empty() {
+            ids.sort_by_key(|id| !self.pinned.contains(*id));
+        }
+        ids
+    }
+
+    /// Performs a local action and records it so it can be undone,
+    /// returning `false` if it was refused or changed nothing
+    pub fn perform(&mut self, action: LocalAction) -> bool {
+        if self.is_viewing_history() {
+            self.record_action(action.to_string(), Outcome::Refused, Some("history is read-only".to_string()));
+            self.set_status("History is read-only; press ] to return to live");
+            return false;
+        }
+        if self.apply_local(&action) {
+            self.record_action(action.to_string(), Outcome::Done, None);
+            self.undo.push(action);
+            true
+        } else {
+            self.record_action(action.to_string(), Outcome::Unchanged, None);
+            false
+        }
+    }
+
+    /// Sends a control action to the data source, or in dry-run mode
+    /// records what it would have done
+    pub fn control(&mut self, action: TaskAction) {
+        if self.is_viewing_history() {
+            self.record_action(action.to_string(), Outcome::Refused, Some("history is read-only".to_string()));
+            self.set_status("History is read-only; press ] to return to live");
+            return;
+        }
+        if self.dry_run {
+            let would_have = action.would_have();
+            self.record_action(action.to_string(), Outcome::DryRun, Some(would_have.clone()));
+            self.set_status(format!("Dry run: {}", would_have));
+            return;
+        }
+        if !self.capabilities.control {
+            self.record_action(action.to_string(), Outcome::Refused, Some("not supported by the data source".to_string()));
+            self.set_status("This data source does not accept control actions (d for a dry run)");
+            return;
+        }
+        match self.source.control(&action) {
+            Ok(true) => {
+                self.record_action(action.to_string(), Outcome::Done, None);
+                self.set_status(format!("Sent: {}", action));
+            }
+            Ok(false) => {
+                self.record_action(action.to_string(), Outcome::Unchanged, None);
+                self.set_status(format!("Cannot {} in its current state", action));
+            }
+            Err(err) => {
+                self.record_action(action.to_string(), Outcome::Failed, Some(format!("{:#}", err)));
+                self.set_status(format!("Cannot {}: {:#}", action, err));
+            }
+        }
+    }
+
+    /// Turns dry-run mode on or off
+    fn toggle_dry_run(&mut self) {
+        self.dry_run = !self.dry_run;
+        let action = if self.dry_run { "dry run on" } else { "dry run off" };
+        self.record_action(action, Outcome::Done, None);
+        if self.dry_run {
+            self.set_status("Dry run on: cancel, retry, and priority changes are recorded, not sent");
+        } else {
+            self.set_status("Dry run off: control actions are sent to the data source");
+        }
+    }
+
+    /// Reverts the most recent local action
+    pub fn undo(&mut self) {
+        if self.is_viewing_history() {
+            self.record_action("undo", Outcome::Refused, Some("history is read-only".to_string()));
+            self.set_status("History is read-only; press ] to return to live");
+            return;
+        }
+        match self.undo.pop() {
+            Some(action) => {
+                self.apply_local(&action.inverse());
+                self.record_action(format!("undo {}", action), Outcome::Done, None);
+                self.set_status(format!("Undid {}", action));
+            }
+            None => self.set_status("Nothing to undo"),
+        }
+    }
+
+    /// Adds an action to the audit trail, reporting a failure to write the
+    /// audit file in the footer
+    fn record_action(&mut self, action: impl Into<String>, outcome: Outcome, detail: Option<String>) {
+        let action = action.into();
+        #[cfg(feature = "telemetry")]
+        if let (Some(telemetry), Outcome::Failed) = (&mut self.telemetry, outcome) {
+            telemetry.failed_action(&action);
+        }
+        if let Err(err) = self.audit.record(action, outcome, detail) {
+            self.set_status(format!("Cannot write audit log: {}", err));
+        }
+    }
+
+    /// Applies a local action, returning `false` if it changed nothing
+    fn apply_local(&mut self, action: &LocalAction) -> bool {
+        match action {
+            LocalAction::Pin { id } => self.pinned.insert(id.clone()),
+            LocalAction::Unpin { id } => self.pinned.remove(id),
+            LocalAction::Archive { id } => {
+                let visible = self.visible_task_ids();
+                let position = visible.iter().position(|visible| *visible == id);
+                let replacement = position.and_then(|i| {
+                    visible.get(i + 1).or_else(|| i.checked_sub(1).and_then(|i| visible.get(i)))
+                });
+                if self.selected_task_id.as_ref() == Some(id) {
+                    self.selected_task_id = replacement.map(|id| (*id).clone());
+                }
+                self.archived.insert(id.clone())
+            }
+            LocalAction::Unarchive { id } => self.archived.remove(id),
+            LocalAction::Rename { id, from, to } => {
+                match to {
+                    Some(name) => self.display_names.insert(id.clone(), name.clone()),
+                    None => self.display_names.remove(id),
+                };
+                self.save_state();
+                from != to
+            }
+        }
+    }
+
+    /// Loads local state such as display names from `path`, and saves it
+    /// there from now on
+    pub fn load_state(&mut self, path: PathBuf) {
+        match LocalState::load(&path) {
+            Ok(state) => self.display_names = state.display_names,
+            Err(err) => self.set_status(format!("Cannot read state file {}: {}", path.display(), err)),
+        }
+        self.state_path = Some(path);
+    }
+
+    /// Writes local state to the state file, if there is one
+    fn save_state(&mut self) {
+        let Some(path) = &self.state_path else {
+            return;
+        };
+        let state = LocalState {
+            display_names: self.display_names.clone(),
+        };
+        if let Err(err) = state.save(path) {
+            let message = format!("Cannot write state file {}: {}", path.display(), err);
+            self.set_status(message);
+        }
+    }
+
+    /// Returns the name a task is shown with: its local display name if it
+    /// has one, otherwise the backend's name
+    pub fn display_name<'a>(&'a self, task: &'a Task) -> &'a str {
+        self.display_names.get(&task.id).map_or(&task.name, String::as_str)
+    }
+
+    /// Starts renaming the selected task, beginning with its current name
+    fn start_rename(&mut self) {
+        if let Some(task) = self.selected_task_id.as_ref().and_then(|id| self.tasks.get(id)) {
+            self.rename = Some(self.display_name(task).to_string());
+        }
+    }
+
+    /// Handles a key while a display name is being typed
+    ///
+    /// Enter saves the name; an empty name, or the backend's own, restores
+    /// the backend's name.
+    fn handle_rename_key(&mut self, key: KeyEvent) {
+        let Some(text) = self.rename.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char(c) => text.push(c),
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Esc => self.rename = None,
+            KeyCode::Enter => {
+                let text = self.rename.take().unwrap_or_default();
+                let Some(task) = self.selected_task_id.as_ref().and_then(|id| self.tasks.get(id)) else {
+                    return;
+                };
+                let name = text.trim();
+                let to = (!name.is_empty() && name != task.name).then(|| name.to_string());
+                let action = LocalAction::Rename {
+                    id: task.id.clone(),
+                    from: self.display_names.get(&task.id).cloned(),
+                    to,
+                };
+                self.perform(action);
+            }
+            _ => {}
+        }
+    }
+
+    /// Collapses the selected task's group, selecting the row that stands
+    /// in for it, or expands the group if it is collapsed
+    fn toggle_selected_group(&mut self) {
+        let Some(task) = self.selected_task_id.as_ref().and_then(|id| self.tasks.get(id)) else {
+            return;
+        };
+        let Some(group) = self.grouping.group_of(task).map(str::to_string) else {
+            return;
+        };
+        let collapsed = self.grouping.toggle(&group);
+        if collapsed {
+            let head = self
+                .task_groups()
+                .into_iter()
+                .find(|listed| listed.name == Some(&group))
+                .and_then(|listed| listed.ids.first().map(|id| id.to_string()));
+            if head.is_some() {
+                self.selected_task_id = head;
+            }
+        }
+    }
+
+    /// Pins the selected task, or unpins it if it already is
+    fn toggle_pin(&mut self) {
+        let Some(id) = self.selected_task_id.clone() else {
+            return;
+        };
+        if self.pinned.contains(&id) {
+            self.perform(LocalAction::Unpin { id });
+        } else {
+            self.perform(LocalAction::Pin { id });
+        }
+    }
+
+    /// Makes the selected task the reference for the diff overlay, or
+    /// clears the reference if it already is
+    fn toggle_reference(&mut self) {
+        let Some(id) = self.selected_task_id.clone() else {
+            return;
+        };
+        if self.reference_task.as_ref() == Some(&id) {
+            self.reference_task = None;
+            self.overlays.close(Overlay::TaskDiff);
+            self.set_status("Reference task cleared");
+        } else {
+            self.set_status(format!("Reference task: {} (E on another task to compare)", id));
+            self.reference_task = Some(id);
+        }
+    }
+
+    /// Shows or hides the diff of the selected task against the reference
+    fn toggle_task_diff(&mut self) {
+        if self.overlays.close(Overlay::TaskDiff) {
+            return;
+        }
+        if self.reference_task.is_none() {
+            self.set_status("Mark a reference task with b first");
+        } else {
+            self.overlays.open(Overlay::TaskDiff);
+        }
+    }
+
+    /// Writes the specs of the reference and selected tasks and queues the
+    /// configured diff tool to compare them
+    fn open_external_diff(&mut self) {
+        let Some(tool) = self.diff_tool.clone() else {
+            self.set_status("No diff tool configured (set diff under [tools] in the configuration)");
+            return;
+        };
+        let reference = self.reference_task.as_ref().and_then(|id| self.tasks.get(id));
+        let task = self.selected_task_id.as_ref().and_then(|id| self.tasks.get(id));
+        let (Some(reference), Some(task)) = (reference, task) else {
+            self.set_status("Mark a reference task with b first");
+            return;
+        };
+        if reference.id == task.id {
+            self.set_status("Select another task to compare with the reference");
+            return;
+        }
+        match external::write_spec(reference).and_then(|reference| Ok((reference, external::write_spec(task)?))) {
+            Ok((reference, task)) => self.pending_command = ExternalCommand::diff(&tool, &reference, &task),
+            Err(err) => self.set_status(format!("Cannot write task specs: {}", err)),
+        }
+    }
+
+    /// Asks to cancel the selected task
+    fn cancel_selected(&mut self) {
+        if let Some(id) = self.selected_task_id.clone() {
+            self.request(Confirmable::Cancel { id });
+        }
+    }
+
+    /// Hides the selected task from the list
+    fn archive_selected(&mut self) {
+        if let Some(id) = self.selected_task_id.clone() {
+            self.request(Confirmable::Archive { id });
+        }
+    }
+
+    /// Selects a task by ID, returning `false` if no such task is known yet
+    ///
+    /// The selection is kept either way so that a task which appears later
+    /// (e.g. once a live source catches up) is focused when it does.
+    pub fn select_task(&mut self, id: &str) -> bool {
+        self.selected_task_id = Some(id.to_string());
+        self.tasks.contains_key(id)
+    }
+    
+    /// Runs an action, first asking for confirmation if the policy says so
+    pub fn request(&mut self, action: Confirmable) {
+        if self.confirm_policy.requires_confirmation(&action) {
+            self.confirmation = Some(action);
+            self.overlays.open(Overlay::Confirmation);
+        } else {
+            self.execute(action);
+        }
+    }
+
+    /// Runs a confirmed action
+    fn execute(&mut self, action: Confirmable) {
+        match action {
+            Confirmable::Archive { id } => {
+                if self.perform(LocalAction::Archive { id: id.clone() }) {
+                    self.set_status(format!("Archived {} (u to undo)", id));
+                }
+            }
+            Confirmable::Cancel { id } => self.control(TaskAction::Cancel { id }),
+            Confirmable::Quit => {
+                self.record_action("quit", Outcome::Done, None);
+                self.should_quit = true;
+            }
+        }
+    }
+
+    /// Handles a key while a confirmation dialog is open
+    fn handle_confirmation_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                self.overlays.close(Overlay::Confirmation);
+                if let Some(action) = self.confirmation.take() {
+                    self.execute(action);
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.overlays.close(Overlay::Confirmation);
+                if let Some(action) = self.confirmation.take() {
+                    self.record_action(action.to_string(), Outcome::Declined, None);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Handles a key while the sort menu is open
+    ///
+    /// Enter or `1` makes the highlighted key the primary sort, `2` the
+    /// secondary one; choosing a key again flips its direction.
+    fn handle_sort_menu_key(&mut self, key: KeyEvent) {
+        let Some(row) = self.sort_menu else {
+            return;
+        };
+        let level = match key.code {
+            KeyCode::Up => {
+                self.sort_menu = Some(row.checked_sub(1).unwrap_or(SortKey::ALL.len() - 1));
+                return;
+            }
+            KeyCode::Down => {
+                self.sort_menu = Some((row + 1) % SortKey::ALL.len());
+                return;
+            }
+            KeyCode::Char('c') => {
+                self.sort.clear();
+                return;
+            }
+            KeyCode::Esc | KeyCode::Char('o') => {
+                self.sort_menu = None;
+                self.overlays.close(Overlay::SortMenu);
+                return;
+            }
+            KeyCode::Enter | KeyCode::Char('1') => 0,
+            KeyCode::Char(c @ '2'..='9') => (c as usize - '1' as usize).min(MENU_LEVELS - 1),
+            _ => return,
+        };
+        // Choosing a sort key leaves top mode, which would override it
+        self.top = None;
+        self.sort.set_level(level, SortKey::ALL[row]);
+    }
+
+    /// Handles keys while the glossary popover is open
+    fn handle_glossary_key(&mut self, key: KeyEvent) {
+        let Some(index) = self.glossary else {
+            return;
+        };
+        let count = glossary::entries(self).len().max(1);
+        match key.code {
+            KeyCode::Left => self.glossary = Some(index.checked_sub(1).unwrap_or(count - 1)),
+            KeyCode::Right => self.glossary = Some((index + 1) % count),
+            KeyCode::Esc | KeyCode::Char('?') => {
+                self.glossary = None;
+                self.overlays.close(Overlay::Glossary);
+            }
+            _ => {}
+        }
+    }
+
+    /// Handles mouse events, tracking the pointer for the row tooltip
+    ///
+    /// Only movement shows the tooltip; clicks and scrolling hide it. The
+    /// selection is never changed.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.blanked {
+            return;
+        }
+        self.hover = (mouse.kind == MouseEventKind::Moved).then_some((mouse.column, mouse.row));
+    }
+
+    /// Handles key events
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        self.last_input = Instant::now();
+        // The key that wakes a blanked dashboard does nothing else
+        if self.blanked {
+            self.blanked = false;
+            return false;
+        }
+        match self.overlays.top() {
+            Some(Overlay::Confirmation) => {
+                self.handle_confirmation_key(key);
+                return self.should_quit;
+            }
+            Some(Overlay::SortMenu) => {
+                self.handle_sort_menu_key(key);
+                return false;
+            }
+            Some(Overlay::Glossary) => {
+                self.handle_glossary_key(key);
+                return false;
+            }
+            _ => {}
+        }
+        if self.rename.is_some() {
+            self.handle_rename_key(key);
+            return false;
+        }
+        if self.save_log_path.is_some() {
+            self.handle_save_log_key(key);
+            return false;
+        }
+        if self.bookmark_note.is_some() {
+            self.handle_bookmark_key(key);
+            return false;
+        }
+        // Esc closes the overlay on top before it quits
+        if key.code == KeyCode::Esc && self.overlays.dismiss().is_some() {
+            return false;
+        }
+
+        #[cfg(feature = "telemetry")]
+        {
+            let tab = self.current_tab();
+            if let Some(telemetry) = &mut self.telemetry {
+                telemetry.key(key.code, tab);
+            }
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.request(Confirmable::Quit);
+                self.should_quit
+            }
+            KeyCode::Char('s' | 'L') if !self.capabilities.logs => {
+                self.set_status("This data source does not provide task logs");
+                false
+            }
+            KeyCode::Char('G') if !self.capabilities.dependencies => {
+                self.set_status("This data source does not report task dependencies");
+                false
+            }
+            KeyCode::Tab => {
+                self.tab_index = (self.tab_index + 1) % Tab::ALL.len(); // Cycle through tabs
+                false
+            }
+            KeyCode::BackTab => {
+                self.tab_index = (self.tab_index + Tab::ALL.len() - 1) % Tab::ALL.len(); // Cycle backwards
+                false
+            }
+            KeyCode::F(12) => {
+                self.overlays.toggle(Overlay::Performance);
+                false
+            }
+            KeyCode::Char('D') => {
+                self.overlays.toggle(Overlay::Diagnostics);
+                false
+            }
+            KeyCode::Char('A') => {
+                self.overlays.toggle(Overlay::Audit);
+                false
+            }
+            KeyCode::Char('i') => {
+                self.overlays.toggle(Overlay::About);
+                false
+            }
+            KeyCode::Char('M') => {
+                let muted = self.sounds.toggle_mute();
+                self.set_status(if muted { "Alert sounds muted" } else { "Alert sounds on" });
+                false
+            }
+            KeyCode::Char('b') => {
+                self.toggle_reference();
+                false
+            }
+            KeyCode::Char('B') => {
+                self.cycle_backend_filter();
+                false
+            }
+            KeyCode::Char('E') => {
+                self.toggle_task_diff();
+                false
+            }
+            KeyCode::Char('O') => {
+                self.open_external_diff();
+                false
+            }
+            KeyCode::Char('W') => {
+                self.show_workflow = !self.show_workflow;
+                false
+            }
+            KeyCode::Char('J') => {
+                self.show_raw_json = !self.show_raw_json;
+                self.show_chart = false;
+                self.json_scroll = 0;
+                false
+            }
+            KeyCode::Char('C') => {
+                self.show_chart = !self.show_chart;
+                self.show_raw_json = false;
+                self.chart.cursor.clear();
+                false
+            }
+            KeyCode::Left if self.show_chart => {
+                self.move_chart_cursor(true);
+                false
+            }
+            KeyCode::Right if self.show_chart => {
+                self.move_chart_cursor(false);
+                false
+            }
+            KeyCode::Char('l') if self.show_chart => {
+                self.chart.memory_scale = self.chart.memory_scale.toggled();
+                self.set_status(format!("Memory chart scale: {}", self.chart.memory_scale));
+                false
+            }
+            KeyCode::Char(c @ '1'..='9') if self.show_chart => {
+                if let Some(&series) = ChartSeries::ALL.get(c as usize - '1' as usize) {
+                    if !self.capabilities.series(series) {
+                        self.set_status(format!("{} is not reported by this data source", series.name()));
+                        return false;
+                    }
+                    let state = if self.chart.toggle(series) { "shown" } else { "hidden" };
+                    self.set_status(format!("{} series {}", series.name(), state));
+                }
+                false
+            }
+            KeyCode::PageDown if self.current_tab() == Tab::Logs => {
+                self.scroll_log_pane(false);
+                false
+            }
+            KeyCode::PageUp if self.current_tab() == Tab::Logs => {
+                self.scroll_log_pane(true);
+                false
+            }
+            KeyCode::PageDown if self.show_raw_json => {
+                self.json_scroll = self.json_scroll.saturating_add(10);
+                false
+            }
+            KeyCode::PageUp if self.show_raw_json => {
+                self.json_scroll = self.json_scroll.saturating_sub(10);
+                false
+            }
+            KeyCode::Char('y') if self.show_raw_json => {
+                self.yank_raw_json();
+                false
+            }
+            KeyCode::Char('T') => {
+                self.pending_table = Some(crate::table::render(self));
+                false
+            }
+            KeyCode::Char('S') => {
+                self.write_snapshot();
+                false
+            }
+            KeyCode::Char('x') if self.available_update.is_some() => {
+                self.available_update = None;
+                false
+            }
+            KeyCode::Char('G') => {
+                self.export_graph();
+                false
+            }
+            KeyCode::Char('[') => {
+                self.scrub_back();
+                false
+            }
+            KeyCode::Char(']') => {
+                self.scrub_forward();
+                false
+            }
+            KeyCode::Char('m') => {
+                self.bookmark_note = Some(String::new());
+                false
+            }
+            KeyCode::Char('o') => {
+                self.sort_menu = Some(0);
+                self.overlays.open(Overlay::SortMenu);
+                false
+            }
+            KeyCode::Char('?') => {
+                self.glossary = Some(0);
+                self.overlays.open(Overlay::Glossary);
+                false
+            }
+            KeyCode::Char('t') if !self.capabilities.has_metrics() => {
+                self.set_status("Top mode unavailable: this data source reports no CPU or memory use");
+                false
+            }
+            KeyCode::Char('t') => {
+                self.top = TopMetric::cycle(self.top);
+                while self.top.is_some_and(|metric| !self.capabilities.top(metric)) {
+                    self.top = TopMetric::cycle(self.top);
+                }
+                match self.top {
+                    Some(metric) => self.set_status(format!("Top mode: busiest {} first", metric.name())),
+                    None => self.set_status(format!("Top mode off; sorted by {}", self.sort)),
+                }
+                false
+            }
+            KeyCode::Char('g') => {
+                self.grouping.enabled = !self.grouping.enabled;
+                let state = if self.grouping.enabled { "grouped by workflow" } else { "ungrouped" };
+                self.set_status(format!("Task list {}", state));
+                false
+            }
+            KeyCode::Char('z') if self.grouping.enabled => {
+                self.toggle_selected_group();
+                false
+            }
+            KeyCode::Char('p') => {
+                self.toggle_pin();
+                false
+            }
+            KeyCode::Char('r') if self.current_tab() == Tab::Tasks => {
+                self.start_rename();
+                false
+            }
+            KeyCode::Char('a') => {
+                self.archive_selected();
+                false
+            }
+            KeyCode::Char('u') => {
+                self.undo();
+                false
+            }
+            KeyCode::Char('d') => {
+                self.toggle_dry_run();
+                false
+            }
+            KeyCode::Char('c') => {
+                self.cancel_selected();
+                false
+            }
+            KeyCode::Char('R') => {
+                if let Some(id) = self.selected_task_id.clone() {
+                    self.control(TaskAction::Retry { id });
+                }
+                false
+            }
+            KeyCode::Char(c @ ('<' | '>')) => {
+                if let Some(id) = self.selected_task_id.clone() {
+                    self.control(TaskAction::Prioritize { id, raise: c == '<' });
+                }
+                false
+            }
+            KeyCode::Char('v') if self.current_tab() == Tab::Timeline => {
+                self.timeline_lanes = self.timeline_lanes.next(self.tasks.values());
+                self.set_status(format!("Timeline lanes: {}", self.timeline_lanes));
+                false
+            }
+            KeyCode::Char('s') => {
+                self.start_save_log();
+                false
+            }
+            KeyCode::Char('+') if self.current_tab() == Tab::Logs => {
+                self.open_log_pane();
+                false
+            }
+            KeyCode::Char('-') if self.current_tab() == Tab::Logs => {
+                self.log_panes.close_focused();
+                false
+            }
+            KeyCode::Char('f') if self.current_tab() == Tab::Logs => {
+                self.log_panes.focus_next();
+                false
+            }
+            KeyCode::Char('F') if self.current_tab() == Tab::Logs => {
+                self.toggle_log_follow();
+                false
+            }
+            KeyCode::Char('e') if self.current_tab() == Tab::Logs => {
+                self.expand_structured_logs = !self.expand_structured_logs;
+                let state = if self.expand_structured_logs { "expanded" } else { "summarised" };
+                self.set_status(format!("JSON log lines {}", state));
+                false
+            }
+            KeyCode::Char('L') if self.current_tab() == Tab::Logs => {
+                self.load_full_log();
+                false
+            }
+            KeyCode::Char('f') if self.current_tab() == Tab::Events => {
+                let listed = match self.feed.cycle_filter() {
+                    Some(kind) => format!("Showing {} events", kind),
+                    None => "Showing every event".to_string(),
+                };
+                self.set_status(listed);
+                false
+            }
+            KeyCode::Enter if self.current_tab() == Tab::Events => {
+                self.jump_to_event_task();
+                false
+            }
+            KeyCode::Down if self.current_tab() == Tab::Events => {
+                self.feed.select_older();
+                false
+            }
+            KeyCode::Up if self.current_tab() == Tab::Events => {
+                self.feed.select_newer();
+                false
+            }
+            KeyCode::Down => {
+                self.next_task();
+                false
+            }
+            KeyCode::Up => {
+                self.previous_task();
+                false
+            }
+            _ => false,
+        }
+    }
+    
+    /// Selects the task the selected event is about and shows it in the
+    /// task list
+    fn jump_to_event_task(&mut self) {
+        let Some(id) = self.feed.selected().and_then(|event| event.task_id.clone()) else {
+            self.set_status("This event is not about a task");
+            return;
+        };
+        if self.select_task(&id) {
+            self.set_tab(Tab::Tasks);
+        } else {
+            self.set_status(format!("Task {} is no longer in the store", id));
+        }
+    }
+
+    /// Handles a key while a bookmark's note is being typed
+    fn handle_bookmark_key(&mut self, key: KeyEvent) {
+        let Some(text) = self.bookmark_note.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char(c) => text.push(c),
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Esc => self.bookmark_note = None,
+            KeyCode::Enter => {
+                let note = self.bookmark_note.take().unwrap_or_default();
+                self.bookmark(note.trim());
+            }
+            _ => {}
+        }
+    }
+
+    /// Bookmarks the current moment with `note`, which may be empty
+    ///
+    /// While an older copy of the store is shown, the bookmark goes to that
+    /// copy instead. Otherwise it is also written to the recording, if one
+    /// is being made.
+    pub fn bookmark(&mut self, note: &str) {
+        let at = match self.scrub.as_ref().map(|scrub| scrub.index) {
+            Some(index) => self.history.bookmark_copy(index, note).unwrap_or_else(record::unix_now),
+            None => {
+                self.history.bookmark(note);
+                self.bus.publish(StateEvent::Bookmarked { note: note.to_string() });
+                record::unix_now()
+            }
+        };
+        self.feed.push(at, EventKind::Bookmark, None, bookmark_text(note));
+        self.set_status(format!("Bookmarked {}", crate::format::clock(at)));
+    }
+
+    /// Returns `true` while an older copy of the store is shown
+    pub fn is_viewing_history(&self) -> bool {
+        self.scrub.is_some()
+    }
+
+    /// Returns the copy of the store being shown, if not live
+    pub fn viewed_snapshot(&self) -> Option<&StoreSnapshot> {
+        self.scrub.as_ref().and_then(|scrub| self.history.get(scrub.index))
+    }
+
+    /// Shows the next older copy of the store
+    fn scrub_back(&mut self) {
+        let index = match &self.scrub {
+            Some(scrub) => scrub.index.saturating_sub(1),
+            None if self.history.is_empty() => {
+                self.set_status("No history yet");
+                return;
+            }
+            None => {
+                self.scrub = Some(Scrub {
+                    index: self.history.len() - 1,
+                    tasks: std::mem::take(&mut self.tasks),
+                    task_ids: std::mem::take(&mut self.task_ids),
+                    held: Vec::new(),
+                });
+                self.history.len() - 1
+            }
+        };
+        self.show_snapshot(index);
+    }
+
+    /// Shows the next newer copy of the store, or returns to live after the
+    /// newest one
+    fn scrub_forward(&mut self) {
+        let Some(scrub) = &self.scrub else {
+            return;
+        };
+        if scrub.index + 1 < self.history.len() {
+            self.show_snapshot(scrub.index + 1);
+        } else {
+            self.return_to_live();
+        }
+    }
+
+    /// Returns a number that changes whenever the tasks change, other than
+    /// by new log lines, for keeping what is worked out from them.
+    pub fn store_version(&self) -> u64 {
+        self.store_version
+    }
+
+    /// Replaces the shown store with a copy from the history
+    fn show_snapshot(&mut self, index: usize) {
+        let Some(snapshot) = self.history.get(index) else {
+            return;
+        };
+        self.task_ids = snapshot.tasks.iter().map(|task| task.id.clone()).collect();
+        self.tasks = snapshot.tasks.iter().map(|task| (task.id.clone(), task.clone())).collect();
+        self.store_version += 1;
+        if let Some(scrub) = self.scrub.as_mut() {
+            scrub.index = index;
+        }
+    }
+
+    /// Restores the live store and applies updates held in the meantime
+    pub fn return_to_live(&mut self) {
+        let Some(scrub) = self.scrub.take() else {
+            return;
+        };
+        self.tasks = scrub.tasks;
+        self.task_ids = scrub.task_ids;
+        self.store_version += 1;
+        for update in scrub.held {
+            self.apply_update(update);
+        }
+    }
+
+    /// Updates the application state
+    ///
+    /// While history is shown, sources are not polled and nothing is
+    /// recorded; polling resumes on return to live.
+    pub fn update(&mut self) {
+        self.tick_count = self.tick_count.wrapping_add(1);
+        if self.blank_after.is_some_and(|after| self.last_input.elapsed() >= after) {
+            self.blanked = true;
+        }
+
+        #[cfg(unix)]
+        if let Some(control) = &self.control {
+            for command in control.drain() {
+                self.apply_control(command);
+            }
+        }
+
+        if let Some(result) = self.update_check.as_ref().and_then(UpdateCheck::poll) {
+            self.update_check = None;
+            match result {
+                Ok(release) => self.available_update = release,
+                Err(err) => crate::crash::log(format!("update check failed: {:#}", err)),
+            }
+        }
+
+        self.track_log_download();
+
+        if self.is_viewing_history() {
+            return;
+        }
+
+        self.hydrate_logs();
+
+        self.poll_source();
+        self.prune_finished();
+        let churn = std::mem::take(&mut self.pending_churn);
+        self.perf.record(churn, self.tasks.len());
+        self.progress.observe(&self.tasks);
+        self.sparklines.observe(&self.tasks);
+        self.metrics.observe(&self.tasks, self.selected_task_id.as_deref());
+        match self.sounds.reload() {
+            Some(Ok(())) => self.set_status("Reloaded alert sounds from the configuration file"),
+            Some(Err(err)) => self.set_status(format!("Cannot reload alert sounds: {:#}", err)),
+            None => {}
+        }
+        let raised = self.alerts.observe(&self.tasks, Instant::now());
+        for alert in &raised {
+            let text = format!("{} {}: {}", alert.rule.severity, alert.rule.name, alert.describe(&self.numbers));
+            self.feed.push(alert.since, EventKind::Alert, Some(alert.task_id.clone()), text);
+        }
+        if let Some(severity) = raised.iter().map(|alert| alert.rule.severity).max() {
+            self.sounds.ring(severity);
+        }
+        if let Some(alert) = raised.first() {
+            let more = match raised.len() {
+                1 => String::new(),
+                n => format!(" (and {} more)", n - 1),
+            };
+            self.set_status(format!(
+                "{} on {}: {}, {}{}",
+                alert.rule.severity,
+                alert.task_id,
+                alert.rule.name,
+                alert.describe(&self.numbers),
+                more
+            ));
+        }
+        let flagged = self.anomalies.observe(&self.tasks, &self.retention.pruned().durations, record::unix_now());
+        if let Some(anomaly) = flagged.first().filter(|_| self.anomalies.config().alert) {
+            let more = match flagged.len() {
+                1 => String::new(),
+                n => format!(" (and {} more)", n - 1),
+            };
+            self.set_status(format!(
+                "{} has run {}× the median of {} ({}){}",
+                anomaly.task_id,
+                self.numbers.decimal(anomaly.ratio(), 1),
+                anomaly.step,
+                self.numbers.duration(Duration::from_secs(anomaly.median)),
+                more
+            ));
+        }
+        self.history.capture(self.task_ids.iter().filter_map(|id| self.tasks.get(id)));
+        self.check_capacity();
+        self.check_error_budget();
+
+        let mut bus = std::mem::take(&mut self.bus);
+        for (name, err) in bus.dispatch(self) {
+            self.set_status(format!("{} stopped: {:#}", name, err));
+        }
+        self.bus = bus;
+    }
+
+    /// Warns when more pending tasks than before request more than any node
+    /// provides, since they would otherwise wait forever without an error
+    fn check_capacity(&mut self) {
+        if !self.capacity.is_known() {
+            return;
+        }
+        let never_run = self
+            .tasks
+            .values()
+            .filter(|task| task.status == TaskStatus::Pending && self.capacity.can_never_run(task))
+            .count();
+        if never_run > self.never_run {
+            self.set_status(format!(
+                "{} pending task(s) request more than any node provides and can never start; see Statistics",
+                self.numbers.integer(never_run as u64)
+            ));
+        }
+        self.never_run = never_run;
+    }
+
+    /// Warns once when failures exceed the error budget
+    fn check_error_budget(&mut self) {
+        let Some(budget) = self.slo.budget(&self.status_counts()) else {
+            return;
+        };
+        let exhausted = budget.is_exhausted();
+        if exhausted && !self.budget_exhausted {
+            self.set_status(format!(
+                "Error budget exhausted: {} failed, {} allowed",
+                self.numbers.integer(budget.failed as u64),
+                self.numbers.decimal(budget.allowed, 1)
+            ));
+        }
+        self.budget_exhausted = exhausted;
+    }
+
+    /// Applies a change to the task store, returning `false` if it refers
+    /// to a task that is not present
+    ///
+    /// Updates arriving while history is shown are held and applied on
+    /// return to live.
+    pub fn apply_update(&mut self, update: TaskUpdate) -> bool {
+        if let Some(scrub) = self.scrub.as_mut() {
+            scrub.held.push(update);
+            return true;
+        }
+        let now = record::unix_now();
+        if !matches!(update, TaskUpdate::LogLine { .. }) {
+            self.store_version += 1;
+        }
+        match update {
+            TaskUpdate::Created(task) => {
+                let mut task = *task;
+                task.last_updated = Some(now);
+                match self.tasks.get_mut(&task.id) {
+                    Some(existing) => {
+                        if existing.status != task.status {
+                            record_status(&mut self.feed, &task.id, existing.status, task.status);
+                            self.bus.publish(StateEvent::StatusChanged {
+                                id: task.id.clone(),
+                                from: existing.status,
+                                to: task.status,
+                            });
+                        }
+                        if let Some(factor) = self.smooth_progress {
+                            let reported = task.progress;
+                            task.progress = progress::smooth(existing.progress, reported, factor);
+                            task.reported_progress = (task.progress != reported).then_some(reported);
+                        }
+                        if existing.progress != task.progress {
+                            self.bus.publish(StateEvent::ProgressChanged { id: task.id.clone() });
+                        }
+                        task.logs = std::mem::take(&mut existing.logs);
+                        *existing = task;
+                        self.pending_churn.updated += 1;
+                    }
+                    None if self.retention.is_pruned(&task) => return false,
+                    None => {
+                        self.bus.publish(StateEvent::TaskAdded { id: task.id.clone() });
+                        task.logs.set_limits(self.log_limits);
+                        self.task_ids.push(task.id.clone());
+                        self.tasks.insert(task.id.clone(), task);
+                        self.pending_churn.added += 1;
+                    }
+                }
+                return true;
+            }
+            TaskUpdate::Removed { id } => {
+                if self.tasks.remove(&id).is_none() {
+                    return false;
+                }
+                self.task_ids.retain(|existing| existing != &id);
+                self.recent_logs.retain(|recent| recent != &id);
+                self.pinned.remove(&id);
+                self.archived.remove(&id);
+                self.pending_churn.removed += 1;
+                self.bus.publish(StateEvent::TaskRemoved { id });
+                return true;
+            }
+            TaskUpdate::StatusChanged { id, status } => {
+                let Some(task) = self.tasks.get_mut(&id) else {
+                    return false;
+                };
+                task.last_updated = Some(now);
+                if task.status != status {
+                    // Queue positions only mean something while pending
+                    if status != TaskStatus::Pending {
+                        task.queue = None;
+                    }
+                    match status {
+                        TaskStatus::Running => {
+                            task.started_at.get_or_insert(now);
+                        }
+                        TaskStatus::Completed | TaskStatus::Failed => {
+                            task.started_at.get_or_insert(now);
+                            task.finished_at.get_or_insert(now);
+                        }
+                        TaskStatus::Pending => {}
+                    }
+                    record_status(&mut self.feed, &id, task.status, status);
+                    self.bus.publish(StateEvent::StatusChanged { id, from: task.status, to: status });
+                    task.status = status;
+                }
+            }
+            TaskUpdate::Progress { id, progress } => {
+                let Some(task) = self.tasks.get_mut(&id) else {
+                    return false;
+                };
+                task.last_updated = Some(now);
+                let reported = progress.clamp(0.0, 1.0);
+                task.progress = match self.smooth_progress {
+                    Some(factor) => progress::smooth(task.progress, reported, factor),
+                    None => reported,
+                };
+                task.reported_progress = (task.progress != reported).then_some(reported);
+                self.bus.publish(StateEvent::ProgressChanged { id });
+            }
+            TaskUpdate::Metrics { id, cpu_usage, memory_usage } => {
+                let Some(task) = self.tasks.get_mut(&id) else {
+                    return false;
+                };
+                task.last_updated = Some(now);
+                if let Some(cpu_usage) = cpu_usage {
+                    task.cpu_usage = cpu_usage;
+                }
+                if let Some(memory_usage) = memory_usage {
+                    task.memory_usage = memory_usage;
+                }
+                self.bus.publish(StateEvent::MetricsChanged { id });
+            }
+            TaskUpdate::Queue { id, queue } => {
+                let Some(task) = self.tasks.get_mut(&id) else {
+                    return false;
+                };
+                task.last_updated = Some(now);
+                task.queue = queue;
+            }
+            TaskUpdate::LogLine { id, line } => {
+                let Some(task) = self.tasks.get_mut(&id) else {
+                    return false;
+                };
+                task.last_updated = Some(now);
+                if !self.bus.is_empty() {
+                    self.bus.publish(StateEvent::LogLine { id, line: line.clone() });
+                }
+                task.logs.push(line);
+            }
+        }
+        self.pending_churn.updated += 1;
+        true
+    }
+
+    /// Prunes the finished tasks older than the retention age, except
+    /// pinned tasks and the selected one
+    fn prune_finished(&mut self) {
+        let candidates = self
+            .tasks
+            .values()
+            .filter(|task| !self.pinned.contains(&task.id) && self.selected_task_id.as_ref() != Some(&task.id));
+        for id in self.retention.expired(candidates, record::unix_now()) {
+            if let Some(task) = self.tasks.get(&id) {
+                self.retention.record(task);
+            }
+            self.apply_update(TaskUpdate::Removed { id });
+        }
+    }
+
+    /// Applies what the data source reported since the last tick
+    fn poll_source(&mut self) {
+        let updates = self.source.poll();
+        let now = record::unix_now();
+        if !updates.is_empty() {
+            self.health.record_success(None);
+            self.connections.record_updates(self.source.name(), now);
+        }
+        for update in updates {
+            self.apply_update(update);
+        }
+        for event in self.source.events() {
+            // Events relayed from a combined source are told apart by origin
+            let (origin, event) = match event {
+                SourceEvent::Relayed { source, event } => (source, *event),
+                event => (self.source.name().to_string(), event),
+            };
+            self.connections.record(&origin, &event, now);
+            match event {
+                SourceEvent::Connected { latency } => self.health.record_success(Some(latency)),
+                SourceEvent::Message(message) => self.set_status(message),
+                SourceEvent::Bookmark(note) => {
+                    self.feed.push(now, EventKind::Bookmark, None, bookmark_text(&note));
+                    self.history.bookmark(note);
+                }
+                SourceEvent::Dropped(detail) => {
+                    self.health.record_dropped(1);
+                    crate::crash::log(format!("undecodable {} update: {}", origin, detail));
+                }
+                SourceEvent::Malformed { kind, detail } => {
+                    self.health.record_malformed(kind);
+                    crate::crash::log(format!("malformed {} update: {}", origin, detail));
+                }
+                SourceEvent::Received(_) | SourceEvent::Relayed { .. } => {}
+                SourceEvent::Coalesced(count) => self.health.record_coalesced(count),
+                SourceEvent::Failed(reason) => {
+                    self.health.record_error(reason.clone());
+                    #[cfg(feature = "telemetry")]
+                    if let Some(telemetry) = &mut self.telemetry {
+                        telemetry.connector_error();
+                    }
+                    self.feed.push(now, EventKind::Backend, None, format!("{}: {}", origin, reason));
+                    self.set_status(reason);
+                }
+                SourceEvent::Reconnecting { reason, attempt, delay } => {
+                    crate::crash::log(format!("{} (attempt {} in {:?})", reason, attempt, delay));
+                    self.feed.push(
+                        now,
+                        EventKind::Backend,
+                        None,
+                        format!("{}: {}; retrying in {} (attempt {})", origin, reason, self.numbers.duration(delay), attempt),
+                    );
+                    self.health.record_reconnecting(reason, attempt, delay);
+                    #[cfg(feature = "telemetry")]
+                    if let Some(telemetry) = &mut self.telemetry {
+                        telemetry.connector_error();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Applies finished log fetches and keeps the viewed task's log streaming
+    fn hydrate_logs(&mut self) {
+        let viewing_logs = self.current_tab() == Tab::Logs;
+        let Some(fetcher) = self.log_fetcher.as_mut() else {
+            return;
+        };
+
+        for (task_id, result) in fetcher.drain() {
+            if let Some(task) = self.tasks.get_mut(&task_id) {
+                match result {
+                    Ok(chunk) => task.logs.apply(chunk),
+                    Err(err) => task.logs.set_error(format!("{:#}", err)),
+                }
+            }
+        }
+
+        // Only the tasks on screen are streamed
+        if !viewing_logs {
+            return;
+        }
+        let ids: Vec<String> = if self.log_panes.is_empty() {
+            self.selected_task_id.iter().cloned().collect()
+        } else {
+            self.log_panes.panes().iter().map(|pane| pane.task_id.clone()).collect()
+        };
+        for id in ids {
+            let Some(task) = self.tasks.get(&id) else {
+                continue;
+            };
+            let range = if task.logs.is_hydrated() {
+                LogRange::From(task.logs.next_index())
+            } else {
+                LogRange::Tail(INITIAL_TAIL_LINES)
+            };
+            if let Some(fetcher) = self.log_fetcher.as_mut() {
+                fetcher.request(&id, range);
+            }
+            self.touch_recent_log(id);
+        }
+    }
+
+    /// Returns the task whose log the Logs tab's keys act on: the focused
+    /// pane's if any is open, otherwise the selected task's
+    fn focused_log_task(&self) -> Option<String> {
+        match self.log_panes.focused() {
+            Some(pane) => Some(pane.task_id.clone()),
+            None => self.selected_task_id.clone(),
+        }
+    }
+
+    /// Opens the selected task's log in a new pane
+    fn open_log_pane(&mut self) {
+        let Some(id) = self.selected_task_id.clone() else {
+            return;
+        };
+        if !self.log_panes.open(&id) {
+            self.set_status(format!("At most {} logs can be open; close one with -", MAX_PANES));
+        }
+    }
+
+    /// Pauses the focused pane at the current end of its log, or follows
+    /// the log again
+    fn toggle_log_follow(&mut self) {
+        let Some(id) = self.focused_log_task() else {
+            return;
+        };
+        let next = self.tasks.get(&id).map_or(0, |task| task.logs.next_index());
+        let Some(pane) = self.log_panes.focused_mut() else {
+            return;
+        };
+        pane.toggle_follow(next);
+        let following = pane.is_following();
+        let state = if following { "Following" } else { "Paused" };
+        self.set_status(format!("{} the log of {}", state, id));
+    }
+
+    /// Scrolls the focused pane by a page
+    fn scroll_log_pane(&mut self, up: bool) {
+        let Some(task) = self.log_panes.focused().and_then(|pane| self.tasks.get(&pane.task_id)) else {
+            return;
+        };
+        let (first, next) = (task.logs.omitted(), task.logs.next_index());
+        if let Some(pane) = self.log_panes.focused_mut() {
+            if up {
+                pane.scroll_up(LOG_SCROLL_LINES, first, next);
+            } else {
+                pane.scroll_down(LOG_SCROLL_LINES, next);
+            }
+        }
+    }
+
+    /// Fetches the focused pane's log, or the selected task's, from the
+    /// beginning
+    fn load_full_log(&mut self) {
+        let id = self.focused_log_task();
+        let (Some(fetcher), Some(id)) = (self.log_fetcher.as_mut(), id) else {
+            return;
+        };
+        if fetcher.request(&id, LogRange::From(0)) {
+            self.touch_recent_log(id);
+        }
+    }
+
+    /// Starts typing the path to save the selected task's full log to,
+    /// beginning with `<task-id>.log` in the working directory
+    fn start_save_log(&mut self) {
+        if self.log_download.as_ref().is_some_and(LogDownload::is_running) {
+            self.set_status("A log is already being saved");
+            return;
+        }
+        if let Some(id) = &self.selected_task_id {
+            self.save_log_path = Some(format!("{}.log", id));
+        }
+    }
+
+    /// Handles a key while the path to save a log to is being typed
+    fn handle_save_log_key(&mut self, key: KeyEvent) {
+        let Some(text) = self.save_log_path.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char(c) => text.push(c),
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Esc => self.save_log_path = None,
+            KeyCode::Enter => {
+                let path = self.save_log_path.take().unwrap_or_default();
+                let path = path.trim();
+                if !path.is_empty() {
+                    self.save_log(PathBuf::from(path));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Saves the selected task's full log to `path` in the background,
+    /// reusing its cached lines if they start at the beginning of the log
+    fn save_log(&mut self, path: PathBuf) {
+        let Some(id) = self.selected_task_id.clone() else {
+            return;
+        };
+        let Some(fetcher) = self.log_fetcher.as_ref() else {
+            self.set_status("No log provider to fetch the log from");
+            return;
+        };
+        let cached = self
+            .tasks
+            .get(&id)
+            .map(|task| &task.logs)
+            .filter(|logs| logs.is_hydrated() && logs.omitted() == 0)
+            .map(|logs| logs.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        fetcher.download(&id, cached, path.clone());
+        self.log_download = Some(LogDownload {
+            task_id: id,
+            path,
+            lines: 0,
+            bytes: 0,
+            state: DownloadState::Running,
+        });
+    }
+
+    /// Applies progress reported by the log download, reporting and
+    /// auditing it once it ends
+    fn track_log_download(&mut self) {
+        let Some(fetcher) = self.log_fetcher.as_mut() else {
+            return;
+        };
+        for progress in fetcher.downloads() {
+            let action = format!("save log of {} to {}", progress.task_id, progress.path.display());
+            match &progress.state {
+                DownloadState::Running => {}
+                DownloadState::Finished => {
+                    self.set_status(format!(
+                        "Saved {} lines of {}'s log to {}",
+                        self.numbers.integer(progress.lines as u64),
+                        progress.task_id,
+                        progress.path.display()
+                    ));
+                    self.record_action(action, Outcome::Done, None);
+                }
+                DownloadState::Failed(err) => {
+                    self.set_status(format!("Cannot save {}'s log: {}", progress.task_id, err));
+                    self.record_action(action, Outcome::Failed, Some(err.clone()));
+                }
+            }
+            self.log_download = Some(progress);
+        }
+    }
+
+    /// Marks a task's log as recently viewed, dropping the least recently
+    /// viewed log from memory when the cache is full
+    fn touch_recent_log(&mut self, id: String) {
+        if self.recent_logs.front() == Some(&id) {
+            return;
+        }
+        self.recent_logs.retain(|recent| recent != &id);
+        self.recent_logs.push_front(id);
+
+        while self.recent_logs.len() > RECENT_LOG_CACHE {
+            if let Some(evicted) = self.recent_logs.pop_back() {
+                if let Some(task) = self.tasks.get_mut(&evicted) {
+                    task.logs.clear();
+                }
+            }
+        }
+    }
+
+    /// Selects the next task in the list
+    fn next_task(&mut self) {
+        let ids = self.visible_task_ids();
+        if ids.is_empty() {
+            return;
+        }
+        
+        let current_index = match &self.selected_task_id {
+            Some(id) => ids.iter().position(|x| *x == id).unwrap_or(0),
+            None => 0,
+        };
+        
+        let next_index = (current_index + 1) % ids.len();
+        self.selected_task_id = Some(ids[next_index].clone());
+        self.json_scroll = 0;
+        self.chart.cursor.clear();
+    }
+    
+    /// Selects the previous task in the list
+    fn previous_task(&mut self) {
+        let ids = self.visible_task_ids();
+        if ids.is_empty() {
+            return;
+        }
+        
+        let current_index = match &self.selected_task_id {
+            Some(id) => ids.iter().position(|x| *x == id).unwrap_or(0),
+            None => 0,
+        };
+        
+        let previous_index = if current_index == 0 {
+            ids.len() - 1
+        } else {
+            current_index - 1
+        };
+        
+        self.selected_task_id = Some(ids[previous_index].clone());
+        self.json_scroll = 0;
+        self.chart.cursor.clear();
+    }
+}
+
+/// Records a task's move between statuses in the event feed, if it is
+/// notable
+fn record_status(feed: &mut EventFeed, id: &str, from: TaskStatus, to: TaskStatus) {
+    let Some(kind) = EventKind::of_status(to) else {
+        return;
+    };
+    let text = match (from, to) {
+        (TaskStatus::Completed | TaskStatus::Failed, TaskStatus::Running) => "started again",
+        (_, TaskStatus::Running) => "started running",
+        (_, TaskStatus::Completed) => "completed",
+        _ => "failed",
+    };
+    feed.push(record::unix_now(), kind, Some(id.to_string()), text);
+}
+
+/// Returns how a bookmark with `note` reads in the event feed
+fn bookmark_text(note: &str) -> &str {
+    if note.is_empty() {
+        "bookmarked"
+    } else {
+        note
+    }
+}
@@ -0,0 +1,148 @@
+//! Audit trail of actions taken from the TUI.
+//!
+//! In shared operations every action someone takes, and whether it went
+//! through, should be traceable afterwards. Each action is kept in memory
+//! for the audit overlay and, if configured, appended to a file as one JSON
+//! object per line:
+//!
+//! ```toml
+//! [audit]
+//! file = "/var/log/crankshaft-audit.jsonl"
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::record::unix_now;
+
+/// Number of entries kept for the overlay.
+pub const MAX_ENTRIES: usize = 500;
+
+/// Options for the audit trail.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuditConfig {
+    /// Append every action to this file
+    pub file: Option<PathBuf>,
+}
+
+/// What became of an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// The action ran
+    Done,
+    /// The action ran but changed nothing
+    Unchanged,
+    /// The user declined to confirm it
+    Declined,
+    /// The action was not allowed in the current state
+    Refused,
+    /// The action ran and failed
+    Failed,
+    /// Dry-run mode was on, so the action was not sent
+    DryRun,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Done => write!(f, "done"),
+            Outcome::Unchanged => write!(f, "unchanged"),
+            Outcome::Declined => write!(f, "declined"),
+            Outcome::Refused => write!(f, "refused"),
+            Outcome::Failed => write!(f, "failed"),
+            Outcome::DryRun => write!(f, "dry run"),
+        }
+    }
+}
+
+/// One recorded action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the action was taken, in seconds since the Unix epoch
+    pub at: u64,
+    /// Login name of the user running the TUI
+    pub user: String,
+    /// What was done, e.g. `archive task-3`
+    pub action: String,
+    /// What became of it
+    pub outcome: Outcome,
+    /// Why it was refused or failed, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Recent actions, and the file they are appended to.
+#[derive(Debug)]
+pub struct AuditLog {
+    /// Login name recorded with every entry
+    user: String,
+    /// Most recent entries, oldest first
+    entries: VecDeque<AuditEntry>,
+    /// Audit file, if one is configured
+    file: Option<File>,
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self {
+            user: current_user(),
+            entries: VecDeque::new(),
+            file: None,
+        }
+    }
+}
+
+impl AuditLog {
+    /// Appends entries to `path` from now on, creating it if needed.
+    pub fn open(&mut self, path: &Path) -> io::Result<()> {
+        self.file = Some(OpenOptions::new().create(true).append(true).open(path)?);
+        Ok(())
+    }
+
+    /// Records an action, writing it to the audit file if there is one.
+    ///
+    /// The entry is kept for the overlay even if writing it fails.
+    pub fn record(&mut self, action: impl Into<String>, outcome: Outcome, detail: Option<String>) -> io::Result<()> {
+        let entry = AuditEntry {
+            at: unix_now(),
+            user: self.user.clone(),
+            action: action.into(),
+            outcome,
+            detail,
+        };
+        let written = match &mut self.file {
+            Some(file) => writeln!(file, "{}", serde_json::to_string(&entry)?).and_then(|()| file.flush()),
+            None => Ok(()),
+        };
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+        written
+    }
+
+    /// Returns the recorded entries, most recent first.
+    pub fn recent(&self) -> impl Iterator<Item = &AuditEntry> {
+        self.entries.iter().rev()
+    }
+
+    /// Returns `true` if an audit file is being written.
+    pub fn is_persistent(&self) -> bool {
+        self.file.is_some()
+    }
+}
+
+/// Returns the login name of the current user, or `unknown`.
+fn current_user() -> String {
+    ["USER", "USERNAME", "LOGNAME"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|user| !user.is_empty()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
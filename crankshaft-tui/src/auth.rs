@@ -0,0 +1,160 @@
+//! Credentials for remote sources.
+//!
+//! A source behind an authenticating proxy or a monitoring endpoint that
+//! needs a bearer token is given one per source, in the `[auth]` section of
+//! the configuration file:
+//!
+//! ```toml
+//! [auth.engine]
+//! token_env = "ENGINE_MONITOR_TOKEN"
+//!
+//! [auth.tes]
+//! token_file = "/run/secrets/tes-token"
+//! ```
+//!
+//! A token can also be written into the file with `token`, though keeping it
+//! in an environment variable or a file of its own keeps it out of the
+//! configuration. Without a section, a source's token is read from
+//! `CRANKSHAFT_TUI_<SOURCE>_TOKEN`, such as `CRANKSHAFT_TUI_ENGINE_TOKEN`,
+//! if it is set. Tokens are sent in an `Authorization: Bearer` header.
+//!
+//! When the server answers `401 Unauthorized` or `403 Forbidden`, the source
+//! stops and the dashboard says whether the token was missing or refused,
+//! since trying again with the same credentials would fail the same way.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use eyre::{eyre, WrapErr};
+use serde::Deserialize;
+
+use crate::tls::Tls;
+
+/// Names of the sources that send a token.
+pub const SOURCES: &[&str] = &["engine", "websocket", "sse", "tes", "k8s"];
+
+/// Where one source's token comes from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Credentials {
+    /// The token itself
+    pub token: Option<String>,
+    /// Environment variable holding the token
+    pub token_env: Option<String>,
+    /// File holding the token, such as a mounted secret
+    pub token_file: Option<PathBuf>,
+}
+
+impl Credentials {
+    /// Reads the token, from the environment variable, the file, or the
+    /// configuration, in that order.
+    fn token(&self) -> eyre::Result<Option<String>> {
+        if let Some(name) = &self.token_env {
+            let token = std::env::var(name).wrap_err_with(|| format!("environment variable `{}` is not set", name))?;
+            return Ok(Some(token.trim().to_string()));
+        }
+        if let Some(path) = &self.token_file {
+            let token = std::fs::read_to_string(path).wrap_err_with(|| format!("failed to read `{}`", path.display()))?;
+            return Ok(Some(token.trim().to_string()));
+        }
+        Ok(self.token.clone())
+    }
+}
+
+/// What a remote source is let in with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteAccess {
+    /// Bearer token sent with each request, if the server needs one
+    pub token: Option<String>,
+    /// Certificates trusted and presented over HTTPS
+    pub tls: Tls,
+}
+
+/// Credentials of each source, by the source's name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct AuthConfig {
+    /// Credentials by source name, such as `engine`
+    pub sources: BTreeMap<String, Credentials>,
+}
+
+impl AuthConfig {
+    /// Reads the token of every source that has one, by source name.
+    pub fn tokens(&self) -> eyre::Result<BTreeMap<String, String>> {
+        if let Some(name) = self.sources.keys().find(|name| !SOURCES.contains(&name.as_str())) {
+            return Err(eyre!("`[auth.{}]` is not a source that sends a token; expected one of {}", name, SOURCES.join(", ")));
+        }
+
+        let mut tokens = BTreeMap::new();
+        for source in SOURCES {
+            let token = match self.sources.get(*source) {
+                Some(credentials) => credentials.token().wrap_err_with(|| format!("cannot read the token in `[auth.{}]`", source))?,
+                None => std::env::var(default_env(source)).ok().map(|token| token.trim().to_string()),
+            };
+            if let Some(token) = token.filter(|token| !token.is_empty()) {
+                tokens.insert(source.to_string(), token);
+            }
+        }
+        Ok(tokens)
+    }
+}
+
+/// Returns the environment variable a source's token is read from when the
+/// configuration does not say, such as `CRANKSHAFT_TUI_ENGINE_TOKEN`.
+pub fn default_env(source: &str) -> String {
+    format!("CRANKSHAFT_TUI_{}_TOKEN", source.to_uppercase())
+}
+
+/// A server refused a source's request for want of valid credentials.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unauthorized {
+    /// Name of the source, such as `engine`
+    pub source: String,
+    /// HTTP status of the answer, 401 or 403
+    pub status: u16,
+    /// Whether a token was sent
+    pub token_sent: bool,
+}
+
+impl Unauthorized {
+    /// Returns the refusal if `status` is `401 Unauthorized` or `403
+    /// Forbidden`.
+    pub fn from_status(source: &str, status: u16, token_sent: bool) -> Option<Self> {
+        matches!(status, 401 | 403).then(|| Self {
+            source: source.to_string(),
+            status,
+            token_sent,
+        })
+    }
+}
+
+impl fmt::Display for Unauthorized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.token_sent {
+            write!(f, "the server refused the {} token (HTTP {})", self.source, self.status)?;
+        } else {
+            write!(f, "the server requires a token (HTTP {})", self.status)?;
+        }
+        write!(f, "; set it in `[auth.{}]` of the config file or in {}", self.source, default_env(&self.source))
+    }
+}
+
+impl std::error::Error for Unauthorized {}
+
+/// Sends `token`, if any, as a bearer token with `request`.
+pub(crate) fn authorize(request: reqwest::RequestBuilder, token: Option<&str>) -> reqwest::RequestBuilder {
+    match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+/// Fails with [`Unauthorized`] if `response` refuses `source`'s credentials,
+/// and with the status for any other error.
+pub(crate) fn check(response: reqwest::Response, source: &str, token_sent: bool) -> eyre::Result<reqwest::Response> {
+    if let Some(refusal) = Unauthorized::from_status(source, response.status().as_u16(), token_sent) {
+        return Err(refusal.into());
+    }
+    Ok(response.error_for_status()?)
+}
@@ -0,0 +1,481 @@
+//! Jobs in AWS Batch job queues.
+//!
+//! The AWS CLI is run on a background thread every ten seconds, listing the
+//! jobs in each queue with `aws batch list-jobs`; each job is one task named
+//! after the job. A queue lists the jobs submitted in the day before the
+//! monitor started or since, and its running jobs however old. Jobs are
+//! described with `aws batch describe-jobs` when first seen and whenever
+//! their status changes, for their resources, command, and log stream.
+//! Credentials and the region come from the AWS CLI's usual configuration,
+//! such as `AWS_PROFILE` and `AWS_REGION`, and `aws` must be on `PATH`.
+//!
+//! The task list shows each job's queue as a column, and the details pane
+//! shows the CloudWatch log stream a started job writes to, the name to
+//! look its output up by. Pending and failed jobs carry the reason AWS
+//! Batch gives as their `reason` label, and jobs retried by their retry
+//! strategy show which attempt they are on.
+//!
+//! Job statuses map onto task statuses as follows:
+//!
+//! - `SUBMITTED`, `PENDING`, and `RUNNABLE` are pending
+//! - `STARTING` and `RUNNING` are running
+//! - `SUCCEEDED` is completed
+//! - `FAILED` is failed
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use eyre::WrapErr;
+use serde::Deserialize;
+
+use crate::app::{MemoryUsage, Task, TaskStatus, TaskUpdate};
+use crate::capabilities::SourceCapabilities;
+use crate::logs::LogBuffer;
+use crate::polling::PollInterval;
+use crate::record::unix_now;
+use crate::source::{DataSource, SourceEvent};
+
+/// How often the queues are listed unless configured otherwise.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long before the monitor started the jobs listed were submitted at
+/// the earliest, unless they are still running.
+const LOOKBACK: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Most jobs `describe-jobs` takes at once.
+const DESCRIBE_BATCH: usize = 100;
+
+/// Log group AWS Batch writes to unless a job definition names another.
+pub const AWS_BATCH_LOG_GROUP: &str = "/aws/batch/job";
+
+/// Labels shown as columns of the task list.
+const LABEL_COLUMNS: [&str; 1] = ["queue"];
+
+/// What the polling thread hands over.
+#[derive(Debug)]
+enum Message {
+    /// A change to the task store
+    Update(TaskUpdate),
+    /// Something that happened to the connection
+    Event(SourceEvent),
+}
+
+/// The jobs in some AWS Batch job queues, polled on a background thread.
+pub struct AwsBatchDataSource {
+    /// Names or ARNs of the queues listed
+    queues: Vec<String>,
+    /// Receives what the polling thread found
+    receiver: Receiver<Message>,
+    /// Events received with the last poll's updates, not yet taken
+    events: Vec<SourceEvent>,
+    /// Time between fetches, shared with the polling thread
+    interval: PollInterval,
+}
+
+impl AwsBatchDataSource {
+    /// Starts polling the jobs in `queues`, given by name or ARN.
+    pub fn connect(queues: &[String]) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let listed = queues.to_vec();
+        let interval = PollInterval::new(POLL_INTERVAL);
+        let shared = interval.clone();
+        thread::spawn(move || follow(&listed, &shared, &sender));
+        Self {
+            queues: queues.to_vec(),
+            receiver,
+            events: Vec::new(),
+            interval,
+        }
+    }
+
+    /// Returns the names or ARNs of the queues listed.
+    pub fn queues(&self) -> &[String] {
+        &self.queues
+    }
+}
+
+impl DataSource for AwsBatchDataSource {
+    fn name(&self) -> &str {
+        "aws-batch"
+    }
+
+    /// Statuses only: AWS Batch reports what jobs asked for, not what they
+    /// use, and their output goes to CloudWatch.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::NONE
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        let mut updates = Vec::new();
+        for message in self.receiver.try_iter() {
+            match message {
+                Message::Update(update) => updates.push(update),
+                Message::Event(event) => self.events.push(event),
+            }
+        }
+        updates
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn interval(&self) -> Option<&PollInterval> {
+        Some(&self.interval)
+    }
+
+    fn label_columns(&self) -> Vec<String> {
+        LABEL_COLUMNS.map(str::to_string).to_vec()
+    }
+}
+
+/// Returns the status a job status stands for.
+pub fn status(status: &str) -> TaskStatus {
+    match status {
+        "SUBMITTED" | "PENDING" | "RUNNABLE" => TaskStatus::Pending,
+        "STARTING" | "RUNNING" => TaskStatus::Running,
+        "SUCCEEDED" => TaskStatus::Completed,
+        _ => TaskStatus::Failed,
+    }
+}
+
+/// The output of `aws batch list-jobs`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Listing {
+    /// The jobs, converted one by one
+    #[serde(default)]
+    job_summary_list: Vec<serde_json::Value>,
+}
+
+/// The output of `aws batch describe-jobs`.
+#[derive(Debug, Deserialize)]
+struct Descriptions {
+    /// The jobs, converted one by one
+    #[serde(default)]
+    jobs: Vec<serde_json::Value>,
+}
+
+/// A job as `list-jobs` summarizes it or `describe-jobs` describes it. A
+/// summary has only the names, status, and times; times are in milliseconds
+/// since the epoch.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Job {
+    /// ID assigned by AWS Batch
+    job_id: String,
+    /// Name given when the job was submitted
+    job_name: String,
+    /// Lifecycle status, such as `RUNNABLE` or `SUCCEEDED`
+    status: String,
+    /// Why the job is in its status
+    status_reason: Option<String>,
+    /// When the job started running
+    started_at: Option<u64>,
+    /// When the job stopped running
+    stopped_at: Option<u64>,
+    /// Jobs this one waits for
+    depends_on: Vec<Dependency>,
+    /// Earlier and current attempts, counted only
+    attempts: Vec<serde_json::Value>,
+    /// The container the job runs in, described only
+    container: Option<Container>,
+}
+
+/// A job another job waits for.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Dependency {
+    /// ID of the job waited for
+    job_id: String,
+}
+
+/// The container a job runs in.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Container {
+    /// Command the container runs
+    command: Vec<String>,
+    /// Environment variables set in the container
+    environment: Vec<Variable>,
+    /// Resources the container asked for
+    resource_requirements: Vec<Requirement>,
+    /// Deprecated in favour of the resource requirements
+    vcpus: Option<f64>,
+    /// Deprecated in favour of the resource requirements, in MiB
+    memory: Option<u64>,
+    /// Exit code of the container, once it has stopped
+    exit_code: Option<i64>,
+    /// Why the container stopped, such as `OutOfMemoryError`
+    reason: Option<String>,
+    /// CloudWatch stream the container logs to
+    log_stream_name: Option<String>,
+    /// Where the container sends its output, if not the default group
+    log_configuration: Option<LogConfiguration>,
+}
+
+/// An environment variable set in a job's container.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Variable {
+    /// Name of the variable
+    name: String,
+    /// Value of the variable
+    value: String,
+}
+
+/// A resource a job's container asked for, such as `VCPU` or `MEMORY` in
+/// MiB.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Requirement {
+    /// The resource, such as `VCPU`, `MEMORY`, or `GPU`
+    #[serde(rename = "type")]
+    kind: String,
+    /// The amount, as a number in text
+    value: String,
+}
+
+/// Where a job's container sends its output.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct LogConfiguration {
+    /// The log driver, such as `awslogs`
+    log_driver: String,
+    /// Options of the driver, such as `awslogs-group`
+    options: BTreeMap<String, String>,
+}
+
+/// Converts a job summarized by `list-jobs` or described by
+/// `describe-jobs` in `queue`, keeping the job as the task's raw JSON.
+pub fn to_task(value: &serde_json::Value, queue: &str) -> serde_json::Result<Task> {
+    let job = Job::deserialize(value)?;
+    let status = status(&job.status);
+    let container = job.container.unwrap_or_default();
+    let mut labels = BTreeMap::from([("backend".to_string(), "aws-batch".to_string())]);
+    // Queues given by ARN are shown by name
+    labels.insert("queue".to_string(), queue.rsplit('/').next().unwrap_or(queue).to_string());
+    if let Some(stream) = container.log_stream_name.filter(|stream| !stream.is_empty()) {
+        let group = container
+            .log_configuration
+            .filter(|logs| logs.log_driver == "awslogs")
+            .and_then(|mut logs| logs.options.remove("awslogs-group"))
+            .unwrap_or_else(|| AWS_BATCH_LOG_GROUP.to_string());
+        labels.insert("log_group".to_string(), group);
+        labels.insert("log_stream".to_string(), stream);
+    }
+    // The container's reason is the more specific, such as an OOM kill
+    let reason = container.reason.or(job.status_reason).filter(|reason| !reason.is_empty());
+    if let Some(reason) = reason.filter(|_| matches!(status, TaskStatus::Pending | TaskStatus::Failed)) {
+        labels.insert("reason".to_string(), reason);
+    }
+    if let Some(exit_code) = container.exit_code.filter(|_| matches!(status, TaskStatus::Completed | TaskStatus::Failed)) {
+        labels.insert("exit_code".to_string(), exit_code.to_string());
+    }
+    let requirement = |kind: &str| {
+        container
+            .resource_requirements
+            .iter()
+            .find(|requirement| requirement.kind == kind)
+            .and_then(|requirement| requirement.value.parse::<f64>().ok())
+    };
+    let memory = requirement("MEMORY").or(container.memory.map(|memory| memory as f64));
+
+    Ok(Task {
+        id: job.job_id.clone(),
+        name: if job.job_name.is_empty() { job.job_id } else { job.job_name },
+        status,
+        progress: if status == TaskStatus::Completed { 1.0 } else { 0.0 },
+        cpu_usage: 0.0,
+        cpus: requirement("VCPU").or(container.vcpus),
+        memory_usage: MemoryUsage {
+            used: 0,
+            requested: memory.map(|memory| (memory * 1024.0 * 1024.0) as u64),
+            limit: None,
+        },
+        started_at: job.started_at.map(|at| at / 1000),
+        finished_at: job.stopped_at.filter(|_| matches!(status, TaskStatus::Completed | TaskStatus::Failed)).map(|at| at / 1000),
+        dependencies: job.depends_on.into_iter().map(|dependency| dependency.job_id).collect(),
+        queue: None,
+        // Attempts are listed once they end, so an unfinished job is on the
+        // one after them
+        attempt: Some(match status {
+            TaskStatus::Completed | TaskStatus::Failed => job.attempts.len().max(1),
+            TaskStatus::Pending | TaskStatus::Running => job.attempts.len() + 1,
+        } as u32),
+        labels,
+        command: container.command,
+        env: container.environment.into_iter().map(|variable| (variable.name, variable.value)).collect(),
+        executors: Vec::new(),
+        container: None,
+        pods: Vec::new(),
+        logs: LogBuffer::default(),
+        raw: Some(value.clone()),
+        reported_progress: None,
+        last_updated: None,
+    })
+}
+
+/// Polls the queues until the app is gone, reporting failed polls and
+/// carrying on after them.
+fn follow(queues: &[String], interval: &PollInterval, sender: &Sender<Message>) {
+    let since = unix_now().saturating_sub(LOOKBACK.as_secs()) * 1000;
+    // The task last handed over for each listed job, to send only changes
+    let mut reported: HashMap<String, Task> = HashMap::new();
+    // The last description of each listed job, with its queue
+    let mut described: HashMap<String, (String, serde_json::Value)> = HashMap::new();
+    let mut reachable = false;
+    loop {
+        let started = Instant::now();
+        let mut messages = Vec::new();
+        match list(queues, since, &mut described) {
+            Ok(jobs) => {
+                messages.push(Message::Event(SourceEvent::Connected { latency: started.elapsed() }));
+                if !reachable {
+                    let message = format!("Listing the AWS Batch jobs in {}", queues.join(", "));
+                    messages.push(Message::Event(SourceEvent::Message(message)));
+                    reachable = true;
+                }
+                let mut stale: HashSet<String> = reported.keys().cloned().collect();
+                for (queue, value) in jobs {
+                    let task = match to_task(&value, &queue) {
+                        Ok(task) => task,
+                        Err(err) => {
+                            messages.push(Message::Event(SourceEvent::Dropped(format!("{}: {}", err, value))));
+                            continue;
+                        }
+                    };
+                    stale.remove(&task.id);
+                    let previous = reported.get(&task.id);
+                    if previous.is_none_or(|previous| serde_json::to_value(previous).ok() != serde_json::to_value(&task).ok()) {
+                        reported.insert(task.id.clone(), task.clone());
+                        messages.push(Message::Update(TaskUpdate::Created(Box::new(task))));
+                    }
+                }
+                // Finished jobs that aged out of the listing stay as they
+                // were last listed; others were cancelled before starting
+                // or lost with their queue
+                for id in stale {
+                    described.remove(&id);
+                    let finished = reported.remove(&id).is_some_and(|task| matches!(task.status, TaskStatus::Completed | TaskStatus::Failed));
+                    if !finished {
+                        messages.push(Message::Update(TaskUpdate::Removed { id }));
+                    }
+                }
+            }
+            Err(err) => {
+                reachable = false;
+                let reason = format!("Cannot list AWS Batch jobs: {:#}", err);
+                messages.push(Message::Event(SourceEvent::Failed(reason)));
+            }
+        }
+        for message in messages {
+            if sender.send(message).is_err() {
+                return;
+            }
+        }
+        interval.wait_from(started);
+    }
+}
+
+/// Lists the jobs in `queues` submitted since `since`, in milliseconds, or
+/// still running, with the queue of each. Jobs are described again when
+/// their status changed since their last description, which is kept in
+/// `described`, and so are unfinished jobs that left the listing, to find
+/// out how they ended.
+fn list(
+    queues: &[String],
+    since: u64,
+    described: &mut HashMap<String, (String, serde_json::Value)>,
+) -> eyre::Result<Vec<(String, serde_json::Value)>> {
+    let mut jobs = Vec::new();
+    let mut seen = HashSet::new();
+    for queue in queues {
+        let filter = format!("name=AFTER_CREATED_AT,values={}", since);
+        // Without a filter, only running jobs are listed
+        for args in [&["--filters", filter.as_str()][..], &[]] {
+            for summary in list_jobs(queue, args).wrap_err_with(|| format!("cannot list the jobs in {}", queue))? {
+                let Some(id) = summary.get("jobId").and_then(serde_json::Value::as_str) else {
+                    continue;
+                };
+                if seen.insert(id.to_string()) {
+                    jobs.push((queue.clone(), summary));
+                }
+            }
+        }
+    }
+
+    // A long-running job is listed only while it runs
+    for (id, (queue, description)) in described.iter() {
+        let status = description.get("status").and_then(serde_json::Value::as_str).unwrap_or_default();
+        if !seen.contains(id) && !matches!(status, "SUCCEEDED" | "FAILED") {
+            jobs.push((queue.clone(), description.clone()));
+        }
+    }
+    let changed: Vec<String> = jobs
+        .iter()
+        .filter(|(_, job)| {
+            let description = described.get(job_id(job));
+            !seen.contains(job_id(job)) || description.is_none_or(|(_, description)| description.get("status") != job.get("status"))
+        })
+        .map(|(_, job)| job_id(job).to_string())
+        .collect();
+    let mut found = HashSet::new();
+    for ids in changed.chunks(DESCRIBE_BATCH) {
+        for description in describe_jobs(ids)? {
+            let id = job_id(&description).to_string();
+            if let Some((queue, _)) = jobs.iter().find(|(_, job)| job_id(job) == id) {
+                found.insert(id.clone());
+                described.insert(id, (queue.clone(), description));
+            }
+        }
+    }
+    // Descriptions are as new as the summaries, or newer; jobs that left
+    // the listing and can no longer be described are gone
+    Ok(jobs
+        .into_iter()
+        .filter(|(_, job)| seen.contains(job_id(job)) || found.contains(job_id(job)))
+        .map(|(queue, job)| match described.get(job_id(&job)) {
+            Some((_, description)) => (queue, description.clone()),
+            None => (queue, job),
+        })
+        .collect())
+}
+
+/// Returns the ID of a job as either command reports it.
+fn job_id(job: &serde_json::Value) -> &str {
+    job.get("jobId").and_then(serde_json::Value::as_str).unwrap_or_default()
+}
+
+/// Lists the jobs in `queue` with `args` added to `aws batch list-jobs`.
+fn list_jobs(queue: &str, args: &[&str]) -> eyre::Result<Vec<serde_json::Value>> {
+    let output = aws(&[&["batch", "list-jobs", "--job-queue", queue], args].concat())?;
+    let listing: Listing = serde_json::from_slice(&output).wrap_err("failed to decode the output of aws batch list-jobs")?;
+    Ok(listing.job_summary_list)
+}
+
+/// Describes the jobs with `ids`.
+fn describe_jobs(ids: &[String]) -> eyre::Result<Vec<serde_json::Value>> {
+    let mut args = vec!["batch", "describe-jobs", "--jobs"];
+    args.extend(ids.iter().map(String::as_str));
+    let output = aws(&args)?;
+    let descriptions: Descriptions = serde_json::from_slice(&output).wrap_err("failed to decode the output of aws batch describe-jobs")?;
+    Ok(descriptions.jobs)
+}
+
+/// Runs the AWS CLI with `args`, returning its output as JSON.
+fn aws(args: &[&str]) -> eyre::Result<Vec<u8>> {
+    let output = Command::new("aws")
+        .args(args)
+        .args(["--output", "json"])
+        .output()
+        .map_err(|err| eyre::eyre!("cannot run aws: {}", err))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        eyre::bail!("aws {} failed ({}): {}", args[..2].join(" "), output.status, stderr.trim());
+    }
+    Ok(output.stdout)
+}
@@ -0,0 +1,222 @@
+//! The channel connector threads hand updates to the app through.
+//!
+//! A connector that follows a stream can receive updates faster than the
+//! app applies them, for instance while the terminal is suspended or a burst
+//! of tasks reports progress at once. The channel is bounded, so a
+//! connector notices when the app falls behind instead of queueing without
+//! limit. From then on, progress and resource updates are held aside, one
+//! per task and kind, each replacing the one before it, and handed over
+//! with the next poll. Everything else, such as status changes and log
+//! lines, waits for room in the channel: those are never dropped, and a
+//! status change or a new state of a task also discards the progress held
+//! for it, which it supersedes. The number of updates replaced this way
+//! reaches the diagnostics overlay as [`SourceEvent::Coalesced`].
+
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::app::TaskUpdate;
+use crate::source::SourceEvent;
+
+/// Messages the channel queues before connectors hold updates aside.
+pub(crate) const CHANNEL_CAPACITY: usize = 1024;
+
+/// What a connector thread hands over.
+#[derive(Debug)]
+pub(crate) enum Message {
+    /// A change to the task store
+    Update(TaskUpdate),
+    /// Something that happened to the connection
+    Event(SourceEvent),
+}
+
+/// Kinds of update that only the latest of matters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    /// [`TaskUpdate::Progress`]
+    Progress,
+    /// [`TaskUpdate::Metrics`]
+    Metrics,
+}
+
+/// Updates held aside while the channel is full.
+#[derive(Debug, Default)]
+struct Held {
+    /// The latest update of each kind for each task
+    updates: HashMap<(String, Kind), TaskUpdate>,
+    /// Updates replaced or discarded since the app last took the held ones
+    coalesced: u64,
+}
+
+/// Creates a channel, returning the connector's end and the app's.
+pub(crate) fn channel() -> (Outbox, Inbox) {
+    let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+    let held = Arc::new(Mutex::new(Held::default()));
+    (
+        Outbox {
+            sender,
+            held: Arc::clone(&held),
+        },
+        Inbox { receiver, held },
+    )
+}
+
+/// The app is gone, so there is nobody to hand messages to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Disconnected;
+
+/// The connector's end of the channel.
+#[derive(Debug, Clone)]
+pub(crate) struct Outbox {
+    /// Queues messages for the app
+    sender: SyncSender<Message>,
+    /// Shared with the app's end
+    held: Arc<Mutex<Held>>,
+}
+
+impl Outbox {
+    /// Hands `message` over, holding it aside if it is a progress or
+    /// resource update and the app is behind, and otherwise waiting for
+    /// room.
+    pub(crate) fn send(&self, message: Message) -> Result<(), Disconnected> {
+        let update = match message {
+            Message::Update(update) => update,
+            event => return self.sender.send(event).map_err(|_| Disconnected),
+        };
+        let Some(key) = key(&update) else {
+            if let TaskUpdate::StatusChanged { id, .. } | TaskUpdate::Removed { id } = &update {
+                self.discard(id);
+            } else if let TaskUpdate::Created(task) = &update {
+                self.discard(&task.id);
+            }
+            return self.sender.send(Message::Update(update)).map_err(|_| Disconnected);
+        };
+        let mut held = self.held.lock().unwrap_or_else(PoisonError::into_inner);
+        // Once updates are held, later ones join them rather than overtake
+        // them through the channel
+        let update = if held.updates.is_empty() {
+            match self.sender.try_send(Message::Update(update)) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Disconnected(_)) => return Err(Disconnected),
+                Err(TrySendError::Full(Message::Update(update))) => update,
+                Err(TrySendError::Full(Message::Event(_))) => unreachable!("an update was sent"),
+            }
+        } else {
+            update
+        };
+        if let Some(previous) = held.updates.remove(&key) {
+            held.coalesced += 1;
+            held.updates.insert(key, merge(previous, update));
+        } else {
+            held.updates.insert(key, update);
+        }
+        Ok(())
+    }
+
+    /// Discards the updates held for task `id`, which a later update
+    /// supersedes.
+    fn discard(&self, id: &str) {
+        let mut held = self.held.lock().unwrap_or_else(PoisonError::into_inner);
+        for kind in [Kind::Progress, Kind::Metrics] {
+            if held.updates.remove(&(id.to_string(), kind)).is_some() {
+                held.coalesced += 1;
+            }
+        }
+    }
+}
+
+/// The app's end of the channel.
+#[derive(Debug)]
+pub(crate) struct Inbox {
+    /// Receives what connectors queued
+    receiver: Receiver<Message>,
+    /// Shared with the connectors' ends
+    held: Arc<Mutex<Held>>,
+}
+
+impl Inbox {
+    /// Takes everything handed over since the last call, returning the
+    /// updates and adding the events to `events`. Updates that were held
+    /// aside come after those queued, along with an event counting the ones
+    /// they replaced.
+    pub(crate) fn poll(&self, events: &mut Vec<SourceEvent>) -> Vec<TaskUpdate> {
+        let mut updates = Vec::new();
+        for message in self.receiver.try_iter() {
+            match message {
+                Message::Update(update) => updates.push(update),
+                Message::Event(event) => events.push(event),
+            }
+        }
+        let mut held = self.held.lock().unwrap_or_else(PoisonError::into_inner);
+        updates.extend(held.updates.drain().map(|(_, update)| update));
+        if held.coalesced > 0 {
+            events.push(SourceEvent::Coalesced(std::mem::take(&mut held.coalesced)));
+        }
+        updates
+    }
+}
+
+/// Replaces progress and resource updates in `updates` with the last of
+/// each kind for each task, returning what is left and how many were
+/// replaced. The latest one takes the place of the first, unless a status
+/// change or a new state of the task comes between them, which they must
+/// not overtake.
+pub(crate) fn coalesce(updates: Vec<TaskUpdate>) -> (Vec<TaskUpdate>, u64) {
+    let mut kept: Vec<Option<TaskUpdate>> = Vec::with_capacity(updates.len());
+    let mut slots: HashMap<(String, Kind), usize> = HashMap::new();
+    let mut coalesced = 0;
+    for update in updates {
+        let Some(key) = key(&update) else {
+            if let TaskUpdate::StatusChanged { id, .. } | TaskUpdate::Removed { id } = &update {
+                slots.retain(|(task, _), _| task != id);
+            } else if let TaskUpdate::Created(task) = &update {
+                slots.retain(|(id, _), _| *id != task.id);
+            }
+            kept.push(Some(update));
+            continue;
+        };
+        match slots.get(&key) {
+            Some(&slot) => {
+                coalesced += 1;
+                kept[slot] = kept[slot].take().map(|previous| merge(previous, update));
+            }
+            None => {
+                slots.insert(key, kept.len());
+                kept.push(Some(update));
+            }
+        }
+    }
+    (kept.into_iter().flatten().collect(), coalesced)
+}
+
+/// Returns what an update is held aside under, if only the latest of its
+/// kind for its task matters.
+fn key(update: &TaskUpdate) -> Option<(String, Kind)> {
+    match update {
+        TaskUpdate::Progress { id, .. } => Some((id.clone(), Kind::Progress)),
+        TaskUpdate::Metrics { id, .. } => Some((id.clone(), Kind::Metrics)),
+        _ => None,
+    }
+}
+
+/// Combines two updates of the same kind for the same task, the later
+/// winning. Resource updates leave out values that did not change, so those
+/// of the earlier one are kept.
+fn merge(earlier: TaskUpdate, later: TaskUpdate) -> TaskUpdate {
+    match (earlier, later) {
+        (
+            TaskUpdate::Metrics {
+                cpu_usage: earlier_cpu,
+                memory_usage: earlier_memory,
+                ..
+            },
+            TaskUpdate::Metrics { id, cpu_usage, memory_usage },
+        ) => TaskUpdate::Metrics {
+            id,
+            cpu_usage: cpu_usage.or(earlier_cpu),
+            memory_usage: memory_usage.or(earlier_memory),
+        },
+        (_, later) => later,
+    }
+}
@@ -0,0 +1,421 @@
+//! A stand-in Crankshaft engine for end-to-end testing.
+//!
+//! Serves the REST protocol (`GET /v1/tasks` and `GET /v1/events`) with
+//! tasks that follow a scripted lifecycle, so connectors and reconnection
+//! logic can be exercised in CI and locally without a real backend.
+//!
+//! Scripts are TOML files with one `[[tasks]]` table per task:
+//!
+//! ```toml
+//! [[tasks]]
+//! id = "align-1"
+//! name = "align_reads"
+//! submit_at = 0       # seconds after the script starts
+//! start_at = 2        # when the task starts running
+//! run_secs = 30       # how long it runs for
+//! outcome = "failed"  # or "completed" (the default)
+//! cpus = 4
+//! memory_limit = 4294967296
+//! dependencies = []
+//! labels = { sample = "NA12878" }
+//! logs = ["loading reference", "aligning"]
+//! ```
+//!
+//! Without `--script`, a small built-in pipeline is served.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use crankshaft_tui::{
+    encode_update, MemoryUsage, Task, TaskList, TaskStatus, TaskUpdate, EVENTS_PATH, TASKS_PATH,
+};
+use serde::Deserialize;
+
+/// How often the script is advanced.
+const TICK: Duration = Duration::from_millis(500);
+
+/// How long finished tasks stay listed before a repeated script restarts.
+const REPEAT_DELAY: Duration = Duration::from_secs(5);
+
+/// The script used when none is given.
+const BUILTIN_SCRIPT: &str = r#"
+[[tasks]]
+id = "fetch-1"
+name = "fetch_inputs"
+start_at = 1
+run_secs = 8
+logs = ["resolving inputs", "downloaded 3 files"]
+
+[[tasks]]
+id = "align-1"
+name = "align_reads"
+submit_at = 1
+start_at = 10
+run_secs = 30
+memory_limit = 8589934592
+dependencies = ["fetch-1"]
+labels = { sample = "NA12878" }
+logs = ["loading reference", "aligning chunk 1/2", "aligning chunk 2/2"]
+
+[[tasks]]
+id = "align-2"
+name = "align_reads"
+submit_at = 1
+start_at = 10
+run_secs = 25
+outcome = "failed"
+memory_limit = 4294967296
+dependencies = ["fetch-1"]
+labels = { sample = "NA12891" }
+logs = ["loading reference", "error: out of memory"]
+
+[[tasks]]
+id = "report-1"
+name = "qc_report"
+submit_at = 2
+start_at = 42
+run_secs = 6
+dependencies = ["align-1", "align-2"]
+logs = ["writing report"]
+"#;
+
+/// Serves scripted tasks over the Crankshaft REST protocol.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:7878")]
+    listen: SocketAddr,
+
+    /// Lifecycle script to serve (defaults to a built-in pipeline).
+    #[arg(long, value_name = "PATH")]
+    script: Option<PathBuf>,
+
+    /// Start the script over once every task has finished.
+    #[arg(long)]
+    repeat: bool,
+
+    /// Close event streams after this many seconds, to exercise client
+    /// reconnection.
+    #[arg(long, value_name = "SECS")]
+    drop_after: Option<u64>,
+}
+
+/// A lifecycle script.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Script {
+    /// The scripted tasks
+    tasks: Vec<ScriptedTask>,
+}
+
+/// How the task ends.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Outcome {
+    /// It completes
+    #[default]
+    Completed,
+    /// It fails
+    Failed,
+}
+
+/// The lifecycle of one task.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScriptedTask {
+    /// ID the task is reported under
+    id: String,
+    /// Name shown for the task
+    name: String,
+    /// Seconds after the start of the script when the task appears
+    #[serde(default)]
+    submit_at: u64,
+    /// Seconds after the start of the script when the task starts running
+    start_at: u64,
+    /// Seconds the task runs for
+    run_secs: u64,
+    /// How the task ends
+    #[serde(default)]
+    outcome: Outcome,
+    /// CPUs the task requests
+    cpus: Option<f64>,
+    /// Bytes of memory the task may use
+    memory_limit: Option<u64>,
+    /// IDs of the tasks it waits for
+    #[serde(default)]
+    dependencies: Vec<String>,
+    /// Labels the task is reported with
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+    /// Lines written at even intervals while the task runs
+    #[serde(default)]
+    logs: Vec<String>,
+}
+
+impl ScriptedTask {
+    /// Returns when the task finishes, in seconds after the start of the
+    /// script.
+    fn end(&self) -> u64 {
+        self.start_at + self.run_secs
+    }
+
+    /// Returns the task's state `elapsed` seconds into the script, or `None`
+    /// if it has not been submitted yet.
+    fn state_at(&self, elapsed: f64, script_start: u64) -> Option<Task> {
+        if elapsed < self.submit_at as f64 {
+            return None;
+        }
+        let running = (elapsed - self.start_at as f64) / self.run_secs.max(1) as f64;
+        let (status, progress) = match running {
+            r if r < 0.0 => (TaskStatus::Pending, 0.0),
+            r if r < 1.0 => (TaskStatus::Running, r),
+            _ => match self.outcome {
+                Outcome::Completed => (TaskStatus::Completed, 1.0),
+                Outcome::Failed => (TaskStatus::Failed, 0.9),
+            },
+        };
+        let active = status == TaskStatus::Running;
+        let limit = self.memory_limit.unwrap_or(2 << 30);
+        Some(Task {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            status,
+            progress,
+            cpu_usage: if active { 0.5 + 0.4 * (elapsed * 0.7).sin().abs() } else { 0.0 },
+            cpus: self.cpus,
+            memory_usage: MemoryUsage {
+                used: if active { (limit as f64 * (0.2 + 0.6 * progress)) as u64 } else { 0 },
+                requested: Some(limit / 2),
+                limit: self.memory_limit,
+            },
+            started_at: (status != TaskStatus::Pending).then_some(script_start + self.start_at),
+            finished_at: matches!(status, TaskStatus::Completed | TaskStatus::Failed)
+                .then_some(script_start + self.end()),
+            dependencies: self.dependencies.clone(),
+            queue: None,
+            attempt: None,
+            labels: self.labels.clone(),
+            command: Vec::new(),
+            env: BTreeMap::new(),
+            executors: Vec::new(),
+            container: None,
+            pods: Vec::new(),
+            logs: Default::default(),
+            raw: None,
+            reported_progress: None,
+            last_updated: None,
+        })
+    }
+
+    /// Returns how many log lines have been written `elapsed` seconds into
+    /// the script.
+    fn logs_at(&self, elapsed: f64) -> usize {
+        let step = self.run_secs as f64 / (self.logs.len() + 1) as f64;
+        let written = ((elapsed - self.start_at as f64) / step).floor().max(0.0) as usize;
+        written.min(self.logs.len())
+    }
+}
+
+/// The engine's view of the world, shared between connections.
+struct Engine {
+    /// The tasks to play out
+    script: Script,
+    /// Whether to start over once every task has finished
+    repeat: bool,
+    /// When the current run of the script started
+    started: Instant,
+    /// The same, in seconds since the Unix epoch
+    started_unix: u64,
+    /// Current state of each submitted task, in script order
+    tasks: Vec<Task>,
+    /// Number of log lines sent for each scripted task
+    logs_sent: Vec<usize>,
+    /// Open event streams
+    streams: Vec<Sender<String>>,
+}
+
+impl Engine {
+    /// Starts a run of `script`.
+    fn new(script: Script, repeat: bool) -> Self {
+        let logs_sent = vec![0; script.tasks.len()];
+        Self {
+            script,
+            repeat,
+            started: Instant::now(),
+            started_unix: unix_now(),
+            tasks: Vec::new(),
+            logs_sent,
+            streams: Vec::new(),
+        }
+    }
+
+    /// Advances the script to the current time and broadcasts the changes.
+    fn advance(&mut self) {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let mut updates = Vec::new();
+
+        for (index, scripted) in self.script.tasks.iter().enumerate() {
+            let Some(next) = scripted.state_at(elapsed, self.started_unix) else {
+                continue;
+            };
+            match self.tasks.iter_mut().find(|task| task.id == next.id) {
+                None => {
+                    updates.push(TaskUpdate::Created(Box::new(next.clone())));
+                    self.tasks.push(next);
+                }
+                Some(task) => {
+                    if task.status != next.status {
+                        updates.push(TaskUpdate::StatusChanged { id: next.id.clone(), status: next.status });
+                    }
+                    if task.progress != next.progress {
+                        updates.push(TaskUpdate::Progress { id: next.id.clone(), progress: next.progress });
+                    }
+                    if task.cpu_usage != next.cpu_usage || task.memory_usage != next.memory_usage {
+                        updates.push(TaskUpdate::Metrics {
+                            id: next.id.clone(),
+                            cpu_usage: Some(next.cpu_usage),
+                            memory_usage: Some(next.memory_usage),
+                        });
+                    }
+                    *task = next;
+                }
+            }
+
+            let written = scripted.logs_at(elapsed);
+            for line in &scripted.logs[self.logs_sent[index]..written] {
+                updates.push(TaskUpdate::LogLine { id: scripted.id.clone(), line: line.clone() });
+            }
+            self.logs_sent[index] = written;
+        }
+
+        let last_end = self.script.tasks.iter().map(ScriptedTask::end).max().unwrap_or(0);
+        if self.repeat && elapsed > (Duration::from_secs(last_end) + REPEAT_DELAY).as_secs_f64() {
+            updates.extend(self.tasks.drain(..).map(|task| TaskUpdate::Removed { id: task.id }));
+            self.logs_sent.iter_mut().for_each(|sent| *sent = 0);
+            self.started = Instant::now();
+            self.started_unix = unix_now();
+        }
+
+        self.broadcast(&updates);
+    }
+
+    /// Sends updates to every open event stream, forgetting closed ones.
+    fn broadcast(&mut self, updates: &[TaskUpdate]) {
+        let lines: Vec<String> = updates.iter().filter_map(|update| encode_update(update).ok()).collect();
+        if lines.is_empty() {
+            return;
+        }
+        self.streams
+            .retain(|stream| lines.iter().all(|line| stream.send(line.clone()).is_ok()));
+    }
+
+    /// Opens an event stream, starting with the full state of every task.
+    fn subscribe(&mut self) -> Receiver<String> {
+        let (sender, receiver) = mpsc::channel();
+        for task in &self.tasks {
+            if let Ok(line) = encode_update(&TaskUpdate::Created(Box::new(task.clone()))) {
+                let _ = sender.send(line);
+            }
+        }
+        self.streams.push(sender);
+        receiver
+    }
+}
+
+/// Returns the seconds since the Unix epoch.
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Serves one HTTP connection.
+fn handle(stream: TcpStream, engine: &Mutex<Engine>, drop_after: Option<Duration>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // The headers carry nothing the mock needs
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut stream = stream;
+    let mut parts = request.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some(TASKS_PATH)) => {
+            let list = TaskList { tasks: lock(engine).tasks.clone() };
+            let body = serde_json::to_string(&list)?;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        }
+        (Some("GET"), Some(EVENTS_PATH)) => {
+            let updates = lock(engine).subscribe();
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nConnection: close\r\n\r\n")?;
+            let opened = Instant::now();
+            loop {
+                if drop_after.is_some_and(|limit| opened.elapsed() >= limit) {
+                    return Ok(());
+                }
+                match updates.recv_timeout(TICK) {
+                    Ok(line) => writeln!(stream, "{}", line)?,
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => return Ok(()),
+                }
+            }
+        }
+        _ => write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+    }
+}
+
+/// Locks the engine, ignoring poisoning since a crashed connection leaves
+/// the state consistent.
+fn lock(engine: &Mutex<Engine>) -> std::sync::MutexGuard<'_, Engine> {
+    engine.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let script = match &args.script {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read {}: {}", path.display(), err))?,
+        None => BUILTIN_SCRIPT.to_string(),
+    };
+    let script: Script = toml::from_str(&script)?;
+    let drop_after = args.drop_after.map(Duration::from_secs);
+
+    let listener = TcpListener::bind(args.listen)?;
+    eprintln!("mock engine serving {} tasks on http://{}", script.tasks.len(), listener.local_addr()?);
+
+    let engine = Arc::new(Mutex::new(Engine::new(script, args.repeat)));
+    {
+        let engine = Arc::clone(&engine);
+        thread::spawn(move || loop {
+            lock(&engine).advance();
+            thread::sleep(TICK);
+        });
+    }
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let engine = Arc::clone(&engine);
+        thread::spawn(move || {
+            if let Err(err) = handle(stream, &engine, drop_after) {
+                eprintln!("connection closed: {}", err);
+            }
+        });
+    }
+    Ok(())
+}
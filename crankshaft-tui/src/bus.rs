@@ -0,0 +1,117 @@
+//! Publish/subscribe of task store changes.
+//!
+//! Side effects that react to state changes (session recording, screen
+//! reader summaries, notifications, alert rules) subscribe to the bus
+//! instead of being called from `App::update` one by one. Changes are queued
+//! as updates are applied and delivered together once the store has settled
+//! for the tick, followed by [`StateEvent::Updated`].
+
+use crate::app::{App, TaskStatus};
+
+/// A change to the application state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateEvent {
+    /// A task was added to the store
+    TaskAdded {
+        /// ID of the task
+        id: String,
+    },
+    /// A task was removed from the store
+    TaskRemoved {
+        /// ID of the task
+        id: String,
+    },
+    /// A task moved between statuses
+    StatusChanged {
+        /// ID of the task
+        id: String,
+        /// Status before the change
+        from: TaskStatus,
+        /// Status after the change
+        to: TaskStatus,
+    },
+    /// A task reported new progress
+    ProgressChanged {
+        /// ID of the task
+        id: String,
+    },
+    /// A task reported new resource usage
+    MetricsChanged {
+        /// ID of the task
+        id: String,
+    },
+    /// A task wrote a line of output
+    LogLine {
+        /// ID of the task
+        id: String,
+        /// The line that was written
+        line: String,
+    },
+    /// The user bookmarked the current moment
+    Bookmarked {
+        /// The note given with it, possibly empty
+        note: String,
+    },
+    /// All changes of the current tick have been applied
+    Updated,
+}
+
+/// A component reacting to state changes.
+pub trait Subscriber {
+    /// Name used when reporting that the subscriber failed.
+    fn name(&self) -> &str;
+
+    /// Handles an event. `app` reflects the state after every change of the
+    /// tick has been applied.
+    ///
+    /// Returning an error unsubscribes the subscriber.
+    fn notify(&mut self, event: &StateEvent, app: &App) -> eyre::Result<()>;
+}
+
+/// Queues state events and delivers them to subscribers.
+#[derive(Default)]
+pub struct EventBus {
+    /// Registered subscribers, in registration order
+    subscribers: Vec<Box<dyn Subscriber>>,
+    /// Events published since the last dispatch
+    queue: Vec<StateEvent>,
+}
+
+impl EventBus {
+    /// Registers a subscriber.
+    pub fn subscribe(&mut self, subscriber: impl Subscriber + 'static) {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    /// Returns `true` if nothing is subscribed.
+    pub fn is_empty(&self) -> bool {
+        self.subscribers.is_empty()
+    }
+
+    /// Queues an event for the next dispatch. Events are discarded while
+    /// nothing is subscribed.
+    pub fn publish(&mut self, event: StateEvent) {
+        if !self.is_empty() {
+            self.queue.push(event);
+        }
+    }
+
+    /// Delivers queued events followed by [`StateEvent::Updated`], returning
+    /// the names and errors of subscribers that failed and were removed.
+    pub(crate) fn dispatch(&mut self, app: &App) -> Vec<(String, eyre::Report)> {
+        let mut events = std::mem::take(&mut self.queue);
+        events.push(StateEvent::Updated);
+
+        let mut failures = Vec::new();
+        self.subscribers.retain_mut(|subscriber| {
+            for event in &events {
+                if let Err(err) = subscriber.notify(event, app) {
+                    failures.push((subscriber.name().to_string(), err));
+                    return false;
+                }
+            }
+            true
+        });
+        failures
+    }
+}
@@ -0,0 +1,340 @@
+//! Caching and rate limiting of fetches from the backend.
+//!
+//! The selected task's log, every open pane, and downloads all fetch
+//! through one provider, so the same part of a log is often asked for more
+//! than once in quick succession: a download paging through a log a pane is
+//! following, the same task open twice, or a log viewed again moments after
+//! it was left. Responses are kept for a short while and repeated fetches
+//! of the same range are answered from them; a fetch made while the same
+//! one is under way waits for it and takes its response instead of going
+//! upstream again. Calls that do reach the provider are limited to a rate,
+//! waiting their turn on the fetching thread rather than holding up the
+//! display, so the engine is not hammered when many logs are open.
+//!
+//! Task data goes through a [`CachedSource`] between the data source and
+//! the app in the same way. Every tab and pane reads the tasks the app
+//! keeps, so the source is asked for changes once per tick however many
+//! are open, and the progress and resource updates a task reported several
+//! times since the last tick are coalesced into the latest of each. Asking
+//! is limited to the same rate; a source asked less often than it would
+//! like keeps what arrived meanwhile for the next time. Both can be set in
+//! the `[fetch]` section of the configuration file:
+//!
+//! ```toml
+//! [fetch]
+//! cache_secs = 2
+//! max_per_second = 5
+//! ```
+//!
+//! A `cache_secs` of 0 turns caching off, and a `max_per_second` of 0 lifts
+//! the limit.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::actions::TaskAction;
+use crate::app::TaskUpdate;
+use crate::backpressure;
+use crate::capabilities::SourceCapabilities;
+use crate::logs::{LogChunk, LogProvider, LogRange};
+use crate::polling::PollInterval;
+use crate::source::{DataSource, SourceEvent};
+
+/// How long responses are kept by default, in seconds.
+pub const DEFAULT_CACHE_SECS: f64 = 1.0;
+
+/// Calls allowed upstream each second by default.
+pub const DEFAULT_MAX_PER_SECOND: f64 = 20.0;
+
+/// How fetches from the backend are cached and limited.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FetchConfig {
+    /// Seconds a response answers repeated fetches of the same range
+    pub cache_secs: f64,
+    /// Calls allowed upstream each second, on average
+    pub max_per_second: f64,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            cache_secs: DEFAULT_CACHE_SECS,
+            max_per_second: DEFAULT_MAX_PER_SECOND,
+        }
+    }
+}
+
+impl FetchConfig {
+    /// Returns how long responses are kept, zero if they are not.
+    pub fn cache_for(&self) -> Duration {
+        Duration::try_from_secs_f64(self.cache_secs).unwrap_or_default()
+    }
+
+    /// Returns the calls allowed each second, if limited.
+    pub fn rate(&self) -> Option<f64> {
+        (self.max_per_second > 0.0).then_some(self.max_per_second)
+    }
+}
+
+/// Counts of the fetches made since the provider or source was set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FetchStats {
+    /// Fetches passed on to the provider or source
+    pub upstream: u64,
+    /// Fetches answered from a kept response
+    pub cached: u64,
+    /// Fetches that waited for the rate limit
+    pub throttled: u64,
+    /// Updates replaced by a later one of the same kind for the same task
+    pub coalesced: u64,
+}
+
+/// The configuration and counts of a [`CachedProvider`] or
+/// [`CachedSource`], shared with the app so they can be changed and read
+/// while a fetch is under way.
+#[derive(Debug, Clone, Default)]
+pub struct FetchControl(Arc<Mutex<(FetchConfig, FetchStats)>>);
+
+impl FetchControl {
+    /// Returns the configuration in use.
+    pub fn config(&self) -> FetchConfig {
+        self.0.lock().map(|shared| shared.0).unwrap_or_default()
+    }
+
+    /// Changes the configuration, from the next fetch on.
+    pub fn set_config(&self, config: FetchConfig) {
+        if let Ok(mut shared) = self.0.lock() {
+            shared.0 = config;
+        }
+    }
+
+    /// Returns the fetches counted so far.
+    pub fn stats(&self) -> FetchStats {
+        self.0.lock().map(|shared| shared.1).unwrap_or_default()
+    }
+
+    /// Adds to the counts.
+    fn count(&self, count: impl FnOnce(&mut FetchStats)) {
+        if let Ok(mut shared) = self.0.lock() {
+            count(&mut shared.1);
+        }
+    }
+}
+
+/// A [`LogProvider`] answering repeated fetches from kept responses and
+/// limiting the calls made to the provider it wraps.
+pub struct CachedProvider {
+    /// The provider fetches are passed on to
+    inner: Box<dyn LogProvider>,
+    /// Configuration and counts, shared with the app
+    control: FetchControl,
+    /// Kept responses, with when they were fetched, by task and range
+    responses: HashMap<(String, LogRange), (Instant, LogChunk)>,
+    /// Spaces out the calls that reach the provider
+    limit: RateLimit,
+}
+
+impl CachedProvider {
+    /// Wraps `provider`, caching and limiting as `config` says.
+    pub fn new(provider: impl LogProvider, config: FetchConfig) -> Self {
+        let control = FetchControl::default();
+        control.set_config(config);
+        Self {
+            inner: Box::new(provider),
+            control,
+            responses: HashMap::new(),
+            limit: RateLimit::default(),
+        }
+    }
+
+    /// Returns the handle for changing the configuration and reading the
+    /// counts.
+    pub fn control(&self) -> FetchControl {
+        self.control.clone()
+    }
+}
+
+impl LogProvider for CachedProvider {
+    fn fetch(&mut self, task_id: &str, range: LogRange) -> eyre::Result<LogChunk> {
+        let config = self.control.config();
+        let cache_for = config.cache_for();
+        let now = Instant::now();
+        self.responses.retain(|_, (fetched_at, _)| now.duration_since(*fetched_at) < cache_for);
+
+        let key = (task_id.to_string(), range);
+        if let Some((_, chunk)) = self.responses.get(&key) {
+            self.control.count(|stats| stats.cached += 1);
+            return Ok(chunk.clone());
+        }
+
+        let wait = self.limit.take(config.rate(), now);
+        if !wait.is_zero() {
+            self.control.count(|stats| stats.throttled += 1);
+            thread::sleep(wait);
+        }
+        self.control.count(|stats| stats.upstream += 1);
+        // Failures are not kept, so the next fetch tries again
+        let chunk = self.inner.fetch(task_id, range)?;
+        if !cache_for.is_zero() {
+            self.responses.insert(key, (Instant::now(), chunk.clone()));
+        }
+        Ok(chunk)
+    }
+}
+
+/// The budget of calls a rate allows.
+#[derive(Debug)]
+struct RateLimit {
+    /// Calls that may still be made at once, below zero when they wait
+    tokens: f64,
+    /// When `tokens` was last topped up
+    refilled: Instant,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            tokens: f64::INFINITY,
+            refilled: Instant::now(),
+        }
+    }
+}
+
+impl RateLimit {
+    /// Takes a call from the budget, returning how long to wait before it
+    /// may be made.
+    ///
+    /// Up to a second's worth of calls may be made at once; beyond that,
+    /// calls are spaced out to the rate.
+    fn take(&mut self, rate: Option<f64>, now: Instant) -> Duration {
+        let Some(rate) = rate else {
+            return Duration::ZERO;
+        };
+        let elapsed = now.duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(rate.max(1.0));
+        self.refilled = now;
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+
+    /// Takes a call from the budget if it may be made now, returning
+    /// `false` and leaving the budget as it was otherwise.
+    fn try_take(&mut self, rate: Option<f64>, now: Instant) -> bool {
+        if self.take(rate, now).is_zero() {
+            return true;
+        }
+        self.tokens += 1.0;
+        false
+    }
+}
+
+/// A [`DataSource`] asking the source it wraps for changes at a limited
+/// rate and coalescing the progress and resource updates each answer
+/// repeats.
+pub struct CachedSource {
+    /// The source changes are asked of
+    inner: Box<dyn DataSource>,
+    /// Configuration and counts, shared with the app
+    control: FetchControl,
+    /// Spaces out the polls that reach the source
+    limit: RateLimit,
+    /// Whether the source was polled on the last call, so that its events
+    /// are taken along with its updates
+    polled: bool,
+}
+
+impl CachedSource {
+    /// Wraps `source`, limiting as `config` says.
+    pub fn new(source: Box<dyn DataSource>, config: FetchConfig) -> Self {
+        let control = FetchControl::default();
+        control.set_config(config);
+        Self {
+            inner: source,
+            control,
+            limit: RateLimit::default(),
+            polled: false,
+        }
+    }
+
+    /// Returns the handle for changing the configuration and reading the
+    /// counts.
+    pub fn control(&self) -> FetchControl {
+        self.control.clone()
+    }
+
+    /// Polls the source unless the rate says to wait at `now`, in which
+    /// case nothing is handed over until the next call that may.
+    pub fn poll_at(&mut self, now: Instant) -> Vec<TaskUpdate> {
+        self.polled = self.limit.try_take(self.control.config().rate(), now);
+        if !self.polled {
+            self.control.count(|stats| stats.throttled += 1);
+            return Vec::new();
+        }
+        let (updates, coalesced) = backpressure::coalesce(self.inner.poll());
+        self.control.count(|stats| {
+            stats.upstream += 1;
+            stats.coalesced += coalesced;
+        });
+        updates
+    }
+}
+
+impl DataSource for CachedSource {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        self.inner.capabilities()
+    }
+
+    /// Polls the source as [`CachedSource::poll_at`] does, now.
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        self.poll_at(Instant::now())
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        if std::mem::take(&mut self.polled) {
+            self.inner.events()
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn origins(&self) -> Vec<String> {
+        self.inner.origins()
+    }
+
+    fn is_live(&self) -> bool {
+        self.inner.is_live()
+    }
+
+    fn label_columns(&self) -> Vec<String> {
+        self.inner.label_columns()
+    }
+
+    fn poll_interval(&self) -> Option<Duration> {
+        self.inner.poll_interval()
+    }
+
+    fn interval(&self) -> Option<&PollInterval> {
+        self.inner.interval()
+    }
+
+    fn set_poll_interval(&mut self, source: &str, interval: Duration) -> bool {
+        self.inner.set_poll_interval(source, interval)
+    }
+
+    fn control(&mut self, action: &TaskAction) -> eyre::Result<bool> {
+        self.inner.control(action)
+    }
+}
@@ -0,0 +1,101 @@
+//! What a data source is able to report.
+//!
+//! Not every backend samples the resource use of its tasks, streams their
+//! logs, or accepts control actions. Each source declares what it provides,
+//! and views of what it lacks (gauges, columns, chart series, rankings, the
+//! log panes, and their key hints) are hidden rather than filled with zeros
+//! that look like idle tasks. The diagnostics overlay lists what the current
+//! source provides.
+
+use crate::chart::ChartSeries;
+use crate::sort::TopMetric;
+use crate::spark::SparklineSource;
+
+/// What a data source reports for its tasks, and what it lets users do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceCapabilities {
+    /// Provides task logs
+    pub logs: bool,
+    /// Reports the CPU usage of tasks
+    pub cpu: bool,
+    /// Reports the memory usage of tasks
+    pub memory: bool,
+    /// Accepts control actions on tasks, such as cancelling them
+    pub control: bool,
+    /// Reports which tasks each task depends on
+    pub dependencies: bool,
+}
+
+impl Default for SourceCapabilities {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl SourceCapabilities {
+    /// A source providing everything.
+    pub const ALL: Self = Self {
+        logs: true,
+        cpu: true,
+        memory: true,
+        control: true,
+        dependencies: true,
+    };
+
+    /// A source reporting only task states.
+    pub const NONE: Self = Self {
+        logs: false,
+        cpu: false,
+        memory: false,
+        control: false,
+        dependencies: false,
+    };
+
+    /// Returns `true` if any resource metric is reported.
+    pub fn has_metrics(self) -> bool {
+        self.cpu || self.memory
+    }
+
+    /// Returns `true` if a chart series has data behind it.
+    pub fn series(self, series: ChartSeries) -> bool {
+        match series {
+            ChartSeries::Cpu => self.cpu,
+            ChartSeries::Memory => self.memory,
+        }
+    }
+
+    /// Returns `true` if tasks can be ranked by a metric.
+    pub fn top(self, metric: TopMetric) -> bool {
+        match metric {
+            TopMetric::Cpu => self.cpu,
+            TopMetric::Memory => self.memory,
+        }
+    }
+
+    /// Returns `true` if a sparkline source has data behind it.
+    pub fn sparkline(self, source: SparklineSource) -> bool {
+        match source {
+            SparklineSource::Cpu => self.cpu,
+            SparklineSource::Off | SparklineSource::Progress => true,
+        }
+    }
+
+    /// Lists what is provided, such as `logs, cpu, memory`.
+    pub fn describe(self) -> String {
+        let names: Vec<&str> = [
+            (self.logs, "logs"),
+            (self.cpu, "cpu"),
+            (self.memory, "memory"),
+            (self.control, "control"),
+            (self.dependencies, "dependencies"),
+        ]
+        .into_iter()
+        .filter_map(|(provided, name)| provided.then_some(name))
+        .collect();
+        if names.is_empty() {
+            "task states only".to_string()
+        } else {
+            names.join(", ")
+        }
+    }
+}
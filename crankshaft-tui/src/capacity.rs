@@ -0,0 +1,100 @@
+//! Resource requests measured against what the site can provide.
+//!
+//! A workflow whose tasks ask for more than the cluster has simply queues;
+//! a task that asks for more than the largest node has never starts, and
+//! the workflow waits on it forever without an error. Both are visible here
+//! once the capacity is known, either from the backend or from the
+//! `[capacity]` configuration section:
+//!
+//! ```toml
+//! [capacity]
+//! cpus = 512
+//! memory = 2199023255552
+//! node_cpus = 64
+//! node_memory = 274877906944
+//! ```
+
+use serde::Deserialize;
+
+use crate::app::{Task, TaskStatus};
+
+/// Resources a site provides. Unknown values are `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Capacity {
+    /// CPUs across the whole site
+    pub cpus: Option<f64>,
+    /// Memory across the whole site, in bytes
+    pub memory: Option<u64>,
+    /// CPUs of the largest node, the most a single task can get
+    pub node_cpus: Option<f64>,
+    /// Memory of the largest node, in bytes
+    pub node_memory: Option<u64>,
+}
+
+impl Capacity {
+    /// Overrides values with those known in `other`.
+    pub fn merge(&mut self, other: &Capacity) {
+        self.cpus = other.cpus.or(self.cpus);
+        self.memory = other.memory.or(self.memory);
+        self.node_cpus = other.node_cpus.or(self.node_cpus);
+        self.node_memory = other.node_memory.or(self.node_memory);
+    }
+
+    /// Returns `true` if any value is known.
+    pub fn is_known(&self) -> bool {
+        *self != Capacity::default()
+    }
+
+    /// Returns `true` if no single node can ever provide what the task
+    /// requests. Without a node size the site total is the limit.
+    pub fn can_never_run(&self, task: &Task) -> bool {
+        let cpus = self.node_cpus.or(self.cpus);
+        let memory = self.node_memory.or(self.memory);
+        task.cpus.zip(cpus).is_some_and(|(requested, available)| requested > available)
+            || task.memory_usage.requested.zip(memory).is_some_and(|(requested, available)| requested > available)
+    }
+}
+
+/// What pending and running tasks request in total.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Demand {
+    /// Number of pending and running tasks
+    pub tasks: usize,
+    /// CPUs requested
+    pub cpus: f64,
+    /// Memory requested, in bytes
+    pub memory: u64,
+    /// IDs of tasks that request more than any node provides
+    pub never_run: Vec<String>,
+}
+
+impl Demand {
+    /// Sums the requests of pending and running tasks.
+    pub fn of<'a>(tasks: impl IntoIterator<Item = &'a Task>, capacity: &Capacity) -> Self {
+        let mut demand = Demand::default();
+        for task in tasks {
+            if !matches!(task.status, TaskStatus::Pending | TaskStatus::Running) {
+                continue;
+            }
+            demand.tasks += 1;
+            demand.cpus += task.cpus.unwrap_or(0.0);
+            demand.memory += task.memory_usage.requested.unwrap_or(0);
+            if task.status == TaskStatus::Pending && capacity.can_never_run(task) {
+                demand.never_run.push(task.id.clone());
+            }
+        }
+        demand.never_run.sort();
+        demand
+    }
+
+    /// Returns requested CPUs as a fraction of the site's, if known.
+    pub fn cpu_ratio(&self, capacity: &Capacity) -> Option<f64> {
+        capacity.cpus.filter(|cpus| *cpus > 0.0).map(|cpus| self.cpus / cpus)
+    }
+
+    /// Returns requested memory as a fraction of the site's, if known.
+    pub fn memory_ratio(&self, capacity: &Capacity) -> Option<f64> {
+        capacity.memory.filter(|memory| *memory > 0).map(|memory| self.memory as f64 / memory as f64)
+    }
+}
@@ -0,0 +1,265 @@
+//! Metric history charted in the task details pane.
+//!
+//! Running tasks are sampled every few seconds into a bounded history. The
+//! chart is hard to read precisely in a terminal, so a cursor can be moved
+//! along its time axis to read the exact values at one sample. Value axes
+//! scale to the samples shown, and metrics with a large dynamic range such as
+//! memory can be drawn on a logarithmic scale.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::app::{MemoryUsage, Task, TaskStatus};
+use crate::record::unix_now;
+
+/// Time between samples.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Samples kept per task, ten minutes at the sample interval.
+pub const MAX_POINTS: usize = 300;
+
+/// Metrics of a task at one point in time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    /// When the sample was taken, in seconds since the Unix epoch
+    pub at: u64,
+    /// CPU usage as a fraction of the task's cores
+    pub cpu: f64,
+    /// Memory usage
+    pub memory: MemoryUsage,
+}
+
+/// Sampled metrics of running tasks, plus the selected task once it stops.
+#[derive(Debug, Clone, Default)]
+pub struct MetricHistory {
+    /// Points per task ID, oldest first
+    points: HashMap<String, VecDeque<Point>>,
+    /// When the last sample was taken
+    last: Option<Instant>,
+}
+
+impl MetricHistory {
+    /// Samples running tasks if the sample interval has elapsed. History of
+    /// tasks that stopped is dropped, except for the selected task's.
+    pub fn observe(&mut self, tasks: &HashMap<String, Task>, selected: Option<&str>) {
+        if self.last.is_some_and(|last| last.elapsed() < SAMPLE_INTERVAL) {
+            return;
+        }
+        self.last = Some(Instant::now());
+
+        let at = unix_now();
+        self.points.retain(|id, _| {
+            Some(id.as_str()) == selected || tasks.get(id).is_some_and(|task| task.status == TaskStatus::Running)
+        });
+        for task in tasks.values().filter(|task| task.status == TaskStatus::Running) {
+            let points = self.points.entry(task.id.clone()).or_default();
+            if points.back().is_some_and(|last| last.at == at) {
+                points.pop_back();
+            }
+            points.push_back(Point {
+                at,
+                cpu: task.cpu_usage,
+                memory: task.memory_usage,
+            });
+            if points.len() > MAX_POINTS {
+                points.pop_front();
+            }
+        }
+    }
+
+    /// Returns a task's points, oldest first.
+    pub fn points(&self, id: &str) -> Option<&VecDeque<Point>> {
+        self.points.get(id)
+    }
+}
+
+/// A metric the task chart can plot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChartSeries {
+    /// CPU usage as a fraction of the task's cores
+    Cpu,
+    /// Memory used, in bytes
+    Memory,
+}
+
+impl ChartSeries {
+    /// Every series, in legend order. The number key toggling a series is
+    /// its position here plus one.
+    pub const ALL: [ChartSeries; 2] = [ChartSeries::Cpu, ChartSeries::Memory];
+
+    /// Returns the name shown in the legend.
+    pub fn name(self) -> &'static str {
+        match self {
+            ChartSeries::Cpu => "CPU",
+            ChartSeries::Memory => "Memory",
+        }
+    }
+
+    /// Returns the series' value at a point.
+    pub fn value(self, point: &Point) -> f64 {
+        match self {
+            ChartSeries::Cpu => point.cpu,
+            ChartSeries::Memory => point.memory.used as f64,
+        }
+    }
+}
+
+/// What a chart view shows: its cursor, the scale of each series, and which
+/// series are hidden.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChartState {
+    /// Sample being read, if the cursor is placed
+    pub cursor: ChartCursor,
+    /// Value scale of the memory series
+    pub memory_scale: Scale,
+    /// Series toggled off
+    hidden: BTreeSet<ChartSeries>,
+}
+
+impl ChartState {
+    /// Returns the value scale of a series.
+    pub fn scale(&self, series: ChartSeries) -> Scale {
+        match series {
+            ChartSeries::Cpu => Scale::Linear,
+            ChartSeries::Memory => self.memory_scale,
+        }
+    }
+
+    /// Returns `true` if the series is drawn.
+    pub fn is_visible(&self, series: ChartSeries) -> bool {
+        !self.hidden.contains(&series)
+    }
+
+    /// Returns the drawn series in legend order.
+    pub fn visible(&self) -> impl Iterator<Item = ChartSeries> + '_ {
+        ChartSeries::ALL.into_iter().filter(|series| self.is_visible(*series))
+    }
+
+    /// Shows a hidden series or hides a shown one, returning whether it is
+    /// now visible.
+    pub fn toggle(&mut self, series: ChartSeries) -> bool {
+        if !self.hidden.remove(&series) {
+            self.hidden.insert(series);
+        }
+        self.is_visible(series)
+    }
+}
+
+/// How values are mapped onto a chart's value axis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scale {
+    /// Evenly spaced values
+    #[default]
+    Linear,
+    /// Evenly spaced powers of ten
+    Log,
+}
+
+impl Scale {
+    /// Returns the other scale.
+    pub fn toggled(self) -> Self {
+        match self {
+            Scale::Linear => Scale::Log,
+            Scale::Log => Scale::Linear,
+        }
+    }
+
+    /// Maps a value to its position on the axis. On a log scale values
+    /// below one are drawn at one.
+    pub fn to_axis(self, value: f64) -> f64 {
+        match self {
+            Scale::Linear => value,
+            Scale::Log => value.max(1.0).log10(),
+        }
+    }
+
+    /// Maps a position on the axis back to the value it shows.
+    pub fn from_axis(self, position: f64) -> f64 {
+        match self {
+            Scale::Linear => position,
+            Scale::Log => 10f64.powf(position),
+        }
+    }
+
+    /// Returns axis bounds, in axis positions, fitting the given values with
+    /// a small margin. Linear axes of non-negative values start at zero
+    /// unless that would flatten the data into a line at the top.
+    pub fn bounds(self, values: impl IntoIterator<Item = f64>) -> [f64; 2] {
+        let (min, max) = values
+            .into_iter()
+            .map(|value| self.to_axis(value))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| (min.min(value), max.max(value)));
+        if !min.is_finite() {
+            return [0.0, 1.0];
+        }
+        let range = max - min;
+        if range == 0.0 {
+            let pad = match self {
+                Scale::Log => 0.1,
+                Scale::Linear if max == 0.0 => 1.0,
+                Scale::Linear => max.abs() * 0.1,
+            };
+            return [if min >= 0.0 { (min - pad).max(0.0) } else { min - pad }, max + pad];
+        }
+        let pad = range * 0.05;
+        let lower = if self == Scale::Linear && min >= 0.0 && min <= range { 0.0 } else { min - pad };
+        [lower, max + pad]
+    }
+}
+
+impl fmt::Display for Scale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scale::Linear => write!(f, "linear"),
+            Scale::Log => write!(f, "log"),
+        }
+    }
+}
+
+/// Position of the chart cursor, as the time of the sample it is on.
+///
+/// Without a cursor the chart follows the newest sample.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChartCursor(Option<u64>);
+
+impl ChartCursor {
+    /// Returns the index of the point under the cursor: the last one at or
+    /// before the cursor time.
+    pub fn index(&self, points: &VecDeque<Point>) -> Option<usize> {
+        let at = self.0?;
+        if points.is_empty() {
+            return None;
+        }
+        Some(points.partition_point(|point| point.at <= at).saturating_sub(1))
+    }
+
+    /// Moves to the previous sample, placing the cursor on the newest one if
+    /// there was none.
+    pub fn left(&mut self, points: &VecDeque<Point>) {
+        let index = match self.index(points) {
+            Some(index) => index.saturating_sub(1),
+            None => points.len().saturating_sub(1),
+        };
+        self.0 = points.get(index).map(|point| point.at);
+    }
+
+    /// Moves to the next sample, removing the cursor when moving past the
+    /// newest one.
+    pub fn right(&mut self, points: &VecDeque<Point>) {
+        self.0 = self
+            .index(points)
+            .and_then(|index| points.get(index + 1))
+            .map(|point| point.at);
+    }
+
+    /// Removes the cursor.
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+
+    /// Returns `true` if the cursor is placed.
+    pub fn is_placed(&self) -> bool {
+        self.0.is_some()
+    }
+}
@@ -0,0 +1,31 @@
+//! Clipboard access through the OSC 52 terminal escape sequence.
+//!
+//! OSC 52 asks the terminal emulator itself to set the system clipboard, so it
+//! works over SSH and inside multiplexers that pass it through, without any
+//! platform clipboard libraries.
+
+use std::io::{self, Write};
+
+/// Characters of the standard base64 alphabet.
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Copies `text` to the clipboard by writing an OSC 52 sequence to `out`.
+pub fn copy(out: &mut impl Write, text: &str) -> io::Result<()> {
+    write!(out, "\x1b]52;c;{}\x07", encode_base64(text.as_bytes()))?;
+    out.flush()
+}
+
+/// Encodes bytes as padded base64.
+pub(crate) fn encode_base64(input: &[u8]) -> String {
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        output.push(BASE64[(n >> 18) as usize & 63] as char);
+        output.push(BASE64[(n >> 12) as usize & 63] as char);
+        output.push(if chunk.len() > 1 { BASE64[(n >> 6) as usize & 63] as char } else { '=' });
+        output.push(if chunk.len() > 2 { BASE64[n as usize & 63] as char } else { '=' });
+    }
+    output
+}
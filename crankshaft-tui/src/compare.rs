@@ -0,0 +1,103 @@
+//! Comparing a task with a reference task.
+//!
+//! When identical jobs behave differently, the cause is often a setting
+//! that differs between them. Marking a task that behaved (`b`) and opening
+//! the diff (`E`) on one that did not lines up their command lines,
+//! environments, resources, executors, and labels side by side, with the
+//! fields that differ picked out.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::app::Task;
+use crate::format::NumberFormat;
+
+/// One field of two tasks, side by side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// What the field is, such as `env JAVA_OPTS` or `cpus`
+    pub field: String,
+    /// Its value on the reference task, if set there
+    pub reference: Option<String>,
+    /// Its value on the compared task, if set there
+    pub task: Option<String>,
+}
+
+impl FieldDiff {
+    /// Returns `true` if the two tasks disagree on the field.
+    pub fn differs(&self) -> bool {
+        self.reference != self.task
+    }
+}
+
+/// Lines up the fields of `task` with those of `reference`.
+///
+/// Fields neither task sets are left out; fields only one of them sets are
+/// kept, with the other side empty.
+pub fn compare(reference: &Task, task: &Task, numbers: &NumberFormat) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+    let mut push = |field: String, reference: Option<String>, task: Option<String>| {
+        if reference.is_some() || task.is_some() {
+            diffs.push(FieldDiff { field, reference, task });
+        }
+    };
+
+    push("command".to_string(), command_line(&reference.command), command_line(&task.command));
+    for (name, reference, task) in keyed(&reference.env, &task.env) {
+        push(format!("env {}", name), reference, task);
+    }
+
+    push(
+        "cpus".to_string(),
+        reference.cpus.map(|cpus| numbers.decimal(cpus, 1)),
+        task.cpus.map(|cpus| numbers.decimal(cpus, 1)),
+    );
+    push(
+        "memory requested".to_string(),
+        reference.memory_usage.requested.map(|bytes| numbers.bytes(bytes)),
+        task.memory_usage.requested.map(|bytes| numbers.bytes(bytes)),
+    );
+    push(
+        "memory limit".to_string(),
+        reference.memory_usage.limit.map(|bytes| numbers.bytes(bytes)),
+        task.memory_usage.limit.map(|bytes| numbers.bytes(bytes)),
+    );
+
+    for index in 0..reference.executors.len().max(task.executors.len()) {
+        let (ours, theirs) = (reference.executors.get(index), task.executors.get(index));
+        let prefix = format!("executor {}", index + 1);
+        push(
+            format!("{} image", prefix),
+            ours.map(|executor| executor.image.clone()),
+            theirs.map(|executor| executor.image.clone()),
+        );
+        push(
+            format!("{} command", prefix),
+            ours.and_then(|executor| command_line(&executor.command)),
+            theirs.and_then(|executor| command_line(&executor.command)),
+        );
+        let empty = BTreeMap::new();
+        let (our_env, their_env) = (ours.map_or(&empty, |executor| &executor.env), theirs.map_or(&empty, |executor| &executor.env));
+        for (name, reference, task) in keyed(our_env, their_env) {
+            push(format!("{} env {}", prefix, name), reference, task);
+        }
+    }
+
+    for (name, reference, task) in keyed(&reference.labels, &task.labels) {
+        push(format!("label {}", name), reference, task);
+    }
+    diffs
+}
+
+/// Joins a command line for display, or `None` if there is none.
+fn command_line(command: &[String]) -> Option<String> {
+    (!command.is_empty()).then(|| command.join(" "))
+}
+
+/// Pairs the values of every key in either map, in key order.
+fn keyed<'a>(
+    reference: &'a BTreeMap<String, String>,
+    task: &'a BTreeMap<String, String>,
+) -> impl Iterator<Item = (&'a str, Option<String>, Option<String>)> + 'a {
+    let names: BTreeSet<&str> = reference.keys().chain(task.keys()).map(String::as_str).collect();
+    names.into_iter().map(|name| (name, reference.get(name).cloned(), task.get(name).cloned()))
+}
@@ -0,0 +1,355 @@
+//! User configuration loaded from a TOML file.
+//!
+//! Every setting has a default, so the file is optional and may set only the
+//! keys a user cares about:
+//!
+//! ```toml
+//! [display]
+//! interpolate_progress = false
+//! reduced_motion = true
+//! locale = "de_DE"
+//! sparkline = "cpu"
+//! smooth_progress = 0.3
+//! terminal_title = false
+//! stale_after_secs = 120
+//!
+//! [logs]
+//! max_lines = 5000
+//!
+//! [theme]
+//! palette = "deuteranopia"
+//! symbols = "shapes"
+//!
+//! [accessibility]
+//! screen_reader = true
+//! summary_file = "/tmp/crankshaft-status.txt"
+//!
+//! [confirm]
+//! expert = true
+//! quit = true
+//!
+//! [sort]
+//! keys = ["status", "-duration"]
+//!
+//! [timeline]
+//! lanes = "sample"
+//!
+//! [capacity]
+//! cpus = 512
+//! node_cpus = 64
+//!
+//! [slo]
+//! max_failure_rate = 0.02
+//!
+//! [debug]
+//! memory_log = "/tmp/crankshaft-memory.log"
+//! memory_log_interval_secs = 300
+//!
+//! [updates]
+//! check = true
+//!
+//! [telemetry]
+//! enabled = true
+//! endpoint = "https://metrics.example.org/crankshaft-tui"
+//!
+//! [privacy]
+//! blank_after_secs = 600
+//!
+//! [anomalies]
+//! factor = 4.0
+//!
+//! [audit]
+//! file = "/var/log/crankshaft-audit.jsonl"
+//!
+//! [grouping]
+//! pattern = "^(.+)-shard-[0-9]+$"
+//!
+//! [tools]
+//! diff = "vimdiff -R"
+//!
+//! [merge]
+//! precedence = ["engine", "docker"]
+//!
+//! [retention]
+//! max_age_hours = 24
+//!
+//! [auth.engine]
+//! token_env = "ENGINE_MONITOR_TOKEN"
+//!
+//! [tls]
+//! ca_file = "/etc/ssl/cluster-ca.pem"
+//!
+//! [tunnel]
+//! host = "head.cluster.example.org"
+//! user = "alice"
+//!
+//! [polling]
+//! slurm = 30
+//!
+//! [fetch]
+//! max_per_second = 5
+//!
+//! [[highlights.rules]]
+//! pattern = "sample-[0-9]+"
+//! fg = "cyan"
+//!
+//! [[alerts.rules]]
+//! name = "memory nearly exhausted"
+//! metric = "memory"
+//! condition = "above"
+//! threshold = 7516192768
+//! for_secs = 120
+//! severity = "critical"
+//!
+//! [sounds]
+//! critical = "double"
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use eyre::WrapErr;
+use serde::Deserialize;
+
+use crate::alerts::AlertRules;
+use crate::anomaly::AnomalyConfig;
+use crate::app::DEFAULT_STALE_AFTER;
+use crate::audit::AuditConfig;
+use crate::auth::AuthConfig;
+use crate::cache::FetchConfig;
+use crate::capacity::Capacity;
+use crate::confirm::ConfirmPolicy;
+use crate::groups::GroupingConfig;
+use crate::highlight::HighlightRules;
+use crate::logs::LogLimits;
+use crate::merge::MergeConfig;
+use crate::polling::PollingConfig;
+use crate::retention::RetentionConfig;
+use crate::slo::Slo;
+use crate::sort::SortOrder;
+use crate::sounds::SoundConfig;
+use crate::spark::SparklineSource;
+use crate::theme::Theme;
+use crate::timeline::LaneKey;
+use crate::tls::TlsConfig;
+use crate::tunnel::TunnelConfig;
+
+/// Name of the configuration file inside the config directory.
+const CONFIG_FILE: &str = "config.toml";
+
+/// Top-level configuration.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Rendering options
+    pub display: DisplayConfig,
+    /// Per-task log retention
+    pub logs: LogLimits,
+    /// Status colors and symbols
+    pub theme: Theme,
+    /// Screen reader support
+    pub accessibility: AccessibilityConfig,
+    /// Which actions ask for confirmation
+    pub confirm: ConfirmPolicy,
+    /// Initial order of the task list
+    pub sort: SortOrder,
+    /// Timeline tab options
+    pub timeline: TimelineConfig,
+    /// Resources the site provides, overriding what the backend reports
+    pub capacity: Capacity,
+    /// Acceptable failure rate, for the error budget
+    pub slo: Slo,
+    /// Diagnostics for reporting problems
+    pub debug: DebugConfig,
+    /// Release checks
+    pub updates: UpdatesConfig,
+    /// Anonymous usage reporting
+    pub telemetry: TelemetryConfig,
+    /// Hiding the dashboard when nobody is using it
+    pub privacy: PrivacyConfig,
+    /// Rules raising alerts on task metrics
+    pub alerts: AlertRules,
+    /// Bells rung when alerts are raised
+    pub sounds: SoundConfig,
+    /// When running tasks are flagged as slow for their step
+    pub anomalies: AnomalyConfig,
+    /// Where actions taken from the TUI are recorded
+    pub audit: AuditConfig,
+    /// Styles applied to matching parts of log lines
+    pub highlights: HighlightRules,
+    /// Grouping of the task list by workflow
+    pub grouping: GroupingConfig,
+    /// External programs tasks are handed to
+    pub tools: ToolsConfig,
+    /// How tasks reported by several sources are combined
+    pub merge: MergeConfig,
+    /// When finished tasks leave the store in long sessions
+    pub retention: RetentionConfig,
+    /// Tokens sent to remote sources, by source name
+    pub auth: AuthConfig,
+    /// Certificates trusted and presented by remote sources over HTTPS
+    pub tls: TlsConfig,
+    /// SSH host remote sources are reached through
+    pub tunnel: TunnelConfig,
+    /// How often polled sources fetch, by source name
+    pub polling: PollingConfig,
+    /// How log fetches are cached and limited, and task polls limited
+    pub fetch: FetchConfig,
+}
+
+/// Options for the timeline tab.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimelineConfig {
+    /// Group bars into lanes by `step`, by a backend label such as `sample`,
+    /// or not at all (`none`)
+    pub lanes: LaneKey,
+}
+
+/// Options for checking for new releases.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpdatesConfig {
+    /// Check crates.io for a newer release on startup
+    pub check: bool,
+}
+
+/// Options for anonymous usage reporting, which is off unless enabled.
+///
+/// The section is accepted even by builds without the `telemetry` feature,
+/// so one configuration file works with every build.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelemetryConfig {
+    /// Send a usage report when the monitor exits
+    pub enabled: bool,
+    /// URL the report is posted to; nothing is sent without one
+    pub endpoint: Option<String>,
+}
+
+/// Options for monitors left running on shared screens.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PrivacyConfig {
+    /// Blank the dashboard after this many seconds without a key press,
+    /// until the next one
+    pub blank_after_secs: Option<u64>,
+}
+
+/// External programs the monitor can hand tasks to.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolsConfig {
+    /// Command comparing two task specs, such as `vimdiff` or `meld`, given
+    /// the reference task's spec and the selected task's as two more
+    /// arguments
+    pub diff: Option<String>,
+}
+
+/// Options for diagnosing problems in long-running sessions.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DebugConfig {
+    /// Append process memory statistics to this file periodically
+    pub memory_log: Option<PathBuf>,
+    /// Seconds between lines of the memory log (default: 60)
+    pub memory_log_interval_secs: Option<u64>,
+}
+
+/// Options for assistive technology.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccessibilityConfig {
+    /// Render without decorative borders and emoji, using linear text
+    pub screen_reader: bool,
+    /// Append plain-text status summaries to this file whenever they change
+    pub summary_file: Option<PathBuf>,
+}
+
+/// Options controlling how values are rendered.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    /// Smoothly advance running tasks' progress between backend updates,
+    /// based on their last observed rate
+    pub interpolate_progress: bool,
+    /// Disable spinners, flashing highlights, and interpolated animation in
+    /// favour of static indicators
+    pub reduced_motion: bool,
+    /// Locale whose number separators to use, e.g. `de_DE`; defaults to the
+    /// locale named by `LC_ALL`, `LC_NUMERIC`, or `LANG`
+    pub locale: Option<String>,
+    /// Activity chart drawn at the end of each task row (`off`, `cpu`, or
+    /// `progress`)
+    pub sparkline: SparklineSource,
+    /// Smooth noisy progress reports so gauges never move backwards: the
+    /// share of each forward step applied, from 0 to 1 (1 only ignores
+    /// regressions). Raw reports stay visible in the JSON view
+    #[serde(deserialize_with = "smoothing_factor")]
+    pub smooth_progress: Option<f64>,
+    /// Show the running and failed task counts in the terminal window's
+    /// title
+    pub terminal_title: bool,
+    /// Draw the timeline as an image in terminals with a graphics protocol.
+    /// Accepted by every build, but only used by those with the `graphics`
+    /// feature
+    pub graphics: bool,
+    /// Dim running tasks and mark them stale once no update has arrived
+    /// for this many seconds, if the source reports live resource use; 0
+    /// turns the marker off
+    pub stale_after_secs: u64,
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            interpolate_progress: true,
+            reduced_motion: false,
+            locale: None,
+            sparkline: SparklineSource::Off,
+            smooth_progress: None,
+            terminal_title: true,
+            graphics: true,
+            stale_after_secs: DEFAULT_STALE_AFTER.as_secs(),
+        }
+    }
+}
+
+/// Accepts smoothing factors above 0 and up to 1.
+fn smoothing_factor<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    let factor = f64::deserialize(deserializer)?;
+    if !(factor > 0.0 && factor <= 1.0) {
+        return Err(serde::de::Error::custom(format!("smooth_progress must be above 0 and at most 1, got {}", factor)));
+    }
+    Ok(Some(factor))
+}
+
+impl Config {
+    /// Loads the configuration.
+    ///
+    /// An explicitly given path must exist. Otherwise the default location is
+    /// used if a file is present there, and built-in defaults if not.
+    pub fn load(path: Option<&Path>) -> eyre::Result<Self> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+
+        let contents = std::fs::read_to_string(&path)
+            .wrap_err_with(|| format!("failed to read config file `{}`", path.display()))?;
+        toml::from_str(&contents)
+            .wrap_err_with(|| format!("invalid config file `{}`", path.display()))
+    }
+}
+
+/// Returns the default configuration file path
+/// (`$XDG_CONFIG_HOME/crankshaft-tui/config.toml` or
+/// `~/.config/crankshaft-tui/config.toml`).
+pub fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("crankshaft-tui").join(CONFIG_FILE))
+}
@@ -0,0 +1,78 @@
+//! Confirmation dialogs and the policy deciding when they are shown.
+//!
+//! Every action that can be confirmed consults [`ConfirmPolicy`] before it
+//! runs. Expert mode skips confirmations by default, and each action can be
+//! forced on or off individually:
+//!
+//! ```toml
+//! [confirm]
+//! expert = true
+//! quit = true
+//! cancel = true
+//! ```
+
+use serde::Deserialize;
+
+/// An action that may need confirmation before it runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Confirmable {
+    /// Hide a task from the list
+    Archive {
+        /// ID of the task
+        id: String,
+    },
+    /// Cancel a task on its backend
+    Cancel {
+        /// ID of the task
+        id: String,
+    },
+    /// Exit the application
+    Quit,
+}
+
+impl Confirmable {
+    /// Returns the question shown in the confirmation dialog.
+    pub fn prompt(&self) -> String {
+        match self {
+            Confirmable::Archive { id } => format!("Archive task {}?", id),
+            Confirmable::Cancel { id } => format!("Cancel task {}?", id),
+            Confirmable::Quit => "Quit crankshaft-tui?".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Confirmable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Confirmable::Archive { id } => write!(f, "archive {}", id),
+            Confirmable::Cancel { id } => write!(f, "cancel {}", id),
+            Confirmable::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+/// Which actions ask for confirmation.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfirmPolicy {
+    /// Skip confirmations unless an action explicitly asks for one
+    pub expert: bool,
+    /// Confirm archiving a task (default: on)
+    pub archive: Option<bool>,
+    /// Confirm cancelling a task (default: on)
+    pub cancel: Option<bool>,
+    /// Confirm quitting (default: off)
+    pub quit: Option<bool>,
+}
+
+impl ConfirmPolicy {
+    /// Returns `true` if `action` should be confirmed before it runs.
+    pub fn requires_confirmation(&self, action: &Confirmable) -> bool {
+        let (setting, default) = match action {
+            Confirmable::Archive { .. } => (self.archive, true),
+            Confirmable::Cancel { .. } => (self.cancel, true),
+            Confirmable::Quit => (self.quit, false),
+        };
+        setting.unwrap_or(default && !self.expert)
+    }
+}
@@ -0,0 +1,161 @@
+//! Unix-domain control socket for driving a running instance.
+//!
+//! The protocol is one command per line; the server answers each with a
+//! single `ok` or `error: <reason>` line. External tools normally use the
+//! `crankshaft-tui ctl` subcommand rather than speaking it directly.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::app::Tab;
+
+/// A command accepted on the control socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlCommand {
+    /// Select the task with the given ID
+    Select(String),
+    /// Apply a text filter to the task list (empty clears it)
+    Filter(String),
+    /// Switch to a tab
+    Tab(Tab),
+}
+
+impl FromStr for ControlCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        let (verb, arg) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let arg = arg.trim();
+
+        match verb {
+            "select" if !arg.is_empty() => Ok(ControlCommand::Select(arg.to_string())),
+            "select" => Err("select requires a task ID".to_string()),
+            "filter" => Ok(ControlCommand::Filter(arg.to_string())),
+            "tab" => arg.parse().map(ControlCommand::Tab),
+            "" => Err("empty command".to_string()),
+            other => Err(format!("unknown command `{}` (expected select, filter, or tab)", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for ControlCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ControlCommand::Select(id) => write!(f, "select {}", id),
+            ControlCommand::Filter(filter) => write!(f, "filter {}", filter),
+            ControlCommand::Tab(tab) => write!(f, "tab {}", tab.title().to_lowercase()),
+        }
+    }
+}
+
+/// Returns the default control socket path for the current user.
+pub fn default_socket_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("crankshaft-tui.sock"),
+        None => {
+            let user = std::env::var("USER").unwrap_or_else(|_| "default".to_string());
+            std::env::temp_dir().join(format!("crankshaft-tui-{}.sock", user))
+        }
+    }
+}
+
+#[cfg(unix)]
+pub use unix::{send, ControlServer};
+
+/// The socket server and client, on Unix only.
+#[cfg(unix)]
+mod unix {
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc;
+    use std::thread;
+
+    use super::ControlCommand;
+    use crate::socket;
+
+    /// Listens on a control socket and queues received commands.
+    pub struct ControlServer {
+        /// Location of the socket, removed on drop
+        path: PathBuf,
+        /// Commands received from clients
+        commands: mpsc::Receiver<ControlCommand>,
+    }
+
+    impl ControlServer {
+        /// Binds the socket at `path` and starts accepting connections.
+        ///
+        /// A stale socket left behind by a crashed instance is replaced, but
+        /// binding fails if another instance is still listening or something
+        /// other than a socket is at `path`.
+        pub fn bind(path: &Path) -> io::Result<Self> {
+            let listener = socket::bind(path)?;
+            let (sender, commands) = mpsc::channel();
+
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(stream) = stream else {
+                        continue;
+                    };
+                    let sender = sender.clone();
+                    thread::spawn(move || serve(stream, sender));
+                }
+            });
+
+            Ok(Self {
+                path: path.to_path_buf(),
+                commands,
+            })
+        }
+
+        /// Returns the socket path.
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        /// Returns all commands received since the last call.
+        pub fn drain(&self) -> Vec<ControlCommand> {
+            self.commands.try_iter().collect()
+        }
+    }
+
+    impl Drop for ControlServer {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    /// Handles a single client connection.
+    fn serve(stream: UnixStream, sender: mpsc::Sender<ControlCommand>) {
+        let Ok(mut writer) = stream.try_clone() else {
+            return;
+        };
+
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                return;
+            };
+            let reply = match line.parse::<ControlCommand>() {
+                Ok(command) => match sender.send(command) {
+                    Ok(()) => "ok".to_string(),
+                    Err(_) => return,
+                },
+                Err(err) => format!("error: {}", err),
+            };
+            if writeln!(writer, "{}", reply).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Sends a command to the instance listening at `path` and returns its reply.
+    pub fn send(path: &Path, command: &ControlCommand) -> io::Result<String> {
+        let mut stream = UnixStream::connect(path)?;
+        writeln!(stream, "{}", command)?;
+
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+        Ok(reply.trim_end().to_string())
+    }
+}
@@ -0,0 +1,191 @@
+//! Crash report bundles.
+//!
+//! When the application panics or exits with a fatal error, a directory is
+//! written to the system temp directory containing what is needed to act on a
+//! bug report:
+//!
+//! - `report.txt`: version, platform, command line, and the failure itself
+//! - `log.txt`: the most recent internal log messages
+//! - `config.toml`: the configuration file in use, with secrets redacted
+//! - `snapshot.json`: the most recent copy of the task store
+//!
+//! The panic hook restores the terminal before anything is printed, so the
+//! bundle's path ends up readable on the normal screen.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::app::App;
+use crate::bus::{StateEvent, Subscriber};
+use crate::record::{unix_now, Snapshot};
+
+/// Number of internal log messages kept for the bundle.
+const LOG_CAPACITY: usize = 200;
+
+/// Time between copies of the task store kept for the bundle.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Substrings of config keys whose values are redacted.
+const SECRET_KEYS: &[&str] = &["token", "secret", "password", "passwd", "credential", "api_key", "apikey"];
+
+/// Information collected while running, for inclusion in a bundle.
+struct CrashContext {
+    /// Recent internal log messages with their Unix time
+    log: VecDeque<(u64, String)>,
+    /// Configuration file in use, if any
+    config_path: Option<PathBuf>,
+    /// Serialized copy of the task store
+    snapshot: Option<String>,
+}
+
+/// What is written into the next crash bundle.
+static CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext {
+    log: VecDeque::new(),
+    config_path: None,
+    snapshot: None,
+});
+
+/// Runs `f` on the context, ignoring a poisoned lock since the data is only
+/// informational.
+fn with_context<T>(f: impl FnOnce(&mut CrashContext) -> T) -> T {
+    let mut context = CONTEXT.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut context)
+}
+
+/// Adds a message to the internal log included in crash bundles.
+pub fn log(message: impl Into<String>) {
+    let message = message.into();
+    with_context(|context| {
+        if context.log.len() == LOG_CAPACITY {
+            context.log.pop_front();
+        }
+        context.log.push_back((unix_now(), message));
+    });
+}
+
+/// Records the configuration file in use.
+pub fn set_config_path(path: Option<PathBuf>) {
+    with_context(|context| context.config_path = path);
+}
+
+/// Returns the configuration file in use, if any.
+pub fn config_path() -> Option<PathBuf> {
+    with_context(|context| context.config_path.clone())
+}
+
+/// Installs a panic hook that restores the terminal, writes a bundle, and
+/// prints its path before the default panic message.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = crate::restore_terminal_state();
+        let reason = format!("panic: {}\n\n{}", info, std::backtrace::Backtrace::force_capture());
+        match write_bundle(&reason) {
+            Ok(path) => eprintln!("crankshaft-tui crashed; a crash report was written to {}", path.display()),
+            Err(err) => eprintln!("crankshaft-tui crashed and the crash report could not be written: {}", err),
+        }
+        default_hook(info);
+    }));
+}
+
+/// Writes a bundle describing `reason` and returns its directory.
+pub fn write_bundle(reason: &str) -> io::Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("crankshaft-tui-crash-{}-{}", unix_now(), std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+
+    let mut report = String::new();
+    let _ = writeln!(report, "crankshaft-tui {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "platform: {} {}", std::env::consts::OS, std::env::consts::ARCH);
+    let _ = writeln!(report, "command line: {}", std::env::args().collect::<Vec<_>>().join(" "));
+    let _ = writeln!(report, "time: {}", crate::format::timestamp(unix_now()));
+    let _ = writeln!(report, "\n{}", reason);
+    std::fs::write(dir.join("report.txt"), report)?;
+
+    let (log, config_path, snapshot) = with_context(|context| {
+        let log = context
+            .log
+            .iter()
+            .map(|(at, message)| format!("{} {}\n", crate::format::timestamp(*at), message))
+            .collect::<String>();
+        (log, context.config_path.clone(), context.snapshot.clone())
+    });
+    std::fs::write(dir.join("log.txt"), log)?;
+    if let Some(config) = config_path.and_then(|path| std::fs::read_to_string(path).ok()) {
+        std::fs::write(dir.join("config.toml"), redact(&config))?;
+    }
+    if let Some(snapshot) = snapshot {
+        std::fs::write(dir.join("snapshot.json"), snapshot)?;
+    }
+
+    Ok(dir)
+}
+
+/// Replaces the values of secret-looking keys in TOML text, in every table
+/// including inline ones. Text that is not valid TOML cannot be told apart
+/// safely, so it is left out.
+fn redact(config: &str) -> String {
+    let Ok(mut table) = config.parse::<toml::Table>() else {
+        return "# not valid TOML, left out of the bundle\n".to_string();
+    };
+    redact_table(&mut table);
+    toml::to_string(&table).unwrap_or_else(|err| format!("# could not be written back: {}\n", err))
+}
+
+/// Replaces the values of secret-looking keys in `table` and the tables
+/// nested in it.
+fn redact_table(table: &mut toml::Table) {
+    for (key, value) in table.iter_mut() {
+        if is_secret(key) {
+            *value = toml::Value::String("<redacted>".to_string());
+        } else {
+            redact_value(value);
+        }
+    }
+}
+
+/// Redacts the tables in `value`, whether it is one or an array of them.
+fn redact_value(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => redact_table(table),
+        toml::Value::Array(values) => values.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// Returns `true` if a config key names a secret.
+fn is_secret(key: &str) -> bool {
+    let key = key.trim().to_ascii_lowercase();
+    SECRET_KEYS.iter().any(|secret| key.contains(secret))
+}
+
+/// Keeps a periodic copy of the task store for crash bundles.
+#[derive(Debug, Default)]
+pub struct CrashSnapshotter {
+    /// When the last copy was taken
+    last: Option<Instant>,
+}
+
+impl Subscriber for CrashSnapshotter {
+    fn name(&self) -> &str {
+        "Crash snapshots"
+    }
+
+    fn notify(&mut self, event: &StateEvent, app: &App) -> eyre::Result<()> {
+        if *event != StateEvent::Updated || self.last.is_some_and(|last| last.elapsed() < SNAPSHOT_INTERVAL) {
+            return Ok(());
+        }
+        self.last = Some(Instant::now());
+
+        let snapshot = Snapshot {
+            taken_at: unix_now(),
+            tasks: app.task_ids.iter().filter_map(|id| app.tasks.get(id)).map(|task| task.without_logs()).collect(),
+        };
+        let json = serde_json::to_string(&snapshot)?;
+        with_context(|context| context.snapshot = Some(json));
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+//! Demo data shown when no other source is chosen.
+//!
+//! A small workflow of nineteen tasks in every state, whose running tasks
+//! make steady progress until they complete. It accepts control actions, so
+//! cancelling, retrying, and reprioritizing can be tried without a backend.
+
+use std::collections::BTreeMap;
+
+use crate::actions::TaskAction;
+use crate::app::{MemoryUsage, QueuePosition, Task, TaskStatus, TaskUpdate};
+use crate::capabilities::SourceCapabilities;
+use crate::capacity::Capacity;
+use crate::logs::LogBuffer;
+use crate::record::unix_now;
+use crate::source::DataSource;
+use crate::workflow::WorkflowMetadata;
+
+/// Memory limit given to every demo task.
+const MEMORY_LIMIT: u64 = 8 << 30;
+
+/// Number of demo tasks.
+const TASK_COUNT: usize = 19;
+
+/// Progress a running demo task makes on every poll.
+const PROGRESS_PER_POLL: f64 = 0.01;
+
+/// The demo tasks, advanced on every poll.
+#[derive(Debug, Clone)]
+pub struct MockDataSource {
+    /// The tasks as last reported, in display order
+    tasks: Vec<Task>,
+    /// Updates not yet handed over: the initial tasks, and the results of
+    /// control actions
+    pending: Vec<TaskUpdate>,
+}
+
+impl Default for MockDataSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockDataSource {
+    /// Creates the demo tasks, reported on the first poll.
+    pub fn new() -> Self {
+        let now = unix_now();
+        let tasks: Vec<Task> = (1..=TASK_COUNT).map(|i| demo_task(i, now)).collect();
+        let pending = tasks.iter().map(|task| TaskUpdate::Created(Box::new(task.clone()))).collect();
+        Self { tasks, pending }
+    }
+
+    /// Returns a site sized for the demo tasks.
+    pub fn capacity(&self) -> Capacity {
+        Capacity {
+            cpus: Some(24.0),
+            memory: Some(4 * MEMORY_LIMIT),
+            node_cpus: Some(8.0),
+            node_memory: Some(2 * MEMORY_LIMIT),
+        }
+    }
+
+    /// Returns the metadata of the demo workflow.
+    pub fn workflow(&self) -> WorkflowMetadata {
+        WorkflowMetadata {
+            run_id: Some("demo-run-0001".to_string()),
+            name: Some("Sample Workflow".to_string()),
+            version: Some("1.0.0".to_string()),
+            submitter: std::env::var("USER").ok(),
+            submitted_at: Some(unix_now()),
+            engine_version: None,
+        }
+    }
+
+    /// Records an update as reported and queues it for the next poll.
+    fn report(&mut self, update: TaskUpdate) {
+        if let Some(task) = self.tasks.iter_mut().find(|task| task.id == update.task_id()) {
+            match &update {
+                TaskUpdate::StatusChanged { status, .. } => {
+                    if *status != TaskStatus::Pending {
+                        task.queue = None;
+                    }
+                    task.status = *status;
+                }
+                TaskUpdate::Progress { progress, .. } => task.progress = *progress,
+                TaskUpdate::Queue { queue, .. } => task.queue = *queue,
+                _ => {}
+            }
+        }
+        self.pending.push(update);
+    }
+}
+
+impl DataSource for MockDataSource {
+    fn name(&self) -> &str {
+        "demo"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::ALL
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        let running: Vec<(String, f64)> = self
+            .tasks
+            .iter()
+            .filter(|task| task.status == TaskStatus::Running)
+            .map(|task| (task.id.clone(), (task.progress + PROGRESS_PER_POLL).min(1.0)))
+            .collect();
+        for (id, progress) in running {
+            self.report(TaskUpdate::Progress { id: id.clone(), progress });
+            if progress >= 1.0 {
+                self.report(TaskUpdate::StatusChanged { id, status: TaskStatus::Completed });
+            }
+        }
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Cancelling fails a pending or running task, retrying queues a
+    /// finished one again from the start, and priority changes move a
+    /// pending task one place in its queue.
+    fn control(&mut self, action: &TaskAction) -> eyre::Result<bool> {
+        let Some(task) = self.tasks.iter().find(|task| task.id == action.id()) else {
+            eyre::bail!("no demo task {}", action.id());
+        };
+        let id = task.id.clone();
+        let updates = match (action, task.status) {
+            (TaskAction::Cancel { .. }, TaskStatus::Pending | TaskStatus::Running) => {
+                vec![TaskUpdate::StatusChanged { id, status: TaskStatus::Failed }]
+            }
+            (TaskAction::Retry { .. }, TaskStatus::Completed | TaskStatus::Failed) => vec![
+                TaskUpdate::StatusChanged { id: id.clone(), status: TaskStatus::Pending },
+                TaskUpdate::Progress { id, progress: 0.0 },
+            ],
+            (TaskAction::Prioritize { raise, .. }, TaskStatus::Pending) => {
+                let Some(mut queue) = task.queue else {
+                    return Ok(false);
+                };
+                let position = if *raise { queue.position.saturating_sub(1).max(1) } else { queue.position + 1 };
+                if position == queue.position {
+                    return Ok(false);
+                }
+                queue.position = position;
+                vec![TaskUpdate::Queue { id, queue: Some(queue) }]
+            }
+            _ => return Ok(false),
+        };
+        for update in updates {
+            self.report(update);
+        }
+        Ok(true)
+    }
+}
+
+/// Creates the `i`th demo task, cycling through the statuses.
+fn demo_task(i: usize, now: u64) -> Task {
+    let status = match i % 4 {
+        0 => TaskStatus::Pending,
+        1 => TaskStatus::Running,
+        2 => TaskStatus::Completed,
+        _ => TaskStatus::Failed,
+    };
+
+    let progress = match status {
+        TaskStatus::Pending => 0.0,
+        TaskStatus::Running => (i as f64 % 10.0) / 10.0,
+        TaskStatus::Completed => 1.0,
+        TaskStatus::Failed => (i as f64 % 10.0) / 10.0,
+    };
+
+    let phase = ["align", "call", "annotate"][(i - 1) * 3 / TASK_COUNT];
+    let sample = format!("sample-{}", (i - 1) / 5 + 1);
+    let threads = i % 4 + 1;
+
+    Task {
+        id: format!("task-{}", i),
+        name: format!("Sample Task {}", i),
+        status,
+        progress,
+        cpu_usage: (i as f64 % 100.0) / 100.0,
+        cpus: Some(threads as f64),
+        memory_usage: MemoryUsage {
+            used: (MEMORY_LIMIT as f64 * (i as f64 % 80.0) / 100.0) as u64,
+            requested: Some(MEMORY_LIMIT / 2),
+            limit: Some(MEMORY_LIMIT),
+        },
+        started_at: (status != TaskStatus::Pending).then(|| now - 60 * i as u64),
+        finished_at: matches!(status, TaskStatus::Completed | TaskStatus::Failed).then(|| now - 30 * i as u64),
+        // A small fan-in/fan-out graph: every task after the first three
+        // needs the one three places before it
+        dependencies: if i > 3 { vec![format!("task-{}", i - 3)] } else { Vec::new() },
+        queue: (status == TaskStatus::Pending).then(|| QueuePosition {
+            position: i as u64 / 4,
+            estimated_start: Some(now + 120 * i as u64 / 4),
+        }),
+        // Some tasks were requeued after losing their node
+        attempt: i.is_multiple_of(7).then_some(2),
+        labels: BTreeMap::from([
+            ("phase".to_string(), phase.to_string()),
+            ("sample".to_string(), sample.clone()),
+            ("backend".to_string(), if i.is_multiple_of(3) { "docker" } else { "local" }.to_string()),
+        ]),
+        command: vec!["run-step".to_string(), phase.to_string(), format!("{}.bam", sample)],
+        // The failed tasks were given a smaller heap, for the reference
+        // task diff to find
+        env: BTreeMap::from([
+            ("THREADS".to_string(), threads.to_string()),
+            ("JAVA_OPTS".to_string(), if status == TaskStatus::Failed { "-Xmx2g" } else { "-Xmx4g" }.to_string()),
+        ]),
+        executors: Vec::new(),
+        container: None,
+        pods: Vec::new(),
+        logs: LogBuffer::default(),
+        raw: None,
+        reported_progress: None,
+        last_updated: None,
+    }
+}
@@ -0,0 +1,244 @@
+//! Health information about the connection to the engine.
+//!
+//! Besides the overall health shown in the diagnostics overlay, the state of
+//! each source's connection is kept for the header: whether it is connected,
+//! reconnecting, or offline, and when it last delivered anything. A source
+//! combining several others relays their events, so each of them is shown
+//! separately.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::protocol::Malformed;
+use crate::source::SourceEvent;
+
+/// How long without updates before the connection is considered stale.
+pub const STALE_AFTER: Duration = Duration::from_secs(10);
+
+/// Connection health reported by the data source and shown in the
+/// diagnostics overlay.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectorHealth {
+    /// Name of the data source in use
+    pub source: String,
+    /// Version reported by the engine, if any
+    pub engine_version: Option<String>,
+    /// Round-trip time of the last request to the engine
+    pub api_latency: Option<Duration>,
+    /// Delay between an event happening on the engine and it being received
+    pub event_lag: Option<Duration>,
+    /// Number of messages that were dropped or could not be decoded
+    pub dropped_messages: u64,
+    /// Of the dropped messages, those that did not follow the update
+    /// format, by what was wrong with them
+    pub malformed: BTreeMap<Malformed, u64>,
+    /// Of the dropped messages, the progress and resource updates replaced
+    /// by later ones while the app was behind
+    pub coalesced: u64,
+    /// The last error reported by the connector and when it happened
+    pub last_error: Option<(String, Instant)>,
+    /// When the last successful update was received
+    pub last_update: Option<Instant>,
+    /// While the source is reconnecting, the attempt it is on and when it
+    /// is made
+    pub reconnecting: Option<(u32, Instant)>,
+}
+
+/// Overall verdict derived from [`ConnectorHealth`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthState {
+    /// Updates are arriving normally
+    Healthy,
+    /// No update has been received recently
+    Stale,
+    /// The last attempt to talk to the engine failed
+    Failing,
+    /// The connection dropped and the source is connecting again
+    Reconnecting,
+    /// Nothing has been received yet
+    Waiting,
+}
+
+impl ConnectorHealth {
+    /// Creates health tracking for the named source.
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Records a successful update that took `latency` to fetch.
+    pub fn record_success(&mut self, latency: Option<Duration>) {
+        self.last_update = Some(Instant::now());
+        self.reconnecting = None;
+        if latency.is_some() {
+            self.api_latency = latency;
+        }
+    }
+
+    /// Records a connector error.
+    pub fn record_error(&mut self, error: impl Into<String>) {
+        self.last_error = Some((error.into(), Instant::now()));
+    }
+
+    /// Records a dropped connection that the source retries after `delay`.
+    pub fn record_reconnecting(&mut self, reason: impl Into<String>, attempt: u32, delay: Duration) {
+        self.record_error(reason);
+        self.reconnecting = Some((attempt, Instant::now() + delay));
+    }
+
+    /// Records messages that were dropped.
+    pub fn record_dropped(&mut self, count: u64) {
+        self.dropped_messages += count;
+    }
+
+    /// Records a dropped message that did not follow the update format.
+    pub fn record_malformed(&mut self, kind: Malformed) {
+        self.record_dropped(1);
+        *self.malformed.entry(kind).or_default() += 1;
+    }
+
+    /// Records progress and resource updates that later ones replaced.
+    pub fn record_coalesced(&mut self, count: u64) {
+        self.record_dropped(count);
+        self.coalesced += count;
+    }
+
+    /// Returns the overall state of the connection.
+    pub fn state(&self) -> HealthState {
+        let errored_since_update = match (&self.last_error, self.last_update) {
+            (Some((_, at)), Some(update)) => *at > update,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        match self.last_update {
+            _ if self.reconnecting.is_some() => HealthState::Reconnecting,
+            _ if errored_since_update => HealthState::Failing,
+            None => HealthState::Waiting,
+            Some(update) if update.elapsed() > STALE_AFTER => HealthState::Stale,
+            Some(_) => HealthState::Healthy,
+        }
+    }
+}
+
+impl std::fmt::Display for HealthState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HealthState::Healthy => write!(f, "Healthy"),
+            HealthState::Stale => write!(f, "Stale"),
+            HealthState::Failing => write!(f, "Failing"),
+            HealthState::Reconnecting => write!(f, "Reconnecting"),
+            HealthState::Waiting => write!(f, "Waiting"),
+        }
+    }
+}
+
+/// State of one source's connection, as shown in the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionState {
+    /// Nothing has been received yet
+    Waiting,
+    /// The backend was reached or updates are arriving
+    Connected,
+    /// The connection dropped and the source is connecting again
+    Reconnecting,
+    /// The last attempt to reach the backend failed
+    Offline,
+}
+
+impl std::fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionState::Waiting => write!(f, "Waiting"),
+            ConnectionState::Connected => write!(f, "Connected"),
+            ConnectionState::Reconnecting => write!(f, "Reconnecting"),
+            ConnectionState::Offline => write!(f, "Offline"),
+        }
+    }
+}
+
+/// The connection of one source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+    /// Name of the source
+    pub source: String,
+    /// Where the connection stands
+    pub state: ConnectionState,
+    /// When the source last reached its backend or delivered updates, in
+    /// seconds since the Unix epoch
+    pub last_success: Option<u64>,
+}
+
+/// The connections of every source the app follows, in the order the
+/// sources were given.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Connections {
+    /// One per source
+    connections: Vec<Connection>,
+}
+
+impl Connections {
+    /// Tracks the connections of the named sources, none of them reached
+    /// yet.
+    pub fn new<S: Into<String>>(sources: impl IntoIterator<Item = S>) -> Self {
+        let connections = sources
+            .into_iter()
+            .map(|source| Connection {
+                source: source.into(),
+                state: ConnectionState::Waiting,
+                last_success: None,
+            })
+            .collect();
+        Self { connections }
+    }
+
+    /// Records updates delivered by `source` at `now`, in seconds since the
+    /// Unix epoch.
+    pub fn record_updates(&mut self, source: &str, now: u64) {
+        if let Some(connection) = self.get_mut(source) {
+            connection.state = ConnectionState::Connected;
+            connection.last_success = Some(now);
+        }
+    }
+
+    /// Records what happened to `source`'s connection at `now`. Events
+    /// about the tasks rather than the connection change nothing, and
+    /// neither do those of sources that are not tracked.
+    pub fn record(&mut self, source: &str, event: &SourceEvent, now: u64) {
+        match event {
+            SourceEvent::Connected { .. } | SourceEvent::Received(_) => self.record_updates(source, now),
+            SourceEvent::Reconnecting { .. } => self.set_state(source, ConnectionState::Reconnecting),
+            SourceEvent::Failed(_) => self.set_state(source, ConnectionState::Offline),
+            SourceEvent::Message(_)
+            | SourceEvent::Bookmark(_)
+            | SourceEvent::Dropped(_)
+            | SourceEvent::Malformed { .. }
+            | SourceEvent::Coalesced(_)
+            | SourceEvent::Relayed { .. } => {}
+        }
+    }
+
+    /// Returns the connection of the named source.
+    pub fn get(&self, source: &str) -> Option<&Connection> {
+        self.connections.iter().find(|connection| connection.source == source)
+    }
+
+    /// Returns every connection, in the order the sources were given.
+    pub fn iter(&self) -> impl Iterator<Item = &Connection> {
+        self.connections.iter()
+    }
+
+    /// Returns the connection of `source`, if it is followed.
+    fn get_mut(&mut self, source: &str) -> Option<&mut Connection> {
+        self.connections.iter_mut().find(|connection| connection.source == source)
+    }
+
+    /// Changes the state of `source`'s connection.
+    fn set_state(&mut self, source: &str, state: ConnectionState) {
+        if let Some(connection) = self.get_mut(source) {
+            connection.state = state;
+        }
+    }
+}
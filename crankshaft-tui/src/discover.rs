@@ -0,0 +1,158 @@
+//! Backends discovered from the Crankshaft engine's configuration file.
+//!
+//! The engine lists its backends in `crankshaft.toml`, each with a name and
+//! a type:
+//!
+//! ```toml
+//! [[backends]]
+//! name = "containers"
+//! type = "Docker"
+//!
+//! [[backends]]
+//! name = "cloud"
+//! type = "TES"
+//! url = "https://tes.example.org/ga4gh/tes/v1"
+//!
+//! [[backends]]
+//! name = "cluster"
+//! type = "Generic"
+//! submit = "sbatch --parsable --wrap '~{command}'"
+//! ```
+//!
+//! With `--discover`, the monitor reads that file and follows each backend
+//! with the matching source, so endpoints already given to the engine need
+//! not be given again: Docker backends through the local Docker daemon, TES
+//! backends at their URL, and generic backends whose commands submit to
+//! Slurm (`sbatch`) or LSF (`bsub`) through that scheduler. Other generic
+//! backends, and those that run their commands on another host, cannot be
+//! followed from here and are reported as skipped.
+//!
+//! The file is read loosely rather than through the engine's own types, so
+//! keys a newer engine adds do not stop the monitor from reading it.
+
+use std::path::Path;
+
+use eyre::{eyre, WrapErr};
+
+/// Name of the engine's configuration file, looked for in the current
+/// directory.
+pub const ENGINE_CONFIG_FILE: &str = "crankshaft.toml";
+
+/// How the monitor follows a backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Backend {
+    /// Containers of the local Docker daemon
+    Docker,
+    /// A TES service at this URL
+    Tes(String),
+    /// Slurm jobs of the current user
+    Slurm,
+    /// LSF jobs of the current user
+    Lsf,
+    /// Not followed, for this reason
+    Unsupported(String),
+}
+
+/// A backend configured in the engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredBackend {
+    /// Name the engine knows it by
+    pub name: String,
+    /// How it is followed
+    pub backend: Backend,
+}
+
+/// Reads the backends configured in the engine's configuration file.
+pub fn load(path: &Path) -> eyre::Result<Vec<DiscoveredBackend>> {
+    let text = std::fs::read_to_string(path).wrap_err_with(|| format!("failed to read `{}`", path.display()))?;
+    parse(&text).wrap_err_with(|| format!("invalid engine configuration `{}`", path.display()))
+}
+
+/// Reads the backends configured in the text of an engine configuration
+/// file.
+pub fn parse(text: &str) -> eyre::Result<Vec<DiscoveredBackend>> {
+    let config: toml::Table = toml::from_str(text)?;
+    let Some(backends) = config.get("backends") else {
+        return Ok(Vec::new());
+    };
+    let backends = backends.as_array().ok_or_else(|| eyre!("`backends` is not a list of tables"))?;
+
+    backends
+        .iter()
+        .enumerate()
+        .map(|(index, backend)| {
+            let backend = backend.as_table().ok_or_else(|| eyre!("backend {} is not a table", index + 1))?;
+            let name = match backend.get("name").and_then(toml::Value::as_str) {
+                Some(name) => name.to_string(),
+                None => format!("backend {}", index + 1),
+            };
+            Ok(DiscoveredBackend {
+                backend: classify(backend),
+                name,
+            })
+        })
+        .collect()
+}
+
+/// Decides how a backend of the engine's configuration is followed.
+fn classify(backend: &toml::Table) -> Backend {
+    // The type is a tag next to the backend's own keys, or a table of them
+    let (kind, settings) = match backend.get("type").or_else(|| backend.get("kind")) {
+        Some(toml::Value::String(kind)) => (kind.clone(), backend),
+        Some(toml::Value::Table(table)) if table.len() == 1 => {
+            let (kind, settings) = table.iter().next().expect("the table has one entry");
+            (kind.clone(), settings.as_table().unwrap_or(backend))
+        }
+        _ => return Backend::Unsupported("no backend type".to_string()),
+    };
+
+    match kind.to_ascii_lowercase().as_str() {
+        "docker" => Backend::Docker,
+        "tes" => match settings.get("url").and_then(toml::Value::as_str) {
+            Some(url) => Backend::Tes(url.to_string()),
+            None => Backend::Unsupported("no TES URL".to_string()),
+        },
+        "generic" => {
+            if is_remote(settings) {
+                return Backend::Unsupported("its commands run on another host".to_string());
+            }
+            let mut commands = Vec::new();
+            settings.values().for_each(|value| strings(value, &mut commands));
+            if commands.iter().any(|command| runs(command, "sbatch")) {
+                Backend::Slurm
+            } else if commands.iter().any(|command| runs(command, "bsub")) {
+                Backend::Lsf
+            } else {
+                Backend::Unsupported("its scheduler is not recognized".to_string())
+            }
+        }
+        other => Backend::Unsupported(format!("backend type `{}` is not supported", other)),
+    }
+}
+
+/// Returns `true` if a generic backend runs its commands over SSH.
+fn is_remote(settings: &toml::Table) -> bool {
+    let driver = settings.get("driver").and_then(toml::Value::as_table).unwrap_or(settings);
+    match driver.get("locale") {
+        Some(toml::Value::String(locale)) => locale.eq_ignore_ascii_case("ssh"),
+        Some(toml::Value::Table(locale)) => locale.keys().any(|key| key.eq_ignore_ascii_case("ssh")),
+        _ => false,
+    }
+}
+
+/// Collects every string in `value`, such as a backend's command templates.
+fn strings<'a>(value: &'a toml::Value, found: &mut Vec<&'a str>) {
+    match value {
+        toml::Value::String(text) => found.push(text),
+        toml::Value::Array(values) => values.iter().for_each(|value| strings(value, found)),
+        toml::Value::Table(table) => table.values().for_each(|value| strings(value, found)),
+        _ => {}
+    }
+}
+
+/// Returns `true` if `command` runs `program`.
+fn runs(command: &str, program: &str) -> bool {
+    command
+        .split(|c: char| c.is_whitespace() || matches!(c, ';' | '&' | '|' | '(' | '`'))
+        .any(|word| word == program || word.ends_with(&format!("/{}", program)))
+}
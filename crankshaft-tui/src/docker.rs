@@ -0,0 +1,538 @@
+//! Containers run by Crankshaft's Docker backend.
+//!
+//! The local Docker daemon is polled over its Unix socket on a background
+//! thread for the containers carrying the [`TASK_LABEL`] label, which the
+//! backend puts on every container it starts. Each container is one task,
+//! named after the label's value, and leaves the list when the container is
+//! removed.
+//!
+//! Running containers are sampled on every poll, so the details pane shows
+//! their live CPU, memory, network, and block I/O use rather than what was
+//! requested. CPU use is averaged between two samples, so it reads zero
+//! until a container has been sampled twice.
+//!
+//! Container states map onto task statuses as follows:
+//!
+//! - `created` is pending
+//! - `running`, `paused`, `restarting`, and `removing` are running
+//! - `exited` and `dead` are completed if the exit code is 0, and failed
+//!   otherwise
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use eyre::WrapErr;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::app::{ContainerStats, MemoryUsage, Task, TaskStatus, TaskUpdate};
+use crate::capabilities::SourceCapabilities;
+use crate::format::parse_timestamp;
+use crate::logs::LogBuffer;
+use crate::polling::PollInterval;
+use crate::source::{DataSource, SourceEvent};
+
+/// Label on the containers of Crankshaft tasks, whose value names the task.
+pub const TASK_LABEL: &str = "crankshaft.task";
+
+/// Socket the daemon listens on unless `DOCKER_HOST` names another.
+const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+
+/// How often containers are listed and sampled unless configured
+/// otherwise.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long one request to the daemon may take.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the polling thread hands over.
+#[derive(Debug)]
+enum Message {
+    /// A change to the task store
+    Update(TaskUpdate),
+    /// Something that happened to the connection
+    Event(SourceEvent),
+}
+
+/// The local Docker daemon, polled on a background thread.
+pub struct DockerDataSource {
+    /// Path of the daemon's socket
+    socket: PathBuf,
+    /// Receives what the polling thread found
+    receiver: Receiver<Message>,
+    /// Events received with the last poll's updates, not yet taken
+    events: Vec<SourceEvent>,
+    /// Time between fetches, shared with the polling thread
+    interval: PollInterval,
+}
+
+impl DockerDataSource {
+    /// Starts polling the daemon listening on `socket`.
+    pub fn connect(socket: &Path) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let path = socket.to_path_buf();
+        let interval = PollInterval::new(POLL_INTERVAL);
+        let shared = interval.clone();
+        thread::spawn(move || follow(&path, &shared, &sender));
+        Self {
+            socket: socket.to_path_buf(),
+            receiver,
+            events: Vec::new(),
+            interval,
+        }
+    }
+
+    /// Returns the path of the daemon's socket.
+    pub fn socket(&self) -> &Path {
+        &self.socket
+    }
+}
+
+impl DataSource for DockerDataSource {
+    fn name(&self) -> &str {
+        "docker"
+    }
+
+    /// Resource use only: logs, control actions, and dependencies between
+    /// tasks are not read from the daemon.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            cpu: true,
+            memory: true,
+            ..SourceCapabilities::NONE
+        }
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        let mut updates = Vec::new();
+        for message in self.receiver.try_iter() {
+            match message {
+                Message::Update(update) => updates.push(update),
+                Message::Event(event) => self.events.push(event),
+            }
+        }
+        updates
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn interval(&self) -> Option<&PollInterval> {
+        Some(&self.interval)
+    }
+}
+
+/// Returns the daemon's socket: the `unix://` path in `DOCKER_HOST` if there
+/// is one, otherwise `/var/run/docker.sock`.
+pub fn default_socket() -> PathBuf {
+    std::env::var("DOCKER_HOST")
+        .ok()
+        .and_then(|host| host.strip_prefix("unix://").map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_SOCKET))
+}
+
+/// Returns the status a container state stands for.
+pub fn status(state: &str, exit_code: i64) -> TaskStatus {
+    match state {
+        "created" => TaskStatus::Pending,
+        "exited" | "dead" if exit_code == 0 => TaskStatus::Completed,
+        "exited" | "dead" => TaskStatus::Failed,
+        _ => TaskStatus::Running,
+    }
+}
+
+/// An entry of `GET /containers/json`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListedContainer {
+    /// Full container ID
+    id: String,
+    /// Lifecycle state, such as `running` or `exited`
+    state: String,
+}
+
+/// The parts of `GET /containers/{id}/json` the monitor shows.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct Inspect {
+    /// Full container ID
+    id: String,
+    /// Container name, with a leading `/`
+    name: String,
+    /// Program the container runs
+    path: String,
+    /// Arguments passed to `path`
+    args: Option<Vec<String>>,
+    /// Where the container is in its life
+    state: InspectState,
+    /// How the container was configured
+    config: InspectConfig,
+    /// Resources the container was given
+    host_config: InspectHostConfig,
+}
+
+/// Where a container is in its life.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct InspectState {
+    /// Lifecycle state, such as `running` or `exited`
+    status: String,
+    /// Exit code of the main process, once it has exited
+    exit_code: i64,
+    /// When the container last started, in RFC 3339
+    started_at: String,
+    /// When the container last stopped, in RFC 3339
+    finished_at: String,
+}
+
+/// How a container was configured.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct InspectConfig {
+    /// Environment variables, as `NAME=value`
+    env: Option<Vec<String>>,
+    /// Labels set on the container
+    labels: Option<BTreeMap<String, String>>,
+}
+
+/// Resources a container was given; zero means unlimited.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct InspectHostConfig {
+    /// CPU limit, in billionths of a CPU
+    nano_cpus: u64,
+    /// Memory limit, in bytes
+    memory: u64,
+    /// Memory reserved for the container, in bytes
+    memory_reservation: u64,
+}
+
+/// Converts a container as `GET /containers/{id}/json` reports it, keeping
+/// the payload as the task's raw JSON. Resource use is left at zero until
+/// the container is sampled.
+pub fn to_task(value: &serde_json::Value) -> serde_json::Result<Task> {
+    let inspect = Inspect::deserialize(value)?;
+    let status = status(&inspect.state.status, inspect.state.exit_code);
+    let id = match inspect.name.trim_start_matches('/') {
+        "" => inspect.id.chars().take(12).collect(),
+        name => name.to_string(),
+    };
+
+    let mut labels = inspect.config.labels.unwrap_or_default();
+    let name = labels.get(TASK_LABEL).filter(|name| !name.is_empty()).cloned().unwrap_or_else(|| id.clone());
+    labels.entry("backend".to_string()).or_insert_with(|| "docker".to_string());
+    let command = match inspect.path.as_str() {
+        "" => Vec::new(),
+        path => std::iter::once(path.to_string()).chain(inspect.args.unwrap_or_default()).collect(),
+    };
+    let env = inspect
+        .config
+        .env
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| match entry.split_once('=') {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => (entry, String::new()),
+        })
+        .collect();
+    let host = inspect.host_config;
+
+    Ok(Task {
+        id,
+        name,
+        status,
+        progress: if status == TaskStatus::Completed { 1.0 } else { 0.0 },
+        cpu_usage: 0.0,
+        cpus: (host.nano_cpus > 0).then(|| host.nano_cpus as f64 / 1e9),
+        memory_usage: MemoryUsage {
+            used: 0,
+            requested: (host.memory_reservation > 0).then_some(host.memory_reservation),
+            limit: (host.memory > 0).then_some(host.memory),
+        },
+        // Docker reports unset times as the year 1, which parses as `None`
+        started_at: (status != TaskStatus::Pending).then(|| parse_timestamp(&inspect.state.started_at)).flatten(),
+        finished_at: matches!(status, TaskStatus::Completed | TaskStatus::Failed)
+            .then(|| parse_timestamp(&inspect.state.finished_at))
+            .flatten(),
+        dependencies: Vec::new(),
+        queue: None,
+        attempt: None,
+        labels,
+        command,
+        env,
+        executors: Vec::new(),
+        container: None,
+        pods: Vec::new(),
+        logs: LogBuffer::default(),
+        raw: Some(value.clone()),
+        reported_progress: None,
+        last_updated: None,
+    })
+}
+
+/// The parts of `GET /containers/{id}/stats` the monitor shows.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Stats {
+    /// CPU time of the container and of the host
+    cpu_stats: CpuStats,
+    /// Memory use of the container
+    memory_stats: MemoryStats,
+    /// Traffic by network interface, absent with host networking
+    networks: Option<HashMap<String, NetworkStats>>,
+    /// Block device traffic
+    blkio_stats: BlkioStats,
+    /// Processes in the container
+    pids_stats: PidsStats,
+}
+
+/// Cumulative CPU time of a container and of the host.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CpuStats {
+    /// CPU time of the container
+    cpu_usage: CpuUsage,
+    /// CPU time of the host, in nanoseconds
+    system_cpu_usage: u64,
+    /// CPUs available to the container
+    online_cpus: u32,
+}
+
+/// Cumulative CPU time of a container, in nanoseconds.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CpuUsage {
+    /// CPU time across all CPUs
+    total_usage: u64,
+}
+
+/// Memory use of a container, in bytes.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MemoryStats {
+    /// Memory in use, page cache included
+    usage: u64,
+    /// Memory the container may use
+    limit: u64,
+    /// The cgroup's detailed counters, such as `inactive_file`
+    stats: HashMap<String, u64>,
+}
+
+/// Traffic through one network interface, in bytes.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct NetworkStats {
+    /// Bytes received
+    rx_bytes: u64,
+    /// Bytes sent
+    tx_bytes: u64,
+}
+
+/// Block device traffic, by operation.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct BlkioStats {
+    /// Bytes moved by each kind of operation, absent on some cgroup setups
+    io_service_bytes_recursive: Option<Vec<BlkioEntry>>,
+}
+
+/// Bytes moved by one kind of block device operation.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct BlkioEntry {
+    /// The operation, such as `read` or `write`
+    op: String,
+    /// Bytes moved
+    value: u64,
+}
+
+/// Processes in a container.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PidsStats {
+    /// Processes running now
+    current: u64,
+}
+
+/// A container as last inspected, with its last CPU sample.
+struct Container {
+    /// Its state when inspected; it is inspected again once that changes
+    state: String,
+    /// The task it is, without resource use
+    task: Task,
+    /// Container and host CPU time at the last sample, in nanoseconds
+    cpu: Option<(u64, u64)>,
+}
+
+impl Container {
+    /// Returns the task with the resource use in `stats`.
+    fn sampled(&mut self, stats: &Stats) -> Task {
+        let cpu = &stats.cpu_stats;
+        let online = f64::from(cpu.online_cpus.max(1));
+        let cpus = match self.cpu {
+            Some((container, system)) if cpu.system_cpu_usage > system => {
+                let container = cpu.cpu_usage.total_usage.saturating_sub(container) as f64;
+                container / (cpu.system_cpu_usage - system) as f64 * online
+            }
+            _ => 0.0,
+        };
+        self.cpu = Some((cpu.cpu_usage.total_usage, cpu.system_cpu_usage));
+        let cpu_limit = self.task.cpus.unwrap_or(online);
+
+        // Like `docker stats`, page cache that could be reclaimed is not
+        // counted as used; cgroup v1 and v2 name it differently
+        let memory = &stats.memory_stats;
+        let inactive = ["inactive_file", "total_inactive_file"].iter().find_map(|key| memory.stats.get(*key));
+        let used = memory.usage.saturating_sub(inactive.copied().unwrap_or(0));
+
+        let networks = stats.networks.iter().flat_map(|networks| networks.values());
+        let block = stats.blkio_stats.io_service_bytes_recursive.iter().flatten();
+        let block_bytes = |op: &str| block.clone().filter(|entry| entry.op.eq_ignore_ascii_case(op)).map(|entry| entry.value).sum();
+
+        let mut task = self.task.clone();
+        task.cpu_usage = (cpus / cpu_limit).min(1.0);
+        task.memory_usage.used = used;
+        if task.memory_usage.limit.is_none() && memory.limit > 0 {
+            task.memory_usage.limit = Some(memory.limit);
+        }
+        task.container = Some(ContainerStats {
+            cpus,
+            cpu_limit,
+            network_rx: networks.clone().map(|network| network.rx_bytes).sum(),
+            network_tx: networks.map(|network| network.tx_bytes).sum(),
+            block_read: block_bytes("read"),
+            block_write: block_bytes("write"),
+            pids: stats.pids_stats.current,
+        });
+        task
+    }
+}
+
+/// Polls the daemon until the app is gone, reporting failed polls and
+/// carrying on after them.
+fn follow(socket: &Path, interval: &PollInterval, sender: &Sender<Message>) {
+    let mut containers: HashMap<String, Container> = HashMap::new();
+    // The task last handed over for each container, to send only changes
+    let mut reported: HashMap<String, serde_json::Value> = HashMap::new();
+    let mut reachable = false;
+    loop {
+        let started = Instant::now();
+        let mut messages = Vec::new();
+        match sample(socket, &mut containers, &mut messages) {
+            Ok(tasks) => {
+                messages.push(Message::Event(SourceEvent::Connected { latency: started.elapsed() }));
+                if !reachable {
+                    let message = format!("Connected to Docker at {}", socket.display());
+                    messages.push(Message::Event(SourceEvent::Message(message)));
+                    reachable = true;
+                }
+                let mut stale: HashSet<String> = reported.keys().cloned().collect();
+                for task in tasks {
+                    stale.remove(&task.id);
+                    let value = serde_json::to_value(&task).unwrap_or_default();
+                    if reported.get(&task.id) != Some(&value) {
+                        reported.insert(task.id.clone(), value);
+                        messages.push(Message::Update(TaskUpdate::Created(Box::new(task))));
+                    }
+                }
+                for id in stale {
+                    reported.remove(&id);
+                    messages.push(Message::Update(TaskUpdate::Removed { id }));
+                }
+            }
+            Err(err) => {
+                reachable = false;
+                let reason = format!("Cannot reach Docker at {}: {:#}", socket.display(), err);
+                messages.push(Message::Event(SourceEvent::Failed(reason)));
+            }
+        }
+        for message in messages {
+            if sender.send(message).is_err() {
+                return;
+            }
+        }
+        interval.wait_from(started);
+    }
+}
+
+/// Lists the containers of Crankshaft tasks, inspecting those that are new
+/// or changed state and sampling those that are running.
+fn sample(socket: &Path, containers: &mut HashMap<String, Container>, messages: &mut Vec<Message>) -> eyre::Result<Vec<Task>> {
+    let path = format!("/containers/json?all=true&filters=%7B%22label%22%3A%5B%22{}%22%5D%7D", TASK_LABEL);
+    let listed: Vec<ListedContainer> = get(socket, &path)?;
+    containers.retain(|id, _| listed.iter().any(|container| container.id == *id));
+
+    let mut tasks = Vec::new();
+    for listed in listed {
+        // A container can be removed between being listed and inspected;
+        // it is skipped, and the next listing no longer has it
+        if containers.get(&listed.id).is_none_or(|container| container.state != listed.state) {
+            let Ok(value) = get::<serde_json::Value>(socket, &format!("/containers/{}/json", listed.id)) else {
+                continue;
+            };
+            match to_task(&value) {
+                Ok(task) => {
+                    containers.insert(listed.id.clone(), Container { state: listed.state.clone(), task, cpu: None });
+                }
+                Err(err) => {
+                    messages.push(Message::Event(SourceEvent::Dropped(format!("{}: {}", err, value))));
+                    continue;
+                }
+            }
+        }
+        let container = containers.get_mut(&listed.id).expect("the container was just inspected");
+        if container.task.status != TaskStatus::Running {
+            tasks.push(container.task.clone());
+            continue;
+        }
+        match get::<Stats>(socket, &format!("/containers/{}/stats?stream=false&one-shot=true", listed.id)) {
+            Ok(stats) => tasks.push(container.sampled(&stats)),
+            Err(_) => tasks.push(container.task.clone()),
+        }
+    }
+    Ok(tasks)
+}
+
+/// An error body of the Docker API.
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    /// What went wrong
+    message: String,
+}
+
+/// Sends `GET path` to the daemon and decodes the JSON response.
+fn get<T: DeserializeOwned>(socket: &Path, path: &str) -> eyre::Result<T> {
+    let mut stream = UnixStream::connect(socket).wrap_err("failed to connect")?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    // HTTP/1.0 keeps the daemon from chunking the body, which then simply
+    // ends when the daemon closes the connection
+    write!(stream, "GET {} HTTP/1.0\r\nHost: docker\r\n\r\n", path)?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).wrap_err_with(|| format!("failed to read the response to {}", path))?;
+
+    let Some(split) = response.windows(4).position(|window| window == b"\r\n\r\n") else {
+        eyre::bail!("malformed response to {}", path);
+    };
+    let head = String::from_utf8_lossy(&response[..split]);
+    let body = &response[split + 4..];
+    let status: u16 = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| eyre::eyre!("malformed status line in the response to {}", path))?;
+    if !(200..300).contains(&status) {
+        let message = match serde_json::from_slice::<ErrorResponse>(body) {
+            Ok(error) => error.message,
+            Err(_) => String::from_utf8_lossy(body).trim().to_string(),
+        };
+        eyre::bail!("{} returned {}: {}", path, status, message);
+    }
+    serde_json::from_slice(body).wrap_err_with(|| format!("failed to decode the response to {}", path))
+}
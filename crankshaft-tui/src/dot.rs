@@ -0,0 +1,67 @@
+//! Export of the task dependency graph as Graphviz DOT.
+//!
+//! Nodes are filled with the status colors of the active theme, so a render
+//! with `dot -Tsvg` matches what the terminal shows.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+use ratatui::style::Color;
+
+use crate::app::Task;
+use crate::theme::Theme;
+
+/// Renders the tasks and the dependencies between them as a DOT digraph.
+///
+/// Dependencies on tasks outside of `tasks` are left out.
+pub fn to_dot<'a>(tasks: impl IntoIterator<Item = &'a Task>, theme: &Theme) -> String {
+    let tasks: Vec<&Task> = tasks.into_iter().collect();
+    let ids: HashSet<&str> = tasks.iter().map(|task| task.id.as_str()).collect();
+
+    let mut dot = String::from("digraph workflow {\n");
+    dot.push_str("    rankdir=LR;\n");
+    dot.push_str("    node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\"];\n");
+
+    for task in &tasks {
+        let _ = writeln!(
+            dot,
+            "    \"{}\" [label=\"{}\\n{}\", fillcolor=\"{}\"];",
+            escape(&task.id),
+            escape(&task.name),
+            task.status,
+            color_name(theme.status_color(task.status))
+        );
+    }
+    for task in &tasks {
+        for dependency in task.dependencies.iter().filter(|id| ids.contains(id.as_str())) {
+            let _ = writeln!(dot, "    \"{}\" -> \"{}\";", escape(dependency), escape(&task.id));
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// Writes the graph of `tasks` to a DOT file.
+pub fn write_dot<'a>(path: &Path, tasks: impl IntoIterator<Item = &'a Task>, theme: &Theme) -> io::Result<()> {
+    std::fs::write(path, to_dot(tasks, theme))
+}
+
+/// Escapes a string for use inside a quoted DOT identifier.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Converts a terminal color into a Graphviz color.
+fn color_name(color: Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        Color::Blue => "lightskyblue".to_string(),
+        Color::Yellow => "gold".to_string(),
+        Color::Green => "palegreen".to_string(),
+        Color::Red => "salmon".to_string(),
+        _ => "lightgray".to_string(),
+    }
+}
@@ -0,0 +1,80 @@
+//! Duration percentiles of completed tasks, per step.
+//!
+//! Shards of one step usually take about the same time; when they do not,
+//! the slowest shard decides when the step finishes. Grouping by step (see
+//! [`step_name`]) and comparing the median with the tail shows which step's
+//! variance dominates the total runtime.
+
+use std::collections::BTreeMap;
+
+use crate::app::{Task, TaskStatus};
+use crate::timeline::step_name;
+
+/// Duration statistics of one step, in seconds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepDurations {
+    /// The step name
+    pub step: String,
+    /// Number of completed tasks of the step
+    pub count: usize,
+    /// Median duration
+    pub p50: u64,
+    /// 90th percentile duration
+    pub p90: u64,
+    /// Longest duration
+    pub max: u64,
+}
+
+impl StepDurations {
+    /// Returns how much longer the slowest task took than the median one,
+    /// which is what the step's variance adds to the runtime.
+    pub fn spread(&self) -> u64 {
+        self.max - self.p50
+    }
+}
+
+/// Returns the durations of completed tasks grouped by step, the step with
+/// the largest spread first.
+pub fn by_step<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> Vec<StepDurations> {
+    by_step_including(tasks, &BTreeMap::new())
+}
+
+/// Returns the durations of completed tasks grouped by step like
+/// [`by_step`], counting along with them the `earlier` durations of tasks
+/// no longer in the store, by step.
+pub fn by_step_including<'a>(tasks: impl IntoIterator<Item = &'a Task>, earlier: &BTreeMap<String, Vec<u64>>) -> Vec<StepDurations> {
+    let mut grouped: BTreeMap<&str, Vec<u64>> =
+        earlier.iter().map(|(step, durations)| (step.as_str(), durations.clone())).collect();
+    for task in tasks {
+        if task.status != TaskStatus::Completed {
+            continue;
+        }
+        let (Some(started), Some(finished)) = (task.started_at, task.finished_at) else {
+            continue;
+        };
+        grouped.entry(step_name(&task.name)).or_default().push(finished.saturating_sub(started));
+    }
+
+    let mut steps: Vec<StepDurations> = grouped
+        .into_iter()
+        .map(|(step, mut durations)| {
+            durations.sort_unstable();
+            StepDurations {
+                step: step.to_string(),
+                count: durations.len(),
+                p50: percentile(&durations, 50),
+                p90: percentile(&durations, 90),
+                max: durations[durations.len() - 1],
+            }
+        })
+        .collect();
+    // Stable, so equal spreads stay in name order
+    steps.sort_by_key(|step| std::cmp::Reverse(step.spread()));
+    steps
+}
+
+/// Returns the nearest-rank percentile of sorted, non-empty durations.
+fn percentile(sorted: &[u64], percent: usize) -> u64 {
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
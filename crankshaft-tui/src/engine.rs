@@ -0,0 +1,164 @@
+//! Connection to an engine serving the monitor's REST protocol.
+//!
+//! The engine is reached over the REST protocol of [`crate::protocol`],
+//! which the mock engine serves; this is not the Crankshaft engine's own
+//! gRPC monitoring service, which the monitor does not speak. A background thread fetches the task list once and
+//! then follows the event stream, decoding each line into a [`TaskUpdate`]
+//! that the app takes on its next poll, so a slow engine never holds up
+//! drawing. Lines that cannot be decoded are counted as dropped and skipped
+//! instead of ending the connection, and progress is coalesced when the app
+//! falls behind the stream (see [`crate::backpressure`]). When the
+//! connection drops, the thread connects again after a backoff and fetches
+//! the task list afresh (see [`crate::reconnect`]). A token for the engine
+//! is sent with every request (see [`crate::auth`]), and an engine served
+//! over HTTPS may be signed by a private authority (see [`crate::tls`]).
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use eyre::WrapErr;
+
+use crate::app::TaskUpdate;
+use crate::auth::{self, RemoteAccess};
+use crate::backpressure::{self, Inbox, Message, Outbox};
+use crate::capabilities::SourceCapabilities;
+use crate::protocol::{decode_update, TaskList, EVENTS_PATH, TASKS_PATH};
+use crate::reconnect::{self, Backoff};
+use crate::source::{DataSource, SourceEvent};
+
+/// How long connecting to the engine may take before it is abandoned.
+///
+/// Only connecting is bounded: the event stream stays open for as long as
+/// the engine keeps it open.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A connection to an engine, followed on a background thread.
+pub struct EngineConnection {
+    /// Base URL of the engine, without a trailing slash
+    url: String,
+    /// Receives what happens on the connection
+    inbox: Inbox,
+    /// Events received with the last poll's updates, not yet taken
+    events: Vec<SourceEvent>,
+}
+
+impl EngineConnection {
+    /// Starts connecting to the engine at `url`, such as
+    /// `http://127.0.0.1:7878`.
+    pub fn connect(url: &str) -> Self {
+        Self::connect_with(url, RemoteAccess::default())
+    }
+
+    /// Starts connecting to the engine at `url` with `access`'s token and
+    /// TLS settings.
+    pub fn connect_with(url: &str, access: RemoteAccess) -> Self {
+        let url = url.trim_end_matches('/').to_string();
+        let (sender, inbox) = backpressure::channel();
+        let base = url.clone();
+        thread::spawn(move || {
+            reconnect::follow_with_retries(&base, &sender, |backoff| {
+                follow(&base, &access, &sender, backoff).map(|()| "the engine closed the event stream".to_string())
+            });
+        });
+        Self {
+            url,
+            inbox,
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns the engine's base URL.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl DataSource for EngineConnection {
+    fn name(&self) -> &str {
+        "engine"
+    }
+
+    /// Everything the protocol carries; it has no endpoints for control
+    /// actions.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            control: false,
+            ..SourceCapabilities::ALL
+        }
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        self.inbox.poll(&mut self.events)
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// Fetches the task list and then follows the event stream until it ends,
+/// the connection fails, or the app is gone, resetting `backoff` once the
+/// engine is reached.
+fn follow(base: &str, access: &RemoteAccess, sender: &Outbox, backoff: &mut Backoff) -> eyre::Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .wrap_err("failed to start the engine connection runtime")?;
+
+    runtime.block_on(async {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("crankshaft-tui/", env!("CARGO_PKG_VERSION")))
+            .connect_timeout(CONNECT_TIMEOUT);
+        let client = access.tls.apply(client)?.build()?;
+        let token = access.token.as_deref();
+
+        let started = Instant::now();
+        let list: TaskList = auth::authorize(client.get(format!("{}{}", base, TASKS_PATH)), token)
+            .send()
+            .await
+            .map_err(eyre::Report::from)
+            .and_then(|response| auth::check(response, "engine", token.is_some()))
+            .wrap_err_with(|| format!("failed to reach the engine at {}", base))?
+            .json()
+            .await
+            .wrap_err("failed to decode the task list")?;
+        backoff.reset();
+        let mut messages = vec![
+            Message::Event(SourceEvent::Connected { latency: started.elapsed() }),
+            Message::Event(SourceEvent::Message(format!("Connected to {}", base))),
+        ];
+        messages.extend(list.tasks.into_iter().map(|task| Message::Update(TaskUpdate::Created(Box::new(task)))));
+        for message in messages {
+            if sender.send(message).is_err() {
+                return Ok(());
+            }
+        }
+
+        let mut response = auth::authorize(client.get(format!("{}{}", base, EVENTS_PATH)), token)
+            .send()
+            .await
+            .map_err(eyre::Report::from)
+            .and_then(|response| auth::check(response, "engine", token.is_some()))
+            .wrap_err("failed to open the event stream")?;
+        let mut pending = Vec::new();
+        while let Some(chunk) = response.chunk().await.wrap_err("the event stream was interrupted")? {
+            pending.extend_from_slice(&chunk);
+            while let Some(end) = pending.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                let message = match decode_update(line) {
+                    Ok(update) => Message::Update(update),
+                    Err(err) => Message::Event(SourceEvent::Malformed { kind: err.kind, detail: format!("{}: {}", err, line) }),
+                };
+                if sender.send(message).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    })
+}
@@ -1,17 +1,30 @@
 //! Event handling for the TUI.
+//!
+//! Input and ticks reach the app through a bounded channel. At most one
+//! tick waits in it at a time: while the app is busy, ticks falling due
+//! find the last one still unread and are skipped, so a stalled app picks
+//! up with a single tick instead of replaying every one it missed in a
+//! burst. Input is never skipped; once the channel is full, the handler
+//! thread waits for the app before reading more.
 
 use std::{
-    sync::mpsc,
+    sync::atomic::{AtomicBool, Ordering},
+    sync::{mpsc::{self, TrySendError}, Arc},
     thread,
     time::{Duration, Instant},
 };
 
-use crossterm::event::{self, Event as CrosstermEvent, KeyEvent};
+use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, MouseEvent};
+
+/// Events the channel holds before the handler thread waits for the app.
+const CAPACITY: usize = 64;
 
 /// Events that can occur in the application.
 pub enum Event {
     /// Input event (keyboard, mouse, etc.)
     Input(KeyEvent),
+    /// Mouse movement, clicks, and scrolling
+    Mouse(MouseEvent),
     /// Tick event for updating the UI
     Tick,
 }
@@ -20,38 +33,71 @@ pub enum Event {
 pub struct EventHandler {
     /// Event sender channel
     #[allow(dead_code)]
-    sender: mpsc::Sender<Event>,
+    sender: mpsc::SyncSender<Event>,
     /// Event receiver channel
     receiver: mpsc::Receiver<Event>,
+    /// Set while a tick is in the channel, unread
+    tick_pending: Arc<AtomicBool>,
     /// Event handler thread
     #[allow(dead_code)]
     handler: thread::JoinHandle<()>,
+    /// Set while another program has the terminal and must get its input
+    paused: Arc<AtomicBool>,
+    /// Receives the handler thread's word that it stopped reading input
+    paused_ack: mpsc::Receiver<()>,
+    /// How often ticks are sent, which bounds a wait for a pause
+    tick_rate: Duration,
 }
 
 impl EventHandler {
     /// Creates a new event handler with the specified tick rate.
     pub fn new(tick_rate: Duration) -> Self {
-        let (sender, receiver) = mpsc::channel();
+        let (sender, receiver) = mpsc::sync_channel(CAPACITY);
+        let tick_pending = Arc::new(AtomicBool::new(false));
+        let paused = Arc::new(AtomicBool::new(false));
+        let (ack_sender, paused_ack) = mpsc::channel();
         let handler = {
             let sender = sender.clone();
+            let tick_pending = Arc::clone(&tick_pending);
+            let paused = Arc::clone(&paused);
             thread::spawn(move || {
                 let mut last_tick = Instant::now();
+                let mut acknowledged = false;
                 loop {
+                    if paused.load(Ordering::SeqCst) {
+                        if !acknowledged {
+                            acknowledged = ack_sender.send(()).is_ok();
+                        }
+                        thread::sleep(Duration::from_millis(10));
+                        continue;
+                    }
+                    acknowledged = false;
+
                     let timeout = tick_rate
                         .checked_sub(last_tick.elapsed())
                         .unwrap_or(Duration::from_secs(0));
 
                     if event::poll(timeout).expect("Failed to poll for events") {
-                        if let CrosstermEvent::Key(key) = event::read().expect("Failed to read event") {
-                            if let Err(_) = sender.send(Event::Input(key)) {
+                        let event = match event::read().expect("Failed to read event") {
+                            CrosstermEvent::Key(key) => Some(Event::Input(key)),
+                            CrosstermEvent::Mouse(mouse) => Some(Event::Mouse(mouse)),
+                            _ => None,
+                        };
+                        if let Some(event) = event {
+                            if sender.send(event).is_err() {
                                 return;
                             }
                         }
                     }
 
                     if last_tick.elapsed() >= tick_rate {
-                        if let Err(_) = sender.send(Event::Tick) {
-                            return;
+                        // A tick still unread covers this one too
+                        if !tick_pending.swap(true, Ordering::SeqCst) {
+                            match sender.try_send(Event::Tick) {
+                                Ok(()) => {}
+                                Err(TrySendError::Full(_)) => tick_pending.store(false, Ordering::SeqCst),
+                                Err(TrySendError::Disconnected(_)) => return,
+                            }
                         }
                         last_tick = Instant::now();
                     }
@@ -62,12 +108,34 @@ impl EventHandler {
         Self {
             sender,
             receiver,
+            tick_pending,
             handler,
+            paused,
+            paused_ack,
+            tick_rate,
         }
     }
 
+    /// Stops reading terminal input, and sending ticks, until
+    /// [`resume`](Self::resume), so that a program run in the foreground
+    /// gets every key. Returns once the handler thread has stopped.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+        // The thread finishes waiting for input first, for at most a tick
+        let _ = self.paused_ack.recv_timeout(self.tick_rate + Duration::from_secs(1));
+    }
+
+    /// Reads terminal input again after [`pause`](Self::pause).
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
     /// Gets the next event from the handler.
     pub fn next(&self) -> Result<Event, mpsc::RecvError> {
-        self.receiver.recv()
+        let event = self.receiver.recv()?;
+        if let Event::Tick = event {
+            self.tick_pending.store(false, Ordering::SeqCst);
+        }
+        Ok(event)
     }
 }
\ No newline at end of file
@@ -0,0 +1,67 @@
+//! Handing task specs to external programs.
+//!
+//! The diff overlay (`E`) lines up the fields the monitor knows about. When
+//! that is not enough, `O` writes the full specs of the reference task and
+//! the selected task to temporary files and opens them in the diff tool set
+//! as `diff` under `[tools]` in the configuration, such as `vimdiff` or
+//! `meld`. The dashboard is suspended until the tool exits.
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+use crate::app::Task;
+
+/// A program to run in the foreground with the terminal handed to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalCommand {
+    /// The program, found on `PATH` unless it is a path
+    pub program: String,
+    /// Its arguments
+    pub args: Vec<String>,
+}
+
+impl ExternalCommand {
+    /// Returns the command comparing two files with `tool`. The tool is
+    /// split on whitespace into a program and its leading arguments, and the
+    /// two paths are appended; returns `None` if it is blank.
+    pub fn diff(tool: &str, reference: &Path, task: &Path) -> Option<Self> {
+        let mut words = tool.split_whitespace().map(str::to_string);
+        let program = words.next()?;
+        let args = words.chain([reference, task].map(|path| path.display().to_string())).collect();
+        Some(Self { program, args })
+    }
+
+    /// Runs the command and waits for it to exit.
+    pub fn run(&self) -> io::Result<ExitStatus> {
+        Command::new(&self.program).args(&self.args).status()
+    }
+}
+
+impl fmt::Display for ExternalCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.program)?;
+        for arg in &self.args {
+            write!(f, " {}", arg)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a task's spec, the payload its backend reported, to a file in a
+/// temporary directory of this process, returning its path. Writing the
+/// same task again replaces the file.
+pub fn write_spec(task: &Task) -> io::Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("crankshaft-tui-specs-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    // Task IDs are chosen by the backend and may contain path separators
+    let name: String = task
+        .id
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    let path = dir.join(format!("{}.json", name));
+    std::fs::write(&path, task.raw_json() + "\n")?;
+    Ok(path)
+}
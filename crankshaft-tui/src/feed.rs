@@ -0,0 +1,189 @@
+//! The run's narrative log, shown in the Events tab.
+//!
+//! Notable moments are kept as they happen: tasks starting, finishing, and
+//! failing, a backend connection dropping or failing, alerts being raised,
+//! and moments bookmarked with `m`. The tab lists them newest first; `f`
+//! narrows the list to one kind of event at a time, and Enter jumps to the
+//! task an event is about.
+//!
+//! Only changes seen during the session are recorded, not the state tasks
+//! were already in when the monitor started, and the oldest events give way
+//! once [`MAX_EVENTS`] are kept.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::app::TaskStatus;
+
+/// Number of events kept.
+pub const MAX_EVENTS: usize = 5_000;
+
+/// What kind of moment an event records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// A task started running
+    Started,
+    /// A task completed
+    Finished,
+    /// A task failed
+    Failed,
+    /// A backend connection dropped or failed
+    Backend,
+    /// A moment bookmarked by the user, or by whoever recorded a replay
+    Bookmark,
+    /// An alert rule started to hold for a task
+    Alert,
+}
+
+impl EventKind {
+    /// Every kind, in the order the filter cycles through them
+    pub const ALL: [EventKind; 6] = [
+        EventKind::Started,
+        EventKind::Finished,
+        EventKind::Failed,
+        EventKind::Backend,
+        EventKind::Bookmark,
+        EventKind::Alert,
+    ];
+
+    /// Returns the kind of event a status change is, if it is notable.
+    pub fn of_status(to: TaskStatus) -> Option<Self> {
+        match to {
+            TaskStatus::Running => Some(EventKind::Started),
+            TaskStatus::Completed => Some(EventKind::Finished),
+            TaskStatus::Failed => Some(EventKind::Failed),
+            TaskStatus::Pending => None,
+        }
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EventKind::Started => "started",
+            EventKind::Finished => "finished",
+            EventKind::Failed => "failed",
+            EventKind::Backend => "backend",
+            EventKind::Bookmark => "bookmark",
+            EventKind::Alert => "alert",
+        })
+    }
+}
+
+/// A notable moment of the run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedEvent {
+    /// Position of the event in the session, counting from 0
+    pub seq: u64,
+    /// When it happened, in seconds since the Unix epoch
+    pub at: u64,
+    /// What kind of moment it was
+    pub kind: EventKind,
+    /// The task it is about, if any
+    pub task_id: Option<String>,
+    /// What happened, such as the reason a connection dropped or a
+    /// bookmark's note
+    pub text: String,
+}
+
+/// The events of the session, with the Events tab's filter and selection.
+#[derive(Debug, Clone, Default)]
+pub struct EventFeed {
+    /// Events kept, oldest first
+    events: VecDeque<FeedEvent>,
+    /// Number of events recorded, including those no longer kept
+    recorded: u64,
+    /// Kind of event listed, or `None` for every kind
+    filter: Option<EventKind>,
+    /// Sequence number of the selected event
+    selected: Option<u64>,
+}
+
+impl EventFeed {
+    /// Records an event.
+    pub fn push(&mut self, at: u64, kind: EventKind, task_id: Option<String>, text: impl Into<String>) {
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(FeedEvent {
+            seq: self.recorded,
+            at,
+            kind,
+            task_id,
+            text: text.into(),
+        });
+        self.recorded += 1;
+    }
+
+    /// Returns the number of events kept.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if no event was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns the events the filter lets through, newest first.
+    pub fn visible(&self) -> impl Iterator<Item = &FeedEvent> {
+        let filter = self.filter;
+        self.events.iter().rev().filter(move |event| match filter {
+            Some(kind) => event.kind == kind,
+            None => true,
+        })
+    }
+
+    /// Returns the kind of event listed, if the list is filtered.
+    pub fn filter(&self) -> Option<EventKind> {
+        self.filter
+    }
+
+    /// Lists the next kind of event, after the last going back to every
+    /// kind, and returns the kind now listed.
+    pub fn cycle_filter(&mut self) -> Option<EventKind> {
+        self.filter = match self.filter {
+            None => Some(EventKind::ALL[0]),
+            Some(kind) => EventKind::ALL
+                .iter()
+                .position(|all| *all == kind)
+                .and_then(|index| EventKind::ALL.get(index + 1))
+                .copied(),
+        };
+        self.filter
+    }
+
+    /// Returns the selected event, or the newest one listed if the
+    /// selection is filtered out or gone.
+    pub fn selected(&self) -> Option<&FeedEvent> {
+        self.visible()
+            .find(|event| Some(event.seq) == self.selected)
+            .or_else(|| self.visible().next())
+    }
+
+    /// Returns the position of the selected event in the list.
+    pub fn selected_index(&self) -> usize {
+        let Some(selected) = self.selected() else {
+            return 0;
+        };
+        self.visible().position(|event| event.seq == selected.seq).unwrap_or(0)
+    }
+
+    /// Selects the next older event.
+    pub fn select_older(&mut self) {
+        let index = self.selected_index() + 1;
+        let seq = self.visible().nth(index).map(|event| event.seq);
+        if seq.is_some() {
+            self.selected = seq;
+        }
+    }
+
+    /// Selects the next newer event.
+    pub fn select_newer(&mut self) {
+        let Some(index) = self.selected_index().checked_sub(1) else {
+            return;
+        };
+        let seq = self.visible().nth(index).map(|event| event.seq);
+        self.selected = seq;
+    }
+}
@@ -0,0 +1,221 @@
+//! Tasks read from a state file the engine writes.
+//!
+//! An engine without a network endpoint can still be monitored if it keeps
+//! the state of its tasks in a file: the file is read again whenever its
+//! size or modification time changes, checked on every tick, and the tasks
+//! that appeared, changed, or disappeared since the last read are applied.
+//! The file holds the full state of every task, either as one JSON document,
+//! in the shape of the engine's task listing or as a bare array:
+//!
+//! ```json
+//! {"tasks": [{"id": "align", "name": "align reads", "status": "running", "progress": 0.5, "started_at": 1700000000}]}
+//! ```
+//!
+//! or as JSON lines, one task per line, which suits engines that append
+//! and rewrite it as they go:
+//!
+//! ```text
+//! {"id": "align", "name": "align reads", "status": "completed", "progress": 1.0, "started_at": 1700000000, "finished_at": 1700000420}
+//! {"id": "call", "name": "call variants", "status": "pending"}
+//! ```
+//!
+//! Tasks use the fields of the engine's task listing (see
+//! [`crate::protocol`]). When a line names a task already read, the later
+//! line wins. Lines that are not tasks are skipped and counted as dropped
+//! in the diagnostics overlay. A document that does not parse, or a last
+//! line cut short, is likely caught halfway through being written, so it
+//! leaves the tasks as they were until the file changes again.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+
+use serde_json::Value;
+
+use crate::app::{Task, TaskUpdate};
+use crate::capabilities::SourceCapabilities;
+use crate::protocol::TaskList;
+use crate::source::{DataSource, SourceEvent};
+
+/// A state file, read again whenever it changes.
+pub struct FileDataSource {
+    /// Path of the file
+    path: PathBuf,
+    /// Size and modification time of the file when last read, or `None`
+    /// before the first read or while it cannot be read
+    read: Option<(u64, Option<SystemTime>)>,
+    /// Why the file could not be read, if that was already reported
+    failure: Option<String>,
+    /// The task last handed over for each task in the file, to send only
+    /// changes
+    reported: HashMap<String, Task>,
+    /// Events found by the last poll, not yet taken
+    events: Vec<SourceEvent>,
+}
+
+impl FileDataSource {
+    /// Starts following the state file at `path`, which need not exist yet.
+    pub fn watch(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            read: None,
+            failure: None,
+            reported: HashMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns the path of the state file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reports that the file cannot be read, once for each reason.
+    fn fail(&mut self, reason: String) {
+        if self.failure.as_ref() != Some(&reason) {
+            self.events.push(SourceEvent::Failed(reason.clone()));
+            self.failure = Some(reason);
+        }
+    }
+}
+
+impl DataSource for FileDataSource {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    /// Whatever a task in the file can hold: there is no log stream, and
+    /// nobody to send control actions to.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            logs: false,
+            control: false,
+            ..SourceCapabilities::ALL
+        }
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        let started = Instant::now();
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                self.read = None;
+                self.fail(format!("Cannot read {}: {}", self.path.display(), err));
+                return Vec::new();
+            }
+        };
+        // Metadata is read before the contents, so a write that lands while
+        // reading changes it again and brings another read
+        let signature = (metadata.len(), metadata.modified().ok());
+        if self.read == Some(signature) {
+            return Vec::new();
+        }
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                self.read = None;
+                self.fail(format!("Cannot read {}: {}", self.path.display(), err));
+                return Vec::new();
+            }
+        };
+        self.read = Some(signature);
+        let tasks = match parse(&contents) {
+            Ok((tasks, dropped)) => {
+                for (number, err) in dropped {
+                    let detail = format!("{} line {}: {}", self.path.display(), number, err);
+                    self.events.push(SourceEvent::Dropped(detail));
+                }
+                tasks
+            }
+            Err(err) => {
+                self.fail(format!("Cannot parse {}: {}", self.path.display(), err));
+                return Vec::new();
+            }
+        };
+        if self.failure.take().is_some() || self.reported.is_empty() && !tasks.is_empty() {
+            let message = format!("Read {} tasks from {}", tasks.len(), self.path.display());
+            self.events.push(SourceEvent::Message(message));
+        }
+        self.events.push(SourceEvent::Connected { latency: started.elapsed() });
+
+        let mut updates = Vec::new();
+        let mut stale: Vec<String> = self.reported.keys().cloned().collect();
+        for task in tasks {
+            stale.retain(|id| id != &task.id);
+            let unchanged = self
+                .reported
+                .get(&task.id)
+                .is_some_and(|previous| serde_json::to_value(previous).ok() == serde_json::to_value(&task).ok());
+            if !unchanged {
+                self.reported.insert(task.id.clone(), task.clone());
+                updates.push(TaskUpdate::Created(Box::new(task)));
+            }
+        }
+        for id in stale {
+            self.reported.remove(&id);
+            updates.push(TaskUpdate::Removed { id });
+        }
+        updates
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// Tasks read from a state file, with the lines skipped by number.
+type Parsed = (Vec<Task>, Vec<(usize, serde_json::Error)>);
+
+/// Parses the contents of a state file into its tasks, in file order,
+/// along with the line number and error of each line that was skipped.
+/// Fails if the file is a JSON document that is not a list of tasks, or
+/// JSON lines whose last line is unfinished.
+fn parse(contents: &str) -> serde_json::Result<Parsed> {
+    // JSON lines start with a task on a line of its own; anything else is
+    // one document, perhaps spread over several lines
+    let first = contents.lines().find(|line| !line.trim().is_empty()).unwrap_or_default();
+    let lines = matches!(serde_json::from_str(first), Ok(Value::Object(object)) if !object.contains_key("tasks"));
+    if !lines {
+        if contents.trim().is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+        let tasks = match serde_json::from_str(contents)? {
+            Value::Object(object) if !object.contains_key("tasks") => vec![serde_json::from_value(Value::Object(object))?],
+            Value::Object(object) => serde_json::from_value::<TaskList>(Value::Object(object))?.tasks,
+            document => serde_json::from_value(document)?,
+        };
+        return Ok((dedup(tasks), Vec::new()));
+    }
+
+    let mut tasks = Vec::new();
+    let mut dropped = Vec::new();
+    let count = contents.lines().count();
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Task>(line) {
+            Ok(task) => tasks.push(task),
+            Err(err) if index + 1 == count && !contents.ends_with('\n') => return Err(err),
+            Err(err) => dropped.push((index + 1, err)),
+        }
+    }
+    Ok((dedup(tasks), dropped))
+}
+
+/// Keeps the last of the tasks sharing an ID, in the place of the first.
+fn dedup(tasks: Vec<Task>) -> Vec<Task> {
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut unique: Vec<Task> = Vec::new();
+    for task in tasks {
+        match index.get(&task.id) {
+            Some(&at) => unique[at] = task,
+            None => {
+                index.insert(task.id.clone(), unique.len());
+                unique.push(task);
+            }
+        }
+    }
+    unique
+}
@@ -0,0 +1,247 @@
+//! Human-readable formatting of values shown in the UI.
+//!
+//! Numbers follow the separators of the configured locale (or the one named
+//! by the environment); units are always written in English abbreviations.
+
+use std::time::Duration;
+
+/// Formats seconds since the Unix epoch as a UTC date and time,
+/// e.g. `2024-03-09 14:05:00 UTC`.
+pub fn timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// Formats seconds since the Unix epoch as a UTC time of day, e.g.
+/// `14:05:00`.
+pub fn clock(secs: u64) -> String {
+    let rem = secs % 86_400;
+    format!("{:02}:{:02}:{:02}", rem / 3600, rem % 3600 / 60, rem % 60)
+}
+
+/// Converts days since 1970-01-01 into a proleptic Gregorian date.
+///
+/// This is Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Parses an RFC 3339 timestamp, such as `2024-05-01T12:30:00.5+02:00`,
+/// into seconds since the Unix epoch.
+pub(crate) fn parse_timestamp(text: &str) -> Option<u64> {
+    let (date, time) = text.split_once(['T', 't', ' '])?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+
+    // The offset follows the seconds and their fraction
+    let offset_at = time.find(['Z', 'z', '+', '-']).unwrap_or(time.len());
+    let (clock, offset) = time.split_at(offset_at);
+    let mut clock = clock.splitn(3, ':');
+    let hour: i64 = clock.next()?.parse().ok()?;
+    let minute: i64 = clock.next()?.parse().ok()?;
+    let second: i64 = clock.next()?.split('.').next()?.parse().ok()?;
+    let offset = match offset.as_bytes().first() {
+        None | Some(b'Z' | b'z') => 0,
+        Some(sign) => {
+            let (hours, minutes) = offset[1..].split_once(':')?;
+            let seconds = hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60;
+            if *sign == b'-' { -seconds } else { seconds }
+        }
+    };
+
+    let seconds = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second - offset;
+    u64::try_from(seconds).ok()
+}
+
+/// Returns the number of days from 1970-01-01 to a date in the proleptic
+/// Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Separators used when rendering numbers, derived from a locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Separator between the integer and fractional part
+    pub decimal: char,
+    /// Separator between groups of three digits
+    pub group: char,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            decimal: '.',
+            group: ',',
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Returns the separators for a POSIX-style locale name such as
+    /// `de_DE.UTF-8`. Unknown locales use English conventions.
+    pub fn from_locale(locale: &str) -> Self {
+        let language = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el" => Self {
+                decimal: ',',
+                group: '.',
+            },
+            "fr" | "ru" | "pl" | "sv" | "fi" | "nb" | "no" | "cs" | "sk" | "uk" | "hu" => Self {
+                decimal: ',',
+                group: '\u{202f}',
+            },
+            _ => Self::default(),
+        }
+    }
+
+    /// Returns the separators for the locale named by the environment
+    /// (`LC_ALL`, `LC_NUMERIC`, then `LANG`).
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .map(|locale| Self::from_locale(&locale))
+            .unwrap_or_default()
+    }
+
+    /// Formats an integer with group separators, e.g. `12,345`.
+    pub fn integer(&self, value: u64) -> String {
+        let digits = value.to_string();
+        let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+        let mut until_group = match digits.len() % 3 {
+            0 => 3,
+            n => n,
+        };
+        for digit in digits.chars() {
+            if until_group == 0 {
+                out.push(self.group);
+                until_group = 3;
+            }
+            out.push(digit);
+            until_group -= 1;
+        }
+        out
+    }
+
+    /// Formats a value with a fixed number of fractional digits.
+    pub fn decimal(&self, value: f64, places: usize) -> String {
+        let text = format!("{:.*}", places, value.abs());
+        let (whole, fraction) = text.split_once('.').unwrap_or((&text, ""));
+        let mut out = String::new();
+        if value < 0.0 && text.chars().any(|c| c != '0' && c != '.') {
+            out.push('-');
+        }
+        out.push_str(&self.integer(whole.parse().unwrap_or(0)));
+        if !fraction.is_empty() {
+            out.push(self.decimal);
+            out.push_str(fraction);
+        }
+        out
+    }
+
+    /// Formats a ratio in `0.0..=1.0` as a percentage, e.g. `42.5%`.
+    pub fn percent(&self, ratio: f64) -> String {
+        format!("{}%", self.decimal(ratio * 100.0, 1))
+    }
+
+    /// Formats a count compactly, e.g. `999`, `12.3k`, `4.5M`.
+    pub fn count(&self, value: u64) -> String {
+        const UNITS: [(f64, &str); 3] = [(1e9, "G"), (1e6, "M"), (1e3, "k")];
+        let value_f = value as f64;
+        for (scale, suffix) in UNITS {
+            if value_f >= scale {
+                return format!("{}{}", self.decimal(value_f / scale, 1), suffix);
+            }
+        }
+        value.to_string()
+    }
+
+    /// Formats a byte size using binary units, e.g. `512 B`, `1.4 GiB`.
+    pub fn bytes(&self, value: u64) -> String {
+        let (scale, unit) = byte_unit(value);
+        if scale == 1 {
+            return format!("{} {}", value, unit);
+        }
+        format!("{} {}", self.decimal(value as f64 / scale as f64, 1), unit)
+    }
+
+    /// Formats an amount out of a total in the total's unit, e.g.
+    /// `3.2/8.0 GiB`.
+    pub fn bytes_of(&self, value: u64, total: u64) -> String {
+        let (scale, unit) = byte_unit(total);
+        let places = if scale == 1 { 0 } else { 1 };
+        format!(
+            "{}/{} {}",
+            self.decimal(value as f64 / scale as f64, places),
+            self.decimal(total as f64 / scale as f64, places),
+            unit
+        )
+    }
+
+    /// Formats a duration, e.g. `1h 23m 45s`, `4m 05s`, `12s`, or `2d 3h` for
+    /// multi-day spans. Durations under ten seconds show tenths (`3.2s`), and
+    /// under one second milliseconds (`250ms`).
+    pub fn duration(&self, duration: Duration) -> String {
+        let secs = duration.as_secs();
+        if secs == 0 {
+            return format!("{}ms", duration.as_millis());
+        }
+        if secs < 10 {
+            return format!("{}s", self.decimal(duration.as_secs_f64(), 1));
+        }
+        let (days, hours, minutes, seconds) = (secs / 86_400, secs % 86_400 / 3600, secs % 3600 / 60, secs % 60);
+        if days > 0 {
+            format!("{}d {}h", days, hours)
+        } else if hours > 0 {
+            format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+        } else if minutes > 0 {
+            format!("{}m {:02}s", minutes, seconds)
+        } else {
+            format!("{}s", seconds)
+        }
+    }
+}
+
+/// Returns the largest binary unit not exceeding `value` and its size in
+/// bytes.
+fn byte_unit(value: u64) -> (u64, &'static str) {
+    const UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    let mut scale = 1u64;
+    let mut unit = 0;
+    while unit < UNITS.len() - 1 && value / scale >= 1024 {
+        scale <<= 10;
+        unit += 1;
+    }
+    (scale, UNITS[unit])
+}
@@ -0,0 +1,118 @@
+//! Explanations of the statuses and figures the dashboard shows.
+//!
+//! Pressing `?` opens a popover explaining what is shown for the selected
+//! task, starting with its status and going on to the figures in its details
+//! pane; ←/→ step through them, and Esc or `?` closes it. Without a task
+//! selected, the popover steps through the whole glossary.
+
+use crate::app::{App, Task};
+
+/// A term and what it means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    /// The term as the dashboard shows it
+    pub term: &'static str,
+    /// What it means
+    pub text: &'static str,
+}
+
+/// Every term explained, statuses first.
+pub const ENTRIES: &[Entry] = &[
+    Entry {
+        term: "Pending",
+        text: "The backend accepted the task but has not started it yet: it may be waiting in a queue, for resources, or for the tasks it depends on.",
+    },
+    Entry {
+        term: "Running",
+        text: "The backend reports the task as started and not yet finished. Its duration keeps counting until it completes or fails.",
+    },
+    Entry {
+        term: "Completed",
+        text: "The task finished successfully. Completed tasks count towards the step durations used to spot slow tasks.",
+    },
+    Entry {
+        term: "Failed",
+        text: "The task finished unsuccessfully, was cancelled, or was lost by the backend. Its logs and exit code usually tell why.",
+    },
+    Entry {
+        term: "Requeued",
+        text: "The backend retried the task after an earlier attempt failed or was preempted; the attempt number is shown next to its status.",
+    },
+    Entry {
+        term: "Queue position",
+        text: "How many jobs are ahead of the task in the scheduler's queue, as reported by backends such as Slurm or LSF.",
+    },
+    Entry {
+        term: "Expected start",
+        text: "When the scheduler expects to start the task, from its own estimate. It moves as jobs ahead finish early or late.",
+    },
+    Entry {
+        term: "Progress",
+        text: "The share of its work the task reports done. Between updates it is advanced at the task's last observed rate, unless `interpolate_progress` is off, and noisy reports can be smoothed with `smooth_progress` so the gauge never moves backwards.",
+    },
+    Entry {
+        term: "CPU usage",
+        text: "The share of its CPUs the task used over the last sample. Sampled containers also show how many CPUs that is out of their limit.",
+    },
+    Entry {
+        term: "Memory",
+        text: "Memory the task uses against its limit, or its request when it has no limit. A task near its limit may be killed by the backend.",
+    },
+    Entry {
+        term: "Slow",
+        text: "The task has run far longer than the median duration of its step's completed tasks (three times by default, set with `[anomalies] factor`); it may be stuck.",
+    },
+    Entry {
+        term: "Stale",
+        text: "No update for the running task has arrived for a while (a minute by default, set with `[display] stale_after_secs`), so its figures may no longer be current.",
+    },
+    Entry {
+        term: "Alert",
+        text: "A rule from the `[alerts]` configuration matched the task, such as memory staying above a threshold for a while.",
+    },
+    Entry {
+        term: "Backend",
+        text: "The backend that reports the task when several sources are combined, such as `engine` or `docker`. B filters the task list by it.",
+    },
+    Entry {
+        term: "Duration",
+        text: "How long the task has run: from its start until it finished, or until now while it runs. Time spent pending is not counted.",
+    },
+];
+
+/// Returns the entry for `term`, ignoring case.
+pub fn lookup(term: &str) -> Option<&'static Entry> {
+    ENTRIES.iter().find(|entry| entry.term.eq_ignore_ascii_case(term))
+}
+
+/// Returns the entries explaining what is shown for `task`, its status
+/// first.
+pub fn for_task(app: &App, task: &Task) -> Vec<&'static Entry> {
+    let mut terms = vec![task.status.to_string()];
+    let mut add = |shown: bool, term: &str| {
+        if shown {
+            terms.push(term.to_string());
+        }
+    };
+    add(task.is_requeued(), "Requeued");
+    add(task.queue.is_some(), "Queue position");
+    add(task.queue.is_some_and(|queue| queue.estimated_start.is_some()), "Expected start");
+    add(true, "Progress");
+    add(app.capabilities.cpu, "CPU usage");
+    add(app.capabilities.memory, "Memory");
+    add(app.anomalies.get(&task.id).is_some(), "Slow");
+    add(app.is_stale(task, crate::record::unix_now()), "Stale");
+    add(app.alerts.for_task(&task.id).next().is_some(), "Alert");
+    add(task.labels.contains_key("backend"), "Backend");
+    add(task.started_at.is_some(), "Duration");
+    terms.iter().filter_map(|term| lookup(term)).collect()
+}
+
+/// Returns the entries the popover steps through: those for the selected
+/// task, or the whole glossary.
+pub fn entries(app: &App) -> Vec<&'static Entry> {
+    match app.selected_task_id.as_ref().and_then(|id| app.tasks.get(id)) {
+        Some(task) => for_task(app, task),
+        None => ENTRIES.iter().collect(),
+    }
+}
@@ -0,0 +1,301 @@
+//! Charts drawn with the terminal's graphics protocol.
+//!
+//! Terminals that speak the kitty graphics protocol, or iTerm2's inline
+//! images (also understood by WezTerm), are sent the timeline's bars as an
+//! image instead of block characters: bars start and end where they should
+//! rather than on the nearest cell, and rows are set apart by a gap. The
+//! labels, legend, and axis stay text.
+//!
+//! The protocol is chosen from the environment when the app starts, and can
+//! be forced or turned off with `CRANKSHAFT_TUI_GRAPHICS` set to `kitty`,
+//! `iterm`, or `off`. Inside tmux or screen, which do not pass images
+//! through, and wherever no protocol is detected, the timeline is drawn with
+//! cells as before; the same happens in screen-reader mode and while an
+//! overlay is open over the chart. Images are only sent when they change.
+//!
+//! Only built with the `graphics` feature.
+
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+
+use crate::clipboard::encode_base64;
+
+/// Image pixels per terminal cell, across and down. Terminals scale the
+/// image to the cells it covers, so this only sets the resolution.
+pub const CELL_PIXELS: (u32, u32) = (8, 16);
+
+/// ID the kitty protocol knows the chart by, so that it can be replaced.
+const IMAGE_ID: u32 = 7_373;
+
+/// Base64 bytes sent per kitty escape sequence.
+const KITTY_CHUNK: usize = 4096;
+
+/// A terminal graphics protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// The kitty graphics protocol
+    Kitty,
+    /// iTerm2's inline images
+    Iterm,
+}
+
+impl Protocol {
+    /// Returns the protocol the terminal speaks, going by the environment.
+    pub fn detect() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        match var("CRANKSHAFT_TUI_GRAPHICS").as_str() {
+            "kitty" => return Some(Protocol::Kitty),
+            "iterm" => return Some(Protocol::Iterm),
+            "off" => return None,
+            _ => {}
+        }
+        // Multiplexers swallow the escape sequences
+        if std::env::var_os("TMUX").is_some() || var("TERM").starts_with("screen") {
+            return None;
+        }
+        if std::env::var_os("KITTY_WINDOW_ID").is_some() || var("TERM") == "xterm-kitty" || var("TERM_PROGRAM") == "ghostty" {
+            return Some(Protocol::Kitty);
+        }
+        match var("TERM_PROGRAM").as_str() {
+            "iTerm.app" | "WezTerm" => Some(Protocol::Iterm),
+            _ => None,
+        }
+    }
+}
+
+/// An RGBA image.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Image {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Pixels row by row, four bytes each
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Creates a transparent image.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// Returns the color of the pixel at (`x`, `y`).
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let at = (y as usize * self.width as usize + x as usize) * 4;
+        [self.pixels[at], self.pixels[at + 1], self.pixels[at + 2], self.pixels[at + 3]]
+    }
+
+    /// Colors the pixels from (`x0`, `y0`) up to but excluding (`x1`, `y1`)
+    /// for which `pattern` holds, clipped to the image.
+    pub fn fill(&mut self, (x0, y0): (u32, u32), (x1, y1): (u32, u32), rgba: [u8; 4], pattern: impl Fn(u32, u32) -> bool) {
+        for y in y0..y1.min(self.height) {
+            for x in x0..x1.min(self.width) {
+                if pattern(x, y) {
+                    let at = (y as usize * self.width as usize + x as usize) * 4;
+                    self.pixels[at..at + 4].copy_from_slice(&rgba);
+                }
+            }
+        }
+    }
+
+    /// Encodes the image as a PNG.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&self.width.to_be_bytes());
+        header.extend_from_slice(&self.height.to_be_bytes());
+        // 8 bits per channel, RGBA, default compression, filter, no interlace
+        header.extend_from_slice(&[8, 6, 0, 0, 0]);
+        png_chunk(&mut png, b"IHDR", &header);
+
+        let mut rows = Vec::with_capacity(self.pixels.len() + self.height as usize);
+        for row in self.pixels.chunks(self.width as usize * 4) {
+            rows.push(0);
+            rows.extend_from_slice(row);
+        }
+        png_chunk(&mut png, b"IDAT", &deflate(&rows));
+        png_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+/// Compresses `data` in the zlib format.
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+    // Writing to a vector cannot fail
+    let _ = encoder.write_all(data);
+    encoder.finish().unwrap_or_default()
+}
+
+/// Appends a PNG chunk of `kind` holding `data`.
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Returns the CRC-32 checksum PNG chunks end with.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { 0xedb8_8320 ^ (crc >> 1) } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Returns the RGB value a terminal usually shows `color` as.
+pub fn rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::Rgb(r, g, b) => [r, g, b],
+        Color::Black => [0, 0, 0],
+        Color::Red => [205, 49, 49],
+        Color::Green => [13, 188, 121],
+        Color::Yellow => [229, 229, 16],
+        Color::Blue => [36, 114, 200],
+        Color::Magenta => [188, 63, 188],
+        Color::Cyan => [17, 168, 205],
+        Color::Gray => [229, 229, 229],
+        Color::DarkGray => [102, 102, 102],
+        Color::LightRed => [241, 76, 76],
+        Color::LightGreen => [35, 209, 139],
+        Color::LightYellow => [245, 245, 67],
+        Color::LightBlue => [59, 142, 234],
+        Color::LightMagenta => [214, 112, 214],
+        Color::LightCyan => [41, 184, 219],
+        _ => [255, 255, 255],
+    }
+}
+
+/// An image placed over the dashboard in the frame being drawn.
+#[derive(Debug)]
+struct Placement {
+    /// Cells the image covers
+    area: Rect,
+    /// The image
+    image: Image,
+}
+
+/// Images drawn over the dashboard with a graphics protocol.
+#[derive(Debug)]
+pub struct Graphics {
+    /// Protocol the terminal speaks
+    protocol: Protocol,
+    /// Image placed while drawing the current frame, written after it
+    placed: RefCell<Option<Placement>>,
+    /// Cells and hash of the image on screen, if any
+    shown: Cell<Option<(Rect, u64)>>,
+}
+
+impl Graphics {
+    /// Draws images with `protocol`.
+    pub fn new(protocol: Protocol) -> Self {
+        Self {
+            protocol,
+            placed: RefCell::new(None),
+            shown: Cell::new(None),
+        }
+    }
+
+    /// Returns graphics for the protocol the terminal speaks, if any.
+    pub fn detect() -> Option<Self> {
+        Protocol::detect().map(Self::new)
+    }
+
+    /// Returns the protocol images are drawn with.
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Places `image` over `area` once the frame being drawn is written.
+    pub fn place(&self, area: Rect, image: Image) {
+        *self.placed.borrow_mut() = Some(Placement { area, image });
+    }
+
+    /// Forgets what is on screen, after it was cleared.
+    pub fn invalidate(&self) {
+        self.shown.set(None);
+    }
+
+    /// Writes the image placed while drawing the last frame to `out`, unless
+    /// it is already shown, and removes one no longer placed.
+    ///
+    /// Returns `true` if the screen must be cleared to remove an image,
+    /// which iTerm2's images only are once their cells are redrawn.
+    pub fn flush(&self, out: &mut impl Write) -> io::Result<bool> {
+        let Some(Placement { area, image }) = self.placed.borrow_mut().take() else {
+            return match (self.shown.take(), self.protocol) {
+                (None, _) => Ok(false),
+                (Some(_), Protocol::Kitty) => {
+                    write!(out, "\x1b_Ga=d,d=I,i={},q=2\x1b\\", IMAGE_ID)?;
+                    out.flush()?;
+                    Ok(false)
+                }
+                (Some(_), Protocol::Iterm) => Ok(true),
+            };
+        };
+
+        let mut hasher = DefaultHasher::new();
+        image.hash(&mut hasher);
+        let shown = (area, hasher.finish());
+        let previous = self.shown.replace(Some(shown));
+        if previous == Some(shown) {
+            return Ok(false);
+        }
+
+        if previous.is_some() && self.protocol == Protocol::Kitty {
+            write!(out, "\x1b_Ga=d,d=I,i={},q=2\x1b\\", IMAGE_ID)?;
+        }
+        write!(out, "\x1b[{};{}H", area.y + 1, area.x + 1)?;
+        match self.protocol {
+            Protocol::Kitty => {
+                let payload = encode_base64(&deflate(&image.pixels));
+                let chunks: Vec<&[u8]> = payload.as_bytes().chunks(KITTY_CHUNK).collect();
+                for (index, chunk) in chunks.iter().enumerate() {
+                    let more = u8::from(index + 1 < chunks.len());
+                    // Base64 is ASCII, so the chunks are valid text
+                    let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+                    if index == 0 {
+                        write!(
+                            out,
+                            "\x1b_Ga=T,f=32,o=z,s={},v={},c={},r={},i={},C=1,q=2,m={};{}\x1b\\",
+                            image.width, image.height, area.width, area.height, IMAGE_ID, more, chunk
+                        )?;
+                    } else {
+                        write!(out, "\x1b_Gm={};{}\x1b\\", more, chunk)?;
+                    }
+                }
+            }
+            Protocol::Iterm => {
+                let png = image.to_png();
+                write!(
+                    out,
+                    "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=0:{}\x07",
+                    png.len(),
+                    area.width,
+                    area.height,
+                    encode_base64(&png)
+                )?;
+            }
+        }
+        out.flush()?;
+        Ok(false)
+    }
+}
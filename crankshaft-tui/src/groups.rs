@@ -0,0 +1,146 @@
+//! Collapsible grouping of the task list by workflow.
+//!
+//! Tasks belong to the workflow named by their `workflow` (or `workflow_id`)
+//! label. Backends that do not report one can still be grouped: a regular
+//! expression matched against each task's ID, or failing that its name,
+//! infers the group from its capture group (the one named `group`, or else
+//! the first):
+//!
+//! ```toml
+//! [grouping]
+//! enabled = true
+//! pattern = "^(.+)-shard-[0-9]+$"
+//! ```
+//!
+//! Shard-heavy runs then collapse to one row per workflow.
+
+use std::collections::{HashMap, HashSet};
+
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+
+use crate::app::Task;
+
+/// Labels naming a task's workflow, in order of preference.
+pub const WORKFLOW_LABELS: [&str; 2] = ["workflow", "workflow_id"];
+
+/// Options for grouping the task list.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GroupingConfig {
+    /// Group the task list on startup
+    pub enabled: bool,
+    /// Infers the group of tasks without a workflow label from their ID or
+    /// name; must have a capture group
+    #[serde(deserialize_with = "pattern")]
+    pub pattern: Option<Regex>,
+}
+
+/// Tasks of one group in list order.
+#[derive(Debug, Clone)]
+pub struct TaskGroup<'a> {
+    /// The group's name, or `None` for the tasks that belong to no group
+    pub name: Option<&'a str>,
+    /// Its tasks, in list order
+    pub ids: Vec<&'a String>,
+    /// Whether the group is shown as a single row
+    pub collapsed: bool,
+}
+
+impl<'a> TaskGroup<'a> {
+    /// Returns the tasks listed for the group: all of them, or only the
+    /// first, which stands in for a collapsed group.
+    pub fn listed(&self) -> &[&'a String] {
+        if self.collapsed {
+            &self.ids[..self.ids.len().min(1)]
+        } else {
+            &self.ids
+        }
+    }
+}
+
+/// Whether the task list is grouped, how groups are inferred, and which
+/// are collapsed.
+#[derive(Debug, Clone, Default)]
+pub struct Grouping {
+    /// Whether the task list is grouped
+    pub enabled: bool,
+    /// Pattern inferring the group of unlabeled tasks
+    pattern: Option<Regex>,
+    /// Names of the collapsed groups
+    collapsed: HashSet<String>,
+}
+
+impl Grouping {
+    /// Applies the configured options.
+    pub fn set_config(&mut self, config: &GroupingConfig) {
+        self.enabled = config.enabled;
+        self.pattern = config.pattern.clone();
+    }
+
+    /// Returns the group a task belongs to, if any.
+    pub fn group_of<'a>(&self, task: &'a Task) -> Option<&'a str> {
+        if let Some(workflow) = WORKFLOW_LABELS.iter().find_map(|label| task.labels.get(*label)) {
+            return Some(workflow);
+        }
+        let pattern = self.pattern.as_ref()?;
+        [task.id.as_str(), task.name.as_str()].into_iter().find_map(|text| {
+            let captures = pattern.captures(text)?;
+            captures.name("group").or_else(|| captures.get(1)).map(|found| found.as_str())
+        })
+    }
+
+    /// Splits tasks into groups, ordered by where each group's first task
+    /// appears, keeping list order within each group. Tasks without a group
+    /// come last.
+    pub fn arrange<'a>(&self, ids: Vec<&'a String>, tasks: &'a HashMap<String, Task>) -> Vec<TaskGroup<'a>> {
+        let mut groups: Vec<TaskGroup<'a>> = Vec::new();
+        let mut index: HashMap<&str, usize> = HashMap::new();
+        let mut ungrouped = Vec::new();
+        for id in ids {
+            let Some(name) = tasks.get(id).and_then(|task| self.group_of(task)) else {
+                ungrouped.push(id);
+                continue;
+            };
+            let position = *index.entry(name).or_insert_with(|| {
+                groups.push(TaskGroup {
+                    name: Some(name),
+                    ids: Vec::new(),
+                    collapsed: self.collapsed.contains(name),
+                });
+                groups.len() - 1
+            });
+            groups[position].ids.push(id);
+        }
+        if !ungrouped.is_empty() {
+            groups.push(TaskGroup {
+                name: None,
+                ids: ungrouped,
+                collapsed: false,
+            });
+        }
+        groups
+    }
+
+    /// Collapses a group, or expands it if it is collapsed. Returns `true`
+    /// if the group is now collapsed.
+    pub fn toggle(&mut self, group: &str) -> bool {
+        if self.collapsed.remove(group) {
+            false
+        } else {
+            self.collapsed.insert(group.to_string());
+            true
+        }
+    }
+}
+
+/// Compiles the grouping pattern, which must capture the group.
+fn pattern<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Regex>, D::Error> {
+    let pattern = String::deserialize(deserializer)?;
+    let regex = Regex::new(&pattern)
+        .map_err(|err| serde::de::Error::custom(format!("invalid pattern {:?}: {}", pattern, err)))?;
+    if regex.captures_len() < 2 {
+        return Err(serde::de::Error::custom(format!("pattern {:?} has no capture group", pattern)));
+    }
+    Ok(Some(regex))
+}
@@ -0,0 +1,188 @@
+//! Highlighting of log lines by configurable patterns.
+//!
+//! Each rule pairs a regular expression with a style; every match in a
+//! viewed log line is drawn in that style. Where matches of several rules
+//! overlap, the rule listed first wins. Configured rules replace the
+//! defaults, which highlight errors and warnings:
+//!
+//! ```toml
+//! [[highlights.rules]]
+//! pattern = "\\bWARN\\b"
+//! fg = "yellow"
+//!
+//! [[highlights.rules]]
+//! pattern = "sample-[0-9]+"
+//! fg = "cyan"
+//! bold = true
+//! ```
+//!
+//! Patterns are compiled once, when the configuration is loaded. Only the
+//! lines on screen are matched, and a combined [`RegexSet`] pass skips the
+//! rules that cannot match a line before any match positions are searched.
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use regex::{Regex, RegexSet};
+use serde::{Deserialize, Deserializer};
+
+/// A pattern and the style its matches are drawn in.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HighlightRule {
+    /// Regular expression matched against each line
+    #[serde(deserialize_with = "pattern")]
+    pub pattern: Regex,
+    /// Foreground color, such as `yellow`, `lightred`, `#ff8800`, or a
+    /// palette index
+    #[serde(default, deserialize_with = "color")]
+    pub fg: Option<Color>,
+    /// Background color, in the same forms as `fg`
+    #[serde(default, deserialize_with = "color")]
+    pub bg: Option<Color>,
+    /// Draw matches in bold
+    #[serde(default)]
+    pub bold: bool,
+}
+
+impl HighlightRule {
+    /// Returns the style matches of the rule are drawn in.
+    pub fn style(&self) -> Style {
+        let mut style = Style::default();
+        if let Some(fg) = self.fg {
+            style = style.fg(fg);
+        }
+        if let Some(bg) = self.bg {
+            style = style.bg(bg);
+        }
+        if self.bold {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        style
+    }
+}
+
+/// The configured highlighting rules.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HighlightRules {
+    /// Rules in order of precedence
+    pub rules: Vec<HighlightRule>,
+}
+
+impl Default for HighlightRules {
+    fn default() -> Self {
+        let rule = |pattern: &str, fg: Color, bold: bool| HighlightRule {
+            pattern: Regex::new(pattern).expect("default highlight patterns are valid"),
+            fg: Some(fg),
+            bg: None,
+            bold,
+        };
+        Self {
+            rules: vec![
+                rule(r"\b(ERROR|FATAL)\b", Color::Red, true),
+                rule(r"\bWARN(ING)?\b", Color::Yellow, false),
+            ],
+        }
+    }
+}
+
+/// Compiled rules applied to log lines as they are drawn.
+#[derive(Debug, Clone)]
+pub struct Highlighter {
+    /// Rules with their styles, in order of precedence
+    rules: Vec<(Regex, Style)>,
+    /// Every rule's pattern, for finding the rules that match a line in one
+    /// pass; `None` if the combined set is too large to compile
+    set: Option<RegexSet>,
+}
+
+impl Default for Highlighter {
+    fn default() -> Self {
+        Self::new(&HighlightRules::default())
+    }
+}
+
+impl Highlighter {
+    /// Prepares the rules for matching.
+    pub fn new(rules: &HighlightRules) -> Self {
+        let set = RegexSet::new(rules.rules.iter().map(|rule| rule.pattern.as_str())).ok();
+        Self {
+            rules: rules.rules.iter().map(|rule| (rule.pattern.clone(), rule.style())).collect(),
+            set,
+        }
+    }
+
+    /// Returns `true` if there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Splits a line into spans, styling the matches of every rule.
+    pub fn line<'a>(&self, line: &'a str) -> Line<'a> {
+        if self.rules.is_empty() {
+            return Line::from(line);
+        }
+        let matching: Vec<usize> = match &self.set {
+            Some(set) => set.matches(line).into_iter().collect(),
+            None => (0..self.rules.len()).collect(),
+        };
+        if matching.is_empty() {
+            return Line::from(line);
+        }
+
+        // Matches as (start, end, rule), earliest first and earlier rules
+        // first among those starting together
+        let mut matches: Vec<(usize, usize, usize)> = matching
+            .into_iter()
+            .flat_map(|rule| {
+                self.rules[rule]
+                    .0
+                    .find_iter(line)
+                    .filter(|found| !found.is_empty())
+                    .map(move |found| (found.start(), found.end(), rule))
+            })
+            .collect();
+        matches.sort_unstable();
+
+        let mut taken: Vec<(usize, usize, usize)> = Vec::new();
+        for found in matches {
+            // A match overlapping one of an earlier rule loses to it, and
+            // displaces those of later rules
+            let overlaps = |other: &(usize, usize, usize)| found.0 < other.1 && other.0 < found.1;
+            if taken.iter().any(|other| overlaps(other) && other.2 < found.2) {
+                continue;
+            }
+            taken.retain(|other| !overlaps(other));
+            taken.push(found);
+        }
+        taken.sort_unstable();
+
+        let mut spans = Vec::new();
+        let mut at = 0;
+        for (start, end, rule) in taken {
+            if start > at {
+                spans.push(Span::raw(&line[at..start]));
+            }
+            spans.push(Span::styled(&line[start..end], self.rules[rule].1));
+            at = end;
+        }
+        if at < line.len() {
+            spans.push(Span::raw(&line[at..]));
+        }
+        Line::from(spans)
+    }
+}
+
+/// Compiles a rule's pattern, rejecting invalid expressions.
+fn pattern<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+    let pattern = String::deserialize(deserializer)?;
+    Regex::new(&pattern).map_err(|err| serde::de::Error::custom(format!("invalid pattern {:?}: {}", pattern, err)))
+}
+
+/// Parses a color name, `#rrggbb` value, or palette index.
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Color>, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    name.parse()
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(format!("unknown color {:?}", name)))
+}
@@ -0,0 +1,120 @@
+//! In-memory history of the task store for the current session.
+//!
+//! A copy of the store is taken periodically so that the dashboard can be
+//! scrubbed back to how it looked a few minutes ago. Retained logs are not
+//! part of the copies, and the oldest copies are dropped once the total
+//! number of retained tasks exceeds a budget, so large stores keep a shorter
+//! history rather than growing without bound.
+//!
+//! Moments bookmarked with `m` are kept with the copies, so scrubbing shows
+//! the notes left at each point; a bookmark is taken with a copy of its own
+//! rather than waiting for the next one.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::app::Task;
+use crate::record::unix_now;
+
+/// Time between copies of the store.
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Maximum number of tasks kept across all copies.
+pub const MAX_RETAINED_TASKS: usize = 200_000;
+
+/// A copy of the task store at one point in time.
+#[derive(Debug, Clone)]
+pub struct StoreSnapshot {
+    /// When the copy was taken, in seconds since the Unix epoch
+    pub taken_at: u64,
+    /// The tasks, in list order and without their logs
+    pub tasks: Vec<Task>,
+    /// Notes of the moments bookmarked at this copy, possibly empty
+    pub bookmarks: Vec<String>,
+}
+
+/// Periodic copies of the task store, oldest first.
+#[derive(Debug, Clone)]
+pub struct History {
+    /// Retained copies
+    snapshots: VecDeque<StoreSnapshot>,
+    /// Total number of tasks across `snapshots`
+    retained: usize,
+    /// When the last copy was taken
+    last: Option<Instant>,
+    /// Time between copies
+    interval: Duration,
+    /// Notes of the moments bookmarked since the last copy
+    bookmarks: Vec<String>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            retained: 0,
+            last: None,
+            interval: SNAPSHOT_INTERVAL,
+            bookmarks: Vec::new(),
+        }
+    }
+}
+
+impl History {
+    /// Takes a copy of the store if the snapshot interval has elapsed or a
+    /// moment was bookmarked.
+    pub fn capture<'a>(&mut self, tasks: impl IntoIterator<Item = &'a Task>) {
+        if self.bookmarks.is_empty() && self.last.is_some_and(|last| last.elapsed() < self.interval) {
+            return;
+        }
+        self.last = Some(Instant::now());
+
+        let tasks: Vec<Task> = tasks.into_iter().map(Task::without_logs).collect();
+        self.retained += tasks.len();
+        self.snapshots.push_back(StoreSnapshot {
+            taken_at: unix_now(),
+            tasks,
+            bookmarks: std::mem::take(&mut self.bookmarks),
+        });
+
+        while self.retained > MAX_RETAINED_TASKS && self.snapshots.len() > 1 {
+            if let Some(dropped) = self.snapshots.pop_front() {
+                self.retained -= dropped.tasks.len();
+            }
+        }
+    }
+
+    /// Adds a bookmark with `note` to the next copy, which is taken without
+    /// waiting for the snapshot interval.
+    pub fn bookmark(&mut self, note: impl Into<String>) {
+        self.bookmarks.push(note.into());
+    }
+
+    /// Adds a bookmark with `note` to the copy at `index`, returning when
+    /// the copy was taken, or `None` if there is no such copy.
+    pub fn bookmark_copy(&mut self, index: usize, note: impl Into<String>) -> Option<u64> {
+        let snapshot = self.snapshots.get_mut(index)?;
+        snapshot.bookmarks.push(note.into());
+        Some(snapshot.taken_at)
+    }
+
+    /// Returns the number of retained copies.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Returns the total number of tasks across all copies.
+    pub fn retained_tasks(&self) -> usize {
+        self.retained
+    }
+
+    /// Returns `true` if no copy has been taken yet.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Returns the copy at `index`, where 0 is the oldest.
+    pub fn get(&self, index: usize) -> Option<&StoreSnapshot> {
+        self.snapshots.get(index)
+    }
+}
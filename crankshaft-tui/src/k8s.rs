@@ -0,0 +1,647 @@
+//! Kubernetes Jobs and the pods that run them.
+//!
+//! The pods of Jobs in one namespace (those carrying the `job-name` label
+//! the Job controller sets) are listed once and then followed through the
+//! watch API on a background thread, so changes show as soon as the API
+//! server reports them. Each Job is one task; the details pane lists its
+//! pods with their nodes, phases, and container restart counts. A Job
+//! leaves the list once all of its pods are deleted.
+//!
+//! Inside a cluster, the API server is reached with the pod's service
+//! account. Elsewhere, the monitor talks to `kubectl proxy`, which handles
+//! authentication, unless another API URL is given.
+//!
+//! A Job is completed once one of its pods succeeded. Otherwise it takes
+//! its status from its newest pod, whose phase maps onto task statuses as
+//! follows:
+//!
+//! - `Pending` is pending
+//! - `Running` and `Unknown` are running
+//! - `Succeeded` is completed
+//! - `Failed` is failed
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use eyre::WrapErr;
+use serde::Deserialize;
+
+use crate::app::{MemoryUsage, PodInfo, Task, TaskStatus, TaskUpdate};
+use crate::capabilities::SourceCapabilities;
+use crate::format::parse_timestamp;
+use crate::logs::LogBuffer;
+use crate::source::{DataSource, SourceEvent};
+use crate::tls::Tls;
+
+/// Label the Job controller puts on the pods of a Job, naming the Job.
+pub const JOB_LABEL: &str = "job-name";
+
+/// Where `kubectl proxy` listens by default.
+pub const PROXY_URL: &str = "http://127.0.0.1:8001";
+
+/// Where a pod's service account is mounted.
+const SERVICE_ACCOUNT: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// How long listing the pods may take.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a watch may stay quiet before the open connection is reported
+/// as a sign of life.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a watch is asked to last before it is opened again.
+const WATCH_TIMEOUT_SECONDS: u32 = 300;
+
+/// How long to wait before listing again after a failure.
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What the watching thread hands over.
+#[derive(Debug)]
+enum Message {
+    /// A change to the task store
+    Update(TaskUpdate),
+    /// Something that happened to the connection
+    Event(SourceEvent),
+}
+
+/// An API server and the namespace to watch in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct K8sTarget {
+    /// Base URL of the API server, without a trailing slash
+    pub api: String,
+    /// Namespace whose Jobs are shown
+    pub namespace: String,
+    /// Bearer token sent with each request, if the server needs one
+    pub token: Option<String>,
+    /// Certificates of the authority that signed the server's certificate,
+    /// if it is not a public one, and of the client
+    pub tls: Tls,
+}
+
+impl K8sTarget {
+    /// Returns a target for the API server at `api`, without credentials.
+    pub fn new(api: &str, namespace: &str) -> Self {
+        Self {
+            api: api.trim_end_matches('/').to_string(),
+            namespace: namespace.to_string(),
+            token: None,
+            tls: Tls::default(),
+        }
+    }
+
+    /// Returns the cluster the monitor runs in, reached with the service
+    /// account mounted into its pod, or `None` outside a cluster. The
+    /// namespace defaults to the pod's own.
+    pub fn in_cluster(namespace: Option<&str>) -> Option<Self> {
+        let host = std::env::var("KUBERNETES_SERVICE_HOST").ok()?;
+        let port = std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| "443".to_string());
+        let account = Path::new(SERVICE_ACCOUNT);
+        let token = std::fs::read_to_string(account.join("token")).ok()?;
+        let namespace = match namespace {
+            Some(namespace) => namespace.to_string(),
+            None => std::fs::read_to_string(account.join("namespace")).map_or_else(|_| "default".to_string(), |own| own.trim().to_string()),
+        };
+        // IPv6 addresses are bracketed in URLs
+        let host = if host.contains(':') { format!("[{}]", host) } else { host };
+        Some(Self {
+            api: format!("https://{}:{}", host, port),
+            namespace,
+            token: Some(token.trim().to_string()),
+            tls: Tls {
+                ca_certificates: std::fs::read(account.join("ca.crt")).ok(),
+                client_identity: None,
+            },
+        })
+    }
+}
+
+/// The Jobs of a Kubernetes namespace, watched on a background thread.
+pub struct K8sDataSource {
+    /// Where the Jobs are watched
+    target: K8sTarget,
+    /// Receives what the watching thread found
+    receiver: Receiver<Message>,
+    /// Events received with the last poll's updates, not yet taken
+    events: Vec<SourceEvent>,
+}
+
+impl K8sDataSource {
+    /// Starts watching the Jobs in `target`.
+    pub fn connect(target: K8sTarget) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let watched = target.clone();
+        thread::spawn(move || {
+            if let Err(err) = follow(&watched, &sender) {
+                let reason = format!("Stopped watching Kubernetes at {}: {:#}", watched.api, err);
+                let _ = sender.send(Message::Event(SourceEvent::Failed(reason)));
+            }
+        });
+        Self {
+            target,
+            receiver,
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns where the Jobs are watched.
+    pub fn target(&self) -> &K8sTarget {
+        &self.target
+    }
+}
+
+impl DataSource for K8sDataSource {
+    fn name(&self) -> &str {
+        "k8s"
+    }
+
+    /// Statuses only: resource use needs the metrics server, and logs are
+    /// not read from the API server.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::NONE
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        let mut updates = Vec::new();
+        for message in self.receiver.try_iter() {
+            match message {
+                Message::Update(update) => updates.push(update),
+                Message::Event(event) => self.events.push(event),
+            }
+        }
+        updates
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// Returns the status a pod phase stands for.
+pub fn status(phase: &str) -> TaskStatus {
+    match phase {
+        "Pending" => TaskStatus::Pending,
+        "Succeeded" => TaskStatus::Completed,
+        "Failed" => TaskStatus::Failed,
+        _ => TaskStatus::Running,
+    }
+}
+
+/// The parts of a pod the monitor shows.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Pod {
+    /// Names and labels of the pod
+    metadata: Metadata,
+    /// What the pod runs, and where
+    spec: PodSpec,
+    /// Where the pod is in its life
+    status: PodStatus,
+}
+
+/// Names and labels of an object.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Metadata {
+    /// Name of the object, unique in its namespace
+    name: String,
+    /// Namespace the object is in
+    namespace: String,
+    /// When the object was created, in RFC 3339
+    creation_timestamp: String,
+    /// Version of the object, or of a list, to watch from
+    resource_version: String,
+    /// Labels set on the object
+    labels: BTreeMap<String, String>,
+}
+
+/// What a pod runs, and where.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct PodSpec {
+    /// Node the pod is scheduled on, once it is
+    node_name: Option<String>,
+    /// The pod's containers, the main one first
+    containers: Vec<Container>,
+}
+
+/// One container of a pod.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Container {
+    /// Entrypoint, replacing the image's
+    command: Vec<String>,
+    /// Arguments to the entrypoint
+    args: Vec<String>,
+    /// Environment variables set on the container
+    env: Vec<EnvVar>,
+    /// Resources requested and limited to
+    resources: Resources,
+}
+
+/// An environment variable; those taken from other objects have no value.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct EnvVar {
+    /// Name of the variable
+    name: String,
+    /// Value of the variable, if given literally
+    value: Option<String>,
+}
+
+/// Resources a container requests and is limited to, as quantities.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Resources {
+    /// Amounts requested, by resource, such as `cpu` or `memory`
+    requests: HashMap<String, String>,
+    /// Amounts the container is limited to, by resource
+    limits: HashMap<String, String>,
+}
+
+/// Where a pod is in its life.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct PodStatus {
+    /// Lifecycle phase, such as `Pending` or `Succeeded`
+    phase: String,
+    /// Why the pod is in its phase, such as `Evicted`
+    reason: Option<String>,
+    /// When the pod was accepted by its node, in RFC 3339
+    start_time: Option<String>,
+    /// Where each container is in its life
+    container_statuses: Vec<ContainerStatus>,
+}
+
+/// Where one container of a pod is in its life.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ContainerStatus {
+    /// Times the container has been restarted
+    restart_count: u32,
+    /// The state the container is in
+    state: ContainerState,
+}
+
+/// The state a container is in; only one of them is set.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ContainerState {
+    /// Set while the container waits to start
+    waiting: Option<StateReason>,
+    /// Set once the container has ended
+    terminated: Option<Terminated>,
+}
+
+/// Why a container is waiting.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct StateReason {
+    /// Why, such as `ImagePullBackOff`
+    reason: Option<String>,
+}
+
+/// How a container ended.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Terminated {
+    /// Exit code of the container's process
+    exit_code: i32,
+    /// Why it ended, such as `OOMKilled`
+    reason: Option<String>,
+    /// When it ended, in RFC 3339
+    finished_at: Option<String>,
+}
+
+impl Pod {
+    /// Returns what the details pane shows of the pod.
+    fn info(&self) -> PodInfo {
+        let containers = &self.status.container_statuses;
+        // A container that is waiting or failed says more than the pod does
+        let reason = containers
+            .iter()
+            .find_map(|container| match &container.state {
+                ContainerState { waiting: Some(waiting), .. } => waiting.reason.clone(),
+                ContainerState { terminated: Some(terminated), .. } if terminated.exit_code != 0 => terminated.reason.clone(),
+                _ => None,
+            })
+            .or_else(|| self.status.reason.clone());
+        PodInfo {
+            name: self.metadata.name.clone(),
+            phase: self.status.phase.clone(),
+            node: self.spec.node_name.clone(),
+            restarts: containers.iter().map(|container| container.restart_count).sum(),
+            reason,
+        }
+    }
+}
+
+/// Converts the pods of the Job named `job`, as the API server reports
+/// them, into a task, keeping the newest pod as the task's raw JSON.
+/// Returns `None` if there are no pods.
+pub fn to_task(job: &str, pods: &[serde_json::Value]) -> serde_json::Result<Option<Task>> {
+    let mut decoded: Vec<(Pod, &serde_json::Value)> =
+        pods.iter().map(|value| Pod::deserialize(value).map(|pod| (pod, value))).collect::<Result<_, _>>()?;
+    // Timestamps are RFC 3339 in UTC, so they sort as text
+    decoded.sort_by(|(a, _), (b, _)| (&a.metadata.creation_timestamp, &a.metadata.name).cmp(&(&b.metadata.creation_timestamp, &b.metadata.name)));
+    let Some((newest, raw)) = decoded.last() else {
+        return Ok(None);
+    };
+
+    let status = match decoded.iter().any(|(pod, _)| pod.status.phase == "Succeeded") {
+        true => TaskStatus::Completed,
+        false => status(&newest.status.phase),
+    };
+    let finished_at = matches!(status, TaskStatus::Completed | TaskStatus::Failed)
+        .then(|| {
+            newest
+                .status
+                .container_statuses
+                .iter()
+                .filter_map(|container| container.state.terminated.as_ref()?.finished_at.as_deref().and_then(parse_timestamp))
+                .max()
+        })
+        .flatten();
+    let started_at = decoded.iter().filter_map(|(pod, _)| pod.status.start_time.as_deref().and_then(parse_timestamp)).min();
+
+    let container = newest.spec.containers.first();
+    let resources = container.map(|container| &container.resources);
+    let quantity = |quantities: Option<&HashMap<String, String>>, name: &str| quantities.and_then(|quantities| parse_quantity(quantities.get(name)?));
+    let mut labels = newest.metadata.labels.clone();
+    labels.insert("backend".to_string(), "k8s".to_string());
+    labels.insert("namespace".to_string(), newest.metadata.namespace.clone());
+    if let Some(node) = &newest.spec.node_name {
+        labels.insert("node".to_string(), node.clone());
+    }
+
+    Ok(Some(Task {
+        id: job.to_string(),
+        name: job.to_string(),
+        status,
+        progress: if status == TaskStatus::Completed { 1.0 } else { 0.0 },
+        cpu_usage: 0.0,
+        cpus: quantity(resources.map(|resources| &resources.requests), "cpu"),
+        memory_usage: MemoryUsage {
+            used: 0,
+            requested: quantity(resources.map(|resources| &resources.requests), "memory").map(|bytes| bytes as u64),
+            limit: quantity(resources.map(|resources| &resources.limits), "memory").map(|bytes| bytes as u64),
+        },
+        started_at: (status != TaskStatus::Pending).then_some(started_at).flatten(),
+        finished_at,
+        dependencies: Vec::new(),
+        queue: None,
+        // A Job retries a failed pod by starting another
+        attempt: Some(decoded.len() as u32),
+        labels,
+        command: container.map(|container| container.command.iter().chain(&container.args).cloned().collect()).unwrap_or_default(),
+        env: container
+            .map(|container| container.env.iter().filter_map(|var| Some((var.name.clone(), var.value.clone()?))).collect())
+            .unwrap_or_default(),
+        executors: Vec::new(),
+        container: None,
+        pods: decoded.iter().map(|(pod, _)| pod.info()).collect(),
+        logs: LogBuffer::default(),
+        raw: Some((*raw).clone()),
+        reported_progress: None,
+        last_updated: None,
+    }))
+}
+
+/// Parses a Kubernetes quantity, such as `500m` CPUs or `2Gi` of memory.
+fn parse_quantity(text: &str) -> Option<f64> {
+    const SUFFIXES: [(&str, f64); 13] = [
+        ("Ki", 1024.0),
+        ("Mi", 1_048_576.0),
+        ("Gi", 1_073_741_824.0),
+        ("Ti", 1_099_511_627_776.0),
+        ("Pi", 1_125_899_906_842_624.0),
+        ("Ei", 1_152_921_504_606_846_976.0),
+        ("n", 1e-9),
+        ("u", 1e-6),
+        ("m", 1e-3),
+        ("k", 1e3),
+        ("M", 1e6),
+        ("G", 1e9),
+        ("T", 1e12),
+    ];
+    let text = text.trim();
+    let (number, scale) = SUFFIXES
+        .iter()
+        .find_map(|(suffix, scale)| Some((text.strip_suffix(suffix)?, *scale)))
+        .unwrap_or((text, 1.0));
+    // Plain numbers and exponents such as `1e3` parse as they are
+    number.parse::<f64>().ok().map(|number| number * scale)
+}
+
+/// A list of pods.
+#[derive(Debug, Deserialize)]
+struct PodList {
+    /// The version of the list, to watch from
+    metadata: Metadata,
+    /// The pods, converted one by one
+    items: Vec<serde_json::Value>,
+}
+
+/// One line of a watch.
+#[derive(Debug, Deserialize)]
+struct WatchEvent {
+    /// What happened, such as `ADDED` or `DELETED`
+    #[serde(rename = "type")]
+    kind: String,
+    /// The object it happened to
+    object: serde_json::Value,
+}
+
+/// The pods of the namespace as last reported, and what was handed over.
+#[derive(Default)]
+struct Watcher {
+    /// Pods by name
+    pods: HashMap<String, serde_json::Value>,
+    /// The task last handed over for each Job, to send only changes
+    reported: HashMap<String, serde_json::Value>,
+    /// Version of the pods last seen, to watch from
+    resource_version: String,
+    /// Whether the API server was reached since the last failure
+    reachable: bool,
+}
+
+impl Watcher {
+    /// Hands over the task of the Job named `job` if it changed, or its
+    /// removal once it has no pods left. Returns `false` once the app is
+    /// gone.
+    fn publish(&mut self, job: &str, sender: &Sender<Message>) -> bool {
+        let pods: Vec<serde_json::Value> = self.pods.values().filter(|pod| job_of(pod) == Some(job)).cloned().collect();
+        let message = match to_task(job, &pods) {
+            Ok(Some(task)) => {
+                let value = serde_json::to_value(&task).unwrap_or_default();
+                if self.reported.get(job) == Some(&value) {
+                    return true;
+                }
+                self.reported.insert(job.to_string(), value);
+                Message::Update(TaskUpdate::Created(Box::new(task)))
+            }
+            Ok(None) if self.reported.remove(job).is_some() => Message::Update(TaskUpdate::Removed { id: job.to_string() }),
+            Ok(None) => return true,
+            Err(err) => Message::Event(SourceEvent::Dropped(format!("{}: pods of {}", err, job))),
+        };
+        sender.send(message).is_ok()
+    }
+}
+
+/// Returns the name of the Job a pod belongs to.
+fn job_of(pod: &serde_json::Value) -> Option<&str> {
+    pod["metadata"]["labels"][JOB_LABEL].as_str()
+}
+
+/// Returns the name of a pod.
+fn name_of(pod: &serde_json::Value) -> Option<&str> {
+    pod["metadata"]["name"].as_str()
+}
+
+/// Lists and watches the pods until the app is gone, reporting failures and
+/// listing again after them.
+fn follow(target: &K8sTarget, sender: &Sender<Message>) -> eyre::Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .wrap_err("failed to start the Kubernetes watching runtime")?;
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("crankshaft-tui/", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(REQUEST_TIMEOUT);
+    let client = target.tls.apply(client)?.build()?;
+
+    let mut watcher = Watcher::default();
+    loop {
+        match runtime.block_on(watch(&client, target, &mut watcher, sender)) {
+            Ok(false) => return Ok(()),
+            // The version watched from is too old; start over from a list
+            Ok(true) => continue,
+            Err(err) => {
+                watcher.reachable = false;
+                let reason = format!("Cannot reach Kubernetes at {}: {:#}", target.api, err);
+                if sender.send(Message::Event(SourceEvent::Failed(reason))).is_err() {
+                    return Ok(());
+                }
+                thread::sleep(RETRY_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Lists the pods of Jobs and follows them until the watch has to start
+/// over, returning `false` once the app is gone.
+async fn watch(client: &reqwest::Client, target: &K8sTarget, watcher: &mut Watcher, sender: &Sender<Message>) -> eyre::Result<bool> {
+    let url = format!("{}/api/v1/namespaces/{}/pods", target.api, target.namespace);
+    let started = Instant::now();
+    let list: PodList = request(client, target, &url, &[("labelSelector", JOB_LABEL)])
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)?
+        .json()
+        .await
+        .wrap_err("failed to decode the pod list")?;
+    let latency = started.elapsed();
+    let mut events = vec![SourceEvent::Connected { latency }];
+    if !watcher.reachable {
+        events.push(SourceEvent::Message(format!("Watching Jobs in {} at {}", target.namespace, target.api)));
+        watcher.reachable = true;
+    }
+    for event in events {
+        if sender.send(Message::Event(event)).is_err() {
+            return Ok(false);
+        }
+    }
+
+    // Jobs whose pods went while nobody watched are removed
+    let mut jobs: HashSet<String> = watcher.reported.keys().cloned().collect();
+    watcher.pods = list.items.into_iter().filter_map(|pod| Some((name_of(&pod)?.to_string(), pod))).collect();
+    jobs.extend(watcher.pods.values().filter_map(job_of).map(str::to_string));
+    for job in jobs {
+        if !watcher.publish(&job, sender) {
+            return Ok(false);
+        }
+    }
+    watcher.resource_version = list.metadata.resource_version;
+
+    loop {
+        let timeout = WATCH_TIMEOUT_SECONDS.to_string();
+        let query = [
+            ("labelSelector", JOB_LABEL),
+            ("watch", "true"),
+            ("allowWatchBookmarks", "true"),
+            ("resourceVersion", watcher.resource_version.as_str()),
+            ("timeoutSeconds", timeout.as_str()),
+        ];
+        let mut response = request(client, target, &url, &query).send().await.and_then(reqwest::Response::error_for_status)?;
+        let mut buffer = Vec::new();
+        loop {
+            let chunk = match tokio::time::timeout(HEARTBEAT_INTERVAL, response.chunk()).await {
+                Ok(chunk) => chunk.wrap_err("the watch broke off")?,
+                // A quiet watch is still connected
+                Err(_) => {
+                    if sender.send(Message::Event(SourceEvent::Connected { latency })).is_err() {
+                        return Ok(false);
+                    }
+                    continue;
+                }
+            };
+            // The server ended the watch; it is opened again from where it was
+            let Some(chunk) = chunk else {
+                break;
+            };
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                let event: WatchEvent = match serde_json::from_slice(&line) {
+                    Ok(event) => event,
+                    Err(err) => {
+                        let detail = format!("{}: {}", err, String::from_utf8_lossy(&line).trim());
+                        if sender.send(Message::Event(SourceEvent::Dropped(detail))).is_err() {
+                            return Ok(false);
+                        }
+                        continue;
+                    }
+                };
+                if let Some(version) = event.object["metadata"]["resourceVersion"].as_str() {
+                    watcher.resource_version = version.to_string();
+                }
+                let job = job_of(&event.object).map(str::to_string);
+                match event.kind.as_str() {
+                    "ADDED" | "MODIFIED" => {
+                        if let Some(name) = name_of(&event.object) {
+                            watcher.pods.insert(name.to_string(), event.object);
+                        }
+                    }
+                    "DELETED" => {
+                        if let Some(name) = name_of(&event.object) {
+                            watcher.pods.remove(name);
+                        }
+                    }
+                    // Most likely `410 Gone`: the version is too old to watch from
+                    "ERROR" => return Ok(true),
+                    _ => continue,
+                }
+                if let Some(job) = job {
+                    if !watcher.publish(&job, sender) {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Starts a `GET` of `url` with `query`, authenticated as `target` says.
+fn request(client: &reqwest::Client, target: &K8sTarget, url: &str, query: &[(&str, &str)]) -> reqwest::RequestBuilder {
+    let request = client.get(url).query(query);
+    match &target.token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
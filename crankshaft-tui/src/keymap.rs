@@ -0,0 +1,216 @@
+//! The keys each mode of the app responds to, as hinted in the footer.
+//!
+//! The footer shows the few keys that matter where the user is rather than
+//! one fixed line: the keys that answer a prompt while one waits, those of
+//! an open menu or text field, and otherwise those of the focused tab or
+//! pane. Hints follow the state they describe, such as whether the focused
+//! log pane is paused, and keys whose feature the data source cannot back
+//! are left out, as in the Help tab.
+
+use crate::app::{App, Tab};
+use crate::overlay::Overlay;
+
+/// What the keys currently act on, most specific first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// A confirmation waits for an answer
+    Confirming,
+    /// The sort menu is open
+    SortMenu,
+    /// The glossary popover is open
+    Glossary,
+    /// A new name for the selected task is being typed
+    Renaming,
+    /// The path to save a log to is being typed
+    SavingLog,
+    /// A bookmark's note is being typed
+    Bookmarking,
+    /// A panel such as the diagnostics is on top
+    Overlay(Overlay),
+    /// An older copy of the store is shown
+    History,
+    /// The selected task's metric chart is shown
+    Chart,
+    /// The selected task's raw JSON is shown
+    RawJson,
+    /// The task list
+    Tasks,
+    /// The log panes
+    Logs,
+    /// Aggregate statistics
+    Statistics,
+    /// The timeline
+    Timeline,
+    /// The event feed
+    Events,
+    /// Keyboard shortcuts and about text
+    Help,
+}
+
+impl Mode {
+    /// Returns the mode the app is in.
+    pub fn of(app: &App) -> Self {
+        let tab = app.current_tab();
+        let top = app.overlays.top();
+        if top == Some(Overlay::Confirmation) {
+            Mode::Confirming
+        } else if top == Some(Overlay::SortMenu) {
+            Mode::SortMenu
+        } else if top == Some(Overlay::Glossary) {
+            Mode::Glossary
+        } else if app.rename.is_some() {
+            Mode::Renaming
+        } else if app.save_log_path.is_some() {
+            Mode::SavingLog
+        } else if app.bookmark_note.is_some() {
+            Mode::Bookmarking
+        } else if let Some(overlay) = top {
+            Mode::Overlay(overlay)
+        } else if app.is_viewing_history() {
+            Mode::History
+        } else if tab == Tab::Tasks && app.show_chart {
+            Mode::Chart
+        } else if tab == Tab::Tasks && app.show_raw_json {
+            Mode::RawJson
+        } else {
+            match tab {
+                Tab::Tasks => Mode::Tasks,
+                Tab::Logs => Mode::Logs,
+                Tab::Statistics => Mode::Statistics,
+                Tab::Timeline => Mode::Timeline,
+                Tab::Events => Mode::Events,
+                Tab::Help => Mode::Help,
+            }
+        }
+    }
+}
+
+/// A key and what it does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hint {
+    /// The key or keys, as written in the footer
+    pub key: &'static str,
+    /// What pressing it does
+    pub action: &'static str,
+}
+
+/// Returns the hints for the mode the app is in, in footer order.
+pub fn hints(app: &App) -> Vec<Hint> {
+    let hint = |key, action| Hint { key, action };
+    let capabilities = app.capabilities;
+    let mut hints = Vec::new();
+    match Mode::of(app) {
+        Mode::Confirming => {
+            hints.push(hint("y/Enter", "confirm"));
+            hints.push(hint("n/Esc", "cancel"));
+        }
+        Mode::SortMenu => {
+            hints.push(hint("↑/↓", "choose a field"));
+            hints.push(hint("Enter/1", "sort by it first"));
+            hints.push(hint("2", "then by it"));
+            hints.push(hint("c", "clear"));
+            hints.push(hint("Esc", "close"));
+        }
+        Mode::Glossary => {
+            hints.push(hint("←/→", "previous / next term"));
+            hints.push(hint("Esc/?", "close"));
+        }
+        Mode::Renaming | Mode::SavingLog => {
+            hints.push(hint("Enter", "save"));
+            hints.push(hint("Esc", "cancel"));
+        }
+        Mode::Bookmarking => {
+            hints.push(hint("Enter", "bookmark"));
+            hints.push(hint("Esc", "cancel"));
+        }
+        Mode::Overlay(overlay) => {
+            hints.push(hint("Esc", "close"));
+            let toggle = match overlay {
+                Overlay::Performance => Some(("F12", "hide the HUD")),
+                Overlay::Diagnostics => Some(("D", "hide diagnostics")),
+                Overlay::Audit => Some(("A", "hide the audit log")),
+                Overlay::About => Some(("i", "hide")),
+                Overlay::TaskDiff => Some(("E", "hide the diff")),
+                Overlay::SortMenu | Overlay::Glossary | Overlay::Confirmation => None,
+            };
+            if let Some((key, action)) = toggle {
+                hints.push(hint(key, action));
+            }
+        }
+        Mode::History => {
+            hints.push(hint("[", "older"));
+            hints.push(hint("]", "newer / back to live"));
+            hints.push(hint("m", "bookmark this copy"));
+        }
+        Mode::Chart => {
+            hints.push(hint("←/→", "move the cursor"));
+            hints.push(hint("1-9", "show or hide a series"));
+            hints.push(hint("l", "memory scale"));
+            hints.push(hint("C", "close the chart"));
+        }
+        Mode::RawJson => {
+            hints.push(hint("PgUp/PgDn", "scroll"));
+            hints.push(hint("y", "copy"));
+            hints.push(hint("J", "close the JSON"));
+        }
+        Mode::Tasks => {
+            hints.push(hint("q", "quit"));
+            hints.push(hint("Tab", "switch tabs"));
+            hints.push(hint("↑/↓", "navigate"));
+            hints.push(hint("o", "sort"));
+            if app.backend_filter.is_some() || app.backends().len() > 1 {
+                hints.push(hint("B", "backend"));
+            }
+            if capabilities.has_metrics() {
+                hints.push(hint("C", "chart"));
+            }
+            hints.push(hint("J", "JSON"));
+            hints.push(hint("?", "explain"));
+        }
+        Mode::Logs => {
+            hints.push(hint("Tab", "switch tabs"));
+            if capabilities.logs {
+                let paused = app.log_panes.focused().is_some_and(|pane| !pane.is_following());
+                hints.push(hint("PgUp/PgDn", "scroll"));
+                hints.push(hint("F", if paused { "follow" } else { "pause" }));
+                hints.push(hint("+", "open a pane"));
+                if app.log_panes.len() > 1 {
+                    hints.push(hint("f", "next pane"));
+                }
+                hints.push(hint("s", "save"));
+            }
+        }
+        Mode::Timeline => {
+            hints.push(hint("q", "quit"));
+            hints.push(hint("Tab", "switch tabs"));
+            hints.push(hint("↑/↓", "navigate"));
+            hints.push(hint("v", "lanes"));
+        }
+        Mode::Events => {
+            hints.push(hint("q", "quit"));
+            hints.push(hint("Tab", "switch tabs"));
+            hints.push(hint("↑/↓", "navigate"));
+            hints.push(hint("f", "filter"));
+            hints.push(hint("Enter", "go to the task"));
+            hints.push(hint("m", "bookmark"));
+        }
+        Mode::Statistics | Mode::Help => {
+            hints.push(hint("q", "quit"));
+            hints.push(hint("Tab", "switch tabs"));
+        }
+    }
+    hints
+}
+
+/// Returns `false` for the help line of a key whose feature the data source
+/// does not provide.
+pub(crate) fn available(app: &App, key: &str) -> bool {
+    let capabilities = app.capabilities;
+    match key {
+        "L" | "s" | "+" | "f" | "PgUp/PgDn" | "e" => capabilities.logs,
+        "C" | "t" => capabilities.has_metrics(),
+        "G" => capabilities.dependencies,
+        "c" | "R" | "< >" => capabilities.control || app.dry_run,
+        _ => true,
+    }
+}
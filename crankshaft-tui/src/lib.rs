@@ -1,15 +1,182 @@
 //! Terminal User Interface for monitoring Crankshaft tasks.
 
+mod about;
+mod accessibility;
+mod actions;
+mod alerts;
+mod anomaly;
 mod app;
+mod audit;
+mod auth;
+mod aws_batch;
+mod backpressure;
+mod bus;
+mod cache;
+mod capabilities;
+mod capacity;
+mod chart;
+mod clipboard;
+mod compare;
+mod config;
+mod confirm;
+mod control;
+mod crash;
+mod demo;
+mod diagnostics;
+mod discover;
+#[cfg(unix)]
+mod docker;
+mod dot;
+mod durations;
 mod ui;
+mod engine;
 mod event;
+mod external;
+mod feed;
+mod file;
+mod format;
+mod glossary;
+#[cfg(feature = "graphics")]
+mod graphics;
+mod groups;
+mod highlight;
+mod history;
+mod k8s;
+mod keymap;
+mod local;
+mod logs;
+mod lsf;
+mod memory;
+mod merge;
+mod overlay;
+mod panes;
+mod perf;
+mod phases;
+mod polling;
+mod progress;
+mod protocol;
+mod reconnect;
+mod record;
+mod regions;
+mod retention;
+mod sim;
+mod slo;
+mod slurm;
+#[cfg(unix)]
+mod socket;
+mod source;
+mod sse;
+mod sort;
+mod sounds;
+mod spark;
+mod stderr;
+mod state;
+mod stream;
+mod structured;
+mod table;
+#[cfg(feature = "telemetry")]
+mod telemetry;
+mod tes;
+mod theme;
+mod timeline;
+mod title;
+mod tls;
+mod tunnel;
+mod undo;
+mod updates;
+mod websocket;
+mod workflow;
 
-pub use app::{App, Task, TaskStatus};
+pub use about::{build as build_info, features as enabled_features, CHANGELOG, SOURCES, VERSION};
+pub use accessibility::{SummaryWriter, status_summary};
+pub use actions::TaskAction;
+pub use alerts::{Alert, AlertRule, AlertRules, Alerts, Condition, Metric, Severity, MAX_WINDOW};
+pub use anomaly::{Anomalies, Anomaly, AnomalyConfig};
+pub use app::{App, ContainerStats, ExecutorLog, MemoryUsage, PodInfo, QueuePosition, StatusCounts, Tab, Task, TaskStatus, TaskUpdate, DEFAULT_STALE_AFTER};
+pub use audit::{AuditConfig, AuditEntry, AuditLog, Outcome};
+pub use auth::{default_env as default_token_env, AuthConfig, Credentials, RemoteAccess, Unauthorized, SOURCES as AUTHENTICATED_SOURCES};
+pub use aws_batch::{status as aws_batch_status, to_task as aws_batch_task, AwsBatchDataSource, AWS_BATCH_LOG_GROUP};
+pub use bus::{EventBus, StateEvent, Subscriber};
+pub use cache::{CachedProvider, CachedSource, FetchConfig, FetchControl, FetchStats};
+pub use capabilities::SourceCapabilities;
+pub use capacity::{Capacity, Demand};
+pub use compare::{compare as compare_tasks, FieldDiff};
+pub use chart::{ChartCursor, ChartSeries, ChartState, MetricHistory, Point as MetricPoint, Scale};
+pub use config::{default_path as default_config_path, AccessibilityConfig, Config, DebugConfig, DisplayConfig, PrivacyConfig, TelemetryConfig, TimelineConfig, ToolsConfig, UpdatesConfig};
+pub use confirm::{Confirmable, ConfirmPolicy};
+pub use control::{ControlCommand, default_socket_path};
+pub use crash::{config_path, install_panic_hook, log as crash_log, set_config_path, write_bundle, CrashSnapshotter};
+#[cfg(unix)]
+pub use control::{ControlServer, send as send_control};
+pub use demo::MockDataSource;
+pub use diagnostics::{Connection, ConnectionState, Connections, ConnectorHealth, HealthState};
+pub use discover::{load as discover_backends, parse as parse_engine_config, Backend as DiscoveredSource, DiscoveredBackend, ENGINE_CONFIG_FILE};
+#[cfg(unix)]
+pub use docker::{default_socket as default_docker_socket, status as docker_status, to_task as docker_task, DockerDataSource, TASK_LABEL as DOCKER_TASK_LABEL};
+pub use dot::{to_dot, write_dot};
+pub use engine::EngineConnection;
 pub use event::{Event, EventHandler};
+pub use external::{write_spec, ExternalCommand};
+pub use feed::{EventFeed, EventKind, FeedEvent, MAX_EVENTS as FEED_MAX_EVENTS};
+pub use file::FileDataSource;
+pub use format::NumberFormat;
+pub use glossary::{entries as glossary_entries, for_task as glossary_for_task, lookup as glossary_lookup, Entry as GlossaryEntry, ENTRIES as GLOSSARY};
+#[cfg(feature = "graphics")]
+pub use graphics::{Graphics, Image, Protocol as GraphicsProtocol};
+pub use groups::{GroupingConfig, Grouping, TaskGroup, WORKFLOW_LABELS};
+pub use highlight::{HighlightRule, HighlightRules, Highlighter};
+pub use history::{History, StoreSnapshot};
+pub use k8s::{status as k8s_status, to_task as k8s_task, K8sDataSource, K8sTarget, JOB_LABEL as K8S_JOB_LABEL, PROXY_URL as K8S_PROXY_URL};
+pub use keymap::{hints as key_hints, Hint as KeyHint, Mode as KeyMode};
+pub use local::LocalDataSource;
+pub use logs::{DownloadState, LogBuffer, LogChunk, LogDownload, LogFetcher, LogLimits, LogProvider, LogRange};
+pub use lsf::{status as lsf_status, to_task as lsf_task, LsfDataSource};
+pub use memory::{process_memory, retained_log_bytes, MemoryLogger, ProcessMemory};
+pub use merge::{Field as MergedField, MergeConfig, MergedDataSource};
+pub use overlay::{Overlay, OverlayStack};
+pub use panes::{LogPane, LogPanes, MAX_PANES};
+pub use perf::{Churn, PerfStats};
+pub use progress::ProgressInterpolator;
+pub use durations::{by_step as durations_by_step, by_step_including as durations_by_step_including, StepDurations};
+pub use phases::{breakdown as phase_breakdown, phase_of, Phase, PHASE_LABELS};
+pub use polling::{PollInterval, PollingConfig, MIN_INTERVAL as MIN_POLL_INTERVAL, POLLED_SOURCES};
+pub use protocol::{
+    decode_update, encode_update, validate_update, DecodeError, Malformed, TaskList, EVENTS_PATH, SCHEMA_VERSION, TASKS_PATH, UPDATE_SCHEMA,
+    UPDATE_TYPES,
+};
+pub use reconnect::{Backoff, INITIAL_DELAY as RECONNECT_INITIAL_DELAY, MAX_DELAY as RECONNECT_MAX_DELAY};
+pub use record::{Compression, Frame, Recorder, Replayer, Snapshot, read_snapshot, write_snapshot};
+pub use regions::{Regions, Slot as RegionSlot};
+pub use retention::{Pruned, Retention, RetentionConfig};
+pub use sim::{Simulator, SyntheticLogProvider};
+pub use slo::{ErrorBudget, Slo};
+pub use slurm::{default_user as default_slurm_user, status as slurm_status, to_task as slurm_task, SlurmDataSource};
+pub use source::{DataSource, SourceEvent};
+pub use sse::{SseDataSource, TASK_UPDATE_EVENT as SSE_TASK_UPDATE_EVENT};
+pub use sort::{SortField, SortKey, SortOrder, TopMetric};
+pub use sounds::{Bell, SoundConfig, Sounds};
+pub use spark::{SparklineSource, Sparklines};
+pub use stderr::{capture as capture_stderr, captured_lines as stderr_captured_lines, last_captured_line as last_stderr_line, release as release_stderr};
+pub use state::{default_path as default_state_path, LocalState};
+pub use stream::StreamDataSource;
+pub use structured::{expanded as expand_structured_line, parse as parse_structured_line, summary as structured_summary};
+pub use table::render as render_task_table;
+#[cfg(feature = "telemetry")]
+pub use telemetry::{Telemetry, UsageReport};
+pub use tes::{status as tes_status, to_task as tes_task, TesDataSource};
+pub use theme::{Palette, StatusSymbols, Theme};
+pub use timeline::{layout as timeline_layout, step_name, Bar, Lane, LaneKey};
+pub use title::text as terminal_title;
+pub use tls::{Tls, TlsConfig};
+pub use tunnel::{Tunnel, TunnelConfig};
 pub use ui::draw;
+pub use undo::{LocalAction, UndoStack, UNDO_LIMIT};
+pub use updates::{Release, UpdateCheck, CHANGELOG_URL};
+pub use websocket::WebSocketDataSource;
+pub use workflow::WorkflowMetadata;
 
-use std::io;
-use std::time::Duration;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
 
 use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
@@ -21,23 +188,76 @@ pub fn init_terminal() -> io::Result<Terminal<CrosstermBackend<io::Stdout>>> {
     terminal::enable_raw_mode()?;
     let mut stdout = io::stdout();
     crossterm::execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    title::save(&mut stdout)?;
     let backend = CrosstermBackend::new(stdout);
     let terminal = Terminal::new(backend)?;
+    // Stray library warnings would land in the middle of the dashboard
+    stderr::capture()?;
     Ok(terminal)
 }
 
+/// Leaves raw mode and the alternate screen without a [`Terminal`], for use
+/// where the terminal handle is unavailable (such as a panic hook).
+pub fn restore_terminal_state() -> io::Result<()> {
+    stderr::release();
+    terminal::disable_raw_mode()?;
+    crossterm::execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, crossterm::cursor::Show)?;
+    title::restore(&mut io::stdout())
+}
+
 /// Restores the terminal to its original state.
 pub fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
+    stderr::release();
     terminal::disable_raw_mode()?;
     crossterm::execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
         DisableMouseCapture
     )?;
+    title::restore(terminal.backend_mut())?;
     terminal.show_cursor()?;
     Ok(())
 }
 
+/// Shows the task table on the normal screen with mouse capture off, so that
+/// it can be selected and copied with the terminal's own selection.
+fn enter_copy_mode(table: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    crossterm::execute!(stdout, LeaveAlternateScreen, DisableMouseCapture, crossterm::cursor::Show)?;
+    // Raw mode stays on to catch the key that returns, so lines need a
+    // carriage return
+    for line in table.lines() {
+        write!(stdout, "{}\r\n", line)?;
+    }
+    write!(stdout, "\r\n-- Copy mode: select the table to copy it, then press any key to return --\r\n")?;
+    stdout.flush()
+}
+
+/// Returns from copy mode to the dashboard, redrawing it in full.
+fn leave_copy_mode<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>) -> io::Result<()> {
+    crossterm::execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+    terminal.clear()
+}
+
+/// Hands the terminal to an external program until it exits, then returns
+/// to the dashboard, redrawing it in full.
+fn run_suspended<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    events: &EventHandler,
+    command: &ExternalCommand,
+) -> io::Result<io::Result<std::process::ExitStatus>> {
+    events.pause();
+    restore_terminal_state()?;
+    let status = command.run();
+    terminal::enable_raw_mode()?;
+    crossterm::execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+    title::save(&mut io::stdout())?;
+    stderr::capture()?;
+    terminal.clear()?;
+    events.resume();
+    Ok(status)
+}
+
 /// Runs the TUI application.
 // In the run_app function
 pub fn run_app<B: ratatui::backend::Backend>(
@@ -46,23 +266,79 @@ pub fn run_app<B: ratatui::backend::Backend>(
     tick_rate: Duration,
 ) -> io::Result<()> {
     let mut event_handler = EventHandler::new(tick_rate);
+    // While the task table is printed for copying, the dashboard is not drawn
+    let mut copy_mode = false;
+    // Title last given to the terminal window, to only write changes
+    let mut window_title: Option<String> = None;
 
     loop {
-        terminal.draw(|f| draw(f, app))?;
+        if !copy_mode {
+            let frame_start = Instant::now();
+            terminal.draw(|f| draw(f, app))?;
+            #[cfg(feature = "graphics")]
+            if let Some(graphics) = &app.graphics {
+                if graphics.flush(&mut io::stdout())? {
+                    terminal.clear()?;
+                }
+            }
+            app.perf.record_frame(frame_start.elapsed());
+            #[cfg(feature = "telemetry")]
+            app.record_terminal_size(terminal.size()?);
+        }
 
         // Fix the error handling for the event handler
         match event_handler.next() {
+            Ok(Event::Input(_)) if copy_mode => {
+                leave_copy_mode(terminal)?;
+                copy_mode = false;
+                #[cfg(feature = "graphics")]
+                if let Some(graphics) = &app.graphics {
+                    graphics.invalidate();
+                }
+            }
             Ok(Event::Input(key)) => {
                 if app.handle_key(key) {
                     break;
                 }
+                if let Some(text) = app.take_clipboard() {
+                    clipboard::copy(&mut io::stdout(), &text)?;
+                }
+                if let Some(table) = app.take_copy_table() {
+                    enter_copy_mode(&table)?;
+                    copy_mode = true;
+                }
+                if let Some(command) = app.take_external_command() {
+                    // Diff tools exit non-zero when the files differ, so only
+                    // a program that could not be started is reported
+                    if let Err(err) = run_suspended(terminal, &event_handler, &command)? {
+                        app.set_status(format!("Cannot run `{}`: {}", command.program, err));
+                    }
+                    // The program may have set a title of its own
+                    window_title = None;
+                    #[cfg(feature = "graphics")]
+                    if let Some(graphics) = &app.graphics {
+                        graphics.invalidate();
+                    }
+                }
             }
+            Ok(Event::Mouse(mouse)) if !copy_mode => app.handle_mouse(mouse),
             Ok(Event::Tick) => {
 app.update();
+                if app.terminal_title {
+                    let text = title::text(app);
+                    if window_title.as_deref() != Some(text.as_str()) {
+                        title::set(&mut io::stdout(), &text)?;
+                        window_title = Some(text);
+                    }
+                }
+                if app.sounds.take_bell() {
+                    io::stdout().write_all(b"\x07")?;
+                    io::stdout().flush()?;
+                }
             }
             Err(err) => {
-                // Handle the error appropriately
-                eprintln!("Error: {:?}", err);
+                // Standard error is captured while the dashboard is shown
+                crash::log(format!("event handler stopped: {:?}", err));
                 break;
             }
             _ => {}
@@ -0,0 +1,272 @@
+//! Processes run by Crankshaft's local executor.
+//!
+//! The local executor runs each task as a child process of the engine, so
+//! the processes on this machine are sampled on a background thread every
+//! two seconds for the children of the engine's process, found by its PID or
+//! its name. Each child is one task, named after its executable, and its
+//! resource use is that of the child and everything it started in turn, as
+//! tasks are often a shell running the actual tool.
+//!
+//! CPU use is a share of this machine's CPUs, averaged between two samples,
+//! so it reads zero until a process has been sampled twice; memory use is
+//! the resident set size, measured against this machine's memory. Whether a
+//! process succeeded is only known to its parent, so tasks leave the list
+//! when their process exits.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use crate::app::{MemoryUsage, Task, TaskStatus, TaskUpdate};
+use crate::capabilities::SourceCapabilities;
+use crate::logs::LogBuffer;
+use crate::polling::PollInterval;
+use crate::source::{DataSource, SourceEvent};
+
+/// How often processes are sampled unless configured otherwise.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What the sampling thread hands over.
+#[derive(Debug)]
+enum Message {
+    /// A change to the task store
+    Update(TaskUpdate),
+    /// Something that happened to the engine's process
+    Event(SourceEvent),
+}
+
+/// The child processes of a local engine, sampled on a background thread.
+pub struct LocalDataSource {
+    /// PID or name of the engine's process
+    engine: String,
+    /// Receives what the sampling thread found
+    receiver: Receiver<Message>,
+    /// Events received with the last poll's updates, not yet taken
+    events: Vec<SourceEvent>,
+    /// Time between fetches, shared with the sampling thread
+    interval: PollInterval,
+}
+
+impl LocalDataSource {
+    /// Starts sampling the children of `engine`, the PID or the name of the
+    /// engine's process. Every process of that name counts as the engine,
+    /// and one that restarts is found again.
+    pub fn watch(engine: &str) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let watched = engine.to_string();
+        let interval = PollInterval::new(POLL_INTERVAL);
+        let shared = interval.clone();
+        thread::spawn(move || follow(&watched, &shared, &sender));
+        Self {
+            engine: engine.to_string(),
+            receiver,
+            events: Vec::new(),
+            interval,
+        }
+    }
+
+    /// Returns the PID or name of the engine's process.
+    pub fn engine(&self) -> &str {
+        &self.engine
+    }
+}
+
+impl DataSource for LocalDataSource {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    /// Resource use only: a process's output and outcome belong to the
+    /// engine that started it.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            cpu: true,
+            memory: true,
+            ..SourceCapabilities::NONE
+        }
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        let mut updates = Vec::new();
+        for message in self.receiver.try_iter() {
+            match message {
+                Message::Update(update) => updates.push(update),
+                Message::Event(event) => self.events.push(event),
+            }
+        }
+        updates
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn interval(&self) -> Option<&PollInterval> {
+        Some(&self.interval)
+    }
+}
+
+/// Samples processes until the app is gone, reporting when the engine's
+/// process cannot be found and carrying on.
+fn follow(engine: &str, interval: &PollInterval, sender: &Sender<Message>) {
+    let mut system = System::new();
+    system.refresh_memory();
+    let memory = system.total_memory();
+    let cpus = thread::available_parallelism().map_or(1, usize::from) as f64;
+    // The task last handed over for each child, to send only changes
+    let mut reported: HashMap<String, Task> = HashMap::new();
+    // Whether the engine was found by the last sample, once sampled
+    let mut found = None;
+    loop {
+        let started = Instant::now();
+        let mut messages = Vec::new();
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_cpu().with_memory().with_cmd(UpdateKind::OnlyIfNotSet).without_tasks(),
+        );
+        let processes = system.processes();
+        let engines = engines(processes, engine);
+        if engines.is_empty() {
+            if found != Some(false) {
+                let reason = format!("Process {} is not running", engine);
+                messages.push(Message::Event(SourceEvent::Failed(reason)));
+                found = Some(false);
+            }
+        } else {
+            messages.push(Message::Event(SourceEvent::Connected { latency: started.elapsed() }));
+            if found != Some(true) {
+                let message = format!("Watching the processes started by process {}", engine);
+                messages.push(Message::Event(SourceEvent::Message(message)));
+                found = Some(true);
+            }
+        }
+
+        let children = children(processes);
+        let mut stale: HashSet<String> = reported.keys().cloned().collect();
+        for pid in engines.iter().flat_map(|engine| children.get(engine)).flatten() {
+            let task = to_task(&processes[pid], &descendants(*pid, &children), processes, cpus, memory);
+            stale.remove(&task.id);
+            let update = match reported.get(&task.id) {
+                None => TaskUpdate::Created(Box::new(task.clone())),
+                Some(previous) if serde_json::to_value(previous).ok() == serde_json::to_value(&task).ok() => continue,
+                // Most samples only change the resource use
+                Some(previous) if same_but_usage(previous, &task) => TaskUpdate::Metrics {
+                    id: task.id.clone(),
+                    cpu_usage: Some(task.cpu_usage),
+                    memory_usage: Some(task.memory_usage),
+                },
+                Some(_) => TaskUpdate::Created(Box::new(task.clone())),
+            };
+            reported.insert(task.id.clone(), task);
+            messages.push(Message::Update(update));
+        }
+        for id in stale {
+            reported.remove(&id);
+            messages.push(Message::Update(TaskUpdate::Removed { id }));
+        }
+        for message in messages {
+            if sender.send(message).is_err() {
+                return;
+            }
+        }
+        interval.wait_from(started);
+    }
+}
+
+/// Returns the processes that count as the engine: the one with its PID, or
+/// those with its name.
+fn engines(processes: &HashMap<Pid, Process>, engine: &str) -> Vec<Pid> {
+    if let Ok(pid) = engine.parse::<u32>() {
+        let pid = Pid::from_u32(pid);
+        return processes.contains_key(&pid).then_some(pid).into_iter().collect();
+    }
+    processes.iter().filter(|(_, process)| process.name() == engine).map(|(pid, _)| *pid).collect()
+}
+
+/// Returns the children of each process, in the order they started.
+fn children(processes: &HashMap<Pid, Process>) -> HashMap<Pid, Vec<Pid>> {
+    let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for (pid, process) in processes {
+        if let Some(parent) = process.parent() {
+            children.entry(parent).or_default().push(*pid);
+        }
+    }
+    for pids in children.values_mut() {
+        pids.sort_by_key(|pid| (processes[pid].start_time(), *pid));
+    }
+    children
+}
+
+/// Returns `pid` and every process it started, directly or not.
+fn descendants(pid: Pid, children: &HashMap<Pid, Vec<Pid>>) -> Vec<Pid> {
+    let mut tree = vec![pid];
+    let mut next = 0;
+    while let Some(pid) = tree.get(next) {
+        tree.extend(children.get(pid).into_iter().flatten());
+        next += 1;
+    }
+    tree
+}
+
+/// Converts a child of the engine, with the processes of its `tree`, into a
+/// task using some of this machine's `cpus` and `memory`.
+fn to_task(process: &Process, tree: &[Pid], processes: &HashMap<Pid, Process>, cpus: f64, memory: u64) -> Task {
+    let tree: Vec<&Process> = tree.iter().filter_map(|pid| processes.get(pid)).collect();
+    // Usage is a percentage of one CPU
+    let used: f64 = tree.iter().map(|process| f64::from(process.cpu_usage()) / 100.0).sum();
+    let pid = process.pid().to_string();
+    let name = process.name().to_string_lossy().into_owned();
+    let command: Vec<String> = process.cmd().iter().map(|arg| arg.to_string_lossy().into_owned()).collect();
+    let mut labels = BTreeMap::from([("backend".to_string(), "local".to_string()), ("pid".to_string(), pid.clone())]);
+    if tree.len() > 1 {
+        labels.insert("processes".to_string(), tree.len().to_string());
+    }
+    let raw = serde_json::json!({
+        "pid": process.pid().as_u32(),
+        "parent": process.parent().map(Pid::as_u32),
+        "name": name,
+        "cmd": command,
+        "start_time": process.start_time(),
+    });
+
+    Task {
+        id: pid,
+        name,
+        status: TaskStatus::Running,
+        progress: 0.0,
+        cpu_usage: (used / cpus).min(1.0),
+        cpus: None,
+        memory_usage: MemoryUsage {
+            used: tree.iter().map(|process| process.memory()).sum(),
+            requested: None,
+            limit: (memory > 0).then_some(memory),
+        },
+        started_at: Some(process.start_time()),
+        finished_at: None,
+        dependencies: Vec::new(),
+        queue: None,
+        attempt: None,
+        labels,
+        command,
+        env: BTreeMap::new(),
+        executors: Vec::new(),
+        container: None,
+        pods: Vec::new(),
+        logs: LogBuffer::default(),
+        raw: Some(raw),
+        reported_progress: None,
+        last_updated: None,
+    }
+}
+
+/// Returns whether two samples of a task differ only in resource use.
+fn same_but_usage(previous: &Task, task: &Task) -> bool {
+    let mut previous = previous.clone();
+    previous.cpu_usage = task.cpu_usage;
+    previous.memory_usage = task.memory_usage;
+    serde_json::to_value(previous).ok() == serde_json::to_value(task).ok()
+}
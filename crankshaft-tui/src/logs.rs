@@ -0,0 +1,448 @@
+//! Memory-bounded per-task log retention and on-demand log fetching.
+//!
+//! Logs are not streamed for every task. Only the task being viewed is kept
+//! up to date by a background [`LogFetcher`]; a handful of recently viewed
+//! tasks keep their buffers as a cache, and everything else is dropped.
+//!
+//! A task's full log can still be saved to a file: the fetcher pages through
+//! it from the provider on a separate thread, reporting progress as it goes.
+//! Fetches reach the provider through a cache limiting how often it is
+//! called (see [`crate::cache`]).
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use serde::Deserialize;
+
+use crate::cache::{CachedProvider, FetchConfig, FetchControl, FetchStats};
+
+/// Default number of lines retained per task.
+pub const DEFAULT_MAX_LINES: usize = 10_000;
+
+/// Default number of bytes retained per task (1 MiB).
+pub const DEFAULT_MAX_BYTES: usize = 1024 * 1024;
+
+/// Number of lines fetched when a task's log is first opened.
+pub const INITIAL_TAIL_LINES: usize = 500;
+
+/// Number of lines written between progress reports of a download.
+const DOWNLOAD_BATCH_LINES: usize = 2_000;
+
+/// Limits applied to every task's log buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogLimits {
+    /// Maximum number of lines retained
+    pub max_lines: usize,
+    /// Maximum number of bytes retained across all lines
+    pub max_bytes: usize,
+}
+
+impl Default for LogLimits {
+    fn default() -> Self {
+        Self {
+            max_lines: DEFAULT_MAX_LINES,
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+}
+
+/// A ring of log lines that evicts the oldest lines once either limit is hit.
+#[derive(Debug, Clone, Default)]
+pub struct LogBuffer {
+    /// Retained lines, oldest first
+    lines: VecDeque<String>,
+    /// Total bytes held by `lines`
+    bytes: usize,
+    /// Number of lines evicted since the buffer was created
+    evicted: usize,
+    /// Absolute index (within the task's full log) of the first retained line
+    first_index: usize,
+    /// Whether any output has been fetched from a log provider
+    hydrated: bool,
+    /// The last error reported while fetching this log
+    error: Option<String>,
+    /// Retention limits
+    limits: LogLimits,
+}
+
+impl LogBuffer {
+    /// Creates an empty buffer with the given limits.
+    pub fn new(limits: LogLimits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    /// Appends a line, evicting the oldest lines if a limit is exceeded.
+    ///
+    /// A single line longer than the byte limit is cut down to fit.
+    pub fn push(&mut self, line: impl Into<String>) {
+        let mut line = line.into();
+        if line.len() > self.limits.max_bytes {
+            let mut end = self.limits.max_bytes;
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+            line.truncate(end);
+        }
+
+        self.bytes += line.len();
+        self.lines.push_back(line);
+        self.enforce();
+    }
+
+    /// Changes the limits, evicting lines immediately if necessary.
+    pub fn set_limits(&mut self, limits: LogLimits) {
+        self.limits = limits;
+        self.enforce();
+    }
+
+    /// Returns the retained lines, oldest first.
+    pub fn lines(&self) -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator {
+        self.lines.iter().map(String::as_str)
+    }
+
+    /// Returns the number of retained lines.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns `true` if no lines are retained.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Returns the number of bytes currently retained.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Returns the number of lines that were evicted to stay within limits.
+    pub fn evicted(&self) -> usize {
+        self.evicted
+    }
+
+    /// Returns `true` if earlier lines have been dropped.
+    pub fn is_truncated(&self) -> bool {
+        self.evicted > 0
+    }
+
+    /// Returns the number of earlier lines that are not held in memory,
+    /// either because they were evicted or because they were never fetched.
+    pub fn omitted(&self) -> usize {
+        self.first_index
+    }
+
+    /// Returns the absolute index of the line following the last retained one.
+    pub fn next_index(&self) -> usize {
+        self.first_index + self.lines.len()
+    }
+
+    /// Returns `true` once output has been fetched from a log provider.
+    pub fn is_hydrated(&self) -> bool {
+        self.hydrated
+    }
+
+    /// Returns the last fetch error, if any.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Merges a fetched chunk into the buffer.
+    ///
+    /// Chunks continuing from the end of the buffer are appended; any other
+    /// chunk (an initial tail or a full reload) replaces the buffer contents.
+    pub fn apply(&mut self, chunk: LogChunk) {
+        self.hydrated = true;
+        self.error = None;
+
+        if chunk.start != self.next_index() || self.lines.is_empty() {
+            self.lines.clear();
+            self.bytes = 0;
+            self.evicted = 0;
+            self.first_index = chunk.start;
+        }
+
+        for line in chunk.lines {
+            self.push(line);
+        }
+    }
+
+    /// Records a failed fetch.
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    /// Drops all retained output, keeping the limits.
+    pub fn clear(&mut self) {
+        *self = Self::new(self.limits);
+    }
+
+    /// Drops lines from the front until both limits are satisfied.
+    fn enforce(&mut self) {
+        while self.lines.len() > self.limits.max_lines || self.bytes > self.limits.max_bytes {
+            match self.lines.pop_front() {
+                Some(line) => {
+                    self.bytes -= line.len();
+                    self.evicted += 1;
+                    self.first_index += 1;
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+/// The portion of a task's log to fetch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogRange {
+    /// The last `n` lines
+    Tail(usize),
+    /// Every line starting at the given absolute index
+    From(usize),
+}
+
+/// A contiguous run of log lines returned by a [`LogProvider`].
+#[derive(Debug, Clone, Default)]
+pub struct LogChunk {
+    /// Absolute index of the first line in `lines`
+    pub start: usize,
+    /// The fetched lines
+    pub lines: Vec<String>,
+}
+
+/// A backend capable of returning log output for a task on request.
+pub trait LogProvider: Send + 'static {
+    /// Fetches the requested range of a task's log.
+    fn fetch(&mut self, task_id: &str, range: LogRange) -> eyre::Result<LogChunk>;
+}
+
+/// Where a log download stands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadState {
+    /// Lines are still being fetched and written
+    Running,
+    /// The whole log was written
+    Finished,
+    /// Fetching or writing failed; the file holds what was written before
+    Failed(String),
+}
+
+/// Progress of saving a task's full log to a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogDownload {
+    /// The task whose log is saved
+    pub task_id: String,
+    /// The file being written
+    pub path: PathBuf,
+    /// Lines written so far
+    pub lines: usize,
+    /// Bytes written so far
+    pub bytes: u64,
+    /// Whether the download is still running
+    pub state: DownloadState,
+}
+
+impl LogDownload {
+    /// Returns `true` while lines are still being written.
+    pub fn is_running(&self) -> bool {
+        self.state == DownloadState::Running
+    }
+}
+
+/// The outcome of a background fetch.
+struct LogResponse {
+    /// The task the fetch was for
+    task_id: String,
+    /// The fetched chunk or the error that occurred
+    result: eyre::Result<LogChunk>,
+}
+
+/// Runs a [`LogProvider`] on a background thread.
+pub struct LogFetcher {
+    /// The provider, shared by the worker and download threads
+    provider: Arc<Mutex<dyn LogProvider>>,
+    /// Request channel to the worker thread
+    requests: mpsc::Sender<(String, LogRange)>,
+    /// Completed fetches from the worker thread
+    responses: mpsc::Receiver<LogResponse>,
+    /// Tasks with an outstanding request
+    in_flight: HashSet<String>,
+    /// Full reloads asked for while a task's request was outstanding, sent
+    /// once it completes
+    upgrades: HashMap<String, LogRange>,
+    /// Caching and limiting of the provider's fetches
+    control: FetchControl,
+    /// Progress channel handed to download threads
+    progress_tx: mpsc::Sender<LogDownload>,
+    /// Progress reported by download threads
+    progress: mpsc::Receiver<LogDownload>,
+}
+
+impl LogFetcher {
+    /// Spawns a worker thread serving requests with the given provider.
+    pub fn spawn(provider: impl LogProvider) -> Self {
+        let provider = CachedProvider::new(provider, FetchConfig::default());
+        let control = provider.control();
+        let provider: Arc<Mutex<dyn LogProvider>> = Arc::new(Mutex::new(provider));
+        let (requests, request_rx) = mpsc::channel::<(String, LogRange)>();
+        let (response_tx, responses) = mpsc::channel();
+        let (progress_tx, progress) = mpsc::channel();
+
+        let worker = Arc::clone(&provider);
+        thread::spawn(move || {
+            for (task_id, range) in request_rx {
+                let result = fetch(&worker, &task_id, range);
+                if response_tx.send(LogResponse { task_id, result }).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Self {
+            provider,
+            requests,
+            responses,
+            in_flight: HashSet::new(),
+            upgrades: HashMap::new(),
+            control,
+            progress_tx,
+            progress,
+        }
+    }
+
+    /// Queues a fetch unless one is already outstanding for the task. A
+    /// full reload, from the first line, is not dropped that way: it waits
+    /// for the outstanding fetch and is sent when that completes.
+    ///
+    /// Returns `true` if a request was queued.
+    pub fn request(&mut self, task_id: &str, range: LogRange) -> bool {
+        if self.in_flight.contains(task_id) {
+            if range != LogRange::From(0) {
+                return false;
+            }
+            self.upgrades.insert(task_id.to_string(), range);
+            return true;
+        }
+        self.send(task_id, range)
+    }
+
+    /// Hands a fetch to the worker thread, marking the task as outstanding.
+    fn send(&mut self, task_id: &str, range: LogRange) -> bool {
+        if self.requests.send((task_id.to_string(), range)).is_err() {
+            return false;
+        }
+        self.in_flight.insert(task_id.to_string());
+        true
+    }
+
+    /// Changes how the provider's fetches are cached and limited.
+    pub fn set_fetch_config(&self, config: FetchConfig) {
+        self.control.set_config(config);
+    }
+
+    /// Returns the fetches made through the provider so far.
+    pub fn fetch_stats(&self) -> FetchStats {
+        self.control.stats()
+    }
+
+    /// Returns `true` if a fetch is outstanding for the task.
+    pub fn is_pending(&self, task_id: &str) -> bool {
+        self.in_flight.contains(task_id)
+    }
+
+    /// Returns all completed fetches without blocking.
+    pub fn drain(&mut self) -> Vec<(String, eyre::Result<LogChunk>)> {
+        let mut completed = Vec::new();
+        while let Ok(response) = self.responses.try_recv() {
+            self.in_flight.remove(&response.task_id);
+            if let Some(range) = self.upgrades.remove(&response.task_id) {
+                self.send(&response.task_id, range);
+            }
+            completed.push((response.task_id, response.result));
+        }
+        completed
+    }
+
+    /// Starts saving a task's full log to `path` on its own thread.
+    ///
+    /// `cached` holds the log's first lines if they are already in memory;
+    /// only the lines after them are fetched. The provider is paged until it
+    /// has no further lines, so a running task's log is saved as far as it
+    /// got by then. Progress is reported through [`LogFetcher::downloads`].
+    pub fn download(&self, task_id: &str, cached: Vec<String>, path: PathBuf) {
+        let provider = Arc::clone(&self.provider);
+        let progress_tx = self.progress_tx.clone();
+        let mut download = LogDownload {
+            task_id: task_id.to_string(),
+            path,
+            lines: 0,
+            bytes: 0,
+            state: DownloadState::Running,
+        };
+
+        thread::spawn(move || {
+            let result = write_log(&provider, &mut download, cached, &progress_tx);
+            download.state = match result {
+                Ok(()) => DownloadState::Finished,
+                Err(err) => DownloadState::Failed(format!("{:#}", err)),
+            };
+            let _ = progress_tx.send(download);
+        });
+    }
+
+    /// Returns the progress reported by downloads since the last call, in
+    /// the order it was reported.
+    pub fn downloads(&mut self) -> Vec<LogDownload> {
+        self.progress.try_iter().collect()
+    }
+}
+
+/// Fetches a range of a task's log with the shared provider.
+fn fetch(provider: &Mutex<dyn LogProvider>, task_id: &str, range: LogRange) -> eyre::Result<LogChunk> {
+    provider
+        .lock()
+        .map_err(|_| eyre::eyre!("log provider panicked"))?
+        .fetch(task_id, range)
+}
+
+/// Writes the cached lines and then every line the provider has after them,
+/// reporting progress every [`DOWNLOAD_BATCH_LINES`] lines.
+fn write_log(
+    provider: &Mutex<dyn LogProvider>,
+    download: &mut LogDownload,
+    cached: Vec<String>,
+    progress_tx: &mpsc::Sender<LogDownload>,
+) -> eyre::Result<()> {
+    let mut file = BufWriter::new(File::create(&download.path)?);
+    let mut reported = 0;
+    let mut next = cached.len();
+    let mut lines = cached;
+    loop {
+        for line in &lines {
+            writeln!(file, "{}", line)?;
+            download.lines += 1;
+            download.bytes += line.len() as u64 + 1;
+            if download.lines - reported >= DOWNLOAD_BATCH_LINES {
+                reported = download.lines;
+                let _ = progress_tx.send(download.clone());
+            }
+        }
+
+        let chunk = fetch(provider, &download.task_id, LogRange::From(next))?;
+        let end = chunk.start + chunk.lines.len();
+        if chunk.lines.is_empty() || end <= next {
+            break;
+        }
+        next = end;
+        lines = chunk.lines;
+    }
+    file.flush()?;
+    Ok(())
+}
@@ -0,0 +1,330 @@
+//! Jobs in an IBM Spectrum LSF cluster.
+//!
+//! `bjobs -json` is run on a background thread every ten seconds, and each
+//! job is one task named after the job. Unless a user is given, LSF lists
+//! the jobs of the user running the monitor. Finished jobs are listed as
+//! well until LSF cleans them up, after which they stay on screen in their
+//! final state. `bjobs` must be on `PATH`, so the monitor runs on a host of
+//! the cluster.
+//!
+//! The task list shows each job's queue and execution hosts as columns, and
+//! the duration column shows the run time LSF reports. Pending jobs carry
+//! the reason they are waiting as their `reason` label.
+//!
+//! Job states map onto task statuses as follows:
+//!
+//! - `PEND` and `PSUSP` are pending
+//! - `RUN`, `PROV`, `USUSP`, `SSUSP`, `WAIT`, and `UNKWN` are running
+//! - `DONE` is completed
+//! - `EXIT` and `ZOMBI` are failed
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use eyre::WrapErr;
+use serde::Deserialize;
+
+use crate::app::{MemoryUsage, Task, TaskStatus, TaskUpdate};
+use crate::capabilities::SourceCapabilities;
+use crate::logs::LogBuffer;
+use crate::polling::PollInterval;
+use crate::record::unix_now;
+use crate::source::{DataSource, SourceEvent};
+
+/// How often jobs are listed unless configured otherwise.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Fields asked of `bjobs`, which reports them under their names in upper
+/// case.
+const BJOBS_FIELDS: &str = "jobid jobindex job_name stat queue exec_host run_time nalloc_slot slots exit_code pend_reason";
+
+/// Labels shown as columns of the task list.
+const LABEL_COLUMNS: [&str; 2] = ["queue", "host"];
+
+/// What the polling thread hands over.
+#[derive(Debug)]
+enum Message {
+    /// A change to the task store
+    Update(TaskUpdate),
+    /// Something that happened to the connection
+    Event(SourceEvent),
+}
+
+/// The jobs of one LSF user, polled on a background thread.
+pub struct LsfDataSource {
+    /// User whose jobs are listed, if not the one running the monitor
+    user: Option<String>,
+    /// Receives what the polling thread found
+    receiver: Receiver<Message>,
+    /// Events received with the last poll's updates, not yet taken
+    events: Vec<SourceEvent>,
+    /// Time between fetches, shared with the polling thread
+    interval: PollInterval,
+}
+
+impl LsfDataSource {
+    /// Starts polling the jobs of `user`, or of the user running the monitor
+    /// if `None`.
+    pub fn connect(user: Option<&str>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let owner = user.map(str::to_string);
+        let interval = PollInterval::new(POLL_INTERVAL);
+        let shared = interval.clone();
+        thread::spawn(move || follow(owner.as_deref(), &shared, &sender));
+        Self {
+            user: user.map(str::to_string),
+            receiver,
+            events: Vec::new(),
+            interval,
+        }
+    }
+
+    /// Returns the user whose jobs are listed, if not the one running the
+    /// monitor.
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+}
+
+impl DataSource for LsfDataSource {
+    fn name(&self) -> &str {
+        "lsf"
+    }
+
+    /// Statuses only: resource use and output are not read from LSF.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::NONE
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        let mut updates = Vec::new();
+        for message in self.receiver.try_iter() {
+            match message {
+                Message::Update(update) => updates.push(update),
+                Message::Event(event) => self.events.push(event),
+            }
+        }
+        updates
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn interval(&self) -> Option<&PollInterval> {
+        Some(&self.interval)
+    }
+
+    fn label_columns(&self) -> Vec<String> {
+        LABEL_COLUMNS.map(str::to_string).to_vec()
+    }
+}
+
+/// Returns the status a job state stands for.
+pub fn status(stat: &str) -> TaskStatus {
+    match stat {
+        "PEND" | "PSUSP" => TaskStatus::Pending,
+        "DONE" => TaskStatus::Completed,
+        "EXIT" | "ZOMBI" => TaskStatus::Failed,
+        _ => TaskStatus::Running,
+    }
+}
+
+/// The output of `bjobs -json`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+struct Listing {
+    /// The jobs, converted one by one
+    #[serde(default)]
+    records: Vec<serde_json::Value>,
+}
+
+/// A job as `bjobs -json` reports the fields the source asks for. LSF
+/// reports every field as a string, empty where it does not apply.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "UPPERCASE")]
+struct Record {
+    /// ID of the job, shared by the elements of a job array
+    jobid: String,
+    /// Index of the element in its job array, `0` for other jobs
+    jobindex: String,
+    /// Name given when the job was submitted
+    job_name: String,
+    /// Job state, such as `PEND`, `RUN`, or `DONE`
+    stat: String,
+    /// Queue the job was submitted to
+    queue: String,
+    /// Hosts the job runs on, `-` before it is dispatched
+    exec_host: String,
+    /// Time the job has run, such as `754 second(s)`
+    run_time: String,
+    /// Slots allocated to the job
+    nalloc_slot: String,
+    /// Slots the job asked for
+    slots: String,
+    /// Exit code of the job, empty if it exited with 0
+    exit_code: String,
+    /// Why the job is still pending
+    pend_reason: String,
+}
+
+/// Converts a record of `bjobs -json` output with the fields the source asks
+/// for, where `now` is when `bjobs` ran, keeping the record as the task's
+/// raw JSON.
+pub fn to_task(value: &serde_json::Value, now: u64) -> serde_json::Result<Task> {
+    let record = Record::deserialize(value)?;
+    let status = status(&record.stat);
+    // Elements of job arrays share the array's ID
+    let id = match record.jobindex.as_str() {
+        "" | "0" => record.jobid.clone(),
+        index => format!("{}[{}]", record.jobid, index),
+    };
+    let mut labels = BTreeMap::from([("backend".to_string(), "lsf".to_string())]);
+    labels.insert("queue".to_string(), record.queue.clone());
+    if !matches!(record.exec_host.as_str(), "" | "-") {
+        labels.insert("host".to_string(), record.exec_host.clone());
+    }
+    if status == TaskStatus::Pending && !record.pend_reason.is_empty() {
+        labels.insert("reason".to_string(), record.pend_reason.trim().trim_end_matches(';').to_string());
+    }
+    if matches!(status, TaskStatus::Completed | TaskStatus::Failed) {
+        let exit_code = if record.exit_code.is_empty() { "0" } else { record.exit_code.as_str() };
+        labels.insert("exit_code".to_string(), exit_code.to_string());
+    }
+    // The run time reads like `754 second(s)`
+    let run_time = record.run_time.split_whitespace().next().and_then(|seconds| seconds.parse::<u64>().ok());
+    let finished = matches!(status, TaskStatus::Completed | TaskStatus::Failed);
+    let slots = [&record.nalloc_slot, &record.slots].into_iter().find_map(|slots| slots.parse::<f64>().ok().filter(|slots| *slots > 0.0));
+
+    Ok(Task {
+        name: if record.job_name.is_empty() { id.clone() } else { record.job_name },
+        id,
+        status,
+        progress: if status == TaskStatus::Completed { 1.0 } else { 0.0 },
+        cpu_usage: 0.0,
+        cpus: slots,
+        memory_usage: MemoryUsage::default(),
+        // Counted back from the run time; a job that has already finished
+        // is taken to have finished now, which keeps its duration right
+        started_at: (status != TaskStatus::Pending).then(|| run_time.map(|run_time| now.saturating_sub(run_time))).flatten(),
+        finished_at: finished.then_some(now),
+        dependencies: Vec::new(),
+        queue: None,
+        attempt: None,
+        labels,
+        command: Vec::new(),
+        env: BTreeMap::new(),
+        executors: Vec::new(),
+        container: None,
+        pods: Vec::new(),
+        logs: LogBuffer::default(),
+        raw: Some(value.clone()),
+        reported_progress: None,
+        last_updated: None,
+    })
+}
+
+/// Polls `bjobs` until the app is gone, reporting failed polls and carrying
+/// on after them.
+fn follow(user: Option<&str>, interval: &PollInterval, sender: &Sender<Message>) {
+    // The task last handed over for each listed job, to send only changes
+    let mut reported: HashMap<String, Task> = HashMap::new();
+    let mut reachable = false;
+    loop {
+        let started = Instant::now();
+        let mut messages = Vec::new();
+        match bjobs(user) {
+            Ok(records) => {
+                messages.push(Message::Event(SourceEvent::Connected { latency: started.elapsed() }));
+                if !reachable {
+                    let message = match user {
+                        Some(user) => format!("Listing the LSF jobs of {}", user),
+                        None => "Listing LSF jobs".to_string(),
+                    };
+                    messages.push(Message::Event(SourceEvent::Message(message)));
+                    reachable = true;
+                }
+                let now = unix_now();
+                let mut stale: HashSet<String> = reported.keys().cloned().collect();
+                for value in records {
+                    // Jobs that LSF no longer knows come back as an error
+                    if value.get("ERROR").is_some() {
+                        continue;
+                    }
+                    let mut task = match to_task(&value, now) {
+                        Ok(task) => task,
+                        Err(err) => {
+                            messages.push(Message::Event(SourceEvent::Dropped(format!("{}: {}", err, value))));
+                            continue;
+                        }
+                    };
+                    stale.remove(&task.id);
+                    let previous = reported.get(&task.id);
+                    keep_times(previous, &mut task);
+                    if previous.is_none_or(|previous| serde_json::to_value(previous).ok() != serde_json::to_value(&task).ok()) {
+                        reported.insert(task.id.clone(), task.clone());
+                        messages.push(Message::Update(TaskUpdate::Created(Box::new(task))));
+                    }
+                }
+                // Finished jobs that LSF cleaned up stay as they were last
+                // listed; others were removed from the cluster
+                for id in stale {
+                    let finished = reported.remove(&id).is_some_and(|task| matches!(task.status, TaskStatus::Completed | TaskStatus::Failed));
+                    if !finished {
+                        messages.push(Message::Update(TaskUpdate::Removed { id }));
+                    }
+                }
+            }
+            Err(err) => {
+                reachable = false;
+                let reason = format!("Cannot list LSF jobs: {:#}", err);
+                messages.push(Message::Event(SourceEvent::Failed(reason)));
+            }
+        }
+        for message in messages {
+            if sender.send(message).is_err() {
+                return;
+            }
+        }
+        interval.wait_from(started);
+    }
+}
+
+/// Keeps the times a job was last handed over with, as those worked out from
+/// the run time shift by a second or so between listings, and those of a
+/// finished job by the time between listings.
+fn keep_times(previous: Option<&Task>, task: &mut Task) {
+    let Some(previous) = previous else {
+        return;
+    };
+    if task.finished_at.is_some() && previous.finished_at.is_some() {
+        task.started_at = previous.started_at;
+        task.finished_at = previous.finished_at;
+    } else if let (Some(previous), Some(started_at)) = (previous.started_at, task.started_at) {
+        if previous.abs_diff(started_at) <= 2 {
+            task.started_at = Some(previous);
+        }
+    }
+}
+
+/// Lists the jobs of `user`, finished ones included.
+fn bjobs(user: Option<&str>) -> eyre::Result<Vec<serde_json::Value>> {
+    let mut command = Command::new("bjobs");
+    command.args(["-a", "-json", "-o", BJOBS_FIELDS]);
+    if let Some(user) = user {
+        command.args(["-u", user]);
+    }
+    let output = command.output().map_err(|err| eyre::eyre!("cannot run bjobs: {}", err))?;
+    // Without jobs to list, bjobs fails but still prints an empty listing
+    match serde_json::from_slice::<Listing>(&output.stdout) {
+        Ok(listing) => Ok(listing.records),
+        Err(_) if !output.status.success() => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            eyre::bail!("bjobs failed ({}): {}", output.status, stderr.trim())
+        }
+        Err(err) => Err(err).wrap_err("failed to decode the output of bjobs"),
+    }
+}
@@ -1,18 +1,560 @@
+use std::io::IsTerminal;
+use std::path::PathBuf;
 use std::time::Duration;
-use crankshaft_tui::{App, init_terminal, restore_terminal, run_app};
+use clap::{Parser, Subcommand};
+use crankshaft_tui::{
+    App, AwsBatchDataSource, Compression, Config, ControlCommand, CrashSnapshotter, DataSource, DiscoveredBackend, DiscoveredSource,
+    EngineConnection, FileDataSource, K8sDataSource, K8sTarget, LocalDataSource, RemoteAccess, LsfDataSource, MergedDataSource, SlurmDataSource, SseDataSource, Tab, TesDataSource, Recorder,
+    Replayer, StreamDataSource, WebSocketDataSource, ENGINE_CONFIG_FILE, init_terminal, restore_terminal, run_app,
+};
+
+/// Terminal User Interface for monitoring Crankshaft tasks.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Path of the control socket used by `ctl` (defaults to a per-user
+    /// socket in `$XDG_RUNTIME_DIR` or the temp directory).
+    #[arg(long, global = true, value_name = "PATH")]
+    control_socket: Option<PathBuf>,
+
+    /// Configuration file (defaults to
+    /// `$XDG_CONFIG_HOME/crankshaft-tui/config.toml` if present).
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Monitor the engine serving the monitor's REST protocol at this URL
+    /// (e.g. `http://127.0.0.1:7878`, as the mock engine does) instead of
+    /// showing the demo data. Any of the sources below can be
+    /// given along with it, and tasks several sources report are combined
+    /// as set in the `[merge]` configuration.
+    #[arg(long, value_name = "URL", conflicts_with_all = ["simulate", "replay"])]
+    engine: Option<String>,
+
+    /// Show the task updates pushed by the WebSocket feed at this URL (e.g.
+    /// `ws://127.0.0.1:7879/v1/events`, or a `wss://` URL), one or more per
+    /// message in the same format as `--stdin`, instead of showing the demo
+    /// data.
+    #[arg(long, value_name = "URL", conflicts_with_all = ["simulate", "replay"])]
+    websocket: Option<String>,
+
+    /// Show the task updates of the Server-Sent Events stream at this URL
+    /// (e.g. `http://127.0.0.1:7879/v1/events`), read from its
+    /// `task_update` events, instead of showing the demo data.
+    #[arg(long, value_name = "URL", conflicts_with_all = ["simulate", "replay"])]
+    sse: Option<String>,
+
+    /// Monitor the GA4GH TES service at this URL (e.g.
+    /// `https://tes.example.org/ga4gh/tes/v1`) instead of showing the demo
+    /// data.
+    #[arg(long, value_name = "URL", conflicts_with_all = ["simulate", "replay"])]
+    tes: Option<String>,
+
+    /// Monitor the containers started by Crankshaft's Docker backend
+    /// through the Docker daemon's socket (defaults to the `unix://` path in
+    /// `$DOCKER_HOST`, or `/var/run/docker.sock`) instead of showing the demo
+    /// data.
+    #[cfg(unix)]
+    #[arg(long, value_name = "SOCKET", conflicts_with_all = ["simulate", "replay"])]
+    docker: Option<Option<PathBuf>>,
+
+    /// Monitor the Jobs in this Kubernetes namespace (defaults to the pod's
+    /// own inside a cluster, and to `default` elsewhere) instead of showing
+    /// the demo data.
+    #[arg(long, value_name = "NAMESPACE", conflicts_with_all = ["simulate", "replay"])]
+    k8s: Option<Option<String>>,
+
+    /// Kubernetes API server to watch the Jobs through (defaults to the
+    /// cluster the monitor runs in, or else `kubectl proxy` at
+    /// `http://127.0.0.1:8001`).
+    #[arg(long, value_name = "URL", requires = "k8s")]
+    k8s_api: Option<String>,
+
+    /// Monitor the processes started by the local engine with this PID or
+    /// process name, with their real CPU and memory use, instead of showing
+    /// the demo data.
+    #[arg(long, value_name = "PID|NAME", conflicts_with_all = ["simulate", "replay"])]
+    local: Option<String>,
+
+    /// Monitor the Slurm jobs of this user (defaults to `$USER`) through
+    /// `squeue` and `sacct` instead of showing the demo data.
+    #[arg(long, value_name = "USER", conflicts_with_all = ["simulate", "replay"])]
+    slurm: Option<Option<String>>,
+
+    /// Monitor the LSF jobs of this user (defaults to the current user)
+    /// through `bjobs` instead of showing the demo data.
+    #[arg(long, value_name = "USER", conflicts_with_all = ["simulate", "replay"])]
+    lsf: Option<Option<String>>,
+
+    /// Monitor the jobs in these AWS Batch job queues, given by name or ARN
+    /// and separated by commas, through the AWS CLI instead of showing the
+    /// demo data.
+    #[arg(
+        long,
+        value_name = "QUEUE",
+        value_delimiter = ',',
+        num_args = 1..,
+        conflicts_with_all = ["simulate", "replay"]
+    )]
+    aws_batch: Option<Vec<String>>,
+
+    /// Show the task updates piped to standard input, one JSON object per
+    /// line in the engine's event format, instead of the demo data.
+    #[arg(long, conflicts_with_all = ["simulate", "replay"])]
+    stdin: bool,
+
+    /// Show the task updates local engines push to this named pipe, or to
+    /// a Unix socket bound at this path, in the same format as `--stdin`.
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["simulate", "replay"])]
+    listen: Option<PathBuf>,
+
+    /// Show the tasks in this state file, which the engine keeps up to date
+    /// as one JSON document or one task per line, reading it again whenever
+    /// it changes instead of showing the demo data.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["simulate", "replay"])]
+    file: Option<PathBuf>,
+
+    /// Follow the backends configured in this Crankshaft engine
+    /// configuration file (defaults to `crankshaft.toml` in the current
+    /// directory), along with any source given above of another kind.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["simulate", "replay"])]
+    discover: Option<Option<PathBuf>>,
+
+    /// Generate this many synthetic tasks with realistic update churn
+    /// instead of showing the demo data (useful for load testing).
+    #[arg(long, value_name = "COUNT")]
+    simulate: Option<usize>,
+
+    /// Maximum number of log lines retained per task (overrides the config).
+    #[arg(long, value_name = "LINES")]
+    log_max_lines: Option<usize>,
+
+    /// Maximum number of log bytes retained per task (overrides the config).
+    #[arg(long, value_name = "BYTES")]
+    log_max_bytes: Option<usize>,
+
+    /// Record the session to this file as JSON lines. A `.gz` or `.zst`
+    /// extension compresses the recording.
+    #[arg(long, value_name = "PATH", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Reach the engine, WebSocket, SSE, and TES URLs given through an SSH
+    /// tunnel to this host, resolving them there (overrides the host and
+    /// user of the `[tunnel]` configuration). URLs served over TLS cannot be
+    /// tunnelled.
+    #[arg(long, value_name = "[USER@]HOST")]
+    tunnel: Option<String>,
+
+    /// Play back a recorded session instead of showing live data.
+    /// Compression is detected from the file extension.
+    #[arg(long, value_name = "PATH", conflicts_with = "simulate")]
+    replay: Option<PathBuf>,
+
+    /// Render for screen readers: no decorative borders or emoji, and the
+    /// task list as plain sentences.
+    #[arg(long)]
+    screen_reader: bool,
+
+    /// Disable spinners and animated progress in favour of static indicators.
+    #[arg(long)]
+    reduced_motion: bool,
+
+    /// Check crates.io for a newer release on startup.
+    #[arg(long)]
+    check_updates: bool,
+
+    /// File where local state such as task display names is kept (defaults
+    /// to `$XDG_STATE_HOME/crankshaft-tui/state.json`).
+    #[arg(long, value_name = "PATH")]
+    state_file: Option<PathBuf>,
+
+    /// Start with this task selected (e.g. `task-1234`).
+    #[arg(long, value_name = "TASK_ID")]
+    select: Option<String>,
+
+    /// Start on this tab (tasks, logs, statistics, timeline, events, help).
+    #[arg(long, value_name = "TAB")]
+    tab: Option<Tab>,
+
+    /// Start in dry-run mode: cancel, retry, and priority changes are
+    /// recorded in the audit log but not sent (toggle with `d`).
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Compression used for snapshots written with `S` (none, gzip, zstd).
+    #[arg(long, value_name = "FORMAT", default_value = "none")]
+    snapshot_compression: Compression,
+}
+
+/// What to do instead of showing the dashboard.
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Send a command to an already-running instance.
+    Ctl {
+        /// The command to send
+        #[command(subcommand)]
+        action: CtlAction,
+    },
+    /// Print the JSON schema of the task updates the monitor reads.
+    Schema,
+}
+
+/// Commands accepted by a running instance's control socket.
+#[derive(Debug, Subcommand)]
+enum CtlAction {
+    /// Select a task by ID.
+    Select {
+        /// The task to select.
+        task_id: String,
+    },
+    /// Filter the task list by ID or name (omit the text to clear it).
+    Filter {
+        /// Text to match.
+        text: Option<String>,
+    },
+    /// Switch tabs.
+    Tab {
+        /// The tab to show (tasks, logs, statistics, timeline, events, help).
+        tab: Tab,
+    },
+}
+
+impl From<CtlAction> for ControlCommand {
+    fn from(action: CtlAction) -> Self {
+        match action {
+            CtlAction::Select { task_id } => ControlCommand::Select(task_id),
+            CtlAction::Filter { text } => ControlCommand::Filter(text.unwrap_or_default()),
+            CtlAction::Tab { tab } => ControlCommand::Tab(tab),
+        }
+    }
+}
+
+/// Sends a control command to a running instance and reports its reply.
+#[cfg(unix)]
+fn run_ctl(socket: &std::path::Path, command: ControlCommand) -> Result<(), Box<dyn std::error::Error>> {
+    let reply = crankshaft_tui::send_control(socket, &command)
+        .map_err(|err| format!("failed to reach {}: {}", socket.display(), err))?;
+    match reply.strip_prefix("error: ") {
+        Some(err) => Err(err.into()),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn run_ctl(_: &std::path::Path, _: ControlCommand) -> Result<(), Box<dyn std::error::Error>> {
+    Err("the control socket is only supported on Unix platforms".into())
+}
+
+/// Creates the application for `--docker`, if it was given.
+#[cfg(unix)]
+fn docker_app(args: &Args) -> Option<App> {
+    let socket = args.docker.clone()?.unwrap_or_else(crankshaft_tui::default_docker_socket);
+    Some(App::with_docker(&socket))
+}
+
+#[cfg(not(unix))]
+fn docker_app(_: &Args) -> Option<App> {
+    None
+}
+
+/// Returns where `--k8s` watches Jobs: the API server given with
+/// `--k8s-api`, the cluster the monitor runs in, or `kubectl proxy`. A
+/// configured token or CA replaces the service account's.
+fn k8s_target(namespace: Option<&str>, api: Option<&str>, access: RemoteAccess) -> K8sTarget {
+    let mut target = match api {
+        Some(api) => K8sTarget::new(api, namespace.unwrap_or("default")),
+        None => K8sTarget::in_cluster(namespace)
+            .unwrap_or_else(|| K8sTarget::new(crankshaft_tui::K8S_PROXY_URL, namespace.unwrap_or("default"))),
+    };
+    if access.token.is_some() {
+        target.token = access.token;
+    }
+    if access.tls.ca_certificates.is_some() {
+        target.tls.ca_certificates = access.tls.ca_certificates;
+    }
+    target.tls.client_identity = access.tls.client_identity;
+    target
+}
+
+/// Starts listening for `--listen`, if it was given.
+#[cfg(unix)]
+fn listener(args: &Args) -> std::io::Result<Option<StreamDataSource>> {
+    let Some(path) = &args.listen else {
+        return Ok(None);
+    };
+    let listener = StreamDataSource::listen(path)
+        .map_err(|err| std::io::Error::new(err.kind(), format!("cannot listen on {}: {}", path.display(), err)))?;
+    Ok(Some(listener))
+}
+
+#[cfg(not(unix))]
+fn listener(_: &Args) -> std::io::Result<Option<StreamDataSource>> {
+    Ok(None)
+}
+
+/// Gives a source to each backend the engine's configuration lists, unless
+/// one of the same kind was given, returning a note for each backend that
+/// is not followed.
+fn discover(args: &mut Args, backends: Vec<DiscoveredBackend>) -> Vec<String> {
+    let mut skipped = Vec::new();
+    for DiscoveredBackend { name, backend } in backends {
+        let given = match backend {
+            #[cfg(unix)]
+            DiscoveredSource::Docker => args.docker.get_or_insert(None).is_none(),
+            #[cfg(not(unix))]
+            DiscoveredSource::Docker => {
+                skipped.push(format!("{} (Docker is only followed on Unix platforms)", name));
+                continue;
+            }
+            DiscoveredSource::Tes(url) => *args.tes.get_or_insert_with(|| url.clone()) == url,
+            DiscoveredSource::Slurm => args.slurm.get_or_insert(None).is_none(),
+            DiscoveredSource::Lsf => args.lsf.get_or_insert(None).is_none(),
+            DiscoveredSource::Unsupported(reason) => {
+                skipped.push(format!("{} ({})", name, reason));
+                continue;
+            }
+        };
+        if !given {
+            skipped.push(format!("{} (another source of its kind was given)", name));
+        }
+    }
+    skipped
+}
+
+/// Returns how many live sources were given.
+fn live_source_count(args: &Args) -> usize {
+    let given = [
+        args.engine.is_some(),
+        args.websocket.is_some(),
+        args.sse.is_some(),
+        args.tes.is_some(),
+        args.k8s.is_some(),
+        args.local.is_some(),
+        args.slurm.is_some(),
+        args.lsf.is_some(),
+        args.aws_batch.is_some(),
+        args.stdin,
+        args.file.is_some(),
+    ];
+    let count = given.into_iter().filter(|given| *given).count();
+    #[cfg(unix)]
+    let count = count + usize::from(args.docker.is_some()) + usize::from(args.listen.is_some());
+    count
+}
+
+/// Connects to every live source given, in the order they take precedence
+/// unless the configuration says otherwise, each with what `access` says it
+/// is let in with.
+fn live_sources(args: &Args, listener: Option<StreamDataSource>, access: impl Fn(&str) -> RemoteAccess) -> Vec<Box<dyn DataSource>> {
+    let mut sources: Vec<Box<dyn DataSource>> = Vec::new();
+    if let Some(url) = &args.engine {
+        sources.push(Box::new(EngineConnection::connect_with(url, access("engine"))));
+    }
+    if let Some(url) = &args.websocket {
+        sources.push(Box::new(WebSocketDataSource::connect_with(url, access("websocket"))));
+    }
+    if let Some(url) = &args.sse {
+        sources.push(Box::new(SseDataSource::connect_with(url, access("sse"))));
+    }
+    if let Some(url) = &args.tes {
+        sources.push(Box::new(TesDataSource::connect_with(url, access("tes"))));
+    }
+    #[cfg(unix)]
+    if let Some(socket) = &args.docker {
+        let socket = socket.clone().unwrap_or_else(crankshaft_tui::default_docker_socket);
+        sources.push(Box::new(crankshaft_tui::DockerDataSource::connect(&socket)));
+    }
+    if let Some(namespace) = &args.k8s {
+        sources.push(Box::new(K8sDataSource::connect(k8s_target(namespace.as_deref(), args.k8s_api.as_deref(), access("k8s")))));
+    }
+    if let Some(engine) = &args.local {
+        sources.push(Box::new(LocalDataSource::watch(engine)));
+    }
+    if let Some(user) = &args.slurm {
+        sources.push(Box::new(SlurmDataSource::connect(&user.clone().unwrap_or_else(crankshaft_tui::default_slurm_user))));
+    }
+    if let Some(user) = &args.lsf {
+        sources.push(Box::new(LsfDataSource::connect(user.as_deref())));
+    }
+    if let Some(queues) = &args.aws_batch {
+        sources.push(Box::new(AwsBatchDataSource::connect(queues)));
+    }
+    if args.stdin {
+        sources.push(Box::new(StreamDataSource::stdin()));
+    }
+    if let Some(listener) = listener {
+        sources.push(Box::new(listener));
+    }
+    if let Some(path) = &args.file {
+        sources.push(Box::new(FileDataSource::watch(path)));
+    }
+    sources
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = Args::parse();
+    let socket = args
+        .control_socket
+        .clone()
+        .unwrap_or_else(crankshaft_tui::default_socket_path);
+
+    match args.command {
+        Some(Command::Ctl { action }) => return run_ctl(&socket, action.into()),
+        Some(Command::Schema) => {
+            print!("{}", crankshaft_tui::UPDATE_SCHEMA);
+            return Ok(());
+        }
+        None => {}
+    }
+
+    // Open files before touching the terminal so errors print normally
+    let mut config = Config::load(args.config.as_deref())?;
+    if let Some(max_lines) = args.log_max_lines {
+        config.logs.max_lines = max_lines;
+    }
+    if let Some(max_bytes) = args.log_max_bytes {
+        config.logs.max_bytes = max_bytes;
+    }
+    if args.screen_reader {
+        config.accessibility.screen_reader = true;
+    }
+    if args.reduced_motion {
+        config.display.reduced_motion = true;
+    }
+    if args.check_updates {
+        config.updates.check = true;
+    }
+    if let Some(destination) = &args.tunnel {
+        config.tunnel.set_destination(destination);
+    }
+    crankshaft_tui::set_config_path(
+        args.config
+            .clone()
+            .or_else(crankshaft_tui::default_config_path)
+            .filter(|path| path.exists()),
+    );
+    if args.stdin && std::io::stdin().is_terminal() {
+        return Err("--stdin reads task updates piped in, such as `my-script | crankshaft-tui --stdin`".into());
+    }
+    let tokens = config.auth.tokens()?;
+    let tls = config.tls.load()?;
+    config.polling.intervals()?;
+    let access = |source: &str| RemoteAccess {
+        token: tokens.get(source).cloned(),
+        tls: tls.clone(),
+    };
+    let discovered = match args.discover.clone() {
+        Some(path) => {
+            let path = path.unwrap_or_else(|| PathBuf::from(ENGINE_CONFIG_FILE));
+            let backends = crankshaft_tui::discover_backends(&path)?;
+            let count = backends.len();
+            let skipped = discover(&mut args, backends);
+            Some((path, count - skipped.len(), skipped))
+        }
+        None => None,
+    };
+    // Tunnels stay open until the monitor exits, and close when dropped
+    let mut tunnels = Vec::new();
+    if config.tunnel.host.is_some() {
+        for url in [&mut args.engine, &mut args.websocket, &mut args.sse, &mut args.tes].into_iter().flatten() {
+            let (tunnel, local) = config.tunnel.open(url)?;
+            *url = local;
+            tunnels.push(tunnel);
+        }
+    }
+    let replay = args.replay.as_deref().map(Replayer::open).transpose()?;
+    let listener = listener(&args)?;
+    let recorder = args.record.as_deref().map(Recorder::create).transpose()?;
+
     // Initialize the terminal
+    crankshaft_tui::install_panic_hook();
     let mut terminal = init_terminal()?;
     
     // Create the application state
-    let mut app = App::new();
+    let mut app = if let Some(replay) = replay {
+        App::with_replay(replay)
+    } else if let Some(count) = args.simulate {
+        App::with_simulation(count)
+    } else if live_source_count(&args) > 1 {
+        App::with_merged(MergedDataSource::new(live_sources(&args, listener, access), &config.merge))
+    } else if let Some(url) = &args.engine {
+        App::with_engine(url, access("engine"))
+    } else if let Some(url) = &args.websocket {
+        App::with_websocket(url, access("websocket"))
+    } else if let Some(url) = &args.sse {
+        App::with_sse(url, access("sse"))
+    } else if let Some(url) = &args.tes {
+        App::with_tes(url, access("tes"))
+    } else if let Some(namespace) = &args.k8s {
+        App::with_k8s(k8s_target(namespace.as_deref(), args.k8s_api.as_deref(), access("k8s")))
+    } else if let Some(engine) = &args.local {
+        App::with_local(engine)
+    } else if let Some(user) = &args.slurm {
+        App::with_slurm(&user.clone().unwrap_or_else(crankshaft_tui::default_slurm_user))
+    } else if let Some(user) = &args.lsf {
+        App::with_lsf(user.as_deref())
+    } else if let Some(queues) = &args.aws_batch {
+        App::with_aws_batch(queues)
+    } else if args.stdin {
+        App::with_stdin()
+    } else if let Some(listener) = listener {
+        App::with_listener(listener)
+    } else if let Some(path) = &args.file {
+        App::with_file(path)
+    } else {
+        docker_app(&args).unwrap_or_default()
+    };
+    if let Some(recorder) = recorder {
+        app.set_recorder(recorder);
+    }
+    app.snapshot_compression = args.snapshot_compression;
+    app.dry_run = args.dry_run;
+    if let Some(tab) = args.tab {
+        app.set_tab(tab);
+    }
+    if let Some(id) = &args.select {
+        if !app.select_task(id) {
+            app.set_status(format!("Task {} not found yet; it will be selected when it appears", id));
+        }
+    }
+    #[cfg(unix)]
+    match crankshaft_tui::ControlServer::bind(&socket) {
+        Ok(server) => app.set_control_server(server),
+        Err(err) => app.set_status(format!("Control socket unavailable: {}", err)),
+    }
+    app.apply_config(&config);
+    if let Some((path, followed, skipped)) = discovered {
+        let mut status = format!("Following {} backends from {}", followed, path.display());
+        if !skipped.is_empty() {
+            status.push_str(&format!("; skipped {}", skipped.join(", ")));
+        }
+        app.set_status(status);
+    }
+    if let Some(path) = args.state_file.clone().or_else(crankshaft_tui::default_state_path) {
+        app.load_state(path);
+    }
+    app.subscribe(CrashSnapshotter::default());
     
     // Run the application with a tick rate of 250ms
-    run_app(&mut terminal, &mut app, Duration::from_millis(250))?;
+    let result = run_app(&mut terminal, &mut app, Duration::from_millis(250));
     
     // Restore the terminal
     restore_terminal(&mut terminal)?;
+    #[cfg(feature = "telemetry")]
+    app.send_usage_report();
+
+    drop(tunnels);
+
+    if let Err(err) = result {
+        match crankshaft_tui::write_bundle(&format!("fatal error: {}", err)) {
+            Ok(path) => eprintln!("a crash report was written to {}", path.display()),
+            Err(bundle_err) => eprintln!("the crash report could not be written: {}", bundle_err),
+        }
+        return Err(err.into());
+    }
     
     Ok(())
-}
\ No newline at end of file
+}
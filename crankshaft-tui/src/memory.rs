@@ -0,0 +1,119 @@
+//! Memory statistics for long-running sessions.
+//!
+//! Process memory is shown in the performance overlay and can be appended
+//! to a log file at a fixed interval, so that growth over a multi-day session
+//! can be reported with data. With the `memory-stats` feature the numbers
+//! come from the `memory-stats` crate; otherwise they are read from `/proc`
+//! on Linux and are unavailable elsewhere.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::app::App;
+use crate::bus::{StateEvent, Subscriber};
+use crate::record::unix_now;
+
+/// Default time between lines of the memory log.
+pub const DEFAULT_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Memory used by this process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessMemory {
+    /// Resident set size in bytes
+    pub resident: u64,
+    /// Virtual memory size in bytes
+    pub virtual_size: u64,
+}
+
+/// Returns the current memory use of this process, if the platform reports
+/// it.
+#[cfg(feature = "memory-stats")]
+pub fn process_memory() -> Option<ProcessMemory> {
+    memory_stats::memory_stats().map(|stats| ProcessMemory {
+        resident: stats.physical_mem as u64,
+        virtual_size: stats.virtual_mem as u64,
+    })
+}
+
+/// Returns the current memory use of this process, if the platform reports
+/// it.
+#[cfg(all(not(feature = "memory-stats"), target_os = "linux"))]
+pub fn process_memory() -> Option<ProcessMemory> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|rest| rest.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+            .map(|kib| kib * 1024)
+    };
+    Some(ProcessMemory {
+        resident: field("VmRSS:")?,
+        virtual_size: field("VmSize:")?,
+    })
+}
+
+/// Returns the current memory use of this process, if the platform reports
+/// it.
+#[cfg(all(not(feature = "memory-stats"), not(target_os = "linux")))]
+pub fn process_memory() -> Option<ProcessMemory> {
+    None
+}
+
+/// Returns the total size of the logs retained for every task, in bytes.
+pub fn retained_log_bytes(app: &App) -> usize {
+    app.tasks.values().map(|task| task.logs.bytes()).sum()
+}
+
+/// Appends memory statistics to a file at a fixed interval.
+///
+/// Each line holds space-separated `key=value` pairs, e.g.
+/// `at=1700000000 rss=52428800 virt=104857600 tasks=10000 log_bytes=0 history_tasks=20000`.
+pub struct MemoryLogger {
+    /// Destination file
+    file: File,
+    /// Time between lines
+    interval: Duration,
+    /// When the last line was written
+    last: Option<Instant>,
+}
+
+impl MemoryLogger {
+    /// Opens `path` for appending, creating it if needed.
+    pub fn open(path: &Path, interval: Duration) -> io::Result<Self> {
+        Ok(Self {
+            file: OpenOptions::new().create(true).append(true).open(path)?,
+            interval,
+            last: None,
+        })
+    }
+}
+
+impl Subscriber for MemoryLogger {
+    fn name(&self) -> &str {
+        "Memory log"
+    }
+
+    fn notify(&mut self, event: &StateEvent, app: &App) -> eyre::Result<()> {
+        if *event != StateEvent::Updated || self.last.is_some_and(|last| last.elapsed() < self.interval) {
+            return Ok(());
+        }
+        self.last = Some(Instant::now());
+
+        let memory = process_memory();
+        writeln!(
+            self.file,
+            "at={} rss={} virt={} tasks={} log_bytes={} history_tasks={}",
+            unix_now(),
+            memory.map_or_else(|| "unknown".to_string(), |memory| memory.resident.to_string()),
+            memory.map_or_else(|| "unknown".to_string(), |memory| memory.virtual_size.to_string()),
+            app.tasks.len(),
+            retained_log_bytes(app),
+            app.history.retained_tasks()
+        )?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
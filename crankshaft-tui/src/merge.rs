@@ -0,0 +1,540 @@
+//! Tasks reported by several sources at once.
+//!
+//! The same task can be seen by more than one backend: the engine knows its
+//! status and progress, while the local process table or the Docker daemon
+//! measures what it actually uses. [`MergedDataSource`] polls several
+//! sources and keeps what each of them last said about every task, so the
+//! task store receives a single task combining them.
+//!
+//! Each field is taken from one source. A finished status wins over an
+//! unfinished one, since a process table never learns that a task failed.
+//! Otherwise sources take precedence in the order they were given, or as
+//! set in the `[merge]` section of the configuration, with separate orders
+//! for particular fields: resource use prefers the sources that measure it.
+//! A value only keeps precedence while it is fresh; once its source has not
+//! reported the field for a while, the most recent value from any source
+//! wins. Only sources whose capabilities cover a field report it.
+//!
+//! Tasks that only one source reports are listed side by side, so a local
+//! Docker daemon and a remote Slurm cluster can be followed in one list.
+//! Each task is tagged with its `backend` label, which the sources that know
+//! their executor set and which otherwise names the source, and the list
+//! shows it as a column; `B` lists the tasks of one backend at a time.
+//!
+//! When sources disagree, the value each of them reported and the one used
+//! are listed under `conflicts` in the task's JSON, next to each source's
+//! own view of the task, and the crash log notes each new conflict:
+//!
+//! ```toml
+//! [merge]
+//! precedence = ["engine", "docker"]
+//! fresh_secs = 30
+//!
+//! [merge.fields]
+//! cpu_usage = ["local", "docker"]
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::actions::TaskAction;
+use crate::app::{Task, TaskStatus, TaskUpdate};
+use crate::capabilities::SourceCapabilities;
+use crate::source::{DataSource, SourceEvent};
+
+/// A part of a task that sources report separately from the rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    /// [`Task::status`]
+    Status,
+    /// [`Task::progress`]
+    Progress,
+    /// [`Task::cpu_usage`]
+    CpuUsage,
+    /// [`Task::memory_usage`]
+    MemoryUsage,
+    /// [`Task::queue`]
+    Queue,
+    /// [`Task::started_at`]
+    StartedAt,
+    /// [`Task::finished_at`]
+    FinishedAt,
+}
+
+impl Field {
+    /// Every field, in the order conflicts are listed.
+    pub const ALL: [Field; 7] = [
+        Field::Status,
+        Field::Progress,
+        Field::CpuUsage,
+        Field::MemoryUsage,
+        Field::Queue,
+        Field::StartedAt,
+        Field::FinishedAt,
+    ];
+
+    /// Returns the name of the field, as in the configuration and the
+    /// task's JSON.
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Status => "status",
+            Field::Progress => "progress",
+            Field::CpuUsage => "cpu_usage",
+            Field::MemoryUsage => "memory_usage",
+            Field::Queue => "queue",
+            Field::StartedAt => "started_at",
+            Field::FinishedAt => "finished_at",
+        }
+    }
+
+    /// Returns whether a source with `capabilities` reports the field.
+    fn reported_by(self, capabilities: SourceCapabilities) -> bool {
+        match self {
+            Field::CpuUsage => capabilities.cpu,
+            Field::MemoryUsage => capabilities.memory,
+            _ => true,
+        }
+    }
+
+    /// Returns the value of the field in `task`.
+    fn of(self, task: &Task) -> Value {
+        match self {
+            Field::Status => json!(task.status),
+            Field::Progress => json!(task.progress),
+            Field::CpuUsage => json!(task.cpu_usage),
+            Field::MemoryUsage => json!(task.memory_usage),
+            Field::Queue => json!(task.queue),
+            Field::StartedAt => json!(task.started_at),
+            Field::FinishedAt => json!(task.finished_at),
+        }
+    }
+
+    /// Sets the field of `task` to `value`, as returned by [`of`](Self::of).
+    fn set(self, task: &mut Task, value: &Value) {
+        fn decode<T: DeserializeOwned>(field: &mut T, value: &Value) {
+            if let Ok(value) = T::deserialize(value) {
+                *field = value;
+            }
+        }
+        match self {
+            Field::Status => decode(&mut task.status, value),
+            Field::Progress => decode(&mut task.progress, value),
+            Field::CpuUsage => decode(&mut task.cpu_usage, value),
+            Field::MemoryUsage => decode(&mut task.memory_usage, value),
+            Field::Queue => decode(&mut task.queue, value),
+            Field::StartedAt => decode(&mut task.started_at, value),
+            Field::FinishedAt => decode(&mut task.finished_at, value),
+        }
+    }
+}
+
+impl std::fmt::Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How tasks reported by several sources are combined.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MergeConfig {
+    /// Names of the sources in order of precedence; those left out follow
+    /// in the order they were given
+    pub precedence: Vec<String>,
+    /// Sources that take precedence for a field ahead of the general order
+    pub fields: BTreeMap<Field, Vec<String>>,
+    /// Seconds a reported value keeps precedence over more recent values
+    /// from other sources
+    pub fresh_secs: u64,
+}
+
+impl Default for MergeConfig {
+    fn default() -> Self {
+        let measured = vec!["local".to_string(), "docker".to_string()];
+        Self {
+            precedence: Vec::new(),
+            fields: BTreeMap::from([(Field::CpuUsage, measured.clone()), (Field::MemoryUsage, measured)]),
+            fresh_secs: 30,
+        }
+    }
+}
+
+/// What one source last said about a task.
+#[derive(Debug)]
+struct View {
+    /// The task as the source sees it, with its updates applied
+    task: Task,
+    /// Whether the source sent the whole task, not only updates to it
+    created: bool,
+    /// When the source last reported each field
+    reported: HashMap<Field, Instant>,
+}
+
+impl View {
+    /// Returns a view of a task the source has only sent updates for.
+    fn partial(id: &str) -> Self {
+        let task = serde_json::from_value(json!({ "id": id, "name": id, "status": TaskStatus::Pending }))
+            .expect("a task with only an ID and a status deserializes");
+        Self {
+            task,
+            created: false,
+            reported: HashMap::new(),
+        }
+    }
+
+    /// Applies `update` from a source with `capabilities`, reported at
+    /// `now`.
+    fn apply(&mut self, update: TaskUpdate, capabilities: SourceCapabilities, now: Instant) {
+        let fields: Vec<Field> = match update {
+            TaskUpdate::Created(task) => {
+                self.task = *task;
+                self.created = true;
+                Field::ALL.to_vec()
+            }
+            TaskUpdate::StatusChanged { status, .. } => {
+                let mut fields = vec![Field::Status];
+                let task = &mut self.task;
+                // The same bookkeeping as the task store, so the times of
+                // the source that saw the change are the ones kept
+                if status != TaskStatus::Pending && task.queue.take().is_some() {
+                    fields.push(Field::Queue);
+                }
+                if status != TaskStatus::Pending && task.started_at.is_none() {
+                    task.started_at = Some(crate::record::unix_now());
+                    fields.push(Field::StartedAt);
+                }
+                if matches!(status, TaskStatus::Completed | TaskStatus::Failed) && task.finished_at.is_none() {
+                    task.finished_at = Some(crate::record::unix_now());
+                    fields.push(Field::FinishedAt);
+                }
+                task.status = status;
+                fields
+            }
+            TaskUpdate::Progress { progress, .. } => {
+                self.task.progress = progress.clamp(0.0, 1.0);
+                vec![Field::Progress]
+            }
+            TaskUpdate::Metrics { cpu_usage, memory_usage, .. } => {
+                let mut fields = Vec::new();
+                if let Some(cpu_usage) = cpu_usage {
+                    self.task.cpu_usage = cpu_usage;
+                    fields.push(Field::CpuUsage);
+                }
+                if let Some(memory_usage) = memory_usage {
+                    self.task.memory_usage = memory_usage;
+                    fields.push(Field::MemoryUsage);
+                }
+                fields
+            }
+            TaskUpdate::Queue { queue, .. } => {
+                self.task.queue = queue;
+                vec![Field::Queue]
+            }
+            TaskUpdate::LogLine { .. } | TaskUpdate::Removed { .. } => Vec::new(),
+        };
+        for field in fields.into_iter().filter(|field| field.reported_by(capabilities)) {
+            self.reported.insert(field, now);
+        }
+    }
+}
+
+/// A field on which sources disagree.
+#[derive(Debug)]
+struct Conflict {
+    /// The field
+    field: Field,
+    /// Index of the source whose value was used
+    chosen: usize,
+    /// What each source reported, in precedence order
+    values: Vec<(usize, Value)>,
+}
+
+/// Several sources whose tasks are combined into one store.
+pub struct MergedDataSource {
+    /// Names of the sources, joined
+    name: String,
+    /// The sources, in the order they were given
+    sources: Vec<Box<dyn DataSource>>,
+    /// Indexes of the sources in order of precedence
+    precedence: Vec<usize>,
+    /// Indexes of the sources in order of precedence for each field
+    fields: HashMap<Field, Vec<usize>>,
+    /// How long a reported value keeps precedence
+    fresh: Duration,
+    /// What each source last said about each task, by task and source index
+    views: HashMap<String, BTreeMap<usize, View>>,
+    /// Fields of each task on which sources disagreed when last merged
+    conflicts: HashMap<String, BTreeSet<Field>>,
+    /// Events received with the last poll's updates, not yet taken
+    events: Vec<SourceEvent>,
+}
+
+impl MergedDataSource {
+    /// Combines `sources`, which take precedence in the order given unless
+    /// `config` orders them otherwise.
+    pub fn new(sources: Vec<Box<dyn DataSource>>, config: &MergeConfig) -> Self {
+        let names: Vec<&str> = sources.iter().map(|source| source.name()).collect();
+        let names = names.as_slice();
+        // The sources named in `preferred`, in that order, then the others
+        // in the order of `rest`
+        let order = move |preferred: &[String], rest: &[usize]| -> Vec<usize> {
+            let mut order: Vec<usize> = preferred
+                .iter()
+                .flat_map(|name| rest.iter().copied().filter(move |&index| names[index] == name))
+                .collect();
+            for &index in rest {
+                if !order.contains(&index) {
+                    order.push(index);
+                }
+            }
+            order
+        };
+        let precedence = order(&config.precedence, &(0..names.len()).collect::<Vec<_>>());
+        let fields = Field::ALL
+            .into_iter()
+            .map(|field| (field, order(config.fields.get(&field).map_or(&[], Vec::as_slice), &precedence)))
+            .collect();
+        Self {
+            name: names.join("+"),
+            precedence,
+            fields,
+            fresh: Duration::from_secs(config.fresh_secs),
+            sources,
+            views: HashMap::new(),
+            conflicts: HashMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns the names of the sources, in the order they were given.
+    pub fn source_names(&self) -> Vec<&str> {
+        self.sources.iter().map(|source| source.name()).collect()
+    }
+
+    /// Combines what every source said about task `id` as of `now`, or
+    /// returns `None` if no source sent the whole task.
+    fn merge(&mut self, id: &str, now: Instant) -> Option<Task> {
+        let views = self.views.get(id)?;
+        let base = self.precedence.iter().find(|index| views.get(index).is_some_and(|view| view.created))?;
+        let mut task = views[base].task.clone();
+        // Labels from every source, the ones with precedence last so they
+        // win
+        for index in self.precedence.iter().rev() {
+            if let Some(view) = views.get(index).filter(|view| view.created) {
+                task.labels.extend(view.task.labels.clone());
+            }
+        }
+        // Tasks from sources that do not name their backend are tagged
+        // with the source itself
+        task.labels.entry("backend".to_string()).or_insert_with(|| self.sources[*base].name().to_string());
+
+        let finished =
+            |value: &Value| matches!(TaskStatus::deserialize(value), Ok(TaskStatus::Completed | TaskStatus::Failed));
+        let mut conflicts = Vec::new();
+        for field in Field::ALL {
+            // What each source reported, when, in precedence order
+            let reports: Vec<(usize, Value, Instant)> = self.fields[&field]
+                .iter()
+                .filter_map(|index| {
+                    let view = views.get(index)?;
+                    let at = *view.reported.get(&field)?;
+                    Some((*index, field.of(&view.task), at))
+                })
+                .collect();
+            // Only finished statuses compete once any source reports one
+            let candidates: Vec<&(usize, Value, Instant)> =
+                if field == Field::Status && reports.iter().any(|(_, value, _)| finished(value)) {
+                    reports.iter().filter(|(_, value, _)| finished(value)).collect()
+                } else {
+                    reports.iter().collect()
+                };
+            let chosen = candidates
+                .iter()
+                .find(|(_, _, at)| now.duration_since(*at) < self.fresh)
+                .or_else(|| candidates.iter().rev().max_by_key(|(_, _, at)| *at));
+            let Some(&&(chosen, ref value, _)) = chosen else {
+                continue;
+            };
+            field.set(&mut task, value);
+            if reports.iter().any(|(_, other, _)| other != value) {
+                let values = reports.iter().map(|(index, value, _)| (*index, value.clone())).collect();
+                conflicts.push(Conflict { field, chosen, values });
+            }
+        }
+
+        let sources: serde_json::Map<String, Value> = views
+            .iter()
+            .map(|(index, view)| (self.sources[*index].name().to_string(), serde_json::to_value(&view.task).unwrap_or_default()))
+            .collect();
+        let listed: Vec<Value> = conflicts
+            .iter()
+            .map(|conflict| {
+                let values: serde_json::Map<String, Value> = conflict
+                    .values
+                    .iter()
+                    .map(|(index, value)| (self.sources[*index].name().to_string(), value.clone()))
+                    .collect();
+                json!({
+                    "field": conflict.field.name(),
+                    "chosen": self.sources[conflict.chosen].name(),
+                    "values": values,
+                })
+            })
+            .collect();
+        task.raw = Some(json!({ "sources": sources, "conflicts": listed }));
+
+        let logged = self.conflicts.entry(id.to_string()).or_default();
+        for conflict in &conflicts {
+            if logged.insert(conflict.field) {
+                let values: Vec<String> = conflict
+                    .values
+                    .iter()
+                    .map(|(index, value)| format!("{} reports {}", self.sources[*index].name(), value))
+                    .collect();
+                crate::crash::log(format!(
+                    "conflicting {} for task {}: {}; using {}",
+                    conflict.field,
+                    id,
+                    values.join(", "),
+                    self.sources[conflict.chosen].name()
+                ));
+            }
+        }
+        logged.retain(|field| conflicts.iter().any(|conflict| conflict.field == *field));
+        Some(task)
+    }
+}
+
+impl DataSource for MergedDataSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whatever any of the sources provides.
+    fn capabilities(&self) -> SourceCapabilities {
+        self.sources.iter().fold(SourceCapabilities::NONE, |merged, source| {
+            let capabilities = source.capabilities();
+            SourceCapabilities {
+                logs: merged.logs || capabilities.logs,
+                cpu: merged.cpu || capabilities.cpu,
+                memory: merged.memory || capabilities.memory,
+                control: merged.control || capabilities.control,
+                dependencies: merged.dependencies || capabilities.dependencies,
+            }
+        })
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        // Tasks to merge again, in the order they were first touched
+        let mut touched: Vec<String> = Vec::new();
+        let mut lines = Vec::new();
+        for (index, source) in self.sources.iter_mut().enumerate() {
+            let updates = source.poll();
+            let mut events = source.events();
+            if !updates.is_empty() {
+                events.insert(0, SourceEvent::Received(updates.len()));
+            }
+            self.events.extend(events.into_iter().map(|event| SourceEvent::Relayed {
+                source: source.name().to_string(),
+                event: Box::new(event),
+            }));
+            let capabilities = source.capabilities();
+            let now = Instant::now();
+            for update in updates {
+                let id = update.task_id().to_string();
+                match update {
+                    TaskUpdate::LogLine { .. } => {
+                        lines.push(update);
+                        continue;
+                    }
+                    TaskUpdate::Removed { .. } => {
+                        if let Some(views) = self.views.get_mut(&id) {
+                            views.remove(&index);
+                        }
+                    }
+                    update => self
+                        .views
+                        .entry(id.clone())
+                        .or_default()
+                        .entry(index)
+                        .or_insert_with(|| View::partial(&id))
+                        .apply(update, capabilities, now),
+                }
+                if !touched.contains(&id) {
+                    touched.push(id);
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let mut updates = Vec::new();
+        for id in touched {
+            match self.merge(&id, now) {
+                Some(task) => updates.push(TaskUpdate::Created(Box::new(task))),
+                None => {
+                    // Gone from every source that sent the whole task
+                    if self.views.get(&id).is_some_and(BTreeMap::is_empty) {
+                        self.views.remove(&id);
+                        self.conflicts.remove(&id);
+                    }
+                    updates.push(TaskUpdate::Removed { id });
+                }
+            }
+        }
+        updates.extend(lines);
+        updates
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Each of the sources, in the order they were given.
+    fn origins(&self) -> Vec<String> {
+        self.sources.iter().map(|source| source.name().to_string()).collect()
+    }
+
+    /// Live only if every source is.
+    fn is_live(&self) -> bool {
+        self.sources.iter().all(|source| source.is_live())
+    }
+
+    /// The backend each task comes from, then the columns of every source,
+    /// each shown once.
+    fn label_columns(&self) -> Vec<String> {
+        let mut columns: Vec<String> = vec!["backend".to_string()];
+        for column in self.sources.iter().flat_map(|source| source.label_columns()) {
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+        columns
+    }
+
+    /// Changes the interval of each of the sources by that name.
+    fn set_poll_interval(&mut self, source: &str, interval: Duration) -> bool {
+        let mut set = false;
+        for polled in &mut self.sources {
+            set |= polled.set_poll_interval(source, interval);
+        }
+        set
+    }
+
+    /// Hands the action to the first source, in precedence order, that
+    /// reports the task and accepts control actions.
+    fn control(&mut self, action: &TaskAction) -> eyre::Result<bool> {
+        let views = self.views.get(action.id());
+        let index = self.precedence.iter().copied().find(|index| {
+            views.is_some_and(|views| views.contains_key(index)) && self.sources[*index].capabilities().control
+        });
+        match index {
+            Some(index) => self.sources[index].control(action),
+            None => Err(eyre::eyre!("none of the sources reporting task {} accepts control actions ({})", action.id(), action)),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! The overlays drawn over the dashboard, in the order they were opened.
+//!
+//! Dialogs, menus, and panels such as the diagnostics or the About screen
+//! share one stack rather than each keeping a flag of its own. The overlay
+//! opened last is drawn on top and takes the keys first: a confirmation, the
+//! sort menu, or the glossary popover takes every key, while the panels only
+//! take Esc, which closes whichever overlay is on top before it quits
+//! anything. Opening an overlay that is already shown brings it back to the
+//! top.
+//!
+//! Everything under an overlay other than the performance HUD is dimmed, so
+//! the layer that is listening stands out; tooltips under the mouse are
+//! hidden meanwhile.
+
+/// An overlay drawn over the dashboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlay {
+    /// The performance HUD in the top-right corner
+    Performance,
+    /// Connection diagnostics
+    Diagnostics,
+    /// Actions taken in this session
+    Audit,
+    /// Version, build, and recent changes
+    About,
+    /// The selected task compared with the reference task
+    TaskDiff,
+    /// The sort menu
+    SortMenu,
+    /// The glossary popover opened with `?`
+    Glossary,
+    /// A confirmation waiting for an answer
+    Confirmation,
+}
+
+impl Overlay {
+    /// Returns `true` if the overlay takes every key while on top, rather
+    /// than only Esc.
+    pub fn is_modal(self) -> bool {
+        matches!(self, Overlay::SortMenu | Overlay::Glossary | Overlay::Confirmation)
+    }
+
+    /// Returns `true` if what is drawn under the overlay is dimmed.
+    pub fn dims(self) -> bool {
+        self != Overlay::Performance
+    }
+}
+
+/// The open overlays, bottom first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OverlayStack {
+    /// Open overlays, the one drawn on top last
+    layers: Vec<Overlay>,
+}
+
+impl OverlayStack {
+    /// Shows `overlay` on top of the others.
+    pub fn open(&mut self, overlay: Overlay) {
+        self.layers.retain(|layer| *layer != overlay);
+        self.layers.push(overlay);
+    }
+
+    /// Hides `overlay`, returning `false` if it was not shown.
+    pub fn close(&mut self, overlay: Overlay) -> bool {
+        let open = self.is_open(overlay);
+        self.layers.retain(|layer| *layer != overlay);
+        open
+    }
+
+    /// Shows `overlay` if it is hidden and hides it otherwise, returning
+    /// `true` if it is now shown.
+    pub fn toggle(&mut self, overlay: Overlay) -> bool {
+        if self.close(overlay) {
+            return false;
+        }
+        self.open(overlay);
+        true
+    }
+
+    /// Hides the overlay on top, returning it.
+    pub fn dismiss(&mut self) -> Option<Overlay> {
+        self.layers.pop()
+    }
+
+    /// Returns the overlay on top, if any is shown.
+    pub fn top(&self) -> Option<Overlay> {
+        self.layers.last().copied()
+    }
+
+    /// Returns `true` if `overlay` is shown.
+    pub fn is_open(&self, overlay: Overlay) -> bool {
+        self.layers.contains(&overlay)
+    }
+
+    /// Returns `true` if no overlay is shown.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Returns `true` if an overlay that dims the dashboard is shown.
+    pub fn dims(&self) -> bool {
+        self.layers.iter().any(|layer| layer.dims())
+    }
+
+    /// Returns the open overlays in drawing order, bottom first.
+    pub fn iter(&self) -> impl Iterator<Item = Overlay> + '_ {
+        self.layers.iter().copied()
+    }
+}
@@ -0,0 +1,139 @@
+//! Tiled log panes for watching several tasks at once.
+//!
+//! Related shards are often easiest to compare side by side. Up to
+//! [`MAX_PANES`] task logs can be opened in the Logs tab; the focused pane
+//! receives the scrolling keys, and each pane either follows its log or stays
+//! where it was scrolled to, independently of the others.
+
+/// Maximum number of panes open at once.
+pub const MAX_PANES: usize = 4;
+
+/// One task log open in the Logs tab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogPane {
+    /// The task whose log is shown
+    pub task_id: String,
+    /// Absolute index following the last line shown, or `None` while the
+    /// pane follows the end of the log
+    end: Option<usize>,
+}
+
+impl LogPane {
+    /// Creates a pane following a task's log.
+    pub fn new(task_id: impl Into<String>) -> Self {
+        Self {
+            task_id: task_id.into(),
+            end: None,
+        }
+    }
+
+    /// Returns `true` if the pane shows new lines as they arrive.
+    pub fn is_following(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// Returns the absolute index following the last line to show, given the
+    /// index following the last line of the log.
+    pub fn end(&self, next_index: usize) -> usize {
+        self.end.map_or(next_index, |end| end.min(next_index))
+    }
+
+    /// Stops following at the current end of the log, or follows it again.
+    pub fn toggle_follow(&mut self, next_index: usize) {
+        self.end = match self.end {
+            Some(_) => None,
+            None => Some(next_index),
+        };
+    }
+
+    /// Scrolls towards the start of the log, which stops following it.
+    /// `first_index` is the index of the oldest line still held.
+    pub fn scroll_up(&mut self, lines: usize, first_index: usize, next_index: usize) {
+        let end = self.end(next_index).saturating_sub(lines).max(first_index + 1);
+        self.end = Some(end.min(next_index));
+    }
+
+    /// Scrolls towards the end of the log, following it again once the end
+    /// is reached.
+    pub fn scroll_down(&mut self, lines: usize, next_index: usize) {
+        if let Some(end) = self.end {
+            let end = end + lines;
+            self.end = (end < next_index).then_some(end);
+        }
+    }
+}
+
+/// The open panes and which one has focus.
+#[derive(Debug, Clone, Default)]
+pub struct LogPanes {
+    /// Open panes, in layout order
+    panes: Vec<LogPane>,
+    /// Index of the focused pane
+    focus: usize,
+}
+
+impl LogPanes {
+    /// Opens a pane for a task and focuses it. A task that already has a pane
+    /// is focused instead.
+    ///
+    /// Returns `false` if every pane is taken.
+    pub fn open(&mut self, task_id: &str) -> bool {
+        if let Some(index) = self.panes.iter().position(|pane| pane.task_id == task_id) {
+            self.focus = index;
+            return true;
+        }
+        if self.panes.len() == MAX_PANES {
+            return false;
+        }
+        self.panes.push(LogPane::new(task_id));
+        self.focus = self.panes.len() - 1;
+        true
+    }
+
+    /// Closes the focused pane, focusing the one before it.
+    pub fn close_focused(&mut self) -> Option<LogPane> {
+        if self.panes.is_empty() {
+            return None;
+        }
+        let pane = self.panes.remove(self.focus);
+        self.focus = self.focus.saturating_sub(1);
+        Some(pane)
+    }
+
+    /// Moves the focus to the next pane, wrapping around.
+    pub fn focus_next(&mut self) {
+        if !self.panes.is_empty() {
+            self.focus = (self.focus + 1) % self.panes.len();
+        }
+    }
+
+    /// Returns the open panes in layout order.
+    pub fn panes(&self) -> &[LogPane] {
+        &self.panes
+    }
+
+    /// Returns the index of the focused pane.
+    pub fn focus(&self) -> usize {
+        self.focus
+    }
+
+    /// Returns the focused pane, if any is open.
+    pub fn focused(&self) -> Option<&LogPane> {
+        self.panes.get(self.focus)
+    }
+
+    /// Returns the focused pane for changing its follow state.
+    pub fn focused_mut(&mut self) -> Option<&mut LogPane> {
+        self.panes.get_mut(self.focus)
+    }
+
+    /// Returns the number of open panes.
+    pub fn len(&self) -> usize {
+        self.panes.len()
+    }
+
+    /// Returns `true` if no pane is open.
+    pub fn is_empty(&self) -> bool {
+        self.panes.is_empty()
+    }
+}
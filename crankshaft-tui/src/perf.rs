@@ -0,0 +1,87 @@
+//! Performance counters shown in the debug overlay.
+
+use std::time::{Duration, Instant};
+
+use crate::memory::{process_memory, ProcessMemory};
+
+/// Length of the window over which rates are averaged.
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Changes made to the task store during a single update.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Churn {
+    /// Number of existing tasks that changed
+    pub updated: usize,
+    /// Number of tasks added to the store
+    pub added: usize,
+    /// Number of tasks removed from the store
+    pub removed: usize,
+}
+
+impl std::ops::AddAssign for Churn {
+    fn add_assign(&mut self, other: Self) {
+        self.updated += other.updated;
+        self.added += other.added;
+        self.removed += other.removed;
+    }
+}
+
+/// Rolling performance statistics for the running session.
+#[derive(Debug, Clone)]
+pub struct PerfStats {
+    /// Start of the current measurement window
+    window_start: Instant,
+    /// Churn accumulated in the current window
+    window: Churn,
+    /// Task updates per second over the last complete window
+    pub updates_per_sec: f64,
+    /// Tasks added per second over the last complete window
+    pub added_per_sec: f64,
+    /// Tasks removed per second over the last complete window
+    pub removed_per_sec: f64,
+    /// Number of tasks in the store after the last update
+    pub store_size: usize,
+    /// Time taken to render the last frame
+    pub last_frame: Duration,
+    /// Memory used by the process at the end of the last complete window
+    pub memory: Option<ProcessMemory>,
+}
+
+impl Default for PerfStats {
+    fn default() -> Self {
+        Self {
+            window_start: Instant::now(),
+            window: Churn::default(),
+            updates_per_sec: 0.0,
+            added_per_sec: 0.0,
+            removed_per_sec: 0.0,
+            store_size: 0,
+            last_frame: Duration::ZERO,
+            memory: None,
+        }
+    }
+}
+
+impl PerfStats {
+    /// Records the churn from one update along with the resulting store size.
+    pub fn record(&mut self, churn: Churn, store_size: usize) {
+        self.window += churn;
+        self.store_size = store_size;
+
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= WINDOW {
+            let secs = elapsed.as_secs_f64();
+            self.updates_per_sec = self.window.updated as f64 / secs;
+            self.added_per_sec = self.window.added as f64 / secs;
+            self.removed_per_sec = self.window.removed as f64 / secs;
+            self.memory = process_memory();
+            self.window = Churn::default();
+            self.window_start = Instant::now();
+        }
+    }
+
+    /// Records how long the last frame took to render.
+    pub fn record_frame(&mut self, duration: Duration) {
+        self.last_frame = duration;
+    }
+}
@@ -0,0 +1,71 @@
+//! Completion of a workflow's phases.
+//!
+//! Backends that tag tasks with a `phase` (or `step`) label describe the
+//! workflow in terms its stakeholders know, such as alignment, variant
+//! calling, and annotation. The header then summarises progress per phase
+//! instead of per task.
+
+use std::collections::HashMap;
+
+use crate::app::{Task, TaskStatus};
+
+/// Labels naming a task's phase, in order of preference.
+pub const PHASE_LABELS: [&str; 2] = ["phase", "step"];
+
+/// Tasks of one phase and how many have finished.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Phase {
+    /// The label value shared by the phase's tasks
+    pub name: String,
+    /// Number of tasks in the phase
+    pub total: usize,
+    /// Tasks that completed
+    pub completed: usize,
+    /// Tasks that failed
+    pub failed: usize,
+}
+
+impl Phase {
+    /// Returns the share of the phase's tasks that completed.
+    pub fn completion(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.completed as f64 / self.total as f64
+        }
+    }
+}
+
+/// Returns the phase a task is labeled with, if any.
+pub fn phase_of(task: &Task) -> Option<&str> {
+    PHASE_LABELS.iter().find_map(|label| task.labels.get(*label)).map(String::as_str)
+}
+
+/// Groups labeled tasks into phases, in the order their first task appears.
+/// Returns nothing if no task carries a phase label.
+pub fn breakdown<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> Vec<Phase> {
+    let mut phases: Vec<Phase> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for task in tasks {
+        let Some(name) = phase_of(task) else {
+            continue;
+        };
+        let position = *index.entry(name).or_insert_with(|| {
+            phases.push(Phase {
+                name: name.to_string(),
+                total: 0,
+                completed: 0,
+                failed: 0,
+            });
+            phases.len() - 1
+        });
+        let phase = &mut phases[position];
+        phase.total += 1;
+        match task.status {
+            TaskStatus::Completed => phase.completed += 1,
+            TaskStatus::Failed => phase.failed += 1,
+            TaskStatus::Pending | TaskStatus::Running => {}
+        }
+    }
+    phases
+}
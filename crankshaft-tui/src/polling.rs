@@ -0,0 +1,97 @@
+//! How often polled sources fetch from their backends.
+//!
+//! Sources whose backend sends changes as they happen, such as the engine
+//! or an event stream, show them as they arrive. Those that have to ask, the
+//! Docker daemon, a TES service, the local process table, Slurm, LSF, and
+//! AWS Batch, fetch on a thread of their own at an interval suited to their
+//! backend rather than on every tick of the UI: every two seconds for
+//! Docker and local processes, every five for TES, and every ten for the
+//! schedulers, which ask not to be queried in tight loops. Each interval can
+//! be changed in the `[polling]` section of the configuration file, in
+//! seconds, by source name:
+//!
+//! ```toml
+//! [polling]
+//! slurm = 30
+//! docker = 0.5
+//! ```
+//!
+//! A new interval takes effect during the wait already under way.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use eyre::eyre;
+use serde::Deserialize;
+
+/// Names of the sources that poll their backend.
+pub const POLLED_SOURCES: &[&str] = &["docker", "tes", "local", "slurm", "lsf", "aws-batch"];
+
+/// Shortest interval accepted, so a typo cannot flood a backend.
+pub const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Longest a polling thread sleeps before looking at its interval again.
+const WAIT_SLICE: Duration = Duration::from_millis(100);
+
+/// Poll intervals of each source, by the source's name.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct PollingConfig {
+    /// Seconds between fetches by source name, such as `slurm`
+    pub intervals: BTreeMap<String, f64>,
+}
+
+impl PollingConfig {
+    /// Returns the interval set for each source, by source name.
+    pub fn intervals(&self) -> eyre::Result<BTreeMap<String, Duration>> {
+        self.intervals
+            .iter()
+            .map(|(name, secs)| {
+                if !POLLED_SOURCES.contains(&name.as_str()) {
+                    return Err(eyre!("`{}` in `[polling]` is not a source that polls; expected one of {}", name, POLLED_SOURCES.join(", ")));
+                }
+                let interval = Duration::try_from_secs_f64(*secs)
+                    .ok()
+                    .filter(|interval| *interval >= MIN_INTERVAL)
+                    .ok_or_else(|| eyre!("`{}` in `[polling]` must be at least {} seconds, not {}", name, MIN_INTERVAL.as_secs_f64(), secs))?;
+                Ok((name.clone(), interval))
+            })
+            .collect()
+    }
+}
+
+/// The interval a polling thread waits between fetches, shared with the
+/// source so it can be changed while the thread runs.
+#[derive(Debug, Clone)]
+pub struct PollInterval(Arc<AtomicU64>);
+
+impl PollInterval {
+    /// Starts with `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self(Arc::new(AtomicU64::new(interval.as_millis() as u64)))
+    }
+
+    /// Returns the current interval.
+    pub fn get(&self) -> Duration {
+        Duration::from_millis(self.0.load(Ordering::Relaxed))
+    }
+
+    /// Changes the interval.
+    pub fn set(&self, interval: Duration) {
+        self.0.store(interval.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Sleeps until the interval has passed since `started`, following any
+    /// change made meanwhile.
+    pub fn wait_from(&self, started: Instant) {
+        loop {
+            let Some(left) = self.get().checked_sub(started.elapsed()).filter(|left| !left.is_zero()) else {
+                return;
+            };
+            thread::sleep(left.min(WAIT_SLICE));
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Interpolation of task progress between backend updates.
+//!
+//! Backends may only report progress every few seconds. To keep gauges moving,
+//! the last observed rate of each running task is extrapolated until the next
+//! report arrives. The estimate is deliberately conservative: it never reaches
+//! completion and stops advancing once a report is overdue.
+//!
+//! Reports themselves can be noisy: some tasks report progress that jumps
+//! backwards or oscillates. With smoothing enabled, the store keeps an
+//! exponential moving average of the reports that only ever moves forwards.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::app::{Task, TaskStatus};
+
+/// Highest progress an interpolated value may show.
+const MAX_ESTIMATE: f64 = 0.99;
+
+/// Extrapolation stops after this many multiples of the usual update interval.
+const MAX_INTERVALS: f64 = 2.0;
+
+/// Difference below which smoothed progress snaps to the reported value.
+const SMOOTHING_EPSILON: f64 = 0.001;
+
+/// Returns the progress to store for a new report, given the smoothing
+/// factor: the share of each forward step that is applied, from just above 0
+/// (heavy smoothing) to 1 (forward steps in full, only regressions ignored).
+///
+/// Reports behind the current value leave it unchanged; a report of
+/// completion, or one close enough to the current value, is taken as is.
+pub fn smooth(current: f64, reported: f64, factor: f64) -> f64 {
+    if reported <= current {
+        return current;
+    }
+    if reported >= 1.0 || reported - current < SMOOTHING_EPSILON {
+        return reported;
+    }
+    current + factor * (reported - current)
+}
+
+/// The last reported progress of a task.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    /// The reported progress
+    progress: f64,
+    /// When it was observed
+    at: Instant,
+    /// Observed progress per second, once two reports are known
+    rate: Option<f64>,
+    /// Time between the last two reports
+    interval: Option<Duration>,
+}
+
+/// Tracks reported progress and produces interpolated values.
+#[derive(Debug, Clone)]
+pub struct ProgressInterpolator {
+    /// Whether interpolation is applied at all
+    enabled: bool,
+    /// Last report per running task
+    samples: HashMap<String, Sample>,
+}
+
+impl Default for ProgressInterpolator {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            samples: HashMap::new(),
+        }
+    }
+}
+
+impl ProgressInterpolator {
+    /// Enables or disables interpolation. When disabled, raw values are shown.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.samples.clear();
+        }
+    }
+
+    /// Returns `true` if interpolation is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Observes the current store, noting any changed progress reports.
+    pub fn observe(&mut self, tasks: &HashMap<String, Task>) {
+        if !self.enabled {
+            return;
+        }
+
+        let now = Instant::now();
+        self.samples
+            .retain(|id, _| tasks.get(id).is_some_and(|task| task.status == TaskStatus::Running));
+
+        for task in tasks.values().filter(|task| task.status == TaskStatus::Running) {
+            match self.samples.get_mut(&task.id) {
+                Some(sample) if sample.progress != task.progress => {
+                    let elapsed = now.duration_since(sample.at);
+                    if task.progress > sample.progress && !elapsed.is_zero() {
+                        sample.rate = Some((task.progress - sample.progress) / elapsed.as_secs_f64());
+                        sample.interval = Some(elapsed);
+                    } else {
+                        sample.rate = None;
+                    }
+                    sample.progress = task.progress;
+                    sample.at = now;
+                }
+                Some(_) => {}
+                None => {
+                    self.samples.insert(
+                        task.id.clone(),
+                        Sample {
+                            progress: task.progress,
+                            at: now,
+                            rate: None,
+                            interval: None,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    /// Returns the progress to display for a task.
+    pub fn progress(&self, task: &Task) -> f64 {
+        if !self.enabled || task.status != TaskStatus::Running {
+            return task.progress;
+        }
+        let Some(sample) = self.samples.get(&task.id) else {
+            return task.progress;
+        };
+        let (Some(rate), Some(interval)) = (sample.rate, sample.interval) else {
+            return task.progress;
+        };
+
+        let horizon = interval.as_secs_f64() * MAX_INTERVALS;
+        let elapsed = sample.at.elapsed().as_secs_f64().min(horizon);
+        let estimate = sample.progress + rate * elapsed;
+        estimate.min(MAX_ESTIMATE).max(task.progress)
+    }
+}
@@ -0,0 +1,165 @@
+//! The monitor's REST protocol, version 1.
+//!
+//! This protocol is the monitor's own: the mock engine serves it, but the
+//! Crankshaft engine itself exposes its monitoring service over gRPC, which
+//! the monitor does not speak. An engine serving it has two endpoints:
+//!
+//! - `GET /v1/tasks` returns a [`TaskList`] with the full state of every task
+//! - `GET /v1/events` streams [`TaskUpdate`]s as JSON lines, starting with a
+//!   `created` update for every existing task so a client that connects (or
+//!   reconnects) late still sees the whole store
+//!
+//! Updates are tagged by their `type` field, e.g.
+//! `{"type":"progress","id":"task-1","progress":0.5}`. Of a task, only `id`,
+//! `name`, and `status` are required; progress and resource use default to
+//! zero.
+//!
+//! The format of updates is versioned and described by a JSON schema,
+//! [`UPDATE_SCHEMA`], which `crankshaft-tui schema` prints. An update may
+//! say which version it follows with a `version` field, and is taken to
+//! follow version 1 without one. Fields the schema does not list are
+//! ignored, so additions that older monitors can do without do not need a
+//! new version. Each update is checked before it is applied, and those that
+//! fail are counted by [`Malformed`] kind in the diagnostics overlay rather
+//! than stopping the stream.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::{Task, TaskUpdate};
+
+/// Version of the update format this build reads and writes.
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// JSON schema of an update in [`SCHEMA_VERSION`] of the format.
+pub const UPDATE_SCHEMA: &str = include_str!("../schema/task-update.v1.json");
+
+/// Values of the `type` field, with the fields each type requires besides
+/// `type` and `id`.
+pub const UPDATE_TYPES: [(&str, &[&str]); 7] = [
+    ("created", &["name", "status"]),
+    ("status_changed", &["status"]),
+    ("progress", &["progress"]),
+    ("metrics", &[]),
+    ("log_line", &["line"]),
+    ("removed", &[]),
+    ("queue", &[]),
+];
+
+/// Path of the endpoint listing every task.
+pub const TASKS_PATH: &str = "/v1/tasks";
+
+/// Path of the endpoint streaming updates.
+pub const EVENTS_PATH: &str = "/v1/events";
+
+/// Body of the task listing endpoint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskList {
+    /// Every task known to the engine, in submission order
+    pub tasks: Vec<Task>,
+}
+
+/// Encodes an update as one line of the event stream, without the newline.
+pub fn encode_update(update: &TaskUpdate) -> serde_json::Result<String> {
+    serde_json::to_string(update)
+}
+
+/// Why an update was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Malformed {
+    /// The line is not JSON
+    NotJson,
+    /// The update follows a newer version of the format
+    UnsupportedVersion,
+    /// The `type` names no kind of update
+    UnknownType,
+    /// A required field is missing
+    MissingField,
+    /// A field has a value of the wrong type, or one that means nothing
+    InvalidValue,
+}
+
+impl Malformed {
+    /// Returns a short description, such as `missing field`.
+    pub fn describe(self) -> &'static str {
+        match self {
+            Malformed::NotJson => "not JSON",
+            Malformed::UnsupportedVersion => "unsupported version",
+            Malformed::UnknownType => "unknown type",
+            Malformed::MissingField => "missing field",
+            Malformed::InvalidValue => "invalid value",
+        }
+    }
+}
+
+impl fmt::Display for Malformed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.describe())
+    }
+}
+
+/// An update that does not follow the format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    /// What is wrong with it
+    pub kind: Malformed,
+    /// What exactly, for the crash log
+    pub message: String,
+}
+
+impl DecodeError {
+    /// Creates an error of `kind`.
+    fn new(kind: Malformed, message: impl fmt::Display) -> Self {
+        Self {
+            kind,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.message)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decodes one line of the event stream, checking it against the schema.
+pub fn decode_update(line: &str) -> Result<TaskUpdate, DecodeError> {
+    let value: serde_json::Value = serde_json::from_str(line).map_err(|err| DecodeError::new(Malformed::NotJson, err))?;
+    validate_update(&value)?;
+    TaskUpdate::deserialize(value).map_err(|err| DecodeError::new(Malformed::InvalidValue, err))
+}
+
+/// Checks that an update has a known type and version and the fields its
+/// type requires. The values of the fields are checked when the update is
+/// decoded.
+pub fn validate_update(value: &serde_json::Value) -> Result<(), DecodeError> {
+    let Some(object) = value.as_object() else {
+        return Err(DecodeError::new(Malformed::InvalidValue, "an update is a JSON object"));
+    };
+    if let Some(version) = object.get("version") {
+        match version.as_u64() {
+            Some(version) if version > SCHEMA_VERSION => {
+                let message = format!("version {} is newer than version {}, which this build reads", version, SCHEMA_VERSION);
+                return Err(DecodeError::new(Malformed::UnsupportedVersion, message));
+            }
+            Some(version) if version > 0 => {}
+            _ => return Err(DecodeError::new(Malformed::InvalidValue, format!("`version` is {}, not a positive integer", version))),
+        }
+    }
+    let kind = match object.get("type") {
+        Some(serde_json::Value::String(kind)) => kind.as_str(),
+        Some(kind) => return Err(DecodeError::new(Malformed::InvalidValue, format!("`type` is {}, not a string", kind))),
+        None => return Err(DecodeError::new(Malformed::MissingField, "`type` is required")),
+    };
+    let Some((_, required)) = UPDATE_TYPES.iter().find(|(name, _)| *name == kind) else {
+        return Err(DecodeError::new(Malformed::UnknownType, format!("`{}` is not a kind of update", kind)));
+    };
+    match std::iter::once(&"id").chain(required.iter()).find(|field| !object.contains_key(**field)) {
+        Some(field) => Err(DecodeError::new(Malformed::MissingField, format!("`{}` is required in a `{}` update", field, kind))),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,127 @@
+//! Reconnecting to remote sources that drop.
+//!
+//! A connector following a stream, such as the engine's event stream, a
+//! WebSocket feed or a Server-Sent Events stream, does not give up when the
+//! connection ends: it reports why, waits, and connects again for as long as
+//! the app runs. The wait doubles after every attempt, from half a second up
+//! to half a minute, and is jittered so that monitors that lost the same
+//! server do not all come back at the same moment. Once a connection is made
+//! it starts over from the shortest wait.
+//!
+//! Meanwhile the app keeps its tasks on screen and shows that the source is
+//! reconnecting, with the attempt and the time until the next one, in the
+//! footer and the diagnostics overlay. A server that refuses the source's
+//! credentials is not tried again, since it would refuse them again.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::thread;
+use std::time::Duration;
+
+use crate::auth::Unauthorized;
+use crate::backpressure::{Message, Outbox};
+use crate::source::SourceEvent;
+
+/// Wait before the first attempt to reconnect.
+pub const INITIAL_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest wait between attempts.
+pub const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Exponentially growing, jittered waits between attempts to reconnect.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    /// Wait before the first attempt
+    initial: Duration,
+    /// Longest wait
+    max: Duration,
+    /// Wait the next attempt is jittered around
+    delay: Duration,
+    /// Attempts made since the last connection
+    attempt: u32,
+    /// State of the xorshift generator the jitter is drawn from
+    rng: u64,
+}
+
+impl Backoff {
+    /// Creates a backoff starting at `initial` and doubling up to `max`.
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        let seed = RandomState::new().build_hasher().finish();
+        Self {
+            initial,
+            max,
+            delay: initial,
+            attempt: 0,
+            rng: seed.max(1),
+        }
+    }
+
+    /// Returns how long to wait before the next attempt, counting it.
+    ///
+    /// The wait is between half and all of the current delay, which then
+    /// doubles.
+    pub fn next_delay(&mut self) -> Duration {
+        self.attempt = self.attempt.saturating_add(1);
+        let delay = self.delay;
+        self.delay = (self.delay * 2).min(self.max);
+        delay / 2 + delay.mul_f64(self.jitter() / 2.0)
+    }
+
+    /// Returns the number of attempts made since the last connection.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Starts over from the shortest wait, once a connection is made.
+    pub fn reset(&mut self) {
+        self.delay = self.initial;
+        self.attempt = 0;
+    }
+
+    /// Returns a float in `[0, 1)`.
+    fn jitter(&mut self) -> f64 {
+        let mut x = self.rng;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng = x;
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(INITIAL_DELAY, MAX_DELAY)
+    }
+}
+
+/// Calls `follow` until the app is gone or the credentials are refused,
+/// reporting why each connection to `target` ended and waiting out the
+/// backoff before the next one.
+///
+/// `follow` connects, hands over what arrives, and returns why it stopped;
+/// it resets the backoff it is given once it has connected.
+pub(crate) fn follow_with_retries(target: &str, sender: &Outbox, mut follow: impl FnMut(&mut Backoff) -> eyre::Result<String>) {
+    let mut backoff = Backoff::default();
+    loop {
+        let reason = match follow(&mut backoff) {
+            Ok(reason) => reason,
+            Err(err) if err.downcast_ref::<Unauthorized>().is_some() => {
+                let _ = sender.send(Message::Event(SourceEvent::Failed(format!("Cannot connect to {}: {:#}", target, err))));
+                return;
+            }
+            Err(err) => format!("{:#}", err),
+        };
+        let delay = backoff.next_delay();
+        let event = SourceEvent::Reconnecting {
+            reason: format!("Disconnected from {}: {}", target, reason),
+            attempt: backoff.attempt(),
+            delay,
+        };
+        // Nobody is left to reconnect for
+        if sender.send(Message::Event(event)).is_err() {
+            return;
+        }
+        thread::sleep(delay);
+    }
+}
@@ -0,0 +1,356 @@
+//! Session recording, replay, and task-store snapshots.
+//!
+//! Recordings are JSON lines, one [`Frame`] per line; snapshots are a single
+//! JSON document. Either can be compressed: the compression is chosen from the
+//! file extension (`.gz` or `.zst`) when writing and detected the same way when
+//! reading, so callers never deal with it directly.
+//!
+//! Moments bookmarked while recording are written with the next frame and
+//! come back as [`SourceEvent::Bookmark`] when playback reaches them, so they
+//! show in the Events tab of the replay at the point they were made.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::{App, Task, TaskUpdate};
+use crate::bus::{StateEvent, Subscriber};
+use crate::capabilities::SourceCapabilities;
+use crate::source::{DataSource, SourceEvent};
+
+/// Default interval between recorded frames.
+pub const DEFAULT_RECORD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Compression applied to recording and snapshot files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    /// Plain, uncompressed text
+    #[default]
+    None,
+    /// gzip (`.gz`)
+    Gzip,
+    /// Zstandard (`.zst`)
+    Zstd,
+}
+
+impl Compression {
+    /// Detects the compression from a file's extension.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") | Some("zstd") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+
+    /// Returns the file extension suffix for this compression, including the
+    /// leading dot, or an empty string for no compression.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Compression::None => "",
+            Compression::Gzip => ".gz",
+            Compression::Zstd => ".zst",
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Compression::None),
+            "gzip" | "gz" => Ok(Compression::Gzip),
+            "zstd" | "zst" => Ok(Compression::Zstd),
+            other => Err(format!("unknown compression `{}` (expected none, gzip, or zstd)", other)),
+        }
+    }
+}
+
+/// Creates a file for writing, compressing according to its extension.
+pub fn create(path: &Path) -> io::Result<Box<dyn Write + Send>> {
+    let file = BufWriter::new(File::create(path)?);
+    Ok(match Compression::from_path(path) {
+        Compression::None => Box::new(file),
+        Compression::Gzip => Box::new(flate2::write::GzEncoder::new(file, flate2::Compression::default())),
+        Compression::Zstd => Box::new(zstd::Encoder::new(file, 0)?.auto_finish()),
+    })
+}
+
+/// Opens a file for reading, decompressing according to its extension.
+pub fn open(path: &Path) -> io::Result<Box<dyn BufRead + Send>> {
+    let file = File::open(path)?;
+    Ok(match Compression::from_path(path) {
+        Compression::None => Box::new(BufReader::new(file)),
+        Compression::Gzip => Box::new(BufReader::new(flate2::read::GzDecoder::new(file))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::Decoder::new(file)?)),
+    })
+}
+
+/// The state of every task at one point of a recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Frame {
+    /// Milliseconds since the recording started
+    pub elapsed_ms: u64,
+    /// Every task in the store, in display order
+    pub tasks: Vec<Task>,
+    /// Notes of the moments bookmarked since the frame before
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bookmarks: Vec<String>,
+}
+
+/// A point-in-time dump of the task store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Seconds since the Unix epoch when the snapshot was taken
+    pub taken_at: u64,
+    /// Every task in the store, in display order
+    pub tasks: Vec<Task>,
+}
+
+/// Writes a snapshot of the given tasks to `path`.
+pub fn write_snapshot<'a>(path: &Path, tasks: impl IntoIterator<Item = &'a Task>) -> io::Result<()> {
+    let snapshot = Snapshot {
+        taken_at: unix_now(),
+        tasks: tasks.into_iter().cloned().collect(),
+    };
+    let mut writer = create(path)?;
+    serde_json::to_writer(&mut writer, &snapshot)?;
+    writer.flush()
+}
+
+/// Reads a snapshot previously written with [`write_snapshot`].
+pub fn read_snapshot(path: &Path) -> io::Result<Snapshot> {
+    Ok(serde_json::from_reader(open(path)?)?)
+}
+
+/// Appends frames of the task store to a recording file.
+pub struct Recorder {
+    /// Destination of the recording
+    writer: Box<dyn Write + Send>,
+    /// When the recording started
+    started: Instant,
+    /// Minimum time between frames
+    interval: Duration,
+    /// When the last frame was written
+    last_frame: Option<Instant>,
+    /// Notes of the moments bookmarked since the last frame
+    bookmarks: Vec<String>,
+}
+
+impl Recorder {
+    /// Starts a recording at `path`.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            writer: create(path)?,
+            started: Instant::now(),
+            interval: DEFAULT_RECORD_INTERVAL,
+            last_frame: None,
+            bookmarks: Vec::new(),
+        })
+    }
+
+    /// Sets the minimum time between recorded frames.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Adds a bookmark with `note` to the next frame, which is written
+    /// without waiting for the recording interval.
+    pub fn bookmark(&mut self, note: impl Into<String>) {
+        self.bookmarks.push(note.into());
+    }
+
+    /// Writes a frame if the recording interval has elapsed or a moment
+    /// was bookmarked.
+    pub fn record<'a>(&mut self, tasks: impl IntoIterator<Item = &'a Task>) -> io::Result<()> {
+        if self.bookmarks.is_empty() && self.last_frame.is_some_and(|last| last.elapsed() < self.interval) {
+            return Ok(());
+        }
+        self.last_frame = Some(Instant::now());
+
+        let frame = Frame {
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            tasks: tasks.into_iter().cloned().collect(),
+            bookmarks: std::mem::take(&mut self.bookmarks),
+        };
+        serde_json::to_writer(&mut self.writer, &frame)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        // Frames still buffered are written out when the session ends
+        let _ = self.writer.flush();
+    }
+}
+
+impl Subscriber for Recorder {
+    fn name(&self) -> &str {
+        "Recording"
+    }
+
+    fn notify(&mut self, event: &StateEvent, app: &App) -> eyre::Result<()> {
+        match event {
+            StateEvent::Bookmarked { note } => self.bookmark(note.clone()),
+            StateEvent::Updated => self.record(app.task_ids.iter().filter_map(|id| app.tasks.get(id)))?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Plays back a recording in real time.
+pub struct Replayer {
+    /// Remaining lines of the recording
+    lines: io::Lines<Box<dyn BufRead + Send>>,
+    /// When playback started
+    started: Instant,
+    /// The next frame, read ahead of its due time
+    pending: Option<Frame>,
+    /// Whether the end of the recording was reached
+    finished: bool,
+    /// IDs of the tasks in the last frame played
+    shown: HashSet<String>,
+    /// Whether playback ended, at the end of the recording or on an error
+    stopped: bool,
+    /// Events not yet taken
+    events: Vec<SourceEvent>,
+}
+
+impl Replayer {
+    /// Opens a recording for playback.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            lines: open(path)?.lines(),
+            started: Instant::now(),
+            pending: None,
+            finished: false,
+            shown: HashSet::new(),
+            stopped: false,
+            events: Vec::new(),
+        })
+    }
+
+    /// Returns `true` once every frame has been played.
+    pub fn is_finished(&self) -> bool {
+        self.finished && self.pending.is_none()
+    }
+
+    /// Returns the most recent frame that is due, skipping any frames that
+    /// were superseded since the last call. The bookmarks of every frame
+    /// passed, skipped or not, are queued as events.
+    pub fn advance(&mut self) -> io::Result<Option<Frame>> {
+        let now = self.started.elapsed().as_millis() as u64;
+        let mut due = None;
+
+        loop {
+            if self.pending.is_none() {
+                self.pending = self.read_frame()?;
+            }
+            match self.pending.take() {
+                Some(mut frame) if frame.elapsed_ms <= now => {
+                    self.events.extend(frame.bookmarks.drain(..).map(SourceEvent::Bookmark));
+                    due = Some(frame);
+                }
+                Some(frame) => {
+                    self.pending = Some(frame);
+                    break;
+                }
+                None => break,
+            }
+        }
+
+        Ok(due)
+    }
+
+    /// Returns the updates turning the last frame played into `frame`.
+    fn frame_updates(&mut self, frame: Frame) -> Vec<TaskUpdate> {
+        let mut stale = std::mem::take(&mut self.shown);
+        let mut updates = Vec::with_capacity(frame.tasks.len());
+        for task in frame.tasks {
+            stale.remove(&task.id);
+            self.shown.insert(task.id.clone());
+            updates.push(TaskUpdate::Created(Box::new(task)));
+        }
+        updates.extend(stale.into_iter().map(|id| TaskUpdate::Removed { id }));
+        updates
+    }
+
+    /// Reads the next frame from the recording, if any.
+    fn read_frame(&mut self) -> io::Result<Option<Frame>> {
+        if self.finished {
+            return Ok(None);
+        }
+        for line in self.lines.by_ref() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Ok(Some(serde_json::from_str(&line)?));
+        }
+        self.finished = true;
+        Ok(None)
+    }
+}
+
+impl DataSource for Replayer {
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    /// What was recorded: everything but logs, which recordings leave out,
+    /// and control actions, since the recorded tasks are long gone.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            logs: false,
+            control: false,
+            ..SourceCapabilities::ALL
+        }
+    }
+
+    /// The latest due frame, as the changes from the one before.
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        if self.stopped {
+            return Vec::new();
+        }
+        match self.advance() {
+            Ok(Some(frame)) => self.frame_updates(frame),
+            Ok(None) => {
+                if self.is_finished() {
+                    self.stopped = true;
+                    self.events.push(SourceEvent::Message("Replay finished".to_string()));
+                }
+                Vec::new()
+            }
+            Err(err) => {
+                self.stopped = true;
+                self.events.push(SourceEvent::Failed(format!("Replay stopped: {}", err)));
+                Vec::new()
+            }
+        }
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+}
+
+/// Returns the number of seconds since the Unix epoch.
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
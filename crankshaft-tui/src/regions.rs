@@ -0,0 +1,149 @@
+//! Redrawing only the parts of the dashboard that changed.
+//!
+//! The frame is drawn by one renderer per region: the tab bar, the phase
+//! bar, the workflow drawer, each log pane, and so on. Regions whose content
+//! only changes with what they show, rather than with the passing of time,
+//! are drawn through [`Regions::draw`] with a stamp of everything they are
+//! drawn from. When a region's stamp and area are those of the last frame,
+//! the cells it drew then are copied instead of rendering it again, so while
+//! logs stream in only the panes receiving lines are rendered.
+//!
+//! The frame's layout and the phase breakdown are kept the same way, the
+//! layout until the terminal is resized or a row is shown or hidden, and the
+//! breakdown until a task changes.
+
+use std::cell::{Cell as Counter, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use ratatui::buffer::Cell;
+use ratatui::layout::Rect;
+use ratatui::Frame;
+
+use crate::phases::Phase;
+
+/// A region of the dashboard drawn by its own renderer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Slot {
+    /// The tab bar and connection states
+    Tabs,
+    /// The phase bar
+    Phases,
+    /// The workflow drawer
+    Workflow,
+    /// A log pane, by its position in the Logs tab
+    LogPane(usize),
+}
+
+/// The cells a region drew, and what they were drawn from.
+#[derive(Debug, Clone)]
+struct Region {
+    /// Area the cells cover
+    area: Rect,
+    /// Stamp of what they were drawn from
+    stamp: u64,
+    /// The cells, row by row
+    cells: Vec<Cell>,
+}
+
+/// Regions, layout, and phase breakdown kept from the last frame.
+#[derive(Debug, Default)]
+pub struct Regions {
+    /// Cells of each region drawn so far
+    regions: RefCell<HashMap<Slot, Region>>,
+    /// The frame's rows and the key they were laid out for
+    layout: RefCell<Option<(u64, Rc<[Rect]>)>>,
+    /// The phase breakdown and the store version it was worked out for
+    phases: RefCell<Option<(u64, Rc<[Phase]>)>>,
+    /// Regions rendered during the last frame
+    drawn: Counter<usize>,
+    /// Regions copied during the last frame
+    reused: Counter<usize>,
+}
+
+impl Regions {
+    /// Draws `slot` over `area` with `render`, unless it was last drawn
+    /// there from the same `stamp`, in which case its cells are copied.
+    pub fn draw(&self, f: &mut Frame, slot: Slot, area: Rect, stamp: u64, render: impl FnOnce(&mut Frame)) {
+        let area = area.intersection(f.size());
+        if let Some(region) = self.regions.borrow().get(&slot).filter(|region| region.area == area && region.stamp == stamp) {
+            let buffer = f.buffer_mut();
+            let positions = (area.top()..area.bottom()).flat_map(|y| (area.left()..area.right()).map(move |x| (x, y)));
+            for ((x, y), cell) in positions.zip(&region.cells) {
+                *buffer.get_mut(x, y) = cell.clone();
+            }
+            self.reused.set(self.reused.get() + 1);
+            return;
+        }
+
+        render(f);
+        let buffer = f.buffer_mut();
+        let cells = (area.top()..area.bottom())
+            .flat_map(|y| (area.left()..area.right()).map(move |x| (x, y)))
+            .map(|(x, y)| buffer.get(x, y).clone())
+            .collect();
+        self.regions.borrow_mut().insert(slot, Region { area, stamp, cells });
+        self.drawn.set(self.drawn.get() + 1);
+    }
+
+    /// Returns the rows laid out by `layout`, reusing those of the last
+    /// frame if `key` is unchanged.
+    pub fn layout(&self, key: u64, layout: impl FnOnce() -> Rc<[Rect]>) -> Rc<[Rect]> {
+        let mut cached = self.layout.borrow_mut();
+        match cached.as_ref() {
+            Some((cached_key, rows)) if *cached_key == key => rows.clone(),
+            _ => {
+                let rows = layout();
+                *cached = Some((key, rows.clone()));
+                rows
+            }
+        }
+    }
+
+    /// Returns the phase breakdown worked out by `breakdown`, reusing that
+    /// of the last frame if the tasks are at the same `version`.
+    pub fn phases(&self, version: u64, breakdown: impl FnOnce() -> Vec<Phase>) -> Rc<[Phase]> {
+        let mut cached = self.phases.borrow_mut();
+        match cached.as_ref() {
+            Some((cached_version, phases)) if *cached_version == version => phases.clone(),
+            _ => {
+                let phases: Rc<[Phase]> = breakdown().into();
+                *cached = Some((version, phases.clone()));
+                phases
+            }
+        }
+    }
+
+    /// Starts counting the regions of a new frame.
+    pub fn start_frame(&self) {
+        self.drawn.set(0);
+        self.reused.set(0);
+    }
+
+    /// Returns how many regions the last frame rendered and copied.
+    pub fn counts(&self) -> (usize, usize) {
+        (self.drawn.get(), self.reused.get())
+    }
+
+    /// Forgets everything kept, so the next frame draws every region, such
+    /// as after the theme or number format changed.
+    pub fn invalidate(&self) {
+        self.regions.borrow_mut().clear();
+        self.layout.borrow_mut().take();
+        self.phases.borrow_mut().take();
+    }
+}
+
+/// Returns a stamp of what `hash` feeds it.
+pub fn stamp(hash: impl FnOnce(&mut DefaultHasher)) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns the stamp of `value`.
+pub fn stamp_of(value: impl Hash) -> u64 {
+    stamp(|hasher| value.hash(hasher))
+}
@@ -0,0 +1,131 @@
+//! Pruning of finished tasks from long sessions.
+//!
+//! A monitor left running for days accumulates every task it ever saw.
+//! With a retention age configured, completed and failed tasks that
+//! finished longer ago than that leave the store, checked once a minute.
+//! What the statistics tab derives from them is kept: they still count
+//! towards the totals per status, the failure rate and the error budget,
+//! and the durations of completed ones towards the percentiles of their
+//! step. Pinned tasks and the selected one are never pruned.
+//!
+//! ```toml
+//! [retention]
+//! max_age_hours = 24
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::app::{Task, TaskStatus};
+use crate::timeline::step_name;
+
+/// Time between checks for tasks to prune.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long finished tasks are kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionConfig {
+    /// Hours after finishing that a task is pruned; kept for the whole
+    /// session without one
+    pub max_age_hours: Option<u64>,
+}
+
+/// What pruned tasks leave behind.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pruned {
+    /// Completed tasks pruned
+    pub completed: usize,
+    /// Failed tasks pruned
+    pub failed: usize,
+    /// Durations of the completed tasks pruned, in seconds, by step
+    pub durations: BTreeMap<String, Vec<u64>>,
+}
+
+impl Pruned {
+    /// Returns the number of tasks pruned.
+    pub fn total(&self) -> usize {
+        self.completed + self.failed
+    }
+}
+
+/// Decides which finished tasks to prune, and keeps what they leave behind.
+#[derive(Debug, Default)]
+pub struct Retention {
+    /// How long finished tasks are kept
+    config: RetentionConfig,
+    /// When tasks were last checked
+    last: Option<Instant>,
+    /// Status each pruned task had, by ID
+    statuses: HashMap<String, TaskStatus>,
+    /// The aggregates of pruned tasks
+    pruned: Pruned,
+}
+
+impl Retention {
+    /// Replaces the retention age, checking again on the next tick.
+    pub fn set_config(&mut self, config: RetentionConfig) {
+        self.config = config;
+        self.last = None;
+    }
+
+    /// Returns what pruned tasks left behind.
+    pub fn pruned(&self) -> &Pruned {
+        &self.pruned
+    }
+
+    /// Returns the IDs of the tasks due to be pruned at `now`, in seconds
+    /// since the Unix epoch, if the check interval has elapsed.
+    pub fn expired<'a>(&mut self, tasks: impl IntoIterator<Item = &'a Task>, now: u64) -> Vec<String> {
+        let Some(hours) = self.config.max_age_hours else {
+            return Vec::new();
+        };
+        if self.last.is_some_and(|last| last.elapsed() < CHECK_INTERVAL) {
+            return Vec::new();
+        }
+        self.last = Some(Instant::now());
+        let max_age = hours.saturating_mul(3600);
+        tasks
+            .into_iter()
+            .filter(|task| matches!(task.status, TaskStatus::Completed | TaskStatus::Failed))
+            .filter(|task| task.finished_at.is_some_and(|finished| now.saturating_sub(finished) >= max_age))
+            .map(|task| task.id.clone())
+            .collect()
+    }
+
+    /// Adds a task about to leave the store to the aggregates.
+    pub fn record(&mut self, task: &Task) {
+        match task.status {
+            TaskStatus::Completed => {
+                self.pruned.completed += 1;
+                if let (Some(started), Some(finished)) = (task.started_at, task.finished_at) {
+                    let step = step_name(&task.name).to_string();
+                    self.pruned.durations.entry(step).or_default().push(finished.saturating_sub(started));
+                }
+            }
+            TaskStatus::Failed => self.pruned.failed += 1,
+            TaskStatus::Pending | TaskStatus::Running => return,
+        }
+        self.statuses.insert(task.id.clone(), task.status);
+    }
+
+    /// Returns whether the backend re-sending `task` should be ignored: it
+    /// was pruned and it is still finished. A pruned task that runs again
+    /// is no longer counted as pruned.
+    pub fn is_pruned(&mut self, task: &Task) -> bool {
+        let Some(&status) = self.statuses.get(&task.id) else {
+            return false;
+        };
+        if matches!(task.status, TaskStatus::Completed | TaskStatus::Failed) {
+            return true;
+        }
+        self.statuses.remove(&task.id);
+        match status {
+            TaskStatus::Completed => self.pruned.completed -= 1,
+            _ => self.pruned.failed -= 1,
+        }
+        false
+    }
+}
@@ -0,0 +1,442 @@
+//! Synthetic task generation for load testing the TUI.
+//!
+//! The simulator keeps a population of roughly `count` tasks alive and
+//! mutates a slice of them on every poll, so the task store and renderer can
+//! be exercised at realistic scale without a real cluster.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use crate::app::{MemoryUsage, QueuePosition, Task, TaskStatus, TaskUpdate};
+use crate::capabilities::SourceCapabilities;
+use crate::capacity::Capacity;
+use crate::logs::{LogBuffer, LogChunk, LogProvider, LogRange};
+use crate::record::unix_now;
+use crate::source::DataSource;
+
+/// Step names used to build realistic looking task names.
+const STEP_NAMES: &[&str] = &[
+    "align_reads",
+    "sort_bam",
+    "mark_duplicates",
+    "call_variants",
+    "joint_genotype",
+    "annotate",
+    "qc_report",
+    "merge_vcfs",
+];
+
+/// Fraction of the population touched on every tick.
+const CHURN_RATIO: f64 = 0.05;
+
+/// Probability that a running task fails instead of completing.
+const FAILURE_RATE: f64 = 0.05;
+
+/// Longest a task of the initial population may already have been running,
+/// in seconds.
+const MAX_INITIAL_AGE: u64 = 4 * 3600;
+
+/// Samples that simulated tasks are labeled with.
+const SAMPLES: &[&str] = &["NA12878", "NA12891", "NA12892", "HG002", "HG003", "HG004"];
+
+/// Backends that simulated tasks are labeled with.
+const BACKENDS: &[&str] = &["slurm", "lsf", "aws-batch"];
+
+/// Deepest simulated scheduler queue position.
+const QUEUE_DEPTH: usize = 500;
+
+/// Seconds a simulated scheduler expects each queued job ahead to take.
+const QUEUE_WAIT_PER_JOB: u64 = 10;
+
+/// CPU requests assigned to simulated tasks.
+const CPU_REQUESTS: &[f64] = &[1.0, 2.0, 4.0, 8.0, 16.0];
+
+/// Memory limits assigned to simulated tasks.
+const MEMORY_LIMITS: &[u64] = &[1 << 30, 2 << 30, 4 << 30, 8 << 30, 16 << 30];
+
+/// A small xorshift generator so simulations are reproducible for a seed.
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    /// Starts from `seed`, which must not be zero for xorshift.
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    /// Returns the next number in the sequence.
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// Returns a float in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns an index in `[0, len)`.
+    fn index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
+
+/// Generates and churns synthetic tasks.
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    /// The population size the simulator tries to maintain
+    count: usize,
+    /// The sequence number used for the next generated task
+    next_id: usize,
+    /// Random source
+    rng: Rng,
+    /// The tasks as last reported
+    tasks: HashMap<String, Task>,
+    /// IDs of the tasks as last reported, in submission order
+    task_ids: Vec<String>,
+}
+
+impl Simulator {
+    /// Creates a simulator that maintains roughly `count` tasks.
+    pub fn new(count: usize) -> Self {
+        Self::with_seed(count, 0x5eed_cafe)
+    }
+
+    /// Creates a simulator with an explicit seed.
+    pub fn with_seed(count: usize, seed: u64) -> Self {
+        Self {
+            count,
+            next_id: 1,
+            rng: Rng::new(seed),
+            tasks: HashMap::new(),
+            task_ids: Vec::new(),
+        }
+    }
+
+    /// Returns the population size the simulator maintains.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns a site large enough to run most of the population at once,
+    /// with nodes that fit the largest simulated request.
+    pub fn capacity(&self) -> Capacity {
+        let largest_memory = MEMORY_LIMITS.iter().max().copied().unwrap_or_default();
+        Capacity {
+            cpus: Some(self.count as f64 * 4.0),
+            memory: Some(self.count as u64 * (2 << 30)),
+            node_cpus: CPU_REQUESTS.iter().copied().reduce(f64::max),
+            node_memory: Some(largest_memory),
+        }
+    }
+
+    /// Advances the simulation by one step, returning the changes to apply.
+    fn tick(&mut self, tasks: &HashMap<String, Task>, task_ids: &[String]) -> Vec<TaskUpdate> {
+        let mut updates = Vec::new();
+        if task_ids.is_empty() {
+            while updates.len() < self.count {
+                let status = self.initial_status();
+                updates.push(TaskUpdate::Created(Box::new(self.spawn(status))));
+            }
+            return updates;
+        }
+
+        let touches = ((task_ids.len() as f64 * CHURN_RATIO) as usize).max(1);
+        let mut finished = 0;
+
+        for _ in 0..touches {
+            let id = &task_ids[self.rng.index(task_ids.len())];
+            let Some(task) = tasks.get(id) else {
+                continue;
+            };
+
+            match task.status {
+                TaskStatus::Pending => {
+                    if self.rng.next_f64() < 0.3 {
+                        updates.push(TaskUpdate::StatusChanged { id: id.clone(), status: TaskStatus::Running });
+                    } else if let Some(queue) = task.queue.filter(|queue| queue.position > 1) {
+                        updates.push(TaskUpdate::Queue {
+                            id: id.clone(),
+                            queue: Some(QueuePosition {
+                                position: queue.position - 1,
+                                ..queue
+                            }),
+                        });
+                    }
+                }
+                TaskStatus::Running => {
+                    let progress = (task.progress + self.rng.next_f64() * 0.1).min(1.0);
+                    let mut memory_usage = task.memory_usage;
+                    if let Some(limit) = memory_usage.limit {
+                        let ratio = jitter(&mut self.rng, memory_usage.ratio(), 0.05);
+                        memory_usage.used = (ratio * limit as f64) as u64;
+                    }
+                    updates.push(TaskUpdate::Progress { id: id.clone(), progress });
+                    updates.push(TaskUpdate::Metrics {
+                        id: id.clone(),
+                        cpu_usage: Some(jitter(&mut self.rng, task.cpu_usage, 0.2)),
+                        memory_usage: Some(memory_usage),
+                    });
+
+                    let outcome = if progress >= 1.0 {
+                        Some(TaskStatus::Completed)
+                    } else if self.rng.next_f64() < FAILURE_RATE * 0.1 {
+                        Some(TaskStatus::Failed)
+                    } else {
+                        None
+                    };
+                    if let Some(status) = outcome {
+                        updates.push(TaskUpdate::StatusChanged { id: id.clone(), status });
+                        updates.push(TaskUpdate::Metrics {
+                            id: id.clone(),
+                            cpu_usage: Some(0.0),
+                            memory_usage: None,
+                        });
+                    }
+                }
+                TaskStatus::Completed | TaskStatus::Failed => {
+                    finished += 1;
+                }
+            }
+        }
+
+        // Retire a batch of finished tasks and submit replacements so the
+        // population (and the add/remove churn) stays roughly constant.
+        let retired: Vec<&String> = task_ids
+            .iter()
+            .filter(|id| {
+                matches!(
+                    tasks.get(*id).map(|t| t.status),
+                    Some(TaskStatus::Completed | TaskStatus::Failed)
+                )
+            })
+            .take(finished)
+            .collect();
+        let remaining = tasks.len() - retired.len();
+        updates.extend(retired.into_iter().map(|id| TaskUpdate::Removed { id: id.clone() }));
+
+        for _ in remaining..self.count {
+            updates.push(TaskUpdate::Created(Box::new(self.spawn(TaskStatus::Pending))));
+        }
+
+        updates
+    }
+
+    /// Keeps the simulator's copy of the tasks in step with what it
+    /// reported.
+    fn mirror(&mut self, update: &TaskUpdate) {
+        if let TaskUpdate::Created(task) = update {
+            if self.tasks.insert(task.id.clone(), task.as_ref().clone()).is_none() {
+                self.task_ids.push(task.id.clone());
+            }
+            return;
+        }
+        if let TaskUpdate::Removed { id } = update {
+            self.tasks.remove(id);
+            self.task_ids.retain(|existing| existing != id);
+            return;
+        }
+        let Some(task) = self.tasks.get_mut(update.task_id()) else {
+            return;
+        };
+        match update {
+            TaskUpdate::StatusChanged { status, .. } => {
+                if *status != TaskStatus::Pending {
+                    task.queue = None;
+                }
+                task.status = *status;
+            }
+            TaskUpdate::Progress { progress, .. } => task.progress = *progress,
+            TaskUpdate::Metrics { cpu_usage, memory_usage, .. } => {
+                if let Some(cpu_usage) = cpu_usage {
+                    task.cpu_usage = *cpu_usage;
+                }
+                if let Some(memory_usage) = memory_usage {
+                    task.memory_usage = *memory_usage;
+                }
+            }
+            TaskUpdate::Queue { queue, .. } => task.queue = *queue,
+            _ => {}
+        }
+    }
+
+    /// Picks a status for a member of the initial population.
+    fn initial_status(&mut self) -> TaskStatus {
+        match self.rng.index(10) {
+            0..=3 => TaskStatus::Pending,
+            4..=6 => TaskStatus::Running,
+            7 | 8 => TaskStatus::Completed,
+            _ => TaskStatus::Failed,
+        }
+    }
+
+    /// Creates a new task in the given state.
+    fn spawn(&mut self, status: TaskStatus) -> Task {
+        let seq = self.next_id;
+        self.next_id += 1;
+
+        let step = STEP_NAMES[self.rng.index(STEP_NAMES.len())];
+        let progress = match status {
+            TaskStatus::Pending => 0.0,
+            TaskStatus::Completed => 1.0,
+            TaskStatus::Running | TaskStatus::Failed => self.rng.next_f64(),
+        };
+        let cpu_usage = match status {
+            TaskStatus::Running => self.rng.next_f64(),
+            _ => 0.0,
+        };
+        let limit = MEMORY_LIMITS[self.rng.index(MEMORY_LIMITS.len())];
+        // Each task depends on one of the few tasks submitted just before it
+        let dependencies = match seq {
+            1 => Vec::new(),
+            _ => vec![format!("sim-{:06}", seq.saturating_sub(1 + self.rng.index(4)).max(1))],
+        };
+        let now = unix_now();
+        let started_at = match status {
+            TaskStatus::Pending => None,
+            _ => Some(now - self.rng.index(MAX_INITIAL_AGE as usize) as u64),
+        };
+        let finished_at = match status {
+            TaskStatus::Completed | TaskStatus::Failed => started_at.map(|started| (started + 60).min(now)),
+            _ => None,
+        };
+
+        let queue = (status == TaskStatus::Pending).then(|| {
+            let position = 1 + self.rng.index(QUEUE_DEPTH) as u64;
+            QueuePosition {
+                position,
+                estimated_start: Some(now + position * QUEUE_WAIT_PER_JOB),
+            }
+        });
+
+        Task {
+            id: format!("sim-{:06}", seq),
+            name: format!("{} (shard {})", step, seq % 512),
+            status,
+            progress,
+            cpu_usage,
+            cpus: Some(CPU_REQUESTS[self.rng.index(CPU_REQUESTS.len())]),
+            memory_usage: MemoryUsage {
+                used: (self.rng.next_f64() * 0.8 * limit as f64) as u64,
+                requested: Some(limit / 2),
+                limit: Some(limit),
+            },
+            started_at,
+            finished_at,
+            dependencies,
+            queue,
+            attempt: None,
+            labels: BTreeMap::from([
+                ("phase".to_string(), step.to_string()),
+                ("sample".to_string(), SAMPLES[seq % SAMPLES.len()].to_string()),
+                ("backend".to_string(), BACKENDS[self.rng.index(BACKENDS.len())].to_string()),
+            ]),
+            command: Vec::new(),
+            env: BTreeMap::new(),
+            executors: Vec::new(),
+            container: None,
+            pods: Vec::new(),
+            logs: LogBuffer::default(),
+            raw: None,
+            reported_progress: None,
+            last_updated: None,
+        }
+    }
+}
+
+impl DataSource for Simulator {
+    fn name(&self) -> &str {
+        "simulation"
+    }
+
+    /// Everything but control actions, which have nothing to act on.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            control: false,
+            ..SourceCapabilities::ALL
+        }
+    }
+
+    /// The whole population on the first poll, then a step of churn.
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        let tasks = std::mem::take(&mut self.tasks);
+        let task_ids = std::mem::take(&mut self.task_ids);
+        let updates = self.tick(&tasks, &task_ids);
+        self.tasks = tasks;
+        self.task_ids = task_ids;
+        for update in &updates {
+            self.mirror(update);
+        }
+        updates
+    }
+}
+
+/// Interval at which synthetic logs grow by one line.
+const LOG_LINE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Produces deterministic log output for any task ID on demand.
+///
+/// Each task's log is a pure function of its ID and the time since the
+/// provider was created, so nothing is stored per task.
+#[derive(Debug, Clone)]
+pub struct SyntheticLogProvider {
+    /// When the provider was created
+    started: Instant,
+}
+
+impl Default for SyntheticLogProvider {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+        }
+    }
+}
+
+impl SyntheticLogProvider {
+    /// Returns the number of lines currently in the task's log.
+    fn len(&self, task_id: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        task_id.hash(&mut hasher);
+        let seed = hasher.finish() as usize;
+
+        let base = seed % 2_000;
+        let cap = base + 1_000 + seed % 20_000;
+        let grown = (self.started.elapsed().as_millis() / LOG_LINE_INTERVAL.as_millis()) as usize;
+        (base + grown).min(cap)
+    }
+
+    /// Returns the line at the given index.
+    fn line(task_id: &str, index: usize) -> String {
+        let step = STEP_NAMES[index % STEP_NAMES.len()];
+        match index % 17 {
+            0 => format!("[{}] WARN {}: retrying slow read (attempt {})", task_id, step, index % 3 + 1),
+            _ => format!("[{}] INFO {}: processed record batch {}", task_id, step, index),
+        }
+    }
+}
+
+impl LogProvider for SyntheticLogProvider {
+    fn fetch(&mut self, task_id: &str, range: LogRange) -> eyre::Result<LogChunk> {
+        let len = self.len(task_id);
+        let start = match range {
+            LogRange::Tail(n) => len.saturating_sub(n),
+            LogRange::From(index) => index.min(len),
+        };
+
+        Ok(LogChunk {
+            start,
+            lines: (start..len).map(|i| Self::line(task_id, i)).collect(),
+        })
+    }
+}
+
+/// Moves `value` by up to `amount` in either direction, clamped to `[0, 1]`.
+fn jitter(rng: &mut Rng, value: f64, amount: f64) -> f64 {
+    (value + (rng.next_f64() * 2.0 - 1.0) * amount).clamp(0.0, 1.0)
+}
@@ -0,0 +1,84 @@
+//! Error budget of a run.
+//!
+//! Large workflows tolerate some failed tasks, retried or skipped later.
+//! Given the acceptable failure rate, the budget is the number of failures a
+//! run of this size may have; the Statistics tab shows how much of it the
+//! failures so far have consumed.
+//!
+//! ```toml
+//! [slo]
+//! max_failure_rate = 0.02
+//! ```
+
+use serde::{Deserialize, Deserializer};
+
+use crate::app::StatusCounts;
+
+/// Share of the budget consumed at which the gauge turns yellow.
+pub const WARNING_AT: f64 = 0.5;
+
+/// Share of the budget consumed at which the gauge turns red.
+pub const CRITICAL_AT: f64 = 0.8;
+
+/// The acceptable failure rate of a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Slo {
+    /// Fraction of the run's tasks allowed to fail, from 0 to 1; no budget
+    /// is tracked if unset
+    #[serde(deserialize_with = "failure_rate")]
+    pub max_failure_rate: Option<f64>,
+}
+
+impl Slo {
+    /// Returns the budget of a run with the given task counts, if a failure
+    /// rate is set.
+    pub fn budget(&self, counts: &StatusCounts) -> Option<ErrorBudget> {
+        let rate = self.max_failure_rate?;
+        Some(ErrorBudget {
+            allowed: rate * counts.total() as f64,
+            failed: counts.failed,
+        })
+    }
+}
+
+/// Failures allowed in a run and failures so far.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ErrorBudget {
+    /// Failures the run may have, not necessarily whole
+    pub allowed: f64,
+    /// Tasks that failed
+    pub failed: usize,
+}
+
+impl ErrorBudget {
+    /// Returns the share of the budget consumed, above 1 once overspent.
+    pub fn consumed(&self) -> f64 {
+        if self.allowed > 0.0 {
+            self.failed as f64 / self.allowed
+        } else if self.failed > 0 {
+            f64::INFINITY
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the share of the budget left, zero once overspent.
+    pub fn remaining(&self) -> f64 {
+        (1.0 - self.consumed()).max(0.0)
+    }
+
+    /// Returns `true` once failures exceed the budget.
+    pub fn is_exhausted(&self) -> bool {
+        self.failed as f64 > self.allowed
+    }
+}
+
+/// Accepts failure rates from 0 to 1.
+fn failure_rate<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    let rate = f64::deserialize(deserializer)?;
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(serde::de::Error::custom(format!("max_failure_rate must be between 0 and 1, got {}", rate)));
+    }
+    Ok(Some(rate))
+}
@@ -0,0 +1,396 @@
+//! Jobs in a Slurm cluster.
+//!
+//! `squeue` is run on a background thread every ten seconds for the jobs of
+//! one user, and each job is one task named after the job. Jobs that leave
+//! the queue are looked up with `sacct` for how they ended; without job
+//! accounting they leave the list instead, since whether they succeeded is
+//! not known. Both commands must be on `PATH`, so the monitor runs on a node
+//! of the cluster, usually a login node.
+//!
+//! The task list shows each job's partition and nodes as columns, and the
+//! duration column shows the elapsed time Slurm reports. Pending jobs carry
+//! the reason they are waiting as their `reason` label.
+//!
+//! Job states map onto task statuses as follows:
+//!
+//! - `PENDING`, `CONFIGURING`, `REQUEUED`, and the held states are pending
+//! - `RUNNING`, `COMPLETING`, `SUSPENDED`, and the other states of a job
+//!   that has started are running
+//! - `COMPLETED` is completed
+//! - `FAILED`, `CANCELLED`, `TIMEOUT`, `OUT_OF_MEMORY`, `NODE_FAIL`,
+//!   `PREEMPTED`, and the other final states are failed
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::app::{MemoryUsage, Task, TaskStatus, TaskUpdate};
+use crate::capabilities::SourceCapabilities;
+use crate::logs::LogBuffer;
+use crate::polling::PollInterval;
+use crate::record::unix_now;
+use crate::source::{DataSource, SourceEvent};
+
+/// How often the queue is listed unless configured otherwise; Slurm asks
+/// users not to run `squeue` in tight loops.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Fields asked of `squeue`, separated by `|`. The job name comes last as
+/// the only field that may itself contain a `|`.
+const SQUEUE_FORMAT: &str = "%i|%P|%T|%N|%M|%C|%m|%r|%j";
+
+/// Fields asked of `sacct` for jobs that left the queue.
+const SACCT_FORMAT: &str = "JobID,State,ExitCode";
+
+/// Polls after which a job that left the queue and is still unknown to
+/// `sacct` is given up on.
+const ACCOUNTING_ATTEMPTS: u32 = 6;
+
+/// Labels shown as columns of the task list.
+const LABEL_COLUMNS: [&str; 2] = ["partition", "node"];
+
+/// What the polling thread hands over.
+#[derive(Debug)]
+enum Message {
+    /// A change to the task store
+    Update(TaskUpdate),
+    /// Something that happened to the connection
+    Event(SourceEvent),
+}
+
+/// The jobs of one Slurm user, polled on a background thread.
+pub struct SlurmDataSource {
+    /// User whose jobs are listed
+    user: String,
+    /// Receives what the polling thread found
+    receiver: Receiver<Message>,
+    /// Events received with the last poll's updates, not yet taken
+    events: Vec<SourceEvent>,
+    /// Time between fetches, shared with the polling thread
+    interval: PollInterval,
+}
+
+impl SlurmDataSource {
+    /// Starts polling the jobs of `user`.
+    pub fn connect(user: &str) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let owner = user.to_string();
+        let interval = PollInterval::new(POLL_INTERVAL);
+        let shared = interval.clone();
+        thread::spawn(move || follow(&owner, &shared, &sender));
+        Self {
+            user: user.to_string(),
+            receiver,
+            events: Vec::new(),
+            interval,
+        }
+    }
+
+    /// Returns the user whose jobs are listed.
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+}
+
+impl DataSource for SlurmDataSource {
+    fn name(&self) -> &str {
+        "slurm"
+    }
+
+    /// Statuses only: `squeue` reports what jobs asked for, not what they
+    /// use, and their output goes to files on the cluster.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::NONE
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        let mut updates = Vec::new();
+        for message in self.receiver.try_iter() {
+            match message {
+                Message::Update(update) => updates.push(update),
+                Message::Event(event) => self.events.push(event),
+            }
+        }
+        updates
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn interval(&self) -> Option<&PollInterval> {
+        Some(&self.interval)
+    }
+
+    fn label_columns(&self) -> Vec<String> {
+        LABEL_COLUMNS.map(str::to_string).to_vec()
+    }
+}
+
+/// Returns the user running the monitor, whose jobs are listed by default.
+pub fn default_user() -> String {
+    std::env::var("USER").or_else(|_| std::env::var("LOGNAME")).unwrap_or_default()
+}
+
+/// Returns the status a job state stands for. States are matched on their
+/// first word, as `sacct` reports cancelled jobs as `CANCELLED by <uid>`.
+pub fn status(state: &str) -> TaskStatus {
+    match state.split_whitespace().next().unwrap_or_default().trim_end_matches('+') {
+        "PENDING" | "CONFIGURING" | "REQUEUED" | "REQUEUE_HOLD" | "REQUEUE_FED" | "RESV_DEL_HOLD" => TaskStatus::Pending,
+        "COMPLETED" => TaskStatus::Completed,
+        "RUNNING" | "COMPLETING" | "SUSPENDED" | "STAGE_OUT" | "SIGNALING" | "RESIZING" | "STOPPED" => TaskStatus::Running,
+        _ => TaskStatus::Failed,
+    }
+}
+
+/// Converts a line of `squeue` output in the format the source asks for,
+/// where `now` is when `squeue` ran. Returns `None` for lines with too few
+/// fields.
+pub fn to_task(line: &str, now: u64) -> Option<Task> {
+    let fields: Vec<&str> = line.trim_end().splitn(9, '|').collect();
+    let [id, partition, state, nodes, elapsed, cpus, memory, reason, name] = fields[..] else {
+        return None;
+    };
+    let status = status(state);
+    let mut labels = BTreeMap::from([("backend".to_string(), "slurm".to_string())]);
+    labels.insert("partition".to_string(), partition.to_string());
+    if !nodes.is_empty() {
+        labels.insert("node".to_string(), nodes.to_string());
+    }
+    if status == TaskStatus::Pending && !matches!(reason, "" | "None") {
+        labels.insert("reason".to_string(), reason.to_string());
+    }
+    let elapsed = parse_elapsed(elapsed);
+    // The elapsed time is left out, as it would make every listing a change
+    let raw = serde_json::json!({
+        "job_id": id,
+        "name": name,
+        "partition": partition,
+        "state": state,
+        "nodes": nodes,
+        "cpus": cpus,
+        "memory": memory,
+        "reason": reason,
+    });
+
+    Some(Task {
+        id: id.to_string(),
+        name: if name.is_empty() { id.to_string() } else { name.to_string() },
+        status,
+        progress: 0.0,
+        cpu_usage: 0.0,
+        cpus: cpus.parse().ok(),
+        memory_usage: MemoryUsage {
+            used: 0,
+            requested: parse_memory(memory),
+            limit: None,
+        },
+        // Slurm counts elapsed time from the start, less any suspension
+        started_at: (status != TaskStatus::Pending).then(|| elapsed.map(|elapsed| now.saturating_sub(elapsed))).flatten(),
+        finished_at: None,
+        dependencies: Vec::new(),
+        queue: None,
+        attempt: None,
+        labels,
+        command: Vec::new(),
+        env: BTreeMap::new(),
+        executors: Vec::new(),
+        container: None,
+        pods: Vec::new(),
+        logs: LogBuffer::default(),
+        raw: Some(raw),
+        reported_progress: None,
+        last_updated: None,
+    })
+}
+
+/// Parses an elapsed time as Slurm prints it, `[days-][hours:]minutes:seconds`,
+/// into seconds.
+fn parse_elapsed(text: &str) -> Option<u64> {
+    let (days, clock) = match text.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, text),
+    };
+    let parts: Vec<u64> = clock.split(':').map(str::parse).collect::<Result<_, _>>().ok()?;
+    let (hours, minutes, seconds) = match parts[..] {
+        [hours, minutes, seconds] => (hours, minutes, seconds),
+        [minutes, seconds] => (0, minutes, seconds),
+        _ => return None,
+    };
+    Some(((days * 24 + hours) * 60 + minutes) * 60 + seconds)
+}
+
+/// Parses requested memory as `squeue` prints it, such as `4G` or `500M`,
+/// into bytes. Slurm's units are powers of 1024, and a bare number is in
+/// megabytes.
+fn parse_memory(text: &str) -> Option<u64> {
+    // Older releases mark memory per node or per CPU with `n` or `c`
+    let text = text.trim_end_matches(['n', 'c']);
+    let (number, shift) = match text.char_indices().last()? {
+        (at, 'K') => (&text[..at], 10),
+        (at, 'M') => (&text[..at], 20),
+        (at, 'G') => (&text[..at], 30),
+        (at, 'T') => (&text[..at], 40),
+        _ => (text, 20),
+    };
+    let bytes = number.parse::<f64>().ok()? * (1u64 << shift) as f64;
+    (bytes > 0.0).then_some(bytes as u64)
+}
+
+/// A job that left the queue, waiting to be found in the accounting.
+struct Leaving {
+    /// The task it was when last listed
+    task: Task,
+    /// Polls it has been looked up in
+    attempts: u32,
+}
+
+/// Polls `squeue` until the app is gone, reporting failed polls and
+/// carrying on after them.
+fn follow(user: &str, interval: &PollInterval, sender: &Sender<Message>) {
+    // The task last handed over for each queued job, to send only changes
+    let mut reported: HashMap<String, Task> = HashMap::new();
+    let mut leaving: HashMap<String, Leaving> = HashMap::new();
+    let mut reachable = false;
+    let mut accounting = true;
+    loop {
+        let started = Instant::now();
+        let mut messages = Vec::new();
+        match squeue(user) {
+            Ok(output) => {
+                messages.push(Message::Event(SourceEvent::Connected { latency: started.elapsed() }));
+                if !reachable {
+                    let message = format!("Listing the Slurm jobs of {}", user);
+                    messages.push(Message::Event(SourceEvent::Message(message)));
+                    reachable = true;
+                }
+                let now = unix_now();
+                let mut stale: HashSet<String> = reported.keys().cloned().collect();
+                for line in output.lines().filter(|line| !line.trim().is_empty()) {
+                    let Some(mut task) = to_task(line, now) else {
+                        messages.push(Message::Event(SourceEvent::Dropped(format!("unexpected squeue output: {}", line))));
+                        continue;
+                    };
+                    stale.remove(&task.id);
+                    leaving.remove(&task.id);
+                    let previous = reported.get(&task.id);
+                    // The start is worked out from the elapsed time, which
+                    // is read a second or so apart from the clock
+                    if let (Some(previous), Some(started_at)) = (previous.and_then(|task| task.started_at), task.started_at) {
+                        if previous.abs_diff(started_at) <= 2 {
+                            task.started_at = Some(previous);
+                        }
+                    }
+                    if previous.is_none_or(|previous| serde_json::to_value(previous).ok() != serde_json::to_value(&task).ok()) {
+                        reported.insert(task.id.clone(), task.clone());
+                        messages.push(Message::Update(TaskUpdate::Created(Box::new(task))));
+                    }
+                }
+                for id in stale {
+                    if let Some(task) = reported.remove(&id) {
+                        leaving.insert(id, Leaving { task, attempts: 0 });
+                    }
+                }
+                if !leaving.is_empty() {
+                    finish(&mut leaving, &mut accounting, &mut messages);
+                }
+            }
+            Err(err) => {
+                reachable = false;
+                let reason = format!("Cannot list Slurm jobs: {:#}", err);
+                messages.push(Message::Event(SourceEvent::Failed(reason)));
+            }
+        }
+        for message in messages {
+            if sender.send(message).is_err() {
+                return;
+            }
+        }
+        interval.wait_from(started);
+    }
+}
+
+/// Looks up the jobs that left the queue in the accounting, handing over
+/// those found in their final state and removing those that cannot be.
+fn finish(leaving: &mut HashMap<String, Leaving>, accounting: &mut bool, messages: &mut Vec<Message>) {
+    let ids: Vec<&str> = leaving.keys().map(String::as_str).collect();
+    let ended = match sacct(&ids) {
+        Ok(ended) => ended,
+        Err(err) => {
+            if *accounting {
+                let message = format!("Finished jobs leave the list, as sacct failed: {:#}", err);
+                messages.push(Message::Event(SourceEvent::Message(message)));
+                *accounting = false;
+            }
+            HashMap::new()
+        }
+    };
+    let now = unix_now();
+    let mut done = Vec::new();
+    for (id, job) in leaving.iter_mut() {
+        job.attempts += 1;
+        match ended.get(id) {
+            Some((status, exit_code)) => {
+                let mut task = job.task.clone();
+                task.status = *status;
+                task.progress = if *status == TaskStatus::Completed { 1.0 } else { 0.0 };
+                task.finished_at = Some(now);
+                task.labels.remove("reason");
+                task.labels.insert("exit_code".to_string(), exit_code.clone());
+                messages.push(Message::Update(TaskUpdate::Created(Box::new(task))));
+                done.push(id.clone());
+            }
+            None if !*accounting || job.attempts >= ACCOUNTING_ATTEMPTS => {
+                messages.push(Message::Update(TaskUpdate::Removed { id: id.clone() }));
+                done.push(id.clone());
+            }
+            None => {}
+        }
+    }
+    for id in done {
+        leaving.remove(&id);
+    }
+}
+
+/// Lists the queued jobs of `user`.
+fn squeue(user: &str) -> eyre::Result<String> {
+    let mut command = Command::new("squeue");
+    command.args(["--noheader", "--format", SQUEUE_FORMAT]);
+    match user {
+        "" => command.arg("--me"),
+        user => command.arg(format!("--user={}", user)),
+    };
+    run(command)
+}
+
+/// Looks up how jobs ended, returning the status and exit code of those
+/// that did. Steps of the jobs are left out.
+fn sacct(ids: &[&str]) -> eyre::Result<HashMap<String, (TaskStatus, String)>> {
+    let mut command = Command::new("sacct");
+    command.args(["--noheader", "--parsable2", "--allocations", "--format", SACCT_FORMAT]);
+    command.arg(format!("--jobs={}", ids.join(",")));
+    let output = run(command)?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('|');
+            let (id, state, exit_code) = (fields.next()?, fields.next()?, fields.next()?);
+            let status = status(state);
+            matches!(status, TaskStatus::Completed | TaskStatus::Failed).then(|| (id.to_string(), (status, exit_code.to_string())))
+        })
+        .collect())
+}
+
+/// Runs a Slurm command and returns its standard output, failing with its
+/// standard error if it does not succeed.
+fn run(mut command: Command) -> eyre::Result<String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let output = command.output().map_err(|err| eyre::eyre!("cannot run {}: {}", program, err))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        eyre::bail!("{} failed ({}): {}", program, output.status, stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,222 @@
+//! Ordering of the task list.
+//!
+//! A sort order is a list of keys applied in turn, e.g. status first and
+//! then duration descending. Ties left after every key are broken by task ID,
+//! so the list never reshuffles between frames. In the config file each key
+//! is written by name, prefixed with `-` to sort descending:
+//!
+//! ```toml
+//! [sort]
+//! keys = ["status", "-duration"]
+//! ```
+//!
+//! Top mode ranks the list by a live resource instead, like `top`: the
+//! busiest tasks first, re-ranked as their usage changes.
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::app::Task;
+
+/// Number of keys that can be chosen from the sort menu.
+pub const MENU_LEVELS: usize = 2;
+
+/// A task attribute the list can be sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Task ID
+    Id,
+    /// Task name
+    Name,
+    /// Task status
+    Status,
+    /// Fraction complete
+    Progress,
+    /// Time since the task started running
+    Duration,
+    /// CPU usage
+    Cpu,
+    /// Memory usage relative to the task's limit
+    Memory,
+}
+
+impl SortKey {
+    /// Every key, in menu order.
+    pub const ALL: [SortKey; 7] = [
+        SortKey::Id,
+        SortKey::Name,
+        SortKey::Status,
+        SortKey::Progress,
+        SortKey::Duration,
+        SortKey::Cpu,
+        SortKey::Memory,
+    ];
+
+    /// Returns the name of the key as used in the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            SortKey::Id => "id",
+            SortKey::Name => "name",
+            SortKey::Status => "status",
+            SortKey::Progress => "progress",
+            SortKey::Duration => "duration",
+            SortKey::Cpu => "cpu",
+            SortKey::Memory => "memory",
+        }
+    }
+
+    /// Compares two tasks by this key alone, in ascending order.
+    fn compare(self, a: &Task, b: &Task, now: u64) -> Ordering {
+        match self {
+            SortKey::Id => a.id.cmp(&b.id),
+            SortKey::Name => a.name.cmp(&b.name),
+            SortKey::Status => a.status.cmp(&b.status),
+            SortKey::Progress => a.progress.total_cmp(&b.progress),
+            SortKey::Duration => a.duration(now).cmp(&b.duration(now)),
+            SortKey::Cpu => a.cpu_usage.total_cmp(&b.cpu_usage),
+            SortKey::Memory => a.memory_ratio().total_cmp(&b.memory_ratio()),
+        }
+    }
+}
+
+/// One level of a sort order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct SortField {
+    /// The attribute compared
+    pub key: SortKey,
+    /// Whether larger values come first
+    pub descending: bool,
+}
+
+impl FromStr for SortField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (descending, name) = match s.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, s),
+        };
+        let key = SortKey::ALL
+            .into_iter()
+            .find(|key| key.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown sort key `{}`", name))?;
+        Ok(Self { key, descending })
+    }
+}
+
+impl TryFrom<String> for SortField {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl std::fmt::Display for SortField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.key.name(), if self.descending { "↓" } else { "↑" })
+    }
+}
+
+/// A compound sort order; empty keeps tasks in arrival order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SortOrder {
+    /// Keys applied in turn, most significant first
+    pub keys: Vec<SortField>,
+}
+
+impl SortOrder {
+    /// Returns `true` if no keys are set.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Compares two tasks, breaking ties by ID.
+    pub fn compare(&self, a: &Task, b: &Task, now: u64) -> Ordering {
+        self.keys
+            .iter()
+            .map(|field| {
+                let ordering = field.key.compare(a, b, now);
+                if field.descending { ordering.reverse() } else { ordering }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.id.cmp(&b.id))
+    }
+
+    /// Sets the key at `level` (0 for primary), moving the keys from that
+    /// level on down one. Choosing the key already at that level flips its
+    /// direction instead; choosing a key used at another level moves it,
+    /// keeping its direction.
+    pub fn set_level(&mut self, level: usize, key: SortKey) {
+        if let Some(field) = self.keys.get_mut(level).filter(|field| field.key == key) {
+            field.descending = !field.descending;
+            return;
+        }
+        let field = match self.keys.iter().position(|field| field.key == key) {
+            Some(index) => self.keys.remove(index),
+            None => SortField { key, descending: false },
+        };
+        self.keys.insert(level.min(self.keys.len()), field);
+    }
+
+    /// Removes every key.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+}
+
+/// The live resource top mode ranks tasks by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopMetric {
+    /// CPU usage
+    Cpu,
+    /// Memory in use, in bytes
+    Memory,
+}
+
+impl TopMetric {
+    /// Returns the mode after `current` when cycling: CPU, memory, then off.
+    pub fn cycle(current: Option<Self>) -> Option<Self> {
+        match current {
+            None => Some(TopMetric::Cpu),
+            Some(TopMetric::Cpu) => Some(TopMetric::Memory),
+            Some(TopMetric::Memory) => None,
+        }
+    }
+
+    /// Returns the name of the metric.
+    pub fn name(self) -> &'static str {
+        match self {
+            TopMetric::Cpu => "cpu",
+            TopMetric::Memory => "memory",
+        }
+    }
+
+    /// Compares two tasks, the larger value first, breaking ties by ID.
+    pub fn compare(self, a: &Task, b: &Task) -> Ordering {
+        let ordering = match self {
+            TopMetric::Cpu => b.cpu_usage.total_cmp(&a.cpu_usage),
+            TopMetric::Memory => b.memory_usage.used.cmp(&a.memory_usage.used),
+        };
+        ordering.then_with(|| a.id.cmp(&b.id))
+    }
+}
+
+impl std::fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.keys.is_empty() {
+            return write!(f, "arrival");
+        }
+        for (i, field) in self.keys.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", field)?;
+        }
+        Ok(())
+    }
+}
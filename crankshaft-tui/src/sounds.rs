@@ -0,0 +1,172 @@
+//! Terminal bells rung when alerts are raised.
+//!
+//! Each alert severity maps to a bell pattern, or to silence, which is the
+//! default for both:
+//!
+//! ```toml
+//! [sounds]
+//! warning = "single"
+//! critical = "triple"
+//! ```
+//!
+//! The bells of a pattern ring one per tick, so the terminal does not merge
+//! them into one, and alerts raised together ring the pattern of the most
+//! severe. The section is read again whenever the configuration file
+//! changes, so sounds can be tuned without restarting the monitor, and `M`
+//! mutes every alert until it is pressed again.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Deserialize;
+
+use crate::alerts::Severity;
+use crate::config::Config;
+
+/// Time between checks of the configuration file for changes.
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What an alert sounds like.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bell {
+    /// No bell
+    #[default]
+    Silent,
+    /// One bell
+    Single,
+    /// Two bells in a row
+    Double,
+    /// Three bells in a row
+    Triple,
+}
+
+impl Bell {
+    /// Returns the number of bells in the pattern.
+    pub fn count(self) -> u32 {
+        match self {
+            Bell::Silent => 0,
+            Bell::Single => 1,
+            Bell::Double => 2,
+            Bell::Triple => 3,
+        }
+    }
+}
+
+/// The bell pattern of each alert severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SoundConfig {
+    /// Rung for warnings
+    pub warning: Bell,
+    /// Rung for critical alerts
+    pub critical: Bell,
+}
+
+impl SoundConfig {
+    /// Returns the bell pattern of `severity`.
+    pub fn bell(&self, severity: Severity) -> Bell {
+        match severity {
+            Severity::Warning => self.warning,
+            Severity::Critical => self.critical,
+        }
+    }
+}
+
+/// The bells waiting to ring, and the configuration file they follow.
+#[derive(Debug, Default)]
+pub struct Sounds {
+    /// Bell pattern of each severity
+    config: SoundConfig,
+    /// Whether every alert is silenced
+    muted: bool,
+    /// Bells left to ring, one per tick
+    pending: u32,
+    /// Configuration file read again when it changes
+    path: Option<PathBuf>,
+    /// Modification time of the file when last read
+    modified: Option<SystemTime>,
+    /// When the file was last checked
+    last_check: Option<Instant>,
+}
+
+impl Sounds {
+    /// Replaces the bell patterns.
+    pub fn set_config(&mut self, config: SoundConfig) {
+        self.config = config;
+    }
+
+    /// Returns the bell patterns in use.
+    pub fn config(&self) -> SoundConfig {
+        self.config
+    }
+
+    /// Follows the configuration file at `path`, already read as it is now,
+    /// or stops following one with `None`.
+    pub fn watch(&mut self, path: Option<PathBuf>) {
+        self.modified = path.as_deref().and_then(modified);
+        self.path = path;
+        self.last_check = None;
+    }
+
+    /// Reads the sounds from the configuration file again if the check
+    /// interval has elapsed and the file changed since it was last read.
+    /// Returns `Some` when the bell patterns changed or the file could not
+    /// be read, and `None` otherwise.
+    pub fn reload(&mut self) -> Option<eyre::Result<()>> {
+        let path = self.path.as_deref()?;
+        if self.last_check.is_some_and(|last| last.elapsed() < CHECK_INTERVAL) {
+            return None;
+        }
+        self.last_check = Some(Instant::now());
+        let modified = modified(path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        let config = match Config::load(Some(path)) {
+            Ok(config) => config.sounds,
+            Err(err) => return Some(Err(err)),
+        };
+        if config == self.config {
+            return None;
+        }
+        self.config = config;
+        Some(Ok(()))
+    }
+
+    /// Flips muting, dropping any bells still waiting, and returns whether
+    /// alerts are now muted.
+    pub fn toggle_mute(&mut self) -> bool {
+        self.muted = !self.muted;
+        self.pending = 0;
+        self.muted
+    }
+
+    /// Returns whether every alert is silenced.
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Queues the bell pattern of an alert of `severity`, unless muted. A
+    /// pattern still ringing is only replaced by a longer one.
+    pub fn ring(&mut self, severity: Severity) {
+        if !self.muted {
+            self.pending = self.pending.max(self.config.bell(severity).count());
+        }
+    }
+
+    /// Takes the next bell to ring, returning whether there was one.
+    pub fn take_bell(&mut self) -> bool {
+        if self.pending == 0 {
+            return false;
+        }
+        self.pending -= 1;
+        true
+    }
+}
+
+/// Returns the modification time of the file at `path`, if it can be read.
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
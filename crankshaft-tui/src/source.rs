@@ -0,0 +1,156 @@
+//! Where tasks come from.
+//!
+//! The app never reaches a backend itself: it polls a [`DataSource`] once
+//! per tick and applies the [`TaskUpdate`]s it returns. The built-in sources
+//! are an engine serving the REST protocol, a WebSocket feed, a Server-Sent
+//! Events stream, a TES server, the Docker daemon, a Kubernetes namespace,
+//! the processes of a local engine, a Slurm or LSF cluster, AWS Batch job
+//! queues, standard input, a named pipe or socket, a state file the engine
+//! writes, a recorded session, the load simulator, and the demo data;
+//! embedding the monitor
+//! against another scheduler means implementing the trait and handing the
+//! source to [`App::with_source`]. Several sources can be followed at once
+//! through a [`MergedDataSource`], which combines the tasks they share.
+//!
+//! Each source also reports which features it can back through
+//! [`SourceCapabilities`], so a partial backend leaves out what it cannot
+//! provide instead of showing empty panes and keys that do nothing.
+//!
+//! [`App::with_source`]: crate::App::with_source
+//! [`MergedDataSource`]: crate::MergedDataSource
+
+use std::time::Duration;
+
+use crate::actions::TaskAction;
+use crate::app::TaskUpdate;
+use crate::capabilities::SourceCapabilities;
+use crate::polling::PollInterval;
+use crate::protocol::Malformed;
+
+/// Something that happened to a source itself rather than to its tasks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceEvent {
+    /// The backend was reached after `latency`
+    Connected {
+        /// Round-trip time of the request that reached it
+        latency: Duration,
+    },
+    /// A message for the footer, such as the end of a recording
+    Message(String),
+    /// A moment was bookmarked, with its note, such as one met again while
+    /// replaying a recording
+    Bookmark(String),
+    /// An update that could not be decoded was skipped
+    Dropped(String),
+    /// An update that does not follow the engine's update format was
+    /// skipped, and why
+    Malformed {
+        /// What is wrong with it
+        kind: Malformed,
+        /// The error and the update, for the crash log
+        detail: String,
+    },
+    /// The app fell behind, and this many progress and resource updates
+    /// were replaced by later ones instead of being applied
+    Coalesced(u64),
+    /// Reaching the backend failed, and why; updates stop unless the
+    /// source recovers by itself
+    Failed(String),
+    /// This many updates were received; only relayed for sources combined
+    /// into another, since the app counts what it polls itself
+    Received(usize),
+    /// What happened to one of the sources combined into this one
+    Relayed {
+        /// Name of the source it happened to
+        source: String,
+        /// What happened
+        event: Box<SourceEvent>,
+    },
+    /// The connection dropped, and the source connects again after `delay`
+    Reconnecting {
+        /// Why the connection ended
+        reason: String,
+        /// Attempts since the source was last connected, this one included
+        attempt: u32,
+        /// Wait before the attempt is made
+        delay: Duration,
+    },
+}
+
+/// A source of task updates.
+pub trait DataSource {
+    /// Returns a short name for the source, shown in diagnostics.
+    fn name(&self) -> &str;
+
+    /// Returns what the source is able to report and do.
+    fn capabilities(&self) -> SourceCapabilities;
+
+    /// Returns the changes since the last call.
+    ///
+    /// Called on every tick, so it must not block: sources that wait on a
+    /// network or a process do so on a thread of their own and hand over
+    /// what arrived here.
+    fn poll(&mut self) -> Vec<TaskUpdate>;
+
+    /// Takes what happened to the source since the last call. Called right
+    /// after [`poll`](Self::poll); sources without a connection to report
+    /// on keep the default, which has nothing to say.
+    fn events(&mut self) -> Vec<SourceEvent> {
+        Vec::new()
+    }
+
+    /// Returns the names of the sources whose connections are shown in the
+    /// header: this one, or each of those it combines, which relay their
+    /// events through [`SourceEvent::Relayed`].
+    fn origins(&self) -> Vec<String> {
+        vec![self.name().to_string()]
+    }
+
+    /// Returns `false` if the source plays back recorded data rather than
+    /// showing tasks as they run.
+    fn is_live(&self) -> bool {
+        true
+    }
+
+    /// Returns the labels shown as columns of the task list, such as the
+    /// partition a scheduler placed each job in. The default shows none.
+    fn label_columns(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns how often the source fetches from its backend, for sources
+    /// that ask rather than being sent changes. Fetches happen on the
+    /// source's own thread; [`poll`](Self::poll) is still called on every
+    /// tick to hand over what they found. The default reads the
+    /// [`interval`](Self::interval).
+    fn poll_interval(&self) -> Option<Duration> {
+        self.interval().map(PollInterval::get)
+    }
+
+    /// Returns the interval a polling source shares with the thread it
+    /// fetches on; giving it is all such a source needs to have its interval
+    /// shown and changed.
+    fn interval(&self) -> Option<&PollInterval> {
+        None
+    }
+
+    /// Changes how often the source named `source` fetches, returning
+    /// `false` if no source by that name polls. Sources combining others
+    /// hand the change to the one named.
+    fn set_poll_interval(&mut self, source: &str, interval: Duration) -> bool {
+        match self.interval() {
+            Some(polled) if source == self.name() => {
+                polled.set(interval);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Carries out a control action, returning `false` if it does not apply
+    /// to the task as it stands. Only called for sources whose capabilities
+    /// include control; the result shows in the next poll.
+    fn control(&mut self, action: &TaskAction) -> eyre::Result<bool> {
+        Err(eyre::eyre!("the {} source does not accept control actions ({})", self.name(), action))
+    }
+}
@@ -0,0 +1,135 @@
+//! Tiny per-task activity charts shown at the end of task rows.
+//!
+//! Each task keeps a fixed number of samples taken once per interval, so the
+//! cost is one small array per task and one pass over the store per second,
+//! independent of the frame rate.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::app::Task;
+
+/// Number of samples shown per row.
+pub const WIDTH: usize = 8;
+
+/// Time between samples.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Block characters from lowest to highest.
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The value charted in each row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SparklineSource {
+    /// No chart
+    #[default]
+    Off,
+    /// CPU usage, on a fixed 0–100% scale
+    Cpu,
+    /// Progress made per sample, scaled to the row's own maximum
+    Progress,
+}
+
+/// The latest samples of one task, oldest first once full.
+#[derive(Debug, Clone, Copy, Default)]
+struct Samples {
+    /// Ring buffer of sampled values
+    values: [f32; WIDTH],
+    /// Number of samples taken, up to `WIDTH`
+    len: usize,
+    /// Index the next sample is written to
+    head: usize,
+    /// Progress at the previous sample, for rates
+    last_progress: f64,
+}
+
+impl Samples {
+    /// Adds a sample, dropping the oldest once the ring is full.
+    fn push(&mut self, value: f32) {
+        self.values[self.head] = value;
+        self.head = (self.head + 1) % WIDTH;
+        self.len = (self.len + 1).min(WIDTH);
+    }
+
+    /// Iterates over the samples from oldest to newest.
+    fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        let start = (self.head + WIDTH - self.len) % WIDTH;
+        (0..self.len).map(move |i| self.values[(start + i) % WIDTH])
+    }
+}
+
+/// Sampled activity of every task in the store.
+#[derive(Debug, Clone, Default)]
+pub struct Sparklines {
+    /// What is charted
+    source: SparklineSource,
+    /// Samples per task ID
+    samples: HashMap<String, Samples>,
+    /// When the last sample was taken
+    last: Option<Instant>,
+}
+
+impl Sparklines {
+    /// Selects what is charted, discarding samples of the previous source.
+    pub fn set_source(&mut self, source: SparklineSource) {
+        if source != self.source {
+            self.source = source;
+            self.samples.clear();
+            self.last = None;
+        }
+    }
+
+    /// Returns what is charted.
+    pub fn source(&self) -> SparklineSource {
+        self.source
+    }
+
+    /// Returns `true` if charts are shown.
+    pub fn is_enabled(&self) -> bool {
+        self.source != SparklineSource::Off
+    }
+
+    /// Samples every task if the sample interval has elapsed.
+    pub fn observe(&mut self, tasks: &HashMap<String, Task>) {
+        if !self.is_enabled() || self.last.is_some_and(|last| last.elapsed() < SAMPLE_INTERVAL) {
+            return;
+        }
+        self.last = Some(Instant::now());
+
+        self.samples.retain(|id, _| tasks.contains_key(id));
+        for task in tasks.values() {
+            let samples = self.samples.entry(task.id.clone()).or_insert_with(|| Samples {
+                last_progress: task.progress,
+                ..Samples::default()
+            });
+            let value = match self.source {
+                SparklineSource::Off => continue,
+                SparklineSource::Cpu => task.cpu_usage as f32,
+                SparklineSource::Progress => (task.progress - samples.last_progress).max(0.0) as f32,
+            };
+            samples.last_progress = task.progress;
+            samples.push(value);
+        }
+    }
+
+    /// Renders a task's chart, padded to [`WIDTH`] characters.
+    pub fn render(&self, id: &str) -> String {
+        let Some(samples) = self.samples.get(id) else {
+            return " ".repeat(WIDTH);
+        };
+        let scale = match self.source {
+            SparklineSource::Progress => samples.iter().fold(0.0f32, f32::max),
+            _ => 1.0,
+        };
+
+        let mut chart = " ".repeat(WIDTH - samples.len);
+        chart.extend(samples.iter().map(|value| {
+            let level = if scale > 0.0 { (value / scale).clamp(0.0, 1.0) } else { 0.0 };
+            BARS[((level * (BARS.len() - 1) as f32).round()) as usize]
+        }));
+        chart
+    }
+}
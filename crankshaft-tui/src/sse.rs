@@ -0,0 +1,202 @@
+//! Task updates pushed as Server-Sent Events.
+//!
+//! Services that only publish their task events as an SSE stream are
+//! followed the same way as a WebSocket feed: the stream is kept open and
+//! every event reaches the app on the next tick. Only events named
+//! `task_update` are read, and the data of each holds one [`TaskUpdate`] in
+//! the engine's event format (see [`crate::protocol`]):
+//!
+//! ```text
+//! event: task_update
+//! data: {"type":"progress","id":"align","progress":0.5}
+//!
+//! ```
+//!
+//! Events of other kinds, comments, and keep-alives are skipped. A data
+//! field spread over several lines is read as one update, since that is how
+//! SSE splits a long value. Updates that do not follow the format are
+//! counted as malformed in the diagnostics overlay, and progress is
+//! coalesced when the app falls behind the stream (see
+//! [`crate::backpressure`]). When the stream ends or drops, the tasks stay on
+//! screen while it is opened again after a backoff (see
+//! [`crate::reconnect`]). A token for the stream is sent when it is opened
+//! (see [`crate::auth`]), and a stream served over HTTPS may be signed by a
+//! private authority (see [`crate::tls`]).
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use eyre::WrapErr;
+
+use crate::app::TaskUpdate;
+use crate::auth::{self, RemoteAccess};
+use crate::backpressure::{self, Inbox, Message, Outbox};
+use crate::capabilities::SourceCapabilities;
+use crate::protocol::decode_update;
+use crate::reconnect::{self, Backoff};
+use crate::source::{DataSource, SourceEvent};
+
+/// How long connecting to the service may take before it is abandoned.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Name of the events that carry task updates.
+pub const TASK_UPDATE_EVENT: &str = "task_update";
+
+/// A Server-Sent Events stream, followed on a background thread.
+pub struct SseDataSource {
+    /// URL of the stream
+    url: String,
+    /// Receives what happens on the connection
+    inbox: Inbox,
+    /// Events received with the last poll's updates, not yet taken
+    events: Vec<SourceEvent>,
+}
+
+impl SseDataSource {
+    /// Starts following the event stream at `url`, such as
+    /// `http://127.0.0.1:7879/v1/events`.
+    pub fn connect(url: &str) -> Self {
+        Self::connect_with(url, RemoteAccess::default())
+    }
+
+    /// Starts following the event stream at `url` with `access`'s token and
+    /// TLS settings.
+    pub fn connect_with(url: &str, access: RemoteAccess) -> Self {
+        let url = url.to_string();
+        let (sender, inbox) = backpressure::channel();
+        let stream = url.clone();
+        thread::spawn(move || {
+            reconnect::follow_with_retries(&stream, &sender, |backoff| {
+                follow(&stream, &access, &sender, backoff).map(|count| format!("the stream ended after {} updates", count))
+            });
+        });
+        Self {
+            url,
+            inbox,
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns the URL of the stream.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl DataSource for SseDataSource {
+    fn name(&self) -> &str {
+        "sse"
+    }
+
+    /// Everything updates can carry; the stream only flows towards the
+    /// monitor, so there is nobody to send control actions to.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            control: false,
+            ..SourceCapabilities::ALL
+        }
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        self.inbox.poll(&mut self.events)
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// The event being read from the stream, field by field.
+#[derive(Debug, Default)]
+struct Pending {
+    /// Name of the event, if the stream named it
+    name: Option<String>,
+    /// Lines of the data field
+    data: Vec<String>,
+}
+
+impl Pending {
+    /// Reads one line of the stream, returning the data of a task update
+    /// the line completes.
+    fn line(&mut self, line: &str) -> Option<String> {
+        if line.is_empty() {
+            let event = std::mem::take(self);
+            let is_update = event.name.as_deref() == Some(TASK_UPDATE_EVENT);
+            return (is_update && !event.data.is_empty()).then(|| event.data.join("\n"));
+        }
+        // Lines starting with a colon are comments, often sent as keep-alives
+        let (field, value) = match line.split_once(':') {
+            Some(("", _)) => return None,
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.name = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Opens the stream and hands over its task updates until it ends, the
+/// connection fails, or the app is gone, returning the number of updates
+/// read. `backoff` is reset once the stream is open.
+fn follow(url: &str, access: &RemoteAccess, sender: &Outbox, backoff: &mut Backoff) -> eyre::Result<usize> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .wrap_err("failed to start the event stream runtime")?;
+
+    runtime.block_on(async {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("crankshaft-tui/", env!("CARGO_PKG_VERSION")))
+            .connect_timeout(CONNECT_TIMEOUT);
+        let client = access.tls.apply(client)?.build()?;
+        let token = access.token.as_deref();
+
+        let started = Instant::now();
+        let mut response = auth::authorize(client.get(url), token)
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .map_err(eyre::Report::from)
+            .and_then(|response| auth::check(response, "sse", token.is_some()))
+            .wrap_err_with(|| format!("failed to open the event stream at {}", url))?;
+        backoff.reset();
+        let connected = [
+            Message::Event(SourceEvent::Connected { latency: started.elapsed() }),
+            Message::Event(SourceEvent::Message(format!("Following {}", url))),
+        ];
+        for message in connected {
+            if sender.send(message).is_err() {
+                return Ok(0);
+            }
+        }
+
+        let mut count = 0;
+        let mut buffer = Vec::new();
+        let mut pending = Pending::default();
+        while let Some(chunk) = response.chunk().await.wrap_err("the event stream was interrupted")? {
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                let Some(data) = pending.line(line.trim_end_matches(['\n', '\r'])) else {
+                    continue;
+                };
+                let message = match decode_update(&data) {
+                    Ok(update) => {
+                        count += 1;
+                        Message::Update(update)
+                    }
+                    Err(err) => Message::Event(SourceEvent::Malformed { kind: err.kind, detail: format!("{}: {}", err, data) }),
+                };
+                if sender.send(message).is_err() {
+                    return Ok(count);
+                }
+            }
+        }
+        Ok(count)
+    })
+}
@@ -0,0 +1,54 @@
+//! Local state kept between sessions.
+//!
+//! Unlike the configuration, this file is written by the application
+//! itself. It holds presentation choices that only exist on this machine,
+//! such as display names given to tasks, and never reaches the engine.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Name of the state file inside the state directory.
+const STATE_FILE: &str = "state.json";
+
+/// Everything persisted in the state file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocalState {
+    /// Display names given to tasks, by task ID
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub display_names: BTreeMap<String, String>,
+}
+
+impl LocalState {
+    /// Reads the state file, returning empty state if it does not exist.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(serde_json::from_str(&text)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes the state file, replacing it atomically so that a crash never
+    /// leaves it truncated.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&temp, path)
+    }
+}
+
+/// Returns the default state file location,
+/// `$XDG_STATE_HOME/crankshaft-tui/state.json` (or under `~/.local/state`).
+pub fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state")))?;
+    Some(base.join("crankshaft-tui").join(STATE_FILE))
+}
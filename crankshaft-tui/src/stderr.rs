@@ -0,0 +1,146 @@
+//! Capture of standard error while the dashboard is shown.
+//!
+//! Libraries write warnings straight to standard error, which lands in the
+//! middle of the alternate screen and corrupts the display until the next
+//! full redraw. While the dashboard is shown, standard error is pointed at a
+//! pipe instead, and each line written to it is added to the internal log
+//! included in crash bundles and counted in the performance HUD. Programs
+//! started by the sources inherit the pipe, so their complaints are captured
+//! too.
+//!
+//! Standard error is given back whenever the terminal is, before a panic
+//! message is printed and while an external program runs in the foreground.
+//! Capture is only supported on Unix; elsewhere standard error is left alone.
+
+use std::io;
+use std::sync::Mutex;
+
+/// Lines written to standard error while it was captured.
+struct Captured {
+    /// Number of lines captured
+    count: usize,
+    /// Most recent line captured
+    last: Option<String>,
+}
+
+/// Lines captured so far, shared with the thread reading the pipe.
+static CAPTURED: Mutex<Captured> = Mutex::new(Captured { count: 0, last: None });
+
+/// Runs `f` on the captured lines, ignoring a poisoned lock since the data
+/// is only informational.
+fn with_captured<T>(f: impl FnOnce(&mut Captured) -> T) -> T {
+    let mut captured = CAPTURED.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut captured)
+}
+
+/// Records a line written to standard error while it was captured.
+fn record(line: &str) {
+    crate::crash::log(format!("stderr: {}", line));
+    with_captured(|captured| {
+        captured.count += 1;
+        captured.last = Some(line.to_string());
+    });
+}
+
+/// Returns the number of lines captured from standard error.
+pub fn captured_lines() -> usize {
+    with_captured(|captured| captured.count)
+}
+
+/// Returns the most recent line captured from standard error, if any.
+pub fn last_captured_line() -> Option<String> {
+    with_captured(|captured| captured.last.clone())
+}
+
+/// Capture through a pipe duplicated over standard error.
+#[cfg(unix)]
+mod imp {
+    use std::fs::File;
+    use std::io::{self, BufRead, BufReader};
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::sync::Mutex;
+    use std::thread;
+
+    /// The original standard error, while it is captured.
+    static SAVED: Mutex<Option<OwnedFd>> = Mutex::new(None);
+
+    /// Returns `-1` as the last OS error.
+    fn check(result: libc::c_int) -> io::Result<libc::c_int> {
+        if result == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(result)
+        }
+    }
+
+    /// Points standard error at a pipe read on a thread of its own.
+    pub fn capture() -> io::Result<()> {
+        let mut saved = SAVED.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if saved.is_some() {
+            return Ok(());
+        }
+
+        let mut fds = [0; 2];
+        // SAFETY: `fds` has room for the two descriptors `pipe` writes, and
+        // each descriptor returned is owned by nothing else
+        let (read, write, original) = unsafe {
+            check(libc::pipe(fds.as_mut_ptr()))?;
+            let read = OwnedFd::from_raw_fd(fds[0]);
+            let write = OwnedFd::from_raw_fd(fds[1]);
+            let original = OwnedFd::from_raw_fd(check(libc::dup(libc::STDERR_FILENO))?);
+            (read, write, original)
+        };
+        // SAFETY: both descriptors are open; the pipe stays open through
+        // descriptor 2 once `write` is dropped
+        unsafe { check(libc::dup2(write.as_raw_fd(), libc::STDERR_FILENO))? };
+        drop(write);
+        *saved = Some(original);
+
+        // The thread ends once every copy of the pipe's write end is closed
+        thread::spawn(move || {
+            let reader = BufReader::new(File::from(read));
+            for line in reader.lines().map_while(Result::ok) {
+                if !line.trim().is_empty() {
+                    super::record(&line);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Puts the original standard error back.
+    pub fn release() {
+        let mut saved = SAVED.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(original) = saved.take() {
+            // SAFETY: `original` is open; descriptor 2 is replaced, closing
+            // this process's copy of the pipe
+            unsafe { libc::dup2(original.as_raw_fd(), libc::STDERR_FILENO) };
+        }
+    }
+}
+
+/// Leaves standard error alone.
+#[cfg(not(unix))]
+mod imp {
+    use std::io;
+
+    /// Does nothing.
+    pub fn capture() -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Does nothing.
+    pub fn release() {}
+}
+
+/// Points standard error at a pipe whose lines go to the internal log,
+/// until [`release`] is called. Does nothing if it is already captured.
+pub fn capture() -> io::Result<()> {
+    imp::capture()
+}
+
+/// Gives standard error back to the terminal. Does nothing if it is not
+/// captured.
+pub fn release() {
+    imp::release()
+}
@@ -0,0 +1,205 @@
+//! Task updates read as JSON lines from a stream, such as standard input.
+//!
+//! Each line is one [`TaskUpdate`] in the engine's event format (see
+//! [`crate::protocol`]), so a shell script or an engine without a supported
+//! protocol can drive the monitor by printing updates:
+//!
+//! ```sh
+//! {
+//!   echo '{"type":"created","id":"align","name":"align reads","status":"running"}'
+//!   echo '{"type":"progress","id":"align","progress":0.5}'
+//!   echo '{"type":"status_changed","id":"align","status":"completed"}'
+//! } | crankshaft-tui --stdin
+//! ```
+//!
+//! On Unix, a local engine can instead push updates to a path the monitor
+//! listens on with `--listen`, without any network setup. A named pipe made
+//! with `mkfifo` is read one writer after another; any other path is bound
+//! as a Unix socket that accepts any number of connections at once:
+//!
+//! ```sh
+//! crankshaft-tui --listen /tmp/crankshaft.sock &
+//! echo '{"type":"created","id":"align","name":"align reads","status":"running"}' | nc -U /tmp/crankshaft.sock
+//! ```
+//!
+//! Blank lines are skipped, and lines that do not follow the format are
+//! counted as malformed in the diagnostics overlay. Progress is coalesced
+//! when the app falls behind the stream (see [`crate::backpressure`]). The
+//! tasks stay on screen after the stream ends.
+
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use crate::app::TaskUpdate;
+use crate::backpressure::{self, Inbox, Message, Outbox};
+use crate::capabilities::SourceCapabilities;
+use crate::protocol::decode_update;
+use crate::source::{DataSource, SourceEvent};
+
+/// A stream of JSON lines, read on a background thread.
+pub struct StreamDataSource {
+    /// Short name of the stream, such as `stdin`
+    name: String,
+    /// Path listened on, if the stream is a named pipe or socket
+    path: Option<PathBuf>,
+    /// Whether the path is a socket bound here, removed on drop
+    bound: bool,
+    /// Receives what the reading thread decoded
+    inbox: Inbox,
+    /// Events received with the last poll's updates, not yet taken
+    events: Vec<SourceEvent>,
+}
+
+impl StreamDataSource {
+    /// Starts reading updates from standard input.
+    ///
+    /// Keys are still read from the terminal, which the terminal backend
+    /// opens directly when standard input is a pipe.
+    pub fn stdin() -> Self {
+        Self::from_reader("stdin", io::stdin())
+    }
+
+    /// Starts reading updates from `reader`, named `name` in messages.
+    pub fn from_reader(name: &str, reader: impl Read + Send + 'static) -> Self {
+        let (sender, inbox) = backpressure::channel();
+        let stream = name.to_string();
+        thread::spawn(move || {
+            let reason = match follow(BufReader::new(reader), &sender) {
+                Ok(count) => SourceEvent::Message(format!("{} closed after {} updates", stream, count)),
+                Err(err) => SourceEvent::Failed(format!("Stopped reading {}: {}", stream, err)),
+            };
+            let _ = sender.send(Message::Event(reason));
+        });
+        Self {
+            name: name.to_string(),
+            path: None,
+            bound: false,
+            inbox,
+            events: Vec::new(),
+        }
+    }
+
+    /// Starts listening for updates on `path`: read from it if it is a named
+    /// pipe, and otherwise accepted on a Unix socket bound there.
+    ///
+    /// A stale socket left behind by a crashed instance is replaced, but
+    /// binding fails if another instance is still listening or `path` is
+    /// anything else, such as a file meant for `--file`.
+    #[cfg(unix)]
+    pub fn listen(path: &Path) -> io::Result<Self> {
+        use std::os::unix::fs::FileTypeExt;
+
+        let (sender, inbox) = backpressure::channel();
+        let fifo = std::fs::metadata(path).is_ok_and(|metadata| metadata.file_type().is_fifo());
+        if fifo {
+            let pipe = path.to_path_buf();
+            thread::spawn(move || follow_fifo(&pipe, &sender));
+        } else {
+            let listener = crate::socket::bind(path)?;
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(stream) = stream else {
+                        continue;
+                    };
+                    let sender = sender.clone();
+                    thread::spawn(move || {
+                        let reason = match follow(BufReader::new(stream), &sender) {
+                            Ok(count) => SourceEvent::Message(format!("Connection closed after {} updates", count)),
+                            Err(err) => SourceEvent::Message(format!("Stopped reading a connection: {}", err)),
+                        };
+                        let _ = sender.send(Message::Event(reason));
+                    });
+                }
+            });
+        }
+        Ok(Self {
+            name: if fifo { "fifo" } else { "socket" }.to_string(),
+            path: Some(path.to_path_buf()),
+            bound: !fifo,
+            inbox,
+            events: Vec::new(),
+        })
+    }
+
+    /// Returns the path listened on, if the stream is a named pipe or
+    /// socket.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+}
+
+impl Drop for StreamDataSource {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.as_ref().filter(|_| self.bound) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+impl DataSource for StreamDataSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Everything updates can carry; there is nobody to send control
+    /// actions to.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            control: false,
+            ..SourceCapabilities::ALL
+        }
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        self.inbox.poll(&mut self.events)
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// Reads a named pipe until the app is gone. Opening it waits for a writer,
+/// and it is opened again for the next one once a writer closes it.
+#[cfg(unix)]
+fn follow_fifo(path: &Path, sender: &Outbox) {
+    loop {
+        let event = match std::fs::File::open(path) {
+            Ok(pipe) => match follow(BufReader::new(pipe), sender) {
+                Ok(count) => SourceEvent::Message(format!("{} closed after {} updates", path.display(), count)),
+                Err(err) => SourceEvent::Message(format!("Stopped reading {}: {}", path.display(), err)),
+            },
+            Err(err) => {
+                let _ = sender.send(Message::Event(SourceEvent::Failed(format!("Cannot open {}: {}", path.display(), err))));
+                return;
+            }
+        };
+        if sender.send(Message::Event(event)).is_err() {
+            return;
+        }
+    }
+}
+
+/// Decodes lines until the stream ends or the app is gone, returning the
+/// number of updates read.
+fn follow(reader: impl BufRead, sender: &Outbox) -> io::Result<usize> {
+    let mut count = 0;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message = match decode_update(&line) {
+            Ok(update) => {
+                count += 1;
+                Message::Update(update)
+            }
+            Err(err) => Message::Event(SourceEvent::Malformed { kind: err.kind, detail: format!("{}: {}", err, line) }),
+        };
+        if sender.send(message).is_err() {
+            break;
+        }
+    }
+    Ok(count)
+}
@@ -0,0 +1,56 @@
+//! Rendering of structured (JSON) log lines.
+//!
+//! Engines that log one JSON object per line are hard to read raw. Such
+//! lines are shown as `key=value` summaries instead, with the usual time,
+//! level, and message keys first; the full object can be expanded with `e`
+//! in the Logs tab. Lines that are not JSON objects are shown unchanged.
+
+use serde_json::{Map, Value};
+
+/// Keys shown first in a summary, in this order, when present.
+const LEADING_KEYS: [&str; 7] = ["time", "timestamp", "ts", "level", "severity", "msg", "message"];
+
+/// Parses a line holding a single JSON object.
+pub fn parse(line: &str) -> Option<Map<String, Value>> {
+    let line = line.trim();
+    if !line.starts_with('{') || !line.ends_with('}') {
+        return None;
+    }
+    match serde_json::from_str(line) {
+        Ok(Value::Object(object)) => Some(object),
+        _ => None,
+    }
+}
+
+/// Returns the object's fields as `(key, value)` pairs for a one-line
+/// summary: the leading keys first, then the rest in key order.
+pub fn summary(object: &Map<String, Value>) -> Vec<(&str, String)> {
+    let leading = LEADING_KEYS
+        .iter()
+        .filter_map(|key| object.get_key_value(*key));
+    let rest = object.iter().filter(|(key, _)| !LEADING_KEYS.contains(&key.as_str()));
+    leading
+        .chain(rest)
+        .map(|(key, value)| (key.as_str(), value_text(value)))
+        .collect()
+}
+
+/// Returns the object pretty-printed over several lines.
+pub fn expanded(object: &Map<String, Value>) -> Vec<String> {
+    serde_json::to_string_pretty(object)
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Formats a value for a summary: strings bare unless they would be
+/// ambiguous, everything else as compact JSON.
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) if !text.is_empty() && !text.contains(|c: char| c.is_whitespace() || c == '=' || c == '"') => {
+            text.clone()
+        }
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,80 @@
+//! Plain-text rendering of the task table.
+//!
+//! Where the terminal does not support OSC 52, text can still be copied by
+//! selecting it with the mouse, which the alternate screen and mouse capture
+//! prevent. Copy mode prints this table to the normal screen instead.
+
+use std::time::Duration;
+
+use crate::app::App;
+use crate::record::unix_now;
+
+/// Column headings, in display order.
+const HEADINGS: [&str; 6] = ["ID", "STATUS", "PROGRESS", "DURATION", "MEMORY", "NAME"];
+
+/// Index of the memory column, left out when the data source does not
+/// report memory.
+const MEMORY_COLUMN: usize = 4;
+
+/// Renders the visible tasks in list order as aligned columns, one line per
+/// task, without colors or other escape sequences. The data source's label
+/// columns come right before the name.
+pub fn render(app: &App) -> String {
+    let now = unix_now();
+    let keep = |row: [String; 6], labels: Vec<String>| -> Vec<String> {
+        let mut cells: Vec<String> = row
+            .into_iter()
+            .enumerate()
+            .filter(|(column, _)| *column != MEMORY_COLUMN || app.capabilities.memory)
+            .map(|(_, cell)| cell)
+            .collect();
+        let name = cells.len() - 1;
+        cells.splice(name..name, labels);
+        cells
+    };
+    let rows: Vec<Vec<String>> = app
+        .visible_task_ids()
+        .into_iter()
+        .filter_map(|id| app.tasks.get(id))
+        .map(|task| {
+            let memory = task.memory_usage;
+            let labels = app
+                .label_columns
+                .iter()
+                .map(|label| task.labels.get(label).cloned().unwrap_or_else(|| "-".to_string()))
+                .collect();
+            keep([
+                task.id.clone(),
+                task.status.to_string(),
+                app.numbers.percent(app.display_progress(task)),
+                task.duration(now).map_or_else(|| "-".to_string(), |secs| app.numbers.duration(Duration::from_secs(secs))),
+                match memory.limit.or(memory.requested) {
+                    Some(total) => app.numbers.bytes_of(memory.used, total),
+                    None => app.numbers.bytes(memory.used),
+                },
+                app.display_name(task).to_string(),
+            ], labels)
+        })
+        .collect();
+
+    let label_headings = app.label_columns.iter().map(|label| label.to_uppercase()).collect();
+    let headings = keep(HEADINGS.map(str::to_string), label_headings);
+    let mut widths: Vec<usize> = headings.iter().map(|heading| heading.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut text = String::new();
+    for row in std::iter::once(&headings).chain(&rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        text.push_str(line.join("  ").trim_end());
+        text.push('\n');
+    }
+    text
+}
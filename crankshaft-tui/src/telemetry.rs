@@ -0,0 +1,184 @@
+//! Opt-in anonymous usage reporting.
+//!
+//! Nothing is collected or sent unless the configuration turns it on and
+//! names where reports go:
+//!
+//! ```toml
+//! [telemetry]
+//! enabled = true
+//! endpoint = "https://metrics.example.org/crankshaft-tui"
+//! ```
+//!
+//! A report is posted once, as JSON, when the monitor exits. It holds only
+//! the version, operating system and architecture, the last terminal size,
+//! how often each feature was used, and how often each category of error
+//! occurred. Feature and error names come from a fixed list in this module;
+//! task IDs, names, labels, logs, paths, and user or host names are never
+//! included. The About overlay (`i`) says whether reporting is on and where
+//! reports go.
+//!
+//! The module is behind the `telemetry` cargo feature; building with
+//! `--no-default-features` removes it entirely.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crossterm::event::KeyCode;
+use eyre::WrapErr;
+use serde::Serialize;
+
+use crate::app::Tab;
+
+/// How long sending the report may take before it is abandoned, so exiting
+/// is never held up for long.
+const TIMEOUT: Duration = Duration::from_secs(3);
+
+/// What a report contains.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    /// The crate version
+    pub version: &'static str,
+    /// Operating system, such as `linux`
+    pub os: &'static str,
+    /// CPU architecture, such as `x86_64`
+    pub arch: &'static str,
+    /// Last terminal size as (columns, rows), once drawn
+    pub terminal: Option<(u16, u16)>,
+    /// Number of times each feature was used
+    pub features: BTreeMap<&'static str, u64>,
+    /// Number of errors of each category
+    pub errors: BTreeMap<&'static str, u64>,
+}
+
+/// Usage counted during a session, for the report sent on exit.
+#[derive(Debug, Clone)]
+pub struct Telemetry {
+    /// Where the report is posted
+    endpoint: String,
+    /// What has been counted so far
+    report: UsageReport,
+}
+
+impl Telemetry {
+    /// Starts counting usage to be reported to `endpoint`.
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            report: UsageReport {
+                version: env!("CARGO_PKG_VERSION"),
+                os: std::env::consts::OS,
+                arch: std::env::consts::ARCH,
+                terminal: None,
+                features: BTreeMap::new(),
+                errors: BTreeMap::new(),
+            },
+        }
+    }
+
+    /// Returns where the report is posted.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Returns what would be sent if the session ended now.
+    pub fn report(&self) -> &UsageReport {
+        &self.report
+    }
+
+    /// Counts a use of the feature bound to a key, if it is one of the
+    /// reported features.
+    pub fn key(&mut self, code: KeyCode, tab: Tab) {
+        if let Some(feature) = key_feature(code, tab) {
+            *self.report.features.entry(feature).or_default() += 1;
+        }
+    }
+
+    /// Counts an error of the category a failed audited action falls in.
+    pub fn failed_action(&mut self, action: &str) {
+        *self.report.errors.entry(error_category(action)).or_default() += 1;
+    }
+
+    /// Counts an error reported by the data source.
+    pub fn connector_error(&mut self) {
+        *self.report.errors.entry("connector").or_default() += 1;
+    }
+
+    /// Remembers the terminal size.
+    pub fn terminal_size(&mut self, columns: u16, rows: u16) {
+        self.report.terminal = Some((columns, rows));
+    }
+
+    /// Posts the report, waiting at most a few seconds.
+    pub fn send(&self) -> eyre::Result<()> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .wrap_err("failed to start the usage report runtime")?;
+        runtime
+            .block_on(async {
+                let client = reqwest::Client::builder()
+                    .user_agent(concat!("crankshaft-tui/", env!("CARGO_PKG_VERSION")))
+                    .timeout(TIMEOUT)
+                    .build()?;
+                client.post(&self.endpoint).json(&self.report).send().await?.error_for_status()
+            })
+            .wrap_err_with(|| format!("failed to send the usage report to {}", self.endpoint))?;
+        Ok(())
+    }
+}
+
+/// Returns the reported feature a key stands for, if any.
+fn key_feature(code: KeyCode, tab: Tab) -> Option<&'static str> {
+    let feature = match code {
+        KeyCode::Tab | KeyCode::BackTab => "switch tab",
+        KeyCode::F(12) => "performance overlay",
+        KeyCode::Char('D') => "diagnostics overlay",
+        KeyCode::Char('A') => "audit overlay",
+        KeyCode::Char('i') => "about overlay",
+        KeyCode::Char('W') => "workflow drawer",
+        KeyCode::Char('J') => "raw json",
+        KeyCode::Char('C') => "metric chart",
+        KeyCode::Char('T') => "copy mode",
+        KeyCode::Char('S') => "snapshot",
+        KeyCode::Char('G') => "graph export",
+        KeyCode::Char('[') | KeyCode::Char(']') => "history",
+        KeyCode::Char('m') => "bookmark",
+        KeyCode::Char('o') => "sort",
+        KeyCode::Char('t') => "top mode",
+        KeyCode::Char('g') => "grouping",
+        KeyCode::Char('p') => "pin",
+        KeyCode::Char('a') => "archive",
+        KeyCode::Char('u') => "undo",
+        KeyCode::Char('c') | KeyCode::Char('R') | KeyCode::Char('<') | KeyCode::Char('>') => "control action",
+        KeyCode::Char('d') => "dry run",
+        KeyCode::Char('b') | KeyCode::Char('E') => "task diff",
+        KeyCode::Char('O') => "external diff",
+        KeyCode::Char('s') => "save log",
+        KeyCode::Char('+') | KeyCode::Char('-') if tab == Tab::Logs => "log panes",
+        KeyCode::Char('e') if tab == Tab::Logs => "structured logs",
+        KeyCode::Char('v') if tab == Tab::Timeline => "timeline lanes",
+        KeyCode::Char('f') | KeyCode::Enter if tab == Tab::Events => "event feed",
+        KeyCode::Char('r') if tab == Tab::Tasks => "rename",
+        _ => return None,
+    };
+    Some(feature)
+}
+
+/// Returns the category of a failed audited action, without any of the
+/// task IDs or paths the action names.
+fn error_category(action: &str) -> &'static str {
+    const CATEGORIES: [(&str, &str); 8] = [
+        ("write snapshot", "snapshot"),
+        ("export graph", "graph export"),
+        ("save log", "log download"),
+        ("undo", "undo"),
+        ("cancel", "control"),
+        ("retry", "control"),
+        ("raise priority", "control"),
+        ("lower priority", "control"),
+    ];
+    CATEGORIES
+        .iter()
+        .find(|(prefix, _)| action.starts_with(prefix))
+        .map_or("other", |(_, category)| category)
+}
@@ -0,0 +1,380 @@
+//! GA4GH Task Execution Service (TES) backends.
+//!
+//! A TES v1 server is polled for its task list (`GET <url>/tasks` with the
+//! `FULL` view, page by page) on a background thread. Tasks that are new or
+//! changed since the last poll are handed to the app whole, and tasks the
+//! server no longer lists are removed. The URL is the service's base, such
+//! as `https://tes.example.org/ga4gh/tes/v1`.
+//!
+//! TES states map onto task statuses as follows:
+//!
+//! - `UNKNOWN`, `QUEUED`, `INITIALIZING`, and `PAUSED` are pending
+//! - `RUNNING` and `CANCELING` are running
+//! - `COMPLETE` is completed
+//! - `EXECUTOR_ERROR`, `SYSTEM_ERROR`, `CANCELED`, and `PREEMPTED` are failed
+//!
+//! TES reports neither resource use nor a log stream, only the end of each
+//! executor's output once it has run. Those tails, with exit codes, are
+//! shown in the task details pane; progress is the share of executors that
+//! have finished.
+//!
+//! A token for the service is sent with every request (see
+//! [`crate::auth`]); if the service refuses it, polling stops. A service
+//! served over HTTPS may be signed by a private authority (see
+//! [`crate::tls`]).
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use eyre::WrapErr;
+use serde::Deserialize;
+
+use crate::app::{ExecutorLog, MemoryUsage, Task, TaskStatus, TaskUpdate};
+use crate::auth::{self, RemoteAccess, Unauthorized};
+use crate::capabilities::SourceCapabilities;
+use crate::format::parse_timestamp;
+use crate::logs::LogBuffer;
+use crate::polling::PollInterval;
+use crate::source::{DataSource, SourceEvent};
+
+/// How often the task list is fetched unless configured otherwise.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long one page of the task list may take to fetch.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Tasks requested per page.
+const PAGE_SIZE: &str = "256";
+
+/// Bytes in a gigabyte, the unit of TES resource requests.
+const GIGABYTE: f64 = 1e9;
+
+/// What the polling thread hands over.
+#[derive(Debug)]
+enum Message {
+    /// A change to the task store
+    Update(TaskUpdate),
+    /// Something that happened to the connection
+    Event(SourceEvent),
+}
+
+/// A TES server, polled on a background thread.
+pub struct TesDataSource {
+    /// Base URL of the service, without a trailing slash
+    url: String,
+    /// Receives what the polling thread found
+    receiver: Receiver<Message>,
+    /// Events received with the last poll's updates, not yet taken
+    events: Vec<SourceEvent>,
+    /// Time between fetches, shared with the polling thread
+    interval: PollInterval,
+}
+
+impl TesDataSource {
+    /// Starts polling the TES service at `url`.
+    pub fn connect(url: &str) -> Self {
+        Self::connect_with(url, RemoteAccess::default())
+    }
+
+    /// Starts polling the TES service at `url` with `access`'s token and TLS
+    /// settings.
+    pub fn connect_with(url: &str, access: RemoteAccess) -> Self {
+        let url = url.trim_end_matches('/').to_string();
+        let (sender, receiver) = mpsc::channel();
+        let base = url.clone();
+        let interval = PollInterval::new(POLL_INTERVAL);
+        let shared = interval.clone();
+        thread::spawn(move || {
+            if let Err(err) = follow(&base, &access, &shared, &sender) {
+                let reason = format!("Stopped polling TES at {}: {:#}", base, err);
+                let _ = sender.send(Message::Event(SourceEvent::Failed(reason)));
+            }
+        });
+        Self {
+            url,
+            receiver,
+            events: Vec::new(),
+            interval,
+        }
+    }
+
+    /// Returns the service's base URL.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl DataSource for TesDataSource {
+    fn name(&self) -> &str {
+        "tes"
+    }
+
+    /// Task states only: TES has no resource metrics, log stream, or
+    /// dependencies between tasks.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::NONE
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        let mut updates = Vec::new();
+        for message in self.receiver.try_iter() {
+            match message {
+                Message::Update(update) => updates.push(update),
+                Message::Event(event) => self.events.push(event),
+            }
+        }
+        updates
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn interval(&self) -> Option<&PollInterval> {
+        Some(&self.interval)
+    }
+}
+
+/// One page of `GET /tasks`.
+#[derive(Debug, Deserialize)]
+struct ListTasksResponse {
+    /// The tasks on this page
+    #[serde(default)]
+    tasks: Vec<serde_json::Value>,
+    /// Token for the next page, absent or empty on the last one
+    #[serde(default)]
+    next_page_token: Option<String>,
+}
+
+/// The parts of a TES task the monitor shows.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct TesTask {
+    /// ID assigned by the service
+    id: String,
+    /// Lifecycle state, such as `QUEUED` or `COMPLETE`
+    state: String,
+    /// Name given when the task was created
+    name: Option<String>,
+    /// Resources the task requested
+    resources: Option<TesResources>,
+    /// Containers run in turn
+    executors: Vec<TesExecutor>,
+    /// One entry per attempt, the latest last
+    logs: Vec<TesTaskLog>,
+    /// Tags set when the task was created
+    tags: BTreeMap<String, String>,
+}
+
+/// Resources a TES task requested.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct TesResources {
+    /// CPU cores requested
+    cpu_cores: Option<f64>,
+    /// Memory requested, in gigabytes
+    ram_gb: Option<f64>,
+}
+
+/// An executor of a TES task.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct TesExecutor {
+    /// Container image the executor runs
+    image: String,
+    /// Command run in the container
+    command: Vec<String>,
+    /// Environment variables set in the container
+    env: BTreeMap<String, String>,
+}
+
+/// One attempt at running a TES task.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct TesTaskLog {
+    /// What each executor reported, in order
+    logs: Vec<TesExecutorLog>,
+    /// When the attempt started, in RFC 3339
+    start_time: Option<String>,
+    /// When the attempt ended, in RFC 3339
+    end_time: Option<String>,
+}
+
+/// What one executor of an attempt reported.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct TesExecutorLog {
+    /// When the executor ended, in RFC 3339
+    end_time: Option<String>,
+    /// Tail of the executor's standard output
+    stdout: String,
+    /// Tail of the executor's standard error
+    stderr: String,
+    /// Exit code of the executor, once it has ended
+    exit_code: Option<i32>,
+}
+
+/// Returns the status a TES task state stands for.
+pub fn status(state: &str) -> TaskStatus {
+    match state {
+        "RUNNING" | "CANCELING" => TaskStatus::Running,
+        "COMPLETE" => TaskStatus::Completed,
+        "EXECUTOR_ERROR" | "SYSTEM_ERROR" | "CANCELED" | "PREEMPTED" => TaskStatus::Failed,
+        _ => TaskStatus::Pending,
+    }
+}
+
+/// Converts a task as TES reports it, keeping the payload as the task's raw
+/// JSON.
+pub fn to_task(value: &serde_json::Value) -> serde_json::Result<Task> {
+    let tes = TesTask::deserialize(value)?;
+    let status = status(&tes.state);
+    // Only the latest attempt counts; earlier ones were retried
+    let attempt = tes.logs.last();
+    let executor_logs = attempt.map(|attempt| attempt.logs.as_slice()).unwrap_or_default();
+
+    let finished = executor_logs.iter().filter(|log| log.end_time.is_some()).count();
+    let progress = match status {
+        TaskStatus::Completed => 1.0,
+        _ if tes.executors.is_empty() => 0.0,
+        _ => (finished as f64 / tes.executors.len() as f64).min(1.0),
+    };
+
+    let executors = tes
+        .executors
+        .iter()
+        .enumerate()
+        .map(|(index, executor)| {
+            let log = executor_logs.get(index);
+            ExecutorLog {
+                image: executor.image.clone(),
+                command: executor.command.clone(),
+                env: executor.env.clone(),
+                exit_code: log.and_then(|log| log.exit_code),
+                stdout: log.map(|log| log.stdout.clone()).unwrap_or_default(),
+                stderr: log.map(|log| log.stderr.clone()).unwrap_or_default(),
+            }
+        })
+        .collect();
+
+    let mut labels = tes.tags;
+    labels.entry("backend".to_string()).or_insert_with(|| "tes".to_string());
+    let resources = tes.resources.unwrap_or_default();
+
+    Ok(Task {
+        name: tes.name.unwrap_or_else(|| tes.id.clone()),
+        id: tes.id,
+        status,
+        progress,
+        cpu_usage: 0.0,
+        cpus: resources.cpu_cores,
+        memory_usage: MemoryUsage {
+            used: 0,
+            requested: resources.ram_gb.map(|gb| (gb * GIGABYTE) as u64),
+            limit: None,
+        },
+        started_at: attempt.and_then(|attempt| attempt.start_time.as_deref()).and_then(parse_timestamp),
+        finished_at: matches!(status, TaskStatus::Completed | TaskStatus::Failed)
+            .then(|| attempt.and_then(|attempt| attempt.end_time.as_deref()).and_then(parse_timestamp))
+            .flatten(),
+        dependencies: Vec::new(),
+        queue: None,
+        attempt: (!tes.logs.is_empty()).then_some(tes.logs.len() as u32),
+        labels,
+        command: Vec::new(),
+        env: BTreeMap::new(),
+        executors,
+        container: None,
+        pods: Vec::new(),
+        logs: LogBuffer::default(),
+        raw: Some(value.clone()),
+        reported_progress: None,
+        last_updated: None,
+    })
+}
+
+/// Polls the task list until the app is gone, reporting failed polls and
+/// carrying on after them unless the service refused the credentials.
+fn follow(base: &str, access: &RemoteAccess, interval: &PollInterval, sender: &Sender<Message>) -> eyre::Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .wrap_err("failed to start the TES polling runtime")?;
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("crankshaft-tui/", env!("CARGO_PKG_VERSION")))
+        .timeout(REQUEST_TIMEOUT);
+    let client = access.tls.apply(client)?.build()?;
+
+    // The payload last handed over for each task, to send only changes
+    let mut reported: HashMap<String, serde_json::Value> = HashMap::new();
+    let mut reachable = false;
+    loop {
+        let started = Instant::now();
+        let mut messages = Vec::new();
+        match runtime.block_on(list_tasks(&client, base, access.token.as_deref())) {
+            Ok(tasks) => {
+                messages.push(Message::Event(SourceEvent::Connected { latency: started.elapsed() }));
+                if !reachable {
+                    messages.push(Message::Event(SourceEvent::Message(format!("Connected to TES at {}", base))));
+                    reachable = true;
+                }
+                let mut stale: HashSet<String> = reported.keys().cloned().collect();
+                for value in tasks {
+                    let task = match to_task(&value) {
+                        Ok(task) => task,
+                        Err(err) => {
+                            messages.push(Message::Event(SourceEvent::Dropped(format!("{}: {}", err, value))));
+                            continue;
+                        }
+                    };
+                    stale.remove(&task.id);
+                    if reported.get(&task.id) != Some(&value) {
+                        reported.insert(task.id.clone(), value);
+                        messages.push(Message::Update(TaskUpdate::Created(Box::new(task))));
+                    }
+                }
+                for id in stale {
+                    reported.remove(&id);
+                    messages.push(Message::Update(TaskUpdate::Removed { id }));
+                }
+            }
+            // Polling again with the same token would be refused the same way
+            Err(err) if err.downcast_ref::<Unauthorized>().is_some() => return Err(err),
+            Err(err) => {
+                reachable = false;
+                let reason = format!("Cannot poll TES at {}: {:#}", base, err);
+                messages.push(Message::Event(SourceEvent::Failed(reason)));
+            }
+        }
+        for message in messages {
+            if sender.send(message).is_err() {
+                return Ok(());
+            }
+        }
+        interval.wait_from(started);
+    }
+}
+
+/// Fetches every page of the task list.
+async fn list_tasks(client: &reqwest::Client, base: &str, token: Option<&str>) -> eyre::Result<Vec<serde_json::Value>> {
+    let mut tasks = Vec::new();
+    let mut page_token: Option<String> = None;
+    loop {
+        let mut request = auth::authorize(client.get(format!("{}/tasks", base)), token)
+            .query(&[("view", "FULL"), ("page_size", PAGE_SIZE)]);
+        if let Some(token) = &page_token {
+            request = request.query(&[("page_token", token)]);
+        }
+        let page: ListTasksResponse = auth::check(request.send().await?, "tes", token.is_some())?
+            .json()
+            .await
+            .wrap_err("failed to decode the task list")?;
+        tasks.extend(page.tasks);
+        match page.next_page_token.filter(|token| !token.is_empty()) {
+            Some(token) => page_token = Some(token),
+            None => return Ok(tasks),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Colors and symbols used to encode task status.
+//!
+//! Status is never conveyed by color alone: every status also has a distinct
+//! symbol, and the palette can be switched to variants that stay
+//! distinguishable with common color vision deficiencies.
+
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::app::{Task, TaskStatus};
+
+/// Color palette for status encodings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Palette {
+    /// Blue/yellow/green/red
+    #[default]
+    Default,
+    /// Okabe-Ito colors chosen to separate well for red-green
+    /// (deuteranopia) color blindness
+    Deuteranopia,
+    /// Okabe-Ito colors avoiding reliance on red (protanopia)
+    Protanopia,
+}
+
+/// Symbol set used alongside status colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusSymbols {
+    /// Emoji icons
+    #[default]
+    Emoji,
+    /// Geometric shapes that render in any terminal font
+    Shapes,
+    /// Single letters (P, R, C, F)
+    Letters,
+}
+
+/// Visual theme of the interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Status color palette
+    pub palette: Palette,
+    /// Status symbol set
+    pub symbols: StatusSymbols,
+}
+
+impl Theme {
+    /// Returns the color used for a status.
+    pub fn status_color(&self, status: TaskStatus) -> Color {
+        match (self.palette, status) {
+            (Palette::Default, TaskStatus::Pending) => Color::Blue,
+            (Palette::Default, TaskStatus::Running) => Color::Yellow,
+            (Palette::Default, TaskStatus::Completed) => Color::Green,
+            (Palette::Default, TaskStatus::Failed) => Color::Red,
+
+            (Palette::Deuteranopia, TaskStatus::Pending) => Color::Rgb(0x56, 0xb4, 0xe9),
+            (Palette::Deuteranopia, TaskStatus::Running) => Color::Rgb(0xf0, 0xe4, 0x42),
+            (Palette::Deuteranopia, TaskStatus::Completed) => Color::Rgb(0x00, 0x72, 0xb2),
+            (Palette::Deuteranopia, TaskStatus::Failed) => Color::Rgb(0xd5, 0x5e, 0x00),
+
+            // Vermillion reads as a dark olive without red cones, so failed
+            // tasks stand apart from the light yellow of running ones
+            (Palette::Protanopia, TaskStatus::Pending) => Color::Rgb(0x56, 0xb4, 0xe9),
+            (Palette::Protanopia, TaskStatus::Running) => Color::Rgb(0xf0, 0xe4, 0x42),
+            (Palette::Protanopia, TaskStatus::Completed) => Color::Rgb(0x00, 0x72, 0xb2),
+            (Palette::Protanopia, TaskStatus::Failed) => Color::Rgb(0xd5, 0x5e, 0x00),
+        }
+    }
+
+    /// Returns the symbol used for a status.
+    pub fn status_symbol(&self, status: TaskStatus) -> &'static str {
+        match (self.symbols, status) {
+            (StatusSymbols::Emoji, TaskStatus::Pending) => "⏳",
+            (StatusSymbols::Emoji, TaskStatus::Running) => "▶️",
+            (StatusSymbols::Emoji, TaskStatus::Completed) => "✅",
+            (StatusSymbols::Emoji, TaskStatus::Failed) => "❌",
+
+            (StatusSymbols::Shapes, TaskStatus::Pending) => "○",
+            (StatusSymbols::Shapes, TaskStatus::Running) => "▶",
+            (StatusSymbols::Shapes, TaskStatus::Completed) => "●",
+            (StatusSymbols::Shapes, TaskStatus::Failed) => "✕",
+
+            (StatusSymbols::Letters, TaskStatus::Pending) => "P",
+            (StatusSymbols::Letters, TaskStatus::Running) => "R",
+            (StatusSymbols::Letters, TaskStatus::Completed) => "C",
+            (StatusSymbols::Letters, TaskStatus::Failed) => "F",
+        }
+    }
+
+    /// Returns the symbol used for a task: that of its status, or a retry
+    /// arrow while a requeued task is waiting or running again. Letters
+    /// keep the status letter, the attempt count alone marking the retry.
+    pub fn task_symbol(&self, task: &Task) -> &'static str {
+        if !task.is_requeued() || matches!(task.status, TaskStatus::Completed | TaskStatus::Failed) {
+            return self.status_symbol(task.status);
+        }
+        match self.symbols {
+            StatusSymbols::Emoji => "🔁",
+            StatusSymbols::Shapes => "↻",
+            StatusSymbols::Letters => self.status_symbol(task.status),
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Lane layout for the timeline tab.
+//!
+//! Every task that has started is drawn as a bar from its start to its end
+//! (or now). Without grouping each task gets its own row; grouped by a key,
+//! tasks sharing a value form a lane and are packed into as few rows as
+//! their overlaps allow, so a workflow's structure shows instead of a wall
+//! of bars.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::app::Task;
+
+/// Lane name for tasks that lack the grouping label.
+pub const UNLABELED: &str = "(none)";
+
+/// What timeline lanes are grouped by.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum LaneKey {
+    /// No grouping: one row per task
+    #[default]
+    None,
+    /// The task's step, i.e. its name without a trailing shard or index
+    Step,
+    /// The value of a backend label, such as `sample` or `backend`
+    Label(String),
+}
+
+impl LaneKey {
+    /// Returns the lane a task belongs to.
+    pub fn lane_of(&self, task: &Task) -> String {
+        match self {
+            LaneKey::None => String::new(),
+            LaneKey::Step => step_name(&task.name).to_string(),
+            LaneKey::Label(key) => task.labels.get(key).cloned().unwrap_or_else(|| UNLABELED.to_string()),
+        }
+    }
+
+    /// Returns the key after this one when cycling: no grouping, then the
+    /// step, then each label present on the tasks in name order.
+    pub fn next<'a>(&self, tasks: impl IntoIterator<Item = &'a Task>) -> LaneKey {
+        let mut keys = vec![LaneKey::None, LaneKey::Step];
+        let labels: std::collections::BTreeSet<&String> = tasks.into_iter().flat_map(|task| task.labels.keys()).collect();
+        keys.extend(labels.into_iter().map(|label| LaneKey::Label(label.clone())));
+        let position = keys.iter().position(|key| key == self);
+        keys[position.map_or(0, |position| (position + 1) % keys.len())].clone()
+    }
+}
+
+impl FromStr for LaneKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" => Err("lane key must not be empty".to_string()),
+            "none" => Ok(LaneKey::None),
+            "step" => Ok(LaneKey::Step),
+            label => Ok(LaneKey::Label(label.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for LaneKey {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for LaneKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LaneKey::None => write!(f, "none"),
+            LaneKey::Step => write!(f, "step"),
+            LaneKey::Label(label) => write!(f, "{}", label),
+        }
+    }
+}
+
+/// Strips a trailing shard or index from a task name, so that
+/// `align_reads (shard 12)` and `Sample Task 3` become `align_reads` and
+/// `Sample Task`.
+pub fn step_name(name: &str) -> &str {
+    let name = match name.rfind(" (") {
+        Some(open) if name.ends_with(')') => &name[..open],
+        _ => name,
+    };
+    let trimmed = name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '-' || c == '_' || c == ' ');
+    if trimmed.is_empty() {
+        name
+    } else {
+        trimmed
+    }
+}
+
+/// A bar on the timeline, in seconds since the Unix epoch.
+#[derive(Debug, Clone, Copy)]
+pub struct Bar<'a> {
+    /// The task drawn
+    pub task: &'a Task,
+    /// When it started
+    pub start: u64,
+    /// When it finished, or now if it is still running
+    pub end: u64,
+}
+
+/// Tasks sharing a lane value.
+#[derive(Debug, Clone)]
+pub struct Lane<'a> {
+    /// The shared value, empty without grouping
+    pub name: String,
+    /// Rows of non-overlapping bars
+    pub rows: Vec<Vec<Bar<'a>>>,
+    /// Number of bars in the lane
+    pub len: usize,
+}
+
+/// Groups started tasks into lanes ordered by name, returning them with the
+/// earliest start time.
+pub fn layout<'a>(tasks: impl IntoIterator<Item = &'a Task>, key: &LaneKey, now: u64) -> (Vec<Lane<'a>>, Option<u64>) {
+    let mut grouped: BTreeMap<String, Vec<Bar<'a>>> = BTreeMap::new();
+    let mut earliest: Option<u64> = None;
+    for task in tasks {
+        let Some(start) = task.started_at else {
+            continue;
+        };
+        earliest = Some(earliest.map_or(start, |earliest| earliest.min(start)));
+        let end = task.finished_at.unwrap_or(now).max(start);
+        grouped.entry(key.lane_of(task)).or_default().push(Bar { task, start, end });
+    }
+
+    let lanes = grouped
+        .into_iter()
+        .map(|(name, mut bars)| {
+            let len = bars.len();
+            let rows = if *key == LaneKey::None {
+                bars.into_iter().map(|bar| vec![bar]).collect()
+            } else {
+                bars.sort_by_key(|bar| (bar.start, bar.end));
+                pack(bars)
+            };
+            Lane { name, rows, len }
+        })
+        .collect();
+    (lanes, earliest)
+}
+
+/// Places each bar, earliest first, in the first row it does not overlap.
+fn pack(bars: Vec<Bar<'_>>) -> Vec<Vec<Bar<'_>>> {
+    let mut rows: Vec<Vec<Bar<'_>>> = Vec::new();
+    for bar in bars {
+        match rows.iter_mut().find(|row| row.last().is_some_and(|last| last.end < bar.start)) {
+            Some(row) => row.push(bar),
+            None => rows.push(vec![bar]),
+        }
+    }
+    rows
+}
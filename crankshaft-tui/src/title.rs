@@ -0,0 +1,41 @@
+//! The terminal window's title.
+//!
+//! While the dashboard is shown, the title carries the task counts, such as
+//! `crankshaft: 42 running / 3 failed`, so that a minimized terminal or its
+//! taskbar entry still tells how the workflow is doing. The title the
+//! terminal had before is pushed onto its title stack on startup and popped
+//! on exit, which xterm and most terminals modelled on it support; others
+//! keep the last title set. While the dashboard is blanked, the title only
+//! names the monitor.
+
+use std::io::{self, Write};
+
+use crate::app::App;
+
+/// Returns the title describing the tasks `app` shows.
+pub fn text(app: &App) -> String {
+    if app.blanked {
+        return "crankshaft".to_string();
+    }
+    let counts = app.status_counts();
+    format!("crankshaft: {} running / {} failed", counts.running, counts.failed)
+}
+
+/// Saves the terminal's title so that [`restore`] can bring it back.
+pub fn save(out: &mut impl Write) -> io::Result<()> {
+    write!(out, "\x1b[22;0t")?;
+    out.flush()
+}
+
+/// Sets the terminal's title to `title`, without any control characters.
+pub fn set(out: &mut impl Write, title: &str) -> io::Result<()> {
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    write!(out, "\x1b]0;{}\x07", title)?;
+    out.flush()
+}
+
+/// Brings back the title saved by [`save`].
+pub fn restore(out: &mut impl Write) -> io::Result<()> {
+    write!(out, "\x1b[23;0t")?;
+    out.flush()
+}
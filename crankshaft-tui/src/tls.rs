@@ -0,0 +1,133 @@
+//! TLS settings for remote sources.
+//!
+//! Sources reached over HTTPS, such as an engine, an event stream, a TES
+//! service, or a Kubernetes API server, trust the public certificate
+//! authorities. Inside a cluster whose services are signed by a private
+//! authority, its certificates are given in the `[tls]` section of the
+//! configuration file, along with a client certificate for servers that
+//! require one:
+//!
+//! ```toml
+//! [tls]
+//! ca_file = "/etc/ssl/cluster-ca.pem"
+//! client_cert = "/run/secrets/monitor.crt"
+//! client_key = "/run/secrets/monitor.key"
+//! ```
+//!
+//! The files are PEM encoded; `ca_file` may hold several certificates, and
+//! `client_key` may be left out if the key follows the certificate in
+//! `client_cert`. They are read when the app starts, so that a missing or
+//! invalid file is reported before the dashboard is shown. WebSocket feeds
+//! followed over `wss://` trust and present the same certificates.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use eyre::{eyre, WrapErr};
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use serde::Deserialize;
+
+/// Line that starts each certificate in a PEM file.
+const CERTIFICATE_START: &str = "-----BEGIN CERTIFICATE-----";
+
+/// Where the TLS settings are read from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM file of the certificate authorities trusted besides the public
+    /// ones
+    pub ca_file: Option<PathBuf>,
+    /// PEM file of the certificate presented to servers that ask for one
+    pub client_cert: Option<PathBuf>,
+    /// PEM file of the client certificate's private key, if it is not in
+    /// `client_cert`
+    pub client_key: Option<PathBuf>,
+}
+
+impl TlsConfig {
+    /// Reads and checks the files named.
+    pub fn load(&self) -> eyre::Result<Tls> {
+        let read = |path: &PathBuf| std::fs::read(path).wrap_err_with(|| format!("failed to read `{}`", path.display()));
+        let mut tls = Tls::default();
+        if let Some(path) = &self.ca_file {
+            tls.ca_certificates = Some(read(path)?);
+        }
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), key) => {
+                let mut identity = read(cert)?;
+                if let Some(key) = key {
+                    identity.push(b'\n');
+                    identity.extend(read(key)?);
+                }
+                tls.client_identity = Some(identity);
+            }
+            (None, Some(_)) => return Err(eyre!("`client_key` is set in `[tls]` without `client_cert`")),
+            (None, None) => {}
+        }
+        tls.apply(reqwest::Client::builder())?
+            .build()
+            .wrap_err("invalid TLS settings in `[tls]`")?;
+        tls.rustls_config().wrap_err("invalid TLS settings in `[tls]`")?;
+        Ok(tls)
+    }
+}
+
+/// Certificates a client trusts and presents, PEM encoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tls {
+    /// Certificate authorities trusted besides the public ones
+    pub ca_certificates: Option<Vec<u8>>,
+    /// Client certificate followed by its private key
+    pub client_identity: Option<Vec<u8>>,
+}
+
+impl Tls {
+    /// Adds the certificates to a client being built.
+    pub(crate) fn apply(&self, mut builder: reqwest::ClientBuilder) -> eyre::Result<reqwest::ClientBuilder> {
+        if let Some(pem) = &self.ca_certificates {
+            let pem = String::from_utf8_lossy(pem);
+            let certificates: Vec<&str> = pem.split(CERTIFICATE_START).skip(1).collect();
+            if certificates.is_empty() {
+                return Err(eyre!("no PEM certificate found in the CA file"));
+            }
+            for certificate in certificates {
+                let certificate = format!("{}{}", CERTIFICATE_START, certificate);
+                builder = builder.add_root_certificate(reqwest::Certificate::from_pem(certificate.as_bytes()).wrap_err("invalid CA certificate")?);
+            }
+        }
+        if let Some(pem) = &self.client_identity {
+            builder = builder.identity(reqwest::Identity::from_pem(pem).wrap_err("invalid client certificate or key")?);
+        }
+        Ok(builder)
+    }
+    /// Returns the settings of a rustls client, for connections made
+    /// without reqwest, such as to WebSocket feeds.
+    pub(crate) fn rustls_config(&self) -> eyre::Result<Arc<rustls::ClientConfig>> {
+        let mut roots = rustls::RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        if let Some(pem) = &self.ca_certificates {
+            let certificates = CertificateDer::pem_slice_iter(pem).collect::<Result<Vec<_>, _>>().wrap_err("invalid CA certificate")?;
+            if certificates.is_empty() {
+                return Err(eyre!("no PEM certificate found in the CA file"));
+            }
+            for certificate in certificates {
+                roots.add(certificate).wrap_err("invalid CA certificate")?;
+            }
+        }
+        let builder = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .wrap_err("no TLS version is supported")?
+            .with_root_certificates(roots);
+        let config = match &self.client_identity {
+            Some(pem) => {
+                let chain = CertificateDer::pem_slice_iter(pem).collect::<Result<Vec<_>, _>>().wrap_err("invalid client certificate")?;
+                let key = PrivateKeyDer::from_pem_slice(pem).wrap_err("invalid client key")?;
+                builder.with_client_auth_cert(chain, key).wrap_err("invalid client certificate or key")?
+            }
+            None => builder.with_no_client_auth(),
+        };
+        Ok(Arc::new(config))
+    }
+}
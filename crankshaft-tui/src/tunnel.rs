@@ -0,0 +1,225 @@
+//! SSH tunnels to engines that only listen on a remote host's loopback.
+//!
+//! An engine on a cluster's head node usually binds `127.0.0.1`, out of
+//! reach of a monitor running elsewhere. With a `[tunnel]` section in the
+//! configuration file, or `--tunnel [USER@]HOST` on the command line, the
+//! monitor starts `ssh` to forward a local port to the address of each
+//! engine, WebSocket, SSE, or TES URL, as resolved on that host, and
+//! connects through it:
+//!
+//! ```toml
+//! [tunnel]
+//! host = "head.cluster.example.org"
+//! user = "alice"
+//! key = "~/.ssh/id_ed25519"
+//! ```
+//!
+//! so `--engine http://127.0.0.1:7878` reaches the engine on the head node.
+//! The tunnel is opened before the dashboard is shown, so a refused key or
+//! an unreachable host is reported plainly, and `ssh` is stopped when the
+//! monitor exits. `ssh` runs in batch mode, as no password can be typed
+//! once the dashboard is shown; keys must be loaded in an agent or have no
+//! passphrase. Only plain `http://` and `ws://` sources can be tunnelled:
+//! going through the tunnel, an `https://` or `wss://` source would be
+//! reached at `127.0.0.1` and its certificate checked against that instead
+//! of its name, so those are refused.
+
+use std::io::{BufRead, BufReader, Read};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use eyre::{eyre, WrapErr};
+use serde::Deserialize;
+
+use crate::crash;
+
+/// How long to wait for the forwarded port when the configuration does not
+/// say.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often the forwarded port is tried while `ssh` starts.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The SSH host remote sources are reached through.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TunnelConfig {
+    /// Host to connect to, such as a cluster's head node; no tunnel is
+    /// opened without one
+    pub host: Option<String>,
+    /// User to log in as (defaults to `ssh`'s own choice)
+    pub user: Option<String>,
+    /// Private key to log in with (defaults to `ssh`'s own choice)
+    pub key: Option<PathBuf>,
+    /// SSH port of the host (default: 22)
+    pub port: Option<u16>,
+    /// Seconds to wait for the tunnel to open (default: 10)
+    pub timeout_secs: Option<u64>,
+}
+
+impl TunnelConfig {
+    /// Sets the host, and the user if given, from `[USER@]HOST`.
+    pub fn set_destination(&mut self, destination: &str) {
+        match destination.split_once('@') {
+            Some((user, host)) => {
+                self.user = Some(user.to_string());
+                self.host = Some(host.to_string());
+            }
+            None => self.host = Some(destination.to_string()),
+        }
+    }
+
+    /// Returns the arguments `ssh` forwards `local` to `remote` with, as
+    /// resolved on the host.
+    pub fn ssh_args(&self, local: u16, remote: (&str, u16)) -> eyre::Result<Vec<String>> {
+        let host = self.host.as_deref().ok_or_else(|| eyre!("`[tunnel]` has no `host`"))?;
+        let mut args = vec![
+            "-N".to_string(),
+            "-o".to_string(),
+            "BatchMode=yes".to_string(),
+            "-o".to_string(),
+            "ExitOnForwardFailure=yes".to_string(),
+            "-L".to_string(),
+            format!("{}:{}:{}:{}", Ipv4Addr::LOCALHOST, local, bracket(remote.0), remote.1),
+        ];
+        if let Some(key) = &self.key {
+            args.extend(["-i".to_string(), expand_home(key).display().to_string()]);
+        }
+        if let Some(port) = self.port {
+            args.extend(["-p".to_string(), port.to_string()]);
+        }
+        // Ends the options, so a host or user starting with `-` is not
+        // taken for one
+        args.push("--".to_string());
+        args.push(match &self.user {
+            Some(user) => format!("{}@{}", user, host),
+            None => host.to_string(),
+        });
+        Ok(args)
+    }
+
+    /// Opens a tunnel to the address of `url` and returns it with the URL
+    /// rewritten to go through it. URLs served over TLS are refused.
+    pub fn open(&self, url: &str) -> eyre::Result<(Tunnel, String)> {
+        let mut url = reqwest::Url::parse(url).wrap_err_with(|| format!("cannot tunnel to `{}`", url))?;
+        if matches!(url.scheme(), "https" | "wss") {
+            return Err(eyre!(
+                "cannot tunnel to `{}`: its certificate would be checked against the tunnel's local address; \
+                 reach it directly, without `[tunnel]`",
+                url
+            ));
+        }
+        let remote_host = url.host_str().ok_or_else(|| eyre!("cannot tunnel to `{}`: it names no host", url))?.to_string();
+        let remote_port = url.port_or_known_default().ok_or_else(|| eyre!("cannot tunnel to `{}`: it names no port", url))?;
+
+        let local = free_port().wrap_err("cannot pick a local port for the tunnel")?;
+        let args = self.ssh_args(local, (&remote_host, remote_port))?;
+        let timeout = self.timeout_secs.map_or(DEFAULT_TIMEOUT, Duration::from_secs);
+        let tunnel = Tunnel::spawn(&args, local, timeout).wrap_err_with(|| {
+            let host = self.host.as_deref().unwrap_or_default();
+            format!("cannot open an SSH tunnel to {}:{} through {}", remote_host, remote_port, host)
+        })?;
+
+        url.set_host(Some(&Ipv4Addr::LOCALHOST.to_string())).wrap_err("cannot point the URL at the tunnel")?;
+        url.set_port(Some(local)).map_err(|()| eyre!("cannot point `{}` at the tunnel", url))?;
+        Ok((tunnel, url.to_string()))
+    }
+}
+
+/// A running `ssh` forwarding a local port, stopped when dropped.
+#[derive(Debug)]
+pub struct Tunnel {
+    /// The `ssh` process
+    child: Child,
+    /// Local port forwarded to the remote address
+    port: u16,
+}
+
+impl Tunnel {
+    /// Starts `ssh` with `args` and waits until it listens on `port`.
+    fn spawn(args: &[String], port: u16, timeout: Duration) -> eyre::Result<Self> {
+        let mut child = Command::new("ssh")
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .wrap_err("cannot run `ssh`")?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(status) = child.try_wait()? {
+                let mut message = String::new();
+                if let Some(mut stderr) = child.stderr.take() {
+                    let _ = stderr.read_to_string(&mut message);
+                }
+                return Err(match message.trim() {
+                    "" => eyre!("`ssh` exited with {}", status),
+                    message => eyre!("`ssh` exited with {}: {}", status, message),
+                });
+            }
+            if TcpStream::connect((Ipv4Addr::LOCALHOST, port)).is_ok() {
+                break;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(eyre!("the tunnel did not open within {} seconds", timeout.as_secs()));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+
+        // Whatever `ssh` says from now on, such as a dropped connection,
+        // goes to the internal log rather than over the dashboard
+        if let Some(stderr) = child.stderr.take() {
+            std::thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    crash::log(format!("ssh: {}", line));
+                }
+            });
+        }
+        crash::log(format!("SSH tunnel open on port {}", port));
+        Ok(Self { child, port })
+    }
+
+    /// Returns the local port the tunnel listens on.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Returns `true` while `ssh` is running.
+    pub fn is_open(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+}
+
+impl Drop for Tunnel {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Returns a local port nothing listens on.
+fn free_port() -> std::io::Result<u16> {
+    Ok(TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?.local_addr()?.port())
+}
+
+/// Puts an IPv6 address in brackets, as `ssh -L` expects.
+fn bracket(host: &str) -> String {
+    if host.contains(':') && !host.starts_with('[') {
+        format!("[{}]", host)
+    } else {
+        host.to_string()
+    }
+}
+
+/// Expands a leading `~` to the home directory.
+fn expand_home(path: &std::path::Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
@@ -1,5 +1,8 @@
 //! UI rendering for the TUI.
 
+use std::collections::HashMap;
+use std::hash::Hash;
+
 use ratatui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect, Alignment},
@@ -7,46 +10,208 @@ use ratatui::{
     symbols,
     text::{Span, Line, Text},
     widgets::{
-        Block, Borders, BorderType, Cell, Gauge, List, ListItem, Paragraph, Row, Table, Tabs,
-        Wrap, Padding, canvas::Canvas,
+        Axis, Block, Borders, BorderType, Cell, Chart, Clear, Dataset, Gauge, GraphType, List, ListItem,
+        Paragraph, Row, Table, Tabs, Wrap, Padding, canvas::Canvas,
     },
     Frame,
 };
 
-use crate::app::{App, TaskStatus};
+use crate::app::{App, MemoryUsage, QueuePosition, Tab, TaskStatus};
+use crate::alerts::Severity;
+use crate::audit::Outcome;
+use crate::capacity::Demand;
+use crate::chart::{ChartSeries, Scale};
+use crate::overlay::Overlay;
+use crate::panes::LogPane;
+use crate::phases::Phase;
+use crate::regions::{stamp, stamp_of, Slot};
+use crate::slo::ErrorBudget;
+use crate::confirm::Confirmable;
+use crate::sort::SortKey;
+use crate::timeline::LaneKey;
+use crate::diagnostics::{ConnectionState, HealthState};
+use crate::feed::EventKind;
+use crate::format::NumberFormat;
+use crate::groups::TaskGroup;
 
 /// Renders the user interface widgets.
 pub fn draw(f: &mut Frame, app: &App) {
-    // Create a layered layout
-    let main_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints(
-            [
-                Constraint::Length(3),
-                Constraint::Min(0),
-                Constraint::Length(3),
-            ]
-            .as_ref(),
-        )
-        .split(f.size());
+    if app.blanked {
+        draw_lock_screen(f);
+        return;
+    }
+    app.regions.start_frame();
+    // Create a layered layout, kept until the terminal is resized or a row
+    // is shown or hidden
+    let drawer_height = if app.show_workflow { 4 } else { 0 };
+    let phases = app
+        .regions
+        .phases(app.store_version(), || crate::phases::breakdown(app.task_ids.iter().filter_map(|id| app.tasks.get(id))));
+    let phases_height = if phases.is_empty() { 0 } else { 2 };
+    let size = f.size();
+    let main_layout = app.regions.layout(stamp_of((size, phases_height, drawer_height)), || {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints(
+                [
+                    Constraint::Length(3),
+                    Constraint::Length(phases_height),
+                    Constraint::Length(drawer_height),
+                    Constraint::Min(0),
+                    Constraint::Length(3),
+                ]
+                .as_ref(),
+            )
+            .split(size)
+    });
 
-    draw_tabs(f, app, main_layout[0]);
+    // The header rows change with what they show rather than with time, so
+    // they are only drawn again once that changes
+    let tabs = stamp(|h| {
+        (app.tab_index, app.is_replaying(), app.dry_run, app.is_viewing_history(), app.screen_reader).hash(h);
+        for connection in app.connections.iter() {
+            (&connection.source, connection.state, connection.last_success).hash(h);
+        }
+    });
+    app.regions.draw(f, Slot::Tabs, main_layout[0], tabs, |f| draw_tabs(f, app, main_layout[0]));
+    if !phases.is_empty() {
+        let shown = stamp_of((app.store_version(), app.screen_reader));
+        app.regions.draw(f, Slot::Phases, main_layout[1], shown, |f| draw_phases(f, app, &phases, main_layout[1]));
+    }
+    if app.show_workflow {
+        let shown = stamp_of((app.workflow.as_ref().map(|workflow| workflow.fields()), app.is_viewing_history(), app.screen_reader));
+        app.regions.draw(f, Slot::Workflow, main_layout[2], shown, |f| draw_workflow_drawer(f, app, main_layout[2]));
+    }
     
-    match app.tab_index {
-        0 => draw_tasks_tab(f, app, main_layout[1]),
-        1 => draw_stats_tab(f, app, main_layout[1]),
-        2 => draw_help_tab(f, app, main_layout[1]),
-        _ => {}
+    // Task rows on screen, for finding the one under the mouse pointer
+    let mut task_rows = Vec::new();
+    match app.current_tab() {
+        Tab::Tasks if app.screen_reader => draw_linear_tasks(f, app, main_layout[3]),
+        Tab::Tasks => task_rows = draw_tasks_tab(f, app, main_layout[3]),
+        Tab::Logs => draw_logs_tab(f, app, main_layout[3]),
+        Tab::Statistics => draw_stats_tab(f, app, main_layout[3]),
+        Tab::Timeline => draw_timeline_tab(f, app, main_layout[3]),
+        Tab::Events => draw_events_tab(f, app, main_layout[3]),
+        Tab::Help => draw_help_tab(f, app, main_layout[3]),
     }
     
-    draw_footer(f, main_layout[2]);
+    draw_footer(f, app, main_layout[4]);
+
+    // Tooltips sit under every overlay, so they are hidden by those dimming
+    // the dashboard
+    if let Some((column, row)) = app.hover.filter(|_| !app.overlays.dims()) {
+        let hovered = task_rows
+            .iter()
+            .find(|(area, _)| area.x <= column && column < area.right() && area.y <= row && row < area.bottom());
+        if let Some(task) = hovered.and_then(|(_, id)| app.tasks.get(*id)) {
+            draw_task_tooltip(f, app, task, (column, row));
+        }
+    }
+
+    for overlay in app.overlays.iter() {
+        if overlay.dims() {
+            dim(f);
+        }
+        match overlay {
+            Overlay::Performance => draw_debug_overlay(f, app),
+            Overlay::Diagnostics => draw_diagnostics_overlay(f, app),
+            Overlay::Audit => draw_audit_overlay(f, app),
+            Overlay::About => draw_about_overlay(f, app),
+            Overlay::TaskDiff => draw_task_diff_overlay(f, app),
+            Overlay::SortMenu => {
+                if let Some(row) = app.sort_menu {
+                    draw_sort_menu(f, app, row);
+                }
+            }
+            Overlay::Glossary => {
+                if let Some(index) = app.glossary {
+                    draw_glossary(f, app, index);
+                }
+            }
+            Overlay::Confirmation => {
+                if let Some(action) = &app.confirmation {
+                    draw_confirmation(f, app, action);
+                }
+            }
+        }
+    }
+}
+
+/// Dims everything drawn so far, leaving the overlay drawn next to stand
+/// out.
+fn dim(f: &mut Frame) {
+    let area = f.size();
+    f.buffer_mut().set_style(area, Style::default().add_modifier(Modifier::DIM));
+}
+
+/// Draws only a lock line, hiding every task name and value on screen.
+fn draw_lock_screen(f: &mut Frame) {
+    let area = centered_rect(f.size().width, 1, f.size());
+    let text = Paragraph::new(Line::styled(
+        "Crankshaft Monitor is blanked after inactivity. Press any key to show it.",
+        Style::default().fg(Color::DarkGray),
+    ))
+    .alignment(Alignment::Center);
+    f.render_widget(text, area);
+}
+
+/// Returns the block used around every pane.
+///
+/// In screen-reader mode the decorative border is dropped so only the title
+/// text remains.
+fn panel<'a>(app: &App, title: impl Into<std::borrow::Cow<'a, str>>) -> Block<'a> {
+    let color = if app.is_viewing_history() { Color::Yellow } else { Color::Cyan };
+    titled_block(app, title, color)
+}
+
+/// Returns the block used around floating overlays.
+fn overlay_panel<'a>(app: &App, title: impl Into<std::borrow::Cow<'a, str>>) -> Block<'a> {
+    titled_block(app, title, Color::Magenta)
+}
+
+fn titled_block<'a>(app: &App, title: impl Into<std::borrow::Cow<'a, str>>, color: Color) -> Block<'a> {
+    let block = if app.screen_reader {
+        Block::default()
+    } else {
+        Block::default().borders(Borders::ALL).border_type(BorderType::Rounded)
+    };
+
+    let title = title.into();
+    if title.is_empty() {
+        return block;
+    }
+    block.title(Span::styled(title, Style::default().fg(color).add_modifier(Modifier::BOLD)))
+}
+
+/// Returns a rectangle of at most the given size centered in `area`.
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// Shortens text to at most `width` characters, marking the cut with an
+/// ellipsis.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut shortened: String = text.chars().take(width.saturating_sub(1)).collect();
+    shortened.push('…');
+    shortened
 }
 
 fn draw_tabs(f: &mut Frame, app: &App, area: Rect) {
-    let titles = ["Tasks", "Statistics", "Help"]
+    let titles = Tab::ALL
         .iter()
-        .map(|t| {
+        .map(|tab| {
+            let t = tab.title();
             let (first, rest) = t.split_at(1);
             Line::from(vec![
                 Span::styled(first, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
@@ -55,14 +220,25 @@ fn draw_tabs(f: &mut Frame, app: &App, area: Rect) {
         })
         .collect();
 
+    let block = panel(app, match (app.is_replaying(), app.dry_run) {
+        (true, true) => " Crankshaft Monitor (replay) [DRY RUN] ",
+        (true, false) => " Crankshaft Monitor (replay) ",
+        (false, true) => " Crankshaft Monitor [DRY RUN] ",
+        (false, false) => " Crankshaft Monitor ",
+    })
+    .title_alignment(Alignment::Center);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // The connections take the right of the row, as far as the tabs allow
+    let connections = if app.is_replaying() { Line::default() } else { connection_line(app) };
+    let width = (connections.width() as u16).min(inner.width / 2);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(width)].as_ref())
+        .split(inner);
+
     let tabs = Tabs::new(titles)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .title(Span::styled(" Crankshaft Monitor ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)))
-                .title_alignment(Alignment::Center)
-        )
         .highlight_style(
             Style::default()
                 .fg(Color::Yellow)
@@ -72,46 +248,338 @@ fn draw_tabs(f: &mut Frame, app: &App, area: Rect) {
         .select(app.tab_index)
         .divider(Span::styled("|", Style::default().fg(Color::DarkGray)));
     
-    f.render_widget(tabs, area);
+    f.render_widget(tabs, chunks[0]);
+    f.render_widget(Paragraph::new(connections).alignment(Alignment::Right), chunks[1]);
+}
+
+/// Returns the state of each source's connection for the header, with the
+/// time it last delivered anything.
+fn connection_line(app: &App) -> Line<'static> {
+    let mut spans = Vec::new();
+    for (index, connection) in app.connections.iter().enumerate() {
+        let (symbol, color) = match connection.state {
+            ConnectionState::Waiting => ("…", Color::Blue),
+            ConnectionState::Connected => ("●", Color::Green),
+            ConnectionState::Reconnecting => ("⟳", Color::Yellow),
+            ConnectionState::Offline => ("○", Color::Red),
+        };
+        if index > 0 {
+            spans.push(Span::raw(if app.screen_reader { "; " } else { "  " }));
+        }
+        let mut text = format!("{} {}", connection.source, connection.state.to_string().to_lowercase());
+        if !app.screen_reader {
+            text = format!("{} {}", symbol, text);
+        }
+        if let Some(last) = connection.last_success {
+            text.push_str(&format!(" {}", crate::format::clock(last)));
+        }
+        spans.push(Span::styled(text, Style::default().fg(color)));
+    }
+    Line::from(spans)
+}
+
+/// Draws a stacked bar with one segment per phase, sized by its number of
+/// tasks and filled as they complete, above a line of completion per phase.
+fn draw_phases(f: &mut Frame, app: &App, phases: &[Phase], area: Rect) {
+    let area = Rect { x: area.x + 1, width: area.width.saturating_sub(2), ..area };
+    let color = |index: usize| LANE_COLORS[index % LANE_COLORS.len()];
+    let mut labels = Vec::new();
+    for (index, phase) in phases.iter().enumerate() {
+        let separator = if index == 0 { "" } else if app.screen_reader { ", " } else { "  " };
+        labels.push(Span::raw(separator));
+        labels.push(Span::styled(
+            format!("{} {}", phase.name, app.numbers.percent(phase.completion())),
+            Style::default().fg(color(index)),
+        ));
+    }
+    if app.screen_reader {
+        labels.insert(0, Span::raw("Phases: "));
+        f.render_widget(Paragraph::new(Line::from(labels)), area);
+        return;
+    }
+
+    // Segment boundaries are rounded from running totals, so the widths add
+    // up to the full bar
+    let total: usize = phases.iter().map(|phase| phase.total).sum();
+    let width = area.width as usize;
+    let mut bar = Vec::new();
+    let mut seen = 0;
+    let mut start = 0;
+    for (index, phase) in phases.iter().enumerate() {
+        seen += phase.total;
+        let end = (seen * width + total / 2) / total.max(1);
+        let segment = end - start;
+        start = end;
+        let completed = (phase.completion() * segment as f64).round() as usize;
+        let failed = ((phase.failed as f64 / phase.total.max(1) as f64) * segment as f64).round() as usize;
+        let failed = failed.min(segment - completed);
+        bar.push(Span::styled("█".repeat(completed), Style::default().fg(color(index))));
+        bar.push(Span::styled("█".repeat(failed), Style::default().fg(app.theme.status_color(TaskStatus::Failed))));
+        bar.push(Span::styled("░".repeat(segment - completed - failed), Style::default().fg(color(index))));
+    }
+    f.render_widget(Paragraph::new(vec![Line::from(bar), Line::from(labels)]), area);
+}
+
+fn draw_workflow_drawer(f: &mut Frame, app: &App, area: Rect) {
+    let block = panel(app, " Workflow ")
+        .padding(Padding::new(1, 1, 0, 0));
+
+    let Some(workflow) = &app.workflow else {
+        let empty = Paragraph::new(Text::styled(
+            "No workflow metadata reported by the data source",
+            Style::default().fg(Color::DarkGray)
+        ))
+        .block(block);
+        f.render_widget(empty, area);
+        return;
+    };
+
+    let fields = workflow.fields();
+    let lines: Vec<Line<'_>> = fields
+        .chunks(3)
+        .map(|row| {
+            let mut spans = Vec::new();
+            for (label, value) in row {
+                spans.push(Span::styled(format!("{}: ", label), Style::default().fg(Color::Gray)));
+                spans.push(Span::styled(format!("{:<28}", value), Style::default().fg(Color::White).add_modifier(Modifier::BOLD)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Renders the task list as plain sentences, one task per line, for
+/// screen readers.
+fn draw_linear_tasks(f: &mut Frame, app: &App, area: Rect) {
+    let mut text = vec![Line::from(crate::accessibility::status_summary(app)), Line::from("")];
+
+    let visible = app.visible_task_ids();
+    let selected = app
+        .selected_task_id
+        .as_ref()
+        .and_then(|id| visible.iter().position(|v| *v == id))
+        .unwrap_or(0);
+    // Keep the selected task in view without drawing a scrollbar
+    let skip = selected.saturating_sub(area.height.saturating_sub(4) as usize / 2);
+
+    let groups = group_heads(app);
+    for id in visible.iter().skip(skip).take(area.height as usize) {
+        let task = &app.tasks[*id];
+        let marker = if app.selected_task_id.as_ref() == Some(*id) { "Selected: " } else { "" };
+        if let Some(group) = groups.iter().find(|group| group.ids.first() == Some(id)) {
+            let state = if group.collapsed { "collapsed" } else { "expanded" };
+            text.push(Line::from(format!(
+                "{}Group {}, {}, {}.",
+                if group.collapsed { marker } else { "" },
+                group.name.unwrap_or_default(),
+                group_summary(app, group),
+                state
+            )));
+            if group.collapsed {
+                continue;
+            }
+        }
+        let pinned = if app.pinned.contains(*id) { "pinned, " } else { "" };
+        let queue = task
+            .queue
+            .map_or_else(String::new, |queue| format!(" {}.", queue_label(app, queue, crate::record::unix_now())));
+        let alerts: String = app
+            .alerts
+            .for_task(id)
+            .map(|alert| format!(" {} alert: {}.", alert.rule.severity, alert.rule.name))
+            .collect();
+        let slow = app
+            .anomalies
+            .get(id)
+            .map_or_else(String::new, |anomaly| format!(" {}.", anomaly_label(app, anomaly)));
+        let memory = if app.capabilities.memory {
+            format!(", memory {}", memory_label(&app.numbers, task.memory_usage))
+        } else {
+            String::new()
+        };
+        let attempt = match task.attempt {
+            Some(attempt) if task.is_requeued() => format!(" attempt {},", attempt),
+            _ => String::new(),
+        };
+        text.push(Line::from(format!(
+            "{}{}{}, {}, {},{} {} percent{}.{}{}{}",
+            marker,
+            pinned,
+            task.id,
+            app.display_name(task),
+            task.status,
+            attempt,
+            app.numbers.decimal(app.display_progress(task) * 100.0, 0),
+            memory,
+            queue,
+            alerts,
+            slow
+        )));
+    }
+
+    f.render_widget(Paragraph::new(text).block(panel(app, "Tasks")), area);
+}
+
+/// Returns the named groups of the task list, if it is grouped. Each is
+/// headed by its first task's row.
+fn group_heads(app: &App) -> Vec<TaskGroup<'_>> {
+    if !app.grouping.enabled {
+        return Vec::new();
+    }
+    app.task_groups().into_iter().filter(|group| group.name.is_some()).collect()
+}
+
+/// Summarises the tasks of a group, e.g. `12 tasks, 3 running, 1 failed`.
+fn group_summary(app: &App, group: &TaskGroup) -> String {
+    let count = |status: TaskStatus| group.ids.iter().filter(|id| app.tasks[**id].status == status).count();
+    let mut summary = format!("{} tasks", app.numbers.integer(group.ids.len() as u64));
+    for status in [TaskStatus::Running, TaskStatus::Completed, TaskStatus::Failed] {
+        let n = count(status);
+        if n > 0 {
+            summary.push_str(&format!(", {} {}", app.numbers.integer(n as u64), status.to_string().to_lowercase()));
+        }
+    }
+    summary
+}
+
+/// Renders the header row of a group, which stands in for the whole group
+/// while it is collapsed.
+fn group_header<'a>(app: &App, group: &TaskGroup) -> Line<'a> {
+    let marker = if group.collapsed { "▸" } else { "▾" };
+    let progress = group.ids.iter().map(|id| app.display_progress(&app.tasks[*id])).sum::<f64>() / group.ids.len().max(1) as f64;
+    Line::from(vec![
+        Span::styled(
+            format!("{} {} ", marker, group.name.unwrap_or_default()),
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!("({}, {} done)", group_summary(app, group), app.numbers.percent(progress)),
+            Style::default().fg(Color::Gray),
+        ),
+    ])
 }
 
-fn draw_tasks_tab(f: &mut Frame, app: &App, area: Rect) {
+/// Widest a label column of the task list grows, such as a Slurm node list.
+const MAX_LABEL_COLUMN_WIDTH: usize = 16;
+
+/// Draws a task list tab, returning the screen area of each visible row.
+fn draw_tasks_tab<'a>(f: &mut Frame, app: &'a App, area: Rect) -> Vec<(Rect, &'a String)> {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(65), Constraint::Percentage(35)].as_ref())
         .split(area);
     
     // Task list
-    let tasks: Vec<ListItem<'_>> = app.task_ids
+    let visible = app.visible_task_ids();
+    let groups = group_heads(app);
+    let heads = groups.iter().filter_map(|group| Some((*group.ids.first()?, group))).collect::<HashMap<_, _>>();
+    let now = crate::record::unix_now();
+    let sparklines = app.sparklines.is_enabled() && app.capabilities.sparkline(app.sparklines.source());
+    // Label columns are as wide as their widest visible value, within reason
+    let label_widths: Vec<(&String, usize)> = app
+        .label_columns
+        .iter()
+        .map(|label| {
+            let widest = visible.iter().filter_map(|id| app.tasks.get(*id)?.labels.get(label)).map(|value| value.chars().count()).max();
+            (label, widest.unwrap_or(0).min(MAX_LABEL_COLUMN_WIDTH))
+        })
+        .filter(|(_, width)| *width > 0)
+        .collect();
+    let tasks: Vec<ListItem<'_>> = visible
         .iter()
         .map(|id| {
-            let task = &app.tasks[id];
-            let (status_color, status_icon) = match task.status {
-                TaskStatus::Pending => (Color::Blue, "⏳"),
-                TaskStatus::Running => (Color::Yellow, "▶️"),
-                TaskStatus::Completed => (Color::Green, "✅"),
-                TaskStatus::Failed => (Color::Red, "❌"),
-            };
+            let head = heads.get(*id);
+            if let Some(group) = head.filter(|group| group.collapsed) {
+                return ListItem::new(group_header(app, group));
+            }
+            let task = &app.tasks[*id];
+            let status_color = app.theme.status_color(task.status);
+            let status_icon = app.theme.task_symbol(task);
+            let stale = app.is_stale(task, now);
             
-            let content = Line::from(vec![
+            // The name being typed replaces the selected row's name
+            let renaming = app.rename.as_ref().filter(|_| app.selected_task_id.as_ref() == Some(*id));
+            let (name, name_style) = match renaming {
+                Some(text) => (format!("{}▏", text), Style::default().fg(Color::Black).bg(Color::Yellow)),
+                None => (app.display_name(task).to_string(), Style::default()),
+            };
+            // Pad names so that charts line up at the end of the rows
+            let name = if sparklines { format!("{:<32} ", name) } else { name };
+            let mut content = Line::from(vec![
                 Span::styled(format!(" {} ", status_icon), Style::default()),
                 Span::styled(format!("{:<8}", task.id), Style::default().fg(Color::White)),
-                Span::styled(format!("{:<12}", task.status), Style::default().fg(status_color)),
-                Span::styled(task.name.clone(), Style::default()),
+                Span::styled(format!("{:<12}", format!("{}{}", task.status, attempt_superscript(task))), Style::default().fg(status_color)),
+                Span::styled(format!("{:>7} ", app.numbers.percent(app.display_progress(task))), Style::default().fg(Color::Gray)),
+                Span::styled(format!("{:>11} ", task_duration(app, task, now)), Style::default().fg(Color::Gray)),
+                Span::styled(
+                    format!("{:>6} ", task.queue.map_or_else(String::new, |queue| format!("#{}", app.numbers.count(queue.position)))),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]);
+            for (label, width) in &label_widths {
+                let value = task.labels.get(*label).map_or("", String::as_str);
+                let cell = format!("{:<width$} ", truncate(value, *width), width = *width);
+                content.spans.push(Span::styled(cell, Style::default().fg(Color::Gray)));
+            }
+            content.spans.extend([
+                Span::styled(if app.pinned.contains(*id) { "* " } else { "" }, Style::default().fg(Color::Yellow)),
+                match app.alerts.severity(id) {
+                    Some(severity) => Span::styled("! ", Style::default().fg(severity_color(severity)).add_modifier(Modifier::BOLD)),
+                    None => Span::raw(""),
+                },
+                Span::styled(if app.anomalies.get(id).is_some() { "slow " } else { "" }, Style::default().fg(Color::LightRed)),
+                Span::styled(if stale { "stale " } else { "" }, Style::default().fg(Color::DarkGray)),
+                Span::styled(
+                    if app.reference_task.as_ref() == Some(*id) { "ref " } else { "" },
+                    Style::default().fg(Color::LightBlue),
+                ),
+                Span::styled(name, name_style),
             ]);
+            if sparklines {
+                content.spans.push(Span::styled(app.sparklines.render(id), Style::default().fg(Color::Cyan)));
+            }
+            // Figures no longer kept current are dimmed
+            if stale {
+                for span in &mut content.spans {
+                    span.style = span.style.add_modifier(Modifier::DIM);
+                }
+            }
             
-            ListItem::new(content)
+            match head {
+                Some(group) => ListItem::new(vec![group_header(app, group), content]),
+                None => ListItem::new(content),
+            }
         })
         .collect();
     
+    let mut title = match &app.filter {
+        Some(filter) => format!(
+            " Tasks (filter: {}, {} of {}) ",
+            filter,
+            app.numbers.integer(visible.len() as u64),
+            app.numbers.integer(app.task_ids.len() as u64)
+        ),
+        None => format!(" Tasks ({}) ", app.numbers.integer(app.task_ids.len() as u64)),
+    };
+    if let Some(backend) = &app.backend_filter {
+        title.push_str(&format!("[backend: {}, {} shown] ", backend, app.numbers.integer(visible.len() as u64)));
+    }
+    let pruned = app.retention.pruned().total();
+    if pruned > 0 {
+        title.push_str(&format!("[{} pruned] ", app.numbers.integer(pruned as u64)));
+    }
+    if let Some(metric) = app.top {
+        title.push_str(&format!("[top: {} ↓] ", metric.name()));
+    } else if !app.sort.is_empty() {
+        title.push_str(&format!("[sort: {}] ", app.sort));
+    }
+    let heights: Vec<usize> = tasks.iter().map(ListItem::height).collect();
+    let block = panel(app, title).padding(Padding::new(1, 1, 0, 0));
+    let inner = block.inner(chunks[0]);
     let tasks_list = List::new(tasks)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .title(Span::styled(" Tasks ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)))
-                .padding(Padding::new(1, 1, 0, 0))
-        )
+        .block(block)
         .highlight_style(
             Style::default()
                 .add_modifier(Modifier::BOLD)
@@ -121,17 +589,35 @@ fn draw_tasks_tab(f: &mut Frame, app: &App, area: Rect) {
     
     let mut state = ratatui::widgets::ListState::default();
     if let Some(selected_id) = &app.selected_task_id {
-        if let Some(index) = app.task_ids.iter().position(|id| id == selected_id) {
+        if let Some(index) = visible.iter().position(|id| *id == selected_id) {
             state.select(Some(index));
         }
     }
     
     f.render_stateful_widget(tasks_list, chunks[0], &mut state);
+
+    // Rows from the scroll offset the list settled on down to the bottom
+    let mut rows = Vec::new();
+    let mut y = inner.y;
+    for (id, height) in visible.iter().zip(&heights).skip(state.offset()) {
+        let height = *height as u16;
+        if y + height > inner.bottom() {
+            break;
+        }
+        rows.push((Rect::new(inner.x, y, inner.width, height), *id));
+        y += height;
+    }
     
     // Task details
     if let Some(selected_id) = &app.selected_task_id {
         if let Some(task) = app.tasks.get(selected_id) {
-            draw_task_details(f, task, chunks[1]);
+            if app.show_raw_json {
+                draw_task_json(f, app, task, chunks[1]);
+            } else if app.show_chart {
+                draw_task_chart(f, app, task, chunks[1]);
+            } else {
+                draw_task_details(f, app, task, chunks[1]);
+            }
         }
     } else {
         let no_selection = Paragraph::new(Text::styled(
@@ -139,37 +625,61 @@ fn draw_tasks_tab(f: &mut Frame, app: &App, area: Rect) {
             Style::default().fg(Color::DarkGray)
         ))
         .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .title(Span::styled(" Task Details ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)))
+            panel(app, " Task Details ")
         )
         .alignment(Alignment::Center);
         f.render_widget(no_selection, chunks[1]);
     }
+    rows
+}
+
+/// Draws a small box with a task's vital statistics next to the mouse
+/// pointer, flipped to stay on screen.
+fn draw_task_tooltip(f: &mut Frame, app: &App, task: &crate::app::Task, (column, row): (u16, u16)) {
+    let label = Style::default().fg(Color::Gray);
+    let line = |name: &'static str, value: String, style: Style| {
+        Line::from(vec![Span::styled(format!("{:<8}", name), label), Span::styled(value, style)])
+    };
+    let mut text = vec![
+        line("Status", task.status.to_string(), Style::default().fg(app.theme.status_color(task.status))),
+        line("Elapsed", task_duration(app, task, crate::record::unix_now()), Style::default()),
+    ];
+    if app.capabilities.cpu {
+        text.push(line("CPU", app.numbers.percent(task.cpu_usage), Style::default()));
+    }
+    if app.capabilities.memory {
+        text.push(line("Memory", memory_label(&app.numbers, task.memory_usage), Style::default()));
+    }
+
+    let screen = f.size();
+    let width = (text.iter().map(Line::width).max().unwrap_or(0) as u16 + 4)
+        .max(task.id.chars().count() as u16 + 6)
+        .min(screen.width);
+    let height = (text.len() as u16 + 2).min(screen.height);
+    let x = if column + 2 + width <= screen.right() { column + 2 } else { column.saturating_sub(width + 1) };
+    let y = if row + 1 + height <= screen.bottom() { row + 1 } else { row.saturating_sub(height) };
+    let area = Rect::new(x.max(screen.x), y.max(screen.y), width, height);
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(text).block(overlay_panel(app, format!(" {} ", task.id)).padding(Padding::new(1, 1, 0, 0))),
+        area,
+    );
 }
 
-fn draw_task_details(f: &mut Frame, task: &crate::app::Task, area: Rect) {
+fn draw_task_details(f: &mut Frame, app: &App, task: &crate::app::Task, area: Rect) {
+    // Gauges of metrics the source does not report are left out entirely
+    let gauges = 1 + usize::from(app.capabilities.cpu) + usize::from(app.capabilities.memory);
+    let mut constraints = vec![Constraint::Length(1); 3];
+    constraints.resize(3 + gauges, Constraint::Length(3));
+    constraints.push(Constraint::Min(0));
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
-        .constraints(
-            [
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(3),
-                Constraint::Length(3),
-                Constraint::Min(0),
-            ]
-            .as_ref(),
-        )
+        .constraints(constraints)
         .split(area);
     
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .title(Span::styled(" Task Details ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)));
+    let block = panel(app, " Task Details ");
     f.render_widget(block, area);
     
     // Task ID
@@ -182,53 +692,663 @@ fn draw_task_details(f: &mut Frame, task: &crate::app::Task, area: Rect) {
     // Task Name
     let name_text = Paragraph::new(Line::from(vec![
         Span::styled("Name: ", Style::default().fg(Color::Gray)),
-        Span::styled(&task.name, Style::default().fg(Color::White)),
+        Span::styled(app.display_name(task), Style::default().fg(Color::White)),
+        Span::styled(
+            if app.display_names.contains_key(&task.id) { format!("  (backend: {})", task.name) } else { String::new() },
+            Style::default().fg(Color::DarkGray),
+        ),
     ]));
     f.render_widget(name_text, chunks[1]);
     
     // Task Status
-    let (status_color, status_icon) = match task.status {
-        TaskStatus::Pending => (Color::Blue, "⏳"),
-        TaskStatus::Running => (Color::Yellow, "▶️"),
-        TaskStatus::Completed => (Color::Green, "✅"),
-        TaskStatus::Failed => (Color::Red, "❌"),
-    };
+    let status_color = app.theme.status_color(task.status);
+    let status_icon = app.theme.task_symbol(task);
+    let now = crate::record::unix_now();
     
     let status_text = Paragraph::new(Line::from(vec![
         Span::styled("Status: ", Style::default().fg(Color::Gray)),
         Span::styled(format!("{} {}", status_icon, task.status), Style::default().fg(status_color).add_modifier(Modifier::BOLD)),
+        Span::styled(
+            match task.attempt {
+                Some(attempt) if task.is_requeued() => format!("  (attempt {})", attempt),
+                _ => String::new(),
+            },
+            Style::default().fg(Color::Yellow),
+        ),
+        Span::styled(
+            match task.started_at {
+                Some(_) => format!("  ({})", task_duration(app, task, now)),
+                None => String::new(),
+            },
+            Style::default().fg(Color::Gray),
+        ),
+        Span::styled(
+            match task.last_updated {
+                Some(at) if app.is_stale(task, now) => {
+                    format!("  stale: no update for {}", app.numbers.duration(std::time::Duration::from_secs(now.saturating_sub(at))))
+                }
+                _ => String::new(),
+            },
+            Style::default().fg(Color::DarkGray),
+        ),
     ]));
     f.render_widget(status_text, chunks[2]);
     
     // Progress bar
-    let progress_label = format!(" {:.1}% ", task.progress * 100.0);
+    let progress = app.display_progress(task);
+    let progress_label = format!(" {} ", app.numbers.percent(progress));
     let gauge = Gauge::default()
         .block(Block::default().title("Progress"))
         .gauge_style(Style::default().fg(Color::Yellow).bg(Color::Black))
-        .ratio(task.progress)
+        .ratio(progress.clamp(0.0, 1.0))
         .label(progress_label)
         .use_unicode(true);
     f.render_widget(gauge, chunks[3]);
     
     // CPU Usage
-    let cpu_label = format!(" {:.1}% ", task.cpu_usage * 100.0);
-    let cpu_gauge = Gauge::default()
-        .block(Block::default().title("CPU Usage"))
-        .gauge_style(Style::default().fg(Color::Cyan).bg(Color::Black))
-        .ratio(task.cpu_usage)
-        .label(cpu_label)
-        .use_unicode(true);
-    f.render_widget(cpu_gauge, chunks[4]);
+    let mut row = 4;
+    if app.capabilities.cpu {
+        let cpu_label = format!(" {} ", app.numbers.percent(task.cpu_usage));
+        // Sampled containers show how many CPUs that is, live
+        let cpu_title = match task.container {
+            Some(stats) => format!(
+                "CPU Usage ({} of {} CPUs)",
+                app.numbers.decimal(stats.cpus, 2),
+                app.numbers.decimal(stats.cpu_limit, 1)
+            ),
+            None => "CPU Usage".to_string(),
+        };
+        let cpu_gauge = Gauge::default()
+            .block(Block::default().title(cpu_title))
+            .gauge_style(Style::default().fg(Color::Cyan).bg(Color::Black))
+            .ratio(task.cpu_usage)
+            .label(cpu_label)
+            .use_unicode(true);
+        f.render_widget(cpu_gauge, chunks[row]);
+        row += 1;
+    }
+    
+    // Memory Usage
+    if app.capabilities.memory {
+        let memory = task.memory_usage;
+        let memory_title = match memory.requested {
+            Some(requested) if memory.limit.is_some() => format!("Memory (requested {})", app.numbers.bytes(requested)),
+            _ => "Memory".to_string(),
+        };
+        let memory_gauge = Gauge::default()
+            .block(Block::default().title(memory_title))
+            .gauge_style(Style::default().fg(Color::Magenta).bg(Color::Black))
+            .ratio(memory.ratio().clamp(0.0, 1.0))
+            .label(format!(" {} ", memory_label(&app.numbers, memory)))
+            .use_unicode(true);
+        f.render_widget(memory_gauge, chunks[row]);
+        row += 1;
+    }
     
-    // Additional info could be added here
-    if chunks.len() > 5 && task.status == TaskStatus::Running {
-        let info_text = Paragraph::new(Text::styled(
-            "Task is currently running...",
-            Style::default().fg(Color::Yellow)
+    // Queue position and alerts go above the running indicator
+    let mut info: Vec<Line> = task
+        .queue
+        .map(|queue| Line::styled(queue_label(app, queue, crate::record::unix_now()), Style::default().fg(Color::Gray)))
+        .into_iter()
+        .collect();
+    info.extend(app
+        .alerts
+        .for_task(&task.id)
+        .map(|alert| {
+            Line::from(vec![
+                Span::styled(
+                    format!("! {}: ", alert.rule.name),
+                    Style::default().fg(severity_color(alert.rule.severity)).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(alert.describe(&app.numbers), Style::default().fg(severity_color(alert.rule.severity))),
+            ])
+        }));
+    if let Some(anomaly) = app.anomalies.get(&task.id) {
+        info.push(Line::styled(anomaly_label(app, anomaly), Style::default().fg(Color::LightRed)));
+    }
+    if let Some(stats) = task.container {
+        info.push(container_line(app, stats));
+    }
+    info.extend(task.executors.iter().enumerate().flat_map(|(index, executor)| executor_lines(index, executor)));
+    info.extend(task.pods.iter().map(|pod| pod_line(app, pod)));
+    info.extend(log_stream_line(task));
+    if task.status == TaskStatus::Running {
+        info.push(Line::styled(
+            format!("{} Task is currently running...", app.spinner()),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    if chunks.len() > row && !info.is_empty() {
+        let info_text = Paragraph::new(info).alignment(Alignment::Center).wrap(Wrap { trim: true });
+        f.render_widget(info_text, chunks[row]);
+    }
+}
+
+/// Returns a container's network and block device traffic and its process
+/// count.
+fn container_line(app: &App, stats: crate::app::ContainerStats) -> Line<'static> {
+    let label = Style::default().fg(Color::Gray);
+    let value = Style::default().fg(Color::White);
+    Line::from(vec![
+        Span::styled("Net ", label),
+        Span::styled(format!("↓{} ↑{}", app.numbers.bytes(stats.network_rx), app.numbers.bytes(stats.network_tx)), value),
+        Span::styled("  Disk ", label),
+        Span::styled(format!("r {} w {}", app.numbers.bytes(stats.block_read), app.numbers.bytes(stats.block_write)), value),
+        Span::styled("  PIDs ", label),
+        Span::styled(app.numbers.count(stats.pids), value),
+    ])
+}
+
+/// Returns the CloudWatch log stream a task writes to and its group, for
+/// sources that report one.
+fn log_stream_line(task: &crate::app::Task) -> Option<Line<'static>> {
+    let stream = task.labels.get("log_stream")?;
+    let mut spans = vec![
+        Span::styled("Log stream ", Style::default().fg(Color::Gray)),
+        Span::styled(stream.clone(), Style::default().fg(Color::White)),
+    ];
+    if let Some(group) = task.labels.get("log_group") {
+        spans.push(Span::styled(format!(" in {}", group), Style::default().fg(Color::Gray)));
+    }
+    Some(Line::from(spans))
+}
+
+/// Returns a pod's name, phase, and node, with how often its containers
+/// restarted and why it is waiting or stopped.
+fn pod_line(app: &App, pod: &crate::app::PodInfo) -> Line<'static> {
+    let phase_color = match pod.phase.as_str() {
+        "Succeeded" => Color::Green,
+        "Failed" => Color::Red,
+        "Pending" => Color::Blue,
+        _ => Color::Yellow,
+    };
+    let mut spans = vec![
+        Span::styled("Pod ", Style::default().fg(Color::Gray)),
+        Span::styled(pod.name.clone(), Style::default().fg(Color::White)),
+        Span::styled(format!("  {}", pod.phase), Style::default().fg(phase_color)),
+    ];
+    if let Some(node) = &pod.node {
+        spans.push(Span::styled(format!(" on {}", node), Style::default().fg(Color::Gray)));
+    }
+    if pod.restarts > 0 {
+        let restarts = match pod.restarts {
+            1 => "1 restart".to_string(),
+            count => format!("{} restarts", app.numbers.count(u64::from(count))),
+        };
+        spans.push(Span::styled(format!("  {}", restarts), Style::default().fg(Color::LightRed)));
+    }
+    if let Some(reason) = &pod.reason {
+        spans.push(Span::styled(format!("  ({})", reason), Style::default().fg(Color::DarkGray)));
+    }
+    Line::from(spans)
+}
+
+/// Returns an executor's image and exit code, followed by the last lines of
+/// its standard error (or, if that is empty, its standard output).
+fn executor_lines(index: usize, executor: &crate::app::ExecutorLog) -> Vec<Line<'static>> {
+    const TAIL_LINES: usize = 3;
+    let (state, color) = match executor.exit_code {
+        Some(0) => ("exit 0".to_string(), Color::Green),
+        Some(code) => (format!("exit {}", code), Color::Red),
+        None => ("no exit code yet".to_string(), Color::Gray),
+    };
+    let mut lines = vec![Line::from(vec![
+        Span::styled(format!("Executor {}: ", index + 1), Style::default().fg(Color::Gray)),
+        Span::styled(executor.image.clone(), Style::default().fg(Color::White)),
+        Span::styled(format!("  ({})", state), Style::default().fg(color)),
+    ])];
+    let output = if executor.stderr.trim().is_empty() { &executor.stdout } else { &executor.stderr };
+    let tail: Vec<&str> = output.lines().filter(|line| !line.trim().is_empty()).collect();
+    lines.extend(
+        tail[tail.len().saturating_sub(TAIL_LINES)..]
+            .iter()
+            .map(|line| Line::styled(line.to_string(), Style::default().fg(Color::DarkGray))),
+    );
+    lines
+}
+
+/// Draws the selected task's sampled CPU and memory use, with the values at
+/// the cursor (or the newest sample) on the line below the chart.
+fn draw_task_chart(f: &mut Frame, app: &App, task: &crate::app::Task, area: Rect) {
+    let block = panel(app, format!(" Metrics: {} ", task.id)).title(
+        ratatui::widgets::block::Title::from(Span::styled(" ←/→ read values ", Style::default().fg(Color::DarkGray)))
+            .position(ratatui::widgets::block::Position::Bottom)
+            .alignment(Alignment::Right),
+    );
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if !app.capabilities.has_metrics() {
+        let text = Paragraph::new("This data source does not report CPU or memory use")
+            .style(Style::default().fg(Color::DarkGray))
+            .wrap(Wrap { trim: true });
+        f.render_widget(text, inner);
+        return;
+    }
+    let points = match app.metrics.points(&task.id) {
+        Some(points) if !points.is_empty() => points,
+        _ => {
+            let text = Paragraph::new("No samples yet: metrics are sampled while the task runs")
+                .style(Style::default().fg(Color::DarkGray))
+                .wrap(Wrap { trim: true });
+            f.render_widget(text, inner);
+            return;
+        }
+    };
+    let cursor = app.chart.cursor.index(points);
+    let shown = points[cursor.unwrap_or(points.len() - 1)];
+    let mut tooltip = vec![
+        Span::styled(
+            format!("{} ", if cursor.is_some() { "▶" } else { "now" }),
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(format!("{}  ", crate::format::clock(shown.at)), Style::default().fg(Color::Gray)),
+    ];
+    for series in app.chart.visible().filter(|series| app.capabilities.series(*series)) {
+        let value = match series {
+            ChartSeries::Cpu => app.numbers.percent(shown.cpu),
+            ChartSeries::Memory => memory_label(&app.numbers, shown.memory),
+        };
+        tooltip.push(Span::styled(format!("{} {}  ", series.name(), value), Style::default().fg(series_color(series))));
+    }
+
+    // Every reported series is listed, hidden ones dimmed, with the key
+    // toggling it
+    let legend: Vec<Span> = ChartSeries::ALL
+        .iter()
+        .enumerate()
+        .filter(|(_, series)| app.capabilities.series(**series))
+        .map(|(index, &series)| {
+            let style = if app.chart.is_visible(series) {
+                Style::default().fg(series_color(series))
+            } else {
+                Style::default().fg(Color::DarkGray).add_modifier(Modifier::CROSSED_OUT)
+            };
+            Span::styled(format!("{} ━ {}  ", index + 1, series.name()), style)
+        })
+        .collect();
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(inner);
+    f.render_widget(Paragraph::new(Line::from(legend)), rows[0]);
+    f.render_widget(Paragraph::new(Line::from(tooltip)), rows[2]);
+    // A braille chart means nothing to a screen reader; the values line does
+    if app.screen_reader {
+        return;
+    }
+
+    let visible: Vec<ChartSeries> = app.chart.visible().filter(|series| app.capabilities.series(*series)).collect();
+    if visible.is_empty() {
+        let text = Paragraph::new("All series hidden: press a number key to show one")
+            .style(Style::default().fg(Color::DarkGray))
+            .wrap(Wrap { trim: true });
+        f.render_widget(text, rows[1]);
+        return;
+    }
+    let charts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Ratio(1, visible.len() as u32); visible.len()])
+        .split(rows[1]);
+    let marker = cursor.map(|_| shown.at);
+    for (&series, &area) in visible.iter().zip(charts.iter()) {
+        let samples: Vec<(u64, f64)> = points.iter().map(|point| (point.at, series.value(point))).collect();
+        let scale = app.chart.scale(series);
+        let title = match series {
+            ChartSeries::Memory => format!("Memory ({}, l toggles)", scale),
+            _ => series.name().to_string(),
+        };
+        let chart = Series {
+            title,
+            color: series_color(series),
+            samples: &samples,
+            scale,
+        };
+        match series {
+            ChartSeries::Cpu => draw_metric_chart(f, area, chart, marker, |value| app.numbers.percent(value)),
+            ChartSeries::Memory => {
+                draw_metric_chart(f, area, chart, marker, |value| app.numbers.bytes(value.max(0.0) as u64))
+            }
+        }
+    }
+}
+
+/// Returns the color a chart series is drawn in.
+fn series_color(series: ChartSeries) -> Color {
+    match series {
+        ChartSeries::Cpu => Color::Cyan,
+        ChartSeries::Memory => Color::Magenta,
+    }
+}
+
+/// One metric to chart over time.
+struct Series<'a> {
+    title: String,
+    color: Color,
+    /// Sample times and values, oldest first
+    samples: &'a [(u64, f64)],
+    scale: Scale,
+}
+
+/// Draws one metric over time, with the value axis scaled to the samples and
+/// a vertical line at the cursor, if placed.
+fn draw_metric_chart(f: &mut Frame, area: Rect, series: Series<'_>, marker: Option<u64>, label: impl Fn(f64) -> String) {
+    let Series { title, color, samples, scale } = series;
+    let (Some(&(first, _)), Some(&(last, _))) = (samples.first(), samples.last()) else {
+        return;
+    };
+    let x = |at: u64| (at - first) as f64;
+    let data: Vec<(f64, f64)> = samples.iter().map(|&(at, value)| (x(at), scale.to_axis(value))).collect();
+    let [lower, upper] = scale.bounds(samples.iter().map(|&(_, value)| value));
+    let marker: Vec<(f64, f64)> = marker.map_or_else(Vec::new, |at| vec![(x(at), lower), (x(at), upper)]);
+    let labels = [lower, (lower + upper) / 2.0, upper]
+        .into_iter()
+        .map(|position| Span::raw(label(scale.from_axis(position))))
+        .collect();
+
+    let datasets = vec![
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(color))
+            .data(&data),
+        Dataset::default()
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::White))
+            .data(&marker),
+    ];
+    let chart = Chart::new(datasets)
+        .block(Block::default().title(Span::styled(title, Style::default().fg(color))))
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::DarkGray))
+                .bounds([0.0, x(last).max(1.0)])
+                .labels(vec![Span::raw(crate::format::clock(first)), Span::raw(crate::format::clock(last))]),
+        )
+        .y_axis(Axis::default().style(Style::default().fg(Color::DarkGray)).bounds([lower, upper]).labels(labels));
+    f.render_widget(chart, area);
+}
+
+/// Describes a slow task, e.g. `Slow: running 4.2× the median of
+/// align_reads (12m 00s)`.
+fn anomaly_label(app: &App, anomaly: &crate::anomaly::Anomaly) -> String {
+    format!(
+        "Slow: running {}× the median of {} ({})",
+        app.numbers.decimal(anomaly.ratio(), 1),
+        anomaly.step,
+        app.numbers.duration(std::time::Duration::from_secs(anomaly.median))
+    )
+}
+
+/// Describes a queue position, e.g. `Queue position 12, expected to start
+/// in 5m 0s`.
+fn queue_label(app: &App, queue: QueuePosition, now: u64) -> String {
+    let position = format!("Queue position {}", app.numbers.integer(queue.position));
+    match queue.estimated_start {
+        Some(start) if start > now => format!(
+            "{}, expected to start in {}",
+            position,
+            app.numbers.duration(std::time::Duration::from_secs(start - now))
+        ),
+        Some(_) => format!("{}, expected to start shortly", position),
+        None => position,
+    }
+}
+
+/// Returns the color used for alerts of a severity.
+fn severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Warning => Color::Yellow,
+        Severity::Critical => Color::Red,
+    }
+}
+
+/// Formats how long a task has been running, or a dash if it has not started.
+/// Returns the attempt of a requeued task in superscript digits, or nothing
+/// for a task on its first attempt.
+fn attempt_superscript(task: &crate::app::Task) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    match task.attempt {
+        Some(attempt) if task.is_requeued() => attempt
+            .to_string()
+            .chars()
+            .filter_map(|digit| digit.to_digit(10).map(|digit| DIGITS[digit as usize]))
+            .collect(),
+        _ => String::new(),
+    }
+}
+
+fn task_duration(app: &App, task: &crate::app::Task, now: u64) -> String {
+    match task.duration(now) {
+        Some(secs) => app.numbers.duration(std::time::Duration::from_secs(secs)),
+        None => "—".to_string(),
+    }
+}
+
+/// Formats memory use against its capacity, e.g. `3.2/8.0 GiB`.
+fn memory_label(numbers: &NumberFormat, memory: MemoryUsage) -> String {
+    match memory.capacity() {
+        Some(capacity) => numbers.bytes_of(memory.used, capacity),
+        None => numbers.bytes(memory.used),
+    }
+}
+
+fn draw_logs_tab(f: &mut Frame, app: &App, area: Rect) {
+    if !app.capabilities.logs {
+        let text = Paragraph::new("This data source does not provide task logs")
+            .style(Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center)
+            .block(panel(app, " Logs "));
+        f.render_widget(text, area);
+        return;
+    }
+    // Only panes receiving lines are drawn again while logs stream
+    let panes = app.log_panes.panes();
+    if panes.is_empty() {
+        let task_id = app.selected_task_id.as_deref();
+        let shown = log_pane_stamp(app, task_id, None);
+        app.regions.draw(f, Slot::LogPane(0), area, shown, |f| draw_log_pane(f, app, task_id, None, area));
+        return;
+    }
+
+    // One row for up to two panes, otherwise a 2x2 grid filled row by row
+    let per_row = if panes.len() > 2 { 2 } else { panes.len() };
+    let rows = panes.len().div_ceil(per_row);
+    let row_areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Ratio(1, rows as u32); rows])
+        .split(area);
+    for (row, (chunk, row_area)) in panes.chunks(per_row).zip(row_areas.iter()).enumerate() {
+        let cells = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Ratio(1, chunk.len() as u32); chunk.len()])
+            .split(*row_area);
+        for (column, (pane, cell)) in chunk.iter().zip(cells.iter()).enumerate() {
+            let index = row * per_row + column;
+            let focused = panes.len() > 1 && index == app.log_panes.focus();
+            let shown = log_pane_stamp(app, Some(&pane.task_id), Some((pane, focused)));
+            app.regions.draw(f, Slot::LogPane(index), *cell, shown, |f| {
+                draw_log_pane(f, app, Some(&pane.task_id), Some((pane, focused)), *cell)
+            });
+        }
+    }
+}
+
+/// Returns a stamp of everything a log pane is drawn from, which for a
+/// streaming log is where it ends rather than its every line.
+fn log_pane_stamp(app: &App, task_id: Option<&str>, pane: Option<(&LogPane, bool)>) -> u64 {
+    stamp(|h| {
+        (task_id, app.log_panes.len(), app.expand_structured_logs, app.is_viewing_history(), app.screen_reader).hash(h);
+        let Some(task) = task_id.and_then(|id| app.tasks.get(id)) else {
+            return;
+        };
+        let logs = &task.logs;
+        let next = logs.next_index();
+        (next, logs.omitted(), logs.len(), logs.bytes(), logs.evicted(), logs.is_hydrated(), logs.error()).hash(h);
+        if let Some((pane, focused)) = pane {
+            (pane.end(next), pane.is_following(), focused).hash(h);
+        }
+        if logs.is_empty() && app.is_log_loading(&task.id) {
+            app.spinner().hash(h);
+        }
+    })
+}
+
+/// Draws one task's log. Without a pane, the log of the selected task is
+/// shown following its end.
+fn draw_log_pane(f: &mut Frame, app: &App, task_id: Option<&str>, pane: Option<(&LogPane, bool)>, area: Rect) {
+    let task = task_id.and_then(|id| app.tasks.get(id));
+    let focus = match pane {
+        Some((_, true)) => "▸ ",
+        _ => "",
+    };
+    let paused = pane
+        .map(|(pane, _)| pane)
+        .filter(|pane| !pane.is_following())
+        .zip(task)
+        .map(|(pane, task)| {
+            let next = task.logs.next_index();
+            format!("(paused, {} newer lines) ", app.numbers.integer((next - pane.end(next)) as u64))
+        })
+        .unwrap_or_default();
+    let title = match task_id {
+        Some(id) => format!(" {}Logs: {} {}", focus, id, paused),
+        None => " Logs ".to_string(),
+    };
+    let block = match pane {
+        Some((_, false)) if app.log_panes.len() > 1 => titled_block(app, title, Color::DarkGray),
+        _ => panel(app, title),
+    }
+    .padding(Padding::new(1, 1, 0, 0));
+
+    let Some(task) = task else {
+        let message = if task_id.is_some() { "This task is no longer available" } else { "Select a task to view its logs" };
+        let no_selection = Paragraph::new(Text::styled(
+            message,
+            Style::default().fg(Color::DarkGray)
         ))
+        .block(block)
         .alignment(Alignment::Center);
-        f.render_widget(info_text, chunks[5]);
+        f.render_widget(no_selection, area);
+        return;
+    };
+
+    // Only build lines for the tail that fits on screen
+    let inner = block.inner(area);
+    let mut capacity = inner.height as usize;
+    let mut text = Vec::with_capacity(capacity);
+    let notice = Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC);
+    if let Some(error) = task.logs.error() {
+        text.push(Line::from(Span::styled(format!("Failed to fetch logs: {}", error), Style::default().fg(Color::Red))));
+        capacity = capacity.saturating_sub(1);
+    }
+    if task.logs.omitted() > 0 && capacity > 0 {
+        let message = if task.logs.is_truncated() {
+            format!("… {} earlier lines truncated", app.numbers.integer(task.logs.omitted() as u64))
+        } else {
+            format!(
+                "… {} earlier lines not loaded (press L to load the full log)",
+                app.numbers.integer(task.logs.omitted() as u64)
+            )
+        };
+        text.push(Line::from(Span::styled(message, notice)));
+        capacity -= 1;
+    }
+    // A paused pane shows the lines leading up to where it was left
+    let next = task.logs.next_index();
+    let end = pane.map_or(next, |(pane, _)| pane.end(next)).max(task.logs.omitted());
+    let shown = end - task.logs.omitted();
+    // Expanded JSON lines take several rows, so fill the pane from the bottom
+    let mut rows = Vec::with_capacity(capacity);
+    for line in task.logs.lines().take(shown).rev() {
+        if rows.len() >= capacity {
+            break;
+        }
+        rows.extend(log_line_rows(app, line).into_iter().rev());
+    }
+    rows.truncate(capacity);
+    text.extend(rows.into_iter().rev());
+
+    if task.logs.is_empty() {
+        let message = if !task.logs.is_hydrated() && app.is_log_loading(&task.id) {
+            format!("{} Loading logs…", app.spinner())
+        } else {
+            "No log output yet".to_string()
+        };
+        text.push(Line::from(Span::styled(message, Style::default().fg(Color::DarkGray))));
+    }
+
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+/// Returns the rows a log line is drawn as: JSON objects as a `key=value`
+/// summary, or pretty-printed when expanded, and other lines highlighted.
+fn log_line_rows<'a>(app: &App, line: &'a str) -> Vec<Line<'a>> {
+    let Some(object) = crate::structured::parse(line) else {
+        return vec![app.highlighter.line(line)];
+    };
+    let key = Style::default().fg(Color::Cyan);
+    if app.expand_structured_logs {
+        return crate::structured::expanded(&object)
+            .into_iter()
+            .map(|row| match row.split_once("\": ") {
+                Some((name, value)) => Line::from(vec![
+                    Span::styled(format!("{}\":", name), key),
+                    Span::raw(format!(" {}", value)),
+                ]),
+                None => Line::from(row),
+            })
+            .collect();
     }
+
+    let mut spans = Vec::new();
+    for (i, (name, value)) in crate::structured::summary(&object).into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled(format!("{}=", name), key));
+        // Values are highlighted like plain lines, so levels keep their colors
+        spans.extend(
+            app.highlighter
+                .line(&value)
+                .spans
+                .into_iter()
+                .map(|span| Span::styled(span.content.into_owned(), span.style)),
+        );
+    }
+    vec![Line::from(spans)]
+}
+
+fn draw_task_json(f: &mut Frame, app: &App, task: &crate::app::Task, area: Rect) {
+    let title = if task.raw.is_some() { " Raw JSON " } else { " Raw JSON (serialized, no backend payload) " };
+    let json = task.raw_json();
+    let lines: Vec<Line<'_>> = json
+        .lines()
+        .map(|line| {
+            // Highlight object keys so large payloads stay scannable
+            match line.split_once("\": ") {
+                Some((key, value)) => Line::from(vec![
+                    Span::styled(format!("{}\":", key), Style::default().fg(Color::Cyan)),
+                    Span::raw(format!(" {}", value)),
+                ]),
+                None => Line::from(line.to_string()),
+            }
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            panel(app, title)
+                .title(
+                    ratatui::widgets::block::Title::from(Span::styled(" PgUp/PgDn scroll · y copy · J close ", Style::default().fg(Color::DarkGray)))
+                        .position(ratatui::widgets::block::Position::Bottom)
+                        .alignment(Alignment::Right),
+                )
+        )
+        .scroll((app.json_scroll, 0));
+    f.render_widget(paragraph, area);
 }
 
 fn draw_stats_tab(f: &mut Frame, app: &App, area: Rect) {
@@ -238,57 +1358,56 @@ fn draw_stats_tab(f: &mut Frame, app: &App, area: Rect) {
         .split(area);
     
     // Task status summary table
-    let mut pending = 0;
-    let mut running = 0;
-    let mut completed = 0;
-    let mut failed = 0;
-    
-    for task in app.tasks.values() {
-        match task.status {
-            TaskStatus::Pending => pending += 1,
-            TaskStatus::Running => running += 1,
-            TaskStatus::Completed => completed += 1,
-            TaskStatus::Failed => failed += 1,
-        }
-    }
+    let counts = app.status_counts();
+    let (pending, running, completed, failed) = (counts.pending, counts.running, counts.completed, counts.failed);
     
-    let total = app.tasks.len();
-    let completed_percent = if total > 0 { (completed as f64 / total as f64) * 100.0 } else { 0.0 };
+    let total = counts.total();
+    let share = |count: usize| if total > 0 { count as f64 / total as f64 } else { 0.0 };
+    let completed_ratio = share(completed);
     
-    let rows = vec![
+    let mut rows = vec![
         Row::new(vec![
             Cell::from("Pending"),
-            Cell::from(pending.to_string()).style(Style::default().fg(Color::Blue)),
-            Cell::from(format!("{:.1}%", if total > 0 { (pending as f64 / total as f64) * 100.0 } else { 0.0 })),
+            Cell::from(app.numbers.integer(pending as u64)).style(Style::default().fg(app.theme.status_color(TaskStatus::Pending))),
+            Cell::from(app.numbers.percent(share(pending))),
         ]),
         Row::new(vec![
             Cell::from("Running"),
-            Cell::from(running.to_string()).style(Style::default().fg(Color::Yellow)),
-            Cell::from(format!("{:.1}%", if total > 0 { (running as f64 / total as f64) * 100.0 } else { 0.0 })),
+            Cell::from(app.numbers.integer(running as u64)).style(Style::default().fg(app.theme.status_color(TaskStatus::Running))),
+            Cell::from(app.numbers.percent(share(running))),
         ]),
         Row::new(vec![
             Cell::from("Completed"),
-            Cell::from(completed.to_string()).style(Style::default().fg(Color::Green)),
-            Cell::from(format!("{:.1}%", completed_percent)),
+            Cell::from(app.numbers.integer(completed as u64)).style(Style::default().fg(app.theme.status_color(TaskStatus::Completed))),
+            Cell::from(app.numbers.percent(completed_ratio)),
         ]),
         Row::new(vec![
             Cell::from("Failed"),
-            Cell::from(failed.to_string()).style(Style::default().fg(Color::Red)),
-            Cell::from(format!("{:.1}%", if total > 0 { (failed as f64 / total as f64) * 100.0 } else { 0.0 })),
+            Cell::from(app.numbers.integer(failed as u64)).style(Style::default().fg(app.theme.status_color(TaskStatus::Failed))),
+            Cell::from(app.numbers.percent(share(failed))),
         ]),
         Row::new(vec![
             Cell::from("Total").style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from(total.to_string()).style(Style::default().add_modifier(Modifier::BOLD)),
-            Cell::from("100.0%").style(Style::default().add_modifier(Modifier::BOLD)),
+            Cell::from(app.numbers.integer(total as u64)).style(Style::default().add_modifier(Modifier::BOLD)),
+            Cell::from(app.numbers.percent(1.0)).style(Style::default().add_modifier(Modifier::BOLD)),
         ]),
     ];
+    // Pruned tasks are among those counted above
+    let pruned = app.retention.pruned().total();
+    if pruned > 0 {
+        rows.push(
+            Row::new(vec![
+                Cell::from("Pruned"),
+                Cell::from(app.numbers.integer(pruned as u64)),
+                Cell::from(app.numbers.percent(share(pruned))),
+            ])
+            .style(Style::default().fg(Color::DarkGray)),
+        );
+    }
     
     let table = Table::new(rows)
         .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .title(Span::styled(" Task Statistics ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)))
+            panel(app, " Task Statistics ")
         )
         .header(
             Row::new(vec!["Status", "Count", "Percentage"])
@@ -299,66 +1418,495 @@ fn draw_stats_tab(f: &mut Frame, app: &App, area: Rect) {
         .highlight_style(Style::default().add_modifier(Modifier::BOLD))
         .highlight_symbol(">> ");
     
-    f.render_widget(table, chunks[0]);
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(chunks[0]);
+    f.render_widget(table, top[0]);
+    draw_step_durations(f, app, top[1]);
     
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(chunks[1]);
+    draw_capacity(f, app, bottom[1]);
+
     // Progress overview
-    let progress_block = Block::default()
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .title(Span::styled(" Overall Progress ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)));
+    let progress_block = panel(app, " Overall Progress ");
     
-    f.render_widget(progress_block, chunks[1]);
+    f.render_widget(progress_block, bottom[0]);
     
     let progress_chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(2)
         .constraints([
+            Constraint::Length(3),
             Constraint::Length(3),
             Constraint::Length(3),
             Constraint::Min(0),
         ].as_ref())
-        .split(chunks[1]);
+        .split(bottom[0]);
     
     // Overall completion gauge
     let completion_gauge = Gauge::default()
         .block(Block::default().title("Completion"))
-        .gauge_style(Style::default().fg(Color::Green).bg(Color::Black))
-        .ratio(completed_percent / 100.0)
-        .label(format!(" {:.1}% ", completed_percent))
+        .gauge_style(Style::default().fg(app.theme.status_color(TaskStatus::Completed)).bg(Color::Black))
+        .ratio(completed_ratio)
+        .label(format!(" {} ", app.numbers.percent(completed_ratio)))
         .use_unicode(true);
     
     f.render_widget(completion_gauge, progress_chunks[0]);
     
     // Failure rate gauge
-    let failure_rate = if total > 0 { (failed as f64 / total as f64) } else { 0.0 };
+    let failure_rate = share(failed);
     let failure_gauge = Gauge::default()
         .block(Block::default().title("Failure Rate"))
-        .gauge_style(Style::default().fg(Color::Red).bg(Color::Black))
+        .gauge_style(Style::default().fg(app.theme.status_color(TaskStatus::Failed)).bg(Color::Black))
         .ratio(failure_rate)
-        .label(format!(" {:.1}% ", failure_rate * 100.0))
+        .label(format!(" {} ", app.numbers.percent(failure_rate)))
         .use_unicode(true);
     
     f.render_widget(failure_gauge, progress_chunks[1]);
+
+    if let Some(budget) = app.slo.budget(&counts) {
+        draw_error_budget(f, app, budget, progress_chunks[2]);
+    }
 }
 
-fn draw_help_tab(f: &mut Frame, _app: &App, area: Rect) {
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .title(Span::styled(" Help & Keyboard Shortcuts ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)));
-    
-    let text = vec![
-        Line::from(vec![
-            Span::styled("Keyboard shortcuts:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
-        ]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("q", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
-            Span::raw(" or "),
-            Span::styled("Esc", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
-            Span::raw(" - Quit the application"),
-        ]),
-        Line::from(vec![
+/// Draws the share of the error budget consumed, yellow from half and red as
+/// it nears exhaustion.
+fn draw_error_budget(f: &mut Frame, app: &App, budget: ErrorBudget, area: Rect) {
+    let consumed = budget.consumed();
+    let color = if consumed >= crate::slo::CRITICAL_AT {
+        Color::Red
+    } else if consumed >= crate::slo::WARNING_AT {
+        Color::Yellow
+    } else {
+        Color::Green
+    };
+    let title = format!(
+        "Error Budget ({} of {} failures, {} left)",
+        app.numbers.integer(budget.failed as u64),
+        app.numbers.decimal(budget.allowed, 1),
+        app.numbers.percent(budget.remaining())
+    );
+    let label = if budget.is_exhausted() {
+        " exhausted ".to_string()
+    } else {
+        format!(" {} consumed ", app.numbers.percent(consumed))
+    };
+    let gauge = Gauge::default()
+        .block(Block::default().title(title))
+        .gauge_style(Style::default().fg(color).bg(Color::Black))
+        .ratio(consumed.min(1.0))
+        .label(label)
+        .use_unicode(true);
+    f.render_widget(gauge, area);
+}
+
+/// Draws duration percentiles of completed tasks per step, the step whose
+/// slowest task lags the median most first.
+fn draw_step_durations(f: &mut Frame, app: &App, area: Rect) {
+    let steps = app.step_durations();
+    let block = panel(app, " Durations by Step ");
+    if steps.is_empty() {
+        let text = Paragraph::new("No completed tasks yet").style(Style::default().fg(Color::DarkGray)).block(block);
+        f.render_widget(text, area);
+        return;
+    }
+
+    let duration = |secs: u64| app.numbers.duration(std::time::Duration::from_secs(secs));
+    let rows = steps.iter().map(|step| {
+        Row::new(vec![
+            Cell::from(step.step.clone()),
+            Cell::from(app.numbers.integer(step.count as u64)),
+            Cell::from(duration(step.p50)),
+            Cell::from(duration(step.p90)),
+            Cell::from(duration(step.max)),
+            Cell::from(format!("+{}", duration(step.spread()))),
+        ])
+    });
+    let table = Table::new(rows)
+        .block(block)
+        .header(
+            Row::new(vec!["Step", "Done", "p50", "p90", "Max", "Spread"])
+                .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        )
+        .widths(&[
+            Constraint::Min(12),
+            Constraint::Length(6),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Length(12),
+        ])
+        .column_spacing(1);
+    f.render_widget(table, area);
+}
+
+/// Draws what pending and running tasks request against the site capacity.
+fn draw_capacity(f: &mut Frame, app: &App, area: Rect) {
+    let block = panel(app, " Requests vs Capacity ");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if !app.capacity.is_known() {
+        let text = Paragraph::new("Capacity unknown: set it in the [capacity] configuration section.")
+            .style(Style::default().fg(Color::DarkGray))
+            .wrap(Wrap { trim: true });
+        f.render_widget(text, inner);
+        return;
+    }
+
+    let demand = Demand::of(app.tasks.values(), &app.capacity);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(2), Constraint::Length(2), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let gauge = |title: String, ratio: Option<f64>| {
+        let ratio = ratio.unwrap_or(0.0);
+        // Over capacity is not an error, but tasks will queue
+        let color = if ratio > 1.0 { Color::Yellow } else { Color::Cyan };
+        Gauge::default()
+            .block(Block::default().title(title))
+            .gauge_style(Style::default().fg(color).bg(Color::Black))
+            .ratio(ratio.min(1.0))
+            .label(format!(" {} ", app.numbers.percent(ratio)))
+            .use_unicode(true)
+    };
+    let cpu_title = match app.capacity.cpus {
+        Some(cpus) => format!("CPUs {} of {}", app.numbers.decimal(demand.cpus, 1), app.numbers.decimal(cpus, 1)),
+        None => format!("CPUs {} (site unknown)", app.numbers.decimal(demand.cpus, 1)),
+    };
+    let memory_title = match app.capacity.memory {
+        Some(memory) => format!("Memory {}", app.numbers.bytes_of(demand.memory, memory)),
+        None => format!("Memory {} (site unknown)", app.numbers.bytes(demand.memory)),
+    };
+    f.render_widget(gauge(cpu_title, demand.cpu_ratio(&app.capacity)), rows[0]);
+    f.render_widget(gauge(memory_title, demand.memory_ratio(&app.capacity)), rows[1]);
+
+    let mut lines = vec![Line::from(format!(
+        "{} pending or running tasks",
+        app.numbers.integer(demand.tasks as u64)
+    ))];
+    if !demand.never_run.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "{} can never run, larger than any node: {}",
+                app.numbers.integer(demand.never_run.len() as u64),
+                demand.never_run.join(", ")
+            ),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )));
+    }
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), rows[2]);
+}
+
+/// Colors cycled through for timeline lanes.
+const LANE_COLORS: [Color; 8] = [
+    Color::Cyan,
+    Color::Magenta,
+    Color::Yellow,
+    Color::Green,
+    Color::Blue,
+    Color::LightRed,
+    Color::LightCyan,
+    Color::LightMagenta,
+];
+
+/// Width of the lane (or task ID) column of the timeline.
+const LANE_LABEL_WIDTH: usize = 16;
+
+/// Returns the character a timeline bar is drawn with, so status stays
+/// readable when color shows the lane.
+fn bar_glyph(status: TaskStatus) -> char {
+    match status {
+        TaskStatus::Completed => '█',
+        TaskStatus::Running => '▓',
+        TaskStatus::Failed => '░',
+        TaskStatus::Pending => ' ',
+    }
+}
+
+fn draw_timeline_tab(f: &mut Frame, app: &App, area: Rect) {
+    let now = crate::record::unix_now();
+    let visible = app.visible_task_ids();
+    let (lanes, earliest) =
+        crate::timeline::layout(visible.iter().filter_map(|id| app.tasks.get(*id)), &app.timeline_lanes, now);
+    let grouped = app.timeline_lanes != LaneKey::None;
+
+    let block = panel(app, format!(" Timeline (lanes: {}) ", app.timeline_lanes)).title(
+        ratatui::widgets::block::Title::from(Span::styled(" v change lanes ", Style::default().fg(Color::DarkGray)))
+            .position(ratatui::widgets::block::Position::Bottom)
+            .alignment(Alignment::Right),
+    );
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let Some(earliest) = earliest else {
+        let empty = Paragraph::new(Text::styled("No task has started yet", Style::default().fg(Color::DarkGray)))
+            .alignment(Alignment::Center);
+        f.render_widget(empty, inner);
+        return;
+    };
+    let lane_color = |index: usize| LANE_COLORS[index % LANE_COLORS.len()];
+
+    if app.screen_reader {
+        let lines: Vec<Line> = lanes
+            .iter()
+            .flat_map(|lane| lane.rows.iter().flatten().map(move |bar| (lane, bar)))
+            .map(|(lane, bar)| {
+                let prefix = if grouped { format!("{}: ", lane.name) } else { String::new() };
+                Line::from(format!(
+                    "{}{} {}, started {}, ran {}.",
+                    prefix,
+                    bar.task.id,
+                    bar.task.status,
+                    crate::format::timestamp(bar.start),
+                    app.numbers.duration(std::time::Duration::from_secs(bar.end - bar.start))
+                ))
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines), inner);
+        return;
+    }
+
+    // Legend: lanes by color, then status by glyph
+    let mut legend = Vec::new();
+    if grouped {
+        for (index, lane) in lanes.iter().enumerate() {
+            legend.push(Span::styled(
+                format!("■ {} ({})  ", lane.name, app.numbers.integer(lane.len as u64)),
+                Style::default().fg(lane_color(index)),
+            ));
+        }
+    }
+    for status in [TaskStatus::Completed, TaskStatus::Running, TaskStatus::Failed] {
+        legend.push(Span::styled(
+            format!("{} {}  ", bar_glyph(status), status),
+            Style::default().fg(if grouped { Color::Gray } else { app.theme.status_color(status) }),
+        ));
+    }
+    let legend_width: usize = legend.iter().map(|span| span.width()).sum();
+    let legend_height = legend_width.div_ceil(inner.width.max(1) as usize).clamp(1, inner.height as usize / 3 + 1) as u16;
+    let legend = Paragraph::new(Line::from(legend)).wrap(Wrap { trim: true });
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(legend_height), Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(inner);
+    f.render_widget(legend, chunks[0]);
+
+    let width = (chunks[1].width as usize).saturating_sub(LANE_LABEL_WIDTH + 1).max(1);
+    // Tasks started by a clock ahead of ours end after now, and the axis
+    // stretches to the last of them
+    let latest = lanes.iter().flat_map(|lane| lane.rows.iter().flatten()).map(|bar| bar.end).fold(now, u64::max);
+    let span = latest.saturating_sub(earliest).max(1);
+    let column = |at: u64| ((at - earliest) as f64 / span as f64 * (width - 1) as f64).round() as usize;
+
+    // Bars drawn as an image leave their cells blank, unless an overlay
+    // would be hidden under the image
+    #[cfg(feature = "graphics")]
+    let graphics = app.graphics.as_ref().filter(|_| app.overlays.is_empty());
+    #[cfg(feature = "graphics")]
+    let as_image = graphics.is_some();
+    #[cfg(not(feature = "graphics"))]
+    let as_image = false;
+
+    let mut rows = Vec::new();
+    for (index, lane) in lanes.iter().enumerate() {
+        for (row_index, row) in lane.rows.iter().enumerate() {
+            let label = match (grouped, row.first()) {
+                (true, _) if row_index == 0 => lane.name.clone(),
+                (false, Some(bar)) => bar.task.id.clone(),
+                _ => String::new(),
+            };
+            let label_color = if grouped { lane_color(index) } else { Color::White };
+            let mut cells = vec![(' ', Color::Reset); width];
+            for bar in row.iter().filter(|_| !as_image) {
+                let color = if grouped { lane_color(index) } else { app.theme.status_color(bar.task.status) };
+                let (from, to) = (column(bar.start), column(bar.end).max(column(bar.start)));
+                for cell in &mut cells[from..=to.min(width - 1)] {
+                    *cell = (bar_glyph(bar.task.status), color);
+                }
+            }
+
+            let truncated: String = label.chars().take(LANE_LABEL_WIDTH).collect();
+            let mut spans = vec![Span::styled(
+                format!("{:<width$} ", truncated, width = LANE_LABEL_WIDTH),
+                Style::default().fg(label_color).add_modifier(Modifier::BOLD),
+            )];
+            // One span per run of equally colored cells
+            let mut run = String::new();
+            let mut run_color = cells[0].1;
+            for (glyph, color) in cells {
+                if color != run_color && !run.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut run), Style::default().fg(run_color)));
+                }
+                run_color = color;
+                run.push(glyph);
+            }
+            spans.push(Span::styled(run, Style::default().fg(run_color)));
+            rows.push(Line::from(spans));
+        }
+    }
+
+    let capacity = chunks[1].height as usize;
+    #[cfg(feature = "graphics")]
+    let bar_rows = if rows.len() > capacity { capacity.saturating_sub(1) } else { rows.len() };
+    if rows.len() > capacity {
+        let hidden = rows.len() - capacity + 1;
+        rows.truncate(capacity.saturating_sub(1));
+        rows.push(Line::styled(
+            format!("… {} more rows", app.numbers.integer(hidden as u64)),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    f.render_widget(Paragraph::new(rows), chunks[1]);
+    #[cfg(feature = "graphics")]
+    if let Some(graphics) = graphics.filter(|_| bar_rows > 0) {
+        let area = Rect::new(chunks[1].x + LANE_LABEL_WIDTH as u16 + 1, chunks[1].y, width as u16, bar_rows as u16);
+        graphics.place(area, timeline_image(app, &lanes, grouped, earliest, span, area));
+    }
+
+    let start = crate::format::timestamp(earliest);
+    let length = app.numbers.duration(std::time::Duration::from_secs(span));
+    let axis = format!(
+        "{:indent$}{}{:>fill$}",
+        "",
+        start,
+        format!("{} → now", length),
+        indent = LANE_LABEL_WIDTH + 1,
+        fill = width.saturating_sub(start.chars().count())
+    );
+    f.render_widget(Paragraph::new(Line::styled(axis, Style::default().fg(Color::DarkGray))), chunks[2]);
+}
+
+/// Draws the bars of the timeline's first rows as an image covering `area`,
+/// one row of cells per row of bars.
+#[cfg(feature = "graphics")]
+fn timeline_image(
+    app: &App,
+    lanes: &[crate::timeline::Lane],
+    grouped: bool,
+    earliest: u64,
+    span: u64,
+    area: Rect,
+) -> crate::graphics::Image {
+    use crate::graphics::{rgb, Image, CELL_PIXELS};
+
+    let (cell_width, cell_height) = CELL_PIXELS;
+    let mut image = Image::new(area.width as u32 * cell_width, area.height as u32 * cell_height);
+    let last = (image.width - 1) as f64;
+    let x = |at: u64| ((at - earliest) as f64 / span as f64 * last).round() as u32;
+
+    let rows = lanes.iter().enumerate().flat_map(|(index, lane)| lane.rows.iter().map(move |row| (index, row)));
+    for (y, (index, row)) in rows.take(area.height as usize).enumerate() {
+        let top = y as u32 * cell_height;
+        for bar in row {
+            let color = if grouped { LANE_COLORS[index % LANE_COLORS.len()] } else { app.theme.status_color(bar.task.status) };
+            let [r, g, b] = rgb(color);
+            // Patterns stand in for the glyphs that tell statuses apart
+            let status = bar.task.status;
+            let pattern = move |px: u32, py: u32| match status {
+                TaskStatus::Completed => true,
+                TaskStatus::Running => px % 4 != 3,
+                TaskStatus::Failed => (px + py) % 2 == 0,
+                TaskStatus::Pending => false,
+            };
+            let from = x(bar.start);
+            image.fill((from, top + 2), (x(bar.end).max(from) + 1, top + cell_height - 2), [r, g, b, 255], pattern);
+        }
+    }
+    image
+}
+
+/// Lists the run's notable events, newest first, with the selected one
+/// highlighted.
+fn draw_events_tab(f: &mut Frame, app: &App, area: Rect) {
+    let title = match app.feed.filter() {
+        Some(kind) => format!(" Events ({} only) ", kind),
+        None => " Events ".to_string(),
+    };
+    let block = panel(app, title).title(
+        ratatui::widgets::block::Title::from(Span::styled(" f filter  Enter go to task ", Style::default().fg(Color::DarkGray)))
+            .position(ratatui::widgets::block::Position::Bottom)
+            .alignment(Alignment::Right),
+    );
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let selected = app.feed.selected_index();
+    let height = inner.height as usize;
+    // Keep the selected event on screen
+    let skip = (selected + 1).saturating_sub(height);
+    let lines: Vec<Line> = app
+        .feed
+        .visible()
+        .enumerate()
+        .skip(skip)
+        .take(height)
+        .map(|(index, event)| {
+            let color = match event.kind {
+                EventKind::Started => app.theme.status_color(TaskStatus::Running),
+                EventKind::Finished => app.theme.status_color(TaskStatus::Completed),
+                EventKind::Failed => app.theme.status_color(TaskStatus::Failed),
+                EventKind::Backend => Color::Yellow,
+                EventKind::Bookmark => Color::Cyan,
+                EventKind::Alert => Color::Magenta,
+            };
+            let task = event.task_id.as_ref().map(|id| match app.tasks.get(id) {
+                Some(task) => app.display_name(task).to_string(),
+                None => id.clone(),
+            });
+            let mut line = vec![
+                Span::styled(format!("{}  ", crate::format::clock(event.at)), Style::default().fg(Color::Gray)),
+                Span::styled(format!("{:<9}", event.kind.to_string()), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            ];
+            if let Some(task) = task {
+                line.push(Span::styled(format!("{}  ", task), Style::default().fg(Color::White)));
+            }
+            line.push(Span::raw(event.text.clone()));
+            if index == selected {
+                for span in &mut line {
+                    span.style = span.style.bg(Color::DarkGray);
+                }
+            }
+            Line::from(line)
+        })
+        .collect();
+
+    if lines.is_empty() {
+        let text = match app.feed.filter() {
+            Some(kind) => format!("No {} events yet", kind),
+            None => "Tasks starting, finishing, and failing, dropped connections, and alerts appear here".to_string(),
+        };
+        let empty = Paragraph::new(Text::styled(text, Style::default().fg(Color::DarkGray)))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(empty, inner);
+        return;
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_help_tab(f: &mut Frame, app: &App, area: Rect) {
+    let block = panel(app, " Help & Keyboard Shortcuts ");
+    
+    let text = vec![
+        Line::from(vec![
+            Span::styled("Keyboard shortcuts:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("q", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" or "),
+            Span::styled("Esc", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Quit the application (Esc first closes the dialog or panel on top)"),
+        ]),
+        Line::from(vec![
             Span::styled("Tab", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
             Span::raw(" - Switch to next tab"),
         ]),
@@ -370,6 +1918,168 @@ fn draw_help_tab(f: &mut Frame, _app: &App, area: Rect) {
             Span::styled("↑/↓", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
             Span::raw(" - Navigate through task list"),
         ]),
+        Line::from(vec![
+            Span::styled("Mouse", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Hover over a task row for its status, elapsed time, CPU, and memory"),
+        ]),
+        Line::from(vec![
+            Span::styled("[/]", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Step back/forward through this session's history (] past the newest returns to live)"),
+        ]),
+        Line::from(vec![
+            Span::styled("f/Enter", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Events tab: show one kind of event at a time; go to the selected event's task"),
+        ]),
+        Line::from(vec![
+            Span::styled("m", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Bookmark this moment with an optional note, kept in the Events tab, history, and recording"),
+        ]),
+        Line::from(vec![
+            Span::styled("o", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Open the sort menu (Enter/1 primary, 2 secondary, c clears)"),
+        ]),
+        Line::from(vec![
+            Span::styled("?", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Explain the selected task's status and figures (←/→ steps through them)"),
+        ]),
+        Line::from(vec![
+            Span::styled("t", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Top mode: rank tasks by live CPU, then memory, then back to the sort order"),
+        ]),
+        Line::from(vec![
+            Span::styled("g", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" / "),
+            Span::styled("z", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Group the task list by workflow, or collapse and expand the selected task's group"),
+        ]),
+        Line::from(vec![
+            Span::styled("p", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Pin or unpin the selected task at the top of the list"),
+        ]),
+        Line::from(vec![
+            Span::styled("r", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Give the selected task a local display name (kept in the state file)"),
+        ]),
+        Line::from(vec![
+            Span::styled("a", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Archive (hide) the selected task"),
+        ]),
+        Line::from(vec![
+            Span::styled("u", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Undo the last pin, archive, or rename"),
+        ]),
+        Line::from(vec![
+            Span::styled("b", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Mark the selected task as the reference to compare others with, or clear it"),
+        ]),
+        Line::from(vec![
+            Span::styled("B", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - List only the tasks of the next backend, when several are followed, or all of them again"),
+        ]),
+        Line::from(vec![
+            Span::styled("E", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Diff the selected task's command, environment, and resources against the reference"),
+        ]),
+        Line::from(vec![
+            Span::styled("O", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Open the reference and selected task specs in the diff tool set under [tools]"),
+        ]),
+        Line::from(vec![
+            Span::styled("c", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Cancel the selected task"),
+        ]),
+        Line::from(vec![
+            Span::styled("R", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Retry the selected task"),
+        ]),
+        Line::from(vec![
+            Span::styled("< >", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Raise or lower the priority of the selected pending task"),
+        ]),
+        Line::from(vec![
+            Span::styled("d", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Toggle dry run: record cancel, retry, and priority changes without sending them"),
+        ]),
+        Line::from(vec![
+            Span::styled("L", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Load the full log of the selected task (Logs tab)"),
+        ]),
+        Line::from(vec![
+            Span::styled("s", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Save the full log of the selected task to a file"),
+        ]),
+        Line::from(vec![
+            Span::styled("+", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" / "),
+            Span::styled("-", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Open the selected task's log in a new pane, or close the focused pane (Logs tab, up to 4)"),
+        ]),
+        Line::from(vec![
+            Span::styled("f", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" / "),
+            Span::styled("F", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Focus the next log pane, or pause and resume following its log (Logs tab)"),
+        ]),
+        Line::from(vec![
+            Span::styled("PgUp/PgDn", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Scroll the focused log; scrolling back to the end follows it again (Logs tab)"),
+        ]),
+        Line::from(vec![
+            Span::styled("e", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Expand JSON log lines in full, or show them as key=value summaries (Logs tab)"),
+        ]),
+        Line::from(vec![
+            Span::styled("W", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Toggle the workflow metadata drawer"),
+        ]),
+        Line::from(vec![
+            Span::styled("J", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Toggle the raw JSON view of the selected task (PgUp/PgDn scroll, y copies)"),
+        ]),
+        Line::from(vec![
+            Span::styled("C", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Toggle the metric charts of the selected task (←/→ move the cursor, 1-9 toggle series, l log memory scale)"),
+        ]),
+        Line::from(vec![
+            Span::styled("T", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Print the task table as plain text to select and copy, any key returns"),
+        ]),
+        Line::from(vec![
+            Span::styled("S", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Write a snapshot of all tasks to the current directory"),
+        ]),
+        Line::from(vec![
+            Span::styled("G", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Export the dependency graph as Graphviz DOT to the current directory"),
+        ]),
+        Line::from(vec![
+            Span::styled("D", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Toggle the connection diagnostics overlay"),
+        ]),
+        Line::from(vec![
+            Span::styled("A", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Toggle the audit log of actions taken in this session"),
+        ]),
+        Line::from(vec![
+            Span::styled("M", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Mute or unmute the bells rung for alerts"),
+        ]),
+        Line::from(vec![
+            Span::styled("i", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Show the version, build, configuration file, and recent changes"),
+        ]),
+        Line::from(vec![
+            Span::styled("F12", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Toggle the performance overlay"),
+        ]),
+        Line::from(vec![
+            Span::styled("v", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Change what the timeline's lanes are grouped by (Timeline tab)"),
+        ]),
+        Line::from(vec![
+            Span::styled("x", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Dismiss the new release banner (enable the check with --check-updates)"),
+        ]),
         Line::from(""),
         Line::from(vec![
             Span::styled("About Crankshaft:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
@@ -382,20 +2092,25 @@ fn draw_help_tab(f: &mut Frame, _app: &App, area: Rect) {
             Span::styled("Task Status Icons:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
         ]),
         Line::from(""),
-        Line::from(vec![
-            Span::raw("⏳ - "),
-            Span::styled("Pending", Style::default().fg(Color::Blue)),
-            Span::raw(" | "),
-            Span::raw("▶️ - "),
-            Span::styled("Running", Style::default().fg(Color::Yellow)),
-            Span::raw(" | "),
-            Span::raw("✅ - "),
-            Span::styled("Completed", Style::default().fg(Color::Green)),
-            Span::raw(" | "),
-            Span::raw("❌ - "),
-            Span::styled("Failed", Style::default().fg(Color::Red)),
-        ]),
+        Line::from(
+            [TaskStatus::Pending, TaskStatus::Running, TaskStatus::Completed, TaskStatus::Failed]
+                .into_iter()
+                .enumerate()
+                .flat_map(|(i, status)| {
+                    let separator = if i > 0 { " | " } else { "" };
+                    [
+                        Span::raw(format!("{}{} - ", separator, app.theme.status_symbol(status))),
+                        Span::styled(status.to_string(), Style::default().fg(app.theme.status_color(status))),
+                    ]
+                })
+                .collect::<Vec<_>>(),
+        ),
     ];
+    // Hints for features the data source cannot back are left out
+    let text: Vec<Line> = text
+        .into_iter()
+        .filter(|line| line.spans.first().is_none_or(|key| crate::keymap::available(app, &key.content)))
+        .collect();
     
     let help_text = Paragraph::new(text)
         .block(block)
@@ -404,26 +2119,532 @@ fn draw_help_tab(f: &mut Frame, _app: &App, area: Rect) {
     f.render_widget(help_text, area);
 }
 
-fn draw_footer(f: &mut Frame, area: Rect) {
-    let text = vec![
-        Line::from(vec![
-            Span::styled("Press ", Style::default().fg(Color::DarkGray)),
-            Span::styled("q", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
-            Span::styled(" to quit | ", Style::default().fg(Color::DarkGray)),
-            Span::styled("Tab", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
-            Span::styled(" to switch tabs | ", Style::default().fg(Color::DarkGray)),
-            Span::styled("↑/↓", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
-            Span::styled(" to navigate", Style::default().fg(Color::DarkGray)),
-        ]),
-    ];
+/// Returns the keymap's hints for the app's mode as footer spans: each key
+/// in bold, followed by what it does.
+fn hint_spans(app: &App) -> Vec<Span<'static>> {
+    let key = Style::default().fg(Color::White).add_modifier(Modifier::BOLD);
+    let hint = Style::default().fg(Color::DarkGray);
+    crate::keymap::hints(app)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, entry)| {
+            let separator = if i > 0 { " | " } else { "" };
+            [Span::styled(separator, hint), Span::styled(entry.key, key), Span::styled(format!(" {}", entry.action), hint)]
+        })
+        .collect()
+}
+
+/// Draws the footer, with a badge on its border while alert sounds are
+/// muted.
+fn draw_footer(f: &mut Frame, app: &App, area: Rect) {
+    draw_footer_text(f, app, area);
+    if app.sounds.is_muted() && area.width > 12 {
+        // Ten columns wide, the icon taking two
+        let area = Rect::new(area.right() - 11, area.y, 10, 1);
+        f.render_widget(Paragraph::new(Span::styled(" 🔇 muted ", Style::default().fg(Color::Yellow))), area);
+    }
+}
+
+fn draw_footer_text(f: &mut Frame, app: &App, area: Rect) {
+    if app.rename.is_some() {
+        let mut spans = vec![Span::styled("Renaming: ", Style::default().fg(Color::Yellow))];
+        spans.extend(hint_spans(app));
+        spans.push(Span::styled(" | an empty name restores the backend's", Style::default().fg(Color::DarkGray)));
+        let paragraph = Paragraph::new(Line::from(spans)).block(panel(app, "")).alignment(Alignment::Center);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    if let Some(path) = &app.save_log_path {
+        let mut spans = vec![
+            Span::styled("Save log to: ", Style::default().fg(Color::Yellow)),
+            Span::styled(format!("{}▏ ", path), Style::default().fg(Color::White)),
+        ];
+        spans.extend(hint_spans(app));
+        let paragraph = Paragraph::new(Line::from(spans)).block(panel(app, "")).alignment(Alignment::Center);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    if let Some(note) = &app.bookmark_note {
+        let mut spans = vec![
+            Span::styled("Bookmark note: ", Style::default().fg(Color::Yellow)),
+            Span::styled(format!("{}▏ ", note), Style::default().fg(Color::White)),
+        ];
+        spans.extend(hint_spans(app));
+        let paragraph = Paragraph::new(Line::from(spans)).block(panel(app, "")).alignment(Alignment::Center);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    if let Some(snapshot) = app.viewed_snapshot() {
+        let age = crate::record::unix_now().saturating_sub(snapshot.taken_at);
+        let keys: Vec<String> =
+            crate::keymap::hints(app).iter().map(|hint| format!("{} {}", hint.key, hint.action)).collect();
+        let notes: Vec<&str> = snapshot.bookmarks.iter().map(String::as_str).filter(|note| !note.is_empty()).collect();
+        let bookmarks = match (snapshot.bookmarks.is_empty(), notes.is_empty()) {
+            (true, _) => String::new(),
+            (false, true) => "bookmarked | ".to_string(),
+            (false, false) => format!("bookmarked: {} | ", notes.join("; ")),
+        };
+        let banner = format!(
+            " HISTORY (read-only): {}, {} ago | {}{} ",
+            crate::format::timestamp(snapshot.taken_at),
+            app.numbers.duration(std::time::Duration::from_secs(age)),
+            bookmarks,
+            keys.join(", ")
+        );
+        let paragraph = Paragraph::new(Line::from(Span::styled(
+            banner,
+            Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )))
+        .block(panel(app, ""))
+        .alignment(Alignment::Center);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    if let Some(message) = app.status() {
+        let paragraph = Paragraph::new(Line::from(Span::styled(message, Style::default().fg(Color::Yellow))))
+            .block(
+                panel(app, "")
+            )
+            .alignment(Alignment::Center);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    if let Some((attempt, at)) = app.health.reconnecting {
+        let wait = at.saturating_duration_since(std::time::Instant::now());
+        let text = match wait.is_zero() {
+            true => format!("{} Reconnecting to {}… (attempt {})", app.spinner(), app.health.source, attempt),
+            false => format!(
+                "{} Reconnecting to {}… (attempt {} in {})",
+                app.spinner(),
+                app.health.source,
+                attempt,
+                app.numbers.duration(wait)
+            ),
+        };
+        let paragraph = Paragraph::new(Line::from(Span::styled(text, Style::default().fg(Color::Yellow))))
+            .block(panel(app, ""))
+            .alignment(Alignment::Center);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    if let Some(download) = app.log_download.as_ref().filter(|download| download.is_running()) {
+        let progress = format!(
+            "{} Saving {}'s log to {}: {} lines, {}",
+            app.spinner(),
+            download.task_id,
+            download.path.display(),
+            app.numbers.integer(download.lines as u64),
+            app.numbers.bytes(download.bytes)
+        );
+        let paragraph = Paragraph::new(Line::from(Span::styled(progress, Style::default().fg(Color::Cyan))))
+            .block(panel(app, ""))
+            .alignment(Alignment::Center);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    if let Some(release) = &app.available_update {
+        let paragraph = Paragraph::new(Line::from(vec![
+            Span::styled(
+                format!("crankshaft-tui {} is available (running {}): {}", release.version, env!("CARGO_PKG_VERSION"), release.changelog),
+                Style::default().fg(Color::Green),
+            ),
+            Span::styled(" | ", Style::default().fg(Color::DarkGray)),
+            Span::styled("x", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::styled(" to dismiss", Style::default().fg(Color::DarkGray)),
+        ]))
+        .block(panel(app, ""))
+        .alignment(Alignment::Center);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let text = vec![Line::from(hint_spans(app))];
     
     let paragraph = Paragraph::new(text)
         .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
+            panel(app, "")
         )
         .alignment(Alignment::Center);
     
     f.render_widget(paragraph, area);
-}
\ No newline at end of file
+}
+
+/// Renders the performance HUD in the top-right corner of the screen.
+fn draw_debug_overlay(f: &mut Frame, app: &App) {
+    let screen = f.size();
+    let width = 34.min(screen.width);
+    let height = 15.min(screen.height);
+    let area = Rect::new(screen.x + screen.width - width, screen.y, width, height);
+
+    let label = Style::default().fg(Color::Gray);
+    let value = Style::default().fg(Color::White).add_modifier(Modifier::BOLD);
+    let row = |name: &'static str, text: String| {
+        Line::from(vec![
+            Span::styled(format!("{:<14}", name), label),
+            Span::styled(text, value),
+        ])
+    };
+
+    let perf = &app.perf;
+    let (drawn, reused) = app.regions.counts();
+    let polls = app.source_fetch_stats();
+    let text = vec![
+        row("Store size", app.numbers.count(perf.store_size as u64)),
+        row("Updates/sec", app.numbers.decimal(perf.updates_per_sec, 1)),
+        row("Added/sec", app.numbers.decimal(perf.added_per_sec, 1)),
+        row("Removed/sec", app.numbers.decimal(perf.removed_per_sec, 1)),
+        row("Frame time", format!("{} ms", app.numbers.decimal(perf.last_frame.as_secs_f64() * 1000.0, 2))),
+        row("Regions", format!("{} drawn, {} reused", drawn, reused)),
+        row("Log fetches", app.fetch_stats().map_or_else(|| "n/a".to_string(), |stats| {
+            format!("{} sent, {} cached", app.numbers.count(stats.upstream), app.numbers.count(stats.cached))
+        })),
+        row("Task polls", format!("{} sent, {} waited", app.numbers.count(polls.upstream), app.numbers.count(polls.throttled))),
+        row("Coalesced", format!("{} updates", app.numbers.count(polls.coalesced))),
+        row("Resident mem", perf.memory.map_or_else(|| "n/a".to_string(), |memory| app.numbers.bytes(memory.resident))),
+        row("Log memory", app.numbers.bytes(crate::memory::retained_log_bytes(app) as u64)),
+        row("History", format!("{} tasks", app.numbers.count(app.history.retained_tasks() as u64))),
+        row("Stderr", format!("{} lines", app.numbers.count(crate::stderr::captured_lines() as u64))),
+    ];
+
+    let overlay = Paragraph::new(text).block(
+        overlay_panel(app, " Performance ")
+            .padding(Padding::new(1, 1, 0, 0))
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(overlay, area);
+}
+
+/// Formats an optional duration in milliseconds, or a dash when unknown.
+fn format_millis(numbers: &NumberFormat, duration: Option<std::time::Duration>) -> String {
+    match duration {
+        Some(d) => format!("{} ms", numbers.decimal(d.as_secs_f64() * 1000.0, 1)),
+        None => "—".to_string(),
+    }
+}
+
+/// Renders the connector health overlay in the center of the screen.
+/// Lists the actions taken in this session, most recent first, with who
+/// took them and what became of them.
+fn draw_audit_overlay(f: &mut Frame, app: &App) {
+    let area = centered_rect(90, 20, f.size());
+    let mut text: Vec<Line> = app
+        .audit
+        .recent()
+        .take(area.height.saturating_sub(2) as usize)
+        .map(|entry| {
+            let color = match entry.outcome {
+                Outcome::Done => Color::Green,
+                Outcome::Unchanged | Outcome::Declined => Color::Gray,
+                Outcome::Refused => Color::Yellow,
+                Outcome::Failed => Color::Red,
+                Outcome::DryRun => Color::Cyan,
+            };
+            let mut line = vec![
+                Span::styled(format!("{}  ", crate::format::timestamp(entry.at)), Style::default().fg(Color::Gray)),
+                Span::styled(format!("{}  ", entry.user), Style::default().fg(Color::White)),
+                Span::styled(format!("{}  ", entry.action), Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+                Span::styled(entry.outcome.to_string(), Style::default().fg(color)),
+            ];
+            if let Some(detail) = &entry.detail {
+                line.push(Span::styled(format!(": {}", detail), Style::default().fg(color)));
+            }
+            Line::from(line)
+        })
+        .collect();
+    if text.is_empty() {
+        text.push(Line::styled("No actions taken yet", Style::default().fg(Color::DarkGray)));
+    }
+
+    let title = if app.audit.is_persistent() { " Audit Log " } else { " Audit Log (not written to a file) " };
+    let overlay = Paragraph::new(text).block(overlay_panel(app, title).padding(Padding::new(1, 1, 0, 0)));
+    f.render_widget(Clear, area);
+    f.render_widget(overlay, area);
+}
+
+/// Shows the selected task's fields beside the reference task's, the
+/// differing ones highlighted.
+fn draw_task_diff_overlay(f: &mut Frame, app: &App) {
+    // Shrunk to the rows shown once they are known
+    let area = centered_rect(110, 24, f.size());
+    let reference = app.reference_task.as_ref().and_then(|id| app.tasks.get(id));
+    let task = app.selected_task_id.as_ref().and_then(|id| app.tasks.get(id));
+
+    let (title, text) = match (reference, task) {
+        (None, _) => (
+            " Task Diff ".to_string(),
+            vec![Line::styled("The reference task is gone; mark another with b", Style::default().fg(Color::DarkGray))],
+        ),
+        (Some(reference), Some(task)) if reference.id != task.id => {
+            let diffs = crate::compare::compare(reference, task, &app.numbers);
+            let differing = diffs.iter().filter(|diff| diff.differs()).count();
+            // Split what is left after the field names between the two tasks
+            let width = area.width.saturating_sub(4) as usize;
+            let field_width = diffs.iter().map(|diff| diff.field.chars().count()).max().unwrap_or(0).min(width / 4) + 2;
+            let column = width.saturating_sub(field_width) / 2;
+            let cell = |value: &Option<String>| {
+                let value = value.as_deref().unwrap_or("—");
+                format!("{:<width$}", truncate(value, column.saturating_sub(1)), width = column)
+            };
+
+            let heading = Style::default().fg(Color::Gray).add_modifier(Modifier::BOLD);
+            let mut text = vec![Line::from(vec![
+                Span::styled(format!("{:<width$}", "", width = field_width), heading),
+                Span::styled(format!("{:<width$}", truncate(&reference.id, column.saturating_sub(1)), width = column), heading),
+                Span::styled(truncate(&task.id, column.saturating_sub(1)), heading),
+            ])];
+            let rows = (area.height as usize).saturating_sub(3);
+            text.extend(diffs.iter().take(rows).map(|diff| {
+                let style = if diff.differs() {
+                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::DarkGray)
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:<width$}", truncate(&diff.field, field_width - 2), width = field_width), Style::default().fg(Color::Gray)),
+                    Span::styled(cell(&diff.reference), style),
+                    Span::styled(cell(&diff.task), style),
+                ])
+            }));
+            if diffs.len() > rows {
+                text.truncate(rows);
+                text.push(Line::styled(format!("… {} more fields", diffs.len() - rows + 1), Style::default().fg(Color::DarkGray)));
+            }
+            let title = match differing {
+                0 => format!(" {} vs {}: no differences ", task.id, reference.id),
+                1 => format!(" {} vs {}: 1 difference ", task.id, reference.id),
+                n => format!(" {} vs {}: {} differences ", task.id, reference.id, n),
+            };
+            (title, text)
+        }
+        (Some(reference), _) => (
+            format!(" Task Diff against {} ", reference.id),
+            vec![Line::styled("Select another task to compare with the reference", Style::default().fg(Color::DarkGray))],
+        ),
+    };
+
+    let area = centered_rect(area.width, text.len() as u16 + 2, f.size());
+    let overlay = Paragraph::new(text).block(overlay_panel(app, title).padding(Padding::new(1, 1, 0, 0)));
+    f.render_widget(Clear, area);
+    f.render_widget(overlay, area);
+}
+
+/// Shows what exactly is running: version, build, features, data source,
+/// configuration file, and the changes in this version.
+fn draw_about_overlay(f: &mut Frame, app: &App) {
+    let area = centered_rect(72, 22, f.size());
+
+    let label = Style::default().fg(Color::Gray);
+    let value = Style::default().fg(Color::White).add_modifier(Modifier::BOLD);
+    let row = |name: &'static str, text: String| {
+        Line::from(vec![Span::styled(format!("{:<16}", name), label), Span::styled(text, value)])
+    };
+
+    let features = crate::about::features();
+    let sources = crate::about::SOURCES
+        .iter()
+        .map(|source| if *source == app.health.source { format!("{} (in use)", source) } else { source.to_string() })
+        .collect::<Vec<_>>();
+    let config = match crate::crash::config_path() {
+        Some(path) => path.display().to_string(),
+        None => "none (defaults)".to_string(),
+    };
+
+    let mut text = vec![
+        row("Version", format!("crankshaft-tui {}", crate::about::VERSION)),
+        row("Build", crate::about::build()),
+        row("Features", if features.is_empty() { "none".to_string() } else { features.join(", ") }),
+        row("Data sources", sources.join(", ")),
+        row("Configuration", config),
+        row("Usage reports", usage_reports(app)),
+        Line::raw(""),
+        Line::styled(format!("Changes in {}", crate::about::VERSION), value),
+    ];
+    text.extend(crate::about::CHANGELOG.iter().map(|change| Line::raw(format!("- {}", change))));
+    text.push(Line::styled(
+        format!("Full changelog: {}", crate::updates::CHANGELOG_URL),
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    let overlay = Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .block(overlay_panel(app, " About ").padding(Padding::new(1, 1, 0, 0)));
+    f.render_widget(Clear, area);
+    f.render_widget(overlay, area);
+}
+
+/// Describes whether usage reports are sent, and where.
+#[cfg(feature = "telemetry")]
+fn usage_reports(app: &App) -> String {
+    match &app.telemetry {
+        Some(telemetry) => format!("sent on exit to {}", telemetry.endpoint()),
+        None => "off".to_string(),
+    }
+}
+
+#[cfg(not(feature = "telemetry"))]
+fn usage_reports(_: &App) -> String {
+    "not included in this build".to_string()
+}
+
+fn draw_diagnostics_overlay(f: &mut Frame, app: &App) {
+    let area = centered_rect(64, 15, f.size());
+    let health = &app.health;
+
+    let state = health.state();
+    let (state_color, verdict) = match state {
+        HealthState::Healthy => (Color::Green, "Updates are arriving normally"),
+        HealthState::Stale => (Color::Yellow, "No recent updates; the monitor may have lost its connection"),
+        HealthState::Failing => (Color::Red, "The connector is reporting errors"),
+        HealthState::Reconnecting => (Color::Yellow, "The connection dropped; reconnecting with backoff"),
+        HealthState::Waiting => (Color::Blue, "Waiting for the first update"),
+    };
+
+    let label = Style::default().fg(Color::Gray);
+    let value = Style::default().fg(Color::White).add_modifier(Modifier::BOLD);
+    let row = |name: &'static str, text: String| {
+        Line::from(vec![Span::styled(format!("{:<18}", name), label), Span::styled(text, value)])
+    };
+
+    let last_update = match health.last_update {
+        Some(at) => format!("{} ago", app.numbers.duration(at.elapsed())),
+        None => "never".to_string(),
+    };
+    let last_error = match &health.last_error {
+        Some((error, at)) => format!("{} ({} ago)", error, app.numbers.duration(at.elapsed())),
+        None => "none".to_string(),
+    };
+    // Progress and resource updates replaced while the app was behind
+    let coalesced = match health.coalesced {
+        0 => "none".to_string(),
+        count => format!("{} updates", app.numbers.integer(count)),
+    };
+    let malformed = match health.malformed.is_empty() {
+        true => "none".to_string(),
+        false => health
+            .malformed
+            .iter()
+            .map(|(kind, count)| format!("{} {}", app.numbers.integer(*count), kind))
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+
+    let text = vec![
+        Line::from(vec![
+            Span::styled(format!("{:<18}", "Connection"), label),
+            Span::styled(state.to_string(), Style::default().fg(state_color).add_modifier(Modifier::BOLD)),
+        ]),
+        Line::from(Span::styled(verdict, Style::default().fg(state_color))),
+        Line::from(""),
+        row("Source", health.source.clone()),
+        row("Provides", app.capabilities.describe()),
+        row("Engine version", health.engine_version.clone().unwrap_or_else(|| "unknown".to_string())),
+        row("API latency", format_millis(&app.numbers, health.api_latency)),
+        row("Event lag", format_millis(&app.numbers, health.event_lag)),
+        row("Dropped messages", app.numbers.integer(health.dropped_messages)),
+        row("Malformed", malformed),
+        row("Coalesced", coalesced),
+        row("Last update", last_update),
+        row("Last error", last_error),
+    ];
+
+    let overlay = Paragraph::new(text)
+        .block(
+            overlay_panel(app, " Diagnostics ")
+                .padding(Padding::new(1, 1, 0, 0))
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(overlay, area);
+}
+
+/// Renders a confirmation dialog for a pending action.
+fn draw_confirmation(f: &mut Frame, app: &App, action: &Confirmable) {
+    let area = centered_rect(48, 5, f.size());
+    let key = Style::default().fg(Color::White).add_modifier(Modifier::BOLD);
+    let text = vec![
+        Line::from(action.prompt()),
+        Line::from(vec![
+            Span::styled("y", key),
+            Span::raw("/"),
+            Span::styled("Enter", key),
+            Span::raw(" confirm   "),
+            Span::styled("n", key),
+            Span::raw("/"),
+            Span::styled("Esc", key),
+            Span::raw(" cancel"),
+        ]),
+    ];
+
+    let dialog = Paragraph::new(text)
+        .block(titled_block(app, " Confirm ", Color::Yellow).padding(Padding::new(1, 1, 0, 0)))
+        .alignment(Alignment::Center);
+
+    f.render_widget(Clear, area);
+    f.render_widget(dialog, area);
+}
+
+/// Renders the glossary popover, explaining the entry at `index`.
+fn draw_glossary(f: &mut Frame, app: &App, index: usize) {
+    let entries = crate::glossary::entries(app);
+    let Some(entry) = entries.get(index.min(entries.len().saturating_sub(1))) else {
+        return;
+    };
+    let width: u16 = 50;
+    // Room for the text wrapped inside the borders and padding, with a line
+    // to spare for words pushed over
+    let inner = width as usize - 4;
+    let lines = ((entry.text.chars().count() + inner - 1) / inner + 1) as u16;
+    let area = centered_rect(width, lines + 4, f.size());
+
+    let title = format!(" {} ({}/{}) ", entry.term, index + 1, entries.len());
+    let text = vec![
+        Line::from(entry.text),
+        Line::from(""),
+        Line::from(Span::styled("←/→ previous/next  Esc close", Style::default().fg(Color::Gray))),
+    ];
+    let popover = Paragraph::new(text)
+        .wrap(Wrap { trim: true })
+        .block(overlay_panel(app, title).padding(Padding::new(1, 1, 0, 0)));
+
+    f.render_widget(Clear, area);
+    f.render_widget(popover, area);
+}
+
+/// Renders the sort menu with the current level of each key.
+fn draw_sort_menu(f: &mut Frame, app: &App, row: usize) {
+    let area = centered_rect(40, SortKey::ALL.len() as u16 + 4, f.size());
+
+    let mut text: Vec<Line<'_>> = SortKey::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let level = app.sort.keys.iter().position(|field| field.key == *key);
+            let marker = match level {
+                Some(level) => format!("{} {}", level + 1, app.sort.keys[level]),
+                None => String::new(),
+            };
+            let style = if i == row {
+                Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(format!("{:<12}{:>14}", key.name(), marker), style))
+        })
+        .collect();
+    text.push(Line::from(""));
+    text.push(Line::from(Span::styled("Enter/1 primary  2 secondary  c clear", Style::default().fg(Color::Gray))));
+
+    let menu = Paragraph::new(text).block(overlay_panel(app, " Sort ").padding(Padding::new(1, 1, 0, 0)));
+
+    f.render_widget(Clear, area);
+    f.render_widget(menu, area);
+}
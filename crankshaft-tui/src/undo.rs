@@ -0,0 +1,106 @@
+//! Undo history for local-only actions.
+//!
+//! Actions that only change how this client presents tasks (pinning,
+//! archiving, renaming) are recorded so that an accidental key press can be
+//! reverted with `u`. Actions that reach the engine are never undone this
+//! way.
+
+use std::collections::VecDeque;
+
+/// Number of actions kept in the undo history.
+pub const UNDO_LIMIT: usize = 20;
+
+/// A reversible local action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalAction {
+    /// A task was pinned to the top of the list
+    Pin {
+        /// ID of the task
+        id: String,
+    },
+    /// A task was unpinned
+    Unpin {
+        /// ID of the task
+        id: String,
+    },
+    /// A task was hidden from the list
+    Archive {
+        /// ID of the task
+        id: String,
+    },
+    /// An archived task was shown again
+    Unarchive {
+        /// ID of the task
+        id: String,
+    },
+    /// A task's display name was changed
+    Rename {
+        /// ID of the task
+        id: String,
+        /// The previous display name, `None` for the backend's name
+        from: Option<String>,
+        /// The new display name, `None` for the backend's name
+        to: Option<String>,
+    },
+}
+
+impl LocalAction {
+    /// Returns the action that reverts this one.
+    pub fn inverse(&self) -> Self {
+        match self {
+            LocalAction::Pin { id } => LocalAction::Unpin { id: id.clone() },
+            LocalAction::Unpin { id } => LocalAction::Pin { id: id.clone() },
+            LocalAction::Archive { id } => LocalAction::Unarchive { id: id.clone() },
+            LocalAction::Unarchive { id } => LocalAction::Archive { id: id.clone() },
+            LocalAction::Rename { id, from, to } => LocalAction::Rename {
+                id: id.clone(),
+                from: to.clone(),
+                to: from.clone(),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for LocalAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LocalAction::Pin { id } => write!(f, "pin {}", id),
+            LocalAction::Unpin { id } => write!(f, "unpin {}", id),
+            LocalAction::Archive { id } => write!(f, "archive {}", id),
+            LocalAction::Unarchive { id } => write!(f, "unarchive {}", id),
+            LocalAction::Rename { id, .. } => write!(f, "rename of {}", id),
+        }
+    }
+}
+
+/// A bounded history of performed actions, most recent last.
+#[derive(Debug, Clone, Default)]
+pub struct UndoStack {
+    /// Performed actions
+    entries: VecDeque<LocalAction>,
+}
+
+impl UndoStack {
+    /// Records a performed action, forgetting the oldest one when full.
+    pub fn push(&mut self, action: LocalAction) {
+        if self.entries.len() == UNDO_LIMIT {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(action);
+    }
+
+    /// Removes and returns the most recent action.
+    pub fn pop(&mut self) -> Option<LocalAction> {
+        self.entries.pop_back()
+    }
+
+    /// Returns the number of actions that can be undone.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there is nothing to undo.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,112 @@
+//! Opt-in check for newer releases.
+//!
+//! When enabled, the latest published version is fetched from crates.io once
+//! on a background thread. A failed check is only logged; it never interrupts
+//! monitoring.
+
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use eyre::WrapErr;
+use serde::Deserialize;
+
+/// crates.io API endpoint describing this crate.
+const CRATES_IO_URL: &str = "https://crates.io/api/v1/crates/crankshaft-tui";
+
+/// Where release notes are published.
+pub const CHANGELOG_URL: &str = "https://github.com/stjude-rust-labs/crankshaft/releases";
+
+/// How long the check may take before it is abandoned.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A release newer than the running version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    /// The released version
+    pub version: String,
+    /// Where its changes are described
+    pub changelog: String,
+}
+
+/// Response of the crates.io crate endpoint, reduced to what is needed.
+#[derive(Deserialize)]
+struct CrateResponse {
+    /// The crate's details
+    #[serde(rename = "crate")]
+    krate: CrateInfo,
+}
+
+/// Details of the crate.
+#[derive(Deserialize)]
+struct CrateInfo {
+    /// Newest version that is not a pre-release, if one was published
+    max_stable_version: Option<String>,
+}
+
+/// A release check running in the background.
+pub struct UpdateCheck {
+    /// Receives the outcome once the check finishes
+    receiver: Receiver<eyre::Result<Option<Release>>>,
+}
+
+impl UpdateCheck {
+    /// Starts checking for a release newer than this build.
+    pub fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(check(env!("CARGO_PKG_VERSION")));
+        });
+        Self { receiver }
+    }
+
+    /// Returns the outcome if the check has finished.
+    pub fn poll(&self) -> Option<eyre::Result<Option<Release>>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(eyre::eyre!("update check stopped unexpectedly"))),
+        }
+    }
+}
+
+/// Fetches the latest stable version and compares it with `current`.
+fn check(current: &str) -> eyre::Result<Option<Release>> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .wrap_err("failed to start the update check runtime")?;
+
+    let response: CrateResponse = runtime.block_on(async {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("crankshaft-tui/", env!("CARGO_PKG_VERSION")))
+            .timeout(TIMEOUT)
+            .build()?;
+        client.get(CRATES_IO_URL).send().await?.error_for_status()?.json().await
+    })
+    .wrap_err("failed to query crates.io")?;
+
+    Ok(response
+        .krate
+        .max_stable_version
+        .filter(|latest| is_newer(latest, current))
+        .map(|version| Release {
+            version,
+            changelog: CHANGELOG_URL.to_string(),
+        }))
+}
+
+/// Returns `true` if dotted version `candidate` is greater than `current`.
+/// Pre-release and build suffixes are ignored.
+fn is_newer(candidate: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    parse(candidate) > parse(current)
+}
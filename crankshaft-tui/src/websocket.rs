@@ -0,0 +1,179 @@
+//! Task updates pushed over a WebSocket.
+//!
+//! An engine or relay that publishes its task events on a WebSocket feed is
+//! subscribed to once, and every update it pushes reaches the app on the
+//! next tick rather than after a polling cycle. Each text or binary message
+//! holds one [`TaskUpdate`] in the engine's event format (see
+//! [`crate::protocol`]), or several separated by newlines, so a feed can
+//! batch them:
+//!
+//! ```sh
+//! crankshaft-tui --websocket ws://127.0.0.1:7879/v1/events
+//! ```
+//!
+//! Pings are answered as they arrive. Updates that do not follow the format
+//! are counted as malformed in the diagnostics overlay, and progress is
+//! coalesced when the app falls behind the feed (see
+//! [`crate::backpressure`]). When the feed ends or drops, the tasks stay on
+//! screen while it is subscribed to again after a backoff (see
+//! [`crate::reconnect`]). A token for the feed is sent with the handshake
+//! (see [`crate::auth`]). A feed followed over `wss://` trusts the public
+//! certificate authorities and those of the `[tls]` section, presenting its
+//! client certificate to servers that ask for one (see [`crate::tls`]).
+
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use eyre::{eyre, WrapErr};
+use tungstenite::client::IntoClientRequest;
+use tungstenite::Connector;
+use tungstenite::Message as Frame;
+
+use crate::app::TaskUpdate;
+use crate::auth::{RemoteAccess, Unauthorized};
+use crate::backpressure::{self, Inbox, Message, Outbox};
+use crate::capabilities::SourceCapabilities;
+use crate::protocol::decode_update;
+use crate::reconnect::{self, Backoff};
+use crate::source::{DataSource, SourceEvent};
+
+/// How long opening the connection and the handshake may take before the
+/// feed is abandoned.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A subscription to a WebSocket feed, followed on a background thread.
+pub struct WebSocketDataSource {
+    /// URL of the feed
+    url: String,
+    /// Receives what happens on the connection
+    inbox: Inbox,
+    /// Events received with the last poll's updates, not yet taken
+    events: Vec<SourceEvent>,
+}
+
+impl WebSocketDataSource {
+    /// Starts subscribing to the feed at `url`, such as
+    /// `ws://127.0.0.1:7879/v1/events` or `wss://relay.example/v1/events`.
+    pub fn connect(url: &str) -> Self {
+        Self::connect_with(url, RemoteAccess::default())
+    }
+
+    /// Starts subscribing to the feed at `url`, sending `access`'s token
+    /// with the handshake and using its certificates over `wss://`.
+    pub fn connect_with(url: &str, access: RemoteAccess) -> Self {
+        let url = url.to_string();
+        let (sender, inbox) = backpressure::channel();
+        let feed = url.clone();
+        thread::spawn(move || {
+            reconnect::follow_with_retries(&feed, &sender, |backoff| {
+                follow(&feed, &access, &sender, backoff).map(|count| format!("the feed closed after {} updates", count))
+            });
+        });
+        Self {
+            url,
+            inbox,
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns the URL of the feed.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl DataSource for WebSocketDataSource {
+    fn name(&self) -> &str {
+        "websocket"
+    }
+
+    /// Everything updates can carry; the feed only flows towards the
+    /// monitor, so there is nobody to send control actions to.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            control: false,
+            ..SourceCapabilities::ALL
+        }
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        self.inbox.poll(&mut self.events)
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// Connects to the feed and hands over its updates until it closes, the
+/// connection fails, or the app is gone, returning the number of updates
+/// read. `backoff` is reset once subscribed.
+fn follow(url: &str, access: &RemoteAccess, sender: &Outbox, backoff: &mut Backoff) -> eyre::Result<usize> {
+    let token = access.token.as_deref();
+    let mut request = url.into_client_request().wrap_err_with(|| format!("{} is not a WebSocket URL", url))?;
+    if let Some(token) = token {
+        let value = format!("Bearer {}", token).parse().wrap_err("the token is not a valid header value")?;
+        request.headers_mut().insert(tungstenite::http::header::AUTHORIZATION, value);
+    }
+    let uri = request.uri();
+    let (default_port, connector) = match uri.scheme_str() {
+        Some("wss") => (443, Some(Connector::Rustls(access.tls.rustls_config().wrap_err("invalid TLS settings in `[tls]`")?))),
+        _ => (80, None),
+    };
+    let host = uri.host().unwrap_or_default().trim_start_matches('[').trim_end_matches(']');
+    let port = uri.port_u16().unwrap_or(default_port);
+
+    let started = Instant::now();
+    let address = (host, port)
+        .to_socket_addrs()
+        .wrap_err_with(|| format!("failed to resolve {}", host))?
+        .next()
+        .ok_or_else(|| eyre!("{} has no address", host))?;
+    let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).wrap_err_with(|| format!("failed to reach {}", address))?;
+    // Bounds the handshake; the feed itself may stay quiet for any time
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+    let tcp = stream.try_clone()?;
+    let (mut socket, _) = tungstenite::client_tls_with_config(request, stream, None, connector).map_err(|err| {
+        let refusal = match &err {
+            tungstenite::HandshakeError::Failure(tungstenite::Error::Http(response)) => Unauthorized::from_status("websocket", response.status().as_u16(), token.is_some()),
+            _ => None,
+        };
+        refusal.map_or_else(|| eyre!("the handshake failed: {}", err), eyre::Report::new)
+    })?;
+    tcp.set_read_timeout(None)?;
+    backoff.reset();
+    let connected = [
+        Message::Event(SourceEvent::Connected { latency: started.elapsed() }),
+        Message::Event(SourceEvent::Message(format!("Subscribed to {}", url))),
+    ];
+    for message in connected {
+        if sender.send(message).is_err() {
+            return Ok(0);
+        }
+    }
+
+    let mut count = 0;
+    loop {
+        let text = match socket.read() {
+            Ok(Frame::Text(text)) => text.to_string(),
+            Ok(Frame::Binary(bytes)) => String::from_utf8_lossy(&bytes).into_owned(),
+            Ok(Frame::Close(_)) | Err(tungstenite::Error::ConnectionClosed) => return Ok(count),
+            Ok(Frame::Ping(_) | Frame::Pong(_) | Frame::Frame(_)) => continue,
+            Err(err) => return Err(err).wrap_err("the feed was interrupted"),
+        };
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let message = match decode_update(line) {
+                Ok(update) => {
+                    count += 1;
+                    Message::Update(update)
+                }
+                Err(err) => Message::Event(SourceEvent::Malformed { kind: err.kind, detail: format!("{}: {}", err, line) }),
+            };
+            if sender.send(message).is_err() {
+                let _ = socket.close(None);
+                return Ok(count);
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! Workflow-level metadata reported by the data source.
+
+use serde::{Deserialize, Serialize};
+
+/// Information about the workflow run being monitored.
+///
+/// Every field is optional because sources differ in what they expose.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkflowMetadata {
+    /// Identifier of the run
+    pub run_id: Option<String>,
+    /// Name of the workflow definition
+    pub name: Option<String>,
+    /// Version of the workflow definition
+    pub version: Option<String>,
+    /// User who submitted the run
+    pub submitter: Option<String>,
+    /// Submission time, in seconds since the Unix epoch
+    pub submitted_at: Option<u64>,
+    /// Version of the engine executing the run
+    pub engine_version: Option<String>,
+}
+
+impl WorkflowMetadata {
+    /// Returns the label/value pairs shown in the metadata drawer, with
+    /// missing values rendered as a dash.
+    pub fn fields(&self) -> [(&'static str, String); 6] {
+        let or_dash = |value: &Option<String>| value.clone().unwrap_or_else(|| "—".to_string());
+        [
+            ("Run ID", or_dash(&self.run_id)),
+            ("Workflow", or_dash(&self.name)),
+            ("Version", or_dash(&self.version)),
+            ("Submitter", or_dash(&self.submitter)),
+            (
+                "Submitted",
+                self.submitted_at
+                    .map(crate::format::timestamp)
+                    .unwrap_or_else(|| "—".to_string()),
+            ),
+            ("Engine", or_dash(&self.engine_version)),
+        ]
+    }
+}
@@ -0,0 +1,149 @@
+//! Tests for alert rules evaluated over sampled task metrics.
+
+pub mod harness;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crankshaft_tui::{AlertRule, AlertRules, Alerts, Condition, Config, Metric, Severity, Task, TaskStatus, MAX_WINDOW};
+
+use harness::quiet::task;
+
+const MIB: f64 = (1 << 20) as f64;
+
+fn rule(metric: Metric, condition: Condition, threshold: f64, for_secs: u64) -> AlertRule {
+    AlertRule {
+        name: format!("{} {:?}", metric.name(), condition),
+        metric,
+        condition,
+        threshold,
+        for_secs,
+        severity: Severity::Warning,
+    }
+}
+
+fn alerts(rules: Vec<AlertRule>) -> Alerts {
+    let mut alerts = Alerts::default();
+    alerts.set_rules(&AlertRules { rules });
+    alerts
+}
+
+/// Returns one running task using `cpu` and `memory` bytes.
+fn running(cpu: f64, memory: f64) -> HashMap<String, Task> {
+    let mut task = task("a", "running");
+    task.cpu_usage = cpu;
+    task.memory_usage.used = memory as u64;
+    HashMap::from([("a".to_string(), task)])
+}
+
+/// Feeds one sample every ten seconds for `secs`, with the metrics
+/// `sample` gives for the seconds elapsed, returning the names of the
+/// rules raised along the way.
+fn feed(alerts: &mut Alerts, start: Instant, secs: u64, sample: impl Fn(f64) -> HashMap<String, Task>) -> Vec<String> {
+    (0..=secs)
+        .step_by(10)
+        .flat_map(|elapsed| alerts.observe(&sample(elapsed as f64), start + Duration::from_secs(elapsed)))
+        .map(|alert| alert.rule.name)
+        .collect()
+}
+
+#[test]
+fn values_above_the_threshold_for_the_whole_window_raise_an_alert_once() {
+    let mut alerts = alerts(vec![rule(Metric::Cpu, Condition::Above, 0.9, 60)]);
+    let start = Instant::now();
+
+    // Not before the window has passed
+    assert!(feed(&mut alerts, start, 50, |_| running(0.95, 0.0)).is_empty());
+    assert!(alerts.active().is_empty());
+
+    let raised = feed(&mut alerts, start + Duration::from_secs(60), 0, |_| running(0.97, 0.0));
+    assert_eq!(raised, ["cpu Above"]);
+    assert_eq!(alerts.severity("a"), Some(Severity::Warning));
+    assert_eq!(alerts.active()[0].observed, 0.97);
+
+    // Kept, not raised again, while the rule holds
+    let since = alerts.active()[0].since;
+    assert!(feed(&mut alerts, start + Duration::from_secs(70), 30, |_| running(0.99, 0.0)).is_empty());
+    assert_eq!(alerts.active().len(), 1);
+    assert_eq!(alerts.active()[0].since, since);
+
+    // One sample below clears it until a whole window is above again
+    assert!(feed(&mut alerts, start + Duration::from_secs(110), 0, |_| running(0.5, 0.0)).is_empty());
+    assert!(alerts.active().is_empty());
+    assert!(feed(&mut alerts, start + Duration::from_secs(120), 50, |_| running(0.95, 0.0)).is_empty());
+    assert_eq!(feed(&mut alerts, start + Duration::from_secs(180), 0, |_| running(0.95, 0.0)), ["cpu Above"]);
+}
+
+#[test]
+fn steady_climbs_faster_than_the_rate_raise_an_alert() {
+    let leak = || alerts(vec![rule(Metric::Memory, Condition::Rate, 100.0 * MIB, 600)]);
+    let start = Instant::now();
+
+    // 200 MiB a minute, with noise the fitted line sees through
+    let mut alerts = leak();
+    let raised = feed(&mut alerts, start, 600, |secs| {
+        let noise = if (secs as u64 / 10).is_multiple_of(2) { 30.0 * MIB } else { -30.0 * MIB };
+        running(0.0, 1024.0 * MIB + secs / 60.0 * 200.0 * MIB + noise)
+    });
+    assert_eq!(raised, ["memory Rate"]);
+    let observed = alerts.active()[0].observed / MIB;
+    assert!((observed - 200.0).abs() < 10.0, "{} MiB/min", observed);
+
+    // Slower climbs, flat usage, and falling usage do not
+    for per_minute in [50.0, 0.0, -200.0] {
+        let mut alerts = leak();
+        assert!(feed(&mut alerts, start, 900, |secs| running(0.0, 4096.0 * MIB + secs / 60.0 * per_minute * MIB)).is_empty());
+    }
+
+    // Nor does the climb of the last minutes alone
+    let mut alerts = leak();
+    let raised = feed(&mut alerts, start, 600, |secs| {
+        running(0.0, 1024.0 * MIB + (secs - 420.0).max(0.0) / 60.0 * 200.0 * MIB)
+    });
+    assert!(raised.is_empty(), "{:?}", raised);
+}
+
+#[test]
+fn finished_tasks_lose_their_history_and_alerts() {
+    let mut alerts = alerts(vec![rule(Metric::Cpu, Condition::Above, 0.5, 20)]);
+    let start = Instant::now();
+    assert_eq!(feed(&mut alerts, start, 20, |_| running(1.0, 0.0)), ["cpu Above"]);
+
+    let mut done = running(1.0, 0.0);
+    done.get_mut("a").unwrap().status = TaskStatus::Completed;
+    alerts.observe(&done, start + Duration::from_secs(30));
+    assert!(alerts.active().is_empty());
+
+    // Running again starts a fresh window
+    assert!(feed(&mut alerts, start + Duration::from_secs(40), 10, |_| running(1.0, 0.0)).is_empty());
+}
+
+#[test]
+fn samples_are_taken_once_per_interval() {
+    let mut alerts = alerts(vec![rule(Metric::Cpu, Condition::Above, 0.5, 20)]);
+    let start = Instant::now();
+    // A dip between samples goes unseen
+    alerts.observe(&running(1.0, 0.0), start);
+    alerts.observe(&running(0.1, 0.0), start + Duration::from_secs(5));
+    alerts.observe(&running(1.0, 0.0), start + Duration::from_secs(10));
+    assert_eq!(alerts.observe(&running(1.0, 0.0), start + Duration::from_secs(20)).len(), 1);
+}
+
+#[test]
+fn the_longest_window_still_raises_alerts() {
+    let mut alerts = alerts(vec![rule(Metric::Cpu, Condition::Above, 0.5, MAX_WINDOW.as_secs())]);
+    let start = Instant::now();
+    assert!(feed(&mut alerts, start, MAX_WINDOW.as_secs() - 10, |_| running(1.0, 0.0)).is_empty());
+    assert_eq!(feed(&mut alerts, start + MAX_WINDOW, 20, |_| running(1.0, 0.0)), ["cpu Above"]);
+    assert_eq!(alerts.active().len(), 1);
+}
+
+#[test]
+fn windows_longer_than_the_kept_metrics_are_refused() {
+    let rule = |for_secs: u64| format!("[[alerts.rules]]\nname = \"slow leak\"\nmetric = \"memory\"\ncondition = \"rate\"\nthreshold = 1\nfor_secs = {}", for_secs);
+    let config: Config = toml::from_str(&rule(7200)).unwrap();
+    assert_eq!(config.alerts.rules[0].for_secs, 7200);
+
+    let err = toml::from_str::<Config>(&rule(7201)).unwrap_err();
+    assert!(err.to_string().contains("`for_secs` is 7201, but metrics are only kept for 7200 seconds"), "{}", err);
+}
@@ -0,0 +1,89 @@
+//! Tests for flagging running tasks far slower than their step's median.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crankshaft_tui::{Anomalies, AnomalyConfig, Config, Task, TaskStatus};
+
+const NOW: u64 = 100_000;
+
+/// Returns a task of `name` started `secs` before [`NOW`], finished then if
+/// `status` says so.
+fn task(name: &str, status: &str, secs: u64) -> Task {
+    let mut task: Task = serde_json::from_value(serde_json::json!({ "id": name, "name": name, "status": status })).expect("a minimal task deserializes");
+    task.started_at = Some(NOW - secs);
+    if task.status == TaskStatus::Completed {
+        task.finished_at = Some(NOW);
+    }
+    task
+}
+
+/// Returns five completed shards of `step` taking 90s to 110s, median 100s,
+/// along with `others`.
+fn store(step: &str, others: impl IntoIterator<Item = Task>) -> HashMap<String, Task> {
+    (0..5)
+        .map(|shard| task(&format!("{} {}", step, shard), "completed", 90 + shard * 5))
+        .chain(others)
+        .map(|task| (task.id.clone(), task))
+        .collect()
+}
+
+/// Evaluates `tasks` now, returning the IDs flagged by this evaluation.
+fn flag(anomalies: &mut Anomalies, tasks: &HashMap<String, Task>, earlier: &BTreeMap<String, Vec<u64>>) -> Vec<String> {
+    // A new configuration skips the wait for the check interval
+    anomalies.set_config(*anomalies.config());
+    anomalies.observe(tasks, earlier, NOW).into_iter().map(|anomaly| anomaly.task_id).collect()
+}
+
+#[test]
+fn tasks_running_past_the_factor_of_the_median_are_flagged_once() {
+    let mut anomalies = Anomalies::default();
+    let tasks = store("align", [task("align 8", "running", 300), task("align 9", "running", 301), task("call 1", "running", 5000)]);
+    assert_eq!(flag(&mut anomalies, &tasks, &BTreeMap::new()), ["align 9"]);
+    let anomaly = anomalies.get("align 9").unwrap();
+    assert_eq!((anomaly.step.as_str(), anomaly.elapsed, anomaly.median), ("align", 301, 100));
+    assert!((anomaly.ratio() - 3.01).abs() < 1e-9);
+    // A step without completed tasks has no median to go by
+    assert!(anomalies.get("call 1").is_none());
+
+    // Still flagged, but not reported again
+    assert!(flag(&mut anomalies, &tasks, &BTreeMap::new()).is_empty());
+    assert_eq!(anomalies.len(), 1);
+
+    // Finishing clears the flag
+    let tasks = store("align", [task("align 9", "completed", 301)]);
+    assert!(flag(&mut anomalies, &tasks, &BTreeMap::new()).is_empty());
+    assert!(anomalies.is_empty());
+}
+
+#[test]
+fn medians_need_enough_completed_tasks() {
+    let mut anomalies = Anomalies::default();
+    let mut tasks = store("align", [task("align 9", "running", 1000)]);
+    tasks.remove("align 0");
+    assert!(flag(&mut anomalies, &tasks, &BTreeMap::new()).is_empty());
+
+    // Durations of tasks pruned from the store count towards the median
+    let earlier = BTreeMap::from([("align".to_string(), vec![100])]);
+    assert_eq!(flag(&mut anomalies, &tasks, &earlier), ["align 9"]);
+}
+
+#[test]
+fn evaluations_wait_for_the_check_interval() {
+    let mut anomalies = Anomalies::default();
+    let quiet = store("align", []);
+    assert!(anomalies.observe(&quiet, &BTreeMap::new(), NOW).is_empty());
+    let slow = store("align", [task("align 9", "running", 1000)]);
+    assert!(anomalies.observe(&slow, &BTreeMap::new(), NOW).is_empty());
+    assert!(anomalies.is_empty());
+}
+
+#[test]
+fn thresholds_are_configured_in_their_own_section() {
+    let config: Config = toml::from_str("[anomalies]\nfactor = 2\nmin_completed = 3").unwrap();
+    assert_eq!(config.anomalies, AnomalyConfig { factor: 2.0, min_completed: 3, alert: true });
+
+    let mut anomalies = Anomalies::default();
+    anomalies.set_config(config.anomalies);
+    let tasks = store("align", [task("align 9", "running", 250)]);
+    assert_eq!(flag(&mut anomalies, &tasks, &BTreeMap::new()), ["align 9"]);
+}
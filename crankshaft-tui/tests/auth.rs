@@ -0,0 +1,49 @@
+//! Tests for reading the tokens of remote sources from the configuration.
+
+use crankshaft_tui::{default_token_env, Config, Unauthorized};
+
+#[test]
+fn tokens_are_read_from_the_config_and_token_files() {
+    let dir = std::env::temp_dir().join(format!("crankshaft-tui-auth-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("tes-token");
+    std::fs::write(&path, "from-a-file\n").unwrap();
+
+    let config: Config = toml::from_str(&format!(
+        "[auth.engine]\ntoken = \"inline\"\n\n[auth.tes]\ntoken_file = {:?}\n\n[auth.k8s]\ntoken = \"\"\n",
+        path
+    ))
+    .unwrap();
+    let tokens = config.auth.tokens().unwrap();
+    assert_eq!(tokens.get("engine").map(String::as_str), Some("inline"));
+    assert_eq!(tokens.get("tes").map(String::as_str), Some("from-a-file"));
+    // An empty token is the same as none
+    assert_eq!(tokens.get("k8s"), None);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unreadable_tokens_and_unknown_sources_are_errors() {
+    let config: Config = toml::from_str("[auth.engine]\ntoken_env = \"CRANKSHAFT_TUI_TEST_UNSET_TOKEN\"").unwrap();
+    let err = config.auth.tokens().unwrap_err();
+    assert!(format!("{:#}", err).contains("`CRANKSHAFT_TUI_TEST_UNSET_TOKEN` is not set"), "{:#}", err);
+
+    let config: Config = toml::from_str("[auth.engin]\ntoken = \"typo\"").unwrap();
+    assert!(config.auth.tokens().is_err());
+}
+
+#[test]
+fn refusals_say_where_the_token_goes() {
+    assert_eq!(Unauthorized::from_status("engine", 404, false), None);
+
+    let missing = Unauthorized::from_status("engine", 401, false).unwrap();
+    assert_eq!(
+        missing.to_string(),
+        "the server requires a token (HTTP 401); set it in `[auth.engine]` of the config file or in CRANKSHAFT_TUI_ENGINE_TOKEN"
+    );
+    assert_eq!(default_token_env("engine"), "CRANKSHAFT_TUI_ENGINE_TOKEN");
+
+    let refused = Unauthorized::from_status("tes", 403, true).unwrap();
+    assert!(refused.to_string().starts_with("the server refused the tes token (HTTP 403)"));
+}
@@ -0,0 +1,86 @@
+//! Tests for reading jobs from AWS Batch job queues.
+//!
+//! The fixtures in `tests/fixtures/aws_batch` are `aws batch describe-jobs`
+//! and `aws batch list-jobs` output.
+
+use std::path::PathBuf;
+
+use crankshaft_tui::{aws_batch_status, aws_batch_task, TaskStatus, AWS_BATCH_LOG_GROUP};
+
+fn fixture(name: &str, key: &str) -> Vec<serde_json::Value> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/aws_batch").join(name);
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("failed to read {}: {}", path.display(), err));
+    let output: serde_json::Value = serde_json::from_str(&text).unwrap();
+    output[key].as_array().unwrap().clone()
+}
+
+#[test]
+fn statuses_map_onto_task_statuses() {
+    assert_eq!(aws_batch_status("SUBMITTED"), TaskStatus::Pending);
+    assert_eq!(aws_batch_status("PENDING"), TaskStatus::Pending);
+    assert_eq!(aws_batch_status("RUNNABLE"), TaskStatus::Pending);
+    assert_eq!(aws_batch_status("STARTING"), TaskStatus::Running);
+    assert_eq!(aws_batch_status("RUNNING"), TaskStatus::Running);
+    assert_eq!(aws_batch_status("SUCCEEDED"), TaskStatus::Completed);
+    assert_eq!(aws_batch_status("FAILED"), TaskStatus::Failed);
+}
+
+#[test]
+fn described_jobs_become_tasks() {
+    let jobs = fixture("describe-jobs.json", "jobs");
+    let queue = "arn:aws:batch:us-east-1:123456789012:job-queue/genomics";
+
+    let running = aws_batch_task(&jobs[0], queue).unwrap();
+    assert_eq!(running.id, "8f3c1e52-6a0e-4c8f-9d1b-2f1f7b1a0c01");
+    assert_eq!(running.name, "align_reads");
+    assert_eq!(running.status, TaskStatus::Running);
+    assert_eq!(running.labels["queue"], "genomics");
+    assert_eq!(running.labels["backend"], "aws-batch");
+    assert_eq!(running.labels["log_stream"], "bwa/default/4d2e9b1c7a3f4e0b8c6d5a4b3c2d1e0f");
+    assert_eq!(running.labels["log_group"], "/genomics/batch");
+    assert_eq!(running.cpus, Some(4.0));
+    assert_eq!(running.memory_usage.requested, Some(8192 << 20));
+    assert_eq!(running.started_at, Some(1_714_599_246));
+    assert_eq!(running.finished_at, None);
+    assert_eq!(running.command, ["bwa", "mem", "ref.fa", "reads.fq"]);
+    assert_eq!(running.env["THREADS"], "4");
+    assert_eq!(running.raw.as_ref(), Some(&jobs[0]));
+
+    // Jobs that have not started have no log stream yet
+    let pending = aws_batch_task(&jobs[1], queue).unwrap();
+    assert_eq!(pending.status, TaskStatus::Pending);
+    assert_eq!(pending.labels["reason"], "Waiting for compute resources");
+    assert!(!pending.labels.contains_key("log_stream"));
+    assert_eq!(pending.dependencies, ["8f3c1e52-6a0e-4c8f-9d1b-2f1f7b1a0c01"]);
+    assert_eq!(pending.cpus, Some(2.0));
+    assert_eq!(pending.memory_usage.requested, Some(4096 << 20));
+
+    // Summaries of jobs not yet described carry only names and status
+    let summaries = fixture("list-jobs.json", "jobSummaryList");
+    let submitted = aws_batch_task(&summaries[0], "genomics").unwrap();
+    assert_eq!(submitted.name, "fetch_inputs");
+    assert_eq!(submitted.status, TaskStatus::Pending);
+    assert_eq!(submitted.cpus, None);
+    assert_eq!(submitted.labels["queue"], "genomics");
+}
+
+#[test]
+fn finished_jobs_keep_their_times_exit_code_and_logs() {
+    let jobs = fixture("describe-jobs.json", "jobs");
+
+    let succeeded = aws_batch_task(&jobs[2], "genomics").unwrap();
+    assert_eq!(succeeded.status, TaskStatus::Completed);
+    assert_eq!(succeeded.progress, 1.0);
+    assert_eq!(succeeded.duration(1_714_600_000), Some(95));
+    assert_eq!(succeeded.labels["exit_code"], "0");
+    assert_eq!(succeeded.labels["log_group"], AWS_BATCH_LOG_GROUP);
+    assert!(!succeeded.labels.contains_key("reason"));
+
+    // The container's reason says more than the job's
+    let failed = aws_batch_task(&jobs[3], "genomics").unwrap();
+    assert_eq!(failed.status, TaskStatus::Failed);
+    assert_eq!(failed.labels["exit_code"], "137");
+    assert_eq!(failed.labels["reason"], "OutOfMemoryError: Container killed due to memory usage");
+    assert_eq!(failed.attempt, Some(2));
+    assert_eq!(failed.labels["log_stream"], "samtools/default/0f1e2d3c4b5a4968a7b6c5d4e3f2a1b0");
+}
@@ -0,0 +1,74 @@
+//! Tests for bookmarking moments of a session.
+
+pub mod harness;
+
+use crankshaft_tui::{App, DataSource, EventKind, History, Recorder, Replayer, SourceEvent};
+use crossterm::event::KeyCode;
+
+use harness::quiet::{press, Quiet};
+
+fn bookmarks(app: &App) -> Vec<&str> {
+    app.feed.visible().filter(|event| event.kind == EventKind::Bookmark).map(|event| event.text.as_str()).collect()
+}
+
+#[test]
+fn m_bookmarks_the_moment_with_a_note() {
+    let mut app = App::with_source(Quiet::NONE);
+    press(&mut app, KeyCode::Char('m'));
+    for c in "nfs blip".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    assert_eq!(app.bookmark_note.as_deref(), Some("nfs blip"));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.bookmark_note, None);
+
+    // Without a note the bookmark still counts; Esc drops it instead
+    press(&mut app, KeyCode::Char('m'));
+    press(&mut app, KeyCode::Enter);
+    press(&mut app, KeyCode::Char('m'));
+    press(&mut app, KeyCode::Char('x'));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(bookmarks(&app), ["bookmarked", "nfs blip"]);
+    assert!(!app.should_quit);
+}
+
+#[test]
+fn bookmarks_take_a_copy_for_the_history() {
+    let mut history = History::default();
+    history.capture([]);
+    history.bookmark("nfs blip");
+    history.capture([]);
+    history.capture([]);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.get(1).unwrap().bookmarks, ["nfs blip"]);
+
+    let taken_at = history.get(0).unwrap().taken_at;
+    assert_eq!(history.bookmark_copy(0, "earlier"), Some(taken_at));
+    assert_eq!(history.get(0).unwrap().bookmarks, ["earlier"]);
+    assert_eq!(history.bookmark_copy(5, "nowhere"), None);
+}
+
+#[test]
+fn recorded_bookmarks_come_back_in_the_replay() {
+    let path = std::env::temp_dir().join(format!("crankshaft-tui-bookmarks-{}.jsonl", std::process::id()));
+    {
+        let mut recorder = Recorder::create(&path).unwrap();
+        recorder.record([]).unwrap();
+        recorder.bookmark("nfs blip");
+        // Written at once, although the interval has not passed
+        recorder.record([]).unwrap();
+    }
+
+    let mut replay = Replayer::open(&path).unwrap();
+    replay.poll();
+    let events = replay.events();
+    assert!(events.contains(&SourceEvent::Bookmark("nfs blip".to_string())), "{:?}", events);
+
+    // The replayed bookmark shows in the feed and the history
+    let mut app = App::with_replay(Replayer::open(&path).unwrap());
+    app.update();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(bookmarks(&app), ["nfs blip"]);
+    let last = app.history.get(app.history.len() - 1).unwrap();
+    assert_eq!(last.bookmarks, ["nfs blip"]);
+}
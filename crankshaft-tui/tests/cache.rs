@@ -0,0 +1,207 @@
+//! Tests for caching and rate limiting log fetches and task polls.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crankshaft_tui::{
+    App, CachedProvider, CachedSource, Config, DataSource, FetchConfig, FetchStats, LogChunk, LogProvider, LogRange,
+    SourceCapabilities, SourceEvent, TaskStatus, TaskUpdate,
+};
+
+/// A provider counting the fetches that reach it, failing the first if
+/// asked to.
+struct Counting {
+    calls: Arc<AtomicUsize>,
+    fail_first: bool,
+}
+
+impl LogProvider for Counting {
+    fn fetch(&mut self, task_id: &str, range: LogRange) -> eyre::Result<LogChunk> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) == 0 && self.fail_first {
+            eyre::bail!("engine unavailable");
+        }
+        let start = match range {
+            LogRange::From(start) => start,
+            LogRange::Tail(_) => 0,
+        };
+        Ok(LogChunk { start, lines: vec![format!("{} line {}", task_id, start)] })
+    }
+}
+
+fn provider(config: FetchConfig) -> (CachedProvider, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counting = Counting { calls: Arc::clone(&calls), fail_first: false };
+    (CachedProvider::new(counting, config), calls)
+}
+
+#[test]
+fn repeated_fetches_are_answered_from_kept_responses() {
+    let (mut provider, calls) = provider(FetchConfig::default());
+    let first = provider.fetch("a", LogRange::From(3)).unwrap();
+    let again = provider.fetch("a", LogRange::From(3)).unwrap();
+    assert_eq!(again.start, first.start);
+    assert_eq!(again.lines, first.lines);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Another range or task is fetched afresh
+    provider.fetch("a", LogRange::From(4)).unwrap();
+    provider.fetch("b", LogRange::From(3)).unwrap();
+    provider.fetch("a", LogRange::Tail(500)).unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+    assert_eq!(provider.control().stats(), FetchStats { upstream: 4, cached: 1, throttled: 0, coalesced: 0 });
+}
+
+#[test]
+fn caching_can_be_turned_off_and_failures_are_not_kept() {
+    let (mut provider, calls) = provider(FetchConfig { cache_secs: 0.0, ..FetchConfig::default() });
+    provider.fetch("a", LogRange::From(0)).unwrap();
+    provider.fetch("a", LogRange::From(0)).unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let flaky = Counting { calls: Arc::clone(&calls), fail_first: true };
+    let mut provider = CachedProvider::new(flaky, FetchConfig::default());
+    assert!(provider.fetch("a", LogRange::Tail(10)).is_err());
+    assert!(provider.fetch("a", LogRange::Tail(10)).is_ok());
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn upstream_calls_are_spaced_out_to_the_rate() {
+    let (mut provider, calls) = provider(FetchConfig { cache_secs: 0.0, max_per_second: 10.0 });
+    let started = Instant::now();
+    // A second's worth go at once, the next waits its turn
+    for _ in 0..10 {
+        provider.fetch("a", LogRange::From(0)).unwrap();
+    }
+    assert_eq!(provider.control().stats().throttled, 0);
+    provider.fetch("a", LogRange::From(0)).unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(80), "{:?}", started.elapsed());
+    assert_eq!(provider.control().stats().throttled, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 11);
+
+    // Lifting the limit takes effect on the next fetch
+    provider.control().set_config(FetchConfig { cache_secs: 0.0, max_per_second: 0.0 });
+    for _ in 0..20 {
+        provider.fetch("a", LogRange::From(0)).unwrap();
+    }
+    assert_eq!(provider.control().stats().throttled, 1);
+}
+
+/// A source handing over one scripted batch of updates each time it is
+/// polled, counting the polls that reach it.
+struct Scripted {
+    polls: Arc<AtomicUsize>,
+    batches: VecDeque<Vec<TaskUpdate>>,
+}
+
+impl DataSource for Scripted {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::ALL
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        self.polls.fetch_add(1, Ordering::SeqCst);
+        self.batches.pop_front().unwrap_or_default()
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        vec![SourceEvent::Message("polled".to_string())]
+    }
+}
+
+fn source(batches: Vec<Vec<TaskUpdate>>, config: FetchConfig) -> (CachedSource, Arc<AtomicUsize>) {
+    let polls = Arc::new(AtomicUsize::new(0));
+    let scripted = Scripted { polls: Arc::clone(&polls), batches: batches.into() };
+    (CachedSource::new(Box::new(scripted), config), polls)
+}
+
+fn progress(id: &str, progress: f64) -> TaskUpdate {
+    TaskUpdate::Progress { id: id.to_string(), progress }
+}
+
+fn cpu(id: &str, cpu_usage: f64) -> TaskUpdate {
+    TaskUpdate::Metrics { id: id.to_string(), cpu_usage: Some(cpu_usage), memory_usage: None }
+}
+
+/// Describes an update briefly, for comparing batches.
+fn describe(update: &TaskUpdate) -> String {
+    match update {
+        TaskUpdate::Progress { id, progress } => format!("{} at {}", id, progress),
+        TaskUpdate::Metrics { id, cpu_usage, .. } => format!("{} using {:?}", id, cpu_usage),
+        TaskUpdate::StatusChanged { id, status } => format!("{} now {:?}", id, status),
+        other => format!("{:?}", other),
+    }
+}
+
+#[test]
+fn repeated_updates_within_a_tick_are_coalesced() {
+    let batch = vec![
+        progress("a", 0.1),
+        cpu("a", 0.5),
+        progress("b", 0.2),
+        progress("a", 0.3),
+        cpu("a", 0.7),
+        TaskUpdate::StatusChanged { id: "a".to_string(), status: TaskStatus::Completed },
+        // Reported after the status change, so it stays after it
+        progress("a", 1.0),
+    ];
+    let (mut source, polls) = source(vec![batch], FetchConfig::default());
+    let updates: Vec<String> = source.poll().iter().map(describe).collect();
+    assert_eq!(updates, ["a at 0.3", "a using Some(0.7)", "b at 0.2", "a now Completed", "a at 1"]);
+    assert_eq!(polls.load(Ordering::SeqCst), 1);
+    assert_eq!(source.control().stats(), FetchStats { upstream: 1, cached: 0, throttled: 0, coalesced: 2 });
+}
+
+#[test]
+fn polls_beyond_the_rate_wait_for_a_later_tick() {
+    let batches = (0..12).map(|tick| vec![progress("a", tick as f64 / 100.0)]).collect();
+    let (mut source, polls) = source(batches, FetchConfig { cache_secs: 0.0, max_per_second: 10.0 });
+    let now = Instant::now();
+    for _ in 0..10 {
+        assert_eq!(source.poll_at(now).len(), 1);
+        assert_eq!(source.events().len(), 1);
+    }
+
+    // The source is not asked, and keeps its updates and events for later
+    assert!(source.poll_at(now).is_empty());
+    assert!(source.events().is_empty());
+    assert_eq!(polls.load(Ordering::SeqCst), 10);
+    assert_eq!(source.control().stats().throttled, 1);
+
+    let updates: Vec<String> = source.poll_at(now + Duration::from_millis(120)).iter().map(describe).collect();
+    assert_eq!(updates, ["a at 0.1"]);
+    assert_eq!(source.events().len(), 1);
+    assert_eq!(polls.load(Ordering::SeqCst), 11);
+}
+
+#[test]
+fn the_app_polls_its_source_through_the_limit() {
+    let polls = Arc::new(AtomicUsize::new(0));
+    let scripted = Scripted { polls: Arc::clone(&polls), batches: VecDeque::new() };
+    let mut app = App::with_source(scripted);
+    app.set_fetch_config(FetchConfig { cache_secs: 0.0, max_per_second: 1.0 });
+    for _ in 0..5 {
+        app.update();
+    }
+    // Besides the poll on creation, only the one the budget allows within
+    // the second goes through
+    assert_eq!(polls.load(Ordering::SeqCst), 2);
+    assert_eq!(app.source_fetch_stats().upstream, 2);
+    assert_eq!(app.source_fetch_stats().throttled, 4);
+}
+
+#[test]
+fn fetching_is_configured_in_its_own_section() {
+    let config: Config = toml::from_str("[fetch]\ncache_secs = 2\nmax_per_second = 0").unwrap();
+    assert_eq!(config.fetch.cache_for(), Duration::from_secs(2));
+    assert_eq!(config.fetch.rate(), None);
+    assert_eq!(Config::default().fetch, FetchConfig::default());
+    assert!(toml::from_str::<Config>("[fetch]\nburst = 3").is_err());
+}
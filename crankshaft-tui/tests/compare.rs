@@ -0,0 +1,89 @@
+//! Tests for diffing a task against a reference task, in the app and in
+//! external diff tools.
+
+use std::path::PathBuf;
+
+use crankshaft_tui::{compare_tasks, tes_task, write_spec, ExternalCommand, NumberFormat, Task};
+
+fn tes_tasks() -> Vec<serde_json::Value> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tes/tasks.json");
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("failed to read {}: {}", path.display(), err));
+    let listing: serde_json::Value = serde_json::from_str(&text).unwrap();
+    listing["tasks"].as_array().unwrap().clone()
+}
+
+fn task(value: serde_json::Value) -> Task {
+    serde_json::from_value(value).expect("the task deserializes")
+}
+
+#[test]
+fn identical_tasks_have_no_differences() {
+    let reference = tes_task(&tes_tasks()[0]).unwrap();
+    let diffs = compare_tasks(&reference, &reference, &NumberFormat::default());
+    assert!(!diffs.is_empty());
+    assert!(diffs.iter().all(|diff| !diff.differs()));
+}
+
+#[test]
+fn differing_fields_are_picked_out() {
+    let reference = task(serde_json::json!({
+        "id": "good", "name": "align", "status": "completed", "progress": 1.0, "cpu_usage": 0.0,
+        "memory_usage": { "used": 0, "limit": 8000000000u64 },
+        "cpus": 4.0,
+        "command": ["bwa", "mem", "ref.fa"],
+        "env": { "JAVA_OPTS": "-Xmx4g", "TMPDIR": "/scratch" },
+    }));
+    let task = task(serde_json::json!({
+        "id": "bad", "name": "align", "status": "failed", "progress": 0.4, "cpu_usage": 0.0,
+        "memory_usage": { "used": 0, "limit": 8000000000u64 },
+        "cpus": 4.0,
+        "command": ["bwa", "mem", "ref.fa"],
+        "env": { "JAVA_OPTS": "-Xmx2g" },
+    }));
+
+    let diffs = compare_tasks(&reference, &task, &NumberFormat::default());
+    let differing: Vec<&str> = diffs.iter().filter(|diff| diff.differs()).map(|diff| diff.field.as_str()).collect();
+    assert_eq!(differing, ["env JAVA_OPTS", "env TMPDIR"]);
+
+    let tmpdir = diffs.iter().find(|diff| diff.field == "env TMPDIR").unwrap();
+    assert_eq!(tmpdir.reference.as_deref(), Some("/scratch"));
+    assert_eq!(tmpdir.task, None);
+    // Fields neither task sets are not listed
+    assert!(diffs.iter().all(|diff| diff.field != "memory requested"));
+}
+
+#[test]
+fn executors_are_compared_one_by_one() {
+    let tasks = tes_tasks();
+    let (aligned, called) = (tes_task(&tasks[0]).unwrap(), tes_task(&tasks[1]).unwrap());
+    let diffs = compare_tasks(&aligned, &called, &NumberFormat::default());
+
+    let image = diffs.iter().find(|diff| diff.field == "executor 1 image").unwrap();
+    assert_eq!(image.reference.as_deref(), Some("ubuntu:22.04"));
+    assert_eq!(image.task.as_deref(), Some("broadinstitute/gatk:4.5.0.0"));
+    let env = diffs.iter().find(|diff| diff.field == "executor 1 env JAVA_OPTS").unwrap();
+    assert_eq!((env.reference.as_deref(), env.task.as_deref()), (None, Some("-Xmx2g")));
+    // Only the reference has a second executor
+    let second = diffs.iter().find(|diff| diff.field == "executor 2 command").unwrap();
+    assert_eq!(second.reference.as_deref(), Some("bwa mem ref.fa reads.fq"));
+    assert_eq!(second.task, None);
+}
+
+#[test]
+fn external_diff_tools_get_both_specs() {
+    let tasks = tes_tasks();
+    let mut reference = tes_task(&tasks[0]).unwrap();
+    reference.id = "runs/5f2a".to_string();
+    let task = tes_task(&tasks[1]).unwrap();
+
+    let (reference_path, task_path) = (write_spec(&reference).unwrap(), write_spec(&task).unwrap());
+    // IDs are made safe to use as file names
+    assert_eq!(reference_path.file_name().unwrap(), "runs_5f2a.json");
+    let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&reference_path).unwrap()).unwrap();
+    assert_eq!(written, tasks[0]);
+
+    let command = ExternalCommand::diff("vimdiff -R", &reference_path, &task_path).unwrap();
+    assert_eq!(command.program, "vimdiff");
+    assert_eq!(command.args, ["-R".to_string(), reference_path.display().to_string(), task_path.display().to_string()]);
+    assert_eq!(ExternalCommand::diff("  ", &reference_path, &task_path), None);
+}
@@ -0,0 +1,25 @@
+//! Tests for the control socket of a running instance.
+#![cfg(unix)]
+
+use crankshaft_tui::ControlServer;
+
+#[test]
+fn only_a_stale_socket_is_replaced() {
+    let dir = std::env::temp_dir().join(format!("crankshaft-tui-control-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // A file given by mistake is kept, and binding fails
+    let file = dir.join("tasks.jsonl");
+    std::fs::write(&file, "{}\n").unwrap();
+    let err = ControlServer::bind(&file).err().expect("a regular file is not replaced");
+    assert!(err.to_string().contains("is not a socket"), "{}", err);
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "{}\n");
+
+    // A socket nobody listens on any more is
+    let socket = dir.join("control.sock");
+    drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+    let server = ControlServer::bind(&socket).unwrap();
+    assert!(ControlServer::bind(&socket).is_err(), "another instance took over the socket");
+    drop(server);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,29 @@
+//! Tests for crash report bundles.
+
+use crankshaft_tui::{set_config_path, write_bundle};
+
+#[test]
+fn secrets_are_redacted_from_the_bundled_config() {
+    let dir = std::env::temp_dir().join(format!("crankshaft-tui-crash-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("config.toml");
+    std::fs::write(
+        &config,
+        "[auth]\nengine = { token = \"abc\" }\n\n[auth.tes]\npassword = \"def\"\nuser = \"alice\"\n\n\
+         [[alerts.rules]]\nname = \"leak\"\napi_key = \"ghi\"\n",
+    )
+    .unwrap();
+    set_config_path(Some(config));
+
+    let bundle = write_bundle("test failure").unwrap();
+    let written = std::fs::read_to_string(bundle.join("config.toml")).unwrap();
+    let _ = std::fs::remove_dir_all(&bundle);
+    let _ = std::fs::remove_dir_all(&dir);
+    for secret in ["abc", "def", "ghi"] {
+        assert!(!written.contains(secret), "{} is in\n{}", secret, written);
+    }
+    let table: toml::Table = written.parse().unwrap();
+    assert_eq!(table["auth"]["engine"]["token"].as_str(), Some("<redacted>"));
+    assert_eq!(table["auth"]["tes"]["user"].as_str(), Some("alice"));
+    assert_eq!(table["alerts"]["rules"][0]["name"].as_str(), Some("leak"));
+}
@@ -0,0 +1,93 @@
+//! Tests for discovering backends from the engine's configuration file.
+
+use crankshaft_tui::{discover_backends, parse_engine_config, DiscoveredBackend, DiscoveredSource};
+
+fn sources(text: &str) -> Vec<(String, DiscoveredSource)> {
+    parse_engine_config(text)
+        .unwrap()
+        .into_iter()
+        .map(|DiscoveredBackend { name, backend }| (name, backend))
+        .collect()
+}
+
+#[test]
+fn each_backend_gets_the_source_that_follows_it() {
+    let found = sources(
+        r#"
+[[backends]]
+name = "containers"
+type = "Docker"
+max_tasks = 10
+
+[[backends]]
+name = "cloud"
+type = "TES"
+url = "https://tes.example.org/ga4gh/tes/v1"
+
+[[backends]]
+name = "cluster"
+type = "Generic"
+submit = "/usr/bin/sbatch --parsable --wrap '~{command}'"
+
+[[backends]]
+name = "farm"
+kind = { Generic = { submit = "bsub -q long < ~{script}" } }
+"#,
+    );
+    assert_eq!(
+        found,
+        [
+            ("containers".to_string(), DiscoveredSource::Docker),
+            ("cloud".to_string(), DiscoveredSource::Tes("https://tes.example.org/ga4gh/tes/v1".to_string())),
+            ("cluster".to_string(), DiscoveredSource::Slurm),
+            ("farm".to_string(), DiscoveredSource::Lsf),
+        ]
+    );
+}
+
+#[test]
+fn backends_that_cannot_be_followed_say_why() {
+    let found = sources(
+        r#"
+[[backends]]
+name = "remote"
+type = "Generic"
+submit = "sbatch ~{script}"
+[backends.driver]
+locale = "SSH"
+
+[[backends]]
+name = "shell"
+type = "Generic"
+submit = "bash ~{script}"
+
+[[backends]]
+type = "Podman"
+"#,
+    );
+    let reasons: Vec<_> = found
+        .iter()
+        .map(|(name, source)| match source {
+            DiscoveredSource::Unsupported(reason) => format!("{}: {}", name, reason),
+            other => panic!("{} should not be followed, but is followed as {:?}", name, other),
+        })
+        .collect();
+    assert_eq!(
+        reasons,
+        [
+            "remote: its commands run on another host",
+            "shell: its scheduler is not recognized",
+            "backend 3: backend type `podman` is not supported",
+        ]
+    );
+
+    // Other sections of the engine's file are ignored
+    assert!(sources("[engine]\nworkers = 4").is_empty());
+}
+
+#[test]
+fn unreadable_files_name_the_file() {
+    let err = discover_backends("/nonexistent/crankshaft.toml".as_ref()).unwrap_err();
+    assert!(format!("{:#}", err).contains("failed to read `/nonexistent/crankshaft.toml`"), "{:#}", err);
+    assert!(parse_engine_config("backends = 3").is_err());
+}
@@ -0,0 +1,119 @@
+//! Tests for reading containers from the Docker daemon.
+//!
+//! The fixtures in `tests/fixtures/docker` are responses of the Docker
+//! Engine API; a stand-in daemon serves them over a Unix socket.
+
+#![cfg(unix)]
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixListener;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crankshaft_tui::{docker_status, docker_task, DataSource, DockerDataSource, TaskStatus, TaskUpdate};
+
+fn fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/docker").join(name);
+    std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("failed to read {}: {}", path.display(), err))
+}
+
+/// Serves the fixtures on `socket`, with stats of a container that keeps
+/// one of the host's four CPUs busy.
+fn serve(socket: &Path) {
+    let listener = UnixListener::bind(socket).unwrap();
+    thread::spawn(move || {
+        for (sample, stream) in listener.incoming().enumerate() {
+            let mut stream = stream.unwrap();
+            let mut request = String::new();
+            BufReader::new(&stream).read_line(&mut request).unwrap();
+            let path = request.split_whitespace().nth(1).unwrap_or_default();
+            let (status, body) = if path.starts_with("/containers/json?") {
+                assert!(path.contains("crankshaft.task"), "containers are not filtered by label: {}", path);
+                ("200 OK", fixture("containers.json"))
+            } else if path.starts_with("/containers/8f14e45fceea167a5a36dedd4bea2543/json") {
+                ("200 OK", fixture("inspect-running.json"))
+            } else if path.starts_with("/containers/c9f0f895fb98ab9159f51fd0297e236d/json") {
+                ("200 OK", fixture("inspect-exited.json"))
+            } else if path.starts_with("/containers/8f14e45fceea167a5a36dedd4bea2543/stats?") {
+                let n = sample as u64;
+                let stats = serde_json::json!({
+                    "cpu_stats": { "cpu_usage": { "total_usage": n * 1_000_000_000 }, "system_cpu_usage": n * 4_000_000_000u64, "online_cpus": 4 },
+                    "memory_stats": { "usage": 3_000_000_000u64, "limit": 8_589_934_592u64, "stats": { "inactive_file": 1_000_000_000u64 } },
+                    "networks": { "eth0": { "rx_bytes": 1000, "tx_bytes": 200 }, "eth1": { "rx_bytes": 24, "tx_bytes": 0 } },
+                    "blkio_stats": { "io_service_bytes_recursive": [{ "op": "read", "value": 4096 }, { "op": "write", "value": 512 }] },
+                    "pids_stats": { "current": 7 },
+                });
+                ("200 OK", stats.to_string())
+            } else {
+                ("404 Not Found", r#"{"message":"page not found"}"#.to_string())
+            };
+            let _ = write!(stream, "HTTP/1.0 {}\r\nContent-Type: application/json\r\n\r\n{}", status, body);
+        }
+    });
+}
+
+#[test]
+fn states_map_onto_statuses() {
+    assert_eq!(docker_status("created", 0), TaskStatus::Pending);
+    assert_eq!(docker_status("running", 0), TaskStatus::Running);
+    assert_eq!(docker_status("paused", 0), TaskStatus::Running);
+    assert_eq!(docker_status("exited", 0), TaskStatus::Completed);
+    assert_eq!(docker_status("exited", 137), TaskStatus::Failed);
+    assert_eq!(docker_status("dead", 1), TaskStatus::Failed);
+}
+
+#[test]
+fn inspected_containers_become_tasks() {
+    let value: serde_json::Value = serde_json::from_str(&fixture("inspect-running.json")).unwrap();
+    let task = docker_task(&value).unwrap();
+    assert_eq!(task.id, "crankshaft-align-1");
+    assert_eq!(task.name, "align_reads");
+    assert_eq!(task.status, TaskStatus::Running);
+    assert_eq!(task.cpus, Some(4.0));
+    assert_eq!(task.memory_usage.limit, Some(8 << 30));
+    assert_eq!(task.started_at, Some(1_714_564_800));
+    assert_eq!(task.finished_at, None);
+    assert_eq!(task.command, ["bwa", "mem", "-t", "4", "ref.fa", "reads.fq"]);
+    assert_eq!(task.env["THREADS"], "4");
+    assert_eq!(task.labels["backend"], "docker");
+
+    let value: serde_json::Value = serde_json::from_str(&fixture("inspect-exited.json")).unwrap();
+    let task = docker_task(&value).unwrap();
+    assert_eq!(task.status, TaskStatus::Failed);
+    assert_eq!(task.finished_at, Some(1_714_567_500));
+    assert!(task.env.is_empty());
+    assert_eq!(task.memory_usage.limit, None);
+}
+
+#[test]
+fn running_containers_report_live_stats() {
+    let socket = std::env::temp_dir().join(format!("crankshaft-tui-docker-test-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&socket);
+    serve(&socket);
+    let mut docker = DockerDataSource::connect(&socket);
+
+    // The first sample has nothing to average CPU use against
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let stats = loop {
+        assert!(Instant::now() < deadline, "no CPU use was reported");
+        let running = docker.poll().into_iter().find_map(|update| match update {
+            TaskUpdate::Created(task) if task.id == "crankshaft-align-1" => Some(task),
+            _ => None,
+        });
+        if let Some(stats) = running.and_then(|task| task.container.filter(|stats| stats.cpus > 0.0).map(|stats| (task, stats))) {
+            break stats;
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+    let _ = std::fs::remove_file(&socket);
+
+    let (task, container) = stats;
+    assert!((container.cpus - 1.0).abs() < 1e-9, "{} CPUs", container.cpus);
+    assert_eq!(container.cpu_limit, 4.0);
+    assert!((task.cpu_usage - 0.25).abs() < 1e-9);
+    assert_eq!(task.memory_usage.used, 2_000_000_000);
+    assert_eq!((container.network_rx, container.network_tx), (1024, 200));
+    assert_eq!((container.block_read, container.block_write), (4096, 512));
+    assert_eq!(container.pids, 7);
+}
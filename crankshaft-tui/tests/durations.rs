@@ -0,0 +1,69 @@
+//! Tests for the duration percentiles of each step.
+
+use std::collections::BTreeMap;
+
+use crankshaft_tui::{durations_by_step, durations_by_step_including, StepDurations, Task};
+
+/// Returns a task of step `name` that ran for `secs`, finished if
+/// `status` says so.
+fn task(name: &str, status: &str, secs: u64) -> Task {
+    let mut task: Task = serde_json::from_value(serde_json::json!({ "id": name, "name": name, "status": status })).expect("a minimal task deserializes");
+    task.started_at = Some(1_000);
+    task.finished_at = Some(1_000 + secs);
+    task
+}
+
+fn step(step: &str, count: usize, p50: u64, p90: u64, max: u64) -> StepDurations {
+    StepDurations { step: step.to_string(), count, p50, p90, max }
+}
+
+#[test]
+fn percentiles_use_the_nearest_rank() {
+    // Shards 1 to 10 took 10s to 100s
+    let tasks: Vec<Task> = (1..=10).map(|shard| task(&format!("align (shard {})", shard), "completed", shard * 10)).collect();
+    assert_eq!(durations_by_step(&tasks), [step("align", 10, 50, 90, 100)]);
+
+    // One shard is its own median and tail
+    assert_eq!(durations_by_step([&task("call-1", "completed", 7)]), [step("call", 1, 7, 7, 7)]);
+
+    let tasks = [task("sort 1", "completed", 4), task("sort 2", "completed", 2), task("sort 3", "completed", 9)];
+    assert_eq!(durations_by_step(&tasks), [step("sort", 3, 4, 9, 9)]);
+}
+
+#[test]
+fn only_completed_tasks_with_both_times_count() {
+    let mut unstarted = task("align_3", "completed", 0);
+    unstarted.started_at = None;
+    let tasks = [
+        task("align_1", "completed", 30),
+        task("align_2", "failed", 500),
+        task("align_4", "running", 900),
+        unstarted,
+    ];
+    assert_eq!(durations_by_step(&tasks), [step("align", 1, 30, 30, 30)]);
+    assert!(durations_by_step(&[task("align_1", "running", 30)]).is_empty());
+}
+
+#[test]
+fn steps_with_the_widest_spread_come_first() {
+    let tasks = [
+        task("align 1", "completed", 100),
+        task("align 2", "completed", 110),
+        task("call 1", "completed", 10),
+        task("call 2", "completed", 400),
+        task("index", "completed", 5),
+        task("merge", "completed", 5),
+    ];
+    let steps = durations_by_step(&tasks);
+    let order: Vec<&str> = steps.iter().map(|step| step.step.as_str()).collect();
+    // Equal spreads stay in name order
+    assert_eq!(order, ["call", "align", "index", "merge"]);
+    assert_eq!(steps[0].spread(), 390);
+}
+
+#[test]
+fn durations_of_pruned_tasks_are_counted_too() {
+    let earlier = BTreeMap::from([("align".to_string(), vec![20, 40]), ("qc".to_string(), vec![3])]);
+    let steps = durations_by_step_including([&task("align 3", "completed", 60)], &earlier);
+    assert_eq!(steps, [step("align", 3, 40, 60, 60), step("qc", 1, 3, 3, 3)]);
+}
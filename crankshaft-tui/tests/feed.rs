@@ -0,0 +1,68 @@
+//! Tests for the Events tab's feed of notable moments.
+
+pub mod harness;
+
+use crankshaft_tui::{App, EventKind, Tab, Task, TaskStatus, TaskUpdate};
+use crossterm::event::KeyCode;
+
+use harness::quiet::{press, Quiet};
+
+fn task(id: &str) -> Task {
+    serde_json::from_value(serde_json::json!({ "id": id, "name": id, "status": "pending" })).expect("a minimal task deserializes")
+}
+
+fn listed(app: &App) -> Vec<(EventKind, Option<&str>)> {
+    app.feed.visible().map(|event| (event.kind, event.task_id.as_deref())).collect()
+}
+
+/// An app where `a` started and failed, then `b` started.
+fn run() -> App {
+    let mut app = App::with_source(Quiet::NONE);
+    for id in ["a", "b"] {
+        app.apply_update(TaskUpdate::Created(Box::new(task(id))));
+    }
+    for (id, status) in [("a", TaskStatus::Running), ("a", TaskStatus::Failed), ("b", TaskStatus::Running)] {
+        app.apply_update(TaskUpdate::StatusChanged { id: id.to_string(), status });
+    }
+    app
+}
+
+#[test]
+fn status_changes_are_listed_newest_first() {
+    let app = run();
+    assert_eq!(
+        listed(&app),
+        [(EventKind::Started, Some("b")), (EventKind::Failed, Some("a")), (EventKind::Started, Some("a"))]
+    );
+    assert_eq!(app.feed.selected().map(|event| event.text.as_str()), Some("started running"));
+    assert_eq!("events".parse::<Tab>(), Ok(Tab::Events));
+}
+
+#[test]
+fn the_feed_filters_by_kind_and_jumps_to_tasks() {
+    let mut app = run();
+    app.set_tab(Tab::Events);
+
+    press(&mut app, KeyCode::Char('f'));
+    assert_eq!(app.feed.filter(), Some(EventKind::Started));
+    assert_eq!(listed(&app), [(EventKind::Started, Some("b")), (EventKind::Started, Some("a"))]);
+    for _ in 1..EventKind::ALL.len() {
+        press(&mut app, KeyCode::Char('f'));
+    }
+    assert_eq!(app.feed.filter(), Some(EventKind::Alert));
+    assert!(listed(&app).is_empty());
+    press(&mut app, KeyCode::Char('f'));
+    assert_eq!(app.feed.filter(), None);
+
+    // The arrows move through the feed rather than the task list
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.feed.selected_index(), 2);
+    press(&mut app, KeyCode::Up);
+    assert_eq!(app.feed.selected().and_then(|event| event.task_id.as_deref()), Some("a"));
+
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.current_tab(), Tab::Tasks);
+    assert_eq!(app.selected_task_id.as_deref(), Some("a"));
+}
@@ -0,0 +1,86 @@
+//! Tests for following a state file the engine writes.
+
+use std::path::PathBuf;
+
+use crankshaft_tui::{DataSource, FileDataSource, SourceEvent, TaskStatus, TaskUpdate};
+
+/// Returns a path for a state file in a fresh temporary directory.
+fn state_file(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("crankshaft-tui-file-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join("tasks.json")
+}
+
+fn created(updates: &[TaskUpdate]) -> Vec<(String, TaskStatus)> {
+    updates
+        .iter()
+        .filter_map(|update| match update {
+            TaskUpdate::Created(task) => Some((task.id.clone(), task.status)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn changes_to_a_document_become_updates() {
+    let path = state_file("document");
+    std::fs::write(
+        &path,
+        r#"{"tasks": [
+            {"id": "align", "name": "align reads", "status": "running", "progress": 0.5, "started_at": 1700000000},
+            {"id": "call", "name": "call variants", "status": "pending"}
+        ]}"#,
+    )
+    .unwrap();
+    let mut source = FileDataSource::watch(&path);
+    let updates = source.poll();
+    assert_eq!(created(&updates), [("align".to_string(), TaskStatus::Running), ("call".to_string(), TaskStatus::Pending)]);
+    assert!(source.events().contains(&SourceEvent::Message(format!("Read 2 tasks from {}", path.display()))));
+    assert!(source.poll().is_empty());
+
+    // Only what changed is sent again, and missing tasks are removed
+    std::fs::write(&path, r#"[{"id": "align", "name": "align reads", "status": "completed", "progress": 1.0}]"#).unwrap();
+    let updates = source.poll();
+    assert_eq!(created(&updates), [("align".to_string(), TaskStatus::Completed)]);
+    assert!(matches!(&updates[1], TaskUpdate::Removed { id } if id == "call"));
+}
+
+#[test]
+fn json_lines_skip_bad_lines_and_wait_for_unfinished_ones() {
+    let path = state_file("lines");
+    std::fs::write(
+        &path,
+        concat!(
+            "{\"id\": \"align\", \"name\": \"align reads\", \"status\": \"running\"}\n",
+            "not a task\n",
+            "{\"id\": \"call\", \"name\": \"call variants\", \"status\": \"pending\"}\n",
+            "{\"id\": \"align\", \"name\": \"align reads\", \"status\": \"failed\"}\n",
+        ),
+    )
+    .unwrap();
+    let mut source = FileDataSource::watch(&path);
+    let updates = source.poll();
+    assert_eq!(created(&updates), [("align".to_string(), TaskStatus::Failed), ("call".to_string(), TaskStatus::Pending)]);
+    let events = source.events();
+    assert!(events.iter().any(|event| matches!(event, SourceEvent::Dropped(detail) if detail.contains("line 2"))));
+
+    // Caught halfway through a rewrite: the tasks stay as they were
+    std::fs::write(&path, "{\"id\": \"align\", \"name\": \"align reads\", \"status\": \"completed\"}\n{\"id\": \"ca").unwrap();
+    assert!(source.poll().is_empty());
+    assert!(source.events().iter().any(|event| matches!(event, SourceEvent::Failed(_))));
+}
+
+#[test]
+fn a_missing_file_is_reported_once_until_it_appears() {
+    let path = state_file("missing");
+    let mut source = FileDataSource::watch(&path);
+    assert!(source.poll().is_empty());
+    assert!(matches!(source.events()[..], [SourceEvent::Failed(_)]));
+    assert!(source.poll().is_empty());
+    assert!(source.events().is_empty());
+
+    std::fs::write(&path, "{\"id\": \"align\", \"name\": \"align reads\", \"status\": \"running\"}\n").unwrap();
+    assert_eq!(created(&source.poll()), [("align".to_string(), TaskStatus::Running)]);
+    assert!(source.events().iter().any(|event| matches!(event, SourceEvent::Connected { .. })));
+}
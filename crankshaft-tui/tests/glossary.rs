@@ -0,0 +1,56 @@
+//! Tests for the glossary popover explaining statuses and figures.
+
+pub mod harness;
+
+use crankshaft_tui::{glossary_entries, glossary_lookup, App, KeyMode, Task, TaskUpdate, GLOSSARY};
+use crossterm::event::KeyCode;
+
+use harness::quiet::{press, Quiet};
+
+fn terms(app: &App) -> Vec<&'static str> {
+    glossary_entries(app).into_iter().map(|entry| entry.term).collect()
+}
+
+#[test]
+fn every_status_is_explained() {
+    for status in ["Pending", "Running", "Completed", "Failed"] {
+        assert!(glossary_lookup(status).is_some(), "{} is not in the glossary", status);
+    }
+    assert_eq!(glossary_lookup("cpu usage").map(|entry| entry.term), Some("CPU usage"));
+    assert_eq!(glossary_lookup("Stuck"), None);
+}
+
+#[test]
+fn the_popover_explains_what_the_selected_task_shows() {
+    let mut app = App::with_source(Quiet::NONE);
+    assert_eq!(glossary_entries(&app).len(), GLOSSARY.len());
+
+    let task: Task = serde_json::from_value(serde_json::json!({
+        "id": "align",
+        "name": "align",
+        "status": "pending",
+        "attempt": 2,
+        "queue": { "position": 4 },
+    }))
+    .unwrap();
+    app.apply_update(TaskUpdate::Created(Box::new(task)));
+    app.selected_task_id = Some("align".to_string());
+    // Without metrics from the source, CPU and memory go unexplained
+    assert_eq!(terms(&app), ["Pending", "Requeued", "Queue position", "Progress"]);
+
+    press(&mut app, KeyCode::Char('?'));
+    assert_eq!(KeyMode::of(&app), KeyMode::Glossary);
+    assert_eq!(app.glossary, Some(0));
+    press(&mut app, KeyCode::Left);
+    assert_eq!(app.glossary, Some(3));
+    press(&mut app, KeyCode::Right);
+    press(&mut app, KeyCode::Right);
+    assert_eq!(app.glossary, Some(1));
+
+    // Other keys are held while the popover is open
+    press(&mut app, KeyCode::Char('q'));
+    assert!(!app.should_quit);
+    press(&mut app, KeyCode::Char('?'));
+    assert_eq!(app.glossary, None);
+    assert_eq!(KeyMode::of(&app), KeyMode::Tasks);
+}
@@ -0,0 +1,63 @@
+//! Tests for charts drawn with the terminal's graphics protocol.
+
+#![cfg(feature = "graphics")]
+
+use crankshaft_tui::{Graphics, GraphicsProtocol, Image};
+use ratatui::layout::Rect;
+
+fn bar() -> Image {
+    let mut image = Image::new(16, 8);
+    image.fill((2, 2), (10, 6), [255, 0, 0, 255], |x, _| x % 2 == 0);
+    image
+}
+
+#[test]
+fn fills_follow_their_pattern_and_stay_inside_the_image() {
+    let mut image = bar();
+    assert_eq!(image.pixel(2, 2), [255, 0, 0, 255]);
+    assert_eq!(image.pixel(3, 2), [0, 0, 0, 0]);
+    assert_eq!(image.pixel(2, 6), [0, 0, 0, 0]);
+
+    image.fill((12, 0), (100, 100), [0, 0, 255, 255], |_, _| true);
+    assert_eq!(image.pixel(15, 7), [0, 0, 255, 255]);
+}
+
+#[test]
+fn images_encode_as_png() {
+    let png = bar().to_png();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(&png[16..24], &[0, 0, 0, 16, 0, 0, 0, 8]);
+    // An empty IEND chunk, with its well-known checksum
+    assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+}
+
+#[test]
+fn images_are_only_sent_when_they_change_and_removed_when_gone() {
+    let graphics = Graphics::new(GraphicsProtocol::Kitty);
+    let area = Rect::new(17, 3, 2, 1);
+    let flush = |graphics: &Graphics| {
+        let mut out = Vec::new();
+        let clear = graphics.flush(&mut out).unwrap();
+        (String::from_utf8(out).unwrap(), clear)
+    };
+
+    graphics.place(area, bar());
+    let (sent, clear) = flush(&graphics);
+    assert!(!clear);
+    assert!(sent.starts_with("\x1b[4;18H\x1b_Ga=T,f=32,o=z,s=16,v=8,c=2,r=1,"), "{:?}", sent);
+
+    graphics.place(area, bar());
+    assert_eq!(flush(&graphics), (String::new(), false));
+
+    let (sent, _) = flush(&graphics);
+    assert!(sent.starts_with("\x1b_Ga=d,"), "{:?}", sent);
+    assert_eq!(flush(&graphics), (String::new(), false));
+
+    // iTerm2's images go once their cells are redrawn
+    let graphics = Graphics::new(GraphicsProtocol::Iterm);
+    graphics.place(area, bar());
+    let (sent, _) = flush(&graphics);
+    assert!(sent.contains("\x1b]1337;File=inline=1;"), "{:?}", sent);
+    assert!(flush(&graphics).1);
+}
@@ -0,0 +1,10 @@
+//! Helpers shared by the integration tests.
+//!
+//! [`pty`] drives the compiled monitor in a pseudo-terminal, and [`quiet`]
+//! builds apps whose tasks and events come from the test alone. Test files
+//! declare the harness `pub`, so the helpers one of them leaves unused are
+//! not reported as dead code.
+
+#[cfg(unix)]
+pub mod pty;
+pub mod quiet;
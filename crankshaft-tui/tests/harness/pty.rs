@@ -0,0 +1,447 @@
+//! Drives the compiled monitor in a pseudo-terminal, expect-style.
+//!
+//! A [`Session`] starts the binary on the slave side of a PTY of a fixed
+//! size, keeps a [`Screen`] up to date from what it draws, and lets a test
+//! type keys and wait for text to appear or disappear. Each session runs
+//! with its own home, configuration, state, and control socket in a
+//! temporary directory, so sessions neither see the user's files nor each
+//! other's.
+//!
+//! The screen model understands what the dashboard draws with: cursor
+//! moves, erasing, and printable text. Colors and other attributes are
+//! dropped, and every character takes one cell, which is enough to find
+//! text since the dashboard positions the cursor before each run of cells.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::fd::{FromRawFd, OwnedFd};
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Columns of the session's terminal.
+pub const COLUMNS: u16 = 120;
+
+/// Rows of the session's terminal.
+pub const ROWS: u16 = 40;
+
+/// Longest a session waits for the screen to show what is expected.
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Keys as the terminal sends them.
+pub mod keys {
+    /// Enter
+    pub const ENTER: &str = "\r";
+    /// Escape
+    pub const ESC: &str = "\x1b";
+    /// Tab
+    pub const TAB: &str = "\t";
+    /// Shift-Tab
+    pub const BACK_TAB: &str = "\x1b[Z";
+    /// Up arrow
+    pub const UP: &str = "\x1b[A";
+    /// Down arrow
+    pub const DOWN: &str = "\x1b[B";
+}
+
+/// The text a terminal shows, without colors.
+#[derive(Debug, Clone)]
+pub struct Screen {
+    /// Characters by row and column
+    cells: Vec<Vec<char>>,
+    /// Cursor row
+    row: usize,
+    /// Cursor column, past the last one when the next character wraps
+    column: usize,
+    /// Bytes of an escape sequence or a character not yet complete
+    pending: Vec<u8>,
+}
+
+impl Screen {
+    /// Creates a blank screen.
+    pub fn new(columns: u16, rows: u16) -> Self {
+        Self {
+            cells: vec![vec![' '; columns as usize]; rows as usize],
+            row: 0,
+            column: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Applies output of the program.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+        let mut start = 0;
+        while start < self.pending.len() {
+            match self.step(start) {
+                Some(end) => start = end,
+                None => break,
+            }
+        }
+        self.pending.drain(..start);
+    }
+
+    /// Returns the rows of the screen, without trailing blanks.
+    pub fn lines(&self) -> Vec<String> {
+        self.cells.iter().map(|row| row.iter().collect::<String>().trim_end().to_string()).collect()
+    }
+
+    /// Returns `true` if `text` shows on one of the rows.
+    pub fn contains(&self, text: &str) -> bool {
+        self.lines().iter().any(|line| line.contains(text))
+    }
+
+    /// Interprets the character or sequence starting at `start`, returning
+    /// where the next one starts, or `None` if it is not complete yet.
+    fn step(&mut self, start: usize) -> Option<usize> {
+        let bytes = &self.pending[start..];
+        match bytes[0] {
+            0x1b => {
+                let end = sequence_end(bytes)?;
+                let sequence = bytes[..end].to_vec();
+                self.escape(&sequence);
+                Some(start + end)
+            }
+            b'\r' => {
+                self.column = 0;
+                Some(start + 1)
+            }
+            b'\n' => {
+                self.line_feed();
+                Some(start + 1)
+            }
+            0x08 => {
+                self.column = self.column.saturating_sub(1);
+                Some(start + 1)
+            }
+            b'\t' => {
+                self.column = ((self.column / 8 + 1) * 8).min(self.columns() - 1);
+                Some(start + 1)
+            }
+            byte if byte < 0x20 || byte == 0x7f => Some(start + 1),
+            byte => {
+                let len = match byte {
+                    0xc0..=0xdf => 2,
+                    0xe0..=0xef => 3,
+                    0xf0..=0xf7 => 4,
+                    _ => 1,
+                };
+                if bytes.len() < len {
+                    return None;
+                }
+                let c = std::str::from_utf8(&bytes[..len]).ok().and_then(|s| s.chars().next()).unwrap_or('\u{fffd}');
+                self.put(c);
+                Some(start + len)
+            }
+        }
+    }
+
+    /// Applies a complete escape sequence.
+    fn escape(&mut self, sequence: &[u8]) {
+        // Only control sequences (`ESC [`) affect the text; titles, graphics,
+        // and the like are dropped
+        if sequence.get(1) != Some(&b'[') {
+            return;
+        }
+        let Some((&last, body)) = sequence[2..].split_last() else {
+            return;
+        };
+        if body.first().is_some_and(|b| matches!(b, b'?' | b'>' | b'<' | b'=')) {
+            return;
+        }
+        let params: Vec<usize> =
+            String::from_utf8_lossy(body).split(';').map(|param| param.parse().unwrap_or(0)).collect();
+        let param = |index: usize, default: usize| params.get(index).copied().filter(|value| *value != 0).unwrap_or(default);
+        let (rows, columns) = (self.rows(), self.columns());
+        match last {
+            b'H' | b'f' => {
+                self.row = (param(0, 1) - 1).min(rows - 1);
+                self.column = (param(1, 1) - 1).min(columns - 1);
+            }
+            b'A' => self.row = self.row.saturating_sub(param(0, 1)),
+            b'B' => self.row = (self.row + param(0, 1)).min(rows - 1),
+            b'C' => self.column = (self.column + param(0, 1)).min(columns - 1),
+            b'D' => self.column = self.column.saturating_sub(param(0, 1)),
+            b'G' => self.column = (param(0, 1) - 1).min(columns - 1),
+            b'd' => self.row = (param(0, 1) - 1).min(rows - 1),
+            b'J' => {
+                let (row, column) = (self.row, self.column.min(columns));
+                match params.first().copied().unwrap_or(0) {
+                    0 => {
+                        self.cells[row][column..].fill(' ');
+                        self.cells[row + 1..].iter_mut().for_each(|line| line.fill(' '));
+                    }
+                    1 => {
+                        self.cells[..row].iter_mut().for_each(|line| line.fill(' '));
+                        self.cells[row][..column].fill(' ');
+                    }
+                    _ => self.cells.iter_mut().for_each(|line| line.fill(' ')),
+                }
+            }
+            b'K' => {
+                let (row, column) = (self.row, self.column.min(columns));
+                match params.first().copied().unwrap_or(0) {
+                    0 => self.cells[row][column..].fill(' '),
+                    1 => self.cells[row][..column].fill(' '),
+                    _ => self.cells[row].fill(' '),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Writes a character at the cursor and moves past it.
+    fn put(&mut self, c: char) {
+        if self.column >= self.columns() {
+            self.column = 0;
+            self.line_feed();
+        }
+        self.cells[self.row][self.column] = c;
+        self.column += 1;
+    }
+
+    /// Moves to the next row, scrolling at the bottom.
+    fn line_feed(&mut self) {
+        if self.row + 1 < self.rows() {
+            self.row += 1;
+        } else {
+            let columns = self.columns();
+            self.cells.remove(0);
+            self.cells.push(vec![' '; columns]);
+        }
+    }
+
+    fn rows(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn columns(&self) -> usize {
+        self.cells[0].len()
+    }
+}
+
+impl std::fmt::Display for Screen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in self.lines() {
+            writeln!(f, "|{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the length of the escape sequence at the start of `bytes`, or
+/// `None` if it is not complete yet.
+fn sequence_end(bytes: &[u8]) -> Option<usize> {
+    match bytes.get(1)? {
+        // Control sequence: parameters and intermediates, then a final byte
+        b'[' => bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b)).map(|end| end + 3),
+        // Strings (OSC, DCS, APC, ...), ended by BEL or ST
+        b']' | b'P' | b'_' | b'^' | b'X' => {
+            let body = &bytes[2..];
+            body.iter().enumerate().find_map(|(index, b)| match b {
+                0x07 => Some(index + 3),
+                0x1b if body.get(index + 1) == Some(&b'\\') => Some(index + 4),
+                _ => None,
+            })
+        }
+        // Character set designations take one more byte
+        b'(' | b')' | b'*' | b'+' => bytes.get(2).map(|_| 3),
+        _ => Some(2),
+    }
+}
+
+/// The monitor running in a pseudo-terminal.
+pub struct Session {
+    /// The program
+    child: Child,
+    /// Master side of the PTY, where keys are written
+    master: File,
+    /// Output of the program, read on a thread of its own
+    output: Receiver<Vec<u8>>,
+    /// What the program shows
+    screen: Screen,
+    /// Home, configuration, and state of the session
+    dir: PathBuf,
+}
+
+impl Session {
+    /// Starts the monitor with `args`, showing the demo data unless they
+    /// choose another source.
+    pub fn start(args: &[&str]) -> Self {
+        Self::start_with_config(args, "")
+    }
+
+    /// Starts the monitor with `args` and a configuration file holding
+    /// `config`.
+    pub fn start_with_config(args: &[&str], config: &str) -> Self {
+        static SESSIONS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let number = SESSIONS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("crankshaft-tui-session-{}-{}", std::process::id(), number));
+        std::fs::create_dir_all(&dir).expect("the session directory can be created");
+        let config_path = dir.join("config.toml");
+        std::fs::write(&config_path, config).expect("the configuration can be written");
+
+        let (master, slave) = open_pty();
+        let stdio = |fd: &OwnedFd| Stdio::from(fd.try_clone().expect("the PTY can be shared"));
+        let mut command = Command::new(env!("CARGO_BIN_EXE_crankshaft-tui"));
+        command
+            .arg("--config")
+            .arg(&config_path)
+            .arg("--control-socket")
+            .arg(dir.join("control.sock"))
+            .arg("--state-file")
+            .arg(dir.join("state.json"))
+            .args(args)
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .env("HOME", &dir)
+            .env("XDG_CONFIG_HOME", &dir)
+            .env("XDG_STATE_HOME", &dir)
+            .env("XDG_RUNTIME_DIR", &dir)
+            .env("TERM", "xterm-256color")
+            .stdin(stdio(&slave))
+            .stdout(stdio(&slave))
+            .stderr(stdio(&slave));
+        // SAFETY: only async-signal-safe calls between fork and exec, making
+        // the PTY the controlling terminal of a new session
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() < 0 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command.spawn().expect("the monitor starts");
+        drop(slave);
+
+        let master = File::from(master);
+        let mut reader = master.try_clone().expect("the PTY can be read");
+        let (sender, output) = mpsc::channel();
+        thread::spawn(move || {
+            let mut buf = [0; 4096];
+            // Reading fails once the program is gone and the slave closed
+            while let Ok(read @ 1..) = reader.read(&mut buf) {
+                if sender.send(buf[..read].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            child,
+            master,
+            output,
+            screen: Screen::new(COLUMNS, ROWS),
+            dir,
+        }
+    }
+
+    /// Types `keys`, as given by [`keys`] or as plain text.
+    pub fn send(&mut self, keys: &str) {
+        self.master.write_all(keys.as_bytes()).expect("keys can be sent");
+        self.master.flush().expect("keys can be sent");
+        // Lets the app read a lone Esc before the next key arrives
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    /// Returns what the screen currently shows.
+    pub fn screen(&mut self) -> &Screen {
+        while let Ok(bytes) = self.output.try_recv() {
+            self.screen.feed(&bytes);
+        }
+        &self.screen
+    }
+
+    /// Waits until the screen shows `text`, panicking with the screen after
+    /// [`TIMEOUT`].
+    pub fn expect(&mut self, text: &str) -> &Screen {
+        self.wait_for(|screen| screen.contains(text), &format!("`{}` to show", text));
+        &self.screen
+    }
+
+    /// Waits until the screen no longer shows `text`.
+    pub fn expect_gone(&mut self, text: &str) -> &Screen {
+        self.wait_for(|screen| !screen.contains(text), &format!("`{}` to go", text));
+        &self.screen
+    }
+
+    /// Runs `crankshaft-tui ctl` with `args` against this session's control
+    /// socket.
+    pub fn ctl(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_crankshaft-tui"))
+            .arg("--control-socket")
+            .arg(self.dir.join("control.sock"))
+            .arg("ctl")
+            .args(args)
+            .output()
+            .expect("ctl runs")
+    }
+
+    /// Waits for the program to exit, panicking after [`TIMEOUT`].
+    pub fn wait_exit(&mut self) -> ExitStatus {
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            if let Some(status) = self.child.try_wait().expect("the monitor can be waited for") {
+                return status;
+            }
+            if Instant::now() >= deadline {
+                panic!("the monitor did not exit; the screen shows:\n{}", self.screen());
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    fn wait_for(&mut self, done: impl Fn(&Screen) -> bool, what: &str) {
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            if done(self.screen()) {
+                return;
+            }
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.output.recv_timeout(left) {
+                Ok(bytes) => self.screen.feed(&bytes),
+                Err(RecvTimeoutError::Timeout) => panic!("timed out waiting for {}; the screen shows:\n{}", what, self.screen),
+                Err(RecvTimeoutError::Disconnected) => {
+                    panic!("the monitor exited while waiting for {}; the screen shows:\n{}", what, self.screen)
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Opens a PTY of [`COLUMNS`] by [`ROWS`], returning its master and slave.
+fn open_pty() -> (OwnedFd, OwnedFd) {
+    let mut master = -1;
+    let mut slave = -1;
+    let size = || libc::winsize {
+        ws_row: ROWS,
+        ws_col: COLUMNS,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // Linux takes the size as constant, the BSDs and macOS as mutable
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    let size = &size();
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    let size = &mut size();
+    // SAFETY: the pointers are valid for the call, and the descriptors
+    // returned are owned by nothing else
+    unsafe {
+        if libc::openpty(&mut master, &mut slave, std::ptr::null_mut(), std::ptr::null_mut(), size) != 0 {
+            panic!("cannot open a PTY: {}", std::io::Error::last_os_error());
+        }
+        (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave))
+    }
+}
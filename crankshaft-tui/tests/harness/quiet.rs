@@ -0,0 +1,43 @@
+//! A source with nothing to report, and the keys pressed in its app.
+
+use crankshaft_tui::{App, DataSource, SourceCapabilities, Task, TaskUpdate};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// A source with nothing to report, so tasks and events come from the test
+/// alone, offering what its capabilities say.
+pub struct Quiet(pub SourceCapabilities);
+
+impl Quiet {
+    /// A quiet source offering nothing beyond the task list.
+    pub const NONE: Quiet = Quiet(SourceCapabilities::NONE);
+
+    /// A quiet source offering task logs.
+    pub const LOGS: Quiet = Quiet(SourceCapabilities {
+        logs: true,
+        ..SourceCapabilities::NONE
+    });
+}
+
+impl DataSource for Quiet {
+    fn name(&self) -> &str {
+        "quiet"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        self.0
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        Vec::new()
+    }
+}
+
+/// Presses `code` without modifiers.
+pub fn press(app: &mut App, code: KeyCode) {
+    app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+}
+
+/// Returns a task with only an id, a name, and `status` set.
+pub fn task(id: &str, status: &str) -> Task {
+    serde_json::from_value(serde_json::json!({ "id": id, "name": id, "status": status })).expect("a minimal task deserializes")
+}
@@ -0,0 +1,74 @@
+//! Tests for scrubbing back through the session's history with `[` and `]`.
+
+pub mod harness;
+
+use crankshaft_tui::{App, Confirmable, History, LocalAction, TaskStatus, TaskUpdate};
+use crossterm::event::KeyCode;
+
+use harness::quiet::{press, task, Quiet};
+
+fn status(app: &App, id: &str) -> TaskStatus {
+    app.tasks[id].status
+}
+
+#[test]
+fn copies_are_taken_once_per_interval_unless_bookmarked() {
+    let mut history = History::default();
+    let running = task("a", "running");
+    history.capture([&running]);
+    history.capture([&running, &running]);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.retained_tasks(), 1);
+
+    history.bookmark("retry storm");
+    history.capture([&running, &running]);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.retained_tasks(), 3);
+    assert_eq!(history.get(1).unwrap().tasks.len(), 2);
+    assert!(history.get(2).is_none());
+}
+
+#[test]
+fn scrubbing_shows_an_older_copy_until_back_to_live() {
+    let mut app = App::with_source(Quiet::NONE);
+    press(&mut app, KeyCode::Char('['));
+    assert!(!app.is_viewing_history());
+    assert_eq!(app.status(), Some("No history yet"));
+
+    app.apply_update(TaskUpdate::Created(Box::new(task("a", "running"))));
+    app.update();
+    app.apply_update(TaskUpdate::StatusChanged { id: "a".to_string(), status: TaskStatus::Completed });
+    app.apply_update(TaskUpdate::Created(Box::new(task("b", "pending"))));
+
+    press(&mut app, KeyCode::Char('['));
+    assert!(app.is_viewing_history());
+    assert_eq!(app.task_ids, ["a"]);
+    assert_eq!(status(&app, "a"), TaskStatus::Running);
+    // There is nothing older to go back to
+    press(&mut app, KeyCode::Char('['));
+    assert_eq!(app.viewed_snapshot().unwrap().tasks.len(), 1);
+
+    // Updates wait, and local actions are refused, while history is shown
+    app.apply_update(TaskUpdate::StatusChanged { id: "b".to_string(), status: TaskStatus::Running });
+    assert!(!app.tasks.contains_key("b"));
+    assert!(!app.perform(LocalAction::Pin { id: "a".to_string() }));
+    assert!(app.pinned.is_empty());
+    assert!(app.undo.is_empty());
+    // The refusal is what the status says, not a claim that it was done
+    app.confirm_policy.archive = Some(false);
+    app.request(Confirmable::Archive { id: "a".to_string() });
+    assert_eq!(app.status(), Some("History is read-only; press ] to return to live"));
+
+    press(&mut app, KeyCode::Char(']'));
+    assert!(!app.is_viewing_history());
+    assert_eq!(app.task_ids, ["a", "b"]);
+    assert_eq!(status(&app, "a"), TaskStatus::Completed);
+    assert_eq!(status(&app, "b"), TaskStatus::Running);
+
+    app.request(Confirmable::Archive { id: "b".to_string() });
+    assert_eq!(app.status(), Some("Archived b (u to undo)"));
+    // Archiving it again changes nothing, and says nothing
+    app.set_status("");
+    app.request(Confirmable::Archive { id: "b".to_string() });
+    assert_eq!(app.status(), Some(""));
+}
@@ -0,0 +1,130 @@
+//! Tests for watching Kubernetes Jobs.
+//!
+//! The fixture in `tests/fixtures/k8s` is a pod list as the API server
+//! returns it; a stand-in server serves it and then a watch.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crankshaft_tui::{k8s_status, k8s_task, App, K8sTarget, TaskStatus};
+
+fn pod_list() -> serde_json::Value {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/k8s/pods.json");
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("failed to read {}: {}", path.display(), err));
+    serde_json::from_str(&text).unwrap()
+}
+
+fn pods_of(job: &str) -> Vec<serde_json::Value> {
+    let list = pod_list();
+    list["items"].as_array().unwrap().iter().filter(|pod| pod["metadata"]["labels"]["job-name"] == job).cloned().collect()
+}
+
+/// Serves the pod list, then a watch in which the running pod succeeds and
+/// the other Job's pod is deleted. Later watches stay quiet; requests that
+/// are not as expected fail the assertions in the serving thread, which
+/// leaves the app waiting.
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for (index, stream) in listener.incoming().enumerate() {
+            let mut stream = stream.unwrap();
+            thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                reader.read_line(&mut request).unwrap();
+                // Skip the headers
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                let path = request.split_whitespace().nth(1).unwrap_or_default().to_string();
+                assert!(path.starts_with("/api/v1/namespaces/genomics/pods?"), "unexpected request: {}", path);
+                assert!(path.contains("labelSelector=job-name"), "pods are not filtered by label: {}", path);
+                let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nConnection: close\r\n\r\n");
+                if !path.contains("watch=true") {
+                    let _ = write!(stream, "{}", pod_list());
+                    return;
+                }
+                // Watches pick up from the last version seen
+                let version = if index > 1 { "resourceVersion=1201" } else { "resourceVersion=1200" };
+                assert!(path.contains(version), "the watch does not start from {}: {}", version, path);
+                if index > 1 {
+                    thread::sleep(Duration::from_secs(30));
+                    return;
+                }
+                let mut succeeded = pods_of("align")[1].clone();
+                succeeded["status"]["phase"] = "Succeeded".into();
+                succeeded["status"]["containerStatuses"][0]["state"] =
+                    serde_json::json!({ "terminated": { "exitCode": 0, "reason": "Completed", "finishedAt": "2024-05-01T12:50:00Z" } });
+                succeeded["metadata"]["resourceVersion"] = "1200".into();
+                let mut deleted = pods_of("index")[0].clone();
+                deleted["metadata"]["resourceVersion"] = "1201".into();
+                for (kind, object) in [("MODIFIED", succeeded), ("DELETED", deleted)] {
+                    let _ = writeln!(stream, "{}", serde_json::json!({ "type": kind, "object": object }));
+                    let _ = stream.flush();
+                    thread::sleep(Duration::from_millis(50));
+                }
+            });
+        }
+    });
+    url
+}
+
+#[test]
+fn phases_map_onto_statuses() {
+    assert_eq!(k8s_status("Pending"), TaskStatus::Pending);
+    assert_eq!(k8s_status("Running"), TaskStatus::Running);
+    assert_eq!(k8s_status("Unknown"), TaskStatus::Running);
+    assert_eq!(k8s_status("Succeeded"), TaskStatus::Completed);
+    assert_eq!(k8s_status("Failed"), TaskStatus::Failed);
+}
+
+#[test]
+fn jobs_take_their_status_from_the_newest_pod() {
+    let task = k8s_task("align", &pods_of("align")).unwrap().unwrap();
+    assert_eq!(task.id, "align");
+    // The first pod failed, and the retry is running
+    assert_eq!(task.status, TaskStatus::Running);
+    assert_eq!(task.started_at, Some(1_714_564_805));
+    assert_eq!(task.finished_at, None);
+    assert_eq!(task.cpus, Some(0.5));
+    assert_eq!(task.memory_usage.requested, Some(6 << 30));
+    assert_eq!(task.memory_usage.limit, Some(8 << 30));
+    assert_eq!(task.command, ["bwa", "mem", "-t", "4", "ref.fa", "reads.fq"]);
+    assert_eq!(task.labels["node"], "node-5");
+    assert_eq!(task.labels["namespace"], "genomics");
+    assert_eq!(task.labels["sample"], "NA12878");
+
+    let pods: Vec<(&str, u32, Option<&str>)> =
+        task.pods.iter().map(|pod| (pod.name.as_str(), pod.restarts, pod.reason.as_deref())).collect();
+    assert_eq!(pods, [("align-7xk2p", 0, Some("OOMKilled")), ("align-9qm4d", 2, Some("CrashLoopBackOff"))]);
+    assert_eq!(task.attempt, Some(2));
+    assert!(task.is_requeued());
+
+    let task = k8s_task("index", &pods_of("index")).unwrap().unwrap();
+    assert_eq!(task.status, TaskStatus::Completed);
+    assert_eq!(task.finished_at, Some(1_714_564_300));
+    assert_eq!(task.pods[0].reason, None);
+
+    assert!(k8s_task("none", &[]).unwrap().is_none());
+}
+
+#[test]
+fn watched_changes_reach_the_task_store() {
+    let url = serve();
+    let mut app = App::with_k8s(K8sTarget::new(&url, "genomics"));
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while app.tasks.get("align").is_none_or(|task| task.status != TaskStatus::Completed) || app.tasks.contains_key("index") {
+        assert!(Instant::now() < deadline, "the watch was not followed: {:?}", app.task_ids);
+        thread::sleep(Duration::from_millis(20));
+        app.update();
+    }
+    assert_eq!(app.task_ids, ["align"]);
+    assert_eq!(app.health.source, "k8s");
+    assert!(app.health.last_error.is_none(), "{:?}", app.health.last_error);
+}
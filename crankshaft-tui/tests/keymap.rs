@@ -0,0 +1,48 @@
+//! Tests for the key hints the footer shows in each mode.
+
+pub mod harness;
+
+use crankshaft_tui::{key_hints, App, KeyMode};
+use crossterm::event::KeyCode;
+
+use harness::quiet::{press, Quiet};
+
+fn keys(app: &App) -> Vec<(&'static str, &'static str)> {
+    key_hints(app).into_iter().map(|hint| (hint.key, hint.action)).collect()
+}
+
+#[test]
+fn prompts_and_menus_hint_the_keys_that_leave_them() {
+    let mut app = App::new();
+    assert_eq!(KeyMode::of(&app), KeyMode::Tasks);
+
+    press(&mut app, KeyCode::Char('o'));
+    assert_eq!(KeyMode::of(&app), KeyMode::SortMenu);
+    assert!(keys(&app).contains(&("Esc", "close")));
+    press(&mut app, KeyCode::Esc);
+
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Char('r'));
+    assert_eq!(KeyMode::of(&app), KeyMode::Renaming);
+    assert_eq!(keys(&app), [("Enter", "save"), ("Esc", "cancel")]);
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(KeyMode::of(&app), KeyMode::Tasks);
+}
+
+#[test]
+fn log_hints_follow_the_focused_pane() {
+    let mut app = App::new();
+    press(&mut app, KeyCode::Down);
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(KeyMode::of(&app), KeyMode::Logs);
+
+    press(&mut app, KeyCode::Char('+'));
+    assert!(keys(&app).contains(&("F", "pause")));
+    press(&mut app, KeyCode::Char('F'));
+    assert!(keys(&app).contains(&("F", "follow")));
+
+    // Without logs, the Logs tab has nothing to scroll or follow
+    let mut app = App::with_source(Quiet::NONE);
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(keys(&app), [("Tab", "switch tabs")]);
+}
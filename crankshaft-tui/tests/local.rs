@@ -0,0 +1,64 @@
+//! Tests for watching the processes of a local engine.
+//!
+//! The test process stands in for the engine, so the processes it starts
+//! are watched as its tasks.
+
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crankshaft_tui::{DataSource, LocalDataSource, SourceEvent, TaskStatus, TaskUpdate};
+
+/// Polls `source` until `done` holds for an update or `timeout` passes.
+fn wait_for(source: &mut LocalDataSource, timeout: Duration, mut done: impl FnMut(&TaskUpdate) -> bool) -> Option<TaskUpdate> {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if let Some(update) = source.poll().into_iter().find(|update| done(update)) {
+            return Some(update);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    None
+}
+
+#[test]
+fn child_processes_are_tasks_with_their_resource_use() {
+    let mut child = Command::new("sh").args(["-c", "sleep 30; true"]).stdin(Stdio::null()).spawn().unwrap();
+    let pid = child.id().to_string();
+    let mut source = LocalDataSource::watch(&std::process::id().to_string());
+
+    // The shell's own child counts towards it, once the shell started it
+    let created = wait_for(&mut source, Duration::from_secs(5), |update| {
+        matches!(update, TaskUpdate::Created(task) if task.id == pid && task.labels.get("processes").is_some_and(|count| count == "2"))
+    });
+    let Some(TaskUpdate::Created(task)) = created else {
+        panic!("the child and its own child were not found");
+    };
+    assert_eq!(task.name, "sh");
+    assert_eq!(task.status, TaskStatus::Running);
+    assert_eq!(task.command, ["sh", "-c", "sleep 30; true"]);
+    assert_eq!(task.labels["backend"], "local");
+    assert!(task.memory_usage.used > 0);
+    assert!(task.memory_usage.limit.is_some());
+    assert!(task.started_at.is_some());
+    let events = source.events();
+    assert!(events.iter().any(|event| matches!(event, SourceEvent::Connected { .. })));
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    let removed = wait_for(&mut source, Duration::from_secs(5), |update| matches!(update, TaskUpdate::Removed { id } if *id == pid));
+    assert!(removed.is_some(), "the child stayed listed after it exited");
+}
+
+#[test]
+fn missing_engines_are_reported() {
+    let mut source = LocalDataSource::watch("no-such-crankshaft-engine");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut events = Vec::new();
+    while events.is_empty() {
+        assert!(Instant::now() < deadline, "nothing was reported");
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(source.poll().is_empty());
+        events = source.events();
+    }
+    assert_eq!(events, [SourceEvent::Failed("Process no-such-crankshaft-engine is not running".to_string())]);
+}
@@ -0,0 +1,57 @@
+//! Tests for fetching the logs of viewed tasks.
+
+pub mod harness;
+
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crankshaft_tui::{App, LogChunk, LogProvider, LogRange, Tab, TaskUpdate};
+use crossterm::event::KeyCode;
+
+use harness::quiet::{press, task, Quiet};
+
+/// Lines in the log of every task.
+const LINES: usize = 1_000;
+
+/// A provider holding back tail fetches until the test lets them through.
+struct Gated {
+    release: Arc<Mutex<Receiver<()>>>,
+}
+
+impl LogProvider for Gated {
+    fn fetch(&mut self, _task_id: &str, range: LogRange) -> eyre::Result<LogChunk> {
+        let start = match range {
+            LogRange::Tail(lines) => {
+                let _ = self.release.lock().unwrap().recv();
+                LINES.saturating_sub(lines)
+            }
+            LogRange::From(start) => start,
+        };
+        Ok(LogChunk { start, lines: (start..LINES).map(|line| format!("line {}", line)).collect() })
+    }
+}
+
+#[test]
+fn a_full_reload_waits_for_the_fetch_under_way() {
+    let (release, gate) = mpsc::channel();
+    let mut app = App::with_source(Quiet::LOGS);
+    app.set_log_provider(Gated { release: Arc::new(Mutex::new(gate)) });
+    app.apply_update(TaskUpdate::Created(Box::new(task("align", "running"))));
+    app.select_task("align");
+    app.set_tab(Tab::Logs);
+
+    // The tail is asked for and held back, so L comes while it is pending
+    app.update();
+    assert!(app.is_log_loading("align"));
+    press(&mut app, KeyCode::Char('L'));
+    release.send(()).unwrap();
+
+    let started = Instant::now();
+    while app.tasks["align"].logs.lines().next() != Some("line 0") {
+        assert!(started.elapsed() < Duration::from_secs(5), "the full log never arrived");
+        std::thread::sleep(Duration::from_millis(5));
+        app.update();
+    }
+    assert_eq!(app.tasks["align"].logs.len(), LINES);
+}
@@ -0,0 +1,69 @@
+//! Tests for reading jobs from an LSF cluster.
+//!
+//! The fixture in `tests/fixtures/lsf` is `bjobs -json` output with the
+//! fields the source asks for.
+
+use std::path::PathBuf;
+
+use crankshaft_tui::{lsf_status, lsf_task, TaskStatus};
+
+/// When the fixture's `bjobs` ran.
+const NOW: u64 = 1_714_600_000;
+
+fn records() -> Vec<serde_json::Value> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/lsf/bjobs.json");
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("failed to read {}: {}", path.display(), err));
+    let listing: serde_json::Value = serde_json::from_str(&text).unwrap();
+    listing["RECORDS"].as_array().unwrap().clone()
+}
+
+#[test]
+fn states_map_onto_statuses() {
+    assert_eq!(lsf_status("PEND"), TaskStatus::Pending);
+    assert_eq!(lsf_status("PSUSP"), TaskStatus::Pending);
+    assert_eq!(lsf_status("RUN"), TaskStatus::Running);
+    assert_eq!(lsf_status("USUSP"), TaskStatus::Running);
+    assert_eq!(lsf_status("DONE"), TaskStatus::Completed);
+    assert_eq!(lsf_status("EXIT"), TaskStatus::Failed);
+}
+
+#[test]
+fn listed_jobs_become_tasks() {
+    let records = records();
+
+    let running = lsf_task(&records[0], NOW).unwrap();
+    assert_eq!(running.id, "7301");
+    assert_eq!(running.name, "align_reads");
+    assert_eq!(running.status, TaskStatus::Running);
+    assert_eq!(running.labels["queue"], "normal");
+    assert_eq!(running.labels["host"], "8*hpc-041");
+    assert_eq!(running.labels["backend"], "lsf");
+    assert_eq!(running.cpus, Some(8.0));
+    assert_eq!(running.duration(NOW), Some(754));
+    assert_eq!(running.raw.as_ref(), Some(&records[0]));
+
+    // Elements of job arrays are told apart by their index
+    let pending = lsf_task(&records[1], NOW).unwrap();
+    assert_eq!(pending.id, "7302[3]");
+    assert_eq!(pending.status, TaskStatus::Pending);
+    assert_eq!(pending.started_at, None);
+    assert_eq!(pending.labels["reason"], "New job is waiting for scheduling");
+    assert!(!pending.labels.contains_key("host"));
+    assert_eq!(pending.cpus, Some(4.0));
+}
+
+#[test]
+fn finished_jobs_keep_their_run_time_and_exit_code() {
+    let records = records();
+
+    let done = lsf_task(&records[2], NOW).unwrap();
+    assert_eq!(done.status, TaskStatus::Completed);
+    assert_eq!(done.progress, 1.0);
+    assert_eq!(done.duration(NOW + 60), Some(95));
+    assert_eq!(done.labels["exit_code"], "0");
+
+    let failed = lsf_task(&records[3], NOW).unwrap();
+    assert_eq!(failed.status, TaskStatus::Failed);
+    assert_eq!(failed.labels["exit_code"], "137");
+    assert_eq!(failed.duration(NOW), Some(31));
+}
@@ -0,0 +1,25 @@
+//! Tests for reading task memory usage.
+
+use crankshaft_tui::{MemoryUsage, Task};
+
+#[test]
+fn memory_recorded_as_a_fraction_still_loads() {
+    // Recordings and snapshots from before memory was tracked in bytes
+    let task: Task = serde_json::from_value(serde_json::json!({
+        "id": "a",
+        "name": "a",
+        "status": "Running",
+        "progress": 0.5,
+        "cpu_usage": 0.1,
+        "memory_usage": 0.25,
+    }))
+    .unwrap();
+    assert_eq!(task.memory_usage, MemoryUsage { used: 1 << 28, requested: None, limit: Some(1 << 30) });
+    assert_eq!(task.memory_ratio(), 0.25);
+
+    // Bytes are written back, and read as they were written
+    let value = serde_json::to_value(task.memory_usage).unwrap();
+    assert_eq!(value, serde_json::json!({ "used": 1 << 28, "limit": 1 << 30 }));
+    assert_eq!(serde_json::from_value::<MemoryUsage>(value).unwrap(), task.memory_usage);
+    assert_eq!(serde_json::from_value::<MemoryUsage>(serde_json::json!({})).unwrap(), MemoryUsage::default());
+}
@@ -0,0 +1,184 @@
+//! Tests for combining the tasks of several sources.
+
+use crankshaft_tui::{App, ConnectionState, DataSource, MergeConfig, MergedDataSource, SourceCapabilities, Task, TaskStatus, TaskUpdate};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// A source named `name` that hands over a fixed script of updates, one
+/// batch per poll.
+struct Scripted {
+    name: &'static str,
+    capabilities: SourceCapabilities,
+    batches: Vec<Vec<TaskUpdate>>,
+}
+
+impl Scripted {
+    fn boxed(name: &'static str, capabilities: SourceCapabilities, batches: Vec<Vec<TaskUpdate>>) -> Box<dyn DataSource> {
+        Box::new(Self { name, capabilities, batches })
+    }
+}
+
+impl DataSource for Scripted {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        self.capabilities
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        if self.batches.is_empty() {
+            Vec::new()
+        } else {
+            self.batches.remove(0)
+        }
+    }
+
+    fn label_columns(&self) -> Vec<String> {
+        vec![format!("{}-column", self.name)]
+    }
+}
+
+fn created(id: &str, status: TaskStatus, progress: f64, cpu_usage: f64) -> TaskUpdate {
+    let task: Task = serde_json::from_value(serde_json::json!({
+        "id": id,
+        "name": id,
+        "status": status,
+        "progress": progress,
+        "cpu_usage": cpu_usage,
+    }))
+    .expect("a minimal task deserializes");
+    TaskUpdate::Created(Box::new(task))
+}
+
+const MEASURED: SourceCapabilities = SourceCapabilities {
+    cpu: true,
+    memory: true,
+    ..SourceCapabilities::NONE
+};
+
+#[test]
+fn fields_follow_precedence_and_conflicts_show_in_the_json() {
+    let engine = Scripted::boxed(
+        "engine",
+        SourceCapabilities::ALL,
+        vec![
+            vec![created("align", TaskStatus::Running, 0.4, 0.0)],
+            vec![TaskUpdate::StatusChanged { id: "align".to_string(), status: TaskStatus::Failed }],
+        ],
+    );
+    let local = Scripted::boxed("local", MEASURED, vec![vec![created("align", TaskStatus::Running, 0.0, 0.75)]]);
+    let mut app = App::with_merged(MergedDataSource::new(vec![engine, local], &MergeConfig::default()));
+    assert_eq!(app.health.source, "engine+local");
+    assert_eq!(app.label_columns, ["backend", "engine-column", "local-column"]);
+
+    let task = &app.tasks["align"];
+    // Progress from the engine, resource use from the source measuring it
+    assert_eq!(task.progress, 0.4);
+    assert_eq!(task.cpu_usage, 0.75);
+    let raw: serde_json::Value = serde_json::from_str(&task.raw_json()).unwrap();
+    assert_eq!(raw["sources"]["local"]["cpu_usage"], 0.75);
+    let conflicts = raw["conflicts"].as_array().unwrap();
+    let progress = conflicts.iter().find(|conflict| conflict["field"] == "progress").unwrap();
+    assert_eq!(progress["chosen"], "engine");
+    assert_eq!(progress["values"]["local"], 0.0);
+    assert!(!conflicts.iter().any(|conflict| conflict["field"] == "status"));
+
+    // A finished status wins whichever source reports it
+    app.update();
+    let task = &app.tasks["align"];
+    assert_eq!(task.status, TaskStatus::Failed);
+    let raw: serde_json::Value = serde_json::from_str(&task.raw_json()).unwrap();
+    let status = raw["conflicts"].as_array().unwrap().iter().find(|conflict| conflict["field"] == "status").unwrap();
+    assert_eq!(status["chosen"], "engine");
+    assert_eq!(status["values"]["local"], "Running");
+}
+
+#[test]
+fn stale_values_give_way_to_more_recent_ones() {
+    let sources = || {
+        vec![
+            Scripted::boxed("engine", SourceCapabilities::ALL, vec![vec![created("align", TaskStatus::Running, 0.2, 0.0)]]),
+            Scripted::boxed(
+                "tes",
+                SourceCapabilities::ALL,
+                vec![vec![], vec![TaskUpdate::Progress { id: "align".to_string(), progress: 0.6 }]],
+            ),
+        ]
+    };
+
+    let mut app = App::with_merged(MergedDataSource::new(sources(), &MergeConfig::default()));
+    app.update();
+    assert_eq!(app.tasks["align"].progress, 0.2);
+
+    let config = MergeConfig {
+        fresh_secs: 0,
+        ..MergeConfig::default()
+    };
+    let mut app = App::with_merged(MergedDataSource::new(sources(), &config));
+    app.update();
+    assert_eq!(app.tasks["align"].progress, 0.6);
+}
+
+#[test]
+fn tasks_stay_until_every_source_removes_them() {
+    let removed = || TaskUpdate::Removed { id: "align".to_string() };
+    let engine = Scripted::boxed(
+        "engine",
+        SourceCapabilities::ALL,
+        vec![vec![created("align", TaskStatus::Running, 0.5, 0.0)], vec![removed()]],
+    );
+    let docker = Scripted::boxed(
+        "docker",
+        MEASURED,
+        vec![vec![created("align", TaskStatus::Running, 0.0, 0.5)], vec![], vec![removed()]],
+    );
+    let config = MergeConfig {
+        precedence: vec!["docker".to_string()],
+        ..MergeConfig::default()
+    };
+    let mut app = App::with_merged(MergedDataSource::new(vec![engine, docker], &config));
+    assert_eq!(app.tasks["align"].progress, 0.0);
+
+    app.update();
+    assert_eq!(app.task_ids, ["align"]);
+    app.update();
+    assert!(app.task_ids.is_empty());
+}
+
+#[test]
+fn tasks_of_separate_backends_are_tagged_and_filtered() {
+    let mut slurm_job = created("slurm-81", TaskStatus::Running, 0.5, 0.0);
+    if let TaskUpdate::Created(task) = &mut slurm_job {
+        task.labels.insert("backend".to_string(), "slurm".to_string());
+    }
+    let slurm = Scripted::boxed("slurm", SourceCapabilities::ALL, vec![vec![slurm_job]]);
+    let stream = Scripted::boxed("stdin", SourceCapabilities::NONE, vec![vec![created("align", TaskStatus::Pending, 0.0, 0.0)]]);
+    let mut app = App::with_merged(MergedDataSource::new(vec![slurm, stream], &MergeConfig::default()));
+    assert_eq!(app.task_ids.len(), 2);
+    // A source that does not name its backend stands in for it
+    assert_eq!(app.tasks["align"].labels["backend"], "stdin");
+    assert_eq!(app.backends(), ["slurm", "stdin"]);
+
+    let shown = |app: &App| app.visible_task_ids().into_iter().cloned().collect::<Vec<_>>();
+    app.handle_key(KeyEvent::new(KeyCode::Char('B'), KeyModifiers::NONE));
+    assert_eq!(shown(&app), ["slurm-81"]);
+    app.handle_key(KeyEvent::new(KeyCode::Char('B'), KeyModifiers::NONE));
+    assert_eq!(shown(&app), ["align"]);
+    app.handle_key(KeyEvent::new(KeyCode::Char('B'), KeyModifiers::NONE));
+    assert_eq!(app.backend_filter, None);
+    assert_eq!(shown(&app).len(), 2);
+}
+
+#[test]
+fn each_source_has_a_connection_of_its_own() {
+    let engine = Scripted::boxed("engine", SourceCapabilities::ALL, vec![vec![created("align", TaskStatus::Running, 0.5, 0.0)]]);
+    let docker = Scripted::boxed("docker", MEASURED, Vec::new());
+    let app = App::with_merged(MergedDataSource::new(vec![engine, docker], &MergeConfig::default()));
+
+    let states: Vec<(&str, ConnectionState)> =
+        app.connections.iter().map(|connection| (connection.source.as_str(), connection.state)).collect();
+    assert_eq!(states, [("engine", ConnectionState::Connected), ("docker", ConnectionState::Waiting)]);
+    assert!(app.connections.get("engine").unwrap().last_success.is_some());
+    assert!(app.connections.get("docker").unwrap().last_success.is_none());
+}
@@ -0,0 +1,43 @@
+//! Tests for the stack of overlays drawn over the dashboard.
+
+use crankshaft_tui::{App, Overlay};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+fn press(app: &mut App, code: KeyCode) -> bool {
+    app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+#[test]
+fn esc_closes_the_overlay_opened_last() {
+    let mut app = App::new();
+    press(&mut app, KeyCode::Char('D'));
+    press(&mut app, KeyCode::Char('i'));
+    assert_eq!(app.overlays.iter().collect::<Vec<_>>(), [Overlay::Diagnostics, Overlay::About]);
+
+    // Hiding an overlay and showing it again brings it to the top
+    press(&mut app, KeyCode::Char('D'));
+    press(&mut app, KeyCode::Char('D'));
+    assert_eq!(app.overlays.top(), Some(Overlay::Diagnostics));
+
+    assert!(!press(&mut app, KeyCode::Esc));
+    assert_eq!(app.overlays.top(), Some(Overlay::About));
+    assert!(!press(&mut app, KeyCode::Esc));
+    assert!(app.overlays.is_empty());
+    // With nothing left to close, Esc quits
+    assert!(press(&mut app, KeyCode::Esc));
+}
+
+#[test]
+fn menus_on_top_take_every_key() {
+    let mut app = App::new();
+    press(&mut app, KeyCode::Char('A'));
+    press(&mut app, KeyCode::Char('o'));
+    assert_eq!(app.overlays.top(), Some(Overlay::SortMenu));
+
+    // The sort menu takes the key rather than the audit log toggling
+    press(&mut app, KeyCode::Char('A'));
+    assert!(app.overlays.is_open(Overlay::Audit));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.sort_menu, None);
+    assert_eq!(app.overlays.top(), Some(Overlay::Audit));
+}
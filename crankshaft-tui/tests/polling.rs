@@ -0,0 +1,52 @@
+//! Tests for per-source poll intervals.
+
+use std::time::{Duration, Instant};
+
+use crankshaft_tui::{Config, DataSource, LocalDataSource, MergeConfig, MergedDataSource, PollInterval, MIN_POLL_INTERVAL};
+
+#[test]
+fn intervals_are_read_in_seconds_by_source_name() {
+    let config: Config = toml::from_str("[polling]\nslurm = 30\ndocker = 0.5").unwrap();
+    let intervals = config.polling.intervals().unwrap();
+    assert_eq!(intervals.get("slurm"), Some(&Duration::from_secs(30)));
+    assert_eq!(intervals.get("docker"), Some(&Duration::from_millis(500)));
+    assert_eq!(intervals.len(), 2);
+}
+
+#[test]
+fn unknown_sources_and_tiny_intervals_are_rejected() {
+    let config: Config = toml::from_str("[polling]\nslrum = 30").unwrap();
+    let err = config.polling.intervals().unwrap_err();
+    assert!(err.to_string().contains("`slrum` in `[polling]` is not a source that polls"), "{}", err);
+
+    for secs in ["0.01", "-1"] {
+        let config: Config = toml::from_str(&format!("[polling]\nlsf = {}", secs)).unwrap();
+        let err = config.polling.intervals().unwrap_err();
+        assert!(err.to_string().contains("must be at least 0.1 seconds"), "{}", err);
+    }
+    assert_eq!(MIN_POLL_INTERVAL, Duration::from_millis(100));
+}
+
+#[test]
+fn a_wait_follows_a_shorter_interval_set_meanwhile() {
+    let interval = PollInterval::new(Duration::from_secs(60));
+    let shared = interval.clone();
+    let started = Instant::now();
+    let waiting = std::thread::spawn(move || shared.wait_from(started));
+    interval.set(Duration::from_millis(200));
+    waiting.join().unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(interval.get(), Duration::from_millis(200));
+}
+
+#[test]
+fn only_the_named_source_changes_its_interval() {
+    let mut local = LocalDataSource::watch("crankshaft-tui-test-no-such-engine");
+    assert!(!local.set_poll_interval("slurm", Duration::from_secs(30)));
+    assert!(local.set_poll_interval("local", Duration::from_secs(7)));
+    assert_eq!(local.poll_interval(), Some(Duration::from_secs(7)));
+
+    let mut merged = MergedDataSource::new(vec![Box::new(local)], &MergeConfig::default());
+    assert!(merged.set_poll_interval("local", Duration::from_secs(3)));
+    assert!(!merged.set_poll_interval("docker", Duration::from_secs(3)));
+}
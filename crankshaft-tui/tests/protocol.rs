@@ -0,0 +1,207 @@
+//! Golden-file conformance tests for the engine REST protocol.
+//!
+//! The fixtures in `tests/fixtures/protocol` are payloads as the engine sends
+//! them. If a change to `Task` or `TaskUpdate` breaks these tests, the TUI no
+//! longer understands the engine: update both sides together, or keep the
+//! old field names readable.
+
+use std::path::PathBuf;
+
+use crankshaft_tui::{
+    decode_update, encode_update, Malformed, MemoryUsage, QueuePosition, TaskList, TaskStatus, TaskUpdate, SCHEMA_VERSION, UPDATE_SCHEMA,
+    UPDATE_TYPES,
+};
+
+fn fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/protocol").join(name);
+    std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("failed to read {}: {}", path.display(), err))
+}
+
+fn events() -> Vec<(String, TaskUpdate)> {
+    fixture("events.jsonl")
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let update = decode_update(line).unwrap_or_else(|err| panic!("failed to decode `{}`: {}", line, err));
+            (line.to_string(), update)
+        })
+        .collect()
+}
+
+#[test]
+fn task_list_deserializes() {
+    let list: TaskList = serde_json::from_str(&fixture("tasks.json")).unwrap();
+    assert_eq!(list.tasks.len(), 3);
+
+    let fetch = &list.tasks[0];
+    assert_eq!(fetch.id, "fetch-1");
+    assert_eq!(fetch.name, "fetch_inputs");
+    assert_eq!(fetch.status, TaskStatus::Completed);
+    assert_eq!(fetch.progress, 1.0);
+    assert_eq!(fetch.started_at, Some(1_700_000_001));
+    assert_eq!(fetch.finished_at, Some(1_700_000_009));
+    assert!(fetch.dependencies.is_empty());
+
+    let align = &list.tasks[1];
+    assert_eq!(align.status, TaskStatus::Running);
+    assert_eq!(align.cpu_usage, 0.75);
+    assert_eq!(align.cpus, Some(4.0));
+    assert_eq!(fetch.cpus, None);
+    assert_eq!(
+        align.memory_usage,
+        MemoryUsage {
+            used: 3 << 30,
+            requested: Some(4 << 30),
+            limit: Some(8 << 30),
+        }
+    );
+    assert_eq!(align.finished_at, None);
+    assert_eq!(align.dependencies, ["fetch-1"]);
+
+    let report = &list.tasks[2];
+    assert_eq!(report.status, TaskStatus::Pending);
+    assert_eq!(report.memory_usage, MemoryUsage::default());
+    assert_eq!(report.started_at, None);
+    assert_eq!(
+        report.queue,
+        Some(QueuePosition {
+            position: 12,
+            estimated_start: Some(1_700_000_600),
+        })
+    );
+    assert_eq!(align.queue, None);
+}
+
+#[test]
+fn events_deserialize() {
+    let updates: Vec<TaskUpdate> = events().into_iter().map(|(_, update)| update).collect();
+    assert_eq!(updates.len(), 10);
+    assert!(updates.iter().all(|update| update.task_id() == "align-2"));
+
+    match &updates[0] {
+        TaskUpdate::Created(task) => {
+            assert_eq!(task.name, "align_reads");
+            assert_eq!(task.status, TaskStatus::Pending);
+            assert_eq!(task.memory_usage.limit, Some(4 << 30));
+            assert_eq!(task.dependencies, ["fetch-1"]);
+        }
+        other => panic!("expected created, got {:?}", other),
+    }
+    assert!(matches!(
+        updates[1],
+        TaskUpdate::Queue { queue: Some(QueuePosition { position: 3, estimated_start: Some(1_700_000_300) }), .. }
+    ));
+    assert!(matches!(updates[2], TaskUpdate::Queue { queue: None, .. }));
+    assert!(matches!(updates[3], TaskUpdate::StatusChanged { status: TaskStatus::Running, .. }));
+    assert!(matches!(updates[4], TaskUpdate::Progress { progress, .. } if progress == 0.5));
+    match &updates[5] {
+        TaskUpdate::Metrics { cpu_usage, memory_usage, .. } => {
+            assert_eq!(*cpu_usage, Some(0.9));
+            assert_eq!(memory_usage.map(|memory| memory.used), Some(1 << 30));
+        }
+        other => panic!("expected metrics, got {:?}", other),
+    }
+    assert!(matches!(
+        updates[6],
+        TaskUpdate::Metrics { cpu_usage: Some(_), memory_usage: None, .. }
+    ));
+    assert!(matches!(&updates[7], TaskUpdate::LogLine { line, .. } if line == "error: out of memory"));
+    assert!(matches!(updates[8], TaskUpdate::StatusChanged { status: TaskStatus::Failed, .. }));
+    assert!(matches!(updates[9], TaskUpdate::Removed { .. }));
+}
+
+#[test]
+fn events_cover_every_update_kind() {
+    let mut seen = [false; 7];
+    for (_, update) in events() {
+        // A new variant fails to compile here until it is added to the fixtures
+        let kind = match update {
+            TaskUpdate::Created(_) => 0,
+            TaskUpdate::StatusChanged { .. } => 1,
+            TaskUpdate::Progress { .. } => 2,
+            TaskUpdate::Metrics { .. } => 3,
+            TaskUpdate::LogLine { .. } => 4,
+            TaskUpdate::Removed { .. } => 5,
+            TaskUpdate::Queue { .. } => 6,
+        };
+        seen[kind] = true;
+    }
+    assert!(seen.iter().all(|&seen| seen), "events.jsonl is missing an update kind: {:?}", seen);
+}
+
+#[test]
+fn events_round_trip() {
+    for (line, update) in events() {
+        let expected: serde_json::Value = serde_json::from_str(&line).unwrap();
+        let actual: serde_json::Value = serde_json::from_str(&encode_update(&update).unwrap()).unwrap();
+        assert_eq!(actual, expected, "re-encoding changed `{}`", line);
+    }
+}
+
+#[test]
+fn unknown_update_kinds_are_rejected() {
+    assert!(decode_update(r#"{"type":"teleported","id":"task-1"}"#).is_err());
+}
+
+#[test]
+fn schema_lists_every_update_type() {
+    let schema: serde_json::Value = serde_json::from_str(UPDATE_SCHEMA).unwrap();
+    let types: Vec<&str> = UPDATE_TYPES.iter().map(|(kind, _)| *kind).collect();
+    assert_eq!(schema["properties"]["type"]["enum"], serde_json::json!(types));
+    assert_eq!(schema["required"], serde_json::json!(["type", "id"]));
+    assert_eq!(schema["properties"]["version"]["maximum"], serde_json::json!(SCHEMA_VERSION));
+    // Each type's branch requires what decoding checks for
+    let branches = schema["oneOf"].as_array().unwrap();
+    assert_eq!(branches.len(), UPDATE_TYPES.len());
+    for (branch, (kind, required)) in branches.iter().zip(UPDATE_TYPES) {
+        assert_eq!(branch["properties"]["type"]["const"], *kind);
+        let listed = branch.get("required").cloned().unwrap_or_else(|| serde_json::json!([]));
+        assert_eq!(listed, serde_json::json!(required), "`{}` requires other fields", kind);
+    }
+}
+
+#[test]
+fn malformed_updates_are_classified() {
+    let cases = [
+        ("not json", Malformed::NotJson),
+        (r#"["progress","task-1"]"#, Malformed::InvalidValue),
+        (r#"{"type":"progress","id":"task-1","progress":0.5,"version":2}"#, Malformed::UnsupportedVersion),
+        (r#"{"type":"progress","id":"task-1","progress":0.5,"version":"1"}"#, Malformed::InvalidValue),
+        (r#"{"type":"teleported","id":"task-1"}"#, Malformed::UnknownType),
+        (r#"{"id":"task-1","progress":0.5}"#, Malformed::MissingField),
+        (r#"{"type":"progress","progress":0.5}"#, Malformed::MissingField),
+        (r#"{"type":"created","id":"task-1","status":"running"}"#, Malformed::MissingField),
+        (r#"{"type":"progress","id":"task-1","progress":"half"}"#, Malformed::InvalidValue),
+        (r#"{"type":"status_changed","id":"task-1","status":"exploded"}"#, Malformed::InvalidValue),
+    ];
+    for (line, expected) in cases {
+        match decode_update(line) {
+            Ok(update) => panic!("`{}` decoded as {:?}", line, update),
+            Err(err) => assert_eq!(err.kind, expected, "`{}` was rejected as {}", line, err),
+        }
+    }
+}
+
+#[test]
+fn versioned_updates_with_unknown_fields_are_read() {
+    let line = r#"{"type":"progress","id":"task-1","progress":0.5,"version":1,"attempt":2}"#;
+    assert!(matches!(decode_update(line), Ok(TaskUpdate::Progress { progress, .. }) if progress == 0.5));
+}
+
+#[test]
+fn engine_state_names_map_onto_statuses() {
+    let cases = [
+        ("queued", TaskStatus::Pending),
+        ("started", TaskStatus::Running),
+        ("succeeded", TaskStatus::Completed),
+        ("cancelled", TaskStatus::Failed),
+        ("Failed", TaskStatus::Failed),
+    ];
+    for (name, expected) in cases {
+        let line = format!(r#"{{"type":"status_changed","id":"task-1","status":"{}"}}"#, name);
+        match decode_update(&line).unwrap() {
+            TaskUpdate::StatusChanged { status, .. } => assert_eq!(status, expected, "`{}` mapped wrongly", name),
+            other => panic!("`{}` decoded as {:?}", line, other),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Tests for the waits between attempts to reconnect.
+
+use std::time::Duration;
+
+use crankshaft_tui::Backoff;
+
+#[test]
+fn waits_double_within_jitter_up_to_the_maximum() {
+    let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(8));
+    for base in [1, 2, 4, 8, 8] {
+        let base = Duration::from_secs(base);
+        let delay = backoff.next_delay();
+        assert!(delay >= base / 2 && delay <= base, "{:?} is not within half of {:?}", delay, base);
+    }
+    assert_eq!(backoff.attempt(), 5);
+
+    backoff.reset();
+    assert_eq!(backoff.attempt(), 0);
+    assert!(backoff.next_delay() <= Duration::from_secs(1));
+}
@@ -0,0 +1,103 @@
+//! Tests for redrawing only the regions of the dashboard that changed.
+
+pub mod harness;
+
+use crankshaft_tui::{draw, App, RegionSlot, Regions, TaskStatus, TaskUpdate};
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::widgets::Paragraph;
+use ratatui::Terminal;
+
+use harness::quiet::{task, Quiet};
+
+fn log_line(app: &mut App, id: &str, line: &str) {
+    app.apply_update(TaskUpdate::LogLine { id: id.to_string(), line: line.to_string() });
+}
+
+/// Draws a frame and returns what is on screen.
+fn frame(terminal: &mut Terminal<TestBackend>, app: &App) -> Buffer {
+    terminal.draw(|f| draw(f, app)).unwrap();
+    terminal.backend().buffer().clone()
+}
+
+#[test]
+fn streaming_logs_only_redraw_their_pane() {
+    let mut app = App::with_source(Quiet::LOGS);
+    app.apply_update(TaskUpdate::Created(Box::new(task("a", "running"))));
+    app.selected_task_id = Some("a".to_string());
+    app.tab_index = 1;
+    log_line(&mut app, "a", "starting");
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+
+    frame(&mut terminal, &app);
+    let (drawn, reused) = app.regions.counts();
+    assert!(drawn >= 2, "{} regions drawn", drawn);
+    assert_eq!(reused, 0);
+
+    // With nothing new, every region is copied from the last frame
+    let before = frame(&mut terminal, &app);
+    assert_eq!(app.regions.counts(), (0, drawn));
+
+    // A new line only redraws the pane it lands in
+    log_line(&mut app, "a", "step one done");
+    let streamed = frame(&mut terminal, &app);
+    assert_eq!(app.regions.counts(), (1, drawn - 1));
+    assert_ne!(before, streamed);
+
+    // The copied regions look as they would if drawn from scratch
+    app.regions.invalidate();
+    assert_eq!(frame(&mut terminal, &app), streamed);
+    assert_eq!(app.regions.counts(), (drawn, 0));
+}
+
+#[test]
+fn task_changes_redraw_the_header_rows_showing_them() {
+    let mut app = App::with_source(Quiet::LOGS);
+    let mut labeled = task("a", "running");
+    labeled.labels.insert("phase".to_string(), "align".to_string());
+    app.apply_update(TaskUpdate::Created(Box::new(labeled)));
+    app.selected_task_id = Some("a".to_string());
+    app.tab_index = 1;
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    frame(&mut terminal, &app);
+
+    let version = app.store_version();
+    log_line(&mut app, "a", "aligning");
+    assert_eq!(app.store_version(), version);
+    frame(&mut terminal, &app);
+
+    // The phase bar follows the task's status, unlike the tabs and the log
+    app.apply_update(TaskUpdate::StatusChanged { id: "a".to_string(), status: TaskStatus::Completed });
+    assert!(app.store_version() > version);
+    frame(&mut terminal, &app);
+    assert_eq!(app.regions.counts(), (1, 2));
+}
+
+#[test]
+fn regions_are_drawn_again_when_moved_or_changed() {
+    let regions = Regions::default();
+    let mut terminal = Terminal::new(TestBackend::new(20, 4)).unwrap();
+    let mut draws = 0;
+    let mut draw_at = |terminal: &mut Terminal<TestBackend>, area: Rect, stamp: u64| {
+        terminal
+            .draw(|f| {
+                regions.start_frame();
+                regions.draw(f, RegionSlot::Tabs, area, stamp, |f| {
+                    draws += 1;
+                    f.render_widget(Paragraph::new("hello"), area);
+                });
+            })
+            .unwrap();
+        terminal.backend().buffer().clone()
+    };
+
+    let top = Rect::new(0, 0, 20, 1);
+    let first = draw_at(&mut terminal, top, 1);
+    let copied = draw_at(&mut terminal, top, 1);
+    assert_eq!(first, copied);
+    draw_at(&mut terminal, top, 2);
+    let moved = draw_at(&mut terminal, Rect::new(0, 2, 20, 1), 2);
+    assert_eq!(moved.get(0, 2).symbol, "h");
+    assert_eq!(draws, 3);
+}
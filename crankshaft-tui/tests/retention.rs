@@ -0,0 +1,65 @@
+//! Tests for pruning finished tasks from long sessions.
+
+pub mod harness;
+
+use crankshaft_tui::{App, Config, Retention, RetentionConfig, Task, TaskStatus, TaskUpdate};
+
+use harness::quiet::Quiet;
+
+fn task(id: &str, status: TaskStatus, finished_at: Option<u64>) -> Task {
+    serde_json::from_value(serde_json::json!({
+        "id": id,
+        "name": "align (shard 1)",
+        "status": status,
+        "started_at": finished_at.map(|finished| finished - 60),
+        "finished_at": finished_at,
+    }))
+    .expect("a minimal task deserializes")
+}
+
+#[test]
+fn only_tasks_finished_long_enough_ago_expire() {
+    let now = 100_000;
+    let tasks = [
+        task("old", TaskStatus::Completed, Some(now - 7200)),
+        task("recent", TaskStatus::Failed, Some(now - 60)),
+        task("running", TaskStatus::Running, None),
+    ];
+    let mut retention = Retention::default();
+    assert!(retention.expired(&tasks, now).is_empty());
+
+    retention.set_config(RetentionConfig { max_age_hours: Some(1) });
+    assert_eq!(retention.expired(&tasks, now), ["old"]);
+    // Checked at most once a minute
+    assert!(retention.expired(&tasks, now).is_empty());
+
+    retention.record(&tasks[0]);
+    assert_eq!(retention.pruned().completed, 1);
+    assert_eq!(retention.pruned().durations["align"], [60]);
+    // Re-sent as finished it stays pruned; running again, it is back
+    assert!(retention.is_pruned(&tasks[0]));
+    assert!(!retention.is_pruned(&task("old", TaskStatus::Running, None)));
+    assert_eq!(retention.pruned().total(), 0);
+}
+
+#[test]
+fn pruned_tasks_leave_the_store_but_stay_in_the_statistics() {
+    let mut app = App::with_source(Quiet::NONE);
+    for task in [task("old", TaskStatus::Completed, Some(1_000)), task("pinned", TaskStatus::Failed, Some(1_000))] {
+        app.apply_update(TaskUpdate::Created(Box::new(task)));
+    }
+    app.pinned.insert("pinned".to_string());
+    let mut config = Config::default();
+    config.retention.max_age_hours = Some(24);
+    app.apply_config(&config);
+
+    app.update();
+    assert!(!app.tasks.contains_key("old"));
+    assert!(app.tasks.contains_key("pinned"));
+    let counts = app.status_counts();
+    assert_eq!((counts.completed, counts.failed), (1, 1));
+    assert_eq!(app.step_durations()[0].count, 1);
+
+    assert!(!app.apply_update(TaskUpdate::Created(Box::new(task("old", TaskStatus::Completed, Some(1_000))))));
+    assert!(!app.tasks.contains_key("old"));
+}
@@ -0,0 +1,95 @@
+//! End-to-end tests driving the compiled monitor through whole sessions in a
+//! pseudo-terminal.
+
+#![cfg(unix)]
+
+pub mod harness;
+
+use harness::pty::{keys, Session};
+
+#[test]
+fn starts_on_the_demo_tasks_and_quits_with_q() {
+    let mut session = Session::start(&[]);
+    let screen = session.expect("Sample Task 2");
+    for tab in ["Tasks", "Logs", "Statistics", "Timeline", "Events", "Help"] {
+        assert!(screen.contains(tab), "the {} tab is missing from:\n{}", tab, screen);
+    }
+
+    session.send("q");
+    assert!(session.wait_exit().success());
+}
+
+#[test]
+fn tabs_are_reached_with_tab_and_back_tab() {
+    let mut session = Session::start(&[]);
+    session.expect("Sample Task 1");
+
+    // Tasks, Logs, Statistics, Timeline, then Events
+    for _ in 0..4 {
+        session.send(keys::TAB);
+    }
+    session.expect("f filter  Enter go to task");
+    session.send(keys::TAB);
+    session.expect("Help & Keyboard Shortcuts");
+    session.send(keys::BACK_TAB);
+    session.expect("f filter  Enter go to task");
+    session.expect_gone("Help & Keyboard Shortcuts");
+
+    // Tab wraps around to the task list
+    session.send(keys::TAB);
+    session.send(keys::TAB);
+    session.expect_gone("f filter  Enter go to task");
+    session.expect("Sample Task 2");
+}
+
+#[test]
+fn filters_narrow_what_is_listed() {
+    let mut session = Session::start(&["--tab", "events"]);
+    session.expect("f filter  Enter go to task");
+    session.send("f");
+    session.expect(" Events (started only) ");
+    session.send("f");
+    session.expect(" Events (finished only) ");
+
+    // A filter sent to the running instance narrows the task list
+    session.send(keys::BACK_TAB);
+    session.send(keys::BACK_TAB);
+    session.send(keys::BACK_TAB);
+    session.send(keys::BACK_TAB);
+    session.expect("Sample Task 2");
+    let output = session.ctl(&["filter", "Task 1"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    session.expect_gone("Sample Task 2");
+    session.expect("Sample Task 10");
+    session.ctl(&["filter"]);
+    session.expect("Sample Task 2");
+}
+
+#[test]
+fn quitting_asks_first_when_configured_to() {
+    let mut session = Session::start_with_config(&[], "[confirm]\nquit = true\n");
+    session.expect("Sample Task 1");
+
+    session.send("q");
+    session.expect("Quit crankshaft-tui?");
+    session.send(keys::ESC);
+    session.expect_gone("Quit crankshaft-tui?");
+    session.expect("Sample Task 1");
+
+    session.send("q");
+    session.expect("Quit crankshaft-tui?");
+    session.send("y");
+    assert!(session.wait_exit().success());
+}
+
+#[test]
+fn the_screen_follows_cursor_moves_and_erasing() {
+    let mut screen = harness::pty::Screen::new(20, 3);
+    screen.feed(b"\x1b[2J\x1b[1;1Hhello\x1b[2;3H\x1b[31mworld\x1b[0m");
+    // Sequences may arrive split across reads
+    screen.feed(b"\x1b[3");
+    screen.feed(";1H\u{2502}ok\x1b]0;title\x07".as_bytes());
+    assert_eq!(screen.lines(), ["hello", "  world", "\u{2502}ok"]);
+    screen.feed(b"\x1b[1;3H\x1b[K");
+    assert_eq!(screen.lines()[0], "he");
+}
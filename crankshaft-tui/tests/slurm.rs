@@ -0,0 +1,67 @@
+//! Tests for reading jobs from a Slurm cluster.
+//!
+//! The fixture in `tests/fixtures/slurm` is `squeue` output in the format
+//! the source asks for.
+
+use std::path::PathBuf;
+
+use crankshaft_tui::{slurm_status, slurm_task, TaskStatus};
+
+/// When the fixture's `squeue` ran.
+const NOW: u64 = 1_714_600_000;
+
+fn fixture_lines() -> Vec<String> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/slurm/squeue.txt");
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("failed to read {}: {}", path.display(), err));
+    text.lines().map(str::to_string).collect()
+}
+
+#[test]
+fn states_map_onto_statuses() {
+    assert_eq!(slurm_status("PENDING"), TaskStatus::Pending);
+    assert_eq!(slurm_status("REQUEUE_HOLD"), TaskStatus::Pending);
+    assert_eq!(slurm_status("RUNNING"), TaskStatus::Running);
+    assert_eq!(slurm_status("COMPLETING"), TaskStatus::Running);
+    assert_eq!(slurm_status("COMPLETED"), TaskStatus::Completed);
+    assert_eq!(slurm_status("TIMEOUT"), TaskStatus::Failed);
+    assert_eq!(slurm_status("OUT_OF_MEMORY"), TaskStatus::Failed);
+    // As sacct reports them
+    assert_eq!(slurm_status("CANCELLED by 1000"), TaskStatus::Failed);
+}
+
+#[test]
+fn queued_jobs_become_tasks() {
+    let lines = fixture_lines();
+
+    let running = slurm_task(&lines[0], NOW).unwrap();
+    assert_eq!(running.id, "4815162");
+    assert_eq!(running.name, "align_reads");
+    assert_eq!(running.status, TaskStatus::Running);
+    assert_eq!(running.labels["partition"], "gpu");
+    assert_eq!(running.labels["node"], "gpu017");
+    assert_eq!(running.labels["backend"], "slurm");
+    assert_eq!(running.cpus, Some(8.0));
+    assert_eq!(running.memory_usage.requested, Some(32 << 30));
+    // The duration column shows the elapsed time
+    assert_eq!(running.duration(NOW), Some(((24 + 2) * 60 + 3) * 60 + 4));
+
+    let pending = slurm_task(&lines[1], NOW).unwrap();
+    assert_eq!(pending.status, TaskStatus::Pending);
+    assert_eq!(pending.started_at, None);
+    assert_eq!(pending.labels["reason"], "Resources");
+    assert!(!pending.labels.contains_key("node"));
+    assert_eq!(pending.memory_usage.requested, Some(4000 << 20));
+}
+
+#[test]
+fn job_names_may_contain_the_separator() {
+    let lines = fixture_lines();
+    let task = slurm_task(&lines[2], NOW).unwrap();
+    assert_eq!(task.id, "4815164_7");
+    assert_eq!(task.name, "joint_genotyping|shard 7");
+    assert_eq!(task.labels["node"], "cn[101-102]");
+    assert_eq!(task.duration(NOW), Some(12 * 60 + 34));
+    assert!(!task.labels.contains_key("reason"));
+
+    assert!(slurm_task("4815165|normal|RUNNING", NOW).is_none());
+}
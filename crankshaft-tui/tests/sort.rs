@@ -0,0 +1,73 @@
+//! Tests for ordering the task list by compound keys.
+
+use std::cmp::Ordering;
+
+use crankshaft_tui::{SortField, SortKey, SortOrder, Task};
+
+fn task(id: &str, status: &str, cpu: f64) -> Task {
+    let mut task: Task = serde_json::from_value(serde_json::json!({ "id": id, "name": id, "status": status })).expect("a minimal task deserializes");
+    task.cpu_usage = cpu;
+    task
+}
+
+fn order(keys: &[&str]) -> SortOrder {
+    SortOrder { keys: keys.iter().map(|key| key.parse().unwrap()).collect() }
+}
+
+/// Returns the ids of `tasks` in `order`.
+fn sorted(order: &SortOrder, mut tasks: Vec<Task>) -> Vec<String> {
+    tasks.sort_by(|a, b| order.compare(a, b, 0));
+    tasks.into_iter().map(|task| task.id).collect()
+}
+
+fn keys(order: &SortOrder) -> Vec<String> {
+    order.keys.iter().map(SortField::to_string).collect()
+}
+
+#[test]
+fn later_keys_order_the_ties_of_earlier_ones() {
+    let tasks = || {
+        vec![
+            task("d", "running", 10.0),
+            task("a", "completed", 5.0),
+            task("c", "running", 80.0),
+            task("b", "completed", 50.0),
+        ]
+    };
+    assert_eq!(sorted(&order(&["status", "-cpu"]), tasks()), ["c", "d", "b", "a"]);
+    assert_eq!(sorted(&order(&["-cpu"]), tasks()), ["c", "b", "d", "a"]);
+    assert_eq!(sorted(&order(&["status", "cpu"]), tasks()), ["d", "c", "a", "b"]);
+}
+
+#[test]
+fn ties_left_after_every_key_are_broken_by_id() {
+    let (a, b) = (task("a", "running", 10.0), task("b", "running", 10.0));
+    for keys in [&["status", "cpu"][..], &["-status", "-cpu"], &[]] {
+        assert_eq!(order(keys).compare(&a, &b, 0), Ordering::Less, "{:?}", keys);
+        assert_eq!(order(keys).compare(&b, &a, 0), Ordering::Greater, "{:?}", keys);
+    }
+    let tasks = vec![task("c", "running", 1.0), task("a", "running", 1.0), task("b", "running", 1.0)];
+    assert_eq!(sorted(&order(&["-cpu"]), tasks), ["a", "b", "c"]);
+}
+
+#[test]
+fn choosing_a_key_moves_the_others_down_a_level() {
+    let mut sort = order(&["status", "cpu"]);
+    sort.set_level(0, SortKey::Cpu);
+    assert_eq!(keys(&sort), ["cpu ↑", "status ↑"]);
+
+    // A new key pushes the rest down rather than replacing one
+    sort.set_level(1, SortKey::Name);
+    assert_eq!(keys(&sort), ["cpu ↑", "name ↑", "status ↑"]);
+
+    // Choosing the key at its own level flips it, and moving it keeps its
+    // direction
+    sort.set_level(2, SortKey::Status);
+    sort.set_level(0, SortKey::Status);
+    assert_eq!(keys(&sort), ["status ↓", "cpu ↑", "name ↑"]);
+
+    // Levels past the last append
+    let mut sort = SortOrder::default();
+    sort.set_level(1, SortKey::Duration);
+    assert_eq!(keys(&sort), ["duration ↑"]);
+}
@@ -0,0 +1,59 @@
+//! Tests for the bells rung when alerts are raised.
+
+use std::time::{Duration, SystemTime};
+
+use crankshaft_tui::{Bell, Config, Severity, SoundConfig, Sounds};
+
+/// Returns how many bells ring before the queue runs dry.
+fn bells(sounds: &mut Sounds) -> usize {
+    std::iter::from_fn(|| sounds.take_bell().then_some(())).count()
+}
+
+#[test]
+fn each_severity_rings_its_pattern_unless_muted() {
+    let config: Config = toml::from_str("[sounds]\ncritical = \"triple\"").unwrap();
+    assert_eq!(config.sounds, SoundConfig { warning: Bell::Silent, critical: Bell::Triple });
+
+    let mut sounds = Sounds::default();
+    sounds.set_config(config.sounds);
+    sounds.ring(Severity::Warning);
+    assert_eq!(bells(&mut sounds), 0);
+    sounds.ring(Severity::Critical);
+    sounds.ring(Severity::Warning);
+    assert_eq!(bells(&mut sounds), 3);
+
+    // Muting drops what is still ringing, and unmuting rings again
+    sounds.ring(Severity::Critical);
+    assert!(sounds.take_bell());
+    assert!(sounds.toggle_mute());
+    sounds.ring(Severity::Critical);
+    assert_eq!(bells(&mut sounds), 0);
+    assert!(!sounds.toggle_mute());
+    sounds.ring(Severity::Critical);
+    assert_eq!(bells(&mut sounds), 3);
+}
+
+#[test]
+fn sounds_follow_changes_to_the_configuration_file() {
+    let dir = std::env::temp_dir().join(format!("crankshaft-tui-sounds-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.toml");
+    std::fs::write(&path, "[sounds]\nwarning = \"single\"\n").unwrap();
+    let mut sounds = Sounds::default();
+    sounds.set_config(Config::load(Some(&path)).unwrap().sounds);
+    sounds.watch(Some(path.clone()));
+
+    // Written a moment later, as an editor saving the file would
+    let rewrite = |contents: &str, later: u64| {
+        std::fs::write(&path, contents).unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(later)).unwrap();
+    };
+    rewrite("[sounds]\nwarning = \"double\"\n", 10);
+    assert!(matches!(sounds.reload(), Some(Ok(()))));
+    assert_eq!(sounds.config().warning, Bell::Double);
+
+    // Checked at most every couple of seconds
+    rewrite("[sounds]\nwarning = \"loud\"\n", 20);
+    assert!(sounds.reload().is_none());
+}
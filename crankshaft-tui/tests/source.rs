@@ -0,0 +1,241 @@
+//! Tests for plugging data sources into the app.
+//!
+//! Embedders only see the public API, so these drive `App` with sources
+//! defined here the way a custom scheduler integration would.
+
+use std::time::{Duration, Instant};
+
+use crankshaft_tui::{
+    App, ConnectionState, DataSource, MockDataSource, SourceCapabilities, SourceEvent, StreamDataSource, TaskAction, TaskStatus, TaskUpdate,
+};
+
+/// A source that hands over a fixed script of updates, one batch per poll.
+struct Scripted {
+    batches: Vec<Vec<TaskUpdate>>,
+    events: Vec<SourceEvent>,
+}
+
+impl DataSource for Scripted {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::NONE
+    }
+
+    fn poll(&mut self) -> Vec<TaskUpdate> {
+        if self.batches.is_empty() {
+            Vec::new()
+        } else {
+            self.batches.remove(0)
+        }
+    }
+
+    fn events(&mut self) -> Vec<SourceEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+fn has_status(updates: &[TaskUpdate], id: &str, expected: TaskStatus) -> bool {
+    updates.iter().any(|update| {
+        matches!(update, TaskUpdate::StatusChanged { id: changed, status } if changed == id && *status == expected)
+    })
+}
+
+fn created(id: &str, status: TaskStatus) -> TaskUpdate {
+    let task = serde_json::from_value(serde_json::json!({
+        "id": id,
+        "name": id,
+        "status": status,
+        "progress": 0.0,
+        "cpu_usage": 0.0,
+        "memory_usage": { "used": 0 },
+    }))
+    .expect("a minimal task deserializes");
+    TaskUpdate::Created(Box::new(task))
+}
+
+#[test]
+fn custom_source_feeds_the_task_store() {
+    let source = Scripted {
+        batches: vec![
+            vec![created("a", TaskStatus::Pending), created("b", TaskStatus::Running)],
+            vec![TaskUpdate::StatusChanged { id: "a".to_string(), status: TaskStatus::Running }],
+            vec![TaskUpdate::Removed { id: "b".to_string() }],
+        ],
+        events: Vec::new(),
+    };
+    let mut app = App::with_source(source);
+    assert_eq!(app.task_ids, ["a", "b"]);
+    assert_eq!(app.health.source, "scripted");
+    assert_eq!(app.capabilities, SourceCapabilities::NONE);
+
+    app.update();
+    assert_eq!(app.tasks["a"].status, TaskStatus::Running);
+
+    app.update();
+    assert_eq!(app.task_ids, ["a"]);
+}
+
+#[test]
+fn source_events_reach_health_and_footer() {
+    let source = Scripted {
+        batches: Vec::new(),
+        events: vec![
+            SourceEvent::Dropped("not json".to_string()),
+            SourceEvent::Failed("Disconnected from the scheduler".to_string()),
+        ],
+    };
+    let app = App::with_source(source);
+    assert_eq!(app.health.dropped_messages, 1);
+    assert_eq!(app.health.last_error.as_ref().map(|(error, _)| error.as_str()), Some("Disconnected from the scheduler"));
+    assert_eq!(app.status(), Some("Disconnected from the scheduler"));
+    assert_eq!(app.connections.get("scripted").map(|connection| connection.state), Some(ConnectionState::Offline));
+}
+
+#[test]
+fn demo_source_carries_out_control_actions() {
+    let mut demo = MockDataSource::new();
+    let initial = demo.poll();
+    assert!(initial.iter().any(|update| matches!(update, TaskUpdate::Created(task) if task.id == "task-1")));
+
+    assert!(demo.control(&TaskAction::Cancel { id: "task-1".to_string() }).unwrap());
+    assert!(has_status(&demo.poll(), "task-1", TaskStatus::Failed));
+
+    // A failed task cannot be cancelled again, but it can be retried
+    assert!(!demo.control(&TaskAction::Cancel { id: "task-1".to_string() }).unwrap());
+    assert!(demo.control(&TaskAction::Retry { id: "task-1".to_string() }).unwrap());
+    assert!(has_status(&demo.poll(), "task-1", TaskStatus::Pending));
+
+    assert!(demo.control(&TaskAction::Cancel { id: "no-such-task".to_string() }).is_err());
+}
+
+#[test]
+fn piped_json_lines_feed_the_task_store() {
+    let lines = concat!(
+        r#"{"type":"created","id":"align","name":"align reads","status":"running"}"#, "\n",
+        "\n",
+        "not an update\n",
+        r#"{"type":"progress","id":"align","progress":0.5}"#, "\n",
+    );
+    let mut app = App::with_source(StreamDataSource::from_reader("pipe", std::io::Cursor::new(lines)));
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while app.status() != Some("pipe closed after 2 updates") {
+        assert!(Instant::now() < deadline, "the stream was not read to the end");
+        std::thread::sleep(Duration::from_millis(10));
+        app.update();
+    }
+    assert_eq!(app.task_ids, ["align"]);
+    assert_eq!(app.tasks["align"].progress, 0.5);
+    assert_eq!(app.health.dropped_messages, 1);
+}
+
+#[test]
+fn bursts_of_progress_are_coalesced_but_status_changes_kept() {
+    let mut lines = String::new();
+    for id in ["align", "call"] {
+        lines.push_str(&format!(r#"{{"type":"created","id":"{}","name":"{}","status":"running"}}"#, id, id));
+        lines.push('\n');
+    }
+    for step in 1..=5000 {
+        for id in ["align", "call"] {
+            lines.push_str(&format!(r#"{{"type":"progress","id":"{}","progress":{}}}"#, id, f64::from(step) / 5000.0));
+            lines.push('\n');
+        }
+    }
+    lines.push_str(r#"{"type":"status_changed","id":"align","status":"completed"}"#);
+    lines.push('\n');
+    // Nothing is taken while the stream is read, so the channel fills up
+    let mut stream = StreamDataSource::from_reader("burst", std::io::Cursor::new(lines));
+    std::thread::sleep(Duration::from_millis(200));
+
+    let mut updates = Vec::new();
+    let mut events = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !events.iter().any(|event| matches!(event, SourceEvent::Message(_))) {
+        assert!(Instant::now() < deadline, "the stream was not read to the end");
+        updates.extend(stream.poll());
+        events.extend(stream.events());
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let coalesced: u64 = events.iter().filter_map(|event| if let SourceEvent::Coalesced(count) = event { Some(*count) } else { None }).sum();
+    assert!(coalesced > 0, "nothing was coalesced");
+    assert_eq!(updates.len() as u64 + coalesced, 10_003);
+    assert!(has_status(&updates, "align", TaskStatus::Completed));
+    // The last progress of a task that is still running is kept, and none
+    // arrives after the status change that superseded it
+    let last_progress = |id: &str| updates.iter().rposition(|update| matches!(update, TaskUpdate::Progress { id: task, .. } if task == id));
+    assert!(matches!(updates[last_progress("call").unwrap()], TaskUpdate::Progress { progress, .. } if progress == 1.0));
+    let completed = updates.iter().position(|update| matches!(update, TaskUpdate::StatusChanged { .. })).unwrap();
+    assert!(last_progress("align").is_none_or(|at| at < completed));
+
+    let mut app = App::with_source(Scripted {
+        batches: vec![Vec::new()],
+        events: vec![SourceEvent::Coalesced(coalesced)],
+    });
+    app.update();
+    assert_eq!(app.health.coalesced, coalesced);
+    assert_eq!(app.health.dropped_messages, coalesced);
+}
+
+#[cfg(unix)]
+#[test]
+fn engines_push_updates_to_a_socket_and_a_named_pipe() {
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    let dir = std::env::temp_dir().join(format!("crankshaft-tui-listen-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let wait_for = |app: &mut App, status: &str| {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.status() != Some(status) {
+            assert!(Instant::now() < deadline, "never saw {:?}, last {:?}", status, app.status());
+            std::thread::sleep(Duration::from_millis(10));
+            app.update();
+        }
+    };
+
+    let socket = dir.join("updates.sock");
+    let mut app = App::with_listener(StreamDataSource::listen(&socket).unwrap());
+    assert_eq!(app.health.source, "socket");
+    // Another instance cannot take over the socket
+    assert!(StreamDataSource::listen(&socket).is_err());
+    // Nor is a file given by mistake replaced
+    let file = dir.join("tasks.jsonl");
+    std::fs::write(&file, "{}\n").unwrap();
+    assert!(StreamDataSource::listen(&file).is_err());
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "{}\n");
+    for id in ["align", "call"] {
+        let mut engine = UnixStream::connect(&socket).unwrap();
+        writeln!(engine, r#"{{"type":"created","id":"{}","name":"{}","status":"running"}}"#, id, id).unwrap();
+    }
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while app.task_ids.len() < 2 {
+        assert!(Instant::now() < deadline, "the connections were not read");
+        std::thread::sleep(Duration::from_millis(10));
+        app.update();
+    }
+    let mut ids = app.task_ids.clone();
+    ids.sort();
+    assert_eq!(ids, ["align", "call"]);
+    drop(app);
+    assert!(!socket.exists(), "the socket is left behind");
+
+    let fifo = dir.join("updates.fifo");
+    let _ = std::fs::remove_file(&fifo);
+    assert!(std::process::Command::new("mkfifo").arg(&fifo).status().unwrap().success());
+    let mut app = App::with_listener(StreamDataSource::listen(&fifo).unwrap());
+    assert_eq!(app.health.source, "fifo");
+    // Each writer is read in turn
+    for update in [r#"{"type":"created","id":"align","name":"align","status":"running"}"#, r#"{"type":"progress","id":"align","progress":0.5}"#] {
+        let mut writer = std::fs::OpenOptions::new().write(true).open(&fifo).unwrap();
+        writeln!(writer, "{}", update).unwrap();
+        drop(writer);
+        wait_for(&mut app, &format!("{} closed after 1 updates", fifo.display()));
+        app.set_status("");
+    }
+    assert_eq!(app.tasks["align"].progress, 0.5);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,122 @@
+//! Tests for following a Server-Sent Events stream of task updates.
+//!
+//! A stand-in service answers one request with a script of events.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crankshaft_tui::{App, ConnectionState, Malformed, RemoteAccess, TaskStatus};
+
+/// Answers one request with `chunks` of an event stream, pausing between
+/// them, then ends the stream.
+fn serve(chunks: Vec<&'static str>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/v1/events", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut accept = false;
+        let mut header = String::new();
+        while reader.read_line(&mut header).unwrap() > 2 {
+            accept |= header.trim_end().eq_ignore_ascii_case("accept: text/event-stream");
+            header.clear();
+        }
+        assert!(accept, "the request does not ask for an event stream");
+        let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n");
+        for chunk in chunks {
+            let _ = stream.write_all(chunk.as_bytes());
+            let _ = stream.flush();
+            thread::sleep(Duration::from_millis(20));
+        }
+    });
+    url
+}
+
+/// Answers every request with `401 Unauthorized`, handing over the
+/// `Authorization` header of each.
+fn refuse() -> (String, mpsc::Receiver<Option<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/v1/events", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut authorization = None;
+            let mut header = String::new();
+            while reader.read_line(&mut header).unwrap() > 2 {
+                if let Some((name, value)) = header.trim_end().split_once(": ") {
+                    if name.eq_ignore_ascii_case("authorization") {
+                        authorization = Some(value.to_string());
+                    }
+                }
+                header.clear();
+            }
+            let _ = sender.send(authorization);
+            let _ = write!(stream, "HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        }
+    });
+    (url, receiver)
+}
+
+/// Updates the app until `done` holds, failing after a few seconds.
+fn update_until(app: &mut App, done: impl Fn(&App) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !done(app) {
+        assert!(Instant::now() < deadline, "the stream was not followed: {:?}", app.task_ids);
+        thread::sleep(Duration::from_millis(10));
+        app.update();
+    }
+}
+
+#[test]
+fn task_update_events_reach_the_task_store() {
+    let url = serve(vec![
+        ": keep-alive\n\n",
+        "event: task_update\ndata: {\"type\":\"created\",\"id\":\"align\",\"name\":\"align reads\",\"status\":\"running\"}\n\n",
+        // Other events are skipped, and a value may span several data lines
+        "event: heartbeat\ndata: {}\n\n",
+        "event: task_update\ndata: {\"type\":\"progress\",\n",
+        "data: \"id\":\"align\",\"progress\":0.5}\r\n\r\n",
+        "event: task_update\ndata: not an update\n\n",
+        "event: task_update\ndata: {\"type\":\"status_changed\",\"id\":\"align\",\"status\":\"completed\"}\n\n",
+    ]);
+    let mut app = App::with_sse(&url, RemoteAccess::default());
+    assert_eq!(app.health.source, "sse");
+
+    update_until(&mut app, |app| app.tasks.get("align").is_some_and(|task| task.status == TaskStatus::Completed));
+    assert_eq!(app.task_ids, ["align"]);
+    assert_eq!(app.tasks["align"].progress, 0.5);
+    assert_eq!(app.health.malformed.get(&Malformed::NotJson), Some(&1));
+}
+
+#[test]
+fn the_end_of_the_stream_is_reported() {
+    let url = serve(vec!["event: task_update\ndata: {\"type\":\"created\",\"id\":\"align\",\"name\":\"align reads\",\"status\":\"running\"}\n\n"]);
+    let mut app = App::with_sse(&url, RemoteAccess::default());
+
+    update_until(&mut app, |app| app.health.last_error.is_some());
+    let (error, _) = app.health.last_error.as_ref().unwrap();
+    assert!(error.contains("the stream ended after 1 updates"), "{}", error);
+    assert_eq!(app.task_ids, ["align"]);
+}
+
+#[test]
+fn a_refused_token_stops_the_stream_with_a_message() {
+    let (url, requests) = refuse();
+    let mut app = App::with_sse(&url, RemoteAccess { token: Some("expired".to_string()), ..RemoteAccess::default() });
+
+    update_until(&mut app, |app| app.health.last_error.is_some());
+    let (error, _) = app.health.last_error.as_ref().unwrap();
+    assert!(error.contains("the server refused the sse token (HTTP 401)"), "{}", error);
+    assert!(error.contains("[auth.sse]"), "{}", error);
+    assert_eq!(app.connections.get("sse").unwrap().state, ConnectionState::Offline);
+    assert_eq!(requests.recv().unwrap().as_deref(), Some("Bearer expired"));
+
+    // The same token is not sent again
+    thread::sleep(Duration::from_secs(1));
+    assert!(requests.try_recv().is_err());
+}
@@ -0,0 +1,64 @@
+//! Tests for marking tasks whose figures are no longer current.
+
+pub mod harness;
+
+use std::time::Duration;
+
+use crankshaft_tui::{App, Config, SourceCapabilities, TaskStatus, TaskUpdate, DEFAULT_STALE_AFTER};
+
+use harness::quiet::{task, Quiet};
+
+/// A live source sampling resource use, with nothing to report.
+const SAMPLING: Quiet = Quiet(SourceCapabilities {
+    cpu: true,
+    memory: true,
+    ..SourceCapabilities::NONE
+});
+
+/// Returns whether task `id` is stale `secs` after its last update.
+fn stale_after(app: &App, id: &str, secs: u64) -> bool {
+    let task = &app.tasks[id];
+    app.is_stale(task, task.last_updated.expect("updates are timestamped") + secs)
+}
+
+#[test]
+fn running_tasks_go_stale_without_updates() {
+    let mut app = App::with_source(SAMPLING);
+    app.apply_update(TaskUpdate::Created(Box::new(task("a", "running"))));
+    app.apply_update(TaskUpdate::Created(Box::new(task("b", "completed"))));
+
+    let limit = DEFAULT_STALE_AFTER.as_secs();
+    assert!(!stale_after(&app, "a", limit - 1));
+    assert!(stale_after(&app, "a", limit));
+    // Finished tasks are not expected to change
+    assert!(!stale_after(&app, "b", limit * 10));
+
+    // Any update counts as fresh data
+    app.tasks.get_mut("a").unwrap().last_updated = Some(0);
+    app.apply_update(TaskUpdate::Progress { id: "a".to_string(), progress: 0.5 });
+    assert!(app.tasks["a"].last_updated.is_some_and(|at| at > 0));
+    assert_eq!(app.tasks["a"].status, TaskStatus::Running);
+}
+
+#[test]
+fn sources_reporting_changes_alone_are_never_stale() {
+    let mut app = App::with_source(Quiet::NONE);
+    app.apply_update(TaskUpdate::Created(Box::new(task("a", "running"))));
+    assert!(!stale_after(&app, "a", 3600));
+}
+
+#[test]
+fn the_threshold_is_configurable() {
+    let mut app = App::with_source(SAMPLING);
+    app.apply_update(TaskUpdate::Created(Box::new(task("a", "running"))));
+
+    let config: Config = toml::from_str("[display]\nstale_after_secs = 5").unwrap();
+    app.apply_config(&config);
+    assert_eq!(app.stale_after, Some(Duration::from_secs(5)));
+    assert!(stale_after(&app, "a", 5));
+
+    let config: Config = toml::from_str("[display]\nstale_after_secs = 0").unwrap();
+    app.apply_config(&config);
+    assert_eq!(app.stale_after, None);
+    assert!(!stale_after(&app, "a", 3600));
+}
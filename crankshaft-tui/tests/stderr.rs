@@ -0,0 +1,27 @@
+//! Tests for the capture of standard error while the dashboard is shown.
+
+#![cfg(unix)]
+
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crankshaft_tui::{capture_stderr, last_stderr_line, release_stderr, stderr_captured_lines};
+
+#[test]
+fn lines_written_to_stderr_are_captured_until_released() {
+    capture_stderr().unwrap();
+    // Written to the descriptor itself, past the test harness's capture
+    std::io::stderr().write_all(b"warning: something a library said\n\n").unwrap();
+    release_stderr();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while stderr_captured_lines() == 0 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(stderr_captured_lines(), 1, "blank lines are not counted");
+    assert_eq!(last_stderr_line().as_deref(), Some("warning: something a library said"));
+
+    // Releasing again, or with nothing captured, does nothing
+    release_stderr();
+}
@@ -0,0 +1,81 @@
+//! Golden-file tests for reading GA4GH TES v1 task listings.
+//!
+//! The fixtures in `tests/fixtures/tes` are `GET /tasks?view=FULL` responses
+//! as TES servers send them.
+
+use std::path::PathBuf;
+
+use crankshaft_tui::{tes_status, tes_task, TaskStatus};
+
+fn tasks() -> Vec<serde_json::Value> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tes/tasks.json");
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("failed to read {}: {}", path.display(), err));
+    let listing: serde_json::Value = serde_json::from_str(&text).unwrap();
+    listing["tasks"].as_array().unwrap().clone()
+}
+
+#[test]
+fn states_map_onto_statuses() {
+    for (state, status) in [
+        ("UNKNOWN", TaskStatus::Pending),
+        ("QUEUED", TaskStatus::Pending),
+        ("INITIALIZING", TaskStatus::Pending),
+        ("PAUSED", TaskStatus::Pending),
+        ("RUNNING", TaskStatus::Running),
+        ("CANCELING", TaskStatus::Running),
+        ("COMPLETE", TaskStatus::Completed),
+        ("EXECUTOR_ERROR", TaskStatus::Failed),
+        ("SYSTEM_ERROR", TaskStatus::Failed),
+        ("CANCELED", TaskStatus::Failed),
+        ("PREEMPTED", TaskStatus::Failed),
+    ] {
+        assert_eq!(tes_status(state), status, "{}", state);
+    }
+}
+
+#[test]
+fn completed_task_converts() {
+    let task = tes_task(&tasks()[0]).unwrap();
+    assert_eq!(task.id, "task-5f2a");
+    assert_eq!(task.name, "align_reads");
+    assert_eq!(task.status, TaskStatus::Completed);
+    assert_eq!(task.progress, 1.0);
+    assert_eq!(task.cpus, Some(4.0));
+    assert_eq!(task.memory_usage.requested, Some(8_000_000_000));
+    assert_eq!(task.started_at, Some(1_714_564_800));
+    assert_eq!(task.finished_at, Some(1_714_566_600));
+    assert_eq!(task.labels["sample"], "NA12878");
+    assert_eq!(task.labels["backend"], "tes");
+    assert!(task.raw.is_some());
+
+    assert_eq!(task.executors.len(), 2);
+    assert_eq!(task.executors[1].image, "quay.io/biocontainers/bwa:0.7.17");
+    assert_eq!(task.executors[1].exit_code, Some(0));
+    assert!(task.executors[1].stderr.ends_with("Real time: 1796.0 sec\n"));
+}
+
+#[test]
+fn failed_task_keeps_executor_output_and_offsets() {
+    let task = tes_task(&tasks()[1]).unwrap();
+    assert_eq!(task.status, TaskStatus::Failed);
+    assert_eq!(task.executors[0].exit_code, Some(137));
+    assert!(task.executors[0].stderr.contains("OutOfMemoryError"));
+    // 14:30 at +02:00 is 12:30 UTC
+    assert_eq!(task.started_at, Some(1_714_566_600));
+    assert_eq!(task.finished_at, Some(1_714_567_500));
+}
+
+#[test]
+fn progress_counts_finished_executors() {
+    let running = tes_task(&tasks()[2]).unwrap();
+    assert_eq!(running.status, TaskStatus::Running);
+    assert_eq!(running.name, "task-9d33");
+    assert_eq!(running.progress, 0.5);
+    assert_eq!(running.finished_at, None);
+    assert_eq!(running.executors[1].exit_code, None);
+
+    let queued = tes_task(&tasks()[3]).unwrap();
+    assert_eq!(queued.status, TaskStatus::Pending);
+    assert_eq!(queued.progress, 0.0);
+    assert_eq!(queued.started_at, None);
+}
@@ -0,0 +1,28 @@
+//! Tests for drawing the Timeline tab.
+
+pub mod harness;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crankshaft_tui::{draw, App, Tab, TaskUpdate};
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+
+use harness::quiet::{task, Quiet};
+
+#[test]
+fn tasks_started_ahead_of_the_local_clock_are_drawn() {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let mut app = App::with_source(Quiet::NONE);
+    for (id, ahead) in [("a", 30), ("b", 90)] {
+        let mut running = task(id, "running");
+        running.started_at = Some(now + ahead);
+        app.apply_update(TaskUpdate::Created(Box::new(running)));
+    }
+    app.tab_index = Tab::ALL.iter().position(|tab| *tab == Tab::Timeline).unwrap();
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    terminal.draw(|f| draw(f, &app)).unwrap();
+    let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol.as_str()).collect();
+    assert!(screen.contains("Timeline"), "{}", screen);
+}
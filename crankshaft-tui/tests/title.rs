@@ -0,0 +1,36 @@
+//! Tests for the task counts shown in the terminal window's title.
+
+pub mod harness;
+
+use crankshaft_tui::{terminal_title, App, Config, Task, TaskStatus, TaskUpdate};
+
+use harness::quiet::Quiet;
+
+fn task(id: &str, status: TaskStatus) -> Task {
+    serde_json::from_value(serde_json::json!({ "id": id, "name": id, "status": status })).expect("a minimal task deserializes")
+}
+
+#[test]
+fn the_title_counts_running_and_failed_tasks() {
+    let mut app = App::with_source(Quiet::NONE);
+    assert_eq!(terminal_title(&app), "crankshaft: 0 running / 0 failed");
+
+    for (id, status) in [("a", TaskStatus::Running), ("b", TaskStatus::Running), ("c", TaskStatus::Failed), ("d", TaskStatus::Completed)] {
+        app.apply_update(TaskUpdate::Created(Box::new(task(id, status))));
+    }
+    assert_eq!(terminal_title(&app), "crankshaft: 2 running / 1 failed");
+
+    // A blanked dashboard gives nothing away in the title either
+    app.blanked = true;
+    assert_eq!(terminal_title(&app), "crankshaft");
+}
+
+#[test]
+fn the_title_can_be_left_alone() {
+    let mut app = App::with_source(Quiet::NONE);
+    assert!(app.terminal_title);
+
+    let config: Config = toml::from_str("[display]\nterminal_title = false").unwrap();
+    app.apply_config(&config);
+    assert!(!app.terminal_title);
+}
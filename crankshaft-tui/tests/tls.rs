@@ -0,0 +1,29 @@
+//! Tests for the TLS settings of remote sources.
+
+use crankshaft_tui::{Config, Tls};
+
+#[test]
+fn no_settings_trust_the_public_authorities_only() {
+    let config: Config = toml::from_str("").unwrap();
+    assert_eq!(config.tls.load().unwrap(), Tls::default());
+}
+
+#[test]
+fn missing_and_invalid_files_are_reported_before_connecting() {
+    let config: Config = toml::from_str("[tls]\nca_file = \"/nonexistent/ca.pem\"").unwrap();
+    let err = config.tls.load().unwrap_err();
+    assert!(format!("{:#}", err).contains("failed to read `/nonexistent/ca.pem`"), "{:#}", err);
+
+    let config: Config = toml::from_str("[tls]\nclient_key = \"/run/secrets/monitor.key\"").unwrap();
+    let err = config.tls.load().unwrap_err();
+    assert!(err.to_string().contains("without `client_cert`"), "{}", err);
+
+    let dir = std::env::temp_dir().join(format!("crankshaft-tui-tls-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("ca.pem");
+    std::fs::write(&path, "not a certificate\n").unwrap();
+    let config: Config = toml::from_str(&format!("[tls]\nca_file = {:?}", path)).unwrap();
+    let err = config.tls.load().unwrap_err();
+    assert!(format!("{:#}", err).contains("no PEM certificate found"), "{:#}", err);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,65 @@
+//! Tests for the SSH tunnels to remote sources.
+
+use crankshaft_tui::{Config, TunnelConfig};
+
+#[test]
+fn no_section_opens_no_tunnel() {
+    let config: Config = toml::from_str("").unwrap();
+    assert_eq!(config.tunnel, TunnelConfig::default());
+}
+
+#[test]
+fn ssh_forwards_a_local_port_to_the_address_on_the_host() {
+    let mut config: Config = toml::from_str("[tunnel]\nhost = \"head\"\nkey = \"/keys/monitor\"\nport = 2222").unwrap();
+    let args = config.tunnel.ssh_args(40001, ("127.0.0.1", 7878)).unwrap();
+    assert_eq!(
+        args,
+        [
+            "-N",
+            "-o",
+            "BatchMode=yes",
+            "-o",
+            "ExitOnForwardFailure=yes",
+            "-L",
+            "127.0.0.1:40001:127.0.0.1:7878",
+            "-i",
+            "/keys/monitor",
+            "-p",
+            "2222",
+            "--",
+            "head",
+        ]
+    );
+
+    // The command line names the host and, optionally, the user
+    config.tunnel.set_destination("alice@login.example.org");
+    let args = config.tunnel.ssh_args(40001, ("[::1]", 7878)).unwrap();
+    assert_eq!(args[6], "127.0.0.1:40001:[::1]:7878");
+    assert_eq!(args.last().unwrap(), "alice@login.example.org");
+
+    // Nor can a destination pass for an option
+    let mut tunnel = TunnelConfig::default();
+    tunnel.set_destination("-oProxyCommand=touch /tmp/owned");
+    let args = tunnel.ssh_args(40001, ("127.0.0.1", 7878)).unwrap();
+    assert_eq!(args[args.len() - 2..], ["--", "-oProxyCommand=touch /tmp/owned"]);
+}
+
+#[test]
+fn urls_without_an_address_cannot_be_tunnelled() {
+    let mut tunnel = TunnelConfig::default();
+    assert!(tunnel.ssh_args(40001, ("127.0.0.1", 7878)).is_err());
+
+    tunnel.set_destination("head");
+    let err = tunnel.open("unix:/run/engine.sock").unwrap_err();
+    assert!(err.to_string().contains("it names no host"), "{}", err);
+}
+
+#[test]
+fn sources_served_over_tls_are_not_tunnelled() {
+    let mut tunnel = TunnelConfig::default();
+    tunnel.set_destination("head");
+    for url in ["https://engine.cluster.example.org:7878", "wss://engine.cluster.example.org/events"] {
+        let err = tunnel.open(url).unwrap_err();
+        assert!(err.to_string().contains("its certificate would be checked"), "{}", err);
+    }
+}
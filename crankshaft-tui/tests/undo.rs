@@ -0,0 +1,101 @@
+//! Tests for pinning, archiving, and undoing local actions.
+
+pub mod harness;
+
+use crankshaft_tui::{App, LocalAction, TaskUpdate, UndoStack, UNDO_LIMIT};
+
+use harness::quiet::{task, Quiet};
+
+/// Returns an app listing tasks `a`, `b`, and `c`, with `b` selected.
+fn app() -> App {
+    let mut app = App::with_source(Quiet::NONE);
+    for id in ["a", "b", "c"] {
+        app.apply_update(TaskUpdate::Created(Box::new(task(id, "running"))));
+    }
+    app.selected_task_id = Some("b".to_string());
+    app
+}
+
+fn listed(app: &App) -> Vec<&str> {
+    app.visible_task_ids().into_iter().map(String::as_str).collect()
+}
+
+fn pin(id: &str) -> LocalAction {
+    LocalAction::Pin { id: id.to_string() }
+}
+
+#[test]
+fn actions_are_undone_newest_first() {
+    let mut app = app();
+    app.perform(pin("c"));
+    assert_eq!(listed(&app), ["c", "a", "b"]);
+    app.perform(LocalAction::Archive { id: "b".to_string() });
+    assert_eq!(listed(&app), ["c", "a"]);
+    // The selection moves off the archived task
+    assert_eq!(app.selected_task_id.as_deref(), Some("a"));
+    assert_eq!(app.undo.len(), 2);
+
+    app.undo();
+    assert_eq!(listed(&app), ["c", "a", "b"]);
+    assert_eq!(app.status(), Some("Undid archive b"));
+    app.undo();
+    assert_eq!(listed(&app), ["a", "b", "c"]);
+    assert!(app.undo.is_empty());
+
+    app.undo();
+    assert_eq!(app.status(), Some("Nothing to undo"));
+    assert_eq!(listed(&app), ["a", "b", "c"]);
+}
+
+#[test]
+fn actions_changing_nothing_are_not_recorded() {
+    let mut app = app();
+    app.perform(pin("a"));
+    app.perform(pin("a"));
+    app.perform(LocalAction::Unarchive { id: "b".to_string() });
+    assert_eq!(app.undo.len(), 1);
+}
+
+#[test]
+fn renames_are_undone_to_the_previous_name() {
+    let mut app = app();
+    let rename = |from: Option<&str>, to: Option<&str>| LocalAction::Rename {
+        id: "a".to_string(),
+        from: from.map(str::to_string),
+        to: to.map(str::to_string),
+    };
+    app.perform(rename(None, Some("align")));
+    app.perform(rename(Some("align"), Some("align reads")));
+    assert_eq!(app.display_name(&app.tasks["a"]), "align reads");
+    app.undo();
+    assert_eq!(app.display_name(&app.tasks["a"]), "align");
+    app.undo();
+    assert_eq!(app.display_name(&app.tasks["a"]), "a");
+}
+
+#[test]
+fn every_action_is_reverted_by_its_inverse() {
+    let actions = [
+        pin("a"),
+        LocalAction::Unpin { id: "a".to_string() },
+        LocalAction::Archive { id: "a".to_string() },
+        LocalAction::Unarchive { id: "a".to_string() },
+        LocalAction::Rename { id: "a".to_string(), from: None, to: Some("align".to_string()) },
+    ];
+    for action in actions {
+        assert_ne!(action.inverse(), action);
+        assert_eq!(action.inverse().inverse(), action);
+    }
+}
+
+#[test]
+fn the_history_keeps_only_the_latest_actions() {
+    let mut stack = UndoStack::default();
+    for index in 0..UNDO_LIMIT + 5 {
+        stack.push(pin(&index.to_string()));
+    }
+    assert_eq!(stack.len(), UNDO_LIMIT);
+    assert_eq!(stack.pop(), Some(pin(&(UNDO_LIMIT + 4).to_string())));
+    let oldest = std::iter::from_fn(|| stack.pop()).last();
+    assert_eq!(oldest, Some(pin("5")));
+}
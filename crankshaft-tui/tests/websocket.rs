@@ -0,0 +1,216 @@
+//! Tests for subscribing to a WebSocket feed of task updates.
+//!
+//! A stand-in feed accepts subscribers and pushes a script of messages to
+//! each. The certificates in `tests/fixtures/tls` are signed by a test
+//! authority: `server.pem` for `localhost`, and `client.pem` for the monitor.
+
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crankshaft_tui::{App, ConnectionState, HealthState, Malformed, RemoteAccess, TaskStatus, Tls};
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use tungstenite::handshake::server::{Callback, ErrorResponse, Request, Response};
+use tungstenite::http::StatusCode;
+use tungstenite::Message;
+
+/// Accepts one subscriber and pushes `messages` to it, pausing between
+/// them, then closes the feed.
+fn serve(messages: Vec<&'static str>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}/v1/events", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut socket = tungstenite::accept(stream).unwrap();
+        for message in messages {
+            socket.send(Message::text(message)).unwrap();
+            thread::sleep(Duration::from_millis(20));
+        }
+        let _ = socket.close(None);
+        // Wait for the subscriber to acknowledge the close
+        while socket.read().is_ok() {}
+    });
+    url
+}
+
+/// Refuses a handshake with `401 Unauthorized`, handing over its
+/// `Authorization` header.
+struct Refusal(mpsc::Sender<Option<String>>);
+
+impl Callback for Refusal {
+    fn on_request(self, request: &Request, _: Response) -> Result<Response, ErrorResponse> {
+        let authorization = request.headers().get("authorization").and_then(|value| value.to_str().ok());
+        let _ = self.0.send(authorization.map(str::to_string));
+        let mut refusal = ErrorResponse::new(None);
+        *refusal.status_mut() = StatusCode::UNAUTHORIZED;
+        Err(refusal)
+    }
+}
+
+/// Answers every handshake with `401 Unauthorized`, handing over the
+/// `Authorization` header of each.
+fn refuse() -> (String, mpsc::Receiver<Option<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}/v1/events", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let _ = tungstenite::accept_hdr(stream.unwrap(), Refusal(sender.clone()));
+        }
+    });
+    (url, receiver)
+}
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tls").join(name);
+    std::fs::read(&path).unwrap_or_else(|err| panic!("failed to read {}: {}", path.display(), err))
+}
+
+/// Accepts subscribers over TLS, asking each for a client certificate
+/// signed by the test authority, and pushes `message` to those presenting
+/// one.
+fn serve_tls(message: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("wss://localhost:{}/v1/events", listener.local_addr().unwrap().port());
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add(CertificateDer::from_pem_slice(&fixture("ca.pem")).unwrap()).unwrap();
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = rustls::server::WebPkiClientVerifier::builder_with_provider(Arc::new(roots), Arc::clone(&provider)).build().unwrap();
+    let config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(
+            CertificateDer::pem_slice_iter(&fixture("server.pem")).collect::<Result<_, _>>().unwrap(),
+            PrivateKeyDer::from_pem_slice(&fixture("server.key")).unwrap(),
+        )
+        .unwrap();
+    let config = Arc::new(config);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let connection = rustls::ServerConnection::new(Arc::clone(&config)).unwrap();
+            let Ok(mut socket) = tungstenite::accept(rustls::StreamOwned::new(connection, stream.unwrap())) else {
+                continue;
+            };
+            socket.send(Message::text(message)).unwrap();
+            let _ = socket.close(None);
+            while socket.read().is_ok() {}
+        }
+    });
+    url
+}
+
+/// Updates the app until `done` holds, failing after a few seconds.
+fn update_until(app: &mut App, done: impl Fn(&App) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !done(app) {
+        assert!(Instant::now() < deadline, "the feed was not followed: {:?}", app.task_ids);
+        thread::sleep(Duration::from_millis(10));
+        app.update();
+    }
+}
+
+#[test]
+fn pushed_updates_are_applied_as_they_arrive() {
+    let url = serve(vec![
+        r#"{"type":"created","id":"align","name":"align reads","status":"running"}"#,
+        concat!(
+            r#"{"type":"progress","id":"align","progress":0.5}"#,
+            "\n",
+            r#"{"type":"created","id":"call","name":"call variants","status":"pending"}"#,
+        ),
+        "not an update",
+        r#"{"type":"status_changed","id":"align","status":"completed"}"#,
+    ]);
+    let mut app = App::with_websocket(&url, RemoteAccess::default());
+    assert_eq!(app.health.source, "websocket");
+
+    update_until(&mut app, |app| app.tasks.get("align").is_some_and(|task| task.status == TaskStatus::Completed));
+    assert_eq!(app.task_ids, ["align", "call"]);
+    assert_eq!(app.tasks["align"].progress, 0.5);
+    assert_eq!(app.health.malformed.get(&Malformed::NotJson), Some(&1));
+}
+
+#[test]
+fn the_tasks_stay_after_the_feed_closes() {
+    let url = serve(vec![r#"{"type":"created","id":"align","name":"align reads","status":"running"}"#]);
+    let mut app = App::with_websocket(&url, RemoteAccess::default());
+
+    update_until(&mut app, |app| app.health.last_error.is_some());
+    let (error, _) = app.health.last_error.as_ref().unwrap();
+    assert!(error.contains("the feed closed after 1 updates"), "{}", error);
+    assert_eq!(app.task_ids, ["align"]);
+}
+
+#[test]
+fn the_feed_is_subscribed_to_again_after_it_drops() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("ws://{}/v1/events", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let scripts = [
+            r#"{"type":"created","id":"align","name":"align reads","status":"running"}"#,
+            r#"{"type":"status_changed","id":"align","status":"completed"}"#,
+        ];
+        for message in scripts {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            socket.send(Message::text(message)).unwrap();
+            let _ = socket.close(None);
+            while socket.read().is_ok() {}
+        }
+    });
+    let mut app = App::with_websocket(&url, RemoteAccess::default());
+
+    update_until(&mut app, |app| app.health.state() == HealthState::Reconnecting);
+    assert_eq!(app.health.reconnecting.map(|(attempt, _)| attempt), Some(1));
+    assert_eq!(app.task_ids, ["align"]);
+
+    // Only a second subscription brings the status change
+    update_until(&mut app, |app| app.tasks["align"].status == TaskStatus::Completed);
+}
+
+#[test]
+fn a_refused_token_stops_the_feed_with_a_message() {
+    let (url, handshakes) = refuse();
+    let mut app = App::with_websocket(&url, RemoteAccess { token: Some("expired".to_string()), ..RemoteAccess::default() });
+
+    update_until(&mut app, |app| app.health.last_error.is_some());
+    let (error, _) = app.health.last_error.as_ref().unwrap();
+    assert!(error.contains("the server refused the websocket token (HTTP 401)"), "{}", error);
+    assert!(error.contains("[auth.websocket]"), "{}", error);
+    assert_eq!(app.connections.get("websocket").unwrap().state, ConnectionState::Offline);
+    assert_eq!(handshakes.recv().unwrap().as_deref(), Some("Bearer expired"));
+
+    // The same token is not sent again
+    thread::sleep(Duration::from_secs(1));
+    assert!(handshakes.try_recv().is_err());
+}
+
+#[test]
+fn secure_feeds_trust_and_present_the_configured_certificates() {
+    let url = serve_tls(r#"{"type":"created","id":"align","name":"align reads","status":"running"}"#);
+    let mut identity = fixture("client.pem");
+    identity.extend(fixture("client.key"));
+    let tls = Tls {
+        ca_certificates: Some(fixture("ca.pem")),
+        client_identity: Some(identity),
+    };
+    let mut app = App::with_websocket(&url, RemoteAccess { tls: tls.clone(), ..RemoteAccess::default() });
+    update_until(&mut app, |app| app.task_ids == ["align"]);
+
+    // The test authority is not a public one
+    let mut app = App::with_websocket(&url, RemoteAccess::default());
+    update_until(&mut app, |app| app.health.last_error.is_some());
+    let (error, _) = app.health.last_error.as_ref().unwrap();
+    assert!(error.contains("the handshake failed"), "{}", error);
+    assert!(app.task_ids.is_empty());
+
+    // Nor is the feed followed without a client certificate
+    let tls = Tls { client_identity: None, ..tls };
+    let mut app = App::with_websocket(&url, RemoteAccess { tls, ..RemoteAccess::default() });
+    update_until(&mut app, |app| app.health.last_error.is_some());
+    assert!(app.task_ids.is_empty());
+}